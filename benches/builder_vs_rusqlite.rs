@@ -7,11 +7,11 @@
 //! 2. Using our builder API directly (programmatic construction)
 //! 3. Using our SQL parser to build from SQL statements
 
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use rusqlite::Connection;
 use sqlite_diff_rs::{
     ChangeDelete, ChangeSet, ChangeUpdate, DiffOps, Insert, PatchDelete, PatchSet, PatchUpdate,
-    SimpleTable, TableSchema, Value,
+    SimpleTable, SingleTableChangeSet, TableSchema, Value,
 };
 use std::hint::black_box;
 use std::string::String;
@@ -876,5 +876,139 @@ fn benchmark_patchset(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, benchmark_changeset, benchmark_patchset);
+/// Number of rows inserted by the pre-sizing benchmark.
+const PRESIZE_ROWS: i64 = 5000;
+
+/// Inserts `PRESIZE_ROWS` rows into `builder`, then encodes it.
+fn bulk_insert_patchset(mut builder: PatchSet<SimpleTable, String, Vec<u8>>) -> Vec<u8> {
+    let table = SimpleTable::new("events", &["id", "kind", "payload"], &[0]);
+    for id in 0..PRESIZE_ROWS {
+        builder = builder.insert(
+            Insert::from(table.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, "click")
+                .unwrap()
+                .set(2, id * 7)
+                .unwrap(),
+        );
+    }
+    builder.build()
+}
+
+fn benchmark_presized(c: &mut Criterion) {
+    let mut group = c.benchmark_group("presized_build");
+    let table = SimpleTable::new("events", &["id", "kind", "payload"], &[0]);
+
+    group.bench_function("default", |b| {
+        b.iter(|| black_box(bulk_insert_patchset(PatchSet::new())));
+    });
+
+    group.bench_function("with_capacity", |b| {
+        b.iter(|| {
+            let mut builder = PatchSet::with_capacity(1);
+            builder.reserve(&table, usize::try_from(PRESIZE_ROWS).unwrap());
+            black_box(bulk_insert_patchset(builder))
+        });
+    });
+
+    group.finish();
+}
+
+/// One staged operation of the single-table workload.
+enum EventOp {
+    Insert(Insert<SimpleTable, String, Vec<u8>>),
+    Update(ChangeUpdate<SimpleTable, String, Vec<u8>>),
+    Delete(ChangeDelete<SimpleTable, String, Vec<u8>>),
+}
+
+/// `PRESIZE_ROWS` inserts into one integer-keyed table, then an update of
+/// every third row and a delete of every fifth.
+fn single_table_workload(table: &SimpleTable) -> Vec<EventOp> {
+    let mut ops: Vec<EventOp> = (0..PRESIZE_ROWS)
+        .map(|id| {
+            EventOp::Insert(
+                Insert::from(table.clone())
+                    .set(0, id)
+                    .unwrap()
+                    .set(1, "click")
+                    .unwrap()
+                    .set(2, id * 7)
+                    .unwrap(),
+            )
+        })
+        .collect();
+    ops.extend((0..PRESIZE_ROWS).step_by(3).map(|id| {
+        EventOp::Update(
+            ChangeUpdate::from(table.clone())
+                .set(0, id, id)
+                .unwrap()
+                .set(1, "click", "view")
+                .unwrap(),
+        )
+    }));
+    ops.extend((0..PRESIZE_ROWS).step_by(5).map(|id| {
+        EventOp::Delete(
+            ChangeDelete::from(table.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, "click")
+                .unwrap()
+                .set(2, id * 7)
+                .unwrap(),
+        )
+    }));
+    ops
+}
+
+fn benchmark_single_table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_table_changeset");
+    let table = SimpleTable::new("events", &["id", "kind", "payload"], &[0]);
+
+    group.bench_function("generic_builder", |b| {
+        b.iter_batched(
+            || single_table_workload(&table),
+            |ops| {
+                let builder = ops.into_iter().fold(
+                    ChangeSet::<SimpleTable, String, Vec<u8>>::new(),
+                    |builder, op| match op {
+                        EventOp::Insert(insert) => builder.insert(insert),
+                        EventOp::Update(update) => builder.update(update),
+                        EventOp::Delete(delete) => builder.delete(delete),
+                    },
+                );
+                black_box(builder.build())
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("single_table", |b| {
+        b.iter_batched(
+            || single_table_workload(&table),
+            |ops| {
+                let builder = ops.into_iter().fold(
+                    SingleTableChangeSet::new(table.clone()),
+                    |builder, op| match op {
+                        EventOp::Insert(insert) => builder.insert(insert),
+                        EventOp::Update(update) => builder.update(update),
+                        EventOp::Delete(delete) => builder.delete(delete),
+                    },
+                );
+                black_box(builder.build())
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_changeset,
+    benchmark_patchset,
+    benchmark_presized,
+    benchmark_single_table
+);
 criterion_main!(benches);
@@ -5,6 +5,10 @@
 //! Outputs structured JSON to stdout. Also saves to plots/results.json and generates SVG plots.
 //!
 //! Run: `cargo run -p payload-size-bench`
+//!
+//! Given the path of a binary changeset or patchset, the formats re-encode
+//! its `messages` operations instead of synthetic ones, and only the JSON is
+//! printed: `cargo run -p payload-size-bench -- capture.bin`
 
 mod binary_serde;
 mod common;
@@ -15,6 +19,7 @@ mod format_msgpack;
 mod format_patchset;
 mod format_protobuf;
 mod format_sql;
+mod parsed;
 mod plots;
 mod results;
 
@@ -23,7 +28,11 @@ use results::BenchmarkResults;
 use std::path::Path;
 use std::time::Instant;
 
-use common::{Compressor, Format, all_compressors, generate_messages, raw_content_message};
+use common::{
+    Compressor, Format, TestMessage, all_compressors, generate_messages, raw_content_message,
+};
+use parsed::ParsedWorkload;
+use sqlite_diff_rs::ParsedDiffSet;
 
 // ---------------------------------------------------------------------------
 // All formats in display order
@@ -55,17 +64,44 @@ fn collect_mixed_workload(
     formats: &[Box<dyn Format>],
     compressors: &[Box<dyn Compressor>],
 ) -> (usize, Vec<usize>, Vec<Vec<(usize, u64, u64)>>) {
-    let iterations = 5;
-
     let msgs = generate_messages(n);
     let n_insert = (n * 60) / 100;
     let n_update = (n * 25) / 100;
-    let n_delete = n - n_insert - n_update;
 
     let inserts = &msgs[..n_insert];
     let updates = &msgs[n_insert..n_insert + n_update];
     let deletes = &msgs[n_insert + n_update..];
 
+    measure_batch(inserts, updates, deletes, formats, compressors)
+}
+
+/// Like [`collect_mixed_workload`], on the operations of a parsed changeset
+/// or patchset.
+fn collect_parsed_workload(
+    workload: &ParsedWorkload,
+    formats: &[Box<dyn Format>],
+    compressors: &[Box<dyn Compressor>],
+) -> (usize, Vec<usize>, Vec<Vec<(usize, u64, u64)>>) {
+    measure_batch(
+        &workload.inserts,
+        &workload.updates,
+        &workload.deletes,
+        formats,
+        compressors,
+    )
+}
+
+/// Serializes one batch with every format and compresses it with every
+/// compressor.
+fn measure_batch(
+    inserts: &[TestMessage],
+    updates: &[TestMessage],
+    deletes: &[TestMessage],
+    formats: &[Box<dyn Format>],
+    compressors: &[Box<dyn Compressor>],
+) -> (usize, Vec<usize>, Vec<Vec<(usize, u64, u64)>>) {
+    let iterations = 5;
+
     let mut uncompressed = Vec::with_capacity(formats.len());
     let mut compression_data = Vec::with_capacity(formats.len());
 
@@ -101,7 +137,7 @@ fn collect_mixed_workload(
     // Raw content: 16-byte binary UUIDs + text content
     let raw_inserts: usize = inserts.iter().map(raw_content_message).sum();
     let raw_updates: usize = updates.iter().map(|m| 16 + m.update_body.len()).sum();
-    let raw_deletes: usize = n_delete * 16;
+    let raw_deletes: usize = deletes.len() * 16;
     let raw_bytes = raw_inserts + raw_updates + raw_deletes;
 
     (raw_bytes, uncompressed, compression_data)
@@ -125,6 +161,21 @@ fn main() {
         TIMING_ITERATIONS,
     );
 
+    // =====================================================================
+    // A captured changeset or patchset, if one is given
+    // =====================================================================
+
+    if let Some(path) = std::env::args().nth(1) {
+        let bytes = std::fs::read(&path).expect("Failed to read the diffset");
+        let parsed = ParsedDiffSet::parse(&bytes).expect("Not a changeset or patchset");
+        let workload = ParsedWorkload::from_parsed(&parsed);
+        let (raw_bytes, uncompressed, compression_data) =
+            collect_parsed_workload(&workload, &formats, &compressors);
+        results.add_mixed_workload(workload.len(), raw_bytes, uncompressed, compression_data);
+        println!("{}", results.to_json());
+        return;
+    }
+
     // =====================================================================
     // Mixed workload at scale
     // =====================================================================
@@ -358,6 +409,31 @@ mod tests {
         }
     }
 
+    /// Test that a parsed changeset is measured like the batch it encodes.
+    #[test]
+    fn test_parsed_workload_is_measured() {
+        let formats = all_formats();
+        let compressors = all_compressors();
+
+        let msgs = generate_messages(10);
+        let bytes = format_changeset::Changeset.batch_mixed(&msgs[..6], &msgs[6..8], &msgs[8..]);
+        let workload = ParsedWorkload::from_parsed(&ParsedDiffSet::parse(&bytes).unwrap());
+
+        let (raw_bytes, uncompressed, compression_data) =
+            collect_parsed_workload(&workload, &formats, &compressors);
+        let (expected_raw, expected_uncompressed, _) =
+            measure_batch(&msgs[..6], &msgs[6..8], &msgs[8..], &formats, &compressors);
+        assert_eq!(raw_bytes, expected_raw);
+        assert_eq!(uncompressed, expected_uncompressed);
+        assert_eq!(compression_data.len(), formats.len());
+
+        let changeset_idx = formats
+            .iter()
+            .position(|f| f.name() == "Changeset")
+            .unwrap();
+        assert_eq!(uncompressed[changeset_idx], bytes.len());
+    }
+
     /// Test BenchmarkResults serialization.
     #[test]
     fn test_results_serialization() {
@@ -0,0 +1,166 @@
+//! Feeding parsed changesets and patchsets to every [`Format`](crate::common::Format).
+//!
+//! The formats serialize batches of synthetic [`TestMessage`]s. A
+//! [`ParsedWorkload`] decodes the `messages` rows of a real changeset or
+//! patchset back into such batches, so a payload captured from production
+//! can be re-encoded by every format and compared with its original size.
+
+use sqlite_diff_rs::{
+    DynTable, OperationVisitor, ParsedDiffSet, TableSchema, UpdateColumns, Value,
+};
+use uuid::Uuid;
+
+use crate::common::TestMessage;
+
+type Schema = TableSchema<String>;
+
+/// The `messages` operations of a parsed diffset, as
+/// [`Format`](crate::common::Format) input.
+///
+/// Operations on other tables are ignored. Columns the diffset does not
+/// carry, such as the old body of a patchset UPDATE or the non-key columns
+/// of a patchset DELETE, are left empty, as the formats would not send them
+/// either.
+#[derive(Default)]
+pub struct ParsedWorkload {
+    /// Inserted rows.
+    pub inserts: Vec<TestMessage>,
+    /// Updated rows, with the new body in `update_body`.
+    pub updates: Vec<TestMessage>,
+    /// Deleted rows.
+    pub deletes: Vec<TestMessage>,
+}
+
+impl ParsedWorkload {
+    /// Collects the `messages` operations of `parsed`, in stored order.
+    pub fn from_parsed(parsed: &ParsedDiffSet) -> Self {
+        let mut workload = Self::default();
+        parsed.visit_all(&mut workload);
+        workload
+    }
+
+    /// Returns the number of operations in the workload.
+    pub fn len(&self) -> usize {
+        self.inserts.len() + self.updates.len() + self.deletes.len()
+    }
+}
+
+impl OperationVisitor<Schema, String, Vec<u8>> for ParsedWorkload {
+    fn visit_insert(&mut self, table: &Schema, values: &[Value<String, Vec<u8>>], _: bool) {
+        if is_messages(table) {
+            self.inserts.push(message(|col| values.get(col)));
+        }
+    }
+
+    fn visit_delete(&mut self, table: &Schema, values: &[Value<String, Vec<u8>>], _: bool) {
+        if is_messages(table) {
+            self.deletes.push(message(|col| values.get(col)));
+        }
+    }
+
+    fn visit_update(
+        &mut self,
+        table: &Schema,
+        columns: UpdateColumns<'_, String, Vec<u8>>,
+        _: bool,
+    ) {
+        if is_messages(table) {
+            // Patchsets keep the key in the new slot and no old values.
+            let mut update = message(|col| columns.old_value(col).or(columns.new_value(col)));
+            update.update_body = text(columns.new_value(3));
+            self.updates.push(update);
+        }
+    }
+}
+
+fn is_messages(table: &Schema) -> bool {
+    table.name() == "messages" && table.number_of_columns() == 5
+}
+
+/// Builds a message from the values of the `messages` columns.
+fn message<'a>(value: impl Fn(usize) -> Option<&'a Value<String, Vec<u8>>>) -> TestMessage {
+    TestMessage {
+        id: uuid(value(0)),
+        sender: uuid(value(1)),
+        receiver: uuid(value(2)),
+        body: text(value(3)),
+        update_body: String::new(),
+        created_at: text(value(4)),
+    }
+}
+
+fn uuid(value: Option<&Value<String, Vec<u8>>>) -> Uuid {
+    match value {
+        Some(Value::Blob(bytes)) => Uuid::from_slice(bytes).unwrap_or_default(),
+        _ => Uuid::nil(),
+    }
+}
+
+fn text(value: Option<&Value<String, Vec<u8>>>) -> String {
+    match value {
+        Some(Value::Text(text)) => text.clone(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{Format, generate_messages};
+    use crate::{format_changeset, format_json, format_patchset};
+
+    fn batch(workload: &ParsedWorkload, format: &dyn Format) -> Vec<u8> {
+        format.batch_mixed(&workload.inserts, &workload.updates, &workload.deletes)
+    }
+
+    #[test]
+    fn test_parsed_changeset_feeds_every_format() {
+        let messages = generate_messages(10);
+        let (inserts, rest) = messages.split_at(6);
+        let (updates, deletes) = rest.split_at(2);
+        let bytes = format_changeset::Changeset.batch_mixed(inserts, updates, deletes);
+
+        let workload = ParsedWorkload::from_parsed(&ParsedDiffSet::parse(&bytes).unwrap());
+        assert_eq!(
+            (
+                workload.inserts.len(),
+                workload.updates.len(),
+                workload.deletes.len()
+            ),
+            (6, 2, 2)
+        );
+        // The rows come back in the session's order, which changes no size:
+        // the changeset carries every column, so each format sees the same
+        // data it was generated from.
+        assert_eq!(
+            batch(&workload, &format_changeset::Changeset).len(),
+            bytes.len()
+        );
+        let json = batch(&workload, &format_json::Json);
+        assert_eq!(
+            json.len(),
+            format_json::Json
+                .batch_mixed(inserts, updates, deletes)
+                .len()
+        );
+        assert!(json.len() > bytes.len());
+    }
+
+    #[test]
+    fn test_parsed_patchset_keeps_its_size() {
+        let messages = generate_messages(10);
+        let (inserts, rest) = messages.split_at(6);
+        let (updates, deletes) = rest.split_at(2);
+        let bytes = format_patchset::Patchset.batch_mixed(inserts, updates, deletes);
+
+        let workload = ParsedWorkload::from_parsed(&ParsedDiffSet::parse(&bytes).unwrap());
+        assert_eq!(workload.len(), 10);
+        assert_eq!(
+            batch(&workload, &format_patchset::Patchset).len(),
+            bytes.len()
+        );
+        let delete = &workload.deletes[0];
+        assert!(deletes.iter().any(|m| m.id == delete.id));
+        assert!(delete.body.is_empty());
+    }
+}
@@ -13,8 +13,14 @@ pub mod sql;
 mod sql_output;
 mod update_operation;
 mod view;
+mod visitor;
 
-pub use change::{ChangeSet, DiffOps, DiffSet, DiffSetBuilder, PatchSet};
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use change::session_pk_order;
+pub use change::{
+    ChangeSet, DiffOps, DiffSet, DiffSetBuilder, GroupKey, GroupedDiffSet, PatchSet,
+    ShadowPatchSet, SingleTableChangeSet, TableScope,
+};
 pub use delete_operation::{ChangeDelete, PatchDelete};
 #[cfg(feature = "diesel-async")]
 pub use diesel_async_query::ApplyOpsAsync;
@@ -30,3 +36,4 @@ pub use operation::{Indirect, Reverse};
 pub use sql_output::ColumnNames;
 pub use update_operation::Update;
 pub use view::{ChangesetOp, ChangesetUpdatePair, PatchsetOp, PatchsetUpdateEntry};
+pub use visitor::{OperationVisitor, UpdateColumns};
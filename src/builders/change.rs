@@ -21,7 +21,7 @@
 //! | INSERT | UPDATE | INSERT with updated values |
 //! | INSERT | DELETE | Remove both (no-op) |
 //! | UPDATE | INSERT | Ignore new |
-//! | UPDATE | UPDATE | Single UPDATE original to final |
+//! | UPDATE | UPDATE | Single UPDATE original to final, no-op if same (changeset) |
 //! | UPDATE | DELETE | DELETE of original |
 //! | DELETE | INSERT | UPDATE if different, no-op if same |
 //! | DELETE | UPDATE | Ignore new |
@@ -42,19 +42,32 @@
 
 use indexmap::IndexMap as IndexMapRaw;
 
+mod grouped;
+mod shadow;
+mod single_table;
+mod table_scope;
+
+pub use grouped::{GroupKey, GroupedDiffSet};
+pub use shadow::ShadowPatchSet;
+pub use single_table::SingleTableChangeSet;
+pub use table_scope::TableScope;
+
+use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
-use core::hash::Hash;
+use core::hash::{Hash, Hasher};
 use core::ops::{BitOr, BitOrAssign};
 
 use crate::{
-    SchemaWithPK,
+    DynTable, NamedColumns, SchemaWithPK, TableSchema,
     builders::{
         ChangeDelete, ChangesetFormat, ChangesetOp, Insert, Operation, PatchDelete, PatchsetFormat,
         PatchsetOp, Update, format::Format,
     },
-    encoding::{MaybeValue, Value, encode_defined_value, encode_value, markers, op_codes},
+    encoding::{
+        MaybeValue, Value, compare_values, encode_defined_value, encode_value, markers, op_codes,
+    },
 };
 
 /// `IndexMap` alias using hashbrown's default hasher for `no_std` compatibility.
@@ -66,12 +79,24 @@ type RowMap<F, S, B> = IndexMap<Vec<Value<S, B>>, Operation<F, S, B>>;
 /// Type alias for the table map.
 type TableMap<F, T, S, B> = IndexMap<T, RowMap<F, S, B>>;
 
+/// Type alias for the primary keys a table's session has recorded, in
+/// recording order.
+type EntryLog<S, B> = IndexMap<Vec<Value<S, B>>, ()>;
+
 /// Type alias for a vector of rows in a table.
 type RowVec<F, S, B> = Vec<(Vec<Value<S, B>>, Operation<F, S, B>)>;
 
 /// Type alias for a vector of tables with their rows.
 type TableVec<F, T, S, B> = Vec<(T, RowVec<F, S, B>)>;
 
+/// Type alias for the primary keys touched in each table.
+type AffectedKeys<'a, T, S, B> = Vec<(&'a T, Vec<Vec<Value<S, B>>>)>;
+
+/// Type alias for the end state of each touched row, keyed by table name and
+/// primary key.
+type FinalRows<S, B> =
+    hashbrown::HashMap<(String, Vec<Value<S, B>>), Option<Vec<Option<Value<S, B>>>>>;
+
 // ============================================================================
 // SQLite session extension hash simulation
 // ============================================================================
@@ -104,11 +129,30 @@ fn session_hash_append_blob(mut h: u32, data: &[u8]) -> u32 {
     h
 }
 
+/// Bound of the integral values a REAL column stores as integers, `2^47`.
+const REAL_AS_INT: f64 = 140_737_488_355_328.0;
+
 /// Hash a primary key using `SQLite`'s `sessionPreupdateHash` algorithm.
 ///
 /// For each PK value: `h = HASH_APPEND(h, type_code)`, then hash the value.
 /// Type codes match `SQLite`: INTEGER=1, FLOAT=2, TEXT=3, BLOB=4.
-fn session_hash_pk<S: AsRef<str>, B: AsRef<[u8]>>(pk: &[Value<S, B>]) -> u32 {
+///
+/// NULL values add nothing to the hash, so keys such as `(NULL, 1)` and
+/// `(1, NULL)` collide. The hash only decides output order: rows are keyed on
+/// their full primary key, so colliding keys stay separate rows and never
+/// consolidate. A real session never records a row with a NULL key; only
+/// rows staged by hand can reach this path.
+///
+/// A REAL column stores an integral value within 48 bits as an integer, and
+/// the session hashes an INSERT from the stored record, so when `inserted`
+/// is set such a [`Value::Real`] key hashes as an INTEGER. Rows recorded from
+/// their old values (UPDATE and DELETE) see the REAL again. Keys are assumed
+/// to come from a REAL column: in an untyped column `2.0` stays REAL and
+/// always hashes as one.
+fn session_hash_pk<'a, S: AsRef<str> + 'a, B: AsRef<[u8]> + 'a>(
+    pk: impl IntoIterator<Item = &'a Value<S, B>>,
+    inserted: bool,
+) -> u32 {
     let mut h: u32 = 0;
     for value in pk {
         match value {
@@ -116,6 +160,19 @@ fn session_hash_pk<S: AsRef<str>, B: AsRef<[u8]>>(pk: &[Value<S, B>]) -> u32 {
                 h = hash_append(h, 1); // SQLITE_INTEGER
                 h = session_hash_append_i64(h, *i);
             }
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_precision_loss,
+                clippy::float_cmp
+            )]
+            Value::Real(f)
+                if inserted
+                    && (-REAL_AS_INT..REAL_AS_INT).contains(f)
+                    && *f as i64 as f64 == *f =>
+            {
+                h = hash_append(h, 1); // SQLITE_INTEGER, as stored
+                h = session_hash_append_i64(h, *f as i64);
+            }
             Value::Real(f) => {
                 h = hash_append(h, 2); // SQLITE_FLOAT
                 // SQLite does memcpy(&iVal, &rVal, 8) then hashes as i64
@@ -131,14 +188,97 @@ fn session_hash_pk<S: AsRef<str>, B: AsRef<[u8]>>(pk: &[Value<S, B>]) -> u32 {
                 h = session_hash_append_blob(h, b.as_ref());
             }
             Value::Null => {
-                // NULL PKs: SQLite skips hashing for these.
-                // In practice, PKs should never be NULL.
+                // SQLite skips NULL key values; see the collision note above.
             }
         }
     }
     h
 }
 
+/// Numeric storage classes found among the primary-key values a table has
+/// staged.
+///
+/// An UPDATE or DELETE whose key is not staged only looks for a numerically
+/// equal key when the table may stage one of the other class. Classes are
+/// never cleared, so a row that cancelled out may leave one set.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct KeyClasses {
+    integer: bool,
+    real: bool,
+}
+
+impl KeyClasses {
+    /// Returns the classes of the values of every key in `keys`.
+    fn of<'key, S: 'key, B: 'key>(keys: impl IntoIterator<Item = &'key Vec<Value<S, B>>>) -> Self {
+        let mut classes = Self::default();
+        for pk in keys {
+            classes.record(pk);
+        }
+        classes
+    }
+
+    /// Records the classes of the values of `pk`.
+    fn record<S, B>(&mut self, pk: &[Value<S, B>]) {
+        for value in pk {
+            match value {
+                Value::Integer(_) => self.integer = true,
+                Value::Real(_) => self.real = true,
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns whether a key numerically equal to `pk`, with a value of the
+    /// other class, may be staged.
+    fn may_stage_twin_of<S, B>(self, pk: &[Value<S, B>]) -> bool {
+        pk.iter().any(|value| match value {
+            Value::Integer(_) => self.real,
+            Value::Real(_) => self.integer,
+            _ => false,
+        })
+    }
+}
+
+/// Returns the keys equal to `pk` under `SQLite`'s numeric comparison, where
+/// `1` and `1.0` are the same number, each with at least one value in the
+/// other storage class: the keys of the rows `pk` also addresses.
+fn numeric_twins<S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>(
+    pk: &[Value<S, B>],
+) -> impl Iterator<Item = Vec<Value<S, B>>> + '_ {
+    let twins: Vec<(usize, Value<S, B>)> = pk
+        .iter()
+        .enumerate()
+        .filter_map(|(col, value)| Some((col, numeric_twin(value)?)))
+        .collect();
+    // Every combination of switched columns but the empty one. Keys with
+    // more than seven numeric columns are left unmatched.
+    let combinations = u32::try_from(twins.len())
+        .ok()
+        .filter(|&count| count < 8)
+        .map_or(0, |count| (1u32 << count) - 1);
+    (1..=combinations).map(move |mask| {
+        let mut key = pk.to_vec();
+        for (bit, (col, twin)) in twins.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                key[*col] = twin.clone();
+            }
+        }
+        key
+    })
+}
+
+/// Returns the value of the other numeric storage class equal to `value`,
+/// if there is one. Casts that saturate or round fail the final comparison.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn numeric_twin<S: AsRef<str>, B: AsRef<[u8]>>(value: &Value<S, B>) -> Option<Value<S, B>> {
+    let twin = match *value {
+        Value::Integer(i) => Value::Real(i as f64),
+        Value::Real(r) if r.fract() == 0.0 => Value::Integer(r as i64),
+        _ => return None,
+    };
+    compare_values(value, &twin).is_eq().then_some(twin)
+}
+
 /// Simulate `SQLite`'s session extension hash table to determine row output order.
 ///
 /// `SQLite`'s session extension tracks changes in a hash table where:
@@ -148,15 +288,17 @@ fn session_hash_pk<S: AsRef<str>, B: AsRef<[u8]>>(pk: &[Value<S, B>]) -> u32 {
 ///
 /// This function returns indices into `rows` in the order that `SQLite`'s
 /// changeset/patchset output would contain them.
-fn session_row_order<S: AsRef<str>, B: AsRef<[u8]>, V>(
+fn session_row_order<T: SchemaWithPK, S: AsRef<str>, B: AsRef<[u8]>, V>(
+    table: &T,
     rows: &IndexMap<Vec<Value<S, B>>, V>,
+    inserted: impl Fn(&Vec<Value<S, B>>, &V) -> bool,
 ) -> Vec<usize> {
     let n = rows.len();
     if n == 0 {
         return Vec::new();
     }
 
-    let pks: Vec<&Vec<Value<S, B>>> = rows.keys().collect();
+    let hashes = session_hashes(table, rows.iter().map(|(pk, row)| (pk, inserted(pk, row))));
 
     // Simulate the hash table. We store each bucket as a Vec of entry indices
     // in the REVERSE of SQLite's linked-list order (we push; SQLite prepends).
@@ -180,7 +322,7 @@ fn session_row_order<S: AsRef<str>, B: AsRef<[u8]>, V>(
             // into our reversed representation).
             for old_bucket in &buckets {
                 for &entry_idx in old_bucket.iter().rev() {
-                    let h = session_hash_pk(pks[entry_idx]) as usize % new_size;
+                    let h = hashes[entry_idx] as usize % new_size;
                     new_buckets[h].push(entry_idx);
                 }
             }
@@ -190,7 +332,7 @@ fn session_row_order<S: AsRef<str>, B: AsRef<[u8]>, V>(
         }
 
         // Insert entry (push = prepend in our reversed representation)
-        let h = session_hash_pk(pks[idx]) as usize % n_change;
+        let h = hashes[idx] as usize % n_change;
         buckets[h].push(idx);
     }
 
@@ -206,6 +348,92 @@ fn session_row_order<S: AsRef<str>, B: AsRef<[u8]>, V>(
     order
 }
 
+/// Hashes each primary key in `pks` as `SQLite`'s session does, each paired
+/// with whether an INSERT first recorded its row.
+fn session_hashes<'a, T: SchemaWithPK, S: AsRef<str> + 'a, B: AsRef<[u8]> + 'a>(
+    table: &T,
+    pks: impl Iterator<Item = (&'a Vec<Value<S, B>>, bool)>,
+) -> Vec<u32> {
+    // Row keys hold the primary key in key order, but `SQLite` hashes the
+    // primary-key columns in column order.
+    let hash_positions: Vec<usize> = (0..table.number_of_columns())
+        .filter_map(|col| table.primary_key_index(col))
+        .collect();
+    pks.map(|(pk, inserted)| session_hash_pk(hash_positions.iter().map(|&pos| &pk[pos]), inserted))
+        .collect()
+}
+
+/// Inverts [`session_row_order`]: returns a recording order, as indices into
+/// `pks`, that `SQLite`'s session would emit in the order `pks` yields them.
+/// Each key is paired with whether an INSERT records its row.
+///
+/// The emitted order walks the final hash table bucket by bucket, so only an
+/// order whose rows are grouped by ascending final bucket can be reproduced;
+/// for any other order, rows come out grouped by bucket, keeping their
+/// relative order within each bucket.
+///
+/// Rows recorded between two table growths are prepended to their bucket,
+/// and each growth reverses the rows a final bucket already holds. Walking
+/// the growth phases from last to first, each phase therefore takes a prefix
+/// of every bucket's remaining list, recorded in reverse, after which the
+/// rest of each list flips.
+fn session_recording_order<'a, T: SchemaWithPK, S: AsRef<str> + 'a, B: AsRef<[u8]> + 'a>(
+    table: &T,
+    pks: impl ExactSizeIterator<Item = (&'a Vec<Value<S, B>>, bool)>,
+) -> Vec<usize> {
+    let n = pks.len();
+
+    // Replay the growth schedule of `session_row_order`.
+    let mut phase_starts: Vec<usize> = Vec::new();
+    let mut n_change: usize = 0;
+    for idx in 0..n {
+        if n_change == 0 || idx >= n_change / 2 {
+            phase_starts.push(idx);
+            n_change = if n_change == 0 { 256 } else { n_change * 2 };
+        }
+    }
+
+    let hashes = session_hashes(table, pks);
+    let mut by_bucket: Vec<usize> = (0..n).collect();
+    by_bucket.sort_by_key(|&idx| hashes[idx] as usize % n_change);
+    let mut lists: Vec<Vec<usize>> = Vec::new();
+    for idx in by_bucket {
+        let bucket = hashes[idx] as usize % n_change;
+        match lists.last_mut() {
+            Some(list) if hashes[list[0]] as usize % n_change == bucket => list.push(idx),
+            _ => lists.push(vec![idx]),
+        }
+    }
+
+    let mut phases: Vec<Vec<usize>> = Vec::with_capacity(phase_starts.len());
+    let mut phase_end = n;
+    for &phase_start in phase_starts.iter().rev() {
+        let mut needed = phase_end - phase_start;
+        let mut phase = Vec::with_capacity(needed);
+        for list in &mut lists {
+            let take = needed.min(list.len());
+            phase.extend(list.drain(..take).rev());
+            needed -= take;
+            list.reverse();
+        }
+        phases.push(phase);
+        phase_end = phase_start;
+    }
+    phases.into_iter().rev().flatten().collect()
+}
+
+/// Returns the order, as indices into `pks`, in which `SQLite` emits rows of
+/// `table` inserted with the given distinct primary keys, in recording order.
+#[cfg(any(test, feature = "testing"))]
+pub(crate) fn session_pk_order<T: SchemaWithPK>(
+    table: &T,
+    pks: &[Vec<Value<String, Vec<u8>>>],
+) -> Vec<usize> {
+    let rows: IndexMap<_, ()> = pks.iter().map(|pk| (pk.clone(), ())).collect();
+    assert_eq!(rows.len(), pks.len(), "primary keys must be distinct");
+    session_row_order(table, &rows, |_, ()| true)
+}
+
 // ============================================================================
 // Shared encoding helpers
 // ============================================================================
@@ -216,7 +444,7 @@ fn session_row_order<S: AsRef<str>, B: AsRef<[u8]>, V>(
 /// - Table marker byte (`'T'` for changeset, `'P'` for patchset)
 /// - Column count (1 byte)
 /// - PK flags (1 byte per column: non-zero = PK ordinal, 0 = not PK)
-/// - Table name (null-terminated UTF-8)
+/// - Table name (null-terminated UTF-8, so it must not contain a NUL byte)
 fn write_table_header<T: SchemaWithPK>(out: &mut Vec<u8>, marker: u8, table: &T) {
     out.push(marker);
 
@@ -272,23 +500,22 @@ fn encode_patchset_delete_values<S: AsRef<str>, B: AsRef<[u8]>>(
     }
 }
 
-/// Encode patchset UPDATE old values: PK columns only, in column order.
+/// Encode a patchset UPDATE record.
 ///
-/// Matches SQLite's session-extension wire layout: the old side of a patchset
-/// UPDATE contains exactly `pk_count` values (no undefined padding for non-PK
-/// columns). The trailing new side (written by the caller) is symmetric and
-/// carries only the `column_count - pk_count` non-PK entries.
-fn encode_patchset_update_old_values<S: AsRef<str>, B: AsRef<[u8]>>(
+/// Matches SQLite's session-extension wire layout: a patchset UPDATE carries
+/// a single record of `column_count` entries in column order, where PK columns
+/// hold their value and every other column its new value, or `0x00`
+/// (undefined) if it did not change.
+fn encode_patchset_update_values<S: AsRef<str>, B: AsRef<[u8]>>(
     out: &mut Vec<u8>,
-    pk_flags: &[u8],
     pk_col_to_pk_pos: &[Option<usize>],
     pk: &[Value<S, B>],
+    values: &[((), MaybeValue<S, B>)],
 ) {
-    for (col_idx, &pk_flag) in pk_flags.iter().enumerate() {
-        if pk_flag > 0
-            && let Some(pk_pos) = pk_col_to_pk_pos[col_idx]
-        {
-            encode_defined_value(out, &pk[pk_pos]);
+    for (col_idx, ((), new)) in values.iter().enumerate() {
+        match pk_col_to_pk_pos[col_idx] {
+            Some(pk_pos) => encode_defined_value(out, &pk[pk_pos]),
+            None => encode_value(out, new.as_ref()),
         }
     }
 }
@@ -355,16 +582,7 @@ fn encode_patchset_op<S: AsRef<str>, B: AsRef<[u8]>>(
         Operation::Update { values, indirect } => {
             out.push(op_codes::UPDATE);
             out.push(u8::from(*indirect));
-            encode_patchset_update_old_values(out, pk_flags, pk_col_to_pk_pos, pk);
-            // New side: non-PK columns in column order. Each entry is either the
-            // new column value or `0x00` (undefined) if that non-PK column did not
-            // change. PK columns are skipped entirely, matching SQLite's session
-            // extension patchset output.
-            for (col_idx, &pk_flag) in pk_flags.iter().enumerate() {
-                if pk_flag == 0 {
-                    encode_value(out, values[col_idx].1.as_ref());
-                }
-            }
+            encode_patchset_update_values(out, pk_col_to_pk_pos, pk, values);
         }
     }
 }
@@ -386,6 +604,16 @@ fn encode_patchset_op<S: AsRef<str>, B: AsRef<[u8]>>(
 #[derive(Debug, Clone)]
 pub struct DiffSetBuilder<F: Format<S, B>, T: SchemaWithPK, S, B> {
     pub(crate) tables: TableMap<F, T, S, B>,
+    /// Every primary key recorded for the tables in which some row later
+    /// cancelled out. `SQLite` keeps a cancelled row's hash-table entry, which
+    /// still counts towards growing the table, so the output order depends on
+    /// it. Tables without cancellations record rows in `tables` order.
+    entry_logs: IndexMap<T, EntryLog<S, B>>,
+    /// Tables in the order they first received an operation, which is the
+    /// order `SQLite`'s session lists them in, each with the numeric classes
+    /// of its staged keys. Registering a table with
+    /// [`add_table`](Self::add_table) does not count.
+    touched: IndexMap<T, KeyClasses>,
 }
 
 /// Custom `PartialEq` that ignores tables with empty operations.
@@ -424,6 +652,55 @@ where
 {
 }
 
+/// `Hash` consistent with the `PartialEq` above.
+///
+/// Empty tables are skipped and tables are hashed in order, as `eq` compares
+/// them. Rows within a table compare as a map, regardless of order, so each
+/// row is hashed on its own and the row hashes are summed before being fed
+/// to `state`.
+impl<F: Format<S, B>, T: SchemaWithPK, S, B> Hash for DiffSetBuilder<F, T, S, B>
+where
+    S: Eq + Hash + AsRef<str>,
+    B: Eq + Hash + AsRef<[u8]>,
+    F::Old: Hash,
+    F::DeleteData: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for (table, rows) in self.tables.iter().filter(|(_, ops)| !ops.is_empty()) {
+            table.hash(state);
+            state.write_usize(rows.len());
+            let rows_hash = rows.iter().fold(0u64, |acc, row| {
+                let mut row_state = RowHasher::default();
+                row.hash(&mut row_state);
+                acc.wrapping_add(row_state.finish())
+            });
+            state.write_u64(rows_hash);
+        }
+    }
+}
+
+/// Deterministic FNV-1a hasher for the per-row hashes combined by
+/// `DiffSetBuilder`'s `Hash` impl, which must not depend on a random seed.
+struct RowHasher(u64);
+
+impl Default for RowHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for RowHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 /// Type alias for building changesets.
 pub type ChangeSet<T, S, B> = DiffSetBuilder<ChangesetFormat, T, S, B>;
 /// Type alias for building patchsets.
@@ -481,9 +758,123 @@ impl<T: SchemaWithPK, S: AsRef<str> + Clone + Hash + Eq, B: AsRef<[u8]> + Clone
 
 impl<F: Format<S, B>, T: SchemaWithPK, S, B> DiffSetBuilder<F, T, S, B> {
     /// Returns the table corresponding to the given name, if it exists in the builder.
-    pub(super) fn table<'builder>(&'builder self, name: &str) -> Option<&'builder T> {
+    pub(crate) fn table_named<'builder>(&'builder self, name: &str) -> Option<&'builder T> {
         self.tables.keys().find(|t| t.name() == name)
     }
+
+    /// Returns the table whose name matches `name` ignoring ASCII case, as
+    /// `SQLite` resolves table names, if it exists in the builder.
+    pub(crate) fn table_named_ignore_case<'builder>(
+        &'builder self,
+        name: &str,
+    ) -> Option<&'builder T> {
+        self.tables
+            .keys()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the rowid `SQLite` gives a row inserted into `table` without
+    /// one, when the table's key aliases the rowid: one past the largest
+    /// integer key staged for an INSERT, or 1 if there is none.
+    ///
+    /// Only staged rows are known, so the caller accounts for the rowids an
+    /// `AUTOINCREMENT` table used for rows that are gone.
+    pub(crate) fn next_rowid(&self, table: &T) -> i64 {
+        self.tables
+            .get(table)
+            .into_iter()
+            .flatten()
+            .filter_map(|(pk, op)| match (&pk[..], op) {
+                ([Value::Integer(rowid)], Operation::Insert { .. }) => Some(*rowid),
+                _ => None,
+            })
+            .max()
+            .map_or(1, |rowid| rowid.saturating_add(1))
+    }
+
+    /// Checks the staged operations against `SQLite`'s default limits, so a
+    /// diffset that would fail to apply is caught before it is built.
+    ///
+    /// Tables holding operations must have at most `SQLITE_MAX_COLUMN`
+    /// (2000) columns, and every text or blob value the diffset would write
+    /// (primary keys, INSERT values and UPDATE new values) at most
+    /// `SQLITE_MAX_LENGTH` (1,000,000,000) bytes. Old values are not
+    /// checked: they must match a row the database already stores.
+    ///
+    /// # Errors
+    ///
+    /// * `TooManyColumns` - If a table holding operations has more columns
+    ///   than `SQLite` allows.
+    /// * `ValueTooLarge` - If a value to write is longer than `SQLite`
+    ///   allows, reporting the first one found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Error, Insert, SimpleTable};
+    ///
+    /// let columns: Vec<String> = (0..2001).map(|i| format!("c{i}")).collect();
+    /// let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+    /// let wide = SimpleTable::new("wide", &columns, &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+    ///     ChangeSet::new().insert(Insert::from(wide).set(0, 1i64).unwrap());
+    /// assert_eq!(
+    ///     changeset.validate_sqlite_limits(),
+    ///     Err(Error::TooManyColumns("wide".into(), 2001))
+    /// );
+    /// ```
+    pub fn validate_sqlite_limits(&self) -> Result<(), crate::errors::Error>
+    where
+        S: AsRef<str>,
+        B: AsRef<[u8]>,
+    {
+        use crate::encoding::limits::{MAX_COLUMN, MAX_LENGTH};
+        use crate::errors::Error;
+
+        for (table, rows) in self.tables.iter().filter(|(_, rows)| !rows.is_empty()) {
+            let columns = table.number_of_columns();
+            if columns > MAX_COLUMN {
+                return Err(Error::TooManyColumns(table.name().into(), columns));
+            }
+            let pk_columns = table.primary_key_columns();
+            for (pk, op) in rows {
+                let written: Vec<(usize, &Value<S, B>)> = match op {
+                    Operation::Insert { values, .. } => values.iter().enumerate().collect(),
+                    Operation::Update { values, .. } => values
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(column, (_, new))| Some((column, new.as_ref()?)))
+                        .collect(),
+                    Operation::Delete { .. } => Vec::new(),
+                };
+                let keys = pk_columns.iter().copied().zip(pk);
+                for (column, value) in keys.chain(written) {
+                    let len = match value {
+                        Value::Text(text) => text.as_ref().len(),
+                        Value::Blob(blob) => blob.as_ref().len(),
+                        _ => continue,
+                    };
+                    if len > MAX_LENGTH {
+                        return Err(Error::ValueTooLarge(table.name().into(), column, len));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every table holding operations has a name the table
+    /// header can store, NUL-terminated.
+    fn check_table_names(&self) -> Result<(), crate::errors::Error> {
+        match self
+            .tables
+            .iter()
+            .find(|(table, rows)| !rows.is_empty() && table.name().contains('\0'))
+        {
+            Some((table, _)) => Err(crate::errors::Error::InvalidTableName(table.name().into())),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]> + Hash + Eq>
@@ -495,9 +886,41 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]>
     pub fn new() -> Self {
         Self {
             tables: IndexMap::default(),
+            entry_logs: IndexMap::default(),
+            touched: IndexMap::default(),
+        }
+    }
+
+    /// Create a new builder with room for `tables` tables before any of its
+    /// per-table maps has to grow.
+    #[inline]
+    #[must_use]
+    pub fn with_capacity(tables: usize) -> Self {
+        Self {
+            tables: IndexMap::with_capacity_and_hasher(
+                tables,
+                hashbrown::DefaultHashBuilder::default(),
+            ),
+            entry_logs: IndexMap::with_capacity_and_hasher(
+                tables,
+                hashbrown::DefaultHashBuilder::default(),
+            ),
+            touched: IndexMap::with_capacity_and_hasher(
+                tables,
+                hashbrown::DefaultHashBuilder::default(),
+            ),
         }
     }
 
+    /// Reserve room for at least `rows` more rows in `table`.
+    ///
+    /// Like [`add_table`](Self::add_table), this registers `table` if it is
+    /// not present yet, so it fixes the table's position in the output.
+    pub fn reserve(&mut self, table: &T, rows: usize) -> &mut Self {
+        self.ensure_table(table).reserve(rows);
+        self
+    }
+
     /// Ensure a table exists in the builder, returning its row map.
     ///
     /// If the table doesn't exist yet, it's inserted at the end of the
@@ -517,6 +940,46 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]>
         self
     }
 
+    /// Returns a [`TableScope`] staging operations on `table` from bare rows,
+    /// so the schema is cloned once rather than into every operation.
+    ///
+    /// Like [`add_table`](Self::add_table), this registers `table` if it is
+    /// not present yet.
+    pub fn table(&mut self, table: &T) -> TableScope<'_, F, T, S, B> {
+        self.ensure_table(table);
+        TableScope::new(self, table.clone())
+    }
+
+    /// Replaces the registered `table` with `widened`, the same table with
+    /// columns appended by `ALTER TABLE ... ADD COLUMN`, keeping its place in
+    /// every table order. The rows staged so far read NULL in the new
+    /// columns, and their UPDATEs leave them undefined.
+    pub(crate) fn widen_table(&mut self, table: &T, widened: T)
+    where
+        S: Clone,
+        B: Clone,
+    {
+        let columns = widened.number_of_columns();
+        if let Some((index, _, mut rows)) = self.tables.shift_remove_full(table) {
+            for operation in rows.values_mut() {
+                match operation {
+                    Operation::Insert { values, .. } => values.resize(columns, Value::Null),
+                    Operation::Delete { data, .. } => F::widen_delete(data, columns),
+                    Operation::Update { values, .. } => {
+                        values.resize_with(columns, Default::default);
+                    }
+                }
+            }
+            self.tables.shift_insert(index, widened.clone(), rows);
+        }
+        if let Some((index, _, log)) = self.entry_logs.shift_remove_full(table) {
+            self.entry_logs.shift_insert(index, widened.clone(), log);
+        }
+        if let Some((index, _, classes)) = self.touched.shift_remove_full(table) {
+            self.touched.shift_insert(index, widened, classes);
+        }
+    }
+
     /// Returns true if the builder has no operations.
     #[inline]
     #[must_use]
@@ -531,9 +994,163 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]>
         self.tables.values().map(IndexMap::len).sum()
     }
 
+    /// Returns the tables holding at least one operation, in registration
+    /// order.
+    ///
+    /// Tables registered with [`add_table`](Self::add_table) or whose
+    /// operations all cancelled out are skipped, as [`build`](Self::build)
+    /// skips them.
+    pub fn non_empty_tables(&self) -> impl Iterator<Item = &T> {
+        self.tables
+            .iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(table, _)| table)
+    }
+
+    /// Returns true if `table` holds no operations, including when the
+    /// builder has never seen it.
+    #[must_use]
+    pub fn is_table_empty(&self, table: &T) -> bool {
+        self.tables.get(table).is_none_or(IndexMap::is_empty)
+    }
+
+    /// Splits the builder into one builder per table holding operations,
+    /// in the order [`build`](Self::build) emits them.
+    ///
+    /// Each builder keeps its table's rows, in the same order, so it builds
+    /// the same table section: concatenating the builds of the parts yields
+    /// the build of the whole. Tables registered without operations are
+    /// dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let posts = SimpleTable::new("posts", &["id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap())
+    ///     .insert(Insert::from(posts).set(0, 1i64).unwrap());
+    ///
+    /// let bytes = changeset.build();
+    /// let parts = changeset.split_by_table();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(parts.iter().flat_map(ChangeSet::build).collect::<Vec<u8>>(), bytes);
+    /// ```
+    #[must_use]
+    pub fn split_by_table(self) -> Vec<Self> {
+        let Self {
+            tables,
+            mut entry_logs,
+            touched,
+            ..
+        } = self;
+        tables
+            .into_iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(table, rows)| {
+                let mut builder = Self::new();
+                if let Some(log) = entry_logs.swap_remove(&table) {
+                    builder.entry_logs.insert(table.clone(), log);
+                }
+                if let Some(&classes) = touched.get(&table) {
+                    builder.touched.insert(table.clone(), classes);
+                }
+                builder.tables.insert(table, rows);
+                builder
+            })
+            .collect()
+    }
+
+    /// Returns the state each touched row ends in once the staged operations
+    /// are applied, keyed by table name and primary key.
+    ///
+    /// `Some(row)` means the row ends present, holding one entry per column:
+    /// an INSERT defines every column, while an UPDATE defines the primary
+    /// key and the columns it sets, leaving `None` for columns whose value
+    /// the builder never saw. `None` means the row ends deleted. An UPDATE
+    /// that moves a row to another key deletes the old key. Rows whose
+    /// operations cancelled out, such as an INSERT followed by a DELETE, are
+    /// absent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{
+    ///     DiffOps, Insert, PatchDelete, PatchSet, PatchsetFormat, SimpleTable, Update, Value,
+    /// };
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let alice = Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, "alice").unwrap();
+    /// let ally = Update::<_, PatchsetFormat, _, _>::from(users.clone())
+    ///     .set(0, 1i64)
+    ///     .unwrap()
+    ///     .set(1, "ally")
+    ///     .unwrap();
+    /// let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+    ///     .insert(alice)
+    ///     .update(ally)
+    ///     .delete(PatchDelete::new(users, vec![2i64.into()]));
+    ///
+    /// let rows = patchset.final_rows();
+    /// assert_eq!(
+    ///     rows[&("users".to_owned(), vec![Value::Integer(1)])],
+    ///     Some(vec![Some(1i64.into()), Some("ally".into())])
+    /// );
+    /// assert_eq!(rows[&("users".to_owned(), vec![Value::Integer(2)])], None);
+    /// ```
+    #[must_use]
+    pub fn final_rows(&self) -> FinalRows<S, B>
+    where
+        S: Clone,
+        B: Clone,
+    {
+        let mut final_rows = FinalRows::default();
+        for (table, rows) in &self.tables {
+            let pk_columns = table.primary_key_columns();
+            for (pk, op) in rows {
+                let key = (String::from(table.name()), pk.clone());
+                match op {
+                    Operation::Insert { values, .. } => {
+                        final_rows.insert(key, Some(values.iter().cloned().map(Some).collect()));
+                    }
+                    Operation::Delete { .. } => {
+                        final_rows.insert(key, None);
+                    }
+                    Operation::Update { values, .. } => {
+                        let mut row: Vec<Option<Value<S, B>>> =
+                            values.iter().map(|(_, new)| new.clone()).collect();
+                        for (&col, value) in pk_columns.iter().zip(pk) {
+                            row[col].get_or_insert_with(|| value.clone());
+                        }
+                        let new_pk: Vec<Value<S, B>> = pk_columns
+                            .iter()
+                            .filter_map(|&col| row[col].clone())
+                            .collect();
+                        if new_pk == *pk {
+                            final_rows.insert(key, Some(row));
+                        } else {
+                            final_rows.insert(key, None);
+                            final_rows.insert((String::from(table.name()), new_pk), Some(row));
+                        }
+                    }
+                }
+            }
+        }
+        final_rows
+    }
+
     /// Add any operation, consolidating with existing operations on the same row.
     ///
     /// The table schema is passed separately, operations are schema-less.
+    ///
+    /// An UPDATE or DELETE addressing a row through a numerically equal key
+    /// of the other storage class, `1.0` for a row stored as `1`, merges into
+    /// that row as the session does for an untyped key column. The builder
+    /// knows no column affinities, so for a REAL key column, which stores an
+    /// integral key as an integer that the session then fails to match with
+    /// the row's later changes, it merges where the session does not.
     pub(crate) fn add_operation(
         &mut self,
         table: &T,
@@ -545,81 +1162,315 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]>
         B: Clone,
         Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
     {
-        let rows = self.ensure_table(table);
+        if !self.touched.contains_key(table) {
+            self.touched.insert(table.clone(), KeyClasses::default());
+        }
+        let classes = &mut self.touched[table];
+        let rows = self.tables.entry(table.clone()).or_default();
+
+        if let Some(log) = self.entry_logs.get_mut(table) {
+            let mut started = Some(core::mem::take(log));
+            Self::consolidate(table, rows, &mut started, classes, pk, new_op);
+            *log = started.unwrap_or_default();
+        } else {
+            let mut started = None;
+            Self::consolidate(table, rows, &mut started, classes, pk, new_op);
+            if let Some(log) = started {
+                self.entry_logs.insert(table.clone(), log);
+            }
+        }
+
+        self
+    }
 
-        match rows.shift_remove_full(&pk) {
-            None => {
-                rows.insert(pk, new_op);
+    /// Consolidates `new_op` into the `rows` of `table`, where `log` holds
+    /// the table's entry log once one has been started and `classes` the
+    /// numeric classes of its staged keys.
+    pub(super) fn consolidate(
+        table: &T,
+        rows: &mut RowMap<F, S, B>,
+        log: &mut Option<EntryLog<S, B>>,
+        classes: &mut KeyClasses,
+        pk: Vec<Value<S, B>>,
+        new_op: Operation<F, S, B>,
+    ) where
+        S: Clone,
+        B: Clone,
+        Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
+    {
+        let index = rows.get_index_of(&pk).or_else(|| {
+            // The session keys a row on its stored values, which an UPDATE or
+            // DELETE may address through numerically equal ones, as
+            // `WHERE id = 1.0` does a row stored with the key `1`. An INSERT
+            // stores its own values, so it is a different row.
+            match new_op {
+                Operation::Insert { .. } => None,
+                _ if classes.may_stage_twin_of(&pk) => {
+                    numeric_twins(&pk).find_map(|twin| rows.get_index_of(&twin))
+                }
+                _ => None,
             }
-            Some((original_index, _removed_key, existing)) => {
-                // Special case: INSERT + UPDATE may change the PK
-                match (&existing, &new_op) {
-                    (Operation::Insert { .. }, Operation::Update { .. }) => {
-                        // Apply update to insert values, then re-extract PK
-                        if let Some(combined) = existing + new_op
-                            && let Operation::Insert { values, .. } = &combined
+        });
+        let Some(index) = index else {
+            if let Some(log) = log {
+                log.insert(pk.clone(), ());
+            }
+            classes.record(&pk);
+            rows.insert(pk, new_op);
+            return;
+        };
+        let mut new_op = new_op;
+        let pk = match rows.get_index(index) {
+            Some((stored, _)) if *stored != pk => {
+                // An UPDATE repeats the key it addresses the row by in its new
+                // values, which must not read as a key change.
+                if let Operation::Update { values, .. } = &mut new_op {
+                    let columns = table.primary_key_columns();
+                    for ((col, addressed), stored) in columns.into_iter().zip(&pk).zip(stored) {
+                        if let Some((_, new @ Some(_))) = values.get_mut(col)
+                            && new.as_ref() == Some(addressed)
                         {
-                            let new_pk = table.extract_pk(values);
-                            // The new PK may collide with a different existing row
-                            rows.shift_remove(&new_pk);
-                            let index = original_index.min(rows.len());
-                            rows.shift_insert(index, new_pk, combined);
-                        }
-                    }
-                    _ => {
-                        // Standard consolidation
-                        if let Some(combined) = existing + new_op {
-                            // Re-insert at original position to preserve row ordering
-                            rows.shift_insert(original_index, pk, combined);
+                            *new = Some(stored.clone());
                         }
                     }
                 }
+                stored.clone()
             }
-        }
+            _ => pk,
+        };
+        // Take the staged operation out in place, so a merge that keeps the
+        // row needs no shifting. The placeholder is overwritten or removed.
+        let existing = core::mem::replace(
+            &mut rows[index],
+            Operation::Insert {
+                values: Vec::new(),
+                indirect: false,
+            },
+        );
 
-        self
+        // Special case: INSERT + UPDATE may change the PK
+        if let (Operation::Insert { .. }, Operation::Update { .. }) = (&existing, &new_op) {
+            // Apply update to insert values, then re-extract PK
+            if let Some(combined) = existing + new_op
+                && let Operation::Insert { values, .. } = &combined
+            {
+                let new_pk = table.extract_pk(values);
+                if new_pk == pk {
+                    rows[index] = combined;
+                    return;
+                }
+                rows.shift_remove_index(index);
+                // The session records the key change as a DELETE of the old
+                // key and an INSERT of the new.
+                Self::entry_log(log, rows, index, &pk).insert(new_pk.clone(), ());
+                // The new PK may collide with a different existing row
+                rows.shift_remove(&new_pk);
+                let index = index.min(rows.len());
+                classes.record(&new_pk);
+                rows.shift_insert(index, new_pk, combined);
+            } else {
+                rows.shift_remove_index(index);
+            }
+        } else if let Some(combined) = Self::merge(table, existing, new_op) {
+            // Standard consolidation, keeping the row's position
+            rows[index] = combined;
+        } else {
+            rows.shift_remove_index(index);
+            Self::entry_log(log, rows, index, &pk);
+        }
     }
-}
-
-// Unified digest entry point for wire events (0.2.0+).
 
-impl<F, T, S, B> DiffSetBuilder<F, T, S, B>
-where
-    F: Format<S, B>,
-    T: crate::schema::NamedColumns,
-    S: Clone + Debug + Hash + Eq + AsRef<str> + Default,
-    B: Clone + Debug + Hash + Eq + AsRef<[u8]> + Default,
-{
-    /// Digest one CDC wire event, resolving each affected table via
-    /// `schema` and decoding column payloads via `adapter`.
-    ///
-    /// # Errors
+    /// Adds `new_op` like [`add_operation`](Self::add_operation), returning
+    /// whether the staged operations changed.
     ///
-    /// Whatever `E::Error` reports (per-source `ConversionError`).
-    pub fn digest<E, Sch, A>(self, event: &E, schema: &Sch, adapter: &A) -> Result<Self, E::Error>
+    /// The row keyed on `pk` is compared before and after, together with the
+    /// table's row count, so merges that leave the row as it was report
+    /// `false` while cancellations and key changes report `true`. UPDATEs
+    /// are compared in settled form, as a merge settles them.
+    fn add_operation_tracked(
+        &mut self,
+        table: &T,
+        pk: &[Value<S, B>],
+        new_op: Operation<F, S, B>,
+    ) -> bool
     where
-        E: crate::wire::Digestable<F, T, S, B>,
-        Sch: crate::wire::WireSchema<Table = T>,
-        A: crate::wire::WireAdapter<E::Src, S, B>,
-        T: crate::wire::WireColumnTypes,
+        S: Clone + PartialEq,
+        B: Clone + PartialEq,
+        F::DeleteData: PartialEq,
+        F::Old: PartialEq,
+        Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
     {
-        event.digest_into(self, schema, adapter)
+        let settled = |op: Option<&Operation<F, S, B>>| {
+            let mut op = op.cloned();
+            if let Some(Operation::Update { values, .. }) = &mut op {
+                F::settle_merged_update(values, |col| table.primary_key_index(col).is_some());
+            }
+            op
+        };
+        let rows = self.tables.get(table);
+        let rows_before = rows.map_or(0, IndexMap::len);
+        let before = settled(rows.and_then(|rows| rows.get(pk)));
+        self.add_operation(table, pk.to_vec(), new_op);
+        let rows = &self.tables[table];
+        rows.len() != rows_before || settled(rows.get(pk)) != before
     }
-}
-
-// ============================================================================
-// DiffOps trait: unified insert / delete / update for DiffSetBuilder & DiffSet
-// ============================================================================
 
-/// Trait for adding DML operations (INSERT, DELETE, UPDATE) to a diff set.
-///
-/// Implemented for both [`DiffSetBuilder`] and [`DiffSet`], allowing
-/// operations to be added to either type. Methods consume `self` and
-/// return a [`DiffSetBuilder`].
-pub trait DiffOps<T: SchemaWithPK, S, B>: Sized {
+    /// Consolidates `new_op` into the `existing` operation on the same row.
+    ///
+    /// UPDATEs produced by merging two UPDATEs, or a DELETE and an INSERT, are
+    /// settled into `SQLite`'s form and dropped if they change nothing.
+    fn merge(
+        table: &T,
+        existing: Operation<F, S, B>,
+        new_op: Operation<F, S, B>,
+    ) -> Option<Operation<F, S, B>>
+    where
+        Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
+    {
+        let merges_update = matches!(
+            (&existing, &new_op),
+            (Operation::Update { .. }, Operation::Update { .. })
+                | (Operation::Delete { .. }, Operation::Insert { .. })
+        );
+        let mut combined = existing + new_op;
+        if let Some(Operation::Update { values, .. }) = &mut combined {
+            if merges_update
+                && !F::settle_merged_update(values, |col| table.primary_key_index(col).is_some())
+            {
+                return None;
+            }
+        }
+        combined
+    }
+
+    /// Returns the entry log in `log`, starting it from `rows` if the table
+    /// has none yet. `pk` has just been taken out of `rows` at `index`.
+    fn entry_log<'log>(
+        log: &'log mut Option<EntryLog<S, B>>,
+        rows: &RowMap<F, S, B>,
+        index: usize,
+        pk: &[Value<S, B>],
+    ) -> &'log mut EntryLog<S, B>
+    where
+        S: Clone,
+        B: Clone,
+    {
+        log.get_or_insert_with(|| {
+            let mut log: EntryLog<S, B> = rows.keys().map(|key| (key.clone(), ())).collect();
+            log.shift_insert(index, pk.to_vec(), ());
+            log
+        })
+    }
+
+    /// Returns the indices of the rows of `table` in the order `SQLite` emits
+    /// them.
+    fn emission_order(&self, table: &T, rows: &RowMap<F, S, B>) -> Vec<usize> {
+        Self::logged_emission_order(table, rows, self.entry_logs.get(table))
+    }
+
+    /// Returns the indices of `rows` sorted by primary key, comparing the key
+    /// columns in key order with [`compare_values`]. Keys that compare equal,
+    /// such as `1` and `1.0`, keep their insertion order.
+    fn pk_sorted_order(rows: &RowMap<F, S, B>) -> Vec<usize>
+    where
+        S: AsRef<str>,
+        B: AsRef<[u8]>,
+    {
+        let keys: Vec<&Vec<Value<S, B>>> = rows.keys().collect();
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by(|&a, &b| {
+            keys[a]
+                .iter()
+                .zip(keys[b])
+                .map(|(a, b)| compare_values(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(core::cmp::Ordering::Equal)
+        });
+        order
+    }
+
+    /// Returns the indices of `rows` in the order `SQLite` emits them, given
+    /// the table's entry log if one was started.
+    pub(super) fn logged_emission_order(
+        table: &T,
+        rows: &RowMap<F, S, B>,
+        log: Option<&EntryLog<S, B>>,
+    ) -> Vec<usize> {
+        match log {
+            None => session_row_order(table, rows, |_, op| op.is_insert()),
+            // A logged row missing from `rows` was inserted, then deleted.
+            Some(log) => session_row_order(table, log, |pk, ()| {
+                rows.get(pk).is_none_or(Operation::is_insert)
+            })
+            .into_iter()
+            .filter_map(|idx| {
+                let (pk, ()) = log.get_index(idx)?;
+                rows.get_index_of(pk)
+            })
+            .collect(),
+        }
+    }
+
+    /// Iterates over the tables in the order `SQLite`'s session would list
+    /// them: by first operation, then any table that never received one
+    /// through this builder, in `tables` order.
+    fn sqlite_table_order(&self) -> impl Iterator<Item = (&T, &RowMap<F, S, B>)> {
+        self.touched
+            .keys()
+            .filter_map(|table| self.tables.get_key_value(table))
+            .chain(
+                self.tables
+                    .iter()
+                    .filter(|(table, _)| !self.touched.contains_key(*table)),
+            )
+    }
+}
+
+// Unified digest entry point for wire events (0.2.0+).
+
+impl<F, T, S, B> DiffSetBuilder<F, T, S, B>
+where
+    F: Format<S, B>,
+    T: crate::schema::NamedColumns,
+    S: Clone + Debug + Hash + Eq + AsRef<str> + Default,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]> + Default,
+{
+    /// Digest one CDC wire event, resolving each affected table via
+    /// `schema` and decoding column payloads via `adapter`.
+    ///
+    /// # Errors
+    ///
+    /// Whatever `E::Error` reports (per-source `ConversionError`).
+    pub fn digest<E, Sch, A>(self, event: &E, schema: &Sch, adapter: &A) -> Result<Self, E::Error>
+    where
+        E: crate::wire::Digestable<F, T, S, B>,
+        Sch: crate::wire::WireSchema<Table = T>,
+        A: crate::wire::WireAdapter<E::Src, S, B>,
+        T: crate::wire::WireColumnTypes,
+        Self: DiffOps<T, S, B, Format = F, Output = Self, DeleteArg = F::Delete<T>>,
+    {
+        event.digest_into(self, schema, adapter)
+    }
+}
+
+// ============================================================================
+// DiffOps trait: unified insert / delete / update for DiffSetBuilder & DiffSet
+// ============================================================================
+
+/// Trait for adding DML operations (INSERT, DELETE, UPDATE) to a diff set.
+///
+/// Implemented for [`DiffSetBuilder`], [`DiffSet`], [`SingleTableChangeSet`]
+/// and [`GroupedDiffSet`], allowing operations to be added to any of them.
+/// Methods consume `self` and return the [`Output`](Self::Output) builder:
+/// a [`DiffSetBuilder`] for the first two, and the builder itself for the
+/// others.
+pub trait DiffOps<T: SchemaWithPK, S, B>: Sized {
     /// The format (changeset or patchset) of the diff set.
     type Format: Format<S, B>;
 
+    /// The builder the operations return.
+    type Output;
+
     /// The argument type for the [`delete`](Self::delete) operation.
     ///
     /// * Changeset: [`ChangeDelete<T, S, B>`]
@@ -627,14 +1478,13 @@ pub trait DiffOps<T: SchemaWithPK, S, B>: Sized {
     type DeleteArg;
 
     /// Add an INSERT operation.
-    fn insert(self, insert: Insert<T, S, B>) -> DiffSetBuilder<Self::Format, T, S, B>;
+    fn insert(self, insert: Insert<T, S, B>) -> Self::Output;
 
     /// Add a DELETE operation.
-    fn delete(self, delete: Self::DeleteArg) -> DiffSetBuilder<Self::Format, T, S, B>;
+    fn delete(self, delete: Self::DeleteArg) -> Self::Output;
 
     /// Add an UPDATE operation.
-    fn update(self, update: Update<T, Self::Format, S, B>)
-    -> DiffSetBuilder<Self::Format, T, S, B>;
+    fn update(self, update: Update<T, Self::Format, S, B>) -> Self::Output;
 }
 
 // -- DiffOps for DiffSetBuilder<ChangesetFormat> ------------------------------
@@ -646,20 +1496,12 @@ impl<
 > DiffOps<T, S, B> for DiffSetBuilder<ChangesetFormat, T, S, B>
 {
     type Format = ChangesetFormat;
+    type Output = Self;
     type DeleteArg = ChangeDelete<T, S, B>;
 
     fn insert(mut self, insert: Insert<T, S, B>) -> Self {
-        let pk = insert.extract_pk();
-        let table = insert.as_ref().clone();
-        let indirect = insert.indirect;
-        self.add_operation(
-            &table,
-            pk,
-            Operation::Insert {
-                values: insert.into_values(),
-                indirect,
-            },
-        );
+        let (table, pk, op) = insert_parts(insert);
+        self.add_operation(&table, pk, op);
         self
     }
 
@@ -678,19 +1520,177 @@ impl<
         self
     }
 
+    /// Update a row, keyed on its old primary-key values.
+    ///
+    /// A primary-key column whose old value was left undefined is keyed as
+    /// NULL, so the update consolidates with any other operation on the same
+    /// table whose key holds NULL in that column.
     fn update(mut self, update: Update<T, ChangesetFormat, S, B>) -> Self {
-        let old_values: Vec<_> = update
-            .values()
+        let (table, pk, op) = changeset_update_parts(update);
+        self.add_operation(&table, pk, op);
+        self
+    }
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<ChangesetFormat, T, S, B>
+{
+    /// Returns the patchset `SQLite`'s session emits alongside this changeset.
+    ///
+    /// Old values are dropped, so an UPDATE keeps its primary key and the new
+    /// values of the columns it changes. Unlike a patchset staged directly, this knows when
+    /// later updates restored a column's original value.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn to_patchset(&self) -> DiffSetBuilder<PatchsetFormat, T, S, B> {
+        let tables = self
+            .tables
             .iter()
-            .map(|(old, _): &(_, _)| old.clone().unwrap_or(Value::Null))
+            .map(|(table, rows)| {
+                let rows = rows
+                    .iter()
+                    .map(|(pk, op)| {
+                        let op = match op {
+                            Operation::Insert { values, indirect } => Operation::Insert {
+                                values: values.clone(),
+                                indirect: *indirect,
+                            },
+                            Operation::Delete { indirect, .. } => Operation::Delete {
+                                data: (),
+                                indirect: *indirect,
+                            },
+                            Operation::Update { values, indirect } => Operation::Update {
+                                values: values
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(col, (_, new))| match table.primary_key_index(col) {
+                                        Some(pos) => ((), Some(pk[pos].clone())),
+                                        None => ((), new.clone()),
+                                    })
+                                    .collect(),
+                                indirect: *indirect,
+                            },
+                        };
+                        (pk.clone(), op)
+                    })
+                    .collect();
+                (table.clone(), rows)
+            })
             .collect();
-        let pk = update.as_ref().extract_pk(&old_values);
-        let table = update.as_ref().clone();
-        let indirect = update.indirect;
-        let values: Vec<(MaybeValue<S, B>, MaybeValue<S, B>)> = update.into();
-        self.add_operation(&table, pk, Operation::Update { values, indirect });
-        self
+        DiffSetBuilder {
+            tables,
+            entry_logs: self.entry_logs.clone(),
+            touched: self.touched.clone(),
+        }
     }
+
+    /// Records an INSERT unless it leaves the staged operations unchanged,
+    /// returning whether anything changed.
+    ///
+    /// Staging an INSERT identical to the one already staged for its row is
+    /// a no-op and returns `false`. Any other outcome, including a merge
+    /// with a staged DELETE, returns `true`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, Insert, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let alice = Insert::from(users).set(0, 1i64).unwrap().set(1, "alice").unwrap();
+    ///
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// assert!(changeset.try_insert(alice.clone()));
+    /// assert!(!changeset.try_insert(alice));
+    /// assert_eq!(changeset.len(), 1);
+    /// ```
+    pub fn try_insert(&mut self, insert: Insert<T, S, B>) -> bool {
+        let (table, pk, op) = insert_parts(insert);
+        self.add_operation_tracked(&table, &pk, op)
+    }
+
+    /// Records an UPDATE unless it leaves the staged operations unchanged,
+    /// returning whether anything changed.
+    ///
+    /// An UPDATE whose new values already hold in the operation staged for
+    /// its row, e.g. a staged INSERT or UPDATE, is a no-op and returns
+    /// `false`.
+    pub fn try_update(&mut self, update: Update<T, ChangesetFormat, S, B>) -> bool {
+        let (table, pk, op) = changeset_update_parts(update);
+        self.add_operation_tracked(&table, &pk, op)
+    }
+}
+
+impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq + AsRef<[u8]>>
+    DiffSetBuilder<PatchsetFormat, T, S, B>
+{
+    /// Records an INSERT unless it leaves the staged operations unchanged,
+    /// returning whether anything changed.
+    ///
+    /// See the changeset [`try_insert`](DiffSetBuilder::try_insert).
+    pub fn try_insert(&mut self, insert: Insert<T, S, B>) -> bool {
+        let (table, pk, op) = insert_parts(insert);
+        self.add_operation_tracked(&table, &pk, op)
+    }
+
+    /// Records an UPDATE unless it leaves the staged operations unchanged,
+    /// returning whether anything changed.
+    ///
+    /// See the changeset [`try_update`](DiffSetBuilder::try_update).
+    pub fn try_update(&mut self, update: Update<T, PatchsetFormat, S, B>) -> bool {
+        let (table, pk, op) = patchset_update_parts(update);
+        self.add_operation_tracked(&table, &pk, op)
+    }
+}
+
+/// A table, the primary key of the row an operation lands on, and the
+/// operation itself.
+type StagedOp<F, T, S, B> = (T, Vec<Value<S, B>>, Operation<F, S, B>);
+
+/// Splits an INSERT into the parts [`DiffSetBuilder::add_operation`] takes.
+fn insert_parts<F: Format<S, B>, T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>(
+    insert: Insert<T, S, B>,
+) -> StagedOp<F, T, S, B> {
+    let pk = insert.extract_pk();
+    let table = insert.as_ref().clone();
+    let indirect = insert.indirect;
+    let values = insert.into_values();
+    (table, pk, Operation::Insert { values, indirect })
+}
+
+/// Splits a changeset UPDATE into the parts [`DiffSetBuilder::add_operation`]
+/// takes, keying the row on its old primary-key values.
+fn changeset_update_parts<
+    T: SchemaWithPK,
+    S: Clone + Debug + AsRef<str>,
+    B: Clone + Debug + AsRef<[u8]>,
+>(
+    update: Update<T, ChangesetFormat, S, B>,
+) -> StagedOp<ChangesetFormat, T, S, B> {
+    let old_values: Vec<_> = update
+        .values()
+        .iter()
+        .map(|(old, _): &(_, _)| old.clone().unwrap_or(Value::Null))
+        .collect();
+    let pk = update.as_ref().extract_pk(&old_values);
+    let table = update.as_ref().clone();
+    let indirect = update.indirect;
+    let values: Vec<(MaybeValue<S, B>, MaybeValue<S, B>)> = update.into();
+    (table, pk, Operation::Update { values, indirect })
+}
+
+/// Splits a patchset UPDATE into the parts [`DiffSetBuilder::add_operation`]
+/// takes.
+fn patchset_update_parts<T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>(
+    update: Update<T, PatchsetFormat, S, B>,
+) -> StagedOp<PatchsetFormat, T, S, B> {
+    let pk = update.extract_pk();
+    let table = update.as_ref().clone();
+    let indirect = update.indirect;
+    let values: Vec<((), MaybeValue<S, B>)> = update.into();
+    (table, pk, Operation::Update { values, indirect })
 }
 
 // -- DiffOps for DiffSetBuilder<PatchsetFormat> -------------------------------
@@ -699,20 +1699,12 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     DiffOps<T, S, B> for DiffSetBuilder<PatchsetFormat, T, S, B>
 {
     type Format = PatchsetFormat;
+    type Output = Self;
     type DeleteArg = PatchDelete<T, S, B>;
 
     fn insert(mut self, insert: Insert<T, S, B>) -> Self {
-        let pk = insert.extract_pk();
-        let table = insert.as_ref().clone();
-        let indirect = insert.indirect;
-        self.add_operation(
-            &table,
-            pk,
-            Operation::Insert {
-                values: insert.into_values(),
-                indirect,
-            },
-        );
+        let (table, pk, op) = insert_parts(insert);
+        self.add_operation(&table, pk, op);
         self
     }
 
@@ -742,6 +1734,11 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
 
     /// Update by primary key.
     ///
+    /// A patchset `UPDATE` stores each primary-key column once, so the row is
+    /// keyed on the PK values set in `update` and cannot move to a new key.
+    /// To record a key change, use [`update_rows`](Self::update_rows), which
+    /// splits it into a DELETE and an INSERT.
+    ///
     /// # Example
     ///
     /// ```
@@ -759,15 +1756,413 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     ///     .update(update);
     /// ```
     fn update(mut self, update: Update<T, PatchsetFormat, S, B>) -> Self {
-        let pk = update.extract_pk();
-        let table = update.as_ref().clone();
-        let indirect = update.indirect;
-        let values: Vec<((), MaybeValue<S, B>)> = update.into();
-        self.add_operation(&table, pk, Operation::Update { values, indirect });
+        let (table, pk, op) = patchset_update_parts(update);
+        self.add_operation(&table, pk, op);
         self
     }
 }
 
+impl<
+    T: NamedColumns,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<ChangesetFormat, T, S, B>
+{
+    /// Records an INSERT of a row given as column name to value pairs.
+    ///
+    /// Columns missing from `row` are inserted as NULL, except primary-key
+    /// columns, which `row` must name.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If `row` names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `row` does not name every primary-key column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use sqlite_diff_rs::{ChangeSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let row: HashMap<String, Value<String, Vec<u8>>> =
+    ///     HashMap::from([("id".into(), 1i64.into()), ("name".into(), "Alice".into())]);
+    ///
+    /// let changeset = ChangeSet::new().insert_map(&users, &row).unwrap();
+    /// assert_eq!(changeset.len(), 1);
+    /// ```
+    pub fn insert_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        let mut insert = Insert::from(table.clone());
+        insert.values = named_row(table, row)?;
+        Ok(self.insert(insert))
+    }
+
+    /// Records a DELETE of a row given as column name to value pairs.
+    ///
+    /// Columns missing from `row` are recorded as NULL in the old row,
+    /// except primary-key columns, which `row` must name.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If `row` names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `row` does not name every primary-key column.
+    pub fn delete_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        Ok(self.delete(ChangeDelete {
+            table: table.clone(),
+            values: named_row(table, row)?,
+            indirect: false,
+        }))
+    }
+
+    /// Records an UPDATE from the `old` and `new` column name to value pairs.
+    ///
+    /// A column named in `new` records its new value, along with its old
+    /// value if `old` names it too. A column named only in `old` keeps its
+    /// old value, which is how the primary key locates the row, so `old` must
+    /// name every primary-key column. Columns named in neither are left
+    /// undefined.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If either map names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `old` does not name every primary-key column.
+    pub fn update_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        old: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+        new: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        let old = named_values(table, old)?;
+        require_pk(table, &old)?;
+        let mut update = Update::<T, ChangesetFormat, S, B>::from(table.clone());
+        for (col_idx, value) in old {
+            update.values[col_idx].0 = Some(value);
+        }
+        for (col_idx, value) in named_values(table, new)? {
+            update.values[col_idx].1 = Some(value);
+        }
+        Ok(self.update(update))
+    }
+}
+
+impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq + AsRef<[u8]>>
+    DiffSetBuilder<PatchsetFormat, T, S, B>
+{
+    /// Records a row of `table` going from `old` to `new`.
+    ///
+    /// When the primary key is unchanged this adds the update built by
+    /// [`Update::from_rows`], or nothing if no column changed. When the key
+    /// differs, a single patchset `UPDATE` cannot express it, so this records
+    /// a DELETE of the old key followed by an INSERT of the new row, which is
+    /// how the session extension records a key change.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either row does not have one value per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{PatchSet, PatchsetOp, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let old: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "Alice".into()];
+    /// let new = vec![2i64.into(), "Alice".into()];
+    ///
+    /// // UPDATE users SET id = 2 WHERE id = 1
+    /// let patchset = PatchSet::new().update_rows(&users, &old, &new);
+    /// let ops: Vec<_> = patchset.iter().collect();
+    /// assert!(matches!(ops[0], PatchsetOp::Delete { .. }));
+    /// assert!(matches!(ops[1], PatchsetOp::Insert { .. }));
+    /// ```
+    #[must_use]
+    pub fn update_rows(self, table: &T, old: &[Value<S, B>], new: &[Value<S, B>]) -> Self {
+        self.update_rows_collated(table, old, new, &[])
+    }
+
+    /// Like [`update_rows`](Self::update_rows), but detects changes with
+    /// [`Update::from_rows_collated`], so a row whose text only changes into
+    /// an equal form under its column's collation records nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either row does not have one value per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{Collation, PatchSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let old: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "alice".into()];
+    /// let new = vec![1i64.into(), "Alice".into()];
+    /// let nocase = [Collation::Binary, Collation::NoCase];
+    ///
+    /// assert_eq!(PatchSet::new().update_rows(&users, &old, &new).len(), 1);
+    /// assert!(PatchSet::new().update_rows_collated(&users, &old, &new, &nocase).is_empty());
+    /// ```
+    #[must_use]
+    pub fn update_rows_collated(
+        self,
+        table: &T,
+        old: &[Value<S, B>],
+        new: &[Value<S, B>],
+        collations: &[crate::Collation],
+    ) -> Self {
+        match Update::from_rows_collated(table.clone(), old, new, collations) {
+            Ok(Some(update)) => self.update(update),
+            Ok(None) => self,
+            Err(_) => {
+                let mut insert = Insert::from(table.clone());
+                insert.values = new.to_vec();
+                self.delete(PatchDelete::new(table.clone(), table.extract_pk(&old)))
+                    .insert(insert)
+            }
+        }
+    }
+}
+
+impl<
+    T: NamedColumns,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<PatchsetFormat, T, S, B>
+{
+    /// Records an INSERT of a row given as column name to value pairs.
+    ///
+    /// Columns missing from `row` are inserted as NULL, except primary-key
+    /// columns, which `row` must name.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If `row` names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `row` does not name every primary-key column.
+    pub fn insert_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        let mut insert = Insert::from(table.clone());
+        insert.values = named_row(table, row)?;
+        Ok(self.insert(insert))
+    }
+
+    /// Records a DELETE of the row whose primary key is given in `row`.
+    ///
+    /// Only the primary-key columns are stored, and `row` must name all of
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If `row` names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `row` does not name every primary-key column.
+    pub fn delete_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        let pk = table.extract_pk(&named_row(table, row)?);
+        Ok(self.delete(PatchDelete::new(table.clone(), pk)))
+    }
+
+    /// Records an UPDATE setting the columns named in `row` to new values.
+    ///
+    /// `row` must name the primary-key columns so the row can be located.
+    /// Columns missing from `row` are left undefined.
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnNotFound` - If `row` names a column that `table` does not have.
+    /// * `MissingPkColumn` - If `row` does not name every primary-key column.
+    pub fn update_map<'a, N: AsRef<str> + ?Sized + 'a>(
+        self,
+        table: &T,
+        row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error>
+    where
+        S: 'a,
+        B: 'a,
+    {
+        let row = named_values(table, row)?;
+        require_pk(table, &row)?;
+        let mut update = Update::<T, PatchsetFormat, S, B>::from(table.clone());
+        for (col_idx, value) in row {
+            update.values[col_idx].1 = Some(value);
+        }
+        Ok(self.update(update))
+    }
+}
+
+impl<F: Format<S, B>, T: SchemaWithPK, S: Clone, B: Clone> DiffSetBuilder<F, T, S, B>
+where
+    Self: DiffOps<T, S, B, Format = F, Output = Self>,
+{
+    /// Records an INSERT of the same row into each of `tables`.
+    ///
+    /// Every table must have exactly one column per value; they are checked
+    /// before any INSERT is recorded.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If a table's column count differs from
+    ///   `values.len()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{PatchSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let users_archive = SimpleTable::new("users_archive", &["id", "name"], &[0]);
+    /// let row: [Value<String, Vec<u8>>; 2] = [1i64.into(), "Alice".into()];
+    ///
+    /// let patchset = PatchSet::new()
+    ///     .insert_into_all(&[users, users_archive], &row)
+    ///     .unwrap();
+    /// assert_eq!(patchset.len(), 2);
+    /// ```
+    pub fn insert_into_all(
+        self,
+        tables: &[T],
+        values: &[Value<S, B>],
+    ) -> Result<Self, crate::errors::Error> {
+        if let Some(table) = tables
+            .iter()
+            .find(|table| table.number_of_columns() != values.len())
+        {
+            return Err(crate::errors::Error::ValueCountMismatch(
+                values.len(),
+                table.number_of_columns(),
+            ));
+        }
+        Ok(tables.iter().fold(self, |builder, table| {
+            builder.insert(Insert::from_values(table.clone(), values.to_vec()))
+        }))
+    }
+
+    /// Records an INSERT whose values win over an INSERT already staged for
+    /// the same row.
+    ///
+    /// By default, as in `sqlite3changegroup_add()`, a second INSERT on a
+    /// row that already holds one is ignored and the first values are kept.
+    /// `upsert` instead replaces the staged INSERT, values and indirect flag,
+    /// with `insert`, as an application-level upsert would. On any other row
+    /// it behaves like [`insert`](DiffOps::insert).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let row = |name| Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, name).unwrap();
+    ///
+    /// let upserted: ChangeSet<_, String, Vec<u8>> =
+    ///     ChangeSet::new().insert(row("Alice")).upsert(row("Alicia"));
+    /// let by_hand = ChangeSet::new().insert(row("Alicia"));
+    /// assert_eq!(upserted.build(), by_hand.build());
+    /// ```
+    #[must_use]
+    pub fn upsert(mut self, insert: Insert<T, S, B>) -> Self
+    where
+        S: Hash + Eq + AsRef<str>,
+        B: Hash + Eq + AsRef<[u8]>,
+    {
+        let pk = insert.extract_pk();
+        let staged = self
+            .tables
+            .get_mut(insert.as_ref())
+            .and_then(|rows| rows.get_mut(&pk));
+        if let Some(op @ Operation::Insert { .. }) = staged {
+            let indirect = insert.indirect;
+            *op = Operation::Insert {
+                values: insert.into_values(),
+                indirect,
+            };
+            return self;
+        }
+        self.insert(insert)
+    }
+}
+
+/// A column index resolved from a name, paired with its value.
+type NamedValue<S, B> = (usize, Value<S, B>);
+
+/// Resolves the column names of `row` against `table`, pairing each column
+/// index with its value.
+fn named_values<'a, T: NamedColumns, N: AsRef<str> + ?Sized + 'a, S: Clone + 'a, B: Clone + 'a>(
+    table: &T,
+    row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+) -> Result<Vec<NamedValue<S, B>>, crate::errors::Error> {
+    row.into_iter()
+        .map(|(name, value)| {
+            let name = name.as_ref();
+            table
+                .column_index(name)
+                .map(|col_idx| (col_idx, value.clone()))
+                .ok_or_else(|| crate::errors::Error::ColumnNotFound(name.into()))
+        })
+        .collect()
+}
+
+/// Checks that `named` holds a value for every primary-key column of `table`.
+fn require_pk<T: SchemaWithPK, S, B>(
+    table: &T,
+    named: &[NamedValue<S, B>],
+) -> Result<(), crate::errors::Error> {
+    match table
+        .primary_key_columns()
+        .into_iter()
+        .find(|pk| !named.iter().any(|(col_idx, _)| col_idx == pk))
+    {
+        Some(col_idx) => Err(crate::errors::Error::MissingPkColumn(col_idx)),
+        None => Ok(()),
+    }
+}
+
+/// Resolves `row` against `table` into a full row, with NULL for every
+/// column that `row` does not name. Every primary-key column must be named.
+fn named_row<'a, T: NamedColumns, N: AsRef<str> + ?Sized + 'a, S: Clone + 'a, B: Clone + 'a>(
+    table: &T,
+    row: impl IntoIterator<Item = (&'a N, &'a Value<S, B>)>,
+) -> Result<Vec<Value<S, B>>, crate::errors::Error> {
+    let named = named_values(table, row)?;
+    require_pk(table, &named)?;
+    let mut values = vec![Value::Null; table.number_of_columns()];
+    for (col_idx, value) in named {
+        values[col_idx] = value;
+    }
+    Ok(values)
+}
+
 // -- DiffOps for DiffSet<ChangesetFormat> -------------------------------------
 
 impl<
@@ -777,6 +2172,7 @@ impl<
 > DiffOps<T, S, B> for DiffSet<ChangesetFormat, T, S, B>
 {
     type Format = ChangesetFormat;
+    type Output = DiffSetBuilder<ChangesetFormat, T, S, B>;
     type DeleteArg = ChangeDelete<T, S, B>;
 
     fn insert(self, insert: Insert<T, S, B>) -> DiffSetBuilder<ChangesetFormat, T, S, B> {
@@ -804,6 +2200,7 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     DiffOps<T, S, B> for DiffSet<PatchsetFormat, T, S, B>
 {
     type Format = PatchsetFormat;
+    type Output = DiffSetBuilder<PatchsetFormat, T, S, B>;
     type DeleteArg = PatchDelete<T, S, B>;
 
     fn insert(self, insert: Insert<T, S, B>) -> DiffSetBuilder<PatchsetFormat, T, S, B> {
@@ -825,15 +2222,57 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     }
 }
 
-impl<T: crate::schema::NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<'a> From<&'a str>>
-    DiffSetBuilder<PatchsetFormat, T, S, Vec<u8>>
+impl<
+    F: crate::builders::sql::SqlFormat<S>,
+    T: crate::schema::NamedColumns,
+    S: Clone + Hash + Eq + AsRef<str> + for<'a> From<&'a str>,
+> DiffSetBuilder<F, T, S, Vec<u8>>
+where
+    Operation<F, S, Vec<u8>>: core::ops::Add<Output = Option<Operation<F, S, Vec<u8>>>>,
 {
     /// Digest a SQL string containing INSERT, UPDATE, and DELETE statements
-    /// into this patchset builder.
+    /// into this builder.
     ///
     /// The SQL statements are parsed and their effects are directly applied
     /// to the builder, consolidating operations by primary key as usual.
     ///
+    /// A patchset only needs the primary key, so its `WHERE` clauses may
+    /// constrain primary-key columns only. A changeset also records old
+    /// values, but SQL exposes only the ones the statement names:
+    ///
+    /// * `UPDATE` takes the old key from `WHERE`, plus the old value of any
+    ///   assigned column that `WHERE` also constrains. Other assigned columns
+    ///   keep an undefined old value.
+    /// * `DELETE` takes the old row from `WHERE`, and every column it does
+    ///   not constrain is recorded as NULL.
+    ///
+    /// Two clauses of modern `SQLite` DML are accepted but simplified. A
+    /// trailing `RETURNING ...` is skipped, since it does not affect the
+    /// change. An `INSERT ... ON CONFLICT [(columns)] DO NOTHING` is digested
+    /// as a plain `INSERT`: consolidation already ignores an `INSERT` on a row
+    /// the builder holds, but a conflict with a row the builder never saw
+    /// cannot be detected. `ON CONFLICT ... DO UPDATE` is rejected.
+    ///
+    /// Names may be quoted as `"name"`, `[name]` or `` `name` ``, and a
+    /// table name qualified with a schema, which is ignored. Besides
+    /// literals, a value may be one of the `replace()`, `char()` and
+    /// `unistr()` calls the `sqlite3` shell's `.dump` writes to spell
+    /// control characters in text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// changeset.add_table(&users);
+    /// changeset
+    ///     .digest_sql("UPDATE users SET name = 'Bob' WHERE id = 1 AND name = 'Alice'")
+    ///     .unwrap();
+    /// assert_eq!(changeset.len(), 1);
+    /// ```
+    ///
     /// # Errors
     ///
     /// Returns a [`crate::builders::sql::ParseError`] if the SQL cannot be parsed.
@@ -845,38 +2284,244 @@ impl<T: crate::schema::NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<'a>
         parser.digest_all()?;
         Ok(self)
     }
-}
 
-// ============================================================================
-// Unified build implementation
-// ============================================================================
+    /// Like [`DiffSetBuilder::digest_sql`], but each `UPDATE` is evaluated
+    /// against the rows `rows` returns for its table, so its `WHERE` clause
+    /// may constrain any column.
+    ///
+    /// The clause is a conjunction of `col = value`, `col IS value` and
+    /// `col IN (value, ...)` constraints. Every matching row is recorded as
+    /// its own `UPDATE`, with the full old row available to a changeset;
+    /// rows the assignments leave unchanged are skipped, and a row whose
+    /// primary key changes is recorded as a `DELETE` and an `INSERT`.
+    ///
+    /// `rows` is called once per `UPDATE` and must reflect the table before
+    /// `input` runs: rows changed by earlier statements in `input` are not
+    /// seen again. `INSERT` and `DELETE` are digested as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{PatchSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "status"], &[0]);
+    /// let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    /// patchset.add_table(&users);
+    /// let rows = |_: &SimpleTable| {
+    ///     (1..=3)
+    ///         .map(|id| vec![Value::Integer(id), Value::Text("active".into())])
+    ///         .collect()
+    /// };
+    /// patchset
+    ///     .digest_sql_with_rows("UPDATE users SET status = 'idle' WHERE id IN (1, 3)", &rows)
+    ///     .unwrap();
+    /// assert_eq!(patchset.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::builders::sql::ParseError`] if the SQL cannot be parsed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a row from `rows` does not hold one value per column.
+    pub fn digest_sql_with_rows<'input>(
+        &mut self,
+        input: &'input str,
+        rows: crate::builders::sql::RowSource<'_, T, S>,
+    ) -> Result<&mut Self, crate::builders::sql::ParseError<'input>> {
+        let mut parser = crate::builders::sql::Parser::new(input, self).with_row_source(rows);
+        parser.digest_all()?;
+        Ok(self)
+    }
+
+    /// Like [`DiffSetBuilder::digest_sql`], but every literal bound to a
+    /// column is first passed through `coerce` with its table and column
+    /// index, as `SQLite` applies column affinity before storing a value.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn digest_sql_coerced<'input>(
+        &mut self,
+        input: &'input str,
+        coerce: crate::builders::sql::Coercion<'_, T, S>,
+    ) -> Result<&mut Self, crate::builders::sql::ParseError<'input>> {
+        let mut parser = crate::builders::sql::Parser::new(input, self).with_coercion(coerce);
+        parser.digest_all()?;
+        Ok(self)
+    }
+}
+
+impl<F: crate::builders::sql::SqlFormat<String>>
+    DiffSetBuilder<F, crate::SimpleTable, String, Vec<u8>>
+where
+    Operation<F, String, Vec<u8>>: core::ops::Add<Output = Option<Operation<F, String, Vec<u8>>>>,
+{
+    /// Builds a diffset from the SQL script written by the `sqlite3` shell's
+    /// `.dump` command, the usual starting point for mirroring an existing
+    /// database.
+    ///
+    /// Every `CREATE TABLE` registers a [`SimpleTable`](crate::SimpleTable),
+    /// and the `INSERT` statements that follow become inserts. An `ALTER
+    /// TABLE ... ADD COLUMN` appends the column to its table, the rows staged
+    /// before it reading NULL there, and any other `ALTER TABLE` is an error.
+    /// The dump's `PRAGMA` and transaction statements, indices, views and
+    /// triggers are skipped, as are tables without a `PRIMARY KEY` and the
+    /// internal `sqlite_*` tables, none of which a session would record.
+    ///
+    /// An `INSERT` leaving the primary key NULL is keyed on the next rowid
+    /// when the key is an `INTEGER PRIMARY KEY`, which aliases the rowid. On
+    /// any other key, such as `INT PRIMARY KEY`, the row is stored with a
+    /// NULL key, which a session does not record, so it is skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, SimpleTable};
+    ///
+    /// let dump = "PRAGMA foreign_keys=OFF;
+    /// BEGIN TRANSACTION;
+    /// CREATE TABLE users(id INTEGER PRIMARY KEY, name TEXT);
+    /// INSERT INTO users VALUES(1,'Alice');
+    /// COMMIT;";
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+    ///     ChangeSet::from_sqlite_dump(dump).unwrap();
+    /// assert_eq!(changeset.len(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`crate::builders::sql::ParseError`] if a `CREATE TABLE` or
+    /// `ALTER TABLE` is malformed or unsupported, or any other statement
+    /// cannot be parsed.
+    pub fn from_sqlite_dump(sql: &str) -> Result<Self, crate::builders::sql::ParseError<'_>> {
+        let mut builder = Self::new();
+        crate::builders::sql::digest_dump(sql, &mut builder)?;
+        Ok(builder)
+    }
+}
+
+// ============================================================================
+// Unified build implementation
+// ============================================================================
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<ChangesetFormat, T, S, B>
+{
+    /// Build the changeset binary data.
+    ///
+    /// Returns the binary representation compatible with `SQLite`'s session extension.
+    /// Tables are emitted in the order they entered the builder, whether by
+    /// registration or by a first operation; see
+    /// [`build_sqlite_table_order`](Self::build_sqlite_table_order).
+    ///
+    /// # Panics
+    ///
+    /// This function does not panic under normal usage. Internal indexing is guaranteed
+    /// to be within bounds.
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        self.build_tables(&self.tables, false)
+    }
+
+    /// Like [`build`](Self::build), but first checks that every table name
+    /// can be stored in a table header.
+    ///
+    /// [`build`](Self::build) writes a name as is, followed by a NUL byte, so
+    /// a name containing one is cut short and the changeset no longer
+    /// parses.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidTableName` - If the name of a table holding operations
+    ///   contains a NUL byte.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Error, Insert, SimpleTable};
+    ///
+    /// let table = SimpleTable::new("bad\0name", &["id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+    ///     ChangeSet::new().insert(Insert::from(table).set(0, 1i64).unwrap());
+    /// assert_eq!(
+    ///     changeset.try_build(),
+    ///     Err(Error::InvalidTableName("bad\0name".into()))
+    /// );
+    /// ```
+    pub fn try_build(&self) -> Result<Vec<u8>, crate::errors::Error> {
+        self.check_table_names()?;
+        Ok(self.build())
+    }
+
+    /// Build the changeset binary data, emitting tables in the order they
+    /// first received an operation rather than the order they were
+    /// registered in.
+    ///
+    /// This is the order `SQLite`'s session extension uses, so it only
+    /// differs from [`build`](Self::build) when a table was registered (for
+    /// example with [`add_table`](Self::add_table)) before another table
+    /// received its first operation.
+    #[must_use]
+    pub fn build_sqlite_table_order(&self) -> Vec<u8> {
+        self.build_tables(self.sqlite_table_order(), false)
+    }
 
-impl<
-    T: SchemaWithPK,
-    S: Clone + Debug + Hash + Eq + AsRef<str>,
-    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
-> DiffSetBuilder<ChangesetFormat, T, S, B>
-{
-    /// Build the changeset binary data.
+    /// Build the changeset binary data with the rows of each table sorted by
+    /// primary key instead of in `SQLite`'s hash-table order.
     ///
-    /// Returns the binary representation compatible with `SQLite`'s session extension.
+    /// Key columns are compared in key order with
+    /// [`compare_values`](crate::compare_values). The output holds the same
+    /// operations as [`build`](Self::build) and tables come in the same
+    /// order, but the row order will generally not match `SQLite`'s own
+    /// output. Within a table it depends only on the keys, not on the order
+    /// rows were staged in or on the session extension's hash function, so
+    /// it is stable across `SQLite` versions and can be compared byte for
+    /// byte.
     ///
-    /// # Panics
+    /// # Example
     ///
-    /// This function does not panic under normal usage. Internal indexing is guaranteed
-    /// to be within bounds.
+    /// ```
+    /// use sqlite_diff_rs::{DiffOps, Insert, ChangeSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let sorted = |ids: &[i64]| {
+    ///     let mut builder: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    ///     for &id in ids {
+    ///         builder = builder.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    ///     }
+    ///     builder.build_pk_sorted()
+    /// };
+    /// assert_eq!(sorted(&[3, 1, 2]), sorted(&[1, 2, 3]));
+    /// ```
     #[must_use]
-    pub fn build(&self) -> Vec<u8> {
+    pub fn build_pk_sorted(&self) -> Vec<u8> {
+        self.build_tables(&self.tables, true)
+    }
+
+    /// Encodes the non-empty `tables`, in the order given, with their rows
+    /// in `SQLite`'s order or, if `pk_sorted`, sorted by primary key.
+    fn build_tables<'a>(
+        &'a self,
+        tables: impl IntoIterator<Item = (&'a T, &'a RowMap<ChangesetFormat, S, B>)>,
+        pk_sorted: bool,
+    ) -> Vec<u8> {
         let mut out = Vec::new();
 
-        for (table, rows) in &self.tables {
+        for (table, rows) in tables {
             if rows.is_empty() {
                 continue;
             }
 
             write_table_header(&mut out, markers::CHANGESET, table);
 
-            for idx in session_row_order(rows) {
+            let order = if pk_sorted {
+                Self::pk_sorted_order(rows)
+            } else {
+                self.emission_order(table, rows)
+            };
+            for idx in order {
                 let (_pk, op) = rows.get_index(idx).unwrap();
                 encode_changeset_op(&mut out, op);
             }
@@ -895,25 +2540,9 @@ impl<T: SchemaWithPK, S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u
     /// session-extension hash ordering only applies at [`build`](Self::build)
     /// time.
     pub fn iter(&self) -> impl Iterator<Item = ChangesetOp<'_, T, S, B>> {
-        self.tables.iter().flat_map(|(table, rows)| {
-            rows.iter().map(move |(_pk, op)| match op {
-                Operation::Insert { values, indirect } => ChangesetOp::Insert {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Update { values, indirect } => ChangesetOp::Update {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Delete { data, indirect } => ChangesetOp::Delete {
-                    table,
-                    old_values: data.as_slice(),
-                    indirect: *indirect,
-                },
-            })
-        })
+        self.tables
+            .iter()
+            .flat_map(|(table, rows)| rows.iter().map(move |(_pk, op)| changeset_view(table, op)))
     }
 }
 
@@ -923,6 +2552,9 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     /// Build the patchset binary data.
     ///
     /// Returns the binary representation compatible with `SQLite`'s session extension.
+    /// Tables are emitted in the order they entered the builder, whether by
+    /// registration or by a first operation; see
+    /// [`build_sqlite_table_order`](Self::build_sqlite_table_order).
     ///
     /// # Panics
     ///
@@ -930,9 +2562,113 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
     /// to be within bounds.
     #[must_use]
     pub fn build(&self) -> Vec<u8> {
-        let mut out = Vec::new();
+        self.build_tables(&self.tables, false)
+    }
 
+    /// Like [`build`](Self::build), but first checks that every table name
+    /// can be stored in a table header and every staged UPDATE carries its
+    /// primary key.
+    ///
+    /// [`build`](Self::build) writes a name as is, followed by a NUL byte, so
+    /// a name containing one is cut short. A patchset UPDATE locates its row
+    /// by the key alone, and an [`Update`] staged without setting a key
+    /// column is keyed by NULL there, which [`build`](Self::build) encodes as
+    /// is.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidTableName` - If the name of a table holding operations
+    ///   contains a NUL byte.
+    /// * `MissingPkInPatchUpdate` - If a staged UPDATE has no value for a
+    ///   primary-key column, reporting the first such column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{DiffOps, Error, PatchSet, PatchsetFormat, SimpleTable, Update};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// // UPDATE users SET name = 'Bob', missing `WHERE id = ...`
+    /// let update = Update::<_, PatchsetFormat, String, Vec<u8>>::from(users)
+    ///     .set(1, "Bob")
+    ///     .unwrap();
+    ///
+    /// let patchset = PatchSet::new().update(update);
+    /// assert_eq!(patchset.try_build(), Err(Error::MissingPkInPatchUpdate(0)));
+    /// ```
+    pub fn try_build(&self) -> Result<Vec<u8>, crate::errors::Error> {
+        self.check_table_names()?;
         for (table, rows) in &self.tables {
+            let pk_columns = table.primary_key_columns();
+            for (pk, op) in rows {
+                if !matches!(op, Operation::Update { .. }) {
+                    continue;
+                }
+                if let Some(pos) = pk.iter().position(|value| matches!(value, Value::Null)) {
+                    return Err(crate::errors::Error::MissingPkInPatchUpdate(
+                        pk_columns[pos],
+                    ));
+                }
+            }
+        }
+        Ok(self.build())
+    }
+
+    /// Build the patchset binary data, emitting tables in the order they
+    /// first received an operation rather than the order they were
+    /// registered in.
+    ///
+    /// This is the order `SQLite`'s session extension uses, so it only
+    /// differs from [`build`](Self::build) when a table was registered (for
+    /// example with [`add_table`](Self::add_table)) before another table
+    /// received its first operation.
+    #[must_use]
+    pub fn build_sqlite_table_order(&self) -> Vec<u8> {
+        self.build_tables(self.sqlite_table_order(), false)
+    }
+
+    /// Build the patchset binary data with the rows of each table sorted by
+    /// primary key instead of in `SQLite`'s hash-table order.
+    ///
+    /// Key columns are compared in key order with
+    /// [`compare_values`](crate::compare_values). The output holds the same
+    /// operations as [`build`](Self::build) and tables come in the same
+    /// order, but the row order will generally not match `SQLite`'s own
+    /// output. Within a table it depends only on the keys, not on the order
+    /// rows were staged in or on the session extension's hash function, so
+    /// it is stable across `SQLite` versions and can be compared byte for
+    /// byte.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{DiffOps, Insert, PatchSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let sorted = |ids: &[i64]| {
+    ///     let mut builder: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    ///     for &id in ids {
+    ///         builder = builder.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    ///     }
+    ///     builder.build_pk_sorted()
+    /// };
+    /// assert_eq!(sorted(&[3, 1, 2]), sorted(&[1, 2, 3]));
+    /// ```
+    #[must_use]
+    pub fn build_pk_sorted(&self) -> Vec<u8> {
+        self.build_tables(&self.tables, true)
+    }
+
+    /// Encodes the non-empty `tables`, in the order given, with their rows
+    /// in `SQLite`'s order or, if `pk_sorted`, sorted by primary key.
+    fn build_tables<'a>(
+        &'a self,
+        tables: impl IntoIterator<Item = (&'a T, &'a RowMap<PatchsetFormat, S, B>)>,
+        pk_sorted: bool,
+    ) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        for (table, rows) in tables {
             if rows.is_empty() {
                 continue;
             }
@@ -941,7 +2677,12 @@ impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq +
 
             let (pk_flags, pk_col_to_pk_pos) = patchset_pk_mapping(table);
 
-            for idx in session_row_order(rows) {
+            let order = if pk_sorted {
+                Self::pk_sorted_order(rows)
+            } else {
+                self.emission_order(table, rows)
+            };
+            for idx in order {
                 let (pk, op) = rows.get_index(idx).unwrap();
                 encode_patchset_op(&mut out, op, pk, &pk_flags, &pk_col_to_pk_pos);
             }
@@ -963,24 +2704,8 @@ impl<T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>
     /// via [`RunQueryDsl`](diesel::RunQueryDsl).
     pub fn iter(&self) -> impl Iterator<Item = PatchsetOp<'_, T, S, B>> {
         self.tables.iter().flat_map(|(table, rows)| {
-            rows.iter().map(move |(pk, op)| match op {
-                Operation::Insert { values, indirect } => PatchsetOp::Insert {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Update { values, indirect } => PatchsetOp::Update {
-                    table,
-                    pk: pk.as_slice(),
-                    entries: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Delete { indirect, .. } => PatchsetOp::Delete {
-                    table,
-                    pk: pk.as_slice(),
-                    indirect: *indirect,
-                },
-            })
+            rows.iter()
+                .map(move |(pk, op)| patchset_view(table, pk, op))
         })
     }
 }
@@ -1004,7 +2729,7 @@ impl<
 
         for (table, rows) in self.tables {
             for (pk, op) in rows {
-                let rev_op = op.reverse();
+                let rev_op = op.reverse_keyed(|col| table.primary_key_index(col).is_some());
 
                 reversed.add_operation(&table, pk, rev_op);
             }
@@ -1014,6 +2739,122 @@ impl<
     }
 }
 
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<ChangesetFormat, T, S, B>
+{
+    /// Reverse every operation, as [`reverse`](Reverse::reverse) does, and
+    /// also reverse the order tables are emitted in.
+    ///
+    /// Plain [`reverse`](Reverse::reverse) keeps the table order, so undoing
+    /// a changeset that filled a parent table before its child deletes the
+    /// parents first. The undo built here deletes the children first instead,
+    /// which keeps referential integrity when the undo is applied table by
+    /// table, with foreign keys enforced on each statement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+    ///
+    /// let parents = SimpleTable::new("parents", &["id"], &[0]);
+    /// let children = SimpleTable::new("children", &["id", "parent"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(parents.clone()).set(0, 1i64).unwrap())
+    ///     .insert(Insert::from(children.clone()).set(0, 1i64).unwrap().set(1, 1i64).unwrap());
+    ///
+    /// let undo = changeset.reverse_for_undo();
+    /// assert_eq!(undo.non_empty_tables().collect::<Vec<_>>(), [&children, &parents]);
+    /// ```
+    #[must_use]
+    pub fn reverse_for_undo(mut self) -> Self {
+        self.tables.reverse();
+        self.reverse()
+    }
+}
+
+impl<
+    F: Format<S, B>,
+    T: SchemaWithPK,
+    S: Clone + Hash + Eq + AsRef<str>,
+    B: Clone + Hash + Eq + AsRef<[u8]>,
+> DiffSetBuilder<F, T, S, B>
+where
+    Operation<F, S, B>: Clone + core::ops::Add<Output = Option<Operation<F, S, B>>>,
+{
+    /// Merge a copy of `other`'s operations into this builder, consolidating
+    /// operations on the same row, and leave `other` untouched.
+    ///
+    /// This is the borrowing counterpart of `|=`, for accumulating batches
+    /// that are reused afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let batch: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap().set(1, "Alice").unwrap());
+    ///
+    /// let mut acc = ChangeSet::new();
+    /// acc.extend(&batch);
+    /// assert_eq!(acc, batch);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if a table of `other` has the name of a table in this builder
+    /// but another shape; see [`try_extend`](Self::try_extend).
+    pub fn extend(&mut self, other: &Self) -> &mut Self {
+        self.try_extend(other)
+            .unwrap_or_else(|mismatch| panic!("{mismatch}"))
+    }
+
+    /// Like [`extend`](Self::extend), but checks every table of `other`
+    /// against the table of the same name in this builder first, as
+    /// [`DiffSet::merge`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SchemaMismatch`](crate::SchemaMismatch) naming the
+    /// differing field when the two tables disagree, leaving this builder
+    /// untouched.
+    pub fn try_extend(&mut self, other: &Self) -> Result<&mut Self, crate::SchemaMismatch> {
+        self.check_compatible(other)?;
+        for (table, rows) in &other.tables {
+            for (pk, op) in rows {
+                self.add_operation(table, pk.clone(), op.clone());
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl<F: Format<S, B>, T: SchemaWithPK, S, B> DiffSetBuilder<F, T, S, B> {
+    /// Checks every table of `other` against the table of the same name in
+    /// this builder, if any, by name, column count and primary-key flags.
+    fn check_compatible(&self, other: &Self) -> Result<(), crate::SchemaMismatch> {
+        for table in other.tables.keys() {
+            if let Some(existing) = self.table_named(table.name()) {
+                if existing != table {
+                    shape_of(existing).compatible_with(&shape_of(table))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name, column count and primary-key flags of `table`.
+fn shape_of<T: DynTable>(table: &T) -> TableSchema<String> {
+    let mut pk_flags = vec![0; table.number_of_columns()];
+    table.write_pk_flags(&mut pk_flags);
+    TableSchema::new(table.name().into(), table.number_of_columns(), pk_flags)
+}
+
 // ============================================================================
 // BitOr / BitOrAssign for DiffSetBuilder (changeset/patchset concatenation,
 // equivalent to SQLite's `sqlite3changeset_concat()`)
@@ -1029,7 +2870,15 @@ where
     Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
 {
     /// Merge another diff set into this one, consolidating operations on the same row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a table of `rhs` has the name of a table in this diff set
+    /// but another shape, as [`DiffSetBuilder::extend`] does.
     fn bitor_assign(&mut self, rhs: Self) {
+        if let Err(mismatch) = self.check_compatible(&rhs) {
+            panic!("{mismatch}");
+        }
         for (table, rows) in rhs.tables {
             for (pk, op) in rows {
                 self.add_operation(&table, pk, op);
@@ -1050,6 +2899,11 @@ where
     type Output = Self;
 
     /// Merge two diff sets, consolidating operations on the same row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two diff sets hold tables of the same name but of
+    /// different shapes, as [`DiffSetBuilder::extend`] does.
     #[inline]
     fn bitor(mut self, rhs: Self) -> Self::Output {
         self |= rhs;
@@ -1073,7 +2927,8 @@ where
 /// row order of parsed binary data across roundtrips.
 ///
 /// To modify a `DiffSet`, convert it back to a [`DiffSetBuilder`] using
-/// `Into::into`.
+/// `Into::into`, or [`into_builder`](Self::into_builder) to keep a parsed
+/// row order byte-for-byte.
 #[derive(Debug, Clone)]
 pub struct DiffSet<F: Format<S, B>, T: SchemaWithPK, S, B> {
     /// Tables and their rows, stored in order. Each row is a `(pk, operation)` pair.
@@ -1106,6 +2961,54 @@ where
 {
 }
 
+/// Compares a frozen diffset with a builder without serializing either, by
+/// the rules of `DiffSetBuilder`'s `PartialEq`: tables with no operations
+/// are ignored, the remaining tables must match in order, and rows within a
+/// table compare as a map, whatever order they are stored in.
+///
+/// The schema types may differ, as between a parsed diffset and the builder
+/// it came from, so tables match when their serialized headers do: the same
+/// name, column count and primary-key flags.
+impl<F: Format<S, B>, T: SchemaWithPK, U: SchemaWithPK, S, B> PartialEq<DiffSetBuilder<F, U, S, B>>
+    for DiffSet<F, T, S, B>
+where
+    S: PartialEq + Eq + Hash + AsRef<str>,
+    B: PartialEq + Eq + Hash + AsRef<[u8]>,
+    F::Old: PartialEq,
+    F::DeleteData: PartialEq,
+{
+    fn eq(&self, other: &DiffSetBuilder<F, U, S, B>) -> bool {
+        let mut ours = self.tables.iter().filter(|(_, rows)| !rows.is_empty());
+        let mut theirs = other.tables.iter().filter(|(_, rows)| !rows.is_empty());
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return true,
+                (Some((table, rows)), Some((other_table, other_rows))) => {
+                    if !same_table_header(table, other_table)
+                        || rows.len() != other_rows.len()
+                        || !rows.iter().all(|(pk, op)| other_rows.get(pk) == Some(op))
+                    {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` serialize to the same table header.
+fn same_table_header<T: SchemaWithPK, U: SchemaWithPK>(a: &T, b: &U) -> bool {
+    if a.name() != b.name() || a.number_of_columns() != b.number_of_columns() {
+        return false;
+    }
+    let mut a_flags = alloc::vec![0u8; a.number_of_columns()];
+    let mut b_flags = alloc::vec![0u8; b.number_of_columns()];
+    a.write_pk_flags(&mut a_flags);
+    b.write_pk_flags(&mut b_flags);
+    a_flags == b_flags
+}
+
 impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]> + Hash + Eq>
     Default for DiffSet<F, T, S, B>
 {
@@ -1147,6 +3050,68 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]>
     }
 }
 
+impl<
+    F: Format<S, B>,
+    T: SchemaWithPK,
+    S: AsRef<str> + Clone + Hash + Eq,
+    B: AsRef<[u8]> + Clone + Hash + Eq,
+> DiffSet<F, T, S, B>
+{
+    /// Returns the operations on the table named `table` whose value in
+    /// column `pk_col` lies in the half-open range `[lo, hi)`, in stored
+    /// order, e.g. to hand one shard of a changeset to each worker.
+    ///
+    /// Values are ordered by [`compare_values`]. `pk_col` is a column index
+    /// and must be part of the primary key, otherwise nothing is selected.
+    /// An UPDATE that moves its row's key is selected by its old key.
+    ///
+    /// [`compare_values`]: crate::compare_values
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// for id in 0..10i64 {
+    ///     changeset = changeset.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    /// }
+    /// let diffset: sqlite_diff_rs::DiffSet<_, _, _, _> = changeset.into();
+    ///
+    /// let shard = diffset.slice_by_pk_range("users", 0, &Value::Integer(3), &Value::Integer(6));
+    /// assert_eq!(shard.len(), 3);
+    /// ```
+    #[must_use]
+    pub fn slice_by_pk_range(
+        &self,
+        table: &str,
+        pk_col: usize,
+        lo: &Value<S, B>,
+        hi: &Value<S, B>,
+    ) -> Self {
+        let tables = self
+            .tables
+            .iter()
+            .filter(|(schema, _)| schema.name() == table)
+            .filter_map(|(schema, rows)| {
+                let position = schema.primary_key_index(pk_col)?;
+                let rows: RowVec<F, S, B> = rows
+                    .iter()
+                    .filter(|(pk, _)| {
+                        pk.get(position).is_some_and(|value| {
+                            compare_values(value, lo).is_ge() && compare_values(value, hi).is_lt()
+                        })
+                    })
+                    .cloned()
+                    .collect();
+                Some((schema.clone(), rows))
+            })
+            .collect();
+        Self { tables }
+    }
+}
+
 impl<T: SchemaWithPK, S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>>
     DiffSet<ChangesetFormat, T, S, B>
 {
@@ -1156,25 +3121,62 @@ impl<T: SchemaWithPK, S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u
     /// [`ChangesetOp`] borrows from this `DiffSet`, so the returned
     /// iterator is invalidated when the `DiffSet` is dropped or mutated.
     pub fn iter(&self) -> impl Iterator<Item = ChangesetOp<'_, T, S, B>> {
-        self.tables.iter().flat_map(|(table, rows)| {
-            rows.iter().map(move |(_pk, op)| match op {
-                Operation::Insert { values, indirect } => ChangesetOp::Insert {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Update { values, indirect } => ChangesetOp::Update {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Delete { data, indirect } => ChangesetOp::Delete {
-                    table,
-                    old_values: data.as_slice(),
-                    indirect: *indirect,
-                },
+        self.tables
+            .iter()
+            .flat_map(|(table, rows)| rows.iter().map(move |(_pk, op)| changeset_view(table, op)))
+    }
+
+    /// Returns the operation on the row keyed on `pk` in the table named
+    /// `table`, or `None` if the changeset does not touch that row.
+    ///
+    /// `pk` holds the primary-key values in key order, as
+    /// [`SchemaWithPK::extract_pk`] returns them.
+    pub fn get(&self, table: &str, pk: &[Value<S, B>]) -> Option<ChangesetOp<'_, T, S, B>>
+    where
+        S: PartialEq,
+        B: PartialEq,
+    {
+        find_row(&self.tables, table, pk).map(|(schema, _, op)| changeset_view(schema, op))
+    }
+
+    /// Returns, per table in stored order, the primary keys of every row the
+    /// changeset inserts, updates, or deletes.
+    ///
+    /// An UPDATE that moves a row to a new key lists both the old and the new
+    /// key, so callers invalidating caches by key miss neither.
+    #[must_use]
+    pub fn affected_keys(&self) -> AffectedKeys<'_, T, S, B>
+    where
+        S: PartialEq,
+        B: PartialEq,
+    {
+        self.tables
+            .iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(table, rows)| {
+                let mut keys = Vec::with_capacity(rows.len());
+                for (pk, op) in rows {
+                    keys.push(pk.clone());
+                    if let Operation::Update { values, .. } = op {
+                        let new_pk: Vec<Value<S, B>> = table
+                            .primary_key_columns()
+                            .into_iter()
+                            .map(|col| {
+                                let (old, new) = &values[col];
+                                new.as_ref()
+                                    .or(old.as_ref())
+                                    .cloned()
+                                    .unwrap_or(Value::Null)
+                            })
+                            .collect();
+                        if new_pk != *pk {
+                            keys.push(new_pk);
+                        }
+                    }
+                }
+                (table, keys)
             })
-        })
+            .collect()
     }
 }
 
@@ -1189,26 +3191,106 @@ impl<T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>
     /// does not carry full old-row values).
     pub fn iter(&self) -> impl Iterator<Item = PatchsetOp<'_, T, S, B>> {
         self.tables.iter().flat_map(|(table, rows)| {
-            rows.iter().map(move |(pk, op)| match op {
-                Operation::Insert { values, indirect } => PatchsetOp::Insert {
-                    table,
-                    values: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Update { values, indirect } => PatchsetOp::Update {
-                    table,
-                    pk: pk.as_slice(),
-                    entries: values.as_slice(),
-                    indirect: *indirect,
-                },
-                Operation::Delete { indirect, .. } => PatchsetOp::Delete {
-                    table,
-                    pk: pk.as_slice(),
-                    indirect: *indirect,
-                },
-            })
+            rows.iter()
+                .map(move |(pk, op)| patchset_view(table, pk, op))
         })
     }
+
+    /// Returns the operation on the row keyed on `pk` in the table named
+    /// `table`, or `None` if the patchset does not touch that row.
+    ///
+    /// `pk` holds the primary-key values in key order, as
+    /// [`SchemaWithPK::extract_pk`] returns them.
+    pub fn get(&self, table: &str, pk: &[Value<S, B>]) -> Option<PatchsetOp<'_, T, S, B>>
+    where
+        S: PartialEq,
+        B: PartialEq,
+    {
+        find_row(&self.tables, table, pk)
+            .map(|(schema, row_pk, op)| patchset_view(schema, row_pk, op))
+    }
+
+    /// Returns, per table in stored order, the primary keys of every row the
+    /// patchset inserts, updates, or deletes.
+    #[must_use]
+    pub fn affected_keys(&self) -> AffectedKeys<'_, T, S, B> {
+        self.tables
+            .iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(table, rows)| (table, rows.iter().map(|(pk, _)| pk.clone()).collect()))
+            .collect()
+    }
+}
+
+/// Views a stored changeset operation on `table`.
+fn changeset_view<'a, T, S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>>(
+    table: &'a T,
+    op: &'a Operation<ChangesetFormat, S, B>,
+) -> ChangesetOp<'a, T, S, B> {
+    match op {
+        Operation::Insert { values, indirect } => ChangesetOp::Insert {
+            table,
+            values: values.as_slice(),
+            indirect: *indirect,
+        },
+        Operation::Update { values, indirect } => ChangesetOp::Update {
+            table,
+            values: values.as_slice(),
+            indirect: *indirect,
+        },
+        Operation::Delete { data, indirect } => ChangesetOp::Delete {
+            table,
+            old_values: data.as_slice(),
+            indirect: *indirect,
+        },
+    }
+}
+
+/// Views a stored patchset operation on `table`, keyed on `pk`.
+fn patchset_view<'a, T, S, B>(
+    table: &'a T,
+    pk: &'a [Value<S, B>],
+    op: &'a Operation<PatchsetFormat, S, B>,
+) -> PatchsetOp<'a, T, S, B> {
+    match op {
+        Operation::Insert { values, indirect } => PatchsetOp::Insert {
+            table,
+            values: values.as_slice(),
+            indirect: *indirect,
+        },
+        Operation::Update { values, indirect } => PatchsetOp::Update {
+            table,
+            pk,
+            entries: values.as_slice(),
+            indirect: *indirect,
+        },
+        Operation::Delete { indirect, .. } => PatchsetOp::Delete {
+            table,
+            pk,
+            indirect: *indirect,
+        },
+    }
+}
+
+/// A row found by [`find_row`]: its table, primary key and operation.
+type FoundRow<'a, F, T, S, B> = (&'a T, &'a [Value<S, B>], &'a Operation<F, S, B>);
+
+/// Finds the row keyed on `pk` in the table named `table`.
+fn find_row<
+    'a,
+    F: Format<S, B>,
+    T: SchemaWithPK,
+    S: PartialEq + AsRef<str>,
+    B: PartialEq + AsRef<[u8]>,
+>(
+    tables: &'a TableVec<F, T, S, B>,
+    table: &str,
+    pk: &[Value<S, B>],
+) -> Option<FoundRow<'a, F, T, S, B>> {
+    let (schema, rows) = tables.iter().find(|(schema, _)| schema.name() == table)?;
+    rows.iter()
+        .find(|(row_pk, _)| row_pk.as_slice() == pk)
+        .map(|(row_pk, op)| (schema, row_pk.as_slice(), op))
 }
 
 // -- Changeset build (DiffSet) ------------------------------------------------
@@ -1334,7 +3416,10 @@ impl<
                 .map(|(table, rows)| {
                     let rev_rows = rows
                         .into_iter()
-                        .map(|(pk, op)| (pk, op.reverse()))
+                        .map(|(pk, op)| {
+                            let is_pk = |col| table.primary_key_index(col).is_some();
+                            (pk, op.reverse_keyed(is_pk))
+                        })
                         .collect();
                     (table, rev_rows)
                 })
@@ -1362,6 +3447,59 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: Hash + Eq + AsRef<str>, B: Hash + Eq +
     }
 }
 
+impl<F: Format<S, B>, T: SchemaWithPK, S: Hash + Eq + AsRef<str>, B: Hash + Eq + AsRef<[u8]>>
+    DiffSet<F, T, S, B>
+{
+    /// Converts into a builder whose [`build`](DiffSetBuilder::build) emits
+    /// the rows in their stored order.
+    ///
+    /// Use this on a parsed `DiffSet` to edit it and re-serialize it: the
+    /// builder records the rows in an order `SQLite`'s session hash table
+    /// walks back into the stored order, so an unedited builder reproduces
+    /// the parsed bytes. Rows added afterwards land where the session would
+    /// emit them. Stored orders no session can emit, e.g. rows of one table
+    /// not grouped by hash bucket, come out in session order instead.
+    ///
+    /// [`From`] keeps the stored order as the recording order, which is what
+    /// a `DiffSet` converted from a builder holds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// for id in 0..300i64 {
+    ///     changeset = changeset.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    /// }
+    /// let bytes = changeset.build();
+    ///
+    /// let ParsedDiffSet::Changeset(parsed) = ParsedDiffSet::parse(&bytes).unwrap() else {
+    ///     unreachable!();
+    /// };
+    /// assert_eq!(parsed.into_builder().build(), bytes);
+    /// ```
+    #[must_use]
+    pub fn into_builder(self) -> DiffSetBuilder<F, T, S, B> {
+        let mut builder = DiffSetBuilder::new();
+        for (table, rows) in self.tables {
+            let order =
+                session_recording_order(&table, rows.iter().map(|(pk, op)| (pk, op.is_insert())));
+            let mut rows: Vec<Option<_>> = rows.into_iter().map(Some).collect();
+            let map: RowMap<F, S, B> = order
+                .into_iter()
+                .filter_map(|idx| rows[idx].take())
+                .collect();
+            builder
+                .touched
+                .insert(table.clone(), KeyClasses::of(map.keys()));
+            builder.tables.insert(table, map);
+        }
+        builder
+    }
+}
+
 impl<F: Format<S, B>, T: SchemaWithPK, S: Hash + Eq + AsRef<str>, B: Hash + Eq + AsRef<[u8]>>
     From<DiffSet<F, T, S, B>> for DiffSetBuilder<F, T, S, B>
 {
@@ -1369,6 +3507,9 @@ impl<F: Format<S, B>, T: SchemaWithPK, S: Hash + Eq + AsRef<str>, B: Hash + Eq +
         let mut builder = Self::new();
         for (table, rows) in diffset.tables {
             let map: IndexMap<Vec<Value<S, B>>, Operation<F, S, B>> = rows.into_iter().collect();
+            builder
+                .touched
+                .insert(table.clone(), KeyClasses::of(map.keys()));
             builder.tables.insert(table, map);
         }
         builder
@@ -1957,7 +4098,41 @@ mod tests {
         let mut cs = ChangesetBuilder::new().insert(insert1);
         cs |= ChangesetBuilder::new().insert(insert2);
 
-        assert_eq!(cs.len(), 2);
+        assert_eq!(cs.len(), 2);
+    }
+
+    #[test]
+    fn test_extend_from_reused_batches_matches_bitor() {
+        let table = TestTable::new("users", 2, 0);
+        let insert = |id: i64, name: &str| {
+            Insert::from(table.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, name)
+                .unwrap()
+        };
+
+        let first = ChangesetBuilder::new()
+            .insert(insert(1, "alice"))
+            .insert(insert(2, "bob"));
+        let second = ChangesetBuilder::new()
+            .delete(
+                ChangeDelete::from(table.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "alice")
+                    .unwrap(),
+            )
+            .insert(insert(3, "carol"));
+
+        let mut acc = ChangesetBuilder::new();
+        acc.extend(&first).extend(&second);
+
+        assert_eq!(acc, first.clone() | second.clone());
+        assert_eq!(acc.build(), (first.clone() | second.clone()).build());
+        // Both batches are left intact.
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 2);
     }
 
     #[test]
@@ -2191,6 +4366,77 @@ mod tests {
         assert_eq!(bytes[INDIRECT_BYTE_OFFSET], 1);
     }
 
+    fn hash_of<V: Hash>(value: &V) -> u64 {
+        let mut state = RowHasher::default();
+        value.hash(&mut state);
+        state.finish()
+    }
+
+    #[test]
+    fn test_hash_ignores_row_order_like_eq() {
+        let table = TestTable::new("t", 2, 0);
+        let first = Insert::from(table.clone())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "a")
+            .unwrap();
+        let second = Insert::from(table.clone())
+            .set(0, 2i64)
+            .unwrap()
+            .set(1, "b")
+            .unwrap();
+        let a = ChangesetBuilder::new()
+            .insert(first.clone())
+            .insert(second.clone());
+        let b = ChangesetBuilder::new().insert(second).insert(first);
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn test_hash_ignores_empty_tables_like_eq() {
+        let empty = TestTable::new("empty", 2, 0);
+        let table = TestTable::new("t", 2, 0);
+        let insert = Insert::from(table)
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "a")
+            .unwrap();
+        let mut with_empty: PatchSet<TestTable, String, Vec<u8>> = PatchSet::new();
+        with_empty.add_table(&empty);
+        let with_empty = with_empty.insert(insert.clone());
+        let without = PatchSet::new().insert(insert);
+        assert_eq!(with_empty, without);
+        assert_eq!(hash_of(&with_empty), hash_of(&without));
+        assert_eq!(
+            hash_of(&PatchSet::<TestTable, String, Vec<u8>>::new()),
+            hash_of(&{
+                let mut only_empty = PatchSet::<TestTable, String, Vec<u8>>::new();
+                only_empty.add_table(&empty);
+                only_empty
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash_distinguishes_different_content() {
+        let table = TestTable::new("t", 2, 0);
+        let insert = |name: &str| {
+            Insert::from(table.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, name)
+                .unwrap()
+        };
+        let a = ChangesetBuilder::new().insert(insert("a"));
+        let b = ChangesetBuilder::new().insert(insert("b"));
+        let indirect = ChangesetBuilder::new().insert(insert("a").indirect(true));
+        assert_ne!(a, b);
+        assert_ne!(hash_of(&a), hash_of(&b));
+        assert_ne!(a, indirect);
+        assert_ne!(hash_of(&a), hash_of(&indirect));
+    }
+
     #[test]
     fn test_operation_eq_indirect_differs() {
         // Two ops with identical payload but different indirect flags must not be equal.
@@ -2536,6 +4782,15 @@ mod tests {
         assert!(!bytes.is_empty());
     }
 
+    #[test]
+    fn test_session_hash_empty_text_and_blob_pk_differ() {
+        let text: Value<String, Vec<u8>> = Value::Text(String::new());
+        let blob: Value<String, Vec<u8>> = Value::Blob(Vec::new());
+        // Nothing follows the type code, so each key hashes as its code alone.
+        assert_eq!(session_hash_pk([&text], true), hash_append(0, 3));
+        assert_eq!(session_hash_pk([&blob], true), hash_append(0, 4));
+    }
+
     // ========================================================================
     // session_row_order empty-rows short-circuit
     // ========================================================================
@@ -2552,7 +4807,7 @@ mod tests {
     fn test_session_row_order_empty_rows_returns_empty_vec() {
         // Direct exercise of the empty-rows short-circuit (line 157-158).
         let rows: RowMap<ChangesetFormat, String, Vec<u8>> = IndexMap::default();
-        assert!(session_row_order(&rows).is_empty());
+        assert!(session_row_order(&TestTable::new("t", 2, 0), &rows, |_, _| true).is_empty());
     }
 
     #[test]
@@ -2839,4 +5094,778 @@ mod tests {
         let names: Vec<&str> = frozen.tables().map(crate::DynTable::name).collect();
         assert_eq!(names, ["t1"]);
     }
+
+    #[test]
+    fn test_presized_builder_output_unchanged() {
+        let users = TestTable::new("users", 2, 0);
+        let posts = TestTable::new("posts", 2, 0);
+        let fill = |mut builder: ChangesetBuilder| {
+            for id in 0..50i64 {
+                for table in [&users, &posts] {
+                    builder = builder.insert(
+                        Insert::from(table.clone())
+                            .set(0, id)
+                            .unwrap()
+                            .set(1, "row")
+                            .unwrap(),
+                    );
+                }
+            }
+            builder
+        };
+
+        let mut presized = ChangesetBuilder::with_capacity(2);
+        assert!(presized.entry_logs.capacity() >= 2);
+        assert!(presized.touched.capacity() >= 2);
+        presized.reserve(&users, 50).reserve(&posts, 50);
+        let presized = fill(presized);
+        let default = fill(ChangesetBuilder::new());
+
+        assert_eq!(presized.len(), 100);
+        assert_eq!(presized, default);
+        assert_eq!(presized.build(), default.build());
+    }
+
+    #[test]
+    fn test_patchset_pk_change_splits_into_delete_and_insert() {
+        let users = TestTable::new("users", 2, 0);
+        let old: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "Alice".into()];
+        let new: Vec<Value<String, Vec<u8>>> = vec![2i64.into(), "Alice".into()];
+
+        let builder: PatchSet<TestTable, String, Vec<u8>> =
+            PatchSet::new().update_rows(&users, &old, &new);
+        let expected: PatchSet<TestTable, String, Vec<u8>> = PatchSet::new()
+            .delete(PatchDelete::new(users.clone(), vec![1i64.into()]))
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 2i64)
+                    .unwrap()
+                    .set(1, "Alice")
+                    .unwrap(),
+            );
+
+        assert_eq!(builder.len(), 2);
+        assert_eq!(builder.build(), expected.build());
+    }
+
+    #[test]
+    fn test_patchset_update_rows_same_pk_is_update() {
+        let users = TestTable::new("users", 2, 0);
+        let old: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "Alice".into()];
+        let new: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "Alicia".into()];
+
+        let builder: PatchSet<TestTable, String, Vec<u8>> =
+            PatchSet::new().update_rows(&users, &old, &new);
+        assert_eq!(builder.len(), 1);
+        assert!(matches!(
+            builder.iter().next(),
+            Some(PatchsetOp::Update { .. })
+        ));
+        assert!(PatchSet::new().update_rows(&users, &old, &old).is_empty());
+    }
+
+    /// Builds a column name to value map from `(name, value)` pairs.
+    fn named(
+        pairs: &[(&str, Value<String, Vec<u8>>)],
+    ) -> hashbrown::HashMap<String, Value<String, Vec<u8>>> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).into(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_changeset_map_rows_match_indexed_builder() {
+        let users = crate::SimpleTable::new("users", &["id", "name", "age"], &[0]);
+        let by_name: ChangeSet<crate::SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert_map(
+                &users,
+                &named(&[("id", 1i64.into()), ("name", "Alice".into())]),
+            )
+            .unwrap()
+            .update_map(
+                &users,
+                &named(&[("id", 2i64.into()), ("age", 30i64.into())]),
+                &named(&[("age", 31i64.into())]),
+            )
+            .unwrap()
+            .delete_map(
+                &users,
+                &named(&[("id", 3i64.into()), ("name", "Carol".into())]),
+            )
+            .unwrap();
+
+        let mut update = Update::<_, ChangesetFormat, String, Vec<u8>>::from(users.clone())
+            .set(2, 30i64, 31i64)
+            .unwrap();
+        update.values[0].0 = Some(2i64.into());
+        let by_index: ChangeSet<crate::SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "Alice")
+                    .unwrap(),
+            )
+            .update(update)
+            .delete(
+                ChangeDelete::from(users)
+                    .set(0, 3i64)
+                    .unwrap()
+                    .set(1, "Carol")
+                    .unwrap(),
+            );
+
+        assert_eq!(by_name.build(), by_index.build());
+    }
+
+    #[test]
+    fn test_patchset_update_map_leaves_unnamed_columns_undefined() {
+        let users = crate::SimpleTable::new("users", &["id", "name", "age"], &[0]);
+        let by_name: PatchSet<crate::SimpleTable, String, Vec<u8>> = PatchSet::new()
+            .update_map(
+                &users,
+                &named(&[("id", 1i64.into()), ("age", 31i64.into())]),
+            )
+            .unwrap()
+            .delete_map(
+                &users,
+                &named(&[("id", 2i64.into()), ("name", "Bob".into())]),
+            )
+            .unwrap();
+        let by_index: PatchSet<crate::SimpleTable, String, Vec<u8>> = PatchSet::new()
+            .update(
+                Update::<_, PatchsetFormat, String, Vec<u8>>::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(2, 31i64)
+                    .unwrap(),
+            )
+            .delete(PatchDelete::new(users, vec![2i64.into()]));
+
+        assert_eq!(by_name.build(), by_index.build());
+    }
+
+    #[test]
+    fn test_map_rows_reject_unknown_column() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let row = named(&[("id", 1i64.into()), ("email", "a@example.com".into())]);
+        let expected = crate::errors::Error::ColumnNotFound("email".into());
+
+        assert_eq!(
+            ChangeSet::<_, String, Vec<u8>>::new()
+                .insert_map(&users, &row)
+                .unwrap_err(),
+            expected
+        );
+        assert_eq!(
+            PatchSet::<_, String, Vec<u8>>::new()
+                .update_map(&users, &row)
+                .unwrap_err(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_map_rows_reject_missing_pk_column() {
+        // CREATE TABLE t (a, b, c, PRIMARY KEY (a, b))
+        let table = crate::SimpleTable::new("t", &["a", "b", "c"], &[0, 1]);
+        let row = named(&[("a", 1i64.into()), ("c", "x".into())]);
+        let expected = crate::errors::Error::MissingPkColumn(1);
+
+        let changeset = || ChangeSet::<_, String, Vec<u8>>::new();
+        assert_eq!(changeset().insert_map(&table, &row).unwrap_err(), expected);
+        assert_eq!(changeset().delete_map(&table, &row).unwrap_err(), expected);
+        assert_eq!(
+            changeset()
+                .update_map(&table, &row, &named(&[("b", 2i64.into())]))
+                .unwrap_err(),
+            expected
+        );
+
+        let patchset = || PatchSet::<_, String, Vec<u8>>::new();
+        assert_eq!(patchset().insert_map(&table, &row).unwrap_err(), expected);
+        assert_eq!(patchset().delete_map(&table, &row).unwrap_err(), expected);
+        assert_eq!(patchset().update_map(&table, &row).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn test_null_pk_hash_collision_keeps_rows_distinct() {
+        // CREATE TABLE t (a, b, c, PRIMARY KEY (a, b))
+        let table = crate::SimpleTable::new("t", &["a", "b", "c"], &[0, 1]);
+        let left: [Value<String, Vec<u8>>; 3] = [Value::Null, 1i64.into(), "left".into()];
+        let right: [Value<String, Vec<u8>>; 3] = [1i64.into(), Value::Null, "right".into()];
+        assert_eq!(
+            session_hash_pk(&left[..2], true),
+            session_hash_pk(&right[..2], true),
+            "NULL key values add nothing to the hash"
+        );
+
+        let builder = ChangeSet::new()
+            .insert(Insert::from_values(table.clone(), left.to_vec()))
+            .insert(Insert::from_values(table.clone(), right.to_vec()));
+        assert_eq!(builder.len(), 2);
+
+        let bytes = builder.build();
+        let parsed = crate::parser::ParsedDiffSet::try_from(bytes.as_slice()).unwrap();
+        for row in [left, right] {
+            let Some(crate::ParsedOp::Changeset(ChangesetOp::Insert { values, .. })) =
+                parsed.find("t", &row[..2])
+            else {
+                panic!("row {row:?} missing from the changeset");
+            };
+            assert_eq!(values, row);
+        }
+    }
+
+    #[test]
+    fn test_undefined_old_pk_is_keyed_as_null() {
+        // CREATE TABLE t (id PRIMARY KEY, v)
+        let table = crate::SimpleTable::new("t", &["id", "v"], &[0]);
+        let undefined_pk = Update::<_, ChangesetFormat, String, Vec<u8>>::from(table.clone())
+            .set(1, "a", "b")
+            .unwrap();
+        let null_pk = Update::<_, ChangesetFormat, String, Vec<u8>>::from(table.clone())
+            .set_null(0)
+            .unwrap()
+            .set(1, "b", "c")
+            .unwrap();
+        let other = Update::<_, ChangesetFormat, String, Vec<u8>>::from(table)
+            .set(0, 1i64, 1i64)
+            .unwrap()
+            .set(1, "x", "y")
+            .unwrap();
+
+        let builder = ChangeSet::new()
+            .update(undefined_pk)
+            .update(other)
+            .update(null_pk);
+        assert_eq!(builder.len(), 2, "the two NULL-keyed updates consolidate");
+        let Some(ChangesetOp::Update { values, .. }) = builder.iter().next() else {
+            panic!("NULL-keyed update missing");
+        };
+        assert_eq!(values[0], (Some(Value::Null), None));
+        assert_eq!(values[1], (Some("a".into()), Some("c".into())));
+
+        let bytes = builder.build();
+        let parsed = crate::parser::ParsedDiffSet::try_from(bytes.as_slice()).unwrap();
+        let reparsed: Vec<u8> = parsed.into();
+        assert_eq!(reparsed, bytes);
+    }
+
+    #[test]
+    fn test_affected_keys_lists_every_touched_row() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let posts = crate::SimpleTable::new("posts", &["id", "title"], &[0]);
+        let changeset: ChangeSet<_, String, Vec<u8>> = ChangeSet::new()
+            .insert(Insert::from_values(
+                users.clone(),
+                vec![1i64.into(), "alice".into()],
+            ))
+            .delete(ChangeDelete::from_values(
+                users.clone(),
+                vec![2i64.into(), "bob".into()],
+            ))
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(users.clone())
+                    .set(0, 3i64, 4i64)
+                    .unwrap(),
+            )
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(posts.clone())
+                    .set(0, 7i64, 7i64)
+                    .unwrap()
+                    .set(1, "old", "new")
+                    .unwrap(),
+            );
+        let keys = |n: &[i64]| -> Vec<Vec<Value<String, Vec<u8>>>> {
+            n.iter().map(|&i| vec![Value::Integer(i)]).collect()
+        };
+        let expected = vec![(&users, keys(&[1, 2, 3, 4])), (&posts, keys(&[7]))];
+
+        let frozen: DiffSet<ChangesetFormat, _, String, Vec<u8>> = changeset.into();
+        assert_eq!(frozen.affected_keys(), expected);
+
+        let patchset: DiffSet<PatchsetFormat, _, String, Vec<u8>> = PatchSet::new()
+            .insert(Insert::from_values(
+                users.clone(),
+                vec![1i64.into(), "alice".into()],
+            ))
+            .delete(PatchDelete::new(users.clone(), vec![2i64.into()]))
+            .into();
+        assert_eq!(patchset.affected_keys(), vec![(&users, keys(&[1, 2]))]);
+    }
+
+    #[test]
+    fn test_try_insert_reports_absent_and_present_rows() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let row = |name: &str| {
+            Insert::from(users.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, name)
+                .unwrap()
+        };
+
+        let mut changeset: ChangeSet<_, String, Vec<u8>> = ChangeSet::new();
+        assert!(changeset.try_insert(row("alice")), "absent row is staged");
+        assert!(
+            !changeset.try_insert(row("alice")),
+            "identical row is a no-op"
+        );
+        assert!(
+            !changeset.try_insert(row("alicia")),
+            "a second INSERT on a staged row is dropped"
+        );
+        assert_eq!(
+            changeset.build(),
+            ChangeSet::new().insert(row("alice")).build()
+        );
+
+        let mut patchset: PatchSet<_, String, Vec<u8>> = PatchSet::new();
+        assert!(patchset.try_insert(row("alice")));
+        assert!(!patchset.try_insert(row("alice")));
+        assert_eq!(
+            patchset.build(),
+            PatchSet::new().insert(row("alice")).build()
+        );
+    }
+
+    #[test]
+    fn test_try_update_reports_different_and_same_values() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let rename = |old: &str, new: &str| {
+            Update::<_, ChangesetFormat, String, Vec<u8>>::from(users.clone())
+                .set(0, 1i64, 1i64)
+                .unwrap()
+                .set(1, old, new)
+                .unwrap()
+        };
+
+        let mut changeset = ChangeSet::new();
+        assert!(
+            changeset.try_update(rename("a", "b")),
+            "absent row is staged"
+        );
+        assert!(
+            !changeset.try_update(rename("b", "b")),
+            "same new value is a no-op"
+        );
+        assert!(
+            changeset.try_update(rename("b", "c")),
+            "different value is merged"
+        );
+        let expected = ChangeSet::new()
+            .update(rename("a", "b"))
+            .update(rename("b", "c"))
+            .build();
+        assert_eq!(changeset.build(), expected);
+
+        let mut patchset: PatchSet<_, String, Vec<u8>> = PatchSet::new();
+        assert!(
+            patchset.try_insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "a")
+                    .unwrap()
+            )
+        );
+        let set_name = |name: &str| {
+            Update::<_, PatchsetFormat, String, Vec<u8>>::from(users.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, name)
+                .unwrap()
+        };
+        assert!(
+            !patchset.try_update(set_name("a")),
+            "staged INSERT already holds it"
+        );
+        assert!(patchset.try_update(set_name("b")));
+        assert_eq!(patchset.len(), 1);
+    }
+
+    #[test]
+    fn test_try_build_rejects_patch_update_without_pk() {
+        // CREATE TABLE t (a, b, c, PRIMARY KEY (a, b))
+        let table = crate::SimpleTable::new("t", &["a", "b", "c"], &[0, 1]);
+        let update = |pk: &[(usize, i64)]| {
+            let mut update =
+                Update::<_, PatchsetFormat, String, Vec<u8>>::from(table.clone()).set(2, "x");
+            for &(col, value) in pk {
+                update = update.and_then(|update| update.set(col, value));
+            }
+            update.unwrap()
+        };
+
+        let complete = PatchSet::new().update(update(&[(0, 1), (1, 2)]));
+        assert_eq!(complete.try_build(), Ok(complete.build()));
+
+        let missing_b = PatchSet::new()
+            .insert(Insert::from_values(
+                table.clone(),
+                vec![1i64.into(), 1i64.into(), "y".into()],
+            ))
+            .update(update(&[(0, 1)]));
+        assert_eq!(
+            missing_b.try_build(),
+            Err(crate::errors::Error::MissingPkInPatchUpdate(1))
+        );
+    }
+
+    #[test]
+    fn test_split_by_table_concatenates_to_build() {
+        let tables = [
+            crate::SimpleTable::new("users", &["id", "name"], &[0]),
+            crate::SimpleTable::new("posts", &["id", "title"], &[0]),
+            crate::SimpleTable::new("tags", &["id", "label"], &[0]),
+        ];
+        let mut builder: ChangeSet<crate::SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        // Interleave the tables, and cancel some rows so one table's order
+        // depends on its entry log.
+        for id in 0..40i64 {
+            for table in &tables {
+                builder = builder.insert(
+                    Insert::from(table.clone())
+                        .set(0, id)
+                        .unwrap()
+                        .set(1, "x")
+                        .unwrap(),
+                );
+            }
+        }
+        for id in (0..40i64).step_by(3) {
+            builder = builder.delete(
+                ChangeDelete::from(tables[1].clone())
+                    .set(0, id)
+                    .unwrap()
+                    .set(1, "x")
+                    .unwrap(),
+            );
+        }
+
+        let bytes = builder.build();
+        let parts = builder.split_by_table();
+        assert_eq!(parts.len(), 3);
+        for (part, table) in parts.iter().zip(&tables) {
+            let parsed = crate::ParsedDiffSet::parse(&part.build()).unwrap();
+            let crate::ParsedDiffSet::Changeset(parsed) = parsed else {
+                panic!("expected a changeset");
+            };
+            assert_eq!(
+                parsed
+                    .tables()
+                    .map(|schema| schema.name().clone())
+                    .collect::<Vec<_>>(),
+                [crate::DynTable::name(table)]
+            );
+        }
+        let rebuilt: Vec<u8> = parts.iter().flat_map(ChangeSet::build).collect();
+        assert_eq!(rebuilt, bytes);
+    }
+
+    #[test]
+    fn test_final_rows_collapses_operations_into_end_state() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let row = |id: i64, name: &str| vec![Value::Integer(id), Value::Text(String::from(name))];
+        let key = |id: i64| (String::from("users"), vec![Value::Integer(id)]);
+
+        let changeset: ChangeSet<_, String, Vec<u8>> = ChangeSet::new()
+            .insert(Insert::from_values(users.clone(), row(1, "alice")))
+            .update(
+                Update::<_, ChangesetFormat, String, Vec<u8>>::from(users.clone())
+                    .set(0, 1i64, 1i64)
+                    .unwrap()
+                    .set(1, "alice", "ally")
+                    .unwrap(),
+            )
+            .insert(Insert::from_values(users.clone(), row(2, "bob")))
+            .delete(ChangeDelete::from_values(users.clone(), row(2, "bob")))
+            .update(
+                Update::<_, ChangesetFormat, String, Vec<u8>>::from(users.clone())
+                    .set(0, 3i64, 4i64)
+                    .unwrap()
+                    .set_new(1, "carol")
+                    .unwrap(),
+            );
+
+        let rows = changeset.final_rows();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[&key(1)],
+            Some(row(1, "ally").into_iter().map(Some).collect())
+        );
+        assert!(!rows.contains_key(&key(2)), "INSERT + DELETE cancels out");
+        assert_eq!(rows[&key(3)], None, "the UPDATE moved the row away");
+        assert_eq!(
+            rows[&key(4)],
+            Some(vec![Some(Value::Integer(4)), Some("carol".into())])
+        );
+    }
+
+    #[test]
+    fn test_session_recording_order_inverts_row_order() {
+        let table = TestTable::new("t", 2, 0);
+        // Sizes on both sides of the 128, 256 and 512 row table growths.
+        for n in [1_i64, 2, 127, 128, 129, 300, 700] {
+            let rows: IndexMap<Vec<Value<String, Vec<u8>>>, ()> = (0..n)
+                .map(|i| (vec![Value::Integer(i * 7919 % 10007)], ()))
+                .collect();
+            let emitted: Vec<&Vec<Value<String, Vec<u8>>>> =
+                session_row_order(&table, &rows, |_, ()| true)
+                    .into_iter()
+                    .map(|idx| rows.get_index(idx).unwrap().0)
+                    .collect();
+
+            let recorded: IndexMap<Vec<Value<String, Vec<u8>>>, ()> =
+                session_recording_order(&table, emitted.iter().map(|&pk| (pk, true)))
+                    .into_iter()
+                    .map(|idx| (emitted[idx].clone(), ()))
+                    .collect();
+            let replayed: Vec<&Vec<Value<String, Vec<u8>>>> =
+                session_row_order(&table, &recorded, |_, ()| true)
+                    .into_iter()
+                    .map(|idx| recorded.get_index(idx).unwrap().0)
+                    .collect();
+            assert_eq!(replayed, emitted, "{n} rows");
+        }
+    }
+
+    #[test]
+    fn test_into_builder_reproduces_parsed_bytes() {
+        let users = TestTable::new("users", 2, 0);
+        let posts = TestTable::new("posts", 2, 0);
+        let mut changeset: ChangeSet<TestTable, String, Vec<u8>> = ChangeSet::new();
+        for i in 0..300i64 {
+            changeset = changeset.insert(
+                Insert::from(users.clone())
+                    .set(0, alloc::format!("user-{i}"))
+                    .unwrap()
+                    .set(1, i)
+                    .unwrap(),
+            );
+        }
+        for i in 0..20i64 {
+            changeset = changeset.delete(
+                ChangeDelete::from(posts.clone())
+                    .set(0, i * 31)
+                    .unwrap()
+                    .set(1, "title")
+                    .unwrap(),
+            );
+        }
+        let bytes = changeset.build();
+
+        let crate::parser::ParsedDiffSet::Changeset(parsed) =
+            crate::parser::ParsedDiffSet::try_from(bytes.as_slice()).unwrap()
+        else {
+            panic!("expected a changeset");
+        };
+        let from_parsed: DiffSetBuilder<ChangesetFormat, _, _, _> = parsed.clone().into();
+        assert_ne!(
+            from_parsed.build(),
+            bytes,
+            "From re-derives the session order"
+        );
+        assert_eq!(parsed.into_builder().build(), bytes);
+
+        let patchset: PatchSet<TestTable, String, Vec<u8>> = (0..300i64)
+            .fold(PatchSet::new(), |patchset, i| {
+                patchset.delete(PatchDelete::new(posts.clone(), vec![Value::Integer(i)]))
+            });
+        let bytes = patchset.build();
+        let crate::parser::ParsedDiffSet::Patchset(parsed) =
+            crate::parser::ParsedDiffSet::try_from(bytes.as_slice()).unwrap()
+        else {
+            panic!("expected a patchset");
+        };
+        assert_eq!(parsed.into_builder().build(), bytes);
+    }
+
+    #[test]
+    fn test_upsert_overwrites_where_insert_is_ignored() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let row = |id: i64, name: &str| {
+            Insert::from(users.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, name)
+                .unwrap()
+        };
+
+        let ignored: ChangeSet<_, String, Vec<u8>> = ChangeSet::new()
+            .insert(row(1, "Alice"))
+            .insert(row(1, "Alicia"));
+        let upserted: ChangeSet<_, String, Vec<u8>> = ChangeSet::new()
+            .insert(row(1, "Alice"))
+            .upsert(row(1, "Alicia"));
+        assert_eq!(
+            ignored.build(),
+            ChangeSet::new().insert(row(1, "Alice")).build()
+        );
+        assert_eq!(
+            upserted.build(),
+            ChangeSet::new().insert(row(1, "Alicia")).build()
+        );
+
+        // Without a staged INSERT, upsert is a plain insert.
+        let staged_delete: PatchSet<_, String, Vec<u8>> =
+            PatchSet::new().delete(PatchDelete::new(users.clone(), vec![1i64.into()]));
+        assert_eq!(
+            staged_delete.clone().upsert(row(1, "Bob")).build(),
+            staged_delete.insert(row(1, "Bob")).build()
+        );
+        assert_eq!(
+            PatchSet::new().upsert(row(2, "Carol")).build(),
+            PatchSet::<_, String, Vec<u8>>::new()
+                .insert(row(2, "Carol"))
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_insert_into_all_fans_one_row_into_each_table() {
+        let users = crate::SimpleTable::new("users", &["id", "name"], &[0]);
+        let archive = crate::SimpleTable::new("users_archive", &["id", "name"], &[0]);
+        let row: [Value<String, Vec<u8>>; 2] = [1i64.into(), "Alice".into()];
+
+        let fanned = ChangeSet::new()
+            .insert_into_all(&[users.clone(), archive.clone()], &row)
+            .unwrap();
+        let by_hand = ChangeSet::new()
+            .insert(Insert::from_values(users.clone(), row.to_vec()))
+            .insert(Insert::from_values(archive, row.to_vec()));
+        assert_eq!(fanned.len(), 2);
+        assert_eq!(fanned.build(), by_hand.build());
+
+        let narrow = crate::SimpleTable::new("names", &["id"], &[0]);
+        assert_eq!(
+            PatchSet::<_, String, Vec<u8>>::new()
+                .insert_into_all(&[users, narrow], &row)
+                .unwrap_err(),
+            crate::errors::Error::ValueCountMismatch(2, 1)
+        );
+    }
+
+    #[test]
+    fn test_try_build_rejects_table_names_with_nul() {
+        let bad = TestTable::new("us\0ers", 2, 0);
+        let good = TestTable::new("posts", 2, 0);
+        let changeset: ChangeSet<TestTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(Insert::from_values(
+                good.clone(),
+                vec![1i64.into(), 2i64.into()],
+            ))
+            .insert(Insert::from_values(
+                bad.clone(),
+                vec![1i64.into(), 2i64.into()],
+            ));
+        let patchset: PatchSet<TestTable, String, Vec<u8>> = changeset.to_patchset();
+
+        // The plain build cuts the name short, so the changeset misparses.
+        assert!(crate::parser::ParsedDiffSet::parse(&changeset.build()).is_err());
+        let expected = Err(crate::errors::Error::InvalidTableName("us\0ers".into()));
+        assert_eq!(changeset.try_build(), expected);
+        assert_eq!(patchset.try_build(), expected);
+
+        // A registered table without operations is never written.
+        let mut registered: ChangeSet<TestTable, String, Vec<u8>> = ChangeSet::new();
+        registered.add_table(&bad);
+        let registered =
+            registered.insert(Insert::from_values(good, vec![1i64.into(), 2i64.into()]));
+        assert_eq!(registered.try_build(), Ok(registered.build()));
+    }
+
+    #[test]
+    fn test_non_empty_tables_skips_cancelled_and_registered_tables() {
+        let users = TestTable::new("users", 2, 0);
+        let posts = TestTable::new("posts", 2, 0);
+        let tags = TestTable::new("tags", 2, 0);
+        let row = |table: &TestTable| {
+            Insert::from(table.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap()
+        };
+
+        let mut builder = ChangesetBuilder::new();
+        builder.add_table(&tags);
+        let builder =
+            builder
+                .insert(row(&users))
+                .insert(row(&posts))
+                .delete(ChangeDelete::from_values(
+                    users.clone(),
+                    alloc::vec![1i64.into(), "a".into()],
+                ));
+
+        assert_eq!(builder.non_empty_tables().collect::<Vec<_>>(), [&posts]);
+        assert!(builder.is_table_empty(&users));
+        assert!(!builder.is_table_empty(&posts));
+        assert!(builder.is_table_empty(&tags));
+        assert!(builder.is_table_empty(&TestTable::new("unknown", 2, 0)));
+    }
+
+    #[test]
+    fn test_build_pk_sorted_orders_rows_by_key() {
+        let users = TestTable::new("users", 2, 0);
+        let mut changeset: ChangeSet<TestTable, String, Vec<u8>> = ChangeSet::new();
+        for i in 0..200i64 {
+            let id = i * 37 % 200 - 100;
+            changeset = changeset.insert(Insert::from_values(
+                users.clone(),
+                alloc::vec![id.into(), alloc::format!("user{id}").into()],
+            ));
+        }
+        for id in 300..320i64 {
+            changeset = changeset.delete(ChangeDelete::from_values(
+                users.clone(),
+                alloc::vec![id.into(), "gone".into()],
+            ));
+        }
+        changeset = changeset.update(Update::from_values(
+            users.clone(),
+            alloc::vec![
+                (Some(250i64.into()), None),
+                (Some("old".into()), Some("new".into())),
+            ],
+        ));
+
+        let sorted = changeset.build_pk_sorted();
+        let parsed = crate::parser::ParsedDiffSet::try_from(sorted.as_slice()).unwrap();
+        let crate::parser::ParsedDiffSet::Changeset(diffset) = &parsed else {
+            panic!("expected a changeset");
+        };
+        let keys: Vec<i64> = diffset
+            .iter()
+            .map(|op| {
+                let key = match op {
+                    ChangesetOp::Insert { values, .. } => &values[0],
+                    ChangesetOp::Delete { old_values, .. } => &old_values[0],
+                    ChangesetOp::Update { values, .. } => values[0].0.as_ref().unwrap(),
+                };
+                match key {
+                    Value::Integer(id) => *id,
+                    other => panic!("unexpected key {other:?}"),
+                }
+            })
+            .collect();
+        assert_eq!(keys.len(), 221);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(sorted, changeset.build());
+        // Same operations as the native order, which canonical JSON sorts.
+        let canonical = |bytes: &[u8]| {
+            crate::parser::ParsedDiffSet::try_from(bytes)
+                .unwrap()
+                .to_canonical_json(&[])
+        };
+        assert_eq!(canonical(&sorted), canonical(&changeset.build()));
+        let patchset = changeset.to_patchset();
+        assert_eq!(
+            canonical(&patchset.build_pk_sorted()),
+            canonical(&patchset.build())
+        );
+    }
 }
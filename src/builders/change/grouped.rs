@@ -0,0 +1,295 @@
+//! [`GroupedDiffSet`]: a builder that tags groups of operations out of band.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use super::{
+    DiffOps, DiffSetBuilder, StagedOp, changeset_update_parts, insert_parts, patchset_update_parts,
+};
+use crate::SchemaWithPK;
+use crate::builders::{
+    ChangeDelete, ChangesetFormat, Format, Insert, Operation, PatchDelete, PatchsetFormat, Update,
+};
+use crate::encoding::Value;
+
+/// The table and primary key of one staged operation.
+pub type GroupKey<T, S, B> = (T, Vec<Value<S, B>>);
+
+/// A closed or recording group: its tag, the key of every operation staged
+/// in it, and those operations consolidated on their own.
+#[derive(Debug, Clone)]
+struct Group<F: Format<S, B>, T: SchemaWithPK, S, B, Tag> {
+    tag: Tag,
+    keys: Vec<GroupKey<T, S, B>>,
+    operations: DiffSetBuilder<F, T, S, B>,
+}
+
+/// Diffset builder that records, out of band, which operations belong to
+/// which tagged group, such as one application transaction.
+///
+/// `SQLite` changesets and patchsets do not encode transactions. This builder
+/// stages every operation on one [`DiffSetBuilder`], which builds the bytes as
+/// usual, and notes the table and primary key of each operation staged while
+/// a group is open. [`split_by_group`](Self::split_by_group) hands each group
+/// back as a builder of its own operations, consolidated within the group
+/// only.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{ChangeSet, ChangesetFormat, DiffOps, GroupedDiffSet, Insert, SimpleTable};
+///
+/// let users = SimpleTable::new("users", &["id"], &[0]);
+/// let row = |id: i64| Insert::from(users.clone()).set(0, id).unwrap();
+///
+/// let mut grouped: GroupedDiffSet<ChangesetFormat, SimpleTable, String, Vec<u8>, &str> =
+///     GroupedDiffSet::new();
+/// grouped.begin_group("signup");
+/// grouped = grouped.insert(row(1)).insert(row(2));
+/// grouped.end_group();
+///
+/// let plain: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(row(1)).insert(row(2));
+/// assert_eq!(grouped.builder().build(), plain.build());
+///
+/// let (tag, keys) = grouped.groups().next().unwrap();
+/// assert_eq!(*tag, "signup");
+/// assert_eq!(keys, [(users.clone(), vec![1i64.into()]), (users, vec![2i64.into()])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GroupedDiffSet<F: Format<S, B>, T: SchemaWithPK, S, B, Tag> {
+    builder: DiffSetBuilder<F, T, S, B>,
+    groups: Vec<Group<F, T, S, B, Tag>>,
+    /// Whether the last group is still recording.
+    open: bool,
+}
+
+impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]> + Hash + Eq, Tag>
+    Default for GroupedDiffSet<F, T, S, B, Tag>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Format<S, B>, T: SchemaWithPK, S: AsRef<str> + Hash + Eq, B: AsRef<[u8]> + Hash + Eq, Tag>
+    GroupedDiffSet<F, T, S, B, Tag>
+{
+    /// Create an empty builder with no groups.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            builder: DiffSetBuilder::new(),
+            groups: Vec::new(),
+            open: false,
+        }
+    }
+
+    /// Start a group of operations tagged `tag`, closing the group being
+    /// recorded if any.
+    pub fn begin_group(&mut self, tag: Tag) -> &mut Self {
+        self.groups.push(Group {
+            tag,
+            keys: Vec::new(),
+            operations: DiffSetBuilder::new(),
+        });
+        self.open = true;
+        self
+    }
+
+    /// Close the group being recorded, if any. Operations staged until the
+    /// next [`begin_group`](Self::begin_group) belong to no group.
+    pub fn end_group(&mut self) -> &mut Self {
+        self.open = false;
+        self
+    }
+
+    /// Returns the closed groups, in the order they were opened, each with
+    /// the table and primary key of every operation staged in it.
+    ///
+    /// The keys follow the staging order and count every INSERT, UPDATE and
+    /// DELETE, including those that consolidated with or cancelled an
+    /// earlier operation on their row.
+    pub fn groups(&self) -> impl Iterator<Item = (&Tag, &[GroupKey<T, S, B>])> {
+        let closed = self.groups.len() - usize::from(self.open);
+        self.groups[..closed]
+            .iter()
+            .map(|group| (&group.tag, group.keys.as_slice()))
+    }
+
+    /// Returns the builder holding every operation staged so far, grouped or
+    /// not.
+    #[inline]
+    #[must_use]
+    pub fn builder(&self) -> &DiffSetBuilder<F, T, S, B> {
+        &self.builder
+    }
+
+    /// Consumes the builder, returning every staged operation and dropping
+    /// the groups.
+    #[inline]
+    #[must_use]
+    pub fn into_builder(self) -> DiffSetBuilder<F, T, S, B> {
+        self.builder
+    }
+
+    /// Splits the builder into one builder per group, in the order the groups
+    /// were opened, each holding the group's operations consolidated with
+    /// one another only.
+    ///
+    /// A group being recorded is closed first. Operations staged outside any
+    /// group are dropped.
+    #[must_use]
+    pub fn split_by_group(self) -> Vec<(Tag, DiffSetBuilder<F, T, S, B>)> {
+        self.groups
+            .into_iter()
+            .map(|group| (group.tag, group.operations))
+            .collect()
+    }
+
+    /// Stage an operation, recording its key in the group being recorded.
+    fn stage(&mut self, (table, pk, op): StagedOp<F, T, S, B>)
+    where
+        S: Clone,
+        B: Clone,
+        Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
+    {
+        if self.open
+            && let Some(group) = self.groups.last_mut()
+        {
+            group
+                .operations
+                .add_operation(&table, pk.clone(), op.clone());
+            group.keys.push((table.clone(), pk.clone()));
+        }
+        self.builder.add_operation(&table, pk, op);
+    }
+}
+
+// -- DiffOps for GroupedDiffSet<ChangesetFormat> ------------------------------
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+    Tag,
+> DiffOps<T, S, B> for GroupedDiffSet<ChangesetFormat, T, S, B, Tag>
+{
+    type Format = ChangesetFormat;
+    type Output = Self;
+    type DeleteArg = ChangeDelete<T, S, B>;
+
+    fn insert(mut self, insert: Insert<T, S, B>) -> Self {
+        self.stage(insert_parts(insert));
+        self
+    }
+
+    fn delete(mut self, delete: ChangeDelete<T, S, B>) -> Self {
+        let pk = delete.as_ref().extract_pk(&delete.values);
+        let table = delete.as_ref().clone();
+        let indirect = delete.indirect;
+        let data = delete.into_values();
+        self.stage((table, pk, Operation::Delete { data, indirect }));
+        self
+    }
+
+    fn update(mut self, update: Update<T, ChangesetFormat, S, B>) -> Self {
+        self.stage(changeset_update_parts(update));
+        self
+    }
+}
+
+// -- DiffOps for GroupedDiffSet<PatchsetFormat> -------------------------------
+
+impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq + AsRef<[u8]>, Tag>
+    DiffOps<T, S, B> for GroupedDiffSet<PatchsetFormat, T, S, B, Tag>
+{
+    type Format = PatchsetFormat;
+    type Output = Self;
+    type DeleteArg = PatchDelete<T, S, B>;
+
+    fn insert(mut self, insert: Insert<T, S, B>) -> Self {
+        self.stage(insert_parts(insert));
+        self
+    }
+
+    fn delete(mut self, delete: PatchDelete<T, S, B>) -> Self {
+        let indirect = delete.indirect;
+        self.stage((
+            delete.table,
+            delete.pk,
+            Operation::Delete { data: (), indirect },
+        ));
+        self
+    }
+
+    fn update(mut self, update: Update<T, PatchsetFormat, S, B>) -> Self {
+        self.stage(patchset_update_parts(update));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleTable;
+    use alloc::string::String;
+    use alloc::vec;
+
+    type Grouped = GroupedDiffSet<ChangesetFormat, SimpleTable, String, Vec<u8>, u32>;
+
+    #[test]
+    fn test_groups_record_keys_and_split_into_builders() {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let row = |id: i64| {
+            Insert::from(users.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, "a")
+                .unwrap()
+        };
+        let key = |id: i64| (users.clone(), vec![Value::Integer(id)]);
+        let mut grouped = Grouped::new();
+        grouped.begin_group(1);
+        grouped = grouped.insert(row(1)).insert(row(2)).insert(row(3));
+        grouped.end_group();
+        grouped = grouped.insert(row(4));
+        grouped.begin_group(2);
+        grouped = grouped.delete(
+            ChangeDelete::from(users.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap(),
+        );
+        // Opening a group closes the one being recorded.
+        grouped.begin_group(3);
+        grouped = grouped.insert(row(5));
+
+        let groups: Vec<_> = grouped.groups().collect();
+        assert_eq!(
+            groups,
+            [(&1, &[key(1), key(2), key(3)][..]), (&2, &[key(1)][..])],
+            "the recording group is not closed yet"
+        );
+        grouped.end_group();
+        assert_eq!(grouped.groups().count(), 3);
+
+        let plain = DiffSetBuilder::<ChangesetFormat, _, String, Vec<u8>>::new()
+            .insert(row(2))
+            .insert(row(3))
+            .insert(row(4))
+            .insert(row(5));
+        assert_eq!(grouped.builder().build(), plain.build());
+
+        let parts = grouped.split_by_group();
+        assert_eq!(
+            parts.iter().map(|(tag, _)| *tag).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        // Within its own group, the DELETE of row 1 has nothing to cancel.
+        assert_eq!(parts[0].1.len(), 3);
+        assert_eq!(parts[1].1.len(), 1);
+        assert_eq!(parts[2].1.len(), 1);
+    }
+}
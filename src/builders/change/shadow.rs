@@ -0,0 +1,177 @@
+//! [`ShadowPatchSet`]: a patchset builder that keeps the old rows it drops.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use super::{ChangeSet, DiffOps, PatchSet};
+use crate::SchemaWithPK;
+use crate::builders::operation::{Indirect, Reverse};
+use crate::builders::{
+    ChangeDelete, ChangesetFormat, Format, Insert, PatchDelete, PatchsetFormat, Update,
+};
+
+/// Patchset builder that also logs, out of band, the old values its
+/// operations drop.
+///
+/// A patchset DELETE carries only the primary key and a patchset UPDATE only
+/// the new values, so a patchset cannot be undone on its own. This builder
+/// takes operations with their old values, stages each on a [`PatchSet`] as
+/// usual, and records it in full on a shadow [`ChangeSet`] that is never
+/// serialized into the patchset. [`reverse_log`](Self::reverse_log) turns the
+/// shadow into the changeset restoring what the patchset changed.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{ChangeDelete, PatchDelete, PatchSet, DiffOps, ShadowPatchSet, SimpleTable};
+///
+/// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+/// let alice = ChangeDelete::<_, String, Vec<u8>>::from(users.clone())
+///     .set(0, 1i64).unwrap()
+///     .set(1, "alice").unwrap();
+///
+/// let shadowed = ShadowPatchSet::new().delete(alice);
+/// let plain: PatchSet<SimpleTable, String, Vec<u8>> =
+///     PatchSet::new().delete(PatchDelete::new(users, vec![1i64.into()]));
+/// assert_eq!(shadowed.build(), plain.build());
+/// // The changeset re-inserting alice.
+/// assert_eq!(shadowed.reverse_log().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShadowPatchSet<T: SchemaWithPK, S, B>
+where
+    ChangesetFormat: Format<S, B>,
+{
+    patchset: PatchSet<T, S, B>,
+    shadow: ChangeSet<T, S, B>,
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> Default for ShadowPatchSet<T, S, B>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> ShadowPatchSet<T, S, B>
+{
+    /// Create an empty builder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            patchset: PatchSet::new(),
+            shadow: ChangeSet::new(),
+        }
+    }
+
+    /// Returns true if the builder has no operations.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.patchset.is_empty()
+    }
+
+    /// Returns the number of operations in the patchset.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.patchset.len()
+    }
+
+    /// Add an INSERT operation.
+    #[must_use]
+    pub fn insert(self, insert: Insert<T, S, B>) -> Self {
+        Self {
+            patchset: self.patchset.insert(insert.clone()),
+            shadow: self.shadow.insert(insert),
+        }
+    }
+
+    /// Add a DELETE operation, given the full old row.
+    ///
+    /// The patchset keeps the row's primary key only.
+    #[must_use]
+    pub fn delete(self, delete: ChangeDelete<T, S, B>) -> Self {
+        let patch = PatchDelete::from_row(delete.as_ref().clone(), &delete.values)
+            .indirect(delete.indirect);
+        Self {
+            patchset: self.patchset.delete(patch),
+            shadow: self.shadow.delete(delete),
+        }
+    }
+
+    /// Add an UPDATE operation, given the old values of its key and changed
+    /// columns.
+    ///
+    /// The patchset keeps the new values, keyed on the old primary key.
+    ///
+    /// # Errors
+    ///
+    /// * `PatchsetPkChange` - If the update gives a key column a new value
+    ///   other than its old one, which a patchset UPDATE cannot express. Stage
+    ///   a key change as a DELETE and an INSERT instead.
+    pub fn update(
+        self,
+        update: Update<T, ChangesetFormat, S, B>,
+    ) -> Result<Self, crate::errors::Error> {
+        let table = update.as_ref();
+        let values = update
+            .values()
+            .iter()
+            .enumerate()
+            .map(
+                |(col, (old, new))| match (table.primary_key_index(col), old, new) {
+                    (None, _, _) => Ok(((), new.clone())),
+                    (Some(_), Some(old_value), Some(new_value))
+                        if crate::values_differ_for_patch(old_value, new_value) =>
+                    {
+                        Err(crate::errors::Error::PatchsetPkChange)
+                    }
+                    (Some(_), _, _) => Ok(((), old.clone())),
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        let patch = Update::<T, PatchsetFormat, S, B>::from_values(table.clone(), values)
+            .indirect(update.indirect);
+        Ok(Self {
+            patchset: self.patchset.update(patch),
+            shadow: self.shadow.update(update),
+        })
+    }
+
+    /// Returns the patchset staged so far.
+    #[inline]
+    #[must_use]
+    pub fn patchset(&self) -> &PatchSet<T, S, B> {
+        &self.patchset
+    }
+
+    /// Consumes the builder, returning the patchset and dropping the log.
+    #[inline]
+    #[must_use]
+    pub fn into_patchset(self) -> PatchSet<T, S, B> {
+        self.patchset
+    }
+
+    /// Build the patchset binary data, as [`PatchSet::build`] would.
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        self.patchset.build()
+    }
+
+    /// Returns the changeset undoing the patchset: applied after it, it
+    /// restores every row the patchset inserted, updated or deleted.
+    #[must_use]
+    pub fn reverse_log(&self) -> ChangeSet<T, S, B> {
+        self.shadow.clone().reverse()
+    }
+}
@@ -0,0 +1,239 @@
+//! [`SingleTableChangeSet`]: a changeset builder specialized to one table.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use super::{
+    ChangeSet, DiffOps, DiffSetBuilder, EntryLog, KeyClasses, RowMap, changeset_update_parts,
+    encode_changeset_op, insert_parts, write_table_header,
+};
+use crate::SchemaWithPK;
+use crate::builders::{ChangeDelete, ChangesetFormat, Format, Insert, Operation, Update};
+use crate::encoding::{Value, markers};
+
+/// Changeset builder for operations that all touch one table.
+///
+/// A [`ChangeSet`] keys its rows by table, so every operation hashes its
+/// table schema to find them. This builder caches the table and keeps a
+/// single row map instead, consolidating operations by the same rules and
+/// producing byte-identical output from [`build`](Self::build).
+///
+/// Operations are added through [`DiffOps`] and must target the cached
+/// table: an operation on any other table panics.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable, SingleTableChangeSet};
+///
+/// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+/// let alice = Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, "alice").unwrap();
+///
+/// let single = SingleTableChangeSet::new(users).insert(alice.clone());
+/// let generic: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(alice);
+/// assert_eq!(single.build(), generic.build());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SingleTableChangeSet<T: SchemaWithPK, S, B>
+where
+    ChangesetFormat: Format<S, B>,
+{
+    table: T,
+    rows: RowMap<ChangesetFormat, S, B>,
+    /// The table's entry log, once a row has cancelled out or changed its
+    /// key; see the `DiffSetBuilder` field of the same name.
+    entry_log: Option<EntryLog<S, B>>,
+    /// The numeric classes of the staged keys.
+    key_classes: KeyClasses,
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> SingleTableChangeSet<T, S, B>
+{
+    /// Create an empty changeset for `table`.
+    #[must_use]
+    pub fn new(table: T) -> Self {
+        Self {
+            table,
+            rows: RowMap::default(),
+            entry_log: None,
+            key_classes: KeyClasses::default(),
+        }
+    }
+
+    /// Returns the table this changeset records operations on.
+    #[inline]
+    #[must_use]
+    pub fn table(&self) -> &T {
+        &self.table
+    }
+
+    /// Returns true if the builder has no operations.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the number of operations.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Build the changeset binary data, as [`ChangeSet::build`] would for
+    /// the same operations.
+    ///
+    /// # Panics
+    ///
+    /// This function does not panic under normal usage. Internal indexing is guaranteed
+    /// to be within bounds.
+    #[must_use]
+    pub fn build(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.rows.is_empty() {
+            return out;
+        }
+
+        write_table_header(&mut out, markers::CHANGESET, &self.table);
+        for idx in ChangeSet::<T, S, B>::logged_emission_order(
+            &self.table,
+            &self.rows,
+            self.entry_log.as_ref(),
+        ) {
+            let (_pk, op) = self.rows.get_index(idx).unwrap();
+            encode_changeset_op(&mut out, op);
+        }
+        out
+    }
+
+    /// Panics unless `table` is the cached table.
+    fn check_table(&self, table: &T, statement: &str) {
+        assert!(
+            table == &self.table,
+            "{statement} targets table {:?}, but this changeset records table {:?}",
+            table.name(),
+            self.table.name()
+        );
+    }
+
+    fn consolidate(&mut self, pk: Vec<Value<S, B>>, op: Operation<ChangesetFormat, S, B>) {
+        DiffSetBuilder::<ChangesetFormat, T, S, B>::consolidate(
+            &self.table,
+            &mut self.rows,
+            &mut self.entry_log,
+            &mut self.key_classes,
+            pk,
+            op,
+        );
+    }
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> DiffOps<T, S, B> for SingleTableChangeSet<T, S, B>
+{
+    type Format = ChangesetFormat;
+    type Output = Self;
+    type DeleteArg = ChangeDelete<T, S, B>;
+
+    /// Add an INSERT operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the INSERT targets another table than the cached one.
+    fn insert(mut self, insert: Insert<T, S, B>) -> Self {
+        self.check_table(insert.as_ref(), "INSERT");
+        let (_, pk, op) = insert_parts(insert);
+        self.consolidate(pk, op);
+        self
+    }
+
+    /// Add a DELETE operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the DELETE targets another table than the cached one.
+    fn delete(mut self, delete: ChangeDelete<T, S, B>) -> Self {
+        self.check_table(delete.as_ref(), "DELETE");
+        let pk = self.table.extract_pk(&delete.values);
+        let indirect = delete.indirect;
+        let data = delete.into_values();
+        self.consolidate(pk, Operation::Delete { data, indirect });
+        self
+    }
+
+    /// Add an UPDATE operation, keyed on its old primary-key values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the UPDATE targets another table than the cached one.
+    fn update(mut self, update: Update<T, ChangesetFormat, S, B>) -> Self {
+        self.check_table(update.as_ref(), "UPDATE");
+        let (_, pk, op) = changeset_update_parts(update);
+        self.consolidate(pk, op);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiffOps, SimpleTable};
+    use alloc::string::String;
+
+    #[test]
+    fn test_single_table_changeset_matches_generic_builder() {
+        let table = SimpleTable::new("events", &["id", "kind"], &[0]);
+        let insert = |id: i64| {
+            Insert::from(table.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, "click")
+                .unwrap()
+        };
+        let mut single = SingleTableChangeSet::new(table.clone());
+        let mut generic: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        for id in 0..200 {
+            single = single.insert(insert(id));
+            generic = generic.insert(insert(id));
+        }
+        for id in (0..200).step_by(3) {
+            let update = Update::<_, ChangesetFormat, _, _>::from(table.clone())
+                .set(0, id, id + 1000)
+                .unwrap()
+                .set(1, "click", "view")
+                .unwrap();
+            single = single.update(update.clone());
+            generic = generic.update(update);
+        }
+        for id in (1..200).step_by(5) {
+            let delete = ChangeDelete::from(table.clone())
+                .set(0, id)
+                .unwrap()
+                .set(1, "click")
+                .unwrap();
+            single = single.delete(delete.clone());
+            generic = generic.delete(delete);
+        }
+
+        assert_eq!(single.len(), generic.len());
+        assert_eq!(single.build(), generic.build());
+    }
+
+    #[test]
+    #[should_panic(expected = "INSERT targets table \"orders\"")]
+    fn test_single_table_changeset_rejects_other_tables() {
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let orders = SimpleTable::new("orders", &["id"], &[0]);
+        let _ = SingleTableChangeSet::<_, String, Vec<u8>>::new(users)
+            .insert(Insert::from(orders).set(0, 1i64).unwrap());
+    }
+}
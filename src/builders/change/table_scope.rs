@@ -0,0 +1,372 @@
+//! [`TableScope`]: stages operations on one table of a builder.
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use super::DiffSetBuilder;
+use crate::SchemaWithPK;
+use crate::builders::{ChangesetFormat, Format, Operation, PatchsetFormat};
+use crate::encoding::{MaybeValue, Value};
+use crate::errors::Error;
+
+/// Stages many operations on one table of a [`DiffSetBuilder`] without
+/// repeating its schema.
+///
+/// Returned by [`DiffSetBuilder::table`], which clones the schema once.
+/// Operations take bare rows instead of [`Insert`](crate::Insert),
+/// [`Update`](crate::Update) and delete values, and consolidate exactly as
+/// the same operations staged through [`DiffOps`](crate::DiffOps) would.
+///
+/// Rows must hold one value per column of the table. This is checked in
+/// debug builds only, as [`Insert::from_values`](crate::Insert::from_values)
+/// does; the `try_` variant of each operation checks it in every build.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable, Value};
+///
+/// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+/// let mut scoped: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+/// scoped
+///     .table(&users)
+///     .insert(vec![1i64.into(), "alice".into()])
+///     .insert(vec![2i64.into(), "bob".into()])
+///     .delete(vec![2i64.into(), "bob".into()]);
+///
+/// let alice = Insert::from(users).set(0, 1i64).unwrap().set(1, "alice").unwrap();
+/// let chained: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(alice);
+/// assert_eq!(scoped.build(), chained.build());
+/// ```
+pub struct TableScope<'builder, F: Format<S, B>, T: SchemaWithPK, S, B> {
+    builder: &'builder mut DiffSetBuilder<F, T, S, B>,
+    table: T,
+}
+
+impl<'builder, F: Format<S, B>, T: SchemaWithPK, S, B> TableScope<'builder, F, T, S, B> {
+    pub(super) fn new(builder: &'builder mut DiffSetBuilder<F, T, S, B>, table: T) -> Self {
+        Self { builder, table }
+    }
+
+    /// Returns the table this scope stages operations on.
+    #[inline]
+    #[must_use]
+    pub fn schema(&self) -> &T {
+        &self.table
+    }
+}
+
+impl<
+    F: Format<S, B>,
+    T: SchemaWithPK,
+    S: Clone + Hash + Eq + AsRef<str>,
+    B: Clone + Hash + Eq + AsRef<[u8]>,
+> TableScope<'_, F, T, S, B>
+where
+    Operation<F, S, B>: core::ops::Add<Output = Option<Operation<F, S, B>>>,
+{
+    /// Add an INSERT of `values`, one per column.
+    pub fn insert(&mut self, values: Vec<Value<S, B>>) -> &mut Self {
+        self.debug_check_len(values.len());
+        let pk = self.table.extract_pk(&values);
+        self.add(
+            pk,
+            Operation::Insert {
+                values,
+                indirect: false,
+            },
+        )
+    }
+
+    /// Add an INSERT of `values`, checking that there is one per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    pub fn try_insert(&mut self, values: Vec<Value<S, B>>) -> Result<&mut Self, Error> {
+        self.check_len(values.len())?;
+        Ok(self.insert(values))
+    }
+
+    fn add(&mut self, pk: Vec<Value<S, B>>, op: Operation<F, S, B>) -> &mut Self {
+        self.builder.add_operation(&self.table, pk, op);
+        self
+    }
+
+    fn debug_check_len(&self, len: usize) {
+        debug_assert_eq!(
+            len,
+            self.table.number_of_columns(),
+            "row must have one value per column"
+        );
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), Error> {
+        let columns = self.table.number_of_columns();
+        if len == columns {
+            Ok(())
+        } else {
+            Err(Error::ValueCountMismatch(len, columns))
+        }
+    }
+}
+
+impl<
+    T: SchemaWithPK,
+    S: Clone + Debug + Hash + Eq + AsRef<str>,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]>,
+> TableScope<'_, ChangesetFormat, T, S, B>
+{
+    /// Add a DELETE of the row holding `values`, one per column.
+    pub fn delete(&mut self, values: Vec<Value<S, B>>) -> &mut Self {
+        self.debug_check_len(values.len());
+        let pk = self.table.extract_pk(&values);
+        self.add(
+            pk,
+            Operation::Delete {
+                data: values,
+                indirect: false,
+            },
+        )
+    }
+
+    /// Add a DELETE of the row holding `values`, checking that there is one
+    /// per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    pub fn try_delete(&mut self, values: Vec<Value<S, B>>) -> Result<&mut Self, Error> {
+        self.check_len(values.len())?;
+        Ok(self.delete(values))
+    }
+
+    /// Add an UPDATE from its `(old, new)` pairs, one per column, as
+    /// [`Update::from_values`](crate::Update::from_values) takes them.
+    ///
+    /// The row is keyed on its old primary-key values, as
+    /// [`DiffOps::update`](crate::DiffOps::update) keys it.
+    pub fn update(&mut self, values: Vec<(MaybeValue<S, B>, MaybeValue<S, B>)>) -> &mut Self {
+        self.debug_check_len(values.len());
+        let old_values: Vec<_> = values
+            .iter()
+            .map(|(old, _)| old.clone().unwrap_or(Value::Null))
+            .collect();
+        let pk = self.table.extract_pk(&old_values);
+        self.add(
+            pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        )
+    }
+
+    /// Add an UPDATE from its `(old, new)` pairs, checking that there is one
+    /// per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    pub fn try_update(
+        &mut self,
+        values: Vec<(MaybeValue<S, B>, MaybeValue<S, B>)>,
+    ) -> Result<&mut Self, Error> {
+        self.check_len(values.len())?;
+        Ok(self.update(values))
+    }
+}
+
+impl<T: SchemaWithPK, S: Clone + Hash + Eq + AsRef<str>, B: Clone + Hash + Eq + AsRef<[u8]>>
+    TableScope<'_, PatchsetFormat, T, S, B>
+{
+    /// Add a DELETE of the row with primary key `pk`.
+    pub fn delete(&mut self, pk: Vec<Value<S, B>>) -> &mut Self {
+        debug_assert_eq!(
+            pk.len(),
+            self.table.number_of_primary_keys(),
+            "key must have one value per primary-key column"
+        );
+        self.add(
+            pk,
+            Operation::Delete {
+                data: (),
+                indirect: false,
+            },
+        )
+    }
+
+    /// Add a DELETE of the row with primary key `pk`, checking that it holds
+    /// one value per primary-key column.
+    ///
+    /// # Errors
+    ///
+    /// * `KeyCountMismatch` - If `pk.len()` differs from the table's primary-key column count.
+    pub fn try_delete(&mut self, pk: Vec<Value<S, B>>) -> Result<&mut Self, Error> {
+        let keys = self.table.number_of_primary_keys();
+        if pk.len() != keys {
+            return Err(Error::KeyCountMismatch(pk.len(), keys));
+        }
+        Ok(self.delete(pk))
+    }
+
+    /// Add an UPDATE setting the defined `new` values, one per column. The
+    /// primary-key columns must hold the row's key.
+    pub fn update(&mut self, new: Vec<MaybeValue<S, B>>) -> &mut Self {
+        self.debug_check_len(new.len());
+        let values: Vec<((), MaybeValue<S, B>)> = new.into_iter().map(|new| ((), new)).collect();
+        let pk = self.table.extract_pk(&values);
+        self.add(
+            pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        )
+    }
+
+    /// Add an UPDATE setting the defined `new` values, checking that there
+    /// is one per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `new.len()` differs from the table's column count.
+    pub fn try_update(&mut self, new: Vec<MaybeValue<S, B>>) -> Result<&mut Self, Error> {
+        self.check_len(new.len())?;
+        Ok(self.update(new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ChangeDelete, ChangeSet, DiffOps, Insert, PatchDelete, PatchSet, SimpleTable, Update,
+    };
+    use alloc::string::String;
+    use alloc::vec;
+
+    type Row = Vec<Value<String, Vec<u8>>>;
+
+    fn users() -> SimpleTable {
+        SimpleTable::new("users", &["id", "name"], &[0])
+    }
+
+    fn posts() -> SimpleTable {
+        SimpleTable::new("posts", &["id", "author", "title"], &[0])
+    }
+
+    fn user(id: i64) -> Row {
+        vec![id.into(), alloc::format!("user{id}").into()]
+    }
+
+    fn post(id: i64) -> Row {
+        vec![
+            id.into(),
+            (id % 7).into(),
+            alloc::format!("post{id}").into(),
+        ]
+    }
+
+    #[test]
+    fn test_changeset_scopes_match_chained_api() {
+        let mut scoped: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        let mut chained: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+
+        let mut scope = scoped.table(&users());
+        for id in 0..50 {
+            scope.insert(user(id));
+            chained = chained.insert(Insert::from_values(users(), user(id)));
+        }
+        for id in (0..50).step_by(4) {
+            let values = vec![
+                (Some(id.into()), None),
+                (Some(user(id)[1].clone()), Some("renamed".into())),
+            ];
+            scope.update(values.clone());
+            chained = chained.update(Update::from_values(users(), values));
+        }
+
+        let mut scope = scoped.table(&posts());
+        for id in 0..80 {
+            scope.insert(post(id));
+            chained = chained.insert(Insert::from_values(posts(), post(id)));
+        }
+        for id in (0..80).step_by(3) {
+            scope.delete(post(id));
+            chained = chained.delete(ChangeDelete::from_values(posts(), post(id)));
+        }
+        // Back to the first table, after the second one was touched.
+        scoped.table(&users()).delete(user(1));
+        chained = chained.delete(ChangeDelete::from_values(users(), user(1)));
+
+        assert_eq!(scoped, chained);
+        assert_eq!(scoped.build(), chained.build());
+    }
+
+    #[test]
+    fn test_patchset_scopes_match_chained_api() {
+        let mut scoped: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+        let mut chained: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+
+        for id in 0..40 {
+            scoped.table(&users()).insert(user(id));
+            chained = chained.insert(Insert::from_values(users(), user(id)));
+            scoped.table(&posts()).insert(post(id));
+            chained = chained.insert(Insert::from_values(posts(), post(id)));
+        }
+        let mut scope = scoped.table(&posts());
+        for id in (0..40).step_by(5) {
+            scope
+                .update(vec![Some(id.into()), None, Some("edited".into())])
+                .delete(vec![(id + 1).into()]);
+            chained = chained
+                .update(Update::from_values(
+                    posts(),
+                    vec![
+                        ((), Some(id.into())),
+                        ((), None),
+                        ((), Some("edited".into())),
+                    ],
+                ))
+                .delete(PatchDelete::new(posts(), vec![(id + 1).into()]));
+        }
+
+        assert_eq!(scoped, chained);
+        assert_eq!(scoped.build(), chained.build());
+    }
+
+    #[test]
+    fn test_try_operations_reject_rows_of_the_wrong_width() {
+        let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        let mut scope = changeset.table(&users());
+        assert_eq!(
+            scope.try_insert(vec![1i64.into()]).err(),
+            Some(Error::ValueCountMismatch(1, 2))
+        );
+        assert_eq!(
+            scope.try_delete(post(1)).err(),
+            Some(Error::ValueCountMismatch(3, 2))
+        );
+        assert_eq!(
+            scope.try_update(vec![(Some(1i64.into()), None)]).err(),
+            Some(Error::ValueCountMismatch(1, 2))
+        );
+        assert!(scope.try_insert(user(1)).is_ok());
+        assert_eq!(changeset.len(), 1);
+
+        let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+        let mut scope = patchset.table(&users());
+        assert_eq!(
+            scope.try_delete(user(1)).err(),
+            Some(Error::KeyCountMismatch(2, 1))
+        );
+        assert_eq!(
+            scope.try_update(vec![Some(1i64.into())]).err(),
+            Some(Error::ValueCountMismatch(1, 2))
+        );
+        assert!(scope.try_delete(vec![1i64.into()]).is_ok());
+        assert_eq!(patchset.len(), 1);
+    }
+}
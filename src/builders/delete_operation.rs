@@ -4,9 +4,19 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
-use crate::{DynTable, builders::operation::Indirect, encoding::Value};
+use crate::{DynTable, SchemaWithPK, builders::operation::Indirect, encoding::Value};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "ChangeDeleteFields<T, S, B>",
+        bound(
+            deserialize = "T: serde::Deserialize<'de>, S: serde::Deserialize<'de>, B: serde::Deserialize<'de>"
+        )
+    )
+)]
 /// Represents a delete operation in changeset format.
 ///
 /// Stores the full old-row values for all columns.
@@ -18,6 +28,27 @@ pub struct ChangeDelete<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> {
     pub(crate) indirect: bool,
 }
 
+/// The serialized fields of a [`ChangeDelete`], checked against the table's
+/// column count on the way back in.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ChangeDeleteFields<T, S, B> {
+    table: T,
+    values: Vec<Value<S, B>>,
+    indirect: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> TryFrom<ChangeDeleteFields<T, S, B>>
+    for ChangeDelete<T, S, B>
+{
+    type Error = crate::errors::Error;
+
+    fn try_from(fields: ChangeDeleteFields<T, S, B>) -> Result<Self, Self::Error> {
+        Ok(Self::try_from_values(fields.table, fields.values)?.indirect(fields.indirect))
+    }
+}
+
 impl<T: DynTable + PartialEq, S: PartialEq + AsRef<str>, B: PartialEq + AsRef<[u8]>> PartialEq
     for ChangeDelete<T, S, B>
 {
@@ -50,6 +81,46 @@ impl<T: DynTable, S: Default + Clone + AsRef<str>, B: Default + Clone + AsRef<[u
 }
 
 impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> ChangeDelete<T, S, B> {
+    /// Creates a delete from the full old row, one value per column.
+    ///
+    /// A fast path for callers whose rows are known to fit the table. A
+    /// length mismatch trips a debug assertion; use
+    /// [`try_from_values`](Self::try_from_values) to validate untrusted rows.
+    #[inline]
+    #[must_use]
+    pub fn from_values(table: T, values: Vec<Value<S, B>>) -> Self {
+        debug_assert_eq!(
+            values.len(),
+            table.number_of_columns(),
+            "row must have one value per column"
+        );
+        Self {
+            table,
+            values,
+            indirect: false,
+        }
+    }
+
+    /// Creates a delete from the full old row, checking that it holds one
+    /// value per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    pub fn try_from_values(
+        table: T,
+        values: Vec<Value<S, B>>,
+    ) -> Result<Self, crate::errors::Error> {
+        let num_cols = table.number_of_columns();
+        if values.len() != num_cols {
+            return Err(crate::errors::Error::ValueCountMismatch(
+                values.len(),
+                num_cols,
+            ));
+        }
+        Ok(Self::from_values(table, values))
+    }
+
     /// Sets the value for a specific column by index.
     ///
     /// # Arguments
@@ -126,6 +197,16 @@ impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> Indirect for ChangeDelete<T, S,
 /// Only stores the table schema and primary key values, as patchsets
 /// don't include full row data for deletions.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "PatchDeleteFields<T, S, B>",
+        bound(
+            deserialize = "T: SchemaWithPK + serde::Deserialize<'de>, S: serde::Deserialize<'de>, B: serde::Deserialize<'de>"
+        )
+    )
+)]
 pub struct PatchDelete<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> {
     pub(crate) table: T,
     /// Primary key values for the deleted row.
@@ -134,6 +215,34 @@ pub struct PatchDelete<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> {
     pub(crate) indirect: bool,
 }
 
+/// The serialized fields of a [`PatchDelete`], checked against the table's
+/// primary-key column count on the way back in.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PatchDeleteFields<T, S, B> {
+    table: T,
+    pk: Vec<Value<S, B>>,
+    indirect: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<T: SchemaWithPK, S: AsRef<str>, B: AsRef<[u8]>> TryFrom<PatchDeleteFields<T, S, B>>
+    for PatchDelete<T, S, B>
+{
+    type Error = crate::errors::Error;
+
+    fn try_from(fields: PatchDeleteFields<T, S, B>) -> Result<Self, Self::Error> {
+        let pk_count = fields.table.number_of_primary_keys();
+        if fields.pk.len() != pk_count {
+            return Err(crate::errors::Error::KeyCountMismatch(
+                fields.pk.len(),
+                pk_count,
+            ));
+        }
+        Ok(Self::new(fields.table, fields.pk).indirect(fields.indirect))
+    }
+}
+
 impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> AsRef<T> for PatchDelete<T, S, B> {
     #[inline]
     fn as_ref(&self) -> &T {
@@ -157,6 +266,36 @@ impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> PatchDelete<T, S, B> {
     }
 }
 
+impl<T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>> PatchDelete<T, S, B> {
+    /// Create a new patchset delete from the full old row.
+    ///
+    /// The primary key values are extracted via
+    /// [`SchemaWithPK::extract_pk`], so callers holding the whole row do not
+    /// need to slice out the key columns themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is shorter than the schema's column count.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{PatchDelete, TableSchema, Value};
+    ///
+    /// // CREATE TABLE items (name TEXT, id INTEGER PRIMARY KEY)
+    /// let schema: TableSchema<String> = TableSchema::new("items".into(), 2, vec![0, 1]);
+    ///
+    /// let row: Vec<Value<String, Vec<u8>>> = vec!["widget".into(), 7i64.into()];
+    /// let from_row = PatchDelete::from_row(schema.clone(), &row);
+    /// assert_eq!(from_row, PatchDelete::new(schema, vec![7i64.into()]));
+    /// ```
+    #[inline]
+    pub fn from_row(table: T, row: &[Value<S, B>]) -> Self {
+        let pk = table.extract_pk(&row);
+        Self::new(table, pk)
+    }
+}
+
 impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> Indirect for PatchDelete<T, S, B> {
     #[inline]
     fn indirect(mut self, indirect: bool) -> Self {
@@ -168,10 +307,11 @@ impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> Indirect for PatchDelete<T, S,
 #[cfg(test)]
 mod tests {
     use super::{ChangeDelete, PatchDelete};
-    use crate::DynTable;
+    use crate::builders::{DiffOps, PatchSet};
     use crate::encoding::Value;
     use crate::errors::Error;
     use crate::schema::SimpleTable;
+    use crate::{DynTable, SchemaWithPK};
     use alloc::string::String;
     use alloc::vec;
     use alloc::vec::Vec;
@@ -180,6 +320,31 @@ mod tests {
         SimpleTable::new("users", &["id", "name"], &[0])
     }
 
+    #[test]
+    fn test_change_delete_try_from_values() {
+        let delete = ChangeDelete::<_, String, Vec<u8>>::try_from_values(
+            users(),
+            vec![1i64.into(), "a".into()],
+        )
+        .unwrap();
+        assert_eq!(
+            delete,
+            ChangeDelete::from(users())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap()
+        );
+        for len in [0, 1, 3] {
+            let err = ChangeDelete::<_, String, Vec<u8>>::try_from_values(
+                users(),
+                vec![Value::Null; len],
+            )
+            .unwrap_err();
+            assert_eq!(err, Error::ValueCountMismatch(len, 2));
+        }
+    }
+
     #[test]
     fn test_change_delete_set_out_of_bounds() {
         let err = ChangeDelete::<_, String, Vec<u8>>::from(users())
@@ -211,6 +376,24 @@ mod tests {
         assert_eq!(t.name(), "users");
     }
 
+    #[test]
+    fn test_patch_delete_from_row_matches_extracted_pk() {
+        let table = SimpleTable::new("users", &["name", "id", "email"], &[1, 2]);
+        let row: Vec<Value<String, Vec<u8>>> = vec![
+            Value::Text("alice".into()),
+            Value::Integer(1),
+            Value::Text("a@example.com".into()),
+        ];
+        let pk = table.extract_pk(&row);
+        let explicit = PatchDelete::new(table.clone(), pk);
+        let from_row = PatchDelete::from_row(table.clone(), &row);
+        assert_eq!(from_row, explicit);
+
+        let a = PatchSet::<_, String, Vec<u8>>::new().delete(from_row);
+        let b = PatchSet::<_, String, Vec<u8>>::new().delete(explicit);
+        assert_eq!(a.build(), b.build());
+    }
+
     #[test]
     fn test_change_delete_eq() {
         let a = ChangeDelete::<_, String, Vec<u8>>::from(users())
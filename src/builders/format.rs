@@ -1,5 +1,7 @@
 //! Format trait defining changeset vs patchset behavior.
 
+use crate::DynTable;
+use crate::builders::{ChangeDelete, PatchDelete};
 use crate::encoding::{MaybeValue, Value};
 use alloc::vec::Vec;
 use core::fmt::Debug;
@@ -22,6 +24,29 @@ pub(crate) trait Format<S, B>: Default + Clone + Copy + PartialEq + Eq + 'static
     /// - Changeset: `Vec<Value<S, B>>` (full old-row values)
     /// - Patchset: `()` (only the PK matters, stored externally)
     type DeleteData: Clone + Debug + Default;
+
+    /// The operation staging a DELETE on a table `T`.
+    ///
+    /// - Changeset: [`ChangeDelete<T, S, B>`] (full old-row values)
+    /// - Patchset: [`PatchDelete<T, S, B>`] (PK values only)
+    type Delete<T: DynTable>
+    where
+        S: AsRef<str>,
+        B: AsRef<[u8]>;
+
+    /// Rewrites the columns of an UPDATE obtained by merging two operations
+    /// on one row into the form `sqlite3changegroup_add()` emits. Returns
+    /// `false` if the merged UPDATE changes no column and should be dropped.
+    ///
+    /// `is_pk` tells whether the column at an index is part of the PK.
+    fn settle_merged_update(
+        values: &mut [(Self::Old, MaybeValue<S, B>)],
+        is_pk: impl Fn(usize) -> bool,
+    ) -> bool;
+
+    /// Pads the DELETE payload of a row to `columns` columns with NULL, as
+    /// `SQLite` reads the columns added to a table after the row was written.
+    fn widen_delete(data: &mut Self::DeleteData, columns: usize);
 }
 
 /// Changeset format marker.
@@ -33,6 +58,46 @@ impl<S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>> Format<S, B>
 {
     type Old = MaybeValue<S, B>;
     type DeleteData = Vec<Value<S, B>>;
+    type Delete<T: DynTable> = ChangeDelete<T, S, B>;
+
+    /// Keeps the old value of PK and changed columns and the new value of
+    /// changed non-PK columns only; everything else becomes undefined.
+    fn settle_merged_update(
+        values: &mut [(Self::Old, MaybeValue<S, B>)],
+        is_pk: impl Fn(usize) -> bool,
+    ) -> bool {
+        let mut changed = false;
+        for (col_idx, (old, new)) in values.iter_mut().enumerate() {
+            let is_pk = is_pk(col_idx);
+            let differs = !same_record_value(old.as_ref(), new.as_ref());
+            changed |= differs && !is_pk;
+            if !is_pk && !differs {
+                *old = None;
+            }
+            if is_pk || !differs {
+                *new = None;
+            }
+        }
+        changed
+    }
+
+    fn widen_delete(data: &mut Self::DeleteData, columns: usize) {
+        data.resize_with(columns, || Value::Null);
+    }
+}
+
+/// Whether two maybe-values serialize to the same record bytes, which is how
+/// `SQLite` compares them when merging changes.
+fn same_record_value<S: AsRef<str>, B: AsRef<[u8]>>(
+    a: Option<&Value<S, B>>,
+    b: Option<&Value<S, B>>,
+) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(Value::Real(a)), Some(Value::Real(b))) => a.to_bits() == b.to_bits(),
+        (Some(a), Some(b)) => !crate::values_differ_for_patch(a, b),
+        _ => false,
+    }
 }
 
 /// Patchset format marker.
@@ -42,4 +107,20 @@ pub struct PatchsetFormat;
 impl<S, B> Format<S, B> for PatchsetFormat {
     type Old = ();
     type DeleteData = ();
+    type Delete<T: DynTable>
+        = PatchDelete<T, S, B>
+    where
+        S: AsRef<str>,
+        B: AsRef<[u8]>;
+
+    /// Patchset merges keep every column as merged.
+    fn settle_merged_update(
+        _: &mut [(Self::Old, MaybeValue<S, B>)],
+        _: impl Fn(usize) -> bool,
+    ) -> bool {
+        true
+    }
+
+    /// Patchset DELETEs store no column values.
+    fn widen_delete((): &mut Self::DeleteData, _: usize) {}
 }
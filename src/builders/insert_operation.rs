@@ -7,6 +7,16 @@ use core::fmt::Debug;
 use crate::{DynTable, SchemaWithPK, builders::operation::Indirect, encoding::Value};
 
 #[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "InsertFields<T, S, B>",
+        bound(
+            deserialize = "T: serde::Deserialize<'de>, S: serde::Deserialize<'de>, B: serde::Deserialize<'de>"
+        )
+    )
+)]
 /// Builder for an insert operation.
 pub struct Insert<T: DynTable, S, B> {
     /// The table being inserted into.
@@ -17,6 +27,25 @@ pub struct Insert<T: DynTable, S, B> {
     pub(crate) indirect: bool,
 }
 
+/// The serialized fields of an [`Insert`], checked against the table's
+/// column count on the way back in.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct InsertFields<T, S, B> {
+    table: T,
+    values: Vec<Value<S, B>>,
+    indirect: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<T: DynTable, S, B> TryFrom<InsertFields<T, S, B>> for Insert<T, S, B> {
+    type Error = crate::errors::Error;
+
+    fn try_from(fields: InsertFields<T, S, B>) -> Result<Self, Self::Error> {
+        Ok(Self::try_from_values(fields.table, fields.values)?.indirect(fields.indirect))
+    }
+}
+
 impl<T: DynTable, S: Clone, B: Clone> Clone for Insert<T, S, B> {
     fn clone(&self) -> Self {
         Self {
@@ -57,6 +86,59 @@ impl<T: DynTable, S: Clone, B: Clone> From<T> for Insert<T, S, B> {
     }
 }
 
+impl<T: DynTable, S, B> Insert<T, S, B> {
+    /// Creates an insert from a full row, one value per column.
+    ///
+    /// A fast path for callers whose rows are known to fit the table. A
+    /// length mismatch trips a debug assertion; use
+    /// [`try_from_values`](Self::try_from_values) to validate untrusted rows.
+    #[inline]
+    #[must_use]
+    pub fn from_values(table: T, values: Vec<Value<S, B>>) -> Self {
+        debug_assert_eq!(
+            values.len(),
+            table.number_of_columns(),
+            "row must have one value per column"
+        );
+        Self {
+            table,
+            values,
+            indirect: false,
+        }
+    }
+
+    /// Creates an insert from a full row, checking that it holds one value
+    /// per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{Insert, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let row: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "Alice".into()];
+    /// assert!(Insert::try_from_values(users.clone(), row).is_ok());
+    /// assert!(Insert::<_, String, Vec<u8>>::try_from_values(users, vec![1i64.into()]).is_err());
+    /// ```
+    pub fn try_from_values(
+        table: T,
+        values: Vec<Value<S, B>>,
+    ) -> Result<Self, crate::errors::Error> {
+        let num_cols = table.number_of_columns();
+        if values.len() != num_cols {
+            return Err(crate::errors::Error::ValueCountMismatch(
+                values.len(),
+                num_cols,
+            ));
+        }
+        Ok(Self::from_values(table, values))
+    }
+}
+
 impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> AsRef<T> for Insert<T, S, B> {
     #[inline]
     fn as_ref(&self) -> &T {
@@ -140,6 +222,7 @@ impl<T: DynTable, S, B> Indirect for Insert<T, S, B> {
 #[cfg(test)]
 mod tests {
     use super::Insert;
+    use crate::encoding::Value;
     use crate::errors::Error;
     use crate::schema::SimpleTable;
     use alloc::string::String;
@@ -149,6 +232,31 @@ mod tests {
         SimpleTable::new("users", &["id", "name"], &[0])
     }
 
+    #[test]
+    fn test_insert_try_from_values() {
+        let insert = Insert::<_, String, Vec<u8>>::try_from_values(
+            users(),
+            alloc::vec![1i64.into(), "a".into()],
+        )
+        .unwrap();
+        assert_eq!(
+            insert,
+            Insert::from(users())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap()
+        );
+        for len in [0, 1, 3] {
+            let err = Insert::<_, String, Vec<u8>>::try_from_values(
+                users(),
+                alloc::vec![Value::Null; len],
+            )
+            .unwrap_err();
+            assert_eq!(err, Error::ValueCountMismatch(len, 2));
+        }
+    }
+
     #[test]
     fn test_insert_set_out_of_bounds() {
         let err = Insert::<_, String, Vec<u8>>::from(users())
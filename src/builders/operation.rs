@@ -6,6 +6,7 @@
 
 use alloc::vec::Vec;
 use core::fmt::Debug;
+use core::hash::{Hash, Hasher};
 
 use crate::{
     builders::{ChangesetFormat, PatchsetFormat, format::Format},
@@ -21,6 +22,14 @@ use crate::{
 /// it lives as the key in `DiffSetBuilder`'s
 /// `IndexMap<T, IndexMap<Vec<Value<S, B>>, Operation<F, S, B>>>`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "S: serde::Serialize, B: serde::Serialize, F::Old: serde::Serialize, F::DeleteData: serde::Serialize",
+        deserialize = "S: serde::Deserialize<'de>, B: serde::Deserialize<'de>, F::Old: serde::Deserialize<'de>, F::DeleteData: serde::Deserialize<'de>"
+    ))
+)]
 pub(crate) enum Operation<F: Format<S, B>, S, B> {
     /// A row was inserted. Stores all column values.
     Insert {
@@ -59,6 +68,12 @@ impl<F: Format<S, B>, S, B> Operation<F, S, B> {
             | Self::Update { indirect, .. } => *indirect,
         }
     }
+
+    /// Returns whether this operation is an INSERT.
+    #[inline]
+    pub(crate) fn is_insert(&self) -> bool {
+        matches!(self, Self::Insert { .. })
+    }
 }
 
 impl<F: Format<S, B>, S: PartialEq + AsRef<str>, B: PartialEq + AsRef<[u8]>> PartialEq
@@ -87,6 +102,22 @@ where
 {
 }
 
+impl<F: Format<S, B>, S: AsRef<str> + Hash, B: AsRef<[u8]> + Hash> Hash for Operation<F, S, B>
+where
+    F::DeleteData: Hash,
+    F::Old: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        self.indirect().hash(state);
+        match self {
+            Self::Insert { values, .. } => values.hash(state),
+            Self::Delete { data, .. } => data.hash(state),
+            Self::Update { values, .. } => values.hash(state),
+        }
+    }
+}
+
 /// Builders that carry the SQLite session-extension indirect-change flag.
 ///
 /// Implemented for [`Insert`](crate::Insert), [`Update`](crate::Update),
@@ -124,6 +155,21 @@ impl<S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>> Reverse
     type Output = Self;
 
     fn reverse(self) -> Self::Output {
+        self.reverse_keyed(|_| false)
+    }
+}
+
+impl<S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>>
+    Operation<ChangesetFormat, S, B>
+{
+    /// Returns the reverse of this operation, given which columns of its
+    /// table belong to the primary key.
+    ///
+    /// An UPDATE's key columns keep their old value and drop their new one,
+    /// as `sqlite3changeset_invert` does, unless the UPDATE changed the key:
+    /// the invert would then look the row up by a key it no longer has, so
+    /// the old and new values are swapped instead.
+    pub(crate) fn reverse_keyed(self, is_pk: impl Fn(usize) -> bool) -> Self {
         match self {
             // INSERT reversed becomes DELETE (same values)
             Self::Insert { values, indirect } => Self::Delete {
@@ -135,9 +181,23 @@ impl<S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>> Reverse
                 values: data,
                 indirect,
             },
-            // UPDATE reversed becomes UPDATE with old/new swapped
+            // UPDATE reversed becomes UPDATE with old/new swapped. Unchanged
+            // columns keep their old value in the old slot, as
+            // `sqlite3changeset_invert` does, so primary keys stay locatable.
             Self::Update { values, indirect } => Self::Update {
-                values: values.into_iter().map(|(old, new)| (new, old)).collect(),
+                values: values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(col, (old, new))| match (old, new) {
+                        (Some(old), Some(new))
+                            if is_pk(col) && !crate::values_differ_for_patch(&old, &new) =>
+                        {
+                            (Some(old), None)
+                        }
+                        (old, Some(new)) => (Some(new), old),
+                        (old, None) => (old, None),
+                    })
+                    .collect(),
                 indirect,
             },
         }
@@ -188,12 +248,13 @@ impl<S: Clone + Debug + PartialEq + AsRef<str>, B: Clone + Debug + PartialEq + A
                 indirect,
             }),
 
-            // UPDATE + UPDATE: keep original old, use final new
+            // UPDATE + UPDATE: keep original old, use final new, falling back
+            // to the other operand for columns one of them left undefined
             (Self::Update { values: lhs, .. }, Self::Update { values: rhs, .. }) => {
                 let merged = lhs
                     .into_iter()
                     .zip(rhs)
-                    .map(|((old, _mid), (_mid2, new))| (old, new))
+                    .map(|((old, mid), (mid2, new))| (old.or(mid2), new.or(mid)))
                     .collect();
                 Some(Self::Update {
                     values: merged,
@@ -202,11 +263,13 @@ impl<S: Clone + Debug + PartialEq + AsRef<str>, B: Clone + Debug + PartialEq + A
             }
 
             // UPDATE + DELETE: delete with original old values
-            (Self::Update { values: upd, .. }, Self::Delete { .. }) => {
-                // Collect old values, converting MaybeValue to Value (None becomes Null)
+            (Self::Update { values: upd, .. }, Self::Delete { data: del, .. }) => {
+                // Columns the update did not record are unchanged in the
+                // deleted row
                 let old_values = upd
                     .into_iter()
-                    .map(|(old, _new)| old.unwrap_or(Value::Null))
+                    .zip(del)
+                    .map(|((old, _new), deleted)| old.unwrap_or(deleted))
                     .collect();
                 Some(Self::Delete {
                     data: old_values,
@@ -284,12 +347,13 @@ impl<S: Clone + PartialEq + AsRef<str>, B: Clone + PartialEq + AsRef<[u8]>> core
                 indirect,
             }),
 
-            // UPDATE + UPDATE: keep original old (unit), use final new
+            // UPDATE + UPDATE: keep original old (unit), use final new, falling
+            // back to the first update for columns the second left undefined
             (Self::Update { values: lhs, .. }, Self::Update { values: rhs, .. }) => {
                 let merged = lhs
                     .into_iter()
                     .zip(rhs)
-                    .map(|((old, _mid), (_mid2, new))| (old, new))
+                    .map(|((old, mid), ((), new))| (old, new.or(mid)))
                     .collect();
                 Some(Self::Update {
                     values: merged,
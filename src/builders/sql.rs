@@ -1,11 +1,17 @@
 //! Simplified SQL parser for changeset/patchset operations.
 //!
 //! A lightweight parser that handles only `INSERT`, `UPDATE`, and `DELETE`
-//! statements, which is all the round-trip path needs. A full SQL parser like
+//! statements, which is all the round-trip path needs, plus enough of
+//! `CREATE TABLE` to bootstrap from a `.dump` script. A full SQL parser like
 //! `sqlparser` would be overkill.
 
+mod dump;
 mod lexer;
 mod parser;
 
-pub use parser::ParseError;
-pub(crate) use parser::Parser;
+pub(crate) use dump::digest_dump;
+pub use lexer::LexerError;
+#[cfg(any(test, feature = "testing"))]
+pub(crate) use parser::Coercion;
+pub use parser::{ParseError, RowSource};
+pub(crate) use parser::{Parser, SqlFormat};
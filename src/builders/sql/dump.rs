@@ -0,0 +1,816 @@
+//! Ingestion of the SQL text produced by the `sqlite3` shell's `.dump`.
+//!
+//! A dump is a script of `CREATE TABLE` and `INSERT` statements wrapped in a
+//! `PRAGMA foreign_keys=OFF; BEGIN TRANSACTION; ... COMMIT;` preamble, with
+//! indices, views and triggers appended at the end. Only the tables and their
+//! rows describe data, so:
+//!
+//! * `CREATE TABLE` registers a [`SimpleTable`] with the declared columns and
+//!   primary key. Tables without an explicit `PRIMARY KEY` are skipped along
+//!   with their rows, as the session extension cannot track them either.
+//! * `ALTER TABLE ... ADD COLUMN` appends the column to the registered table,
+//!   and the rows staged before it read NULL there. Every other `ALTER
+//!   TABLE` is rejected, as the rows staged so far could not follow it.
+//! * `INSERT`, `UPDATE` and `DELETE` are digested by the SQL [`Parser`]. An
+//!   `INSERT` leaving the key NULL is keyed on the next rowid when the key
+//!   is an `INTEGER PRIMARY KEY`, which aliases the rowid, and skipped
+//!   otherwise, as the session records no row with a NULL key. The next
+//!   rowid follows the largest key staged so far; an `AUTOINCREMENT` table
+//!   also never reuses the key of a row the script deleted. Rows the
+//!   database held before the script are unknown and not accounted for.
+//!   Statements on the internal `sqlite_*` tables (such as the
+//!   `sqlite_sequence` bookkeeping of `AUTOINCREMENT`) are skipped.
+//! * Transaction control, `PRAGMA`, `ANALYZE` and every other `CREATE` are
+//!   skipped. Any other statement is handed to the parser, which rejects it.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{DiffSetBuilder, DynTable, SimpleTable, builders::operation::Operation};
+
+use super::parser::{ParseError, Parser, SqlFormat};
+
+/// Leading keywords of the statements that carry no row data.
+const SKIPPED_STATEMENTS: [&str; 8] = [
+    "PRAGMA",
+    "BEGIN",
+    "COMMIT",
+    "END",
+    "ROLLBACK",
+    "SAVEPOINT",
+    "RELEASE",
+    "ANALYZE",
+];
+
+/// Digests every data statement of `dump` into `builder`.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if a `CREATE TABLE` or `ALTER TABLE` is
+/// malformed or unsupported, or a data statement cannot be parsed.
+pub(crate) fn digest_dump<'input, F: SqlFormat<String>>(
+    dump: &'input str,
+    builder: &mut DiffSetBuilder<F, SimpleTable, String, Vec<u8>>,
+) -> Result<(), ParseError<'input>>
+where
+    Operation<F, String, Vec<u8>>: core::ops::Add<Output = Option<Operation<F, String, Vec<u8>>>>,
+{
+    let mut untracked: Vec<String> = Vec::new();
+    // The tables whose primary key aliases the rowid, each with the largest
+    // rowid it has used if it is an `AUTOINCREMENT` table.
+    let mut aliased: Vec<(String, Option<i64>)> = Vec::new();
+    let is_skipped = |untracked: &[String], name: &str| {
+        name.get(..7)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("sqlite_"))
+            || untracked
+                .iter()
+                .any(|table| table.eq_ignore_ascii_case(name))
+    };
+    for statement in split_statements(dump) {
+        let mut words = bare_words(statement);
+        let Some(first) = words.next() else {
+            continue;
+        };
+        let target = if first.eq_ignore_ascii_case("CREATE") {
+            let is_table = words
+                .find(|word| {
+                    !word.eq_ignore_ascii_case("TEMP") && !word.eq_ignore_ascii_case("TEMPORARY")
+                })
+                .is_some_and(|word| word.eq_ignore_ascii_case("TABLE"));
+            if is_table {
+                match parse_create_table(statement)? {
+                    (name, Some(table), rowid) => {
+                        builder.add_table(&table);
+                        match rowid {
+                            RowidKey::Distinct => {}
+                            RowidKey::Alias => aliased.push((name, None)),
+                            RowidKey::AutoIncrement => aliased.push((name, Some(0))),
+                        }
+                    }
+                    (name, None, _) => untracked.push(name),
+                }
+            }
+            continue;
+        } else if first.eq_ignore_ascii_case("ALTER") {
+            let (raw_name, name, column) = parse_add_column(statement)?;
+            if !is_skipped(&untracked, &name) {
+                let table = builder
+                    .table_named_ignore_case(&name)
+                    .ok_or(ParseError::UnknownTable(raw_name))?
+                    .clone();
+                if table
+                    .column_names()
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(&column))
+                {
+                    return Err(ParseError::DuplicateColumn(column));
+                }
+                let mut columns: Vec<&str> =
+                    table.column_names().iter().map(String::as_str).collect();
+                columns.push(&column);
+                let widened = SimpleTable::try_new(table.name(), &columns, &table.pk_indices())?;
+                builder.widen_table(&table, widened);
+            }
+            continue;
+        } else if SKIPPED_STATEMENTS
+            .iter()
+            .any(|keyword| first.eq_ignore_ascii_case(keyword))
+        {
+            continue;
+        } else if first.eq_ignore_ascii_case("INSERT") {
+            table_after(statement, "INTO")
+        } else if first.eq_ignore_ascii_case("DELETE") {
+            table_after(statement, "FROM")
+        } else {
+            table_after(statement, "UPDATE")
+        };
+
+        if !target
+            .as_ref()
+            .is_some_and(|name| is_skipped(&untracked, name))
+        {
+            if !first.eq_ignore_ascii_case("INSERT")
+                && let Some(name) = &target
+                && let Some((_, Some(used))) = aliased
+                    .iter_mut()
+                    .find(|(table, _)| table.eq_ignore_ascii_case(name))
+                && let Some(table) = builder.table_named_ignore_case(name)
+            {
+                // The statement may remove the row holding the largest
+                // rowid, which an `AUTOINCREMENT` table still never reuses.
+                *used = (*used).max(builder.next_rowid(table) - 1);
+            }
+            let aliases_rowid = |table: &SimpleTable| {
+                aliased
+                    .iter()
+                    .find(|(name, _)| name == table.name())
+                    .map(|(_, used)| used.map_or(1, |used| used.saturating_add(1)))
+            };
+            Parser::new(statement, builder)
+                .with_rowid_alias(&aliases_rowid)
+                .digest_all()?;
+        }
+    }
+    Ok(())
+}
+
+/// Splits `sql` into statements at top-level semicolons.
+///
+/// Semicolons inside quotes, comments and `CREATE TRIGGER ... END` bodies do
+/// not end a statement. The returned statements exclude the semicolon.
+fn split_statements(sql: &str) -> impl Iterator<Item = &str> {
+    let bytes = sql.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => pos = skip_past(bytes, pos + 1, quote),
+            b'[' => pos = skip_past(bytes, pos + 1, b']'),
+            b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                pos = skip_past(bytes, pos + 2, b'\n');
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = sql[pos + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| pos + 2 + end + 2);
+            }
+            b';' => {
+                let statement = &sql[start..pos];
+                pos += 1;
+                if !is_open_trigger(statement) {
+                    statements.push(statement);
+                    start = pos;
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+    statements.push(&sql[start..]);
+    statements
+        .into_iter()
+        .filter(|statement| bare_words(statement).next().is_some())
+}
+
+/// Returns the position just past the next `close` byte at or after `pos`.
+fn skip_past(bytes: &[u8], pos: usize, close: u8) -> usize {
+    bytes[pos.min(bytes.len())..]
+        .iter()
+        .position(|&b| b == close)
+        .map_or(bytes.len(), |offset| pos + offset + 1)
+}
+
+/// Returns `true` if `statement` is a `CREATE TRIGGER` whose body has not
+/// reached its closing `END` yet.
+fn is_open_trigger(statement: &str) -> bool {
+    let mut words = bare_words(statement);
+    let is_trigger = words
+        .next()
+        .is_some_and(|w| w.eq_ignore_ascii_case("CREATE"))
+        && words
+            .find(|w| !w.eq_ignore_ascii_case("TEMP") && !w.eq_ignore_ascii_case("TEMPORARY"))
+            .is_some_and(|w| w.eq_ignore_ascii_case("TRIGGER"));
+    is_trigger
+        && !bare_words(statement)
+            .last()
+            .is_some_and(|w| w.eq_ignore_ascii_case("END"))
+}
+
+/// Iterates over the unquoted words of `sql`, skipping comments, quoted
+/// strings and identifiers, and punctuation.
+fn bare_words(sql: &str) -> impl Iterator<Item = &str> {
+    let bytes = sql.as_bytes();
+    let mut pos = 0;
+    core::iter::from_fn(move || {
+        while pos < bytes.len() {
+            let b = bytes[pos];
+            match b {
+                quote @ (b'\'' | b'"' | b'`') => pos = skip_past(bytes, pos + 1, quote),
+                b'[' => pos = skip_past(bytes, pos + 1, b']'),
+                b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                    pos = skip_past(bytes, pos + 2, b'\n');
+                }
+                b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                    pos = sql[pos + 2..]
+                        .find("*/")
+                        .map_or(bytes.len(), |end| pos + 2 + end + 2);
+                }
+                _ if is_word_byte(b) => {
+                    let start = pos;
+                    while pos < bytes.len() && is_word_byte(bytes[pos]) {
+                        pos += 1;
+                    }
+                    return Some(&sql[start..pos]);
+                }
+                _ => pos += 1,
+            }
+        }
+        None
+    })
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+/// Returns the leading identifier of `sql`, unquoted, and the rest of the
+/// text after it.
+fn leading_identifier(sql: &str) -> Option<(String, &str)> {
+    let sql = sql.trim_start();
+    let bytes = sql.as_bytes();
+    let end = match *bytes.first()? {
+        quote @ (b'"' | b'`' | b'\'') => skip_past(bytes, 1, quote),
+        b'[' => skip_past(bytes, 1, b']'),
+        b if is_word_byte(b) => bytes
+            .iter()
+            .position(|&b| !is_word_byte(b))
+            .unwrap_or(bytes.len()),
+        _ => return None,
+    };
+    Some((unquote(&sql[..end]), &sql[end..]))
+}
+
+/// Strips the quotes around an identifier, undoubling escaped quotes.
+fn unquote(identifier: &str) -> String {
+    let bytes = identifier.as_bytes();
+    match (bytes.first(), bytes.last()) {
+        (Some(&b'['), Some(&b']')) if bytes.len() >= 2 => {
+            identifier[1..identifier.len() - 1].into()
+        }
+        (Some(&open @ (b'"' | b'`' | b'\'')), Some(&close))
+            if bytes.len() >= 2 && open == close =>
+        {
+            let quote = open as char;
+            identifier[1..identifier.len() - 1].replace(
+                &alloc::format!("{quote}{quote}"),
+                &alloc::format!("{quote}"),
+            )
+        }
+        _ => identifier.into(),
+    }
+}
+
+/// Returns the unquoted table name following `keyword` in `statement`,
+/// without the schema name qualifying it, if any.
+fn table_after(statement: &str, keyword: &str) -> Option<String> {
+    let mut rest = statement.trim_start();
+    while let Some((word, tail)) = leading_identifier(rest) {
+        if word.eq_ignore_ascii_case(keyword) {
+            let (name, tail) = leading_identifier(tail)?;
+            return match tail.trim_start().strip_prefix('.') {
+                Some(qualified) => leading_identifier(qualified).map(|(table, _)| table),
+                None => Some(name),
+            };
+        }
+        rest = tail.trim_start();
+    }
+    None
+}
+
+/// How a table's primary key relates to its rowid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowidKey {
+    /// The key is stored apart from the rowid.
+    Distinct,
+    /// The key aliases the rowid.
+    Alias,
+    /// The key aliases the rowid, which is declared `AUTOINCREMENT` and so
+    /// never reused.
+    AutoIncrement,
+}
+
+/// Parses a `CREATE TABLE` statement into its name, its schema if it
+/// declares a primary key, and how that key relates to the rowid.
+///
+/// A key aliases the rowid when it is a single column declared with the
+/// type `INTEGER` exactly, as in `id INTEGER PRIMARY KEY`, in a rowid table.
+/// `INT PRIMARY KEY`, `INTEGER PRIMARY KEY DESC` on the column itself, and
+/// any key of a `WITHOUT ROWID` table do not. Other column types,
+/// constraints other than `PRIMARY KEY` and `AUTOINCREMENT`, and table
+/// options such as `STRICT` are ignored.
+fn parse_create_table(
+    statement: &str,
+) -> Result<(String, Option<SimpleTable>, RowidKey), ParseError<'_>> {
+    // Skip `CREATE [TEMP] TABLE [IF NOT EXISTS]` to reach the table name.
+    let mut rest = statement;
+    loop {
+        let (word, tail) = leading_identifier(rest).ok_or(ParseError::UnexpectedEof {
+            expected: "table name",
+        })?;
+        rest = tail;
+        if word.eq_ignore_ascii_case("TABLE") {
+            break;
+        }
+    }
+    let (mut name, mut tail) = leading_identifier(rest).ok_or(ParseError::UnexpectedEof {
+        expected: "table name",
+    })?;
+    if name.eq_ignore_ascii_case("IF") {
+        for _ in 0..3 {
+            (name, tail) = leading_identifier(tail).ok_or(ParseError::UnexpectedEof {
+                expected: "table name",
+            })?;
+        }
+    }
+    // Drop the schema qualifier of `main.users`.
+    if let Some(unqualified) = tail.trim_start().strip_prefix('.') {
+        (name, tail) = leading_identifier(unqualified).ok_or(ParseError::UnexpectedEof {
+            expected: "table name",
+        })?;
+    }
+    let (body, options) = tail
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|body| {
+            let end = matching_paren(body)?;
+            Some((&body[..end], &body[end + 1..]))
+        })
+        .ok_or(ParseError::UnexpectedEof { expected: ")" })?;
+
+    let mut columns: Vec<String> = Vec::new();
+    // Per column, whether its declared type is exactly `INTEGER`.
+    let mut integer_columns: Vec<bool> = Vec::new();
+    let mut pk_indices: Vec<usize> = Vec::new();
+    let mut pk_columns: Vec<String> = Vec::new();
+    let mut descending_column_key = false;
+    let mut autoincrement = false;
+    for definition in split_top_level(body) {
+        let Some((first, after)) = leading_identifier(definition) else {
+            continue;
+        };
+        let is_constraint = ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"]
+            .iter()
+            .any(|keyword| first.eq_ignore_ascii_case(keyword))
+            && !definition.trim_start().starts_with(['"', '`', '[', '\'']);
+        if is_constraint {
+            if let Some(list) = primary_key_list(definition) {
+                pk_columns.extend(
+                    split_top_level(list)
+                        .filter_map(|entry| leading_identifier(entry).map(|(column, _)| column)),
+                );
+            }
+            continue;
+        }
+        if columns
+            .iter()
+            .any(|column| column.eq_ignore_ascii_case(&first))
+        {
+            return Err(ParseError::DuplicateColumn(first));
+        }
+        if declares_primary_key(after) {
+            pk_indices.push(columns.len());
+            descending_column_key |= declares_descending_key(after);
+            autoincrement |= declares_autoincrement(after);
+        }
+        columns.push(first);
+        integer_columns.push(declares_integer_type(after));
+    }
+    if columns.is_empty() {
+        return Err(ParseError::EmptyColumnList);
+    }
+    for column in pk_columns {
+        let idx = columns
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(&column))
+            .ok_or(ParseError::UnknownPKColumn { column })?;
+        pk_indices.push(idx);
+    }
+    if pk_indices.is_empty() {
+        return Ok((name, None, RowidKey::Distinct));
+    }
+
+    let mut words = bare_words(options);
+    let without_rowid = words.any(|word| word.eq_ignore_ascii_case("WITHOUT"))
+        && words
+            .next()
+            .is_some_and(|word| word.eq_ignore_ascii_case("ROWID"));
+    let aliases = matches!(pk_indices[..], [pk] if integer_columns[pk]);
+    let rowid = match (
+        aliases && !descending_column_key && !without_rowid,
+        autoincrement,
+    ) {
+        (false, _) => RowidKey::Distinct,
+        (true, false) => RowidKey::Alias,
+        (true, true) => RowidKey::AutoIncrement,
+    };
+    let column_refs: Vec<&str> = columns.iter().map(String::as_str).collect();
+    let table = SimpleTable::try_new(name.clone(), &column_refs, &pk_indices)?;
+    Ok((name, Some(table), rowid))
+}
+
+/// Parses an `ALTER TABLE [schema.]table ADD [COLUMN] column ...` statement
+/// into the table name as written, the unquoted table name and the unquoted
+/// name of the added column.
+///
+/// The column's type and constraints are ignored, except that adding a
+/// `PRIMARY KEY` column, which `SQLite` refuses, is rejected like every other
+/// `ALTER TABLE` action.
+fn parse_add_column(statement: &str) -> Result<(&str, String, String), ParseError<'_>> {
+    let unsupported = ParseError::UnsupportedAlterTable(statement.trim());
+    // Skip `ALTER TABLE` to reach the table name.
+    let mut rest = statement;
+    for _ in 0..2 {
+        (_, rest) = leading_identifier(rest).ok_or(ParseError::UnexpectedEof {
+            expected: "table name",
+        })?;
+    }
+    let eof = ParseError::UnexpectedEof {
+        expected: "table name",
+    };
+    let (mut name, mut tail) = leading_identifier(rest).ok_or(eof.clone())?;
+    // Drop the schema qualifier of `main.users`.
+    if let Some(unqualified) = tail.trim_start().strip_prefix('.') {
+        rest = unqualified;
+        (name, tail) = leading_identifier(unqualified).ok_or(eof)?;
+    }
+    let written = rest.trim_start();
+    let raw_name = &written[..written.len() - tail.len()];
+
+    let (action, mut tail) = leading_identifier(tail).ok_or(ParseError::UnexpectedEof {
+        expected: "ADD COLUMN",
+    })?;
+    if !action.eq_ignore_ascii_case("ADD") {
+        return Err(unsupported);
+    }
+    let eof = ParseError::UnexpectedEof {
+        expected: "column name",
+    };
+    let (mut column, mut definition) = leading_identifier(tail).ok_or(eof.clone())?;
+    if column.eq_ignore_ascii_case("COLUMN")
+        && !tail.trim_start().starts_with(['"', '`', '[', '\''])
+    {
+        tail = definition;
+        (column, definition) = leading_identifier(tail).ok_or(eof)?;
+    }
+    if declares_primary_key(definition) {
+        return Err(unsupported);
+    }
+    Ok((raw_name, name, column))
+}
+
+/// Returns the offset of the `)` closing a parenthesis opened just before
+/// `body`.
+fn matching_paren(body: &str) -> Option<usize> {
+    let bytes = body.as_bytes();
+    let mut depth = 0usize;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => pos = skip_past(bytes, pos + 1, quote),
+            b'[' => pos = skip_past(bytes, pos + 1, b']'),
+            b'(' => {
+                depth += 1;
+                pos += 1;
+            }
+            b')' if depth == 0 => return Some(pos),
+            b')' => {
+                depth -= 1;
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+/// Splits `list` at commas outside parentheses and quotes.
+fn split_top_level(list: &str) -> impl Iterator<Item = &str> {
+    let bytes = list.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => pos = skip_past(bytes, pos + 1, quote),
+            b'[' => pos = skip_past(bytes, pos + 1, b']'),
+            b'(' => {
+                depth += 1;
+                pos += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                pos += 1;
+            }
+            b',' if depth == 0 => {
+                parts.push(&list[start..pos]);
+                pos += 1;
+                start = pos;
+            }
+            _ => pos += 1,
+        }
+    }
+    parts.push(&list[start..]);
+    parts.into_iter()
+}
+
+/// Returns `true` if a column's constraints contain `PRIMARY KEY`.
+fn declares_primary_key(constraints: &str) -> bool {
+    let mut words = bare_words(constraints).peekable();
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("PRIMARY")
+            && words.peek().is_some_and(|w| w.eq_ignore_ascii_case("KEY"))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns whether the `PRIMARY KEY` among the constraints of a column
+/// definition is declared `DESC`.
+fn declares_descending_key(constraints: &str) -> bool {
+    let mut words = bare_words(constraints);
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("KEY") {
+            return words
+                .next()
+                .is_some_and(|word| word.eq_ignore_ascii_case("DESC"));
+        }
+    }
+    false
+}
+
+/// Returns whether the constraints of a column definition declare
+/// `AUTOINCREMENT`.
+fn declares_autoincrement(constraints: &str) -> bool {
+    bare_words(constraints).any(|word| word.eq_ignore_ascii_case("AUTOINCREMENT"))
+}
+
+/// Returns whether a column definition, after the column name, declares the
+/// type `INTEGER` and nothing more, such as `INTEGER(10)` or `INTEGER
+/// UNSIGNED`.
+fn declares_integer_type(definition: &str) -> bool {
+    const CONSTRAINTS: [&str; 11] = [
+        "CONSTRAINT",
+        "PRIMARY",
+        "NOT",
+        "NULL",
+        "UNIQUE",
+        "CHECK",
+        "DEFAULT",
+        "COLLATE",
+        "REFERENCES",
+        "GENERATED",
+        "AS",
+    ];
+    let mut words = bare_words(definition);
+    words
+        .next()
+        .is_some_and(|word| word.eq_ignore_ascii_case("INTEGER"))
+        && words.next().is_none_or(|word| {
+            CONSTRAINTS
+                .iter()
+                .any(|keyword| word.eq_ignore_ascii_case(keyword))
+        })
+}
+
+/// Returns the column list of a `PRIMARY KEY (...)` table constraint.
+fn primary_key_list(constraint: &str) -> Option<&str> {
+    let mut rest = constraint;
+    let mut previous_is_primary = false;
+    while let Some((word, tail)) = leading_identifier(rest) {
+        if previous_is_primary && word.eq_ignore_ascii_case("KEY") {
+            let inner = tail.trim_start().strip_prefix('(')?;
+            return Some(&inner[..matching_paren(inner)?]);
+        }
+        previous_is_primary = word.eq_ignore_ascii_case("PRIMARY");
+        rest = tail.trim_start();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChangeSet, DiffOps, SchemaWithPK};
+
+    #[test]
+    fn test_split_statements_keeps_quoted_semicolons() {
+        let statements: Vec<&str> =
+            split_statements("INSERT INTO t VALUES(1,'a;b'); -- c;\nCOMMIT;").collect();
+        assert_eq!(
+            statements,
+            ["INSERT INTO t VALUES(1,'a;b')", " -- c;\nCOMMIT"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_keeps_trigger_bodies() {
+        let statements: Vec<&str> = split_statements(
+            "CREATE TRIGGER t_ai AFTER INSERT ON t BEGIN UPDATE t SET v = 1; END;\nCOMMIT;",
+        )
+        .collect();
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("END"));
+    }
+
+    #[test]
+    fn test_parse_create_table_composite_key() {
+        let (name, table, rowid) = parse_create_table(
+            "CREATE TABLE IF NOT EXISTS \"order items\" (\n  order_id INTEGER NOT NULL,\n  \
+             sku TEXT CHECK(length(sku) > 0),\n  qty INTEGER DEFAULT 1,\n  \
+             PRIMARY KEY (sku, order_id)\n) WITHOUT ROWID",
+        )
+        .unwrap();
+        let table = table.unwrap();
+        assert_eq!(name, "order items");
+        assert_eq!(table.column_index("qty"), Some(2));
+        assert_eq!(table.primary_key_index(1), Some(0));
+        assert_eq!(table.primary_key_index(0), Some(1));
+        assert_eq!(rowid, RowidKey::Distinct);
+    }
+
+    #[test]
+    fn test_parse_create_table_without_primary_key() {
+        let (name, table, _) =
+            parse_create_table("CREATE TABLE log(msg TEXT, at INTEGER)").unwrap();
+        assert_eq!(name, "log");
+        assert!(table.is_none());
+    }
+
+    #[test]
+    fn test_parse_create_table_rowid_alias() {
+        let rowid = |statement| parse_create_table(statement).unwrap().2;
+        let aliases = |statement| rowid(statement) != RowidKey::Distinct;
+        assert_eq!(
+            rowid("CREATE TABLE t(id INTEGER PRIMARY KEY, v TEXT)"),
+            RowidKey::Alias
+        );
+        assert_eq!(
+            rowid("CREATE TABLE t(id integer primary key asc autoincrement)"),
+            RowidKey::AutoIncrement
+        );
+        assert!(aliases(
+            "CREATE TABLE t(id INTEGER NOT NULL, v TEXT, PRIMARY KEY(id DESC))"
+        ));
+        assert!(!aliases("CREATE TABLE t(id INT PRIMARY KEY, v TEXT)"));
+        assert!(!aliases(
+            "CREATE TABLE t(id INTEGER PRIMARY KEY DESC, v TEXT)"
+        ));
+        assert!(!aliases("CREATE TABLE t(id INTEGER(10) PRIMARY KEY)"));
+        assert!(!aliases(
+            "CREATE TABLE t(id INTEGER PRIMARY KEY) WITHOUT ROWID"
+        ));
+        assert!(!aliases(
+            "CREATE TABLE t(a INTEGER, b INTEGER, PRIMARY KEY(a, b))"
+        ));
+    }
+
+    #[test]
+    fn test_parse_create_table_errors() {
+        assert_eq!(
+            parse_create_table("CREATE TABLE t(a INTEGER PRIMARY KEY, A TEXT)"),
+            Err(ParseError::DuplicateColumn("A".into()))
+        );
+        assert_eq!(
+            parse_create_table("CREATE TABLE t(a INTEGER, PRIMARY KEY(b))"),
+            Err(ParseError::UnknownPKColumn { column: "b".into() })
+        );
+        assert_eq!(
+            parse_create_table("CREATE TABLE t(a INTEGER PRIMARY KEY"),
+            Err(ParseError::UnexpectedEof { expected: ")" })
+        );
+        assert_eq!(
+            parse_create_table("CREATE TABLE t (a, PRIMARY KEY (a, a))"),
+            Err(ParseError::InvalidTable(
+                crate::SimpleTableError::DuplicatePrimaryKeyIndex(0)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_add_column_widens_staged_rows() {
+        let dump = "CREATE TABLE users(id INTEGER PRIMARY KEY, name TEXT);
+            INSERT INTO users VALUES(1,'Alice');
+            ALTER TABLE main.\"users\" ADD COLUMN email TEXT DEFAULT NULL;
+            INSERT INTO users VALUES(2,'Bob','bob@example.com');";
+        let digested: ChangeSet<SimpleTable, String, Vec<u8>> =
+            ChangeSet::from_sqlite_dump(dump).unwrap();
+
+        let users = SimpleTable::new("users", &["id", "name", "email"], &[0]);
+        let expected: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(
+                crate::Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "Alice")
+                    .unwrap(),
+            )
+            .insert(
+                crate::Insert::from(users)
+                    .set(0, 2i64)
+                    .unwrap()
+                    .set(1, "Bob")
+                    .unwrap()
+                    .set(2, "bob@example.com")
+                    .unwrap(),
+            );
+        assert_eq!(digested.build(), expected.build());
+    }
+
+    #[test]
+    fn test_add_column_matches_table_name_ignoring_case() {
+        let dump = "CREATE TABLE users(id INTEGER PRIMARY KEY);
+            ALTER TABLE USERS ADD COLUMN email TEXT;
+            INSERT INTO users VALUES(1,'alice@example.com');";
+        let digested: ChangeSet<SimpleTable, String, Vec<u8>> =
+            ChangeSet::from_sqlite_dump(dump).unwrap();
+        let users = digested.table_named("users").unwrap();
+        assert_eq!(users.name(), "users");
+        assert_eq!(users.column_names(), ["id", "email"]);
+        assert_eq!(digested.len(), 1);
+    }
+
+    #[test]
+    fn test_add_column_without_column_keyword_on_untracked_table() {
+        let dump = "CREATE TABLE log(msg TEXT);
+            ALTER TABLE log ADD at INTEGER;
+            CREATE TABLE t(id INTEGER PRIMARY KEY);
+            ALTER TABLE t ADD \"column\" TEXT;
+            INSERT INTO t VALUES(1,'x');";
+        let digested: ChangeSet<SimpleTable, String, Vec<u8>> =
+            ChangeSet::from_sqlite_dump(dump).unwrap();
+        assert_eq!(digested.len(), 1);
+        assert_eq!(
+            digested.table_named("t").unwrap().column_names(),
+            ["id", "column"]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_alter_table_is_rejected() {
+        let create = "CREATE TABLE t(id INTEGER PRIMARY KEY, v TEXT);";
+        for alter in [
+            "ALTER TABLE t RENAME TO u",
+            "ALTER TABLE t RENAME COLUMN v TO w",
+            "ALTER TABLE t DROP COLUMN v",
+            "ALTER TABLE t ADD COLUMN k INTEGER PRIMARY KEY",
+        ] {
+            let mut builder: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+            assert_eq!(
+                digest_dump(&alloc::format!("{create}\n{alter};"), &mut builder),
+                Err(ParseError::UnsupportedAlterTable(alter))
+            );
+        }
+
+        let mut builder: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        assert_eq!(
+            digest_dump("ALTER TABLE missing ADD COLUMN v TEXT;", &mut builder),
+            Err(ParseError::UnknownTable("missing"))
+        );
+        assert_eq!(
+            digest_dump(
+                &alloc::format!("{create} ALTER TABLE t ADD V TEXT;"),
+                &mut builder
+            ),
+            Err(ParseError::DuplicateColumn("V".into()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_statement_is_rejected() {
+        let mut builder: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        assert!(digest_dump("VACUUM;", &mut builder).is_err());
+    }
+}
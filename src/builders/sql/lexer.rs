@@ -52,6 +52,20 @@ pub(super) enum TokenKind<'input> {
     Blob,
     /// NOT keyword
     Not,
+    /// IS keyword
+    Is,
+    /// ON keyword
+    On,
+    /// CONFLICT keyword
+    Conflict,
+    /// DO keyword
+    Do,
+    /// NOTHING keyword
+    Nothing,
+    /// RETURNING keyword
+    Returning,
+    /// IN keyword
+    In,
 
     // Literals
     /// Integer literal
@@ -66,6 +80,8 @@ pub(super) enum TokenKind<'input> {
     // Identifiers
     /// An identifier (table name, column name, etc.)
     Identifier(&'input str),
+    /// An identifier quoted with brackets or backticks, unquoted
+    QuotedIdentifier(Cow<'input, str>),
 
     // Symbols
     /// Left parenthesis
@@ -80,6 +96,8 @@ pub(super) enum TokenKind<'input> {
     Equals,
     /// Minus sign
     Minus,
+    /// Dot, separating a schema name from a table name
+    Dot,
 
     // Special
     /// End of input
@@ -111,17 +129,25 @@ impl TokenKind<'_> {
             TokenKind::Text => "TEXT",
             TokenKind::Blob => "BLOB",
             TokenKind::Not => "NOT",
+            TokenKind::Is => "IS",
+            TokenKind::On => "ON",
+            TokenKind::Conflict => "CONFLICT",
+            TokenKind::Do => "DO",
+            TokenKind::Nothing => "NOTHING",
+            TokenKind::Returning => "RETURNING",
+            TokenKind::In => "IN",
             TokenKind::IntegerLiteral(_) => "<integer>",
             TokenKind::RealLiteral(_) => "<real>",
             TokenKind::StringLiteral(_) => "<string>",
             TokenKind::BlobLiteral(_) => "<blob>",
-            TokenKind::Identifier(_) => "<identifier>",
+            TokenKind::Identifier(_) | TokenKind::QuotedIdentifier(_) => "<identifier>",
             TokenKind::LParen => "(",
             TokenKind::RParen => ")",
             TokenKind::Comma => ",",
             TokenKind::Semicolon => ";",
             TokenKind::Equals => "=",
             TokenKind::Minus => "-",
+            TokenKind::Dot => ".",
             TokenKind::Eof => "<eof>",
         }
     }
@@ -148,9 +174,16 @@ impl AsRef<str> for TokenKind<'_> {
             TokenKind::Text => "TEXT",
             TokenKind::Blob => "BLOB",
             TokenKind::Not => "NOT",
+            TokenKind::Is => "IS",
+            TokenKind::On => "ON",
+            TokenKind::Conflict => "CONFLICT",
+            TokenKind::Do => "DO",
+            TokenKind::Nothing => "NOTHING",
+            TokenKind::Returning => "RETURNING",
+            TokenKind::In => "IN",
             TokenKind::IntegerLiteral(_) => "<integer>",
             TokenKind::RealLiteral(_) => "<real>",
-            TokenKind::StringLiteral(s) => s.as_ref(),
+            TokenKind::StringLiteral(s) | TokenKind::QuotedIdentifier(s) => s.as_ref(),
             TokenKind::BlobLiteral(_) => "<blob>",
             TokenKind::Identifier(s) => s,
             TokenKind::LParen => "(",
@@ -159,6 +192,7 @@ impl AsRef<str> for TokenKind<'_> {
             TokenKind::Semicolon => ";",
             TokenKind::Equals => "=",
             TokenKind::Minus => "-",
+            TokenKind::Dot => ".",
             TokenKind::Eof => "<eof>",
         }
     }
@@ -198,6 +232,36 @@ impl<'input> Lexer<'input> {
         self.next_token()
     }
 
+    /// The spelling in the input of `token`, the token [`Lexer::next`] has
+    /// just returned.
+    pub(super) fn spelling(&self, token: &Token<'input>) -> &'input str {
+        &self.input[token.pos..self.pos]
+    }
+
+    /// Skip the raw input up to the next `;` outside a quoted string, or to
+    /// the end of input, without tokenizing it.
+    ///
+    /// Used for clauses whose contents are ignored, such as `RETURNING`,
+    /// which may hold expressions the lexer has no tokens for.
+    pub(super) fn skip_to_statement_end(&mut self) {
+        let bytes = self.input.as_bytes();
+        if let Some(token) = self.peeked.take() {
+            self.pos = token.pos;
+        }
+        let mut quote = None;
+        while self.pos < bytes.len() {
+            let b = bytes[self.pos];
+            quote = match quote {
+                // A doubled quote closes and immediately reopens the string.
+                Some(q) if b == q => None,
+                None if b == b';' => break,
+                None if b == b'\'' || b == b'"' => Some(b),
+                unchanged => unchanged,
+            };
+            self.pos += 1;
+        }
+    }
+
     /// Skip whitespace and comments.
     fn skip_whitespace(&mut self) {
         let bytes = self.input.as_bytes();
@@ -269,7 +333,24 @@ impl<'input> Lexer<'input> {
                 self.pos += 1;
                 TokenKind::Minus
             }
-            b'\'' | b'"' => return self.read_string(start_pos),
+            b'.' => {
+                self.pos += 1;
+                TokenKind::Dot
+            }
+            b'\'' | b'"' => {
+                let value = self.read_quoted(start_pos, b)?;
+                TokenKind::StringLiteral(value)
+            }
+            b'`' => TokenKind::QuotedIdentifier(self.read_quoted(start_pos, b'`')?),
+            // Brackets have no escape: the identifier ends at the first `]`.
+            b'[' => {
+                let Some(len) = self.input[self.pos + 1..].find(']') else {
+                    return Err(LexerError::UnterminatedString { pos: start_pos });
+                };
+                let name = &self.input[self.pos + 1..self.pos + 1 + len];
+                self.pos += len + 2;
+                TokenKind::QuotedIdentifier(Cow::Borrowed(name))
+            }
             b'X' | b'x' if self.pos + 1 < bytes.len() && bytes[self.pos + 1] == b'\'' => {
                 return self.read_blob(start_pos);
             }
@@ -289,9 +370,9 @@ impl<'input> Lexer<'input> {
         })
     }
 
-    fn read_string(&mut self, start_pos: usize) -> Result<Token<'input>, LexerError> {
+    /// Read the text quoted up to `quote`, undoubling any doubled `quote`.
+    fn read_quoted(&mut self, start_pos: usize, quote: u8) -> Result<Cow<'input, str>, LexerError> {
         let bytes = self.input.as_bytes();
-        let quote = bytes[self.pos];
         self.pos += 1;
 
         let start = self.pos;
@@ -306,16 +387,12 @@ impl<'input> Lexer<'input> {
                 } else {
                     let raw = &self.input[start..self.pos];
                     self.pos += 1;
-                    let value = if has_escape {
+                    return Ok(if has_escape {
                         let q = quote as char;
                         let doubled = alloc::format!("{q}{q}");
                         Cow::Owned(raw.replace(&doubled, &alloc::format!("{q}")))
                     } else {
                         Cow::Borrowed(raw)
-                    };
-                    return Ok(Token {
-                        kind: TokenKind::StringLiteral(value),
-                        pos: start_pos,
                     });
                 }
             } else {
@@ -465,6 +542,13 @@ impl<'input> Lexer<'input> {
             "TEXT" => TokenKind::Text,
             "BLOB" => TokenKind::Blob,
             "NOT" => TokenKind::Not,
+            "IS" => TokenKind::Is,
+            "ON" => TokenKind::On,
+            "CONFLICT" => TokenKind::Conflict,
+            "DO" => TokenKind::Do,
+            "NOTHING" => TokenKind::Nothing,
+            "RETURNING" => TokenKind::Returning,
+            "IN" => TokenKind::In,
             _ => TokenKind::Identifier(ident),
         };
 
@@ -475,14 +559,15 @@ impl<'input> Lexer<'input> {
     }
 }
 
-/// Check if a byte can start an identifier.
+/// Check if a byte can start an identifier. Every byte of a non-ASCII
+/// character can, as in `SQLite`.
 fn is_ident_start(b: u8) -> bool {
-    b.is_ascii_alphabetic() || b == b'_'
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
 }
 
 /// Check if a byte can continue an identifier.
 fn is_ident_cont(b: u8) -> bool {
-    b.is_ascii_alphanumeric() || b == b'_'
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
 }
 
 use alloc::string::ToString;
@@ -581,6 +666,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quoted_identifiers() {
+        let mut lexer = Lexer::new("[order items] `a``b` main.t");
+        assert_eq!(
+            lexer.next().unwrap().kind,
+            TokenKind::QuotedIdentifier("order items".into())
+        );
+        assert_eq!(
+            lexer.next().unwrap().kind,
+            TokenKind::QuotedIdentifier("a`b".into())
+        );
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Identifier("main"));
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Dot);
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Identifier("t"));
+    }
+
     #[test]
     fn test_blob() {
         let mut lexer = Lexer::new("X'DEADBEEF'");
@@ -621,6 +722,13 @@ mod tests {
             (TokenKind::Text, "TEXT"),
             (TokenKind::Blob, "BLOB"),
             (TokenKind::Not, "NOT"),
+            (TokenKind::Is, "IS"),
+            (TokenKind::On, "ON"),
+            (TokenKind::Conflict, "CONFLICT"),
+            (TokenKind::Do, "DO"),
+            (TokenKind::Nothing, "NOTHING"),
+            (TokenKind::Returning, "RETURNING"),
+            (TokenKind::In, "IN"),
             (TokenKind::IntegerLiteral(0), "<integer>"),
             (TokenKind::RealLiteral(0.0), "<real>"),
             (TokenKind::BlobLiteral(vec![]), "<blob>"),
@@ -630,6 +738,7 @@ mod tests {
             (TokenKind::Semicolon, ";"),
             (TokenKind::Equals, "="),
             (TokenKind::Minus, "-"),
+            (TokenKind::Dot, "."),
             (TokenKind::Eof, "<eof>"),
         ]
     }
@@ -671,6 +780,15 @@ mod tests {
         assert_eq!(<TokenKind<'_> as AsRef<str>>::as_ref(&i), "user_table");
     }
 
+    #[test]
+    fn test_skip_to_statement_end_ignores_quoted_semicolons() {
+        let mut lexer = Lexer::new("RETURNING *, 'a;b', \"c\"\"d;\" ; DELETE");
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Returning);
+        lexer.skip_to_statement_end();
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Semicolon);
+        assert_eq!(lexer.next().unwrap().kind, TokenKind::Delete);
+    }
+
     #[test]
     fn test_line_comment_skipped() {
         let mut lexer = Lexer::new("-- a comment\nINSERT");
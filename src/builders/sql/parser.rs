@@ -1,9 +1,12 @@
 //! SQL parser for changeset/patchset operations.
 
+use core::fmt::Debug;
 use core::hash::Hash;
 
 use crate::{
-    DiffSetBuilder, PatchsetFormat, SchemaWithPK, Value, builders::operation::Operation,
+    ChangesetFormat, DiffSetBuilder, PatchsetFormat, SchemaWithPK, Value,
+    builders::{format::Format, operation::Operation},
+    encoding::serial::compare_integer_real,
     schema::NamedColumns,
 };
 use alloc::borrow::Cow;
@@ -65,29 +68,427 @@ pub enum ParseError<'a> {
         /// The column name.
         column: &'a str,
     },
+    /// `ALTER TABLE` statement other than an `ADD COLUMN` of a non-key
+    /// column.
+    #[error("Unsupported ALTER TABLE statement: {0}")]
+    UnsupportedAlterTable(&'a str),
+    /// `CREATE TABLE` or `ALTER TABLE` describing a table that
+    /// [`SimpleTable::try_new`](crate::SimpleTable::try_new) rejects, such as
+    /// one listing a column twice in its primary key.
+    #[error("Invalid table definition: {0}")]
+    InvalidTable(#[from] crate::SimpleTableError),
 }
 
+/// A `WHERE` clause, one slot per table column holding the column name as
+/// written in the input and the value it was compared against.
+type WhereClause<'input, S> = Vec<Option<(&'input str, Value<S, Vec<u8>>)>>;
+
+/// An operation paired with the primary key of the row it affects.
+type KeyedOperation<F, S> = (Vec<Value<S, Vec<u8>>>, Operation<F, S, Vec<u8>>);
+
+/// Format-specific construction of operations from parsed SQL.
+///
+/// `INSERT` carries the full row in both formats, so only `UPDATE` and
+/// `DELETE` differ: a patchset keeps the primary key alone, while a changeset
+/// also records whatever old values the statement exposes.
+pub(crate) trait SqlFormat<S>: Format<S, Vec<u8>> {
+    /// Build an UPDATE from its `SET` assignments and `WHERE` clause,
+    /// returning the primary key of the affected row and the operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::WhereNonPKColumn`] if the `WHERE` clause
+    /// constrains a column the format cannot record.
+    fn update_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        set: Vec<Option<Value<S, Vec<u8>>>>,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>>;
+
+    /// Build a DELETE from its `WHERE` clause, returning the primary key of
+    /// the affected row and the operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::WhereNonPKColumn`] if the `WHERE` clause
+    /// constrains a column the format cannot record.
+    fn delete_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>>;
+
+    /// Build the operations recording a row going from `old` to `new`, as
+    /// the session would: nothing if no column changes, an UPDATE if the
+    /// primary key is kept, and a DELETE followed by an INSERT if it moves.
+    fn row_update<T: SchemaWithPK>(
+        table: &T,
+        old: Vec<Value<S, Vec<u8>>>,
+        new: Vec<Value<S, Vec<u8>>>,
+    ) -> Vec<KeyedOperation<Self, S>>;
+}
+
+/// A row change staged by [`SqlFormat::row_update`]: the primary keys before
+/// and after, and whether each column changed.
+struct RowChange<S> {
+    old_pk: Vec<Value<S, Vec<u8>>>,
+    new_pk: Vec<Value<S, Vec<u8>>>,
+    changed: Vec<bool>,
+}
+
+impl<S: Clone + AsRef<str>> RowChange<S> {
+    fn new<T: SchemaWithPK>(
+        table: &T,
+        old: &[Value<S, Vec<u8>>],
+        new: &[Value<S, Vec<u8>>],
+    ) -> Self {
+        Self {
+            old_pk: table.extract_pk(&old),
+            new_pk: table.extract_pk(&new),
+            changed: old
+                .iter()
+                .zip(new)
+                .map(|(old, new)| crate::values_differ_for_patch(old, new))
+                .collect(),
+        }
+    }
+
+    /// Whether a column inside (`key`) or outside the primary key changes.
+    fn changes<T: SchemaWithPK>(&self, table: &T, key: bool) -> bool {
+        self.changed
+            .iter()
+            .enumerate()
+            .any(|(col, &differs)| differs && table.primary_key_index(col).is_some() == key)
+    }
+
+    /// The DELETE of the old key and INSERT of `new` recording a key move.
+    fn into_move<F: Format<S, Vec<u8>>>(
+        self,
+        delete: F::DeleteData,
+        new: Vec<Value<S, Vec<u8>>>,
+    ) -> Vec<KeyedOperation<F, S>> {
+        vec![
+            (
+                self.old_pk,
+                Operation::Delete {
+                    data: delete,
+                    indirect: false,
+                },
+            ),
+            (
+                self.new_pk,
+                Operation::Insert {
+                    values: new,
+                    indirect: false,
+                },
+            ),
+        ]
+    }
+}
+
+impl<S: Clone + AsRef<str>> SqlFormat<S> for PatchsetFormat {
+    fn update_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        set: Vec<Option<Value<S, Vec<u8>>>>,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>> {
+        let pk = patchset_pk(table, filter)?;
+        let values = set.into_iter().map(|new| ((), new)).collect();
+        Ok((
+            pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        ))
+    }
+
+    fn delete_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>> {
+        let pk = patchset_pk(table, filter)?;
+        Ok((
+            pk,
+            Operation::Delete {
+                data: (),
+                indirect: false,
+            },
+        ))
+    }
+
+    fn row_update<T: SchemaWithPK>(
+        table: &T,
+        old: Vec<Value<S, Vec<u8>>>,
+        new: Vec<Value<S, Vec<u8>>>,
+    ) -> Vec<KeyedOperation<Self, S>> {
+        let change = RowChange::new(table, &old, &new);
+        if change.changes(table, true) {
+            return change.into_move((), new);
+        }
+        if !change.changes(table, false) {
+            return Vec::new();
+        }
+        let values = new
+            .into_iter()
+            .zip(&change.changed)
+            .enumerate()
+            .map(|(col, (new, &differs))| {
+                let keep = differs || table.primary_key_index(col).is_some();
+                ((), keep.then_some(new))
+            })
+            .collect();
+        vec![(
+            change.old_pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        )]
+    }
+}
+
+/// Collect the primary key from a `WHERE` clause, rejecting any constraint on
+/// a non-PK column since a patchset has nowhere to keep it.
+fn patchset_pk<'input, T: SchemaWithPK, S: Clone>(
+    table: &T,
+    filter: WhereClause<'input, S>,
+) -> Result<Vec<Value<S, Vec<u8>>>, ParseError<'input>> {
+    let mut pk = vec![Value::Null; table.number_of_primary_keys()];
+    for (col_idx, (col_name, val)) in filter
+        .into_iter()
+        .enumerate()
+        .filter_map(|(col_idx, entry)| entry.map(|entry| (col_idx, entry)))
+    {
+        let primary_key_index = table
+            .primary_key_index(col_idx)
+            .ok_or(ParseError::WhereNonPKColumn { column: col_name })?;
+        pk[primary_key_index] = val;
+    }
+    Ok(pk)
+}
+
+impl<S: Clone + Debug + AsRef<str>> SqlFormat<S> for ChangesetFormat {
+    /// The `WHERE` clause supplies the old primary key and the old value of
+    /// any assigned column it also constrains. Assigned columns it does not
+    /// mention keep an undefined old value, and a constraint on a column that
+    /// is neither a primary key nor assigned is rejected, since a changeset
+    /// UPDATE records old values only for the key and the changed columns.
+    fn update_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        set: Vec<Option<Value<S, Vec<u8>>>>,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>> {
+        let mut pk = vec![Value::Null; table.number_of_primary_keys()];
+        let mut values = Vec::with_capacity(set.len());
+        for (col_idx, (new, old)) in set.into_iter().zip(filter).enumerate() {
+            let value = match (table.primary_key_index(col_idx), new, old) {
+                (Some(primary_key_index), new, old) => {
+                    let old = old.map_or(Value::Null, |(_, val)| val);
+                    pk[primary_key_index] = old.clone();
+                    (Some(old), new)
+                }
+                (None, Some(new), old) => (old.map(|(_, val)| val), Some(new)),
+                (None, None, Some((col_name, _))) => {
+                    return Err(ParseError::WhereNonPKColumn { column: col_name });
+                }
+                (None, None, None) => (None, None),
+            };
+            values.push(value);
+        }
+        Ok((
+            pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        ))
+    }
+
+    /// The `WHERE` clause supplies the old row. Columns it does not mention
+    /// are recorded as NULL, so a changeset DELETE built from SQL applies
+    /// cleanly only if the statement names every non-NULL column.
+    fn delete_operation<'input, T: SchemaWithPK>(
+        table: &T,
+        filter: WhereClause<'input, S>,
+    ) -> Result<KeyedOperation<Self, S>, ParseError<'input>> {
+        let data: Vec<Value<S, Vec<u8>>> = filter
+            .into_iter()
+            .map(|entry| entry.map_or(Value::Null, |(_, val)| val))
+            .collect();
+        let pk = table.extract_pk(&data);
+        Ok((
+            pk,
+            Operation::Delete {
+                data,
+                indirect: false,
+            },
+        ))
+    }
+
+    /// Old values come from `old`: the key and the changed columns carry
+    /// theirs, the other columns are undefined on both sides.
+    fn row_update<T: SchemaWithPK>(
+        table: &T,
+        old: Vec<Value<S, Vec<u8>>>,
+        new: Vec<Value<S, Vec<u8>>>,
+    ) -> Vec<KeyedOperation<Self, S>> {
+        let change = RowChange::new(table, &old, &new);
+        if change.changes(table, true) {
+            return change.into_move(old, new);
+        }
+        if !change.changes(table, false) {
+            return Vec::new();
+        }
+        let values = old
+            .into_iter()
+            .zip(new)
+            .zip(&change.changed)
+            .enumerate()
+            .map(|(col, ((old, new), &differs))| {
+                if table.primary_key_index(col).is_some() {
+                    (Some(old), None)
+                } else if differs {
+                    (Some(old), Some(new))
+                } else {
+                    (None, None)
+                }
+            })
+            .collect();
+        vec![(
+            change.old_pk,
+            Operation::Update {
+                values,
+                indirect: false,
+            },
+        )]
+    }
+}
+
+/// Whether `value` satisfies a comparison with `literal`, as `SQLite` decides
+/// it: integers and reals compare numerically, and NULL matches only under
+/// `IS` (`null_safe`).
+fn sql_equals<S: AsRef<str>>(
+    value: &Value<S, Vec<u8>>,
+    literal: &Value<S, Vec<u8>>,
+    null_safe: bool,
+) -> bool {
+    match (value, literal) {
+        (Value::Null, Value::Null) => null_safe,
+        (Value::Integer(i), Value::Real(r)) | (Value::Real(r), Value::Integer(i)) => {
+            compare_integer_real(*i, *r).is_eq()
+        }
+        _ => !crate::values_differ_for_patch(value, literal),
+    }
+}
+
+/// Decodes the escapes of a `unistr()` argument, as `SQLite` does: `\\` is
+/// a backslash, and `\XXXX`, `\uXXXX`, `\+XXXXXX` and `\UXXXXXXXX` the
+/// code point with those hexadecimal digits. Returns `None` on any other
+/// escape or on a digit sequence that is no code point.
+fn unistr(escaped: &str) -> Option<String> {
+    let mut text = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(at) = rest.find('\\') {
+        text.push_str(&rest[..at]);
+        rest = &rest[at + 1..];
+        let (prefix, digits) = match rest.as_bytes().first()? {
+            b'\\' => {
+                text.push('\\');
+                rest = &rest[1..];
+                continue;
+            }
+            b'u' => (1, 4),
+            b'+' => (1, 6),
+            b'U' => (1, 8),
+            _ => (0, 4),
+        };
+        let hex = rest.get(prefix..prefix + digits)?;
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        text.push(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+        rest = &rest[prefix + digits..];
+    }
+    text.push_str(rest);
+    Some(text)
+}
+
+/// Hook applied to each literal bound to a column, given its table and
+/// column index. Used to model column affinity, which the SQL text alone
+/// does not carry.
+pub(crate) type Coercion<'a, T, S> = &'a dyn Fn(&T, usize, Value<S, Vec<u8>>) -> Value<S, Vec<u8>>;
+
+/// Source of the rows a table holds before the SQL runs, one value per
+/// column, against which `UPDATE ... WHERE` predicates are evaluated.
+pub type RowSource<'a, T, S> = &'a dyn Fn(&T) -> Vec<Vec<Value<S, Vec<u8>>>>;
+
+/// The smallest rowid a table gives a row inserted without one, if its
+/// primary key aliases the rowid, as an `INTEGER PRIMARY KEY` does.
+pub(crate) type RowidAlias<'a, T> = &'a dyn Fn(&T) -> Option<i64>;
+
+/// A `WHERE` clause evaluated against a [`RowSource`]: per constrained
+/// column, whether it compares with `IS` and the values it may hold.
+type RowPredicate<S> = Vec<(usize, bool, Vec<Value<S, Vec<u8>>>)>;
+
 /// SQL parser.
-pub(crate) struct Parser<'input, 'builder, T: SchemaWithPK, S> {
+pub(crate) struct Parser<'input, 'builder, F: Format<S, Vec<u8>>, T: SchemaWithPK, S> {
     lexer: Lexer<'input>,
-    builder: &'builder mut DiffSetBuilder<PatchsetFormat, T, S, Vec<u8>>,
+    builder: &'builder mut DiffSetBuilder<F, T, S, Vec<u8>>,
+    coerce: Option<Coercion<'builder, T, S>>,
+    rows: Option<RowSource<'builder, T, S>>,
+    rowid_alias: Option<RowidAlias<'builder, T>>,
 }
 
-impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<'a> From<&'a str>>
-    Parser<'input, 'builder, T, S>
+impl<
+    'input,
+    'builder,
+    F: SqlFormat<S>,
+    T: NamedColumns,
+    S: Clone + Hash + Eq + AsRef<str> + for<'a> From<&'a str>,
+> Parser<'input, 'builder, F, T, S>
+where
+    Operation<F, S, Vec<u8>>: core::ops::Add<Output = Option<Operation<F, S, Vec<u8>>>>,
 {
     /// Create a new parser for the given input.
     #[must_use]
     pub(crate) fn new(
         input: &'input str,
-        builder: &'builder mut DiffSetBuilder<PatchsetFormat, T, S, Vec<u8>>,
+        builder: &'builder mut DiffSetBuilder<F, T, S, Vec<u8>>,
     ) -> Self {
         Self {
             lexer: Lexer::new(input),
             builder,
+            coerce: None,
+            rows: None,
+            rowid_alias: None,
         }
     }
 
+    /// Evaluate `UPDATE` predicates against the rows `rows` returns.
+    #[must_use]
+    pub(crate) fn with_row_source(mut self, rows: RowSource<'builder, T, S>) -> Self {
+        self.rows = Some(rows);
+        self
+    }
+
+    /// Key an `INSERT` leaving the primary key NULL as the session would:
+    /// on the next rowid, but no lower than the one `rowid_alias` returns
+    /// for the table, and not at all when it returns `None`, as the session
+    /// records no row with a NULL key.
+    #[must_use]
+    pub(crate) fn with_rowid_alias(mut self, rowid_alias: RowidAlias<'builder, T>) -> Self {
+        self.rowid_alias = Some(rowid_alias);
+        self
+    }
+
+    /// Pass every literal bound to a column through `coerce` before digesting it.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub(crate) fn with_coercion(mut self, coerce: Coercion<'builder, T, S>) -> Self {
+        self.coerce = Some(coerce);
+        self
+    }
+
     /// Parse all statements from the input.
     ///
     /// # Errors
@@ -105,6 +506,17 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
             }
 
             self.digest_statement()?;
+
+            // Statements must be separated, so that a forgotten `;` is
+            // reported here rather than as a confusing error further on.
+            let token = self.lexer.peek()?;
+            if !matches!(token.kind, TokenKind::Semicolon | TokenKind::Eof) {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "';' or end of input",
+                    found: token.kind.clone(),
+                    pos: token.pos,
+                });
+            }
         }
 
         Ok(())
@@ -165,14 +577,15 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
                 if col_idx > 0 {
                     self.expect(&TokenKind::Comma)?;
                 }
-                *value_ref = self.parse_value()?;
+                *value_ref = self.parse_column_value(&table, col_idx)?;
                 if let Some(pk_idx) = table.primary_key_index(col_idx) {
                     pks[pk_idx] = (*value_ref).clone();
                 }
             }
         } else {
             for column_index in column_identifiers {
-                values[usize::from(column_index)] = self.parse_value()?;
+                values[usize::from(column_index)] =
+                    self.parse_column_value(&table, usize::from(column_index))?;
                 if let Some(primary_key_index) = table.primary_key_index(usize::from(column_index))
                 {
                     pks[primary_key_index] = values[usize::from(column_index)].clone();
@@ -186,6 +599,25 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
 
         self.expect(&TokenKind::RParen)?;
 
+        if self.lexer.peek()?.kind == TokenKind::On {
+            self.digest_on_conflict_do_nothing(&table)?;
+        }
+        self.skip_returning()?;
+
+        if let Some(rowid_alias) = self.rowid_alias
+            && pks.contains(&Value::Null)
+        {
+            let Some(lowest) = rowid_alias(&table) else {
+                return Ok(());
+            };
+            let rowid = Value::Integer(self.builder.next_rowid(&table).max(lowest));
+            if let Some(col) = (0..values.len()).find(|&col| table.primary_key_index(col).is_some())
+            {
+                values[col] = rowid.clone();
+            }
+            pks = vec![rowid];
+        }
+
         self.builder.add_operation(
             &table,
             pks,
@@ -205,14 +637,14 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
         let table = self.expect_table()?;
         self.expect(&TokenKind::Set)?;
 
-        let mut new_values = vec![((), None); table.number_of_columns()];
+        let mut new_values = vec![None; table.number_of_columns()];
 
         // Parse SET assignments
         loop {
             let (col_idx, _) = self.expect_column(&table)?;
             self.expect(&TokenKind::Equals)?;
-            let val = self.parse_value()?;
-            new_values[usize::from(col_idx)] = ((), Some(val));
+            let val = self.parse_column_value(&table, usize::from(col_idx))?;
+            new_values[usize::from(col_idx)] = Some(val);
 
             if self.lexer.peek()?.kind != TokenKind::Comma {
                 break;
@@ -227,25 +659,38 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
             });
         }
 
-        let mut pk = vec![Value::Null; table.number_of_primary_keys()];
-
-        self.digest_where(&table, |col_idx, col_name, val| {
-            if let Some(primary_key_index) = table.primary_key_index(usize::from(col_idx)) {
-                pk[primary_key_index] = val.clone();
-                Ok(())
-            } else {
-                Err(ParseError::WhereNonPKColumn { column: col_name })
+        if let Some(rows) = self.rows {
+            let predicate = self.digest_row_predicate(&table)?;
+            self.skip_returning()?;
+            for old in rows(&table) {
+                assert_eq!(
+                    old.len(),
+                    table.number_of_columns(),
+                    "row must have one value per column"
+                );
+                if !predicate.iter().all(|(col, null_safe, values)| {
+                    values
+                        .iter()
+                        .any(|value| sql_equals(&old[*col], value, *null_safe))
+                }) {
+                    continue;
+                }
+                let new = old
+                    .iter()
+                    .zip(&new_values)
+                    .map(|(old, set)| set.as_ref().unwrap_or(old).clone())
+                    .collect();
+                for (pk, operation) in F::row_update(&table, old, new) {
+                    self.builder.add_operation(&table, pk, operation);
+                }
             }
-        })?;
+            return Ok(());
+        }
 
-        self.builder.add_operation(
-            &table,
-            pk,
-            Operation::Update {
-                values: new_values,
-                indirect: false,
-            },
-        );
+        let filter = self.digest_where(&table)?;
+        self.skip_returning()?;
+        let (pk, operation) = F::update_operation(&table, new_values, filter)?;
+        self.builder.add_operation(&table, pk, operation);
 
         Ok(())
     }
@@ -256,7 +701,6 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
         self.expect(&TokenKind::From)?;
 
         let table = self.expect_table()?;
-        let mut pks = vec![Value::Null; table.number_of_primary_keys()];
 
         // WHERE clause is required
         if self.lexer.peek()?.kind != TokenKind::Where {
@@ -264,39 +708,65 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
                 statement: "DELETE",
             });
         }
-        self.digest_where(&table, |col_idx, col_name, val| {
-            if let Some(primary_key_index) = table.primary_key_index(usize::from(col_idx)) {
-                pks[primary_key_index] = val.clone();
-                Ok(())
-            } else {
-                Err(ParseError::WhereNonPKColumn { column: col_name })
-            }
-        })?;
 
-        self.builder.add_operation(
-            &table,
-            pks,
-            Operation::Delete {
-                data: (),
-                indirect: false,
-            },
-        );
+        let filter = self.digest_where(&table)?;
+        self.skip_returning()?;
+        let (pk, operation) = F::delete_operation(&table, filter)?;
+        self.builder.add_operation(&table, pk, operation);
 
         Ok(())
     }
 
-    /// Parse a WHERE clause.
-    fn digest_where<D>(&mut self, table: &T, mut digestor: D) -> Result<(), ParseError<'input>>
-    where
-        D: FnMut(u16, &'input str, Value<S, Vec<u8>>) -> Result<(), ParseError<'input>>,
-    {
+    /// Parse an `ON CONFLICT [(columns)] DO NOTHING` upsert clause.
+    ///
+    /// The clause needs no handling of its own: an INSERT on a row the
+    /// builder already holds is ignored by consolidation anyway, which is what
+    /// `DO NOTHING` does. `DO UPDATE` is rejected.
+    fn digest_on_conflict_do_nothing(&mut self, table: &T) -> Result<(), ParseError<'input>> {
+        self.expect(&TokenKind::On)?;
+        self.expect(&TokenKind::Conflict)?;
+        if self.lexer.peek()?.kind == TokenKind::LParen {
+            self.lexer.next()?;
+            loop {
+                self.expect_column(table)?;
+                if self.lexer.peek()?.kind != TokenKind::Comma {
+                    break;
+                }
+                self.lexer.next()?;
+            }
+            self.expect(&TokenKind::RParen)?;
+        }
+        self.expect(&TokenKind::Do)?;
+        self.expect(&TokenKind::Nothing)?;
+        Ok(())
+    }
+
+    /// Skip a trailing `RETURNING` clause, whose output has no bearing on the
+    /// recorded change.
+    fn skip_returning(&mut self) -> Result<(), ParseError<'input>> {
+        if self.lexer.peek()?.kind == TokenKind::Returning {
+            self.lexer.next()?;
+            self.lexer.skip_to_statement_end();
+        }
+        Ok(())
+    }
+
+    /// Parse a WHERE clause into per-column equality constraints.
+    fn digest_where(&mut self, table: &T) -> Result<WhereClause<'input, S>, ParseError<'input>> {
         self.expect(&TokenKind::Where)?;
 
+        let mut filter = vec![None; table.number_of_columns()];
         loop {
             let (col_idx, col_name) = self.expect_column(table)?;
-            self.expect(&TokenKind::Equals)?;
-            let val = self.parse_value()?;
-            digestor(col_idx, col_name, val)?;
+            // `IS` is SQLite's null-safe equality, so `col IS NULL` pins the
+            // column to NULL just as `col = <value>` pins it to the value.
+            if self.lexer.peek()?.kind == TokenKind::Is {
+                self.lexer.next()?;
+            } else {
+                self.expect(&TokenKind::Equals)?;
+            }
+            let val = self.parse_column_value(table, usize::from(col_idx))?;
+            filter[usize::from(col_idx)] = Some((col_name, val));
 
             if self.lexer.peek()?.kind != TokenKind::And {
                 break;
@@ -304,7 +774,63 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
             self.lexer.next()?;
         }
 
-        Ok(())
+        Ok(filter)
+    }
+
+    /// Parse a WHERE clause of `col = value`, `col IS value` and
+    /// `col IN (value, ...)` constraints joined by `AND`, to be evaluated
+    /// against a row source.
+    fn digest_row_predicate(&mut self, table: &T) -> Result<RowPredicate<S>, ParseError<'input>> {
+        self.expect(&TokenKind::Where)?;
+
+        let mut predicate = Vec::new();
+        loop {
+            let col_idx = usize::from(self.expect_column(table)?.0);
+            let token = self.lexer.next()?;
+            let (null_safe, values) = match token.kind {
+                TokenKind::Equals => (false, vec![self.parse_column_value(table, col_idx)?]),
+                TokenKind::Is => (true, vec![self.parse_column_value(table, col_idx)?]),
+                TokenKind::In => {
+                    self.expect(&TokenKind::LParen)?;
+                    let mut values = vec![self.parse_column_value(table, col_idx)?];
+                    while self.lexer.peek()?.kind == TokenKind::Comma {
+                        self.lexer.next()?;
+                        values.push(self.parse_column_value(table, col_idx)?);
+                    }
+                    self.expect(&TokenKind::RParen)?;
+                    (false, values)
+                }
+                other => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "=, IS or IN",
+                        found: other,
+                        pos: token.pos,
+                    });
+                }
+            };
+            predicate.push((col_idx, null_safe, values));
+
+            if self.lexer.peek()?.kind != TokenKind::And {
+                break;
+            }
+            self.lexer.next()?;
+        }
+
+        Ok(predicate)
+    }
+
+    /// Parse a value literal bound to column `column` of `table`, applying
+    /// the coercion hook if one is set.
+    fn parse_column_value(
+        &mut self,
+        table: &T,
+        column: usize,
+    ) -> Result<Value<S, Vec<u8>>, ParseError<'input>> {
+        let value = self.parse_value()?;
+        Ok(match self.coerce {
+            Some(coerce) => coerce(table, column, value),
+            None => value,
+        })
     }
 
     /// Parse a value literal.
@@ -322,26 +848,23 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
                 Ok(Value::Text(text))
             }
             TokenKind::BlobLiteral(b) => Ok(Value::Blob(b)),
+            TokenKind::Identifier(function) if self.lexer.peek()?.kind == TokenKind::LParen => {
+                let text = self.parse_text_function(function, token.pos)?;
+                Ok(Value::Text(S::from(text.as_str())))
+            }
             TokenKind::Minus => {
                 // Negative number
                 let next = self.lexer.next()?;
                 match next.kind {
                     TokenKind::IntegerLiteral(v) => Ok(Value::Integer(-v)),
                     TokenKind::RealLiteral(v) => {
-                        // Check if the negated float is exactly representable as i64.
-                        // This handles the i64::MIN case: 9223372036854775808 overflows
-                        // i64 in the lexer (positive), but -9223372036854775808 is valid.
+                        // Only i64::MIN arrives here as an integer: 9223372036854775808
+                        // overflows i64 in the lexer (positive), but -9223372036854775808
+                        // is valid. Any other negated real, `-1.0` included, stays REAL.
                         let neg = -v;
-                        #[allow(
-                            clippy::cast_precision_loss,
-                            clippy::float_cmp,
-                            clippy::cast_possible_truncation
-                        )]
-                        if neg >= i64::MIN as f64
-                            && neg <= i64::MAX as f64
-                            && neg == (neg as i64 as f64)
-                        {
-                            Ok(Value::Integer(neg as i64))
+                        #[allow(clippy::cast_precision_loss, clippy::float_cmp)]
+                        if neg == i64::MIN as f64 {
+                            Ok(Value::Integer(i64::MIN))
                         } else {
                             Ok(Value::Real(neg))
                         }
@@ -361,6 +884,93 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
         }
     }
 
+    /// Parse a text value: a string literal or a text function call.
+    fn parse_text(&mut self) -> Result<String, ParseError<'input>> {
+        let token = self.lexer.next()?;
+        match token.kind {
+            TokenKind::StringLiteral(s) => Ok(s.into_owned()),
+            TokenKind::Identifier(function) if self.lexer.peek()?.kind == TokenKind::LParen => {
+                self.parse_text_function(function, token.pos)
+            }
+            other => Err(ParseError::UnexpectedToken {
+                expected: "text",
+                found: other,
+                pos: token.pos,
+            }),
+        }
+    }
+
+    /// Evaluate a call to `function`, at `pos`, whose name has been consumed.
+    ///
+    /// Only the functions the `sqlite3` shell's `.dump` writes to spell the
+    /// control characters of a TEXT value are supported: `replace(X, Y, Z)`,
+    /// `char(N, ...)` and `unistr(X)`, as in
+    /// `replace('a\nb','\n',char(10))` or `unistr('a\u000ab')`.
+    fn parse_text_function(
+        &mut self,
+        function: &'input str,
+        pos: usize,
+    ) -> Result<String, ParseError<'input>> {
+        self.expect(&TokenKind::LParen)?;
+        let text = if function.eq_ignore_ascii_case("replace") {
+            let text = self.parse_text()?;
+            self.expect(&TokenKind::Comma)?;
+            let pattern = self.parse_text()?;
+            self.expect(&TokenKind::Comma)?;
+            let replacement = self.parse_text()?;
+            // As in `SQLite`, an empty pattern leaves the text unchanged.
+            if pattern.is_empty() {
+                text
+            } else {
+                text.replace(&pattern, &replacement)
+            }
+        } else if function.eq_ignore_ascii_case("char") {
+            let mut text = String::new();
+            loop {
+                let token = self.lexer.next()?;
+                let TokenKind::IntegerLiteral(code) = token.kind else {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "code point",
+                        found: token.kind,
+                        pos: token.pos,
+                    });
+                };
+                // `SQLite` spells a code point beyond Unicode as U+FFFD.
+                text.push(
+                    u32::try_from(code)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER),
+                );
+                if self.lexer.peek()?.kind != TokenKind::Comma {
+                    break;
+                }
+                self.lexer.next()?;
+            }
+            text
+        } else if function.eq_ignore_ascii_case("unistr") {
+            let escaped = self.parse_text()?;
+            match unistr(&escaped) {
+                Some(text) => text,
+                None => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "unistr() escapes",
+                        found: TokenKind::StringLiteral(Cow::Owned(escaped)),
+                        pos,
+                    });
+                }
+            }
+        } else {
+            return Err(ParseError::UnexpectedToken {
+                expected: "value (NULL, number, string, or blob)",
+                found: TokenKind::Identifier(function),
+                pos,
+            });
+        };
+        self.expect(&TokenKind::RParen)?;
+        Ok(text)
+    }
+
     /// Expect a specific token kind.
     fn expect(
         &mut self,
@@ -378,65 +988,94 @@ impl<'input, 'builder, T: NamedColumns, S: Clone + Hash + Eq + AsRef<str> + for<
         }
     }
 
-    /// Expects a column identifier and returns the corresponding column index in the table schema.
+    /// Expects a column identifier and returns the corresponding column index
+    /// in the table schema, along with the column name as written.
     fn expect_column(&mut self, table: &T) -> Result<(u16, &'input str), ParseError<'input>> {
-        let column_name = self.expect_identifier()?;
+        let (column_name, spelling) = self.expect_identifier()?;
         #[allow(clippy::cast_possible_truncation)]
         table
-            .column_index(column_name)
-            .map(|idx| (idx as u16, column_name))
-            .ok_or(ParseError::UnknownColumn(column_name))
+            .column_index(&column_name)
+            .map(|idx| (idx as u16, spelling))
+            .ok_or(ParseError::UnknownColumn(spelling))
     }
 
     /// Expects a table existing in the builder's schema and returns a clone.
+    ///
+    /// The name may be qualified with a schema, as in `main.users`, which is
+    /// ignored: the builder has a single namespace of tables.
     fn expect_table(&mut self) -> Result<T, ParseError<'input>> {
-        let table_name = self.expect_identifier()?;
+        let (mut table_name, mut spelling) = self.expect_identifier()?;
+        if self.lexer.peek()?.kind == TokenKind::Dot {
+            self.lexer.next()?;
+            (table_name, spelling) = self.expect_identifier()?;
+        }
         self.builder
-            .table(table_name)
+            .table_named(&table_name)
             .cloned()
-            .ok_or(ParseError::UnknownTable(table_name))
+            .ok_or(ParseError::UnknownTable(spelling))
     }
 
-    /// Expect an identifier and return its name.
-    fn expect_identifier(&mut self) -> Result<&'input str, ParseError<'input>> {
+    /// Expect an identifier and return its name, unquoted, along with its
+    /// spelling in the input.
+    ///
+    /// Besides bare, bracketed and backtick-quoted identifiers, a quoted
+    /// string is taken as the name it holds, as `SQLite` does for `"name"`
+    /// and, in a table or column position, for `'name'`.
+    fn expect_identifier(&mut self) -> Result<(Cow<'input, str>, &'input str), ParseError<'input>> {
         let token = self.lexer.next()?;
-        match token.kind {
-            TokenKind::Identifier(name) => Ok(name),
+        let spelling = self.lexer.spelling(&token);
+        let name = match token.kind {
+            TokenKind::Identifier(name) => name,
+            TokenKind::QuotedIdentifier(name) | TokenKind::StringLiteral(name) => {
+                return Ok((name, spelling));
+            }
             // Also accept keywords as identifiers (common in SQL)
-            TokenKind::Insert => Ok("INSERT"),
-            TokenKind::Into => Ok("INTO"),
-            TokenKind::Values => Ok("VALUES"),
-            TokenKind::Update => Ok("UPDATE"),
-            TokenKind::Set => Ok("SET"),
-            TokenKind::Delete => Ok("DELETE"),
-            TokenKind::From => Ok("FROM"),
-            TokenKind::Where => Ok("WHERE"),
-            TokenKind::And => Ok("AND"),
-            TokenKind::Primary => Ok("PRIMARY"),
-            TokenKind::Key => Ok("KEY"),
-            TokenKind::Null => Ok("NULL"),
-            TokenKind::Integer => Ok("INTEGER"),
-            TokenKind::Int => Ok("INT"),
-            TokenKind::Real => Ok("REAL"),
-            TokenKind::Text => Ok("TEXT"),
-            TokenKind::Blob => Ok("BLOB"),
-            TokenKind::Not => Ok("NOT"),
-            other => Err(ParseError::UnexpectedToken {
-                expected: "identifier",
-                found: other,
-                pos: token.pos,
-            }),
-        }
+            TokenKind::Insert => "INSERT",
+            TokenKind::Into => "INTO",
+            TokenKind::Values => "VALUES",
+            TokenKind::Update => "UPDATE",
+            TokenKind::Set => "SET",
+            TokenKind::Delete => "DELETE",
+            TokenKind::From => "FROM",
+            TokenKind::Where => "WHERE",
+            TokenKind::And => "AND",
+            TokenKind::Primary => "PRIMARY",
+            TokenKind::Key => "KEY",
+            TokenKind::Null => "NULL",
+            TokenKind::Integer => "INTEGER",
+            TokenKind::Int => "INT",
+            TokenKind::Real => "REAL",
+            TokenKind::Text => "TEXT",
+            TokenKind::Blob => "BLOB",
+            TokenKind::Not => "NOT",
+            TokenKind::Is => "IS",
+            TokenKind::On => "ON",
+            TokenKind::Conflict => "CONFLICT",
+            TokenKind::Do => "DO",
+            TokenKind::Nothing => "NOTHING",
+            TokenKind::Returning => "RETURNING",
+            TokenKind::In => "IN",
+            other => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "identifier",
+                    found: other,
+                    pos: token.pos,
+                });
+            }
+        };
+        Ok((Cow::Borrowed(name), spelling))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use alloc::string::String;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
     use alloc::vec::Vec;
 
+    use super::TokenKind;
     use crate::schema::SimpleTable;
-    use crate::{DiffSetBuilder, PatchsetFormat};
+    use crate::{DiffOps, DiffSetBuilder, Insert, PatchDelete, PatchSet, PatchsetFormat, Value};
 
     fn make_builder(
         tables: &[SimpleTable],
@@ -543,6 +1182,121 @@ mod tests {
         assert_eq!(builder.len(), 1);
     }
 
+    #[test]
+    fn test_digest_null_value_maps_to_value_null() {
+        let t = SimpleTable::new("t", &["id", "v"], &[0]);
+        let mut builder = make_builder(core::slice::from_ref(&t));
+        builder
+            .digest_sql("INSERT INTO t VALUES (1, NULL)")
+            .unwrap();
+
+        let expected = PatchSet::new().insert(
+            Insert::<_, String, Vec<u8>>::from(t)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, Value::Null)
+                .unwrap(),
+        );
+        assert_eq!(builder, expected);
+    }
+
+    #[test]
+    fn test_digest_quoted_and_qualified_names() {
+        let t = SimpleTable::new("order items", &["id", "unit price"], &[0]);
+        let mut builder = make_builder(core::slice::from_ref(&t));
+        builder
+            .digest_sql(
+                "INSERT INTO \"order items\" VALUES (1, 2);
+                 INSERT INTO [order items] (`unit price`, \"id\") VALUES (3, 4);
+                 UPDATE main.\"order items\" SET [unit price] = 5 WHERE \"id\" = 1;
+                 DELETE FROM \"main\".`order items` WHERE id = 4;",
+            )
+            .unwrap();
+
+        let expected = PatchSet::new().insert(
+            Insert::<_, String, Vec<u8>>::from(t)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, 5i64)
+                .unwrap(),
+        );
+        assert_eq!(builder.build(), expected.build());
+
+        let err = builder
+            .digest_sql("INSERT INTO main.\"orders\" VALUES (1, 2)")
+            .unwrap_err();
+        assert_eq!(err, ParseError::UnknownTable("\"orders\""));
+    }
+
+    #[test]
+    fn test_digest_text_functions() {
+        let t = SimpleTable::new("t", &["id", "v"], &[0]);
+        for (sql, text) in [
+            ("replace('a\\nb','\\n',char(10))", "a\nb"),
+            (
+                "replace(replace('a\\r\\nb','\\r',char(13)),'\\n',char(10))",
+                "a\r\nb",
+            ),
+            ("REPLACE('ab', '', 'x')", "ab"),
+            ("char(72, 105)", "Hi"),
+            (
+                "unistr('a\\000a\\u000db\\+01F600\\U0001F600\\\\')",
+                "a\n\rb😀😀\\",
+            ),
+        ] {
+            let mut builder = make_builder(core::slice::from_ref(&t));
+            builder
+                .digest_sql(&alloc::format!("INSERT INTO t VALUES (1, {sql})"))
+                .unwrap();
+            let expected = PatchSet::new().insert(
+                Insert::<_, String, Vec<u8>>::from(t.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, text)
+                    .unwrap(),
+            );
+            assert_eq!(builder.build(), expected.build(), "{sql}");
+        }
+
+        for sql in [
+            "unistr('\\q')",
+            "unistr('\\u12')",
+            "unistr('\\uD800')",
+            "lower('A')",
+        ] {
+            let mut builder = make_builder(core::slice::from_ref(&t));
+            let statement = alloc::format!("INSERT INTO t VALUES (1, {sql})");
+            let err = builder.digest_sql(&statement).unwrap_err();
+            assert!(matches!(err, ParseError::UnexpectedToken { .. }), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_digest_delete_is_null_predicate() {
+        let t = SimpleTable::new("t", &["id", "k", "v"], &[0, 1]);
+        let mut builder = make_builder(core::slice::from_ref(&t));
+        builder
+            .digest_sql("DELETE FROM t WHERE id = 1 AND k IS NULL")
+            .unwrap();
+
+        let expected = PatchSet::<_, String, Vec<u8>>::new()
+            .delete(PatchDelete::new(t, vec![Value::Integer(1), Value::Null]));
+        assert_eq!(builder, expected);
+    }
+
+    #[test]
+    fn test_digest_where_is_not_null_rejected() {
+        let t = SimpleTable::new("t", &["id"], &[0]);
+        let mut builder = make_builder(&[t]);
+        let err = builder
+            .digest_sql("DELETE FROM t WHERE id IS NOT NULL")
+            .unwrap_err();
+        assert!(
+            matches!(err, ParseError::UnexpectedToken { .. }),
+            "got {err:?}"
+        );
+    }
+
     #[test]
     fn test_digest_negative_numbers() {
         let t = SimpleTable::new("t", &["a", "b"], &[0]);
@@ -553,26 +1307,125 @@ mod tests {
         assert_eq!(builder.len(), 1);
     }
 
+    #[test]
+    fn test_digest_negative_integral_real_stays_real() {
+        let t = SimpleTable::new("t", &["a", "b"], &[0]);
+        let mut builder = make_builder(core::slice::from_ref(&t));
+        builder
+            .digest_sql("INSERT INTO t (a, b) VALUES (-2.0, -9223372036854775808)")
+            .unwrap();
+        let expected: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(
+            Insert::from_values(t, alloc::vec![Value::Real(-2.0), Value::Integer(i64::MIN)]),
+        );
+        assert_eq!(builder.build(), expected.build());
+    }
+
     #[test]
     fn test_digest_keyword_column_names() {
         // Each reserved keyword is a column name. This forces expect_identifier
         // to take every keyword arm. The names registered on the schema match
         // the uppercase constants the parser returns for those arms.
         let cols = [
-            "INSERT", "INTO", "VALUES", "UPDATE", "SET", "DELETE", "FROM", "WHERE", "AND",
-            "PRIMARY", "KEY", "NULL", "INTEGER", "INT", "REAL", "TEXT", "BLOB", "NOT",
+            "INSERT",
+            "INTO",
+            "VALUES",
+            "UPDATE",
+            "SET",
+            "DELETE",
+            "FROM",
+            "WHERE",
+            "AND",
+            "PRIMARY",
+            "KEY",
+            "NULL",
+            "INTEGER",
+            "INT",
+            "REAL",
+            "TEXT",
+            "BLOB",
+            "NOT",
+            "IS",
+            "ON",
+            "CONFLICT",
+            "DO",
+            "NOTHING",
+            "RETURNING",
         ];
         let t = SimpleTable::new("kwords", &cols, &[0]);
         let mut builder = make_builder(&[t]);
         builder
             .digest_sql(
-                "INSERT INTO kwords (insert, into, values, update, set, delete, from, where, and, primary, key, null, integer, int, real, text, blob, not) \
-                 VALUES (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18)",
+                "INSERT INTO kwords (insert, into, values, update, set, delete, from, where, and, primary, key, null, integer, int, real, text, blob, not, is, on, conflict, do, nothing, returning) \
+                 VALUES (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24)",
             )
             .unwrap();
         assert_eq!(builder.len(), 1);
     }
 
+    #[test]
+    fn test_digest_insert_on_conflict_do_nothing() {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let mut expected = make_builder(core::slice::from_ref(&users));
+        expected
+            .digest_sql("INSERT INTO users (id, name) VALUES (1, 'Alice')")
+            .unwrap();
+
+        let mut builder = make_builder(&[users]);
+        builder
+            .digest_sql(
+                "INSERT INTO users (id, name) VALUES (1, 'Alice') ON CONFLICT DO NOTHING;\
+                 INSERT INTO users (id, name) VALUES (1, 'Bob') ON CONFLICT(id) DO NOTHING;",
+            )
+            .unwrap();
+        assert_eq!(builder, expected);
+    }
+
+    #[test]
+    fn test_digest_on_conflict_do_update_rejected() {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let mut builder = make_builder(&[users]);
+        let err = builder
+            .digest_sql(
+                "INSERT INTO users VALUES (1, 'Bob') ON CONFLICT(id) DO UPDATE SET name = 'Bob'",
+            )
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ParseError::UnexpectedToken {
+                    expected: "NOTHING",
+                    ..
+                }
+            ),
+            "got {err:?}"
+        );
+    }
+
+    #[test]
+    fn test_digest_returning_ignored() {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let mut expected = make_builder(core::slice::from_ref(&users));
+        expected
+            .digest_sql(
+                "INSERT INTO users VALUES (1, 'Alice');\
+                 INSERT INTO users VALUES (2, 'Bob');\
+                 UPDATE users SET name = 'Carol' WHERE id = 2;\
+                 DELETE FROM users WHERE id = 3;",
+            )
+            .unwrap();
+
+        let mut builder = make_builder(&[users]);
+        builder
+            .digest_sql(
+                "INSERT INTO users VALUES (1, 'Alice') RETURNING *;\
+                 INSERT INTO users VALUES (2, 'Bob') ON CONFLICT DO NOTHING RETURNING id;\
+                 UPDATE users SET name = 'Carol' WHERE id = 2 RETURNING name || ';' AS n;\
+                 DELETE FROM users WHERE id = 3 returning id, name",
+            )
+            .unwrap();
+        assert_eq!(builder, expected);
+    }
+
     // ---- ParseError variant tests ----
 
     use crate::builders::sql::ParseError;
@@ -647,4 +1500,35 @@ mod tests {
         let err = builder.digest_sql("INSERT INTO 42 VALUES (1)").unwrap_err();
         assert!(matches!(err, ParseError::UnexpectedToken { .. }));
     }
+
+    #[test]
+    fn test_digest_missing_semicolon_rejected() {
+        let t = SimpleTable::new("t", &["id"], &[0]);
+        let mut builder = make_builder(&[t]);
+        let err = builder
+            .digest_sql("INSERT INTO t VALUES (1) INSERT INTO t VALUES (2)")
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::UnexpectedToken {
+                expected: "';' or end of input",
+                found: TokenKind::Insert,
+                pos: 25,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Unexpected token Insert at position 25, expected ';' or end of input"
+        );
+    }
+
+    #[test]
+    fn test_digest_trailing_semicolons_accepted() {
+        let t = SimpleTable::new("t", &["id"], &[0]);
+        let mut builder = make_builder(&[t]);
+        builder
+            .digest_sql("INSERT INTO t VALUES (1); INSERT INTO t VALUES (2);;")
+            .unwrap();
+        assert_eq!(builder.len(), 2);
+    }
 }
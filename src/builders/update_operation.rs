@@ -11,6 +11,17 @@ use crate::{
 };
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "UpdateFields<T, F, S, B>",
+        bound(
+            serialize = "T: serde::Serialize, S: serde::Serialize, B: serde::Serialize, F::Old: serde::Serialize",
+            deserialize = "T: DynTable + serde::Deserialize<'de>, S: serde::Deserialize<'de>, B: serde::Deserialize<'de>, F::Old: serde::Deserialize<'de>"
+        )
+    )
+)]
 /// Builder for an update operation, parameterized by the format type `F` and value types `S`, `B`.
 pub struct Update<T, F: Format<S, B>, S, B> {
     /// The table being updated.
@@ -22,6 +33,28 @@ pub struct Update<T, F: Format<S, B>, S, B> {
     pub(crate) indirect: bool,
 }
 
+/// The serialized fields of an [`Update`], checked against the table's
+/// column count on the way back in.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(
+    deserialize = "T: serde::Deserialize<'de>, S: serde::Deserialize<'de>, B: serde::Deserialize<'de>, F::Old: serde::Deserialize<'de>"
+))]
+struct UpdateFields<T, F: Format<S, B>, S, B> {
+    table: T,
+    values: Vec<(F::Old, MaybeValue<S, B>)>,
+    indirect: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<T: DynTable, F: Format<S, B>, S, B> TryFrom<UpdateFields<T, F, S, B>> for Update<T, F, S, B> {
+    type Error = crate::errors::Error;
+
+    fn try_from(fields: UpdateFields<T, F, S, B>) -> Result<Self, Self::Error> {
+        Ok(Self::try_from_values(fields.table, fields.values)?.indirect(fields.indirect))
+    }
+}
+
 impl<
     T: DynTable + PartialEq,
     F: Format<S, B>,
@@ -92,6 +125,67 @@ where
     }
 }
 
+impl<T: DynTable, F: Format<S, B>, S, B> Update<T, F, S, B> {
+    /// Creates an update from its `(old, new)` pairs, one per column, as
+    /// returned by [`values`](Self::values).
+    ///
+    /// A fast path for callers whose rows are known to fit the table. A
+    /// length mismatch trips a debug assertion; use
+    /// [`try_from_values`](Self::try_from_values) to validate untrusted rows.
+    #[inline]
+    #[must_use]
+    pub fn from_values(table: T, values: Vec<(F::Old, MaybeValue<S, B>)>) -> Self {
+        debug_assert_eq!(
+            values.len(),
+            table.number_of_columns(),
+            "row must have one value pair per column"
+        );
+        Self {
+            table,
+            values,
+            indirect: false,
+        }
+    }
+
+    /// Creates an update from its `(old, new)` pairs, checking that there is
+    /// one per column.
+    ///
+    /// # Errors
+    ///
+    /// * `ValueCountMismatch` - If `values.len()` differs from the table's column count.
+    pub fn try_from_values(
+        table: T,
+        values: Vec<(F::Old, MaybeValue<S, B>)>,
+    ) -> Result<Self, crate::errors::Error> {
+        let num_cols = table.number_of_columns();
+        if values.len() != num_cols {
+            return Err(crate::errors::Error::ValueCountMismatch(
+                values.len(),
+                num_cols,
+            ));
+        }
+        Ok(Self::from_values(table, values))
+    }
+
+    /// Sets the new value of a column in either format, keeping its old
+    /// value, if any.
+    pub(crate) fn set_new_value(
+        mut self,
+        col_idx: usize,
+        new: Value<S, B>,
+    ) -> Result<Self, crate::errors::Error> {
+        let num_cols = self.values.len();
+        let (_, slot) =
+            self.values
+                .get_mut(col_idx)
+                .ok_or(crate::errors::Error::ColumnIndexOutOfBounds(
+                    col_idx, num_cols,
+                ))?;
+        *slot = Some(new);
+        Ok(self)
+    }
+}
+
 impl<T, F: Format<S, B>, S, B> Indirect for Update<T, F, S, B> {
     #[inline]
     fn indirect(mut self, indirect: bool) -> Self {
@@ -203,6 +297,70 @@ impl<T: DynTable, S: Clone + Debug + AsRef<str>, B: Clone + Debug + AsRef<[u8]>>
     {
         self.set(col_idx, Value::Null, Value::Null)
     }
+
+    /// Sets the old and new values of several columns at once.
+    ///
+    /// Each entry is `(col_idx, old, new)`, as passed to [`set`](Self::set).
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnIndexOutOfBounds` - If any column index is out of bounds for the table schema.
+    /// * `DuplicateColumnIndex` - If a column index appears more than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{Update, ChangesetFormat, TableSchema, Value};
+    ///
+    /// // CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)
+    /// let schema: TableSchema<String> = TableSchema::new("users".into(), 3, vec![1, 0, 0]);
+    ///
+    /// // UPDATE users SET name = 'Bob', age = 31 WHERE id = 1
+    /// let update = Update::<_, ChangesetFormat, String, Vec<u8>>::from(schema)
+    ///     .set_many(&[
+    ///         (0, 1i64.into(), 1i64.into()),
+    ///         (1, "Alice".into(), "Bob".into()),
+    ///         (2, 30i64.into(), Value::Integer(31)),
+    ///     ])
+    ///     .unwrap();
+    /// ```
+    pub fn set_many(
+        mut self,
+        pairs: &[ChangesetAssignment<S, B>],
+    ) -> Result<Self, crate::errors::Error> {
+        check_batch_indices(
+            pairs.iter().map(|(col_idx, _, _)| *col_idx),
+            self.values.len(),
+        )?;
+        for (col_idx, old, new) in pairs {
+            self.values[*col_idx] = (Some(old.clone()), Some(new.clone()));
+        }
+        Ok(self)
+    }
+}
+
+/// A `(col_idx, old, new)` entry of [`Update::set_many`] on a changeset.
+type ChangesetAssignment<S, B> = (usize, Value<S, B>, Value<S, B>);
+
+/// Checks that every index of a batch assignment is in bounds and unique.
+fn check_batch_indices(
+    indices: impl Iterator<Item = usize>,
+    num_cols: usize,
+) -> Result<(), crate::errors::Error> {
+    let mut assigned = vec![false; num_cols];
+    for col_idx in indices {
+        let slot =
+            assigned
+                .get_mut(col_idx)
+                .ok_or(crate::errors::Error::ColumnIndexOutOfBounds(
+                    col_idx, num_cols,
+                ))?;
+        if *slot {
+            return Err(crate::errors::Error::DuplicateColumnIndex(col_idx));
+        }
+        *slot = true;
+    }
+    Ok(())
 }
 
 impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> Update<T, PatchsetFormat, S, B> {
@@ -264,21 +422,213 @@ impl<T: DynTable, S: AsRef<str>, B: AsRef<[u8]>> Update<T, PatchsetFormat, S, B>
     {
         self.set(col_idx, Value::Null)
     }
+
+    /// Sets the new values of several columns at once.
+    ///
+    /// Each entry is `(col_idx, new)`, as passed to [`set`](Self::set).
+    ///
+    /// # Errors
+    ///
+    /// * `ColumnIndexOutOfBounds` - If any column index is out of bounds for the table schema.
+    /// * `DuplicateColumnIndex` - If a column index appears more than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{Update, PatchsetFormat, TableSchema};
+    ///
+    /// // CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)
+    /// let schema: TableSchema<String> = TableSchema::new("users".into(), 3, vec![1, 0, 0]);
+    ///
+    /// // UPDATE users SET name = 'Bob', age = 31 WHERE id = 1
+    /// let update = Update::<_, PatchsetFormat, String, Vec<u8>>::from(schema)
+    ///     .set_many(&[(0, 1i64.into()), (1, "Bob".into()), (2, 31i64.into())])
+    ///     .unwrap();
+    /// ```
+    pub fn set_many(mut self, pairs: &[(usize, Value<S, B>)]) -> Result<Self, crate::errors::Error>
+    where
+        S: Clone,
+        B: Clone,
+    {
+        check_batch_indices(pairs.iter().map(|(col_idx, _)| *col_idx), self.values.len())?;
+        for (col_idx, new) in pairs {
+            self.values[*col_idx] = ((), Some(new.clone()));
+        }
+        Ok(self)
+    }
+}
+
+impl<T: SchemaWithPK, S: Clone + AsRef<str>, B: Clone + AsRef<[u8]>>
+    Update<T, PatchsetFormat, S, B>
+{
+    /// Builds a patchset update from the full old and new rows.
+    ///
+    /// Primary-key columns always carry their new value, which identifies the
+    /// row. Every other column is set only when
+    /// [`values_differ_for_patch`](crate::values_differ_for_patch) reports a
+    /// change, and left undefined otherwise.
+    ///
+    /// Returns `Ok(None)` when no non-key column changed: SQLite records no
+    /// `UPDATE` for such a row.
+    ///
+    /// # Errors
+    ///
+    /// * `PatchsetPkChange` - If the primary key differs between the rows. A
+    ///   patchset stores the key once, so the change cannot be one `UPDATE`;
+    ///   [`DiffSetBuilder::update_rows`](crate::DiffSetBuilder::update_rows)
+    ///   records it as a DELETE plus an INSERT instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either row does not have one value per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{DiffOps, PatchSet, PatchsetFormat, SimpleTable, Update, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name", "age"], &[0]);
+    /// let old: Vec<Value<String, Vec<u8>>> =
+    ///     vec![1i64.into(), "Alice".into(), 30i64.into()];
+    /// let new = vec![1i64.into(), "Alice".into(), 31i64.into()];
+    ///
+    /// let update = Update::<_, PatchsetFormat, _, _>::from_rows(users, &old, &new)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().update(update);
+    /// let op = patchset.iter().next().unwrap();
+    /// assert_eq!(
+    ///     op.update_new_values().unwrap(),
+    ///     [&Some(Value::Integer(1)), &None, &Some(Value::Integer(31))]
+    /// );
+    /// ```
+    pub fn from_rows(
+        table: T,
+        old: &[Value<S, B>],
+        new: &[Value<S, B>],
+    ) -> Result<Option<Self>, crate::errors::Error> {
+        Self::from_rows_collated(table, old, new, &[])
+    }
+
+    /// Like [`from_rows`](Self::from_rows), but compares text columns under
+    /// their collation, so text that is equal under it counts as unchanged.
+    ///
+    /// `collations` holds one [`Collation`](crate::Collation) per column, in
+    /// column order; columns past its end compare as
+    /// [`Binary`](crate::Collation::Binary).
+    ///
+    /// # Errors
+    ///
+    /// * `PatchsetPkChange` - If the primary key differs between the rows
+    ///   under its columns' collations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either row does not have one value per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{Collation, PatchsetFormat, SimpleTable, Update, Value};
+    ///
+    /// // CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT COLLATE NOCASE)
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let old: Vec<Value<String, Vec<u8>>> = vec![1i64.into(), "alice".into()];
+    /// let new = vec![1i64.into(), "ALICE".into()];
+    /// let collations = [Collation::Binary, Collation::NoCase];
+    ///
+    /// let update =
+    ///     Update::<_, PatchsetFormat, _, _>::from_rows_collated(users, &old, &new, &collations);
+    /// assert_eq!(update, Ok(None));
+    /// ```
+    pub fn from_rows_collated(
+        table: T,
+        old: &[Value<S, B>],
+        new: &[Value<S, B>],
+        collations: &[crate::Collation],
+    ) -> Result<Option<Self>, crate::errors::Error> {
+        let num_cols = table.number_of_columns();
+        assert_eq!(
+            old.len(),
+            num_cols,
+            "old row must have one value per column"
+        );
+        assert_eq!(
+            new.len(),
+            num_cols,
+            "new row must have one value per column"
+        );
+
+        let mut changed = false;
+        let mut update = Self::from(table);
+        for (col_idx, (old, new)) in old.iter().zip(new).enumerate() {
+            let is_pk = update.table.primary_key_index(col_idx).is_some();
+            let collation = collations.get(col_idx).copied().unwrap_or_default();
+            let differs = crate::values_differ_collated(old, new, collation);
+            if is_pk && differs {
+                return Err(crate::errors::Error::PatchsetPkChange);
+            }
+            if is_pk || differs {
+                update.values[col_idx].1 = Some(new.clone());
+            }
+            changed |= differs && !is_pk;
+        }
+        Ok(changed.then_some(update))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Update;
     use crate::builders::{ChangesetFormat, PatchsetFormat};
+    use crate::encoding::Value;
     use crate::errors::Error;
     use crate::schema::SimpleTable;
     use alloc::string::String;
+    use alloc::vec;
     use alloc::vec::Vec;
 
     fn users() -> SimpleTable {
         SimpleTable::new("users", &["id", "name"], &[0])
     }
 
+    #[test]
+    fn test_update_try_from_values() {
+        let pairs = vec![
+            (Some(Value::Integer(1)), Some(Value::Integer(1))),
+            (Some("a".into()), Some("b".into())),
+        ];
+        let update =
+            Update::<_, ChangesetFormat, String, Vec<u8>>::try_from_values(users(), pairs).unwrap();
+        assert_eq!(
+            update,
+            Update::<_, ChangesetFormat, String, Vec<u8>>::from(users())
+                .set(0, 1i64, 1i64)
+                .unwrap()
+                .set(1, "a", "b")
+                .unwrap()
+        );
+        let err = Update::<_, ChangesetFormat, String, Vec<u8>>::try_from_values(
+            users(),
+            vec![(None, None)],
+        )
+        .unwrap_err();
+        assert_eq!(err, Error::ValueCountMismatch(1, 2));
+
+        let patch = Update::<_, PatchsetFormat, String, Vec<u8>>::try_from_values(
+            users(),
+            vec![((), Some(1i64.into())), ((), None)],
+        )
+        .unwrap();
+        assert_eq!(patch.values().len(), 2);
+        let err = Update::<_, PatchsetFormat, String, Vec<u8>>::try_from_values(
+            users(),
+            vec![((), None); 3],
+        )
+        .unwrap_err();
+        assert_eq!(err, Error::ValueCountMismatch(3, 2));
+    }
+
     #[test]
     fn test_changeset_update_set_out_of_bounds() {
         let err = Update::<_, ChangesetFormat, String, Vec<u8>>::from(users())
@@ -349,4 +699,118 @@ mod tests {
             .unwrap();
         assert_ne!(a, c);
     }
+
+    #[test]
+    fn test_patchset_from_rows_keeps_pk_and_changed_columns() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let old: Vec<Val> = alloc::vec![1i64.into(), "alice".into()];
+        let new: Vec<Val> = alloc::vec![1i64.into(), "Alice".into()];
+        let update = Update::<_, PatchsetFormat, String, Vec<u8>>::from_rows(users(), &old, &new)
+            .unwrap()
+            .expect("case change is a change");
+        assert_eq!(update.values()[0].1, Some(1i64.into()));
+        assert_eq!(update.values()[1].1, Some("Alice".into()));
+    }
+
+    #[test]
+    fn test_patchset_from_rows_unchanged_row_is_none() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let row: Vec<Val> = alloc::vec![1i64.into(), crate::encoding::Value::Null];
+        assert!(
+            Update::<_, PatchsetFormat, String, Vec<u8>>::from_rows(users(), &row, &row)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_patchset_from_rows_collated_binary_vs_nocase() {
+        use crate::Collation;
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let old: Vec<Val> = alloc::vec![1i64.into(), "alice".into()];
+        let new: Vec<Val> = alloc::vec![1i64.into(), "ALICE".into()];
+        let from_rows = |collations: &[Collation]| {
+            Update::<_, PatchsetFormat, String, Vec<u8>>::from_rows_collated(
+                users(),
+                &old,
+                &new,
+                collations,
+            )
+            .unwrap()
+        };
+
+        assert!(
+            from_rows(&[]).is_some(),
+            "unlisted columns compare as BINARY"
+        );
+        assert!(from_rows(&[Collation::Binary, Collation::Binary]).is_some());
+        assert!(from_rows(&[Collation::Binary, Collation::NoCase]).is_none());
+        assert!(from_rows(&[Collation::Binary, Collation::RTrim]).is_some());
+    }
+
+    #[test]
+    fn test_patchset_from_rows_rejects_pk_change() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let old: Vec<Val> = alloc::vec![1i64.into(), "alice".into()];
+        let new: Vec<Val> = alloc::vec![2i64.into(), "alice".into()];
+        assert_eq!(
+            Update::<_, PatchsetFormat, String, Vec<u8>>::from_rows(users(), &old, &new),
+            Err(Error::PatchsetPkChange)
+        );
+    }
+
+    #[test]
+    fn test_changeset_set_many_matches_individual_sets() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let pairs: Vec<(usize, Val, Val)> = alloc::vec![
+            (0, 1i64.into(), 1i64.into()),
+            (1, "alice".into(), "Alice".into()),
+        ];
+        let batch = Update::<_, ChangesetFormat, String, Vec<u8>>::from(users())
+            .set_many(&pairs)
+            .unwrap();
+        let single = Update::<_, ChangesetFormat, String, Vec<u8>>::from(users())
+            .set(0, 1i64, 1i64)
+            .unwrap()
+            .set(1, "alice", "Alice")
+            .unwrap();
+        assert_eq!(batch, single);
+    }
+
+    #[test]
+    fn test_changeset_set_many_rejects_bad_indices() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let duplicate: Vec<(usize, Val, Val)> =
+            alloc::vec![(1, "a".into(), "b".into()), (1, "b".into(), "c".into())];
+        assert_eq!(
+            Update::<_, ChangesetFormat, String, Vec<u8>>::from(users()).set_many(&duplicate),
+            Err(Error::DuplicateColumnIndex(1))
+        );
+        let out_of_bounds: Vec<(usize, Val, Val)> = alloc::vec![(2, 1i64.into(), 2i64.into())];
+        assert_eq!(
+            Update::<_, ChangesetFormat, String, Vec<u8>>::from(users()).set_many(&out_of_bounds),
+            Err(Error::ColumnIndexOutOfBounds(2, 2))
+        );
+    }
+
+    #[test]
+    fn test_patchset_set_many() {
+        type Val = crate::encoding::Value<String, Vec<u8>>;
+        let pairs: Vec<(usize, Val)> = alloc::vec![(0, 1i64.into()), (1, "Alice".into())];
+        let batch = Update::<_, PatchsetFormat, String, Vec<u8>>::from(users())
+            .set_many(&pairs)
+            .unwrap();
+        let single = Update::<_, PatchsetFormat, String, Vec<u8>>::from(users())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "Alice")
+            .unwrap();
+        assert_eq!(batch, single);
+
+        let duplicate: Vec<(usize, Val)> = alloc::vec![(0, 1i64.into()), (0, 2i64.into())];
+        assert_eq!(
+            Update::<_, PatchsetFormat, String, Vec<u8>>::from(users()).set_many(&duplicate),
+            Err(Error::DuplicateColumnIndex(0))
+        );
+    }
 }
@@ -0,0 +1,250 @@
+//! Visitor over the operations of a changeset or patchset.
+//!
+//! [`OperationVisitor`] receives one callback per operation kind, so code that
+//! only tallies, filters or renders rows does not have to match on
+//! [`ChangesetOp`] and [`PatchsetOp`] itself. [`ChangesetOp::accept`] and
+//! [`PatchsetOp::accept`] dispatch a single operation, and
+//! [`ParsedDiffSet::visit_all`] walks a parsed diffset in stored order.
+//!
+//! Every callback has an empty default body; implement only the kinds you
+//! care about.
+//!
+//! [`ParsedDiffSet::visit_all`]: crate::ParsedDiffSet::visit_all
+
+use crate::encoding::Value;
+
+use super::view::{ChangesetOp, ChangesetUpdatePair, PatchsetOp, PatchsetUpdateEntry};
+
+/// Per-column values of an `UPDATE`, in column order.
+///
+/// Changesets carry an old and a new slot per column. Patchsets carry only
+/// the new slot, so [`old_value`](Self::old_value) is always `None` for them.
+#[derive(Debug, Clone, Copy)]
+pub enum UpdateColumns<'a, S, B> {
+    /// Changeset `(old, new)` pairs.
+    Changeset(&'a [ChangesetUpdatePair<S, B>]),
+    /// Patchset `(unit, new)` entries.
+    Patchset(&'a [PatchsetUpdateEntry<S, B>]),
+}
+
+impl<'a, S, B> UpdateColumns<'a, S, B> {
+    /// Returns the number of columns.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Changeset(pairs) => pairs.len(),
+            Self::Patchset(entries) => entries.len(),
+        }
+    }
+
+    /// Returns `true` if the table has no columns.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the old value of column `col`, or `None` if it is undefined,
+    /// not stored by the format, or out of bounds.
+    #[must_use]
+    pub fn old_value(&self, col: usize) -> Option<&'a Value<S, B>> {
+        match self {
+            Self::Changeset(pairs) => pairs.get(col).and_then(|(old, _)| old.as_ref()),
+            Self::Patchset(_) => None,
+        }
+    }
+
+    /// Returns the new value of column `col`, or `None` if it is undefined
+    /// or out of bounds.
+    #[must_use]
+    pub fn new_value(&self, col: usize) -> Option<&'a Value<S, B>> {
+        match self {
+            Self::Changeset(pairs) => pairs.get(col).and_then(|(_, new)| new.as_ref()),
+            Self::Patchset(entries) => entries.get(col).and_then(|((), new)| new.as_ref()),
+        }
+    }
+}
+
+/// Callbacks invoked once per operation by `accept` and `visit_all`.
+pub trait OperationVisitor<T, S, B> {
+    /// Called for an `INSERT` with every column's value, in column order.
+    fn visit_insert(&mut self, table: &T, values: &[Value<S, B>], indirect: bool) {
+        let _ = (table, values, indirect);
+    }
+
+    /// Called for a `DELETE`. A changeset passes the full old row in column
+    /// order; a patchset passes only the primary-key values, in key order.
+    fn visit_delete(&mut self, table: &T, values: &[Value<S, B>], indirect: bool) {
+        let _ = (table, values, indirect);
+    }
+
+    /// Called for an `UPDATE` with its per-column values.
+    fn visit_update(&mut self, table: &T, columns: UpdateColumns<'_, S, B>, indirect: bool) {
+        let _ = (table, columns, indirect);
+    }
+}
+
+impl<T, S, B> ChangesetOp<'_, T, S, B> {
+    /// Dispatches this operation to the matching `visitor` callback.
+    pub fn accept<V: OperationVisitor<T, S, B> + ?Sized>(&self, visitor: &mut V) {
+        match *self {
+            Self::Insert {
+                table,
+                values,
+                indirect,
+            } => visitor.visit_insert(table, values, indirect),
+            Self::Update {
+                table,
+                values,
+                indirect,
+            } => visitor.visit_update(table, UpdateColumns::Changeset(values), indirect),
+            Self::Delete {
+                table,
+                old_values,
+                indirect,
+            } => visitor.visit_delete(table, old_values, indirect),
+        }
+    }
+}
+
+impl<T, S, B> PatchsetOp<'_, T, S, B> {
+    /// Dispatches this operation to the matching `visitor` callback.
+    pub fn accept<V: OperationVisitor<T, S, B> + ?Sized>(&self, visitor: &mut V) {
+        match *self {
+            Self::Insert {
+                table,
+                values,
+                indirect,
+            } => visitor.visit_insert(table, values, indirect),
+            Self::Update {
+                table,
+                entries,
+                indirect,
+                ..
+            } => visitor.visit_update(table, UpdateColumns::Patchset(entries), indirect),
+            Self::Delete {
+                table,
+                pk,
+                indirect,
+            } => visitor.visit_delete(table, pk, indirect),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, ParsedDiffSet, PatchDelete,
+        PatchSet, PatchsetFormat, SimpleTable, TableSchema, Update,
+    };
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// Tallies operations per kind, plus the columns whose new value is set.
+    #[derive(Debug, Default)]
+    struct Counter {
+        inserts: usize,
+        deletes: usize,
+        updates: usize,
+        changed_columns: usize,
+    }
+
+    impl OperationVisitor<TableSchema<String>, String, Vec<u8>> for Counter {
+        fn visit_insert(&mut self, _: &TableSchema<String>, _: &[Value<String, Vec<u8>>], _: bool) {
+            self.inserts += 1;
+        }
+
+        fn visit_delete(&mut self, _: &TableSchema<String>, _: &[Value<String, Vec<u8>>], _: bool) {
+            self.deletes += 1;
+        }
+
+        fn visit_update(
+            &mut self,
+            _: &TableSchema<String>,
+            columns: UpdateColumns<'_, String, Vec<u8>>,
+            _: bool,
+        ) {
+            self.updates += 1;
+            self.changed_columns += (0..columns.len())
+                .filter(|&col| columns.new_value(col).is_some())
+                .count();
+        }
+    }
+
+    fn mixed_changeset() -> ChangeSet<SimpleTable, String, Vec<u8>> {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        ChangeSet::new()
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "Alice")
+                    .unwrap(),
+            )
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 2i64)
+                    .unwrap()
+                    .set(1, "Bob")
+                    .unwrap(),
+            )
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(users.clone())
+                    .set(0, 3i64, 3i64)
+                    .unwrap()
+                    .set(1, "Carol", "Caroline")
+                    .unwrap(),
+            )
+            .delete(
+                ChangeDelete::from(users)
+                    .set(0, 4i64)
+                    .unwrap()
+                    .set(1, "Dave")
+                    .unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_counting_visitor_changeset() {
+        let parsed = ParsedDiffSet::parse(&mixed_changeset().build()).unwrap();
+        let mut counter = Counter::default();
+        parsed.visit_all(&mut counter);
+        assert_eq!(counter.inserts, 2);
+        assert_eq!(counter.updates, 1);
+        assert_eq!(counter.deletes, 1);
+        assert_eq!(counter.changed_columns, 2);
+    }
+
+    #[test]
+    fn test_counting_visitor_patchset() {
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+            .insert(Insert::from(users.clone()).set(0, 1i64).unwrap())
+            .update(
+                Update::<_, PatchsetFormat, _, _>::from(users.clone())
+                    .set(0, 3i64)
+                    .unwrap()
+                    .set(1, "Caroline")
+                    .unwrap(),
+            )
+            .delete(PatchDelete::new(users, alloc::vec![Value::Integer(4)]));
+        let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+        let mut counter = Counter::default();
+        parsed.visit_all(&mut counter);
+        assert_eq!(counter.inserts, 1);
+        assert_eq!(counter.updates, 1);
+        assert_eq!(counter.deletes, 1);
+    }
+
+    #[test]
+    fn test_update_columns_patchset_has_no_old_values() {
+        let entries: Vec<PatchsetUpdateEntry<String, Vec<u8>>> =
+            alloc::vec![((), Some(Value::Integer(1))), ((), None)];
+        let columns = UpdateColumns::Patchset(&entries);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns.old_value(0), None);
+        assert_eq!(columns.new_value(0), Some(&Value::Integer(1)));
+        assert_eq!(columns.new_value(1), None);
+        assert_eq!(columns.new_value(2), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Compressed framing for changeset and patchset payloads.
+//!
+//! Transports that send diffs as discrete binary messages (for instance
+//! WebSocket frames) do not benefit from HTTP-level response compression.
+//! [`compress_frame`] wraps a payload as a one-byte [`Codec`] tag followed by
+//! the (possibly compressed) bytes, and [`decompress_frame`] reverses it, so
+//! the receiver never has to be told out of band which codec was used. The
+//! receiver also caps the decompressed size, so a small hostile frame cannot
+//! expand into an unbounded allocation.
+//!
+//! ```text
+//! Frame:
+//! ├── Codec tag (1 byte): 0x00 = identity, 0x01 = deflate, 0x02 = zstd
+//! └── Payload (rest of the frame)
+//! ```
+//!
+//! The `deflate` feature enables [`Codec::Deflate`] (raw DEFLATE via
+//! `miniz_oxide`, `no_std` friendly) and the `zstd` feature enables
+//! [`Codec::Zstd`]. [`Codec::Identity`] is always available, which lets a
+//! sender skip compression for small frames.
+//!
+//! # Example
+//!
+//! ```
+//! use sqlite_diff_rs::compression::{Codec, compress_frame, decompress_frame};
+//!
+//! let frame = compress_frame(b"patchset bytes", Codec::Identity);
+//! assert_eq!(frame[0], Codec::Identity.tag());
+//! assert_eq!(decompress_frame(&frame, 1024).unwrap(), b"patchset bytes");
+//! ```
+
+use alloc::vec::Vec;
+
+/// Compression level passed to the DEFLATE encoder (0 to 10).
+#[cfg(feature = "deflate")]
+const DEFLATE_LEVEL: u8 = 6;
+
+/// Compression level passed to the zstd encoder.
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Codec applied to a frame payload, stored as the frame's first byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Codec {
+    /// Payload stored as-is.
+    Identity,
+    /// Raw DEFLATE stream.
+    #[cfg(feature = "deflate")]
+    Deflate,
+    /// Zstandard frame.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Codec {
+    /// Returns the one-byte tag written ahead of the payload.
+    #[inline]
+    #[must_use]
+    pub const fn tag(self) -> u8 {
+        match self {
+            Self::Identity => 0x00,
+            #[cfg(feature = "deflate")]
+            Self::Deflate => 0x01,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => 0x02,
+        }
+    }
+
+    /// Returns the codec for `tag`, or `None` if the tag is unknown or its
+    /// codec is not enabled in this build.
+    #[inline]
+    #[must_use]
+    pub const fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0x00 => Some(Self::Identity),
+            #[cfg(feature = "deflate")]
+            0x01 => Some(Self::Deflate),
+            #[cfg(feature = "zstd")]
+            0x02 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can occur while decoding a frame.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FrameError {
+    /// The frame has no codec tag.
+    #[error("Empty frame")]
+    Empty,
+
+    /// The codec tag is unknown or its codec is not enabled.
+    #[error("Unsupported codec tag 0x{0:02x}")]
+    UnsupportedCodec(u8),
+
+    /// The payload could not be decompressed with the tagged codec.
+    #[error("Corrupt {0:?} payload")]
+    Corrupt(Codec),
+
+    /// The payload decompresses to more bytes than the given maximum.
+    #[error("Payload exceeds the maximum of {0} bytes")]
+    TooLarge(usize),
+}
+
+/// Encodes `bytes` as a frame: the codec tag followed by the payload
+/// compressed with `codec`.
+#[must_use]
+pub fn compress_frame(bytes: &[u8], codec: Codec) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(bytes.len() + 1);
+    frame.push(codec.tag());
+    match codec {
+        Codec::Identity => frame.extend_from_slice(bytes),
+        #[cfg(feature = "deflate")]
+        Codec::Deflate => {
+            frame.extend(miniz_oxide::deflate::compress_to_vec(bytes, DEFLATE_LEVEL));
+        }
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => frame.extend(
+            zstd::bulk::compress(bytes, ZSTD_LEVEL)
+                .expect("zstd compression of an in-memory slice"),
+        ),
+    }
+    frame
+}
+
+/// Decodes a frame produced by [`compress_frame`], returning the original
+/// payload, which may be at most `max_len` bytes long.
+///
+/// Decompression stops as soon as the output would exceed `max_len`, so the
+/// allocation stays bounded whatever the frame claims to hold.
+///
+/// # Errors
+///
+/// Returns a [`FrameError`] if the frame is empty, carries an unsupported
+/// codec tag, its payload is not valid for the tagged codec, or the payload
+/// is longer than `max_len`.
+pub fn decompress_frame(frame: &[u8], max_len: usize) -> Result<Vec<u8>, FrameError> {
+    let (&tag, payload) = frame.split_first().ok_or(FrameError::Empty)?;
+    let codec = Codec::from_tag(tag).ok_or(FrameError::UnsupportedCodec(tag))?;
+    match codec {
+        Codec::Identity if payload.len() > max_len => Err(FrameError::TooLarge(max_len)),
+        Codec::Identity => Ok(payload.to_vec()),
+        #[cfg(feature = "deflate")]
+        Codec::Deflate => miniz_oxide::inflate::decompress_to_vec_with_limit(payload, max_len)
+            .map_err(|err| match err.status {
+                miniz_oxide::inflate::TINFLStatus::HasMoreOutput => FrameError::TooLarge(max_len),
+                _ => FrameError::Corrupt(codec),
+            }),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => decompress_zstd(payload, max_len),
+    }
+}
+
+/// Decodes a zstd payload, reading at most one byte past `max_len` to tell
+/// an oversized payload from one that fits.
+#[cfg(feature = "zstd")]
+fn decompress_zstd(payload: &[u8], max_len: usize) -> Result<Vec<u8>, FrameError> {
+    extern crate std;
+    use std::io::Read;
+
+    let corrupt = |_| FrameError::Corrupt(Codec::Zstd);
+    let decoder = zstd::stream::read::Decoder::with_buffer(payload).map_err(corrupt)?;
+    let limit = u64::try_from(max_len).map_or(u64::MAX, |max_len| max_len.saturating_add(1));
+    let mut bytes = Vec::new();
+    decoder
+        .take(limit)
+        .read_to_end(&mut bytes)
+        .map_err(corrupt)?;
+    if bytes.len() > max_len {
+        return Err(FrameError::TooLarge(max_len));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_frame_round_trip() {
+        let frame = compress_frame(b"abc", Codec::Identity);
+        assert_eq!(frame, [0x00, b'a', b'b', b'c']);
+        assert_eq!(decompress_frame(&frame, 3).unwrap(), b"abc");
+        assert_eq!(decompress_frame(&frame, 2), Err(FrameError::TooLarge(2)));
+    }
+
+    #[test]
+    fn test_empty_frame_is_rejected() {
+        assert_eq!(decompress_frame(&[], 0), Err(FrameError::Empty));
+    }
+
+    #[test]
+    fn test_unknown_tag_is_rejected() {
+        assert_eq!(
+            decompress_frame(&[0x7f, 1, 2], 2),
+            Err(FrameError::UnsupportedCodec(0x7f))
+        );
+    }
+
+    #[test]
+    fn test_tags_round_trip() {
+        let codecs = [
+            Codec::Identity,
+            #[cfg(feature = "deflate")]
+            Codec::Deflate,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd,
+        ];
+        for codec in codecs {
+            assert_eq!(Codec::from_tag(codec.tag()), Some(codec));
+        }
+    }
+}
@@ -0,0 +1,811 @@
+//! Debezium flattened record parsing and conversion to `SQLite` changeset
+//! operations.
+//!
+//! [Debezium](https://debezium.io/) is a CDC platform that streams row-level
+//! changes to Kafka. With the `ExtractNewRecordState` single message transform
+//! (SMT) the change-event envelope is unwrapped, so each message value is the
+//! row itself plus the metadata fields requested through `add.fields`, each
+//! prefixed with `__`. This module parses those flattened records and converts
+//! them into changeset operations compatible with this crate's builders.
+//!
+//! Conversion reads `__op` and `__table`, so the SMT must run with
+//! `add.fields=op,table`. Under `delete.handling.mode=rewrite` a delete arrives
+//! as the old row with `__deleted` set to `"true"`; the Kafka tombstone that may
+//! follow it (a `null` message value) parses into a record flagged
+//! [`tombstone`](FlatRecord::tombstone) that digests as a no-op. Under
+//! `delete.tombstone.handling.mode=tombstone` the tombstone is all that is
+//! left of a delete, and the row's key is only in the Kafka key message:
+//! attach it with [`FlatRecord::with_key`] to digest the tombstone as a
+//! patchset DELETE.
+//!
+//! The SMT drops the `before` image, so an update only carries the new row. It
+//! converts into a patchset UPDATE; a changeset UPDATE needs the old values and
+//! is rejected with [`ConversionError::MissingData`].
+//!
+//! Flattened records carry no trigger-origin marker, so converted ops default
+//! to `indirect = false`.
+//!
+//! The default [`TypeMap`](crate::TypeMap) reads decimals emitted under
+//! `decimal.handling.mode=string` or `double`, and rejects the base64 of the
+//! default `precise` mode, whose scale only the dropped schema holds. Dates
+//! may be ISO-8601 strings or the day count of `io.debezium.time.Date`.
+//! Timestamps and times must be strings, as the zoned types are: their
+//! epoch-number forms, such as `io.debezium.time.MicroTimestamp`, count a
+//! unit the record does not name, and are rejected.
+//!
+//! [`debezium_stream_to_changesets`] and [`debezium_stream_to_patchsets`] fold a
+//! whole message stream at once, emitting one serialized diffset per
+//! `__transaction_id` (requires `add.fields=transaction.id`).
+//!
+//! # Example
+//!
+//! ```
+//! use sqlite_diff_rs::debezium::{parse_flattened, FlatOp};
+//!
+//! let json = r#"{"id":1,"name":"Alice","__op":"c","__table":"users","__source_ts_ms":1700000000000}"#;
+//! let record = parse_flattened(json).unwrap();
+//!
+//! assert_eq!(record.op, Some(FlatOp::Create));
+//! assert_eq!(record.table.as_deref(), Some("users"));
+//! assert_eq!(record.columns.len(), 2);
+//! assert!(record.metadata.contains_key("__source_ts_ms"));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::Error as _;
+
+/// Debezium operation code, as carried by the `__op` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlatOp {
+    /// Row created (`"c"`).
+    Create,
+    /// Row read during an initial snapshot (`"r"`).
+    Read,
+    /// Row updated (`"u"`).
+    Update,
+    /// Row deleted (`"d"`).
+    Delete,
+}
+
+impl FlatOp {
+    /// Parses a Debezium operation code.
+    #[must_use]
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "c" => Some(Self::Create),
+            "r" => Some(Self::Read),
+            "u" => Some(Self::Update),
+            "d" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// One Debezium record flattened by the `ExtractNewRecordState` SMT.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FlatRecord {
+    /// Operation code from `__op`, if the SMT added it.
+    pub op: Option<FlatOp>,
+    /// Table name from `__table`, if the SMT added it.
+    pub table: Option<String>,
+    /// Whether `__deleted` was `true`, marking a rewritten delete.
+    pub deleted: bool,
+    /// Whether the message value was `null`, a Kafka tombstone.
+    pub tombstone: bool,
+    /// Row columns: every field whose name does not start with `__`.
+    pub columns: BTreeMap<String, serde_json::Value>,
+    /// Every other `__`-prefixed metadata field, such as `__source_ts_ms`.
+    pub metadata: BTreeMap<String, serde_json::Value>,
+}
+
+impl FlatRecord {
+    /// Operation this record stands for, or `None` for a tombstone without
+    /// a [key](Self::with_key).
+    ///
+    /// A record with `__deleted` set, or a keyed tombstone, is a delete
+    /// whatever its `__op`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError::MissingData`] if the record is neither a
+    /// tombstone nor a rewritten delete and carries no `__op`.
+    pub fn operation(&self) -> Result<Option<FlatOp>, ConversionError> {
+        if self.tombstone && self.columns.is_empty() {
+            Ok(None)
+        } else if self.tombstone || self.deleted {
+            Ok(Some(FlatOp::Delete))
+        } else {
+            self.op
+                .map(Some)
+                .ok_or(ConversionError::MissingData("__op", "record"))
+        }
+    }
+
+    /// Attaches the Kafka key message of a tombstone, parsed with
+    /// [`parse_flattened`], so that it converts into the delete of the keyed
+    /// row of `table`, the table the message's topic carries.
+    ///
+    /// The key only holds the primary-key columns, which a patchset DELETE
+    /// is made of. A changeset DELETE needs the whole old row, so a keyed
+    /// tombstone converts into one only with
+    /// [`ConversionError::MissingData`]. Records that are not tombstones are
+    /// returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::debezium::{parse_flattened, FlatOp};
+    ///
+    /// let key = parse_flattened(r#"{"id":1}"#).unwrap();
+    /// let record = parse_flattened("null").unwrap().with_key("users", &key);
+    /// assert_eq!(record.operation().unwrap(), Some(FlatOp::Delete));
+    /// assert_eq!(record.table.as_deref(), Some("users"));
+    /// ```
+    #[must_use]
+    pub fn with_key(mut self, table: impl Into<String>, key: &FlatRecord) -> Self {
+        if self.tombstone {
+            self.table = Some(table.into());
+            self.columns.clone_from(&key.columns);
+        }
+        self
+    }
+
+    /// Transaction id from `__transaction_id`, added by the SMT when it runs
+    /// with `add.fields=transaction.id` and the connector with
+    /// `provide.transaction.metadata=true`.
+    #[must_use]
+    pub fn transaction_id(&self) -> Option<&serde_json::Value> {
+        self.metadata.get("__transaction_id")
+    }
+}
+
+/// Parse a record flattened by Debezium's `ExtractNewRecordState` SMT.
+///
+/// A JSON `null` parses into a [`tombstone`](FlatRecord::tombstone) record.
+///
+/// # Errors
+///
+/// Returns a [`serde_json::Error`] if the JSON is malformed, is neither an
+/// object nor `null`, or carries an unknown `__op` or a malformed `__table`
+/// or `__deleted`.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::debezium::{parse_flattened, FlatOp};
+///
+/// let record = parse_flattened(r#"{"id":1,"__op":"d","__table":"users","__deleted":"true"}"#).unwrap();
+/// assert_eq!(record.operation().unwrap(), Some(FlatOp::Delete));
+///
+/// let tombstone = parse_flattened("null").unwrap();
+/// assert_eq!(tombstone.operation().unwrap(), None);
+/// ```
+pub fn parse_flattened(json: &str) -> Result<FlatRecord, serde_json::Error> {
+    match serde_json::from_str(json)? {
+        serde_json::Value::Null => Ok(FlatRecord {
+            tombstone: true,
+            ..FlatRecord::default()
+        }),
+        serde_json::Value::Object(fields) => {
+            let mut record = FlatRecord::default();
+            for (name, value) in fields {
+                match name.as_str() {
+                    "__op" => {
+                        let op = value.as_str().and_then(FlatOp::from_code).ok_or_else(|| {
+                            serde_json::Error::custom("`__op` is not a Debezium operation code")
+                        })?;
+                        record.op = Some(op);
+                    }
+                    "__table" => match value {
+                        serde_json::Value::String(table) => record.table = Some(table),
+                        _ => return Err(serde_json::Error::custom("`__table` is not a string")),
+                    },
+                    "__deleted" => {
+                        record.deleted = match value {
+                            serde_json::Value::Bool(deleted) => deleted,
+                            serde_json::Value::String(deleted) if deleted == "true" => true,
+                            serde_json::Value::String(deleted) if deleted == "false" => false,
+                            _ => {
+                                return Err(serde_json::Error::custom(
+                                    "`__deleted` is not a boolean",
+                                ));
+                            }
+                        };
+                    }
+                    _ if name.starts_with("__") => {
+                        record.metadata.insert(name, value);
+                    }
+                    _ => {
+                        record.columns.insert(name, value);
+                    }
+                }
+            }
+            Ok(record)
+        }
+        _ => Err(serde_json::Error::custom(
+            "flattened record must be a JSON object or null",
+        )),
+    }
+}
+
+/// Schemas inferred from flattened records, one [`SimpleTable`] per table.
+///
+/// For schema-on-read pipelines that have no catalog: the first record seen
+/// for a table registers its columns, and later records carrying columns not
+/// seen yet add them. A new table's columns follow the order
+/// [`FlatRecord::columns`] yields, sorted by name, and columns seen later are
+/// appended after them in the same order, so the columns already registered
+/// keep their positions. The primary key is read from the Kafka key
+/// message when one is supplied to [`observe_keyed`](Self::observe_keyed),
+/// composite keys in column-name order; otherwise a new table defaults to its
+/// first column.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::debezium::{DebeziumSchemaRegistry, parse_flattened};
+///
+/// let mut registry = DebeziumSchemaRegistry::new();
+/// let record = parse_flattened(r#"{"sku":"a1","name":"Widget","__op":"c","__table":"items"}"#)
+///     .unwrap();
+/// let key = parse_flattened(r#"{"sku":"a1"}"#).unwrap();
+/// registry.observe_keyed(&record, &key).unwrap();
+///
+/// let items = registry.get("items").unwrap();
+/// assert_eq!(items.column_names(), ["name", "sku"]);
+/// assert_eq!(items.pk_indices(), [1]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DebeziumSchemaRegistry {
+    tables: BTreeMap<String, SimpleTable>,
+}
+
+impl DebeziumSchemaRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers or extends the schema of `record`'s table, keeping the
+    /// primary key already inferred for it, or its first column if new.
+    ///
+    /// Tombstones carry no row columns and are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError::MissingData`] if `record` carries no
+    /// `__table`, or [`ConversionError::InvalidTable`] if the inferred table
+    /// is rejected by [`SimpleTable::try_new`].
+    pub fn observe(&mut self, record: &FlatRecord) -> Result<(), ConversionError> {
+        self.register(record, None)
+    }
+
+    /// Registers or extends the schema of `record`'s table, taking its
+    /// primary key from the columns of `key`, the record's Kafka key message
+    /// parsed with [`parse_flattened`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConversionError::MissingData`] if `record` carries no
+    /// `__table`, or [`ConversionError::InvalidTable`] if the inferred table
+    /// is rejected by [`SimpleTable::try_new`].
+    pub fn observe_keyed(
+        &mut self,
+        record: &FlatRecord,
+        key: &FlatRecord,
+    ) -> Result<(), ConversionError> {
+        self.register(record, Some(key))
+    }
+
+    /// Returns the schema inferred for `table`, if any record named it.
+    #[must_use]
+    pub fn get(&self, table: &str) -> Option<&SimpleTable> {
+        self.tables.get(table)
+    }
+
+    fn register(
+        &mut self,
+        record: &FlatRecord,
+        key: Option<&FlatRecord>,
+    ) -> Result<(), ConversionError> {
+        if record.tombstone {
+            return Ok(());
+        }
+        let name = record
+            .table
+            .as_deref()
+            .ok_or(ConversionError::MissingData("__table", "record"))?;
+        let known = self.tables.get(name);
+
+        let mut columns: Vec<&str> = known
+            .map(|table| table.column_names().iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        let mut pk_names: Vec<&str> = Vec::new();
+        if let Some(key) = key {
+            pk_names.extend(key.columns.keys().map(String::as_str));
+        } else if let Some(table) = known {
+            pk_names.extend(
+                table
+                    .pk_indices()
+                    .into_iter()
+                    .filter_map(|idx| table.column_name(idx)),
+            );
+        }
+        for column in record
+            .columns
+            .keys()
+            .map(String::as_str)
+            .chain(pk_names.clone())
+        {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        if columns.is_empty() {
+            return Ok(());
+        }
+
+        let pk_indices: Vec<usize> = if pk_names.is_empty() {
+            alloc::vec![0]
+        } else {
+            pk_names
+                .iter()
+                .filter_map(|pk| columns.iter().position(|column| column == pk))
+                .collect()
+        };
+        let table = SimpleTable::try_new(name, &columns, &pk_indices)?;
+        self.tables.insert(name.to_string(), table);
+        Ok(())
+    }
+}
+
+/// Errors during Debezium to changeset conversion.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConversionError {
+    /// A column name from the record was not found in the table schema.
+    #[error("Column '{0}' not found in table schema")]
+    ColumnNotFound(String),
+
+    /// The event sets a column twice, under one name or two that resolve to
+    /// the same column.
+    #[error("Column '{0}' set more than once")]
+    DuplicateColumn(String),
+
+    /// Table named in the record is not in the schema.
+    #[error("Table '{0}' not found in schema")]
+    TableNotFound(String),
+
+    /// The record is missing required data for the operation.
+    #[error("Missing {0} data for {1} operation")]
+    MissingData(&'static str, &'static str),
+
+    /// User-registered decoder rejected a column payload.
+    #[error("Decoder failed: {0}")]
+    Decode(#[from] crate::wire::DecodeError),
+
+    /// A message in a stream could not be parsed by [`parse_flattened`].
+    #[error("Malformed record: {0}")]
+    MalformedRecord(String),
+    /// Record metadata could not be recorded in the requested column.
+    #[error("Enrichment failed: {0}")]
+    Enrichment(#[from] crate::errors::Error),
+    /// The table inferred by a [`DebeziumSchemaRegistry`] is not a valid
+    /// [`SimpleTable`].
+    #[error("Invalid inferred table: {0}")]
+    InvalidTable(#[from] crate::schema::SimpleTableError),
+}
+
+impl From<PlacementError<'_>> for ConversionError {
+    fn from(error: PlacementError<'_>) -> Self {
+        match error {
+            PlacementError::NotFound(name) => Self::ColumnNotFound(name.into()),
+            PlacementError::Duplicate(name) => Self::DuplicateColumn(name.into()),
+        }
+    }
+}
+
+use crate::ChangesetFormat;
+use crate::builders::{ChangeDelete, Insert, PatchDelete, Update};
+use crate::encoding::Value;
+use crate::schema::{NamedColumns, SimpleTable};
+
+use crate::wire::{ColumnPlacer, PlacementError, Sealed, WireSource, WireType};
+
+use crate::builders::{DiffOps, DiffSetBuilder, Format, PatchsetFormat};
+use crate::wire::WireAdapter;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+/// Marker type for the `debezium` source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Debezium;
+
+impl Sealed for Debezium {}
+
+impl WireSource for Debezium {
+    type Payload<'a> = DebeziumColumn<'a>;
+
+    fn wire_type(payload: &Self::Payload<'_>) -> WireType {
+        payload.wire_type
+    }
+
+    fn column_name<'a>(payload: &'a Self::Payload<'_>) -> &'a str {
+        payload.column_name
+    }
+}
+
+/// Per-column payload for the `debezium` source.
+///
+/// The schema supplies the semantic [`WireType`] per column; flattened
+/// records carry no type information.
+#[derive(Debug, Clone, Copy)]
+pub struct DebeziumColumn<'a> {
+    /// Column name.
+    pub column_name: &'a str,
+    /// Semantic column type driving decoder dispatch.
+    pub wire_type: WireType,
+    /// Column value as a JSON value.
+    pub value: &'a serde_json::Value,
+}
+
+impl DebeziumColumn<'_> {
+    /// Ergonomic helper for calling a specific [`Decoder`](crate::wire::Decoder) on this
+    /// payload without fully-qualified syntax. Fixes the `Src` generic
+    /// to [`Debezium`] so the compiler can pick the impl.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the decoder's [`DecodeError`](crate::wire::DecodeError).
+    pub fn decoded_by<D, S, B>(
+        self,
+        decoder: &D,
+    ) -> Result<crate::encoding::Value<S, B>, crate::wire::DecodeError>
+    where
+        D: crate::wire::Decoder<Debezium, S, B>,
+    {
+        decoder.decode(self)
+    }
+}
+
+use crate::wire::{
+    Digestable, Enrichment, MaybeOperation, WireColumnTypes, WireOperation, WireSchema,
+};
+
+impl<T, S, B> Digestable<ChangesetFormat, T, S, B> for FlatRecord
+where
+    T: NamedColumns + WireColumnTypes,
+    S: Clone + Debug + Hash + Eq + AsRef<str> + Default,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]> + Default,
+{
+    type Src = Debezium;
+    type Error = ConversionError;
+
+    fn to_operation<Sch, A>(
+        &self,
+        schema: &Sch,
+        adapter: &A,
+    ) -> Result<MaybeOperation<ChangesetFormat, T, S, B>, ConversionError>
+    where
+        Sch: WireSchema<Table = T>,
+        A: WireAdapter<Debezium, S, B>,
+    {
+        let Some(op) = self.operation()? else {
+            return Ok(None);
+        };
+        let table = resolve_table(schema, self.table.as_deref())?;
+        match op {
+            FlatOp::Create | FlatOp::Read => Ok(Some(WireOperation::Insert(insert_row(
+                &self.columns,
+                table,
+                adapter,
+            )?))),
+            FlatOp::Update => Err(ConversionError::MissingData("before", "UPDATE")),
+            FlatOp::Delete if self.tombstone => {
+                Err(ConversionError::MissingData("before", "DELETE"))
+            }
+            FlatOp::Delete => {
+                let mut delete = ChangeDelete::from(table.clone());
+                for (name, col_idx, value) in decode_columns(&self.columns, table, adapter)? {
+                    delete = delete
+                        .set(col_idx, value)
+                        .map_err(|_| ConversionError::ColumnNotFound(name.clone()))?;
+                }
+                Ok(Some(WireOperation::Delete(delete)))
+            }
+        }
+    }
+}
+
+impl<T, S, B> Digestable<PatchsetFormat, T, S, B> for FlatRecord
+where
+    T: NamedColumns + WireColumnTypes,
+    S: Clone + Debug + Hash + Eq + AsRef<str> + Default,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]> + Default,
+{
+    type Src = Debezium;
+    type Error = ConversionError;
+
+    fn to_operation<Sch, A>(
+        &self,
+        schema: &Sch,
+        adapter: &A,
+    ) -> Result<MaybeOperation<PatchsetFormat, T, S, B>, ConversionError>
+    where
+        Sch: WireSchema<Table = T>,
+        A: WireAdapter<Debezium, S, B>,
+    {
+        let Some(op) = self.operation()? else {
+            return Ok(None);
+        };
+        let table = resolve_table(schema, self.table.as_deref())?;
+        match op {
+            FlatOp::Create | FlatOp::Read => Ok(Some(WireOperation::Insert(insert_row(
+                &self.columns,
+                table,
+                adapter,
+            )?))),
+            FlatOp::Update => {
+                let mut update: Update<T, PatchsetFormat, S, B> = Update::from(table.clone());
+                for (name, col_idx, value) in decode_columns(&self.columns, table, adapter)? {
+                    update = update
+                        .set(col_idx, value)
+                        .map_err(|_| ConversionError::ColumnNotFound(name.clone()))?;
+                }
+                Ok(Some(WireOperation::Update(update)))
+            }
+            FlatOp::Delete => {
+                let mut pk_slots: Vec<Option<Value<S, B>>> =
+                    alloc::vec![None; table.number_of_primary_keys()];
+                for (_, col_idx, value) in decode_columns(&self.columns, table, adapter)? {
+                    if let Some(pk_idx) = table.primary_key_index(col_idx) {
+                        pk_slots[pk_idx] = Some(value);
+                    }
+                }
+                let pk = pk_slots
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(ConversionError::MissingData("pk", "DELETE"))?;
+                Ok(Some(WireOperation::Delete(PatchDelete::new(
+                    table.clone(),
+                    pk,
+                ))))
+            }
+        }
+    }
+}
+
+impl FlatRecord {
+    /// Metadata of this record to record for `enrichment`, or NULL if the
+    /// record does not carry it.
+    ///
+    /// The event timestamp comes from `__source_ts_ms`, when the database
+    /// applied the change, falling back to `__ts_ms`, when the connector
+    /// processed it; the transaction id from `__transaction_id`; and the
+    /// [`Lsn`](Enrichment::Lsn) from `__source_lsn`. The SMT adds each only
+    /// when listed in `add.fields`. JSON integers are recorded as INTEGER
+    /// and strings as TEXT.
+    #[must_use]
+    pub fn enrichment<S: From<String>, B>(&self, enrichment: Enrichment) -> Value<S, B> {
+        let field = match enrichment {
+            Enrichment::EventTimestamp => self
+                .metadata
+                .get("__source_ts_ms")
+                .or_else(|| self.metadata.get("__ts_ms")),
+            Enrichment::TransactionId => self.transaction_id(),
+            Enrichment::Lsn => self.metadata.get("__source_lsn"),
+        };
+        match field {
+            Some(serde_json::Value::Number(n)) => n
+                .as_i64()
+                .map_or_else(|| Value::Text(S::from(n.to_string())), Value::Integer),
+            Some(serde_json::Value::String(s)) => Value::Text(S::from(s.clone())),
+            _ => Value::Null,
+        }
+    }
+
+    /// Convert this record as [`Digestable::to_operation`] does, then record
+    /// the requested metadata in the row: each `(column, enrichment)` pair
+    /// writes [`enrichment`](Self::enrichment) into the column at that index,
+    /// as [`WireOperation::enrich`] does.
+    ///
+    /// # Errors
+    ///
+    /// Any conversion error, or [`ConversionError::Enrichment`] if a column
+    /// index is out of bounds.
+    pub fn to_enriched_operation<F, T, S, B, Sch, A>(
+        &self,
+        schema: &Sch,
+        adapter: &A,
+        enrichments: &[(usize, Enrichment)],
+    ) -> Result<MaybeOperation<F, T, S, B>, ConversionError>
+    where
+        F: Format<S, B>,
+        T: NamedColumns + WireColumnTypes,
+        S: AsRef<str> + From<String>,
+        B: AsRef<[u8]>,
+        Sch: WireSchema<Table = T>,
+        A: WireAdapter<Debezium, S, B>,
+        Self: Digestable<F, T, S, B, Src = Debezium, Error = ConversionError>,
+    {
+        let Some(operation) = self.to_operation(schema, adapter)? else {
+            return Ok(None);
+        };
+        let values = enrichments
+            .iter()
+            .map(|&(column, enrichment)| (column, self.enrichment(enrichment)));
+        Ok(Some(operation.enrich(values)?))
+    }
+}
+
+fn resolve_table<'a, Sch>(
+    schema: &'a Sch,
+    name: Option<&str>,
+) -> Result<&'a Sch::Table, ConversionError>
+where
+    Sch: WireSchema,
+{
+    let name = name.ok_or(ConversionError::MissingData("__table", "record"))?;
+    schema
+        .get(name)
+        .ok_or_else(|| ConversionError::TableNotFound(name.into()))
+}
+
+/// Build the INSERT of the row made of `columns`.
+fn insert_row<T, S, B, A>(
+    columns: &BTreeMap<String, serde_json::Value>,
+    table: &T,
+    adapter: &A,
+) -> Result<Insert<T, S, B>, ConversionError>
+where
+    T: NamedColumns + WireColumnTypes,
+    S: Clone + Debug + Hash + Eq + AsRef<str> + Default,
+    B: Clone + Debug + Hash + Eq + AsRef<[u8]> + Default,
+    A: WireAdapter<Debezium, S, B>,
+{
+    let mut insert = Insert::from(table.clone());
+    for (name, col_idx, value) in decode_columns(columns, table, adapter)? {
+        insert = insert
+            .set(col_idx, value)
+            .map_err(|_| ConversionError::ColumnNotFound(name.clone()))?;
+    }
+    Ok(insert)
+}
+
+/// A decoded column with its name and index in the table schema.
+type DecodedColumn<'a, S, B> = (&'a String, usize, Value<S, B>);
+
+/// Decode every row column, paired with its name and index in `table`.
+fn decode_columns<'a, T, S, B, A>(
+    columns: &'a BTreeMap<String, serde_json::Value>,
+    table: &T,
+    adapter: &A,
+) -> Result<Vec<DecodedColumn<'a, S, B>>, ConversionError>
+where
+    T: NamedColumns + WireColumnTypes,
+    A: WireAdapter<Debezium, S, B>,
+{
+    let mut placer = ColumnPlacer::new(table);
+    columns
+        .iter()
+        .map(|(name, value)| {
+            let col_idx = placer.place(table, name)?;
+            let payload = DebeziumColumn {
+                column_name: name.as_str(),
+                wire_type: table.column_type(col_idx),
+                value,
+            };
+            Ok((name, col_idx, adapter.decode(payload)?))
+        })
+        .collect()
+}
+
+/// Digest a stream of flattened Debezium messages into one serialized
+/// changeset per transaction.
+///
+/// Consecutive records with the same
+/// [`transaction_id`](FlatRecord::transaction_id) make up one transaction,
+/// and a new changeset starts whenever the id changes. Records without an id,
+/// such as snapshot reads, group the same way, so a run of them yields one
+/// changeset. Tombstones digest as no-ops and never start a transaction; the
+/// stream carries no Kafka keys, so digest
+/// [keyed tombstones](FlatRecord::with_key) record by record instead.
+///
+/// Flattened updates carry no old values, so any UPDATE fails with
+/// [`ConversionError::MissingData`]; see
+/// [`debezium_stream_to_patchsets`] to keep them.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::MalformedRecord`] if a message does not parse,
+/// or the first error raised while digesting a record.
+pub fn debezium_stream_to_changesets<'a, I, Sch, A>(
+    jsons: I,
+    schema: &Sch,
+    adapter: &A,
+) -> Result<Vec<Vec<u8>>, ConversionError>
+where
+    I: IntoIterator<Item = &'a str>,
+    Sch: WireSchema,
+    A: WireAdapter<Debezium, String, Vec<u8>>,
+{
+    stream_to_diffsets::<ChangesetFormat, _, _, _>(jsons, schema, adapter)
+}
+
+/// Digest a stream of flattened Debezium messages into one serialized
+/// patchset per transaction.
+///
+/// Transactions are delimited as in [`debezium_stream_to_changesets`].
+/// Patchsets need no old values, so updates are kept.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::MalformedRecord`] if a message does not parse,
+/// or the first error raised while digesting a record.
+pub fn debezium_stream_to_patchsets<'a, I, Sch, A>(
+    jsons: I,
+    schema: &Sch,
+    adapter: &A,
+) -> Result<Vec<Vec<u8>>, ConversionError>
+where
+    I: IntoIterator<Item = &'a str>,
+    Sch: WireSchema,
+    A: WireAdapter<Debezium, String, Vec<u8>>,
+{
+    stream_to_diffsets::<PatchsetFormat, _, _, _>(jsons, schema, adapter)
+}
+
+/// The transaction a stream is digesting: its id and the builder collecting it.
+type OpenTransaction<F, T> = (
+    Option<serde_json::Value>,
+    DiffSetBuilder<F, T, String, Vec<u8>>,
+);
+
+/// Shared body of the stream helpers, generic over the output format.
+fn stream_to_diffsets<'a, F, I, Sch, A>(
+    jsons: I,
+    schema: &Sch,
+    adapter: &A,
+) -> Result<Vec<Vec<u8>>, ConversionError>
+where
+    F: Format<String, Vec<u8>>,
+    I: IntoIterator<Item = &'a str>,
+    Sch: WireSchema,
+    A: WireAdapter<Debezium, String, Vec<u8>>,
+    FlatRecord: Digestable<F, Sch::Table, String, Vec<u8>, Src = Debezium, Error = ConversionError>,
+    Vec<u8>: From<DiffSetBuilder<F, Sch::Table, String, Vec<u8>>>,
+    DiffSetBuilder<F, Sch::Table, String, Vec<u8>>: DiffOps<
+            Sch::Table,
+            String,
+            Vec<u8>,
+            Format = F,
+            Output = DiffSetBuilder<F, Sch::Table, String, Vec<u8>>,
+            DeleteArg = F::Delete<Sch::Table>,
+        >,
+{
+    let mut diffsets = Vec::new();
+    let mut open: Option<OpenTransaction<F, Sch::Table>> = None;
+    for json in jsons {
+        let record = parse_flattened(json)
+            .map_err(|err| ConversionError::MalformedRecord(err.to_string()))?;
+        if record.tombstone {
+            continue;
+        }
+        let id = record.transaction_id().cloned();
+        let builder = match open.take() {
+            Some((open_id, builder)) if open_id == id => builder,
+            Some((_, builder)) => {
+                diffsets.push(builder.into());
+                DiffSetBuilder::default()
+            }
+            None => DiffSetBuilder::default(),
+        };
+        open = Some((id, record.digest_into(builder, schema, adapter)?));
+    }
+    if let Some((_, builder)) = open {
+        diffsets.push(builder.into());
+    }
+    Ok(diffsets)
+}
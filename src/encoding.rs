@@ -3,9 +3,13 @@
 //! `SQLite` uses specific binary encodings for varints and value serialization.
 
 pub(crate) mod constants;
+pub(crate) mod hex;
 pub(crate) mod serial;
 pub(crate) mod varint;
 
-pub(crate) use constants::{markers, op_codes};
-pub use serial::Value;
-pub(crate) use serial::{MaybeValue, decode_value, encode_defined_value, encode_value};
+pub(crate) use constants::{limits, markers, op_codes};
+pub use serial::{
+    Collation, TruncatedValue, Value, compare_values, decode_value, encode_value, format_truncated,
+    values_differ_collated, values_differ_for_patch,
+};
+pub(crate) use serial::{MaybeValue, decode_maybe_value, encode_defined_value};
@@ -17,3 +17,11 @@ pub mod markers {
     /// Patchset table marker ('P').
     pub const PATCHSET: u8 = b'P';
 }
+
+/// Default compile-time limits of `SQLite` that a diffset can exceed.
+pub mod limits {
+    /// `SQLITE_MAX_COLUMN`: most columns a table can have.
+    pub const MAX_COLUMN: usize = 2000;
+    /// `SQLITE_MAX_LENGTH`: most bytes a text or blob value can hold.
+    pub const MAX_LENGTH: usize = 1_000_000_000;
+}
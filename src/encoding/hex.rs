@@ -0,0 +1,63 @@
+//! Hex encoding for blob rendering, using only `core::fmt`.
+//!
+//! SQL blob literals (`X'..'`) use uppercase digits and `PostgreSQL` `BYTEA`
+//! text (`\x..`) and canonical JSON blobs use lowercase digits. All go
+//! through [`write_hex`], which writes into any [`core::fmt::Write`] sink
+//! without allocating, so the rendering stays available to `no_std`
+//! consumers.
+
+use core::fmt;
+
+/// Uppercase hex digits, as used in SQL blob literals.
+pub(crate) const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Lowercase hex digits, as used in `PostgreSQL` `BYTEA` text and canonical
+/// JSON blobs.
+pub(crate) const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+
+/// Writes `bytes` as two hex digits per byte, drawn from `digits`.
+pub(crate) fn write_hex<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    bytes: &[u8],
+    digits: &[u8; 16],
+) -> fmt::Result {
+    for &byte in bytes {
+        out.write_char(char::from(digits[usize::from(byte >> 4)]))?;
+        out.write_char(char::from(digits[usize::from(byte & 0x0f)]))?;
+    }
+    Ok(())
+}
+
+/// Writes `bytes` as the SQL blob literal `X'..'`.
+pub(crate) fn write_blob_literal<W: fmt::Write + ?Sized>(out: &mut W, bytes: &[u8]) -> fmt::Result {
+    out.write_str("X'")?;
+    write_hex(out, bytes, HEX_UPPER)?;
+    out.write_char('\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+
+    #[test]
+    fn test_blob_literal_deadbeef() {
+        let mut out = String::new();
+        write_blob_literal(&mut out, &[0xde, 0xad, 0xbe, 0xef]).unwrap();
+        assert_eq!(out, "X'DEADBEEF'");
+    }
+
+    #[test]
+    fn test_blob_literal_empty() {
+        let mut out = String::new();
+        write_blob_literal(&mut out, &[]).unwrap();
+        assert_eq!(out, "X''");
+    }
+
+    #[test]
+    fn test_lowercase_digits() {
+        let mut out = String::new();
+        write_hex(&mut out, &[0x00, 0x0f, 0xa5], HEX_LOWER).unwrap();
+        assert_eq!(out, "000fa5");
+    }
+}
@@ -15,12 +15,15 @@
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::hash::{Hash, Hasher};
 
 use super::varint::encode_varint_simple;
+use crate::parser::ParseError;
 
 /// A value that can be encoded in `SQLite` changeset format.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value<S, B> {
     /// SQL NULL
     Null,
@@ -152,6 +155,156 @@ impl<S: AsRef<str>, B: AsRef<[u8]>> Value<S, B> {
 
 mod display;
 
+pub use display::{TruncatedValue, format_truncated};
+
+/// Returns `true` if a column going from `old` to `new` counts as changed in
+/// a patchset `UPDATE`.
+///
+/// This follows the session extension's change detection: values of
+/// different storage classes always differ, so `1` and `1.0` are distinct,
+/// `NULL` equals `NULL`, reals compare numerically, and text and blobs
+/// compare byte for byte (text comparison is case-sensitive).
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{Value, values_differ_for_patch};
+///
+/// type V = Value<&'static str, &'static [u8]>;
+///
+/// assert!(values_differ_for_patch(&V::Integer(1), &V::Real(1.0)));
+/// assert!(!values_differ_for_patch(&V::Null, &V::Null));
+/// assert!(values_differ_for_patch(&V::Text("a"), &V::Text("A")));
+/// ```
+#[must_use]
+pub fn values_differ_for_patch<S: AsRef<str>, B: AsRef<[u8]>>(
+    old: &Value<S, B>,
+    new: &Value<S, B>,
+) -> bool {
+    match (old, new) {
+        (Value::Null, Value::Null) => false,
+        (Value::Integer(a), Value::Integer(b)) => a != b,
+        #[allow(clippy::float_cmp)]
+        (Value::Real(a), Value::Real(b)) => a != b,
+        (Value::Text(a), Value::Text(b)) => a.as_ref() != b.as_ref(),
+        (Value::Blob(a), Value::Blob(b)) => a.as_ref() != b.as_ref(),
+        _ => true,
+    }
+}
+
+/// Orders two values as `SQLite` sorts them: NULL first, then integers and
+/// reals compared numerically, then text byte for byte (the `BINARY`
+/// collation), then blobs byte for byte.
+///
+/// Unlike [`PartialEq`], which tells storage classes apart, `1` and `1.0`
+/// compare equal here.
+///
+/// # Example
+///
+/// ```
+/// use core::cmp::Ordering;
+/// use sqlite_diff_rs::{Value, compare_values};
+///
+/// type V = Value<&'static str, &'static [u8]>;
+///
+/// assert_eq!(compare_values(&V::Null, &V::Integer(i64::MIN)), Ordering::Less);
+/// assert_eq!(compare_values(&V::Integer(2), &V::Real(1.5)), Ordering::Greater);
+/// assert_eq!(compare_values(&V::Integer(1), &V::Real(1.0)), Ordering::Equal);
+/// assert_eq!(compare_values(&V::Real(1e300), &V::Text("0")), Ordering::Less);
+/// assert_eq!(compare_values(&V::Text("b"), &V::Blob(b"a")), Ordering::Less);
+/// ```
+#[must_use]
+pub fn compare_values<S: AsRef<str>, B: AsRef<[u8]>>(a: &Value<S, B>, b: &Value<S, B>) -> Ordering {
+    /// Rank of a value's storage class in the sort order.
+    fn rank<S, B>(value: &Value<S, B>) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Integer(_) | Value::Real(_) => 1,
+            Value::Text(_) => 2,
+            Value::Blob(_) => 3,
+        }
+    }
+
+    match (a, b) {
+        (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+        (Value::Real(a), Value::Real(b)) => a.partial_cmp(b).unwrap_or_else(|| a.total_cmp(b)),
+        (Value::Integer(i), Value::Real(r)) => compare_integer_real(*i, *r),
+        (Value::Real(r), Value::Integer(i)) => compare_integer_real(*i, *r).reverse(),
+        (Value::Text(a), Value::Text(b)) => a.as_ref().cmp(b.as_ref()),
+        (Value::Blob(a), Value::Blob(b)) => a.as_ref().cmp(b.as_ref()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Compares an integer with a real exactly, as `sqlite3IntFloatCompare` does,
+/// rather than through a lossy conversion of the integer.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+pub(crate) fn compare_integer_real(i: i64, r: f64) -> Ordering {
+    if r < -9_223_372_036_854_775_808.0 {
+        return Ordering::Greater;
+    }
+    if r >= 9_223_372_036_854_775_808.0 {
+        return Ordering::Less;
+    }
+    let truncated = r as i64;
+    i.cmp(&truncated)
+        .then_with(|| (i as f64).partial_cmp(&r).unwrap_or(Ordering::Equal))
+}
+
+/// A `SQLite` text collation, deciding whether two text values are equal.
+///
+/// The session extension itself compares text byte for byte, whatever the
+/// column's collation; [`values_differ_collated`] lets change detection
+/// follow the collation instead, so an UPDATE that only rewrites text into
+/// an equal form under it is treated as a no-op.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Collation {
+    /// Byte-for-byte comparison, `SQLite`'s default.
+    #[default]
+    Binary,
+    /// Case-insensitive for the 26 ASCII letters only, as `SQLite`'s `NOCASE`.
+    NoCase,
+    /// Ignores trailing spaces, as `SQLite`'s `RTRIM`.
+    RTrim,
+}
+
+impl Collation {
+    /// Returns `true` if `a` and `b` are equal under this collation.
+    #[must_use]
+    pub fn text_eq(self, a: &str, b: &str) -> bool {
+        match self {
+            Collation::Binary => a == b,
+            Collation::NoCase => a.eq_ignore_ascii_case(b),
+            Collation::RTrim => a.trim_end_matches(' ') == b.trim_end_matches(' '),
+        }
+    }
+}
+
+/// Like [`values_differ_for_patch`], but compares text under `collation`.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{Collation, Value, values_differ_collated};
+///
+/// type V = Value<&'static str, &'static [u8]>;
+///
+/// assert!(values_differ_collated(&V::Text("abc"), &V::Text("ABC"), Collation::Binary));
+/// assert!(!values_differ_collated(&V::Text("abc"), &V::Text("ABC"), Collation::NoCase));
+/// assert!(!values_differ_collated(&V::Text("abc  "), &V::Text("abc"), Collation::RTrim));
+/// ```
+#[must_use]
+pub fn values_differ_collated<S: AsRef<str>, B: AsRef<[u8]>>(
+    old: &Value<S, B>,
+    new: &Value<S, B>,
+    collation: Collation,
+) -> bool {
+    match (old, new) {
+        (Value::Text(a), Value::Text(b)) => !collation.text_eq(a.as_ref(), b.as_ref()),
+        _ => values_differ_for_patch(old, new),
+    }
+}
+
 /// Encode the "undefined" marker (type 0) into the changeset binary format.
 ///
 /// This is used for unchanged columns in UPDATE operations.
@@ -160,7 +313,8 @@ pub(crate) fn encode_undefined(out: &mut Vec<u8>) {
     out.push(0x00);
 }
 
-/// Encode a Maybe value (Option<Value>) into the changeset binary format.
+/// Encode a value, or `None` for the undefined marker, into the changeset
+/// binary format, appending it to `out`.
 ///
 /// `SQLite` changesets use a DIFFERENT encoding than database records:
 /// - Type 0: Undefined (special marker for unchanged columns in UPDATE)
@@ -171,10 +325,22 @@ pub(crate) fn encode_undefined(out: &mut Vec<u8>) {
 /// - Type 5: NULL (no data follows)
 ///
 /// This is NOT the same as `SQLite` serial types used in database records!
-pub(crate) fn encode_value<S: AsRef<str>, B: AsRef<[u8]>>(
-    out: &mut Vec<u8>,
-    value: Option<&Value<S, B>>,
-) {
+/// As `SQLite` does, a NaN real is written as NULL and `-0.0` as `0.0`.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{Value, decode_value, encode_value};
+///
+/// let mut out = Vec::new();
+/// encode_value(&mut out, Some(&Value::<&str, &[u8]>::Text("hi")));
+/// encode_value::<&str, &[u8]>(&mut out, None);
+/// assert_eq!(out, [0x03, 2, b'h', b'i', 0x00]);
+///
+/// assert_eq!(decode_value(&out), Ok((Some(Value::Text("hi".into())), 4)));
+/// assert_eq!(decode_value(&out[4..]), Ok((None, 1)));
+/// ```
+pub fn encode_value<S: AsRef<str>, B: AsRef<[u8]>>(out: &mut Vec<u8>, value: Option<&Value<S, B>>) {
     match value {
         None => encode_undefined(out),
         Some(v) => encode_defined_value(out, v),
@@ -225,7 +391,7 @@ pub(crate) fn encode_defined_value<S: AsRef<str>, B: AsRef<[u8]>>(
     }
 }
 
-/// Decode a value from changeset binary format.
+/// Decode one value from the start of `input`, in changeset binary format.
 ///
 /// `SQLite` changesets use the following type codes:
 /// - 0: Undefined (unchanged column in UPDATE)
@@ -235,9 +401,27 @@ pub(crate) fn encode_defined_value<S: AsRef<str>, B: AsRef<[u8]>>(
 /// - 4: BLOB (varint length + raw bytes)
 /// - 5: NULL
 ///
-/// Returns the value (None for Undefined) and number of bytes consumed.
+/// Returns the value (None for Undefined) and number of bytes consumed;
+/// bytes after the value are left alone. See [`encode_value`] for an
+/// example.
+///
+/// # Errors
+///
+/// Returns [`ParseError::UnexpectedEof`] if `input` is empty and
+/// [`ParseError::InvalidValue`] if it does not start with a well-formed
+/// value: an unknown type code, a truncated payload or invalid UTF-8 text.
+/// Positions are offsets into `input`.
+pub fn decode_value(input: &[u8]) -> Result<(MaybeValue<String, Vec<u8>>, usize), ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::UnexpectedEof(0));
+    }
+    decode_maybe_value(input).ok_or(ParseError::InvalidValue(0))
+}
+
+/// Decode a value from changeset binary format, as [`decode_value`] does,
+/// returning `None` on malformed input.
 #[must_use]
-pub(crate) fn decode_value(data: &[u8]) -> Option<(MaybeValue<String, Vec<u8>>, usize)> {
+pub(crate) fn decode_maybe_value(data: &[u8]) -> Option<(MaybeValue<String, Vec<u8>>, usize)> {
     use super::varint::decode_varint;
 
     if data.is_empty() {
@@ -487,4 +671,72 @@ mod tests {
         let v: V = None::<i64>.into();
         assert_eq!(v, Value::Null);
     }
+
+    #[test]
+    fn test_values_differ_for_patch_int_vs_real() {
+        assert!(values_differ_for_patch(
+            &TestValue::Integer(1),
+            &TestValue::Real(1.0)
+        ));
+        assert!(!values_differ_for_patch(
+            &TestValue::Real(0.0),
+            &TestValue::Real(-0.0)
+        ));
+        assert!(values_differ_for_patch(
+            &TestValue::Integer(1),
+            &TestValue::Integer(2)
+        ));
+    }
+
+    #[test]
+    fn test_values_differ_collated_binary_vs_nocase() {
+        let lower = TestValue::Text("abc".into());
+        let upper = TestValue::Text("ABC".into());
+        assert!(values_differ_collated(&lower, &upper, Collation::Binary));
+        assert!(!values_differ_collated(&lower, &upper, Collation::NoCase));
+        assert!(values_differ_collated(&lower, &upper, Collation::RTrim));
+        // NOCASE folds ASCII letters only.
+        assert!(values_differ_collated(
+            &TestValue::Text("é".into()),
+            &TestValue::Text("É".into()),
+            Collation::NoCase
+        ));
+
+        let padded = TestValue::Text("abc  ".into());
+        assert!(!values_differ_collated(&lower, &padded, Collation::RTrim));
+        assert!(values_differ_collated(&lower, &padded, Collation::NoCase));
+        assert!(values_differ_collated(
+            &TestValue::Text(" abc".into()),
+            &lower,
+            Collation::RTrim
+        ));
+
+        // Other storage classes are unaffected.
+        assert!(values_differ_collated(
+            &TestValue::Text("a".into()),
+            &TestValue::Blob(b"A".to_vec()),
+            Collation::NoCase
+        ));
+    }
+
+    #[test]
+    fn test_values_differ_for_patch_null() {
+        assert!(!values_differ_for_patch(&TestValue::Null, &TestValue::Null));
+        assert!(values_differ_for_patch(
+            &TestValue::Null,
+            &TestValue::Integer(0)
+        ));
+    }
+
+    #[test]
+    fn test_values_differ_for_patch_text_is_case_sensitive() {
+        let lower = TestValue::Text("alice".into());
+        let upper = TestValue::Text("Alice".into());
+        assert!(values_differ_for_patch(&lower, &upper));
+        assert!(!values_differ_for_patch(&lower, &lower.clone()));
+        assert!(values_differ_for_patch(
+            &TestValue::Text("a".into()),
+            &TestValue::Blob(b"a".to_vec())
+        ));
+    }
 }
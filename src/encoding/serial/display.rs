@@ -1,6 +1,9 @@
 //! Display implementation for Value as SQL literals.
 
+use alloc::string::{String, ToString};
+
 use super::Value;
+use crate::encoding::hex::write_blob_literal;
 
 impl<S: AsRef<str>, B: AsRef<[u8]>> core::fmt::Display for Value<S, B> {
     /// Format a Value as a SQL literal.
@@ -32,21 +35,85 @@ impl<S: AsRef<str>, B: AsRef<[u8]>> core::fmt::Display for Value<S, B> {
                 }
                 write!(f, "'")
             }
-            Value::Blob(b) => {
-                write!(f, "X'")?;
-                for byte in b.as_ref() {
-                    write!(f, "{byte:02X}")?;
+            Value::Blob(b) => write_blob_literal(f, b.as_ref()),
+            Value::Null => write!(f, "NULL"),
+        }
+    }
+}
+
+/// Renders `value` as [`Display`](core::fmt::Display) does, keeping at most
+/// `max` bytes of text or blob with a `…(N bytes)` suffix giving the full
+/// length.
+///
+/// Meant for logging parsed operations, where a large blob would otherwise
+/// flood the log. Text is cut on a character boundary, so it may keep fewer
+/// than `max` bytes.
+///
+/// # Example
+///
+/// ```
+/// use sqlite_diff_rs::{Value, format_truncated};
+///
+/// let value: Value<String, Vec<u8>> = Value::Blob(vec![0xAB; 1024]);
+/// assert_eq!(format_truncated(&value, 2), "X'ABAB'…(1024 bytes)");
+/// ```
+#[must_use]
+pub fn format_truncated<S: AsRef<str>, B: AsRef<[u8]>>(value: &Value<S, B>, max: usize) -> String {
+    value.truncated(max).to_string()
+}
+
+/// A [`Value`] rendered with its text or blob truncated, returned by
+/// [`Value::truncated`].
+///
+/// Both [`Display`](core::fmt::Display) and [`Debug`](core::fmt::Debug)
+/// render as [`format_truncated`], so it can be passed to logging macros
+/// without allocating.
+#[derive(Clone, Copy)]
+pub struct TruncatedValue<'value, S, B> {
+    value: &'value Value<S, B>,
+    max: usize,
+}
+
+impl<S, B> Value<S, B> {
+    /// Wraps the value to render it as [`format_truncated`] does.
+    #[inline]
+    #[must_use]
+    pub fn truncated(&self, max: usize) -> TruncatedValue<'_, S, B> {
+        TruncatedValue { value: self, max }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> core::fmt::Display for TruncatedValue<'_, S, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.value {
+            Value::Text(s) if s.as_ref().len() > self.max => {
+                let s = s.as_ref();
+                let mut end = self.max;
+                while !s.is_char_boundary(end) {
+                    end -= 1;
                 }
-                write!(f, "'")
+                let kept: Value<&str, &[u8]> = Value::Text(&s[..end]);
+                write!(f, "{kept}…({} bytes)", s.len())
             }
-            Value::Null => write!(f, "NULL"),
+            Value::Blob(b) if b.as_ref().len() > self.max => {
+                let b = b.as_ref();
+                write_blob_literal(f, &b[..self.max])?;
+                write!(f, "…({} bytes)", b.len())
+            }
+            value => core::fmt::Display::fmt(value, f),
         }
     }
 }
 
+impl<S: AsRef<str>, B: AsRef<[u8]>> core::fmt::Debug for TruncatedValue<'_, S, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(self, f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Value;
+    use super::{Value, format_truncated};
     use alloc::format;
     use alloc::string::String;
     use alloc::vec;
@@ -139,4 +206,23 @@ mod tests {
         let v: TestValue = Value::Blob(Vec::new());
         assert_eq!(format!("{v}"), "X''");
     }
+
+    #[test]
+    fn test_format_truncated_long_text_reports_byte_count() {
+        let v: TestValue = Value::Text("é".repeat(600));
+        assert_eq!(format_truncated(&v, 5), "'éé'…(1200 bytes)");
+        assert_eq!(format!("{:?}", v.truncated(4)), "'éé'…(1200 bytes)");
+    }
+
+    #[test]
+    fn test_format_truncated_keeps_short_values() {
+        let v: TestValue = Value::Text("it's".into());
+        assert_eq!(format_truncated(&v, 4), "'it''s'");
+        let v: TestValue = Value::Blob(vec![0x01, 0xAB]);
+        assert_eq!(format_truncated(&v, 2), "X'01AB'");
+        assert_eq!(
+            format_truncated(&Value::<String, Vec<u8>>::Integer(12345), 1),
+            "12345"
+        );
+    }
 }
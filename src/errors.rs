@@ -1,9 +1,269 @@
 //! Submodule defining the errors used across the crate.
 
+use alloc::string::{String, ToString};
+
 /// Errors that can occur during diffing and patching operations.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum Error {
     /// The provided index is out of bounds for the number of columns in the table.
     #[error("Column index {0} out of bounds for table with {1} columns")]
     ColumnIndexOutOfBounds(usize, usize),
+
+    /// The same column index was assigned more than once in a single batch.
+    #[error("Column index {0} assigned more than once")]
+    DuplicateColumnIndex(usize),
+
+    /// A patchset UPDATE was asked to change the primary key, which the
+    /// format cannot express: it stores the key only once.
+    #[error("A patchset UPDATE cannot change the primary key")]
+    PatchsetPkChange,
+
+    /// A patchset UPDATE was built without a value for a primary-key column,
+    /// so the patchset could not locate the row it changes.
+    #[error("A patchset UPDATE has no value for primary key column {0}")]
+    MissingPkInPatchUpdate(usize),
+
+    /// A row was given a number of values different from its table's
+    /// column count.
+    #[error("{0} values given for table with {1} columns")]
+    ValueCountMismatch(usize, usize),
+
+    /// A primary key was given a number of values different from its
+    /// table's primary-key column count.
+    #[error("{0} key values given for table with {1} primary-key columns")]
+    KeyCountMismatch(usize, usize),
+
+    /// A row keyed by column name refers to a column the table does not have.
+    #[error("Column {0:?} not found")]
+    ColumnNotFound(String),
+
+    /// A row keyed by column name has no value for a primary-key column, so
+    /// the row it stands for could not be located.
+    #[error("A row keyed by column name has no value for primary key column {0}")]
+    MissingPkColumn(usize),
+
+    /// A table name contains a NUL byte, which would end the name early in
+    /// the table header and leave the rest of the diffset unparseable.
+    #[error("Table name {0:?} contains a NUL byte")]
+    InvalidTableName(String),
+
+    /// A table has more columns than `SQLite` allows, so it cannot exist in
+    /// the database the diffset is applied to.
+    #[error(
+        "Table {0:?} has {1} columns, more than SQLite's limit of {max}",
+        max = crate::encoding::limits::MAX_COLUMN
+    )]
+    TooManyColumns(String, usize),
+
+    /// A text or blob value, in the table and column given, is longer than
+    /// `SQLite` allows, so applying the diffset would fail to store it.
+    #[error(
+        "Value of column {1} in table {0:?} is {2} bytes, more than SQLite's limit of {max}",
+        max = crate::encoding::limits::MAX_LENGTH
+    )]
+    ValueTooLarge(String, usize, usize),
+}
+
+/// Any error this crate reports, for callers that want a single error type
+/// to propagate with `?`.
+///
+/// Every specific error type converts into it, so none of them has to be
+/// matched at the call site. A SQL [`ParseError`](crate::builders::sql::ParseError)
+/// borrows from its input and is kept as its message, unless it wraps a
+/// [`LexerError`](crate::builders::sql::LexerError), which is kept as is.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum AnyError {
+    /// An operation could not be built.
+    #[error(transparent)]
+    Builder(#[from] Error),
+
+    /// A table definition was invalid.
+    #[error(transparent)]
+    SimpleTable(#[from] crate::schema::SimpleTableError),
+
+    /// A table schema did not hold one entry per column.
+    #[error(transparent)]
+    TableSchema(#[from] crate::parser::TableSchemaError),
+
+    /// A changeset or patchset could not be parsed.
+    #[error(transparent)]
+    Diffset(#[from] crate::parser::ParseError),
+
+    /// SQL could not be parsed.
+    #[error("SQL parse error: {0}")]
+    Sql(String),
+
+    /// SQL could not be split into tokens.
+    #[error(transparent)]
+    Lexer(#[from] crate::builders::sql::LexerError),
+
+    /// Two schemas of the same table disagree.
+    #[error(transparent)]
+    SchemaMismatch(#[from] crate::parser::SchemaMismatch),
+
+    /// A wire value could not be decoded.
+    #[error(transparent)]
+    Decode(#[from] crate::wire::DecodeError),
+
+    /// A compressed frame could not be decoded.
+    #[error(transparent)]
+    Frame(#[from] crate::compression::FrameError),
+
+    /// A versioned envelope could not be decoded.
+    #[error(transparent)]
+    Versioned(#[from] crate::versioned::VersionedError),
+
+    /// A self-describing payload could not be decoded.
+    #[error(transparent)]
+    SelfDescribing(#[from] crate::self_describing::SelfDescribingError),
+
+    /// An inbound patchset could not be applied to a replica.
+    #[error(transparent)]
+    Replicator(#[from] crate::replicator::ReplicatorError),
+
+    /// A Debezium record could not be converted.
+    #[cfg(feature = "debezium")]
+    #[error(transparent)]
+    Debezium(#[from] crate::debezium::ConversionError),
+
+    /// A Maxwell record could not be converted.
+    #[cfg(feature = "maxwell")]
+    #[error(transparent)]
+    Maxwell(#[from] crate::maxwell::ConversionError),
+
+    /// A wal2json record could not be converted.
+    #[cfg(feature = "wal2json")]
+    #[error(transparent)]
+    Wal2Json(#[from] crate::wal2json::ConversionError),
+
+    /// A `pg_walstream` event could not be converted.
+    #[cfg(feature = "pg-walstream")]
+    #[error(transparent)]
+    PgWalstream(#[from] crate::pg_walstream::ConversionError),
+
+    /// An operation could not be converted into a `pg_walstream` message.
+    #[cfg(feature = "pg-walstream")]
+    #[error(transparent)]
+    PgWalstreamReverse(#[from] crate::pg_walstream_reverse::ConversionError),
+}
+
+impl From<crate::builders::sql::ParseError<'_>> for AnyError {
+    #[inline]
+    fn from(error: crate::builders::sql::ParseError<'_>) -> Self {
+        match error {
+            crate::builders::sql::ParseError::Lexer(error) => Self::Lexer(error),
+            error => Self::Sql(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyError, Error};
+    use crate::{DiffSetBuilder, ParsedDiffSet, PatchsetFormat, SimpleTable};
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    fn parse(bytes: &[u8]) -> Result<ParsedDiffSet, AnyError> {
+        Ok(ParsedDiffSet::try_from(bytes)?)
+    }
+
+    fn digest(sql: &str) -> Result<(), AnyError> {
+        let mut builder: DiffSetBuilder<PatchsetFormat, SimpleTable, String, Vec<u8>> =
+            DiffSetBuilder::new();
+        builder.add_table(&SimpleTable::try_new("t", &["id"], &[0])?);
+        builder.digest_sql(sql)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_error_converts_into_any_error() {
+        assert!(matches!(parse(b"X"), Err(AnyError::Diffset(_))));
+        assert_eq!(
+            digest("INSERT INTO u (id) VALUES (1)"),
+            Err(AnyError::Sql("Unknown table name: u".to_string()))
+        );
+        assert_eq!(
+            digest("INSERT INTO t (id) VALUES (X'0G')"),
+            Err(AnyError::Lexer(
+                crate::builders::sql::LexerError::InvalidHexDigit { char: 'G', pos: 30 }
+            ))
+        );
+
+        assert_eq!(
+            AnyError::from(Error::DuplicateColumnIndex(1)),
+            AnyError::Builder(Error::DuplicateColumnIndex(1))
+        );
+        let table = crate::schema::SimpleTableError::DuplicatePrimaryKeyIndex(0);
+        assert_eq!(AnyError::from(table.clone()), AnyError::SimpleTable(table));
+        let decode = crate::wire::DecodeError::NotYetImplemented { decoder: "d" };
+        assert_eq!(AnyError::from(decode.clone()), AnyError::Decode(decode));
+        let frame = crate::compression::FrameError::Empty;
+        assert_eq!(AnyError::from(frame.clone()), AnyError::Frame(frame));
+        let versioned = crate::versioned::VersionedError::BadMagic;
+        assert_eq!(
+            AnyError::from(versioned.clone()),
+            AnyError::Versioned(versioned)
+        );
+        let described = crate::self_describing::SelfDescribingError::BadMagic;
+        assert_eq!(
+            AnyError::from(described.clone()),
+            AnyError::SelfDescribing(described)
+        );
+        let mismatch = crate::parser::SchemaMismatch::Name {
+            left: "a".into(),
+            right: "b".into(),
+        };
+        assert_eq!(
+            AnyError::from(mismatch.clone()),
+            AnyError::SchemaMismatch(mismatch)
+        );
+        let replicator = crate::replicator::ReplicatorError::NotPatchset;
+        assert_eq!(
+            AnyError::from(replicator.clone()),
+            AnyError::Replicator(replicator)
+        );
+        // Transparent variants keep the inner message.
+        assert_eq!(
+            AnyError::from(Error::DuplicateColumnIndex(1)).to_string(),
+            Error::DuplicateColumnIndex(1).to_string()
+        );
+    }
+
+    #[cfg(feature = "debezium")]
+    #[test]
+    fn test_debezium_error_converts_into_any_error() {
+        let error = crate::debezium::ConversionError::TableNotFound("t".into());
+        assert_eq!(AnyError::from(error.clone()), AnyError::Debezium(error));
+    }
+
+    #[cfg(feature = "maxwell")]
+    #[test]
+    fn test_maxwell_error_converts_into_any_error() {
+        let error = crate::maxwell::ConversionError::TableNotFound("t".into());
+        assert_eq!(AnyError::from(error.clone()), AnyError::Maxwell(error));
+    }
+
+    #[cfg(feature = "wal2json")]
+    #[test]
+    fn test_wal2json_error_converts_into_any_error() {
+        let error = crate::wal2json::ConversionError::TableNotFound("t".into());
+        assert_eq!(AnyError::from(error.clone()), AnyError::Wal2Json(error));
+    }
+
+    #[cfg(feature = "pg-walstream")]
+    #[test]
+    fn test_pg_walstream_errors_convert_into_any_error() {
+        let error = crate::pg_walstream::ConversionError::TableNotFound("t".into());
+        assert_eq!(AnyError::from(error.clone()), AnyError::PgWalstream(error));
+        let error = crate::pg_walstream_reverse::ConversionError::ArityMismatch {
+            schema_arity: 1,
+            op_arity: 2,
+        };
+        assert_eq!(
+            AnyError::from(error.clone()),
+            AnyError::PgWalstreamReverse(error)
+        );
+    }
 }
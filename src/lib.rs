@@ -6,6 +6,9 @@
 extern crate alloc;
 
 pub mod builders;
+pub mod compression;
+#[cfg(feature = "debezium")]
+pub mod debezium;
 #[cfg(any(test, feature = "testing"))]
 pub mod differential_testing;
 pub(crate) mod encoding;
@@ -17,9 +20,12 @@ pub mod parser;
 pub mod pg_walstream;
 #[cfg(feature = "pg-walstream")]
 pub mod pg_walstream_reverse;
+pub mod replicator;
 pub mod schema;
+pub mod self_describing;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
+pub mod versioned;
 #[cfg(feature = "wal2json")]
 pub mod wal2json;
 pub mod wire;
@@ -27,26 +33,36 @@ pub mod wire;
 // Re-export main types
 #[cfg(feature = "diesel-async")]
 pub use builders::ApplyOpsAsync;
+pub use builders::sql::RowSource;
 #[cfg(feature = "diesel")]
 pub use builders::{
     Adapter, ApplyOps, Binder, BoundChangesetOp, BoundOp, BoundPatchsetOp, DefaultBinder,
 };
 pub use builders::{
     ChangeDelete, ChangeSet, ChangesetFormat, ChangesetOp, ChangesetUpdatePair, ColumnNames,
-    DiffOps, DiffSet, DiffSetBuilder, Indirect, Insert, PatchDelete, PatchSet, PatchsetFormat,
-    PatchsetOp, PatchsetUpdateEntry, Reverse, Update,
+    DiffOps, DiffSet, DiffSetBuilder, GroupKey, GroupedDiffSet, Indirect, Insert, OperationVisitor,
+    PatchDelete, PatchSet, PatchsetFormat, PatchsetOp, PatchsetUpdateEntry, Reverse,
+    ShadowPatchSet, SingleTableChangeSet, TableScope, Update, UpdateColumns,
+};
+pub use encoding::{
+    Collation, TruncatedValue, Value, compare_values, decode_value, encode_value, format_truncated,
+    values_differ_collated, values_differ_for_patch,
+};
+pub use parser::{
+    FormatMarker, ParseError, ParsedDiffSet, ParsedOp, RawOperation, SchemaMismatch, TableSchema,
+    TableSchemaError, TableSection,
+};
+pub use schema::{
+    DynTable, IndexableValues, NamedColumns, SchemaWithPK, SimpleTable, SimpleTableError,
 };
-pub use encoding::Value;
-pub use parser::{FormatMarker, ParseError, ParsedDiffSet, TableSchema};
-pub use schema::{DynTable, IndexableValues, NamedColumns, SchemaWithPK, SimpleTable};
 pub use wire::{
-    BoolDecoder, DateVerbatimDecoder, DecimalTextDecoder, DecodeError, Decoder, Digestable,
-    Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder,
-    JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder,
-    PgByteaTextModeDecoder, RealDecoder, TextDecoder, TimeVerbatimDecoder,
-    TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, TypeMap, TypeMapDefaults,
-    UuidBlob16Decoder, UuidText36Decoder, WireAdapter, WireColumnTypes, WireSchema, WireSource,
-    WireType,
+    BoolDecoder, BoundedBlobDecoder, DateVerbatimDecoder, DecimalTextDecoder, DecodeError, Decoder,
+    Digestable, Enrichment, Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder,
+    JsonCanonicalDecoder, JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder,
+    PgByteaBinaryDecoder, PgByteaTextModeDecoder, RealDecoder, TextDecoder, TextOrJsonDecoder,
+    TimeVerbatimDecoder, TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, TypeMap,
+    TypeMapDefaults, UuidBlob16Decoder, UuidText36Decoder, WireAdapter, WireColumnTypes,
+    WireOperation, WireSchema, WireSource, WireType,
 };
 
 // Type aliases for common use cases
@@ -61,4 +77,4 @@ pub type ChangeUpdate<T, S, B> = Update<T, ChangesetFormat, S, B>;
 pub type PatchUpdate<T, S, B> = Update<T, PatchsetFormat, S, B>;
 
 // Re-export errors
-pub use errors::Error;
+pub use errors::{AnyError, Error};
@@ -113,6 +113,11 @@ pub enum ConversionError {
     #[error("Column '{0}' not found in table schema")]
     ColumnNotFound(String),
 
+    /// The event sets a column twice, under one name or two that resolve to
+    /// the same column.
+    #[error("Column '{0}' set more than once")]
+    DuplicateColumn(String),
+
     /// The table name in the event doesn't match the expected schema.
     #[error("Table name mismatch: expected '{expected}', got '{actual}'")]
     TableMismatch {
@@ -141,6 +146,19 @@ pub enum ConversionError {
     /// User-registered decoder rejected a column payload.
     #[error("Decoder failed: {0}")]
     Decode(#[from] crate::wire::DecodeError),
+
+    /// Event metadata could not be recorded in the requested column.
+    #[error("Enrichment failed: {0}")]
+    Enrichment(#[from] crate::errors::Error),
+}
+
+impl From<PlacementError<'_>> for ConversionError {
+    fn from(error: PlacementError<'_>) -> Self {
+        match error {
+            PlacementError::NotFound(name) => Self::ColumnNotFound(name.into()),
+            PlacementError::Duplicate(name) => Self::DuplicateColumn(name.into()),
+        }
+    }
 }
 
 use crate::ChangesetFormat;
@@ -148,9 +166,9 @@ use crate::builders::{ChangeDelete, Insert, PatchDelete, Update};
 use crate::encoding::Value;
 use crate::schema::NamedColumns;
 
-use crate::wire::{Sealed, WireSource, WireType};
+use crate::wire::{ColumnPlacer, PlacementError, Sealed, WireSource, WireType};
 
-use crate::builders::{DiffOps, DiffSetBuilder, PatchsetFormat};
+use crate::builders::{Format, PatchsetFormat};
 use crate::wire::WireAdapter;
 use core::fmt::Debug;
 use core::hash::Hash;
@@ -206,7 +224,9 @@ impl MaxwellColumn<'_> {
     }
 }
 
-use crate::wire::{Digestable, WireColumnTypes, WireSchema};
+use crate::wire::{
+    Digestable, Enrichment, MaybeOperation, WireColumnTypes, WireOperation, WireSchema,
+};
 
 impl<T, S, B> Digestable<ChangesetFormat, T, S, B> for Message
 where
@@ -217,12 +237,11 @@ where
     type Src = Maxwell;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<ChangesetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<ChangesetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<ChangesetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Maxwell, S, B>,
@@ -231,7 +250,7 @@ where
         match self.op_type {
             OpType::Insert => {
                 let insert = build_insert_from_maxwell(&self.data, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             OpType::Update => {
                 let update = build_changeset_update_from_maxwell(
@@ -240,11 +259,11 @@ where
                     table,
                     adapter,
                 )?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             OpType::Delete => {
                 let delete = build_changeset_delete_from_maxwell(&self.data, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
         }
     }
@@ -259,12 +278,11 @@ where
     type Src = Maxwell;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<PatchsetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<PatchsetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<PatchsetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Maxwell, S, B>,
@@ -273,20 +291,77 @@ where
         match self.op_type {
             OpType::Insert => {
                 let insert = build_insert_from_maxwell(&self.data, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             OpType::Update => {
                 let update = build_patchset_update_from_maxwell(&self.data, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             OpType::Delete => {
                 let delete = build_patch_delete_from_maxwell(&self.data, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
         }
     }
 }
 
+impl Message {
+    /// Metadata of this event to record for `enrichment`, or NULL if the
+    /// event does not carry it.
+    ///
+    /// The event timestamp comes from `ts`, in seconds, scaled to
+    /// milliseconds; the transaction id from `xid`; and the
+    /// [`Lsn`](Enrichment::Lsn) is the binlog `position`, as TEXT.
+    #[must_use]
+    pub fn enrichment<S: From<String>, B>(&self, enrichment: Enrichment) -> Value<S, B> {
+        let value = match enrichment {
+            Enrichment::EventTimestamp => self
+                .ts
+                .and_then(|ts| ts.checked_mul(1000))
+                .map(Value::Integer),
+            Enrichment::TransactionId => self.xid.map(Value::Integer),
+            Enrichment::Lsn => self
+                .position
+                .as_ref()
+                .map(|position| Value::Text(S::from(position.clone()))),
+        };
+        value.unwrap_or(Value::Null)
+    }
+
+    /// Convert this event as [`Digestable::to_operation`] does, then record
+    /// the requested event metadata in the row: each `(column, enrichment)`
+    /// pair writes [`enrichment`](Self::enrichment) into the column at that
+    /// index, as [`WireOperation::enrich`] does.
+    ///
+    /// # Errors
+    ///
+    /// Any conversion error, or [`ConversionError::Enrichment`] if a column
+    /// index is out of bounds.
+    pub fn to_enriched_operation<F, T, S, B, Sch, A>(
+        &self,
+        schema: &Sch,
+        adapter: &A,
+        enrichments: &[(usize, Enrichment)],
+    ) -> Result<MaybeOperation<F, T, S, B>, ConversionError>
+    where
+        F: Format<S, B>,
+        T: NamedColumns + WireColumnTypes,
+        S: AsRef<str> + From<String>,
+        B: AsRef<[u8]>,
+        Sch: WireSchema<Table = T>,
+        A: WireAdapter<Maxwell, S, B>,
+        Self: Digestable<F, T, S, B, Src = Maxwell, Error = ConversionError>,
+    {
+        let Some(operation) = self.to_operation(schema, adapter)? else {
+            return Ok(None);
+        };
+        let values = enrichments
+            .iter()
+            .map(|&(column, enrichment)| (column, self.enrichment(enrichment)));
+        Ok(Some(operation.enrich(values)?))
+    }
+}
+
 fn resolve_table<'a, Sch>(schema: &'a Sch, name: &str) -> Result<&'a Sch::Table, ConversionError>
 where
     Sch: WireSchema,
@@ -308,10 +383,9 @@ where
     A: WireAdapter<Maxwell, S, B>,
 {
     let mut insert = Insert::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in data {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.clone()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = MaxwellColumn {
             column_name: name.as_str(),
@@ -339,10 +413,9 @@ where
     A: WireAdapter<Maxwell, S, B>,
 {
     let mut update: Update<T, ChangesetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, new_value) in data {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.clone()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
 
         let new_payload = MaxwellColumn {
@@ -385,10 +458,9 @@ where
     A: WireAdapter<Maxwell, S, B>,
 {
     let mut update: Update<T, PatchsetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in data {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.clone()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = MaxwellColumn {
             column_name: name.as_str(),
@@ -415,10 +487,9 @@ where
     A: WireAdapter<Maxwell, S, B>,
 {
     let mut delete = ChangeDelete::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in data {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.clone()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = MaxwellColumn {
             column_name: name.as_str(),
@@ -447,10 +518,9 @@ where
     let num_pks = table.number_of_primary_keys();
     let mut pk_slots: Vec<Option<Value<S, B>>> = alloc::vec![None; num_pks];
 
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in data {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.clone()))?;
+        let col_idx = placer.place(table, name)?;
         if let Some(pk_idx) = table.primary_key_index(col_idx) {
             let wire_type = table.column_type(col_idx);
             let payload = MaxwellColumn {
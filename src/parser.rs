@@ -25,20 +25,37 @@ use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::hash::Hash;
+use core::ops::Range;
 
-use crate::IndexableValues;
+use crate::{IndexableValues, OperationVisitor};
 
 /// Type alias for update operation values.
 type UpdateValues = Vec<(MaybeValue<String, Vec<u8>>, MaybeValue<String, Vec<u8>>)>;
 
 /// Type alias for parsed values result.
 type ParsedValues = (Vec<MaybeValue<String, Vec<u8>>>, usize);
-use crate::builders::{ChangesetFormat, DiffSet, DiffSetBuilder, Operation, PatchsetFormat};
-use crate::encoding::{MaybeValue, Value, decode_value, markers, op_codes};
-use crate::schema::{DynTable, SchemaWithPK};
+
+/// A parsed diffset paired with the number of input bytes it spans.
+type ParsedPrefix<F> = (DiffSet<F, TableSchema<String>, String, Vec<u8>>, usize);
+
+/// Type alias for the primary keys touched in each parsed table.
+type ParsedAffectedKeys<'a> = Vec<(&'a TableSchema<String>, Vec<Vec<Value<String, Vec<u8>>>>)>;
+
+mod canonical_json;
+
+use crate::builders::{
+    ChangesetFormat, ChangesetOp, DiffSet, DiffSetBuilder, Format, Operation, PatchsetFormat,
+    PatchsetOp,
+};
+use crate::encoding::{
+    MaybeValue, Value, decode_maybe_value, markers, op_codes, values_differ_for_patch,
+};
+use crate::replicator::{Conflict, ConflictKind, RowStore};
+use crate::schema::{DynTable, NamedColumns, SchemaWithPK};
 
 /// Errors that can occur during parsing.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
 pub enum ParseError {
     /// Unexpected end of input.
     #[error("Unexpected end of input at position {0}")]
@@ -64,6 +81,28 @@ pub enum ParseError {
     #[error("Table name not null-terminated")]
     UnterminatedTableName,
 
+    /// An operation whose record does not fit its table: it holds fewer or
+    /// more values than the column count and primary-key flags declare, or
+    /// leaves a primary-key value undefined.
+    #[error("Malformed operation 0x{op_code:02x} at position {position}")]
+    MalformedOperation {
+        /// The operation code.
+        op_code: u8,
+        /// The position of the operation header.
+        position: usize,
+    },
+
+    /// The first byte is not a table marker, so the input is not a
+    /// changeset or patchset at all.
+    #[error(
+        "Not a changeset or patchset: first byte 0x{first_byte:02x} is not a table marker \
+         (input may be compressed or in a different format)"
+    )]
+    NotAChangeset {
+        /// The first byte of the input.
+        first_byte: u8,
+    },
+
     /// Mixed format markers in the same file.
     #[error("Mixed format markers: expected {expected:?}, found {found:?} at position {position}")]
     MixedFormats {
@@ -74,6 +113,10 @@ pub enum ParseError {
         /// The position where the mismatch occurred.
         position: usize,
     },
+
+    /// The versioned envelope around the diffset could not be opened.
+    #[error(transparent)]
+    Versioned(#[from] crate::versioned::VersionedError),
 }
 
 /// The detected format marker.
@@ -85,11 +128,75 @@ pub enum FormatMarker {
     Patchset,
 }
 
+/// Errors reported when two schemas of the same table disagree.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaMismatch {
+    /// The table names differ.
+    #[error("Table name mismatch: {left:?} vs {right:?}")]
+    Name {
+        /// Name on the receiving side.
+        left: String,
+        /// Name on the incoming side.
+        right: String,
+    },
+
+    /// The column counts differ.
+    #[error("Column count mismatch for table {table:?}: {left} vs {right}")]
+    ColumnCount {
+        /// The table name.
+        table: String,
+        /// Column count on the receiving side.
+        left: usize,
+        /// Column count on the incoming side.
+        right: usize,
+    },
+
+    /// The primary-key flags differ.
+    #[error("Primary key flags mismatch for table {table:?}: {left:?} vs {right:?}")]
+    PkFlags {
+        /// The table name.
+        table: String,
+        /// Primary-key flags on the receiving side.
+        left: Vec<u8>,
+        /// Primary-key flags on the incoming side.
+        right: Vec<u8>,
+    },
+}
+
+/// Errors reported by [`TableSchema::try_new`], or when a deserialized
+/// [`TableSchema`] does not hold one entry per column.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TableSchemaError {
+    /// The primary-key flags do not hold one byte per column.
+    #[error("{0} primary-key flags given for table with {1} columns")]
+    PkFlagCount(usize, usize),
+
+    /// The column names do not hold one name per column.
+    #[error("{0} column names given for table with {1} columns")]
+    ColumnNameCount(usize, usize),
+}
+
 /// A table schema parsed from binary changeset/patchset data.
 ///
 /// This type implements [`DynTable`] and [`SchemaWithPK`], allowing it
 /// to be used with [`DiffSetBuilder`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// The binary format carries no column names. Attach them from known DDL with
+/// [`with_column_names`](Self::with_column_names) to resolve columns by name
+/// through [`NamedColumns`] and render named SQL through
+/// [`ColumnNames`](crate::ColumnNames). Names do not take part in equality
+/// or hashing, which compare the table name, column count and primary-key
+/// flags the binary format carries, so a named schema and its unnamed
+/// original are the same table to a builder.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        try_from = "TableSchemaFields<S>",
+        bound(deserialize = "S: serde::Deserialize<'de>")
+    )
+)]
 pub struct TableSchema<S> {
     /// The table name.
     name: S,
@@ -102,10 +209,69 @@ pub struct TableSchema<S> {
     /// For example, `[1, 0, 2]` means column 0 is the first PK column,
     /// column 1 is not a PK column, and column 2 is the second PK column.
     pk_flags: Vec<u8>,
+    /// Column names in column order, or empty when unknown.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    column_names: Vec<String>,
+}
+
+/// The serialized fields of a [`TableSchema`], checked for one entry per
+/// column on the way back in.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TableSchemaFields<S> {
+    name: S,
+    column_count: usize,
+    pk_flags: Vec<u8>,
+    #[serde(default)]
+    column_names: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl<S> TryFrom<TableSchemaFields<S>> for TableSchema<S> {
+    type Error = TableSchemaError;
+
+    fn try_from(fields: TableSchemaFields<S>) -> Result<Self, Self::Error> {
+        let mut schema = Self::try_new(fields.name, fields.column_count, fields.pk_flags)?;
+        if !fields.column_names.is_empty() {
+            if fields.column_names.len() != fields.column_count {
+                return Err(TableSchemaError::ColumnNameCount(
+                    fields.column_names.len(),
+                    fields.column_count,
+                ));
+            }
+            schema.column_names = fields.column_names;
+        }
+        Ok(schema)
+    }
+}
+
+impl<S: PartialEq> PartialEq for TableSchema<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.column_count == other.column_count
+            && self.pk_flags == other.pk_flags
+    }
+}
+
+impl<S: Eq> Eq for TableSchema<S> {}
+
+impl<S: Hash> Hash for TableSchema<S> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.column_count.hash(state);
+        self.pk_flags.hash(state);
+    }
 }
 
 impl<S> TableSchema<S> {
     /// Create a new parsed table schema.
+    ///
+    /// A fast path for callers whose flags are known to fit the columns. A
+    /// length mismatch trips a debug assertion; use
+    /// [`try_new`](Self::try_new) to validate untrusted flags.
     #[inline]
     #[must_use]
     pub fn new(name: S, column_count: usize, pk_flags: Vec<u8>) -> Self {
@@ -114,9 +280,62 @@ impl<S> TableSchema<S> {
             name,
             column_count,
             pk_flags,
+            column_names: Vec::new(),
         }
     }
 
+    /// Create a new parsed table schema, checking that there is one
+    /// primary-key flag per column.
+    ///
+    /// # Errors
+    ///
+    /// * `PkFlagCount` - If `pk_flags.len()` differs from `column_count`.
+    pub fn try_new(
+        name: S,
+        column_count: usize,
+        pk_flags: Vec<u8>,
+    ) -> Result<Self, TableSchemaError> {
+        if pk_flags.len() != column_count {
+            return Err(TableSchemaError::PkFlagCount(pk_flags.len(), column_count));
+        }
+        Ok(Self::new(name, column_count, pk_flags))
+    }
+
+    /// Attaches the column names, in column order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not exactly one name per column.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{NamedColumns, TableSchema};
+    ///
+    /// let schema: TableSchema<String> = TableSchema::new("users".into(), 2, vec![1, 0])
+    ///     .with_column_names(vec!["id".into(), "name".into()]);
+    /// assert_eq!(schema.column_name(1), Some("name"));
+    /// assert_eq!(schema.column_index("id"), Some(0));
+    /// ```
+    #[must_use]
+    pub fn with_column_names(mut self, column_names: Vec<String>) -> Self {
+        assert_eq!(
+            column_names.len(),
+            self.column_count,
+            "expected one name per column"
+        );
+        self.column_names = column_names;
+        self
+    }
+
+    /// Returns the name of column `index`, or `None` if it is out of bounds
+    /// or no names were attached.
+    #[inline]
+    #[must_use]
+    pub fn column_name(&self, index: usize) -> Option<&str> {
+        self.column_names.get(index).map(String::as_str)
+    }
+
     /// Returns the name of the table.
     #[inline]
     #[must_use]
@@ -134,6 +353,72 @@ impl<S> TableSchema<S> {
         &self.pk_flags
     }
 
+    /// Checks that `other` describes the same table: same name, same column
+    /// count and same primary-key flags.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`SchemaMismatch`] found, in that order.
+    pub fn compatible_with(&self, other: &TableSchema<String>) -> Result<(), SchemaMismatch>
+    where
+        S: AsRef<str>,
+    {
+        let table = self.name.as_ref();
+        if table != other.name {
+            return Err(SchemaMismatch::Name {
+                left: table.into(),
+                right: other.name.clone(),
+            });
+        }
+        if self.column_count != other.column_count {
+            return Err(SchemaMismatch::ColumnCount {
+                table: table.into(),
+                left: self.column_count,
+                right: other.column_count,
+            });
+        }
+        if self.pk_flags != other.pk_flags {
+            return Err(SchemaMismatch::PkFlags {
+                table: table.into(),
+                left: self.pk_flags.clone(),
+                right: other.pk_flags.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Builds the [`SimpleTable`](crate::SimpleTable) with this name and
+    /// primary key, naming the columns `names` in column order.
+    ///
+    /// `TableSchema::from(&simple)` converts back, dropping the names.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not exactly one name per column, or if two names
+    /// are equal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{SimpleTable, TableSchema};
+    ///
+    /// let schema: TableSchema<String> = TableSchema::new("users".into(), 2, vec![1, 0]);
+    /// let users = schema.to_simple_with_names(&["id", "name"]);
+    /// assert_eq!(users, SimpleTable::new("users", &["id", "name"], &[0]));
+    /// ```
+    #[must_use]
+    pub fn to_simple_with_names(&self, names: &[&str]) -> crate::SimpleTable
+    where
+        S: AsRef<str>,
+    {
+        assert_eq!(
+            names.len(),
+            self.column_count,
+            "expected one name per column"
+        );
+        crate::SimpleTable::new(self.name.as_ref(), names, &self.pk_indices())
+    }
+
     /// Get the indices of primary key columns, in PK order.
     #[must_use]
     pub(crate) fn pk_indices(&self) -> Vec<usize> {
@@ -181,14 +466,30 @@ impl<N: AsRef<str> + Clone + core::hash::Hash + Eq + core::fmt::Debug> SchemaWit
         self.pk_flags.iter().filter(|&&b| b > 0).count()
     }
 
+    /// Returns the position of column `col_idx` in the key vector built by
+    /// [`extract_pk`](Self::extract_pk).
+    ///
+    /// That is the column's rank among the primary-key columns by ordinal,
+    /// not the ordinal minus one, so flags whose ordinals have gaps, such as
+    /// `[3, 0, 1]`, still map into a key of [`number_of_primary_keys`]
+    /// entries.
+    ///
+    /// [`number_of_primary_keys`]: Self::number_of_primary_keys
     fn primary_key_index(&self, col_idx: usize) -> Option<usize> {
-        self.pk_flags.get(col_idx).and_then(|&pk_ordinal| {
-            if pk_ordinal > 0 {
-                Some(usize::from(pk_ordinal - 1))
-            } else {
-                None
-            }
-        })
+        let pk_ordinal = *self.pk_flags.get(col_idx)?;
+        if pk_ordinal == 0 {
+            return None;
+        }
+        // `pk_indices` sorts stably, so equal ordinals keep column order.
+        Some(
+            self.pk_flags
+                .iter()
+                .enumerate()
+                .filter(|&(i, &ord)| {
+                    ord > 0 && (ord < pk_ordinal || (ord == pk_ordinal && i < col_idx))
+                })
+                .count(),
+        )
     }
 
     fn extract_pk<S, B>(
@@ -210,10 +511,41 @@ impl<N: AsRef<str> + Clone + core::hash::Hash + Eq + core::fmt::Debug> SchemaWit
     }
 }
 
+impl<S: AsRef<str> + Clone + core::hash::Hash + Eq + core::fmt::Debug> NamedColumns
+    for TableSchema<S>
+{
+    #[inline]
+    fn column_index(&self, column_name: &str) -> Option<usize> {
+        self.column_names
+            .iter()
+            .position(|name| name == column_name)
+    }
+}
+
+impl<S: AsRef<str> + Clone + core::hash::Hash + Eq + core::fmt::Debug> crate::ColumnNames
+    for TableSchema<S>
+{
+    #[inline]
+    fn column_name(&self, index: usize) -> Option<&str> {
+        TableSchema::column_name(self, index)
+    }
+
+    fn pk_indices(&self) -> Vec<usize> {
+        TableSchema::pk_indices(self)
+    }
+}
+
 /// A parsed changeset or patchset.
 ///
 /// This represents a frozen (immutable) diffset produced by the binary parser.
 /// To modify it, convert it to a [`DiffSetBuilder`] using `Into::into`.
+///
+/// Tables and their operations are stored in the order their bytes appear in
+/// the input, which is the session's hash order rather than the order the
+/// rows were recorded in, and every iteration over the diffset walks them in
+/// that order. Records repeating a row, which no single session emits, are
+/// consolidated into the operation at the row's first record, and sections
+/// repeating a table into the table's first section.
 #[derive(Debug, Clone, Eq)]
 pub enum ParsedDiffSet {
     /// A parsed changeset.
@@ -222,6 +554,16 @@ pub enum ParsedDiffSet {
     Patchset(DiffSet<PatchsetFormat, TableSchema<String>, String, Vec<u8>>),
 }
 
+/// View over one operation of a [`ParsedDiffSet`], in the format it was
+/// parsed from.
+#[derive(Debug)]
+pub enum ParsedOp<'a> {
+    /// An operation of a parsed changeset.
+    Changeset(ChangesetOp<'a, TableSchema<String>, String, Vec<u8>>),
+    /// An operation of a parsed patchset.
+    Patchset(PatchsetOp<'a, TableSchema<String>, String, Vec<u8>>),
+}
+
 impl PartialEq for ParsedDiffSet {
     fn eq(&self, other: &Self) -> bool {
         let self_empty = match self {
@@ -246,6 +588,31 @@ impl PartialEq for ParsedDiffSet {
     }
 }
 
+/// Compares structurally with the builder a diffset may have been parsed
+/// from, by the rules of the `DiffSet` comparison; as between two parsed
+/// diffsets, an empty diffset equals an empty builder of either format.
+impl<T: SchemaWithPK> PartialEq<DiffSetBuilder<ChangesetFormat, T, String, Vec<u8>>>
+    for ParsedDiffSet
+{
+    fn eq(&self, other: &DiffSetBuilder<ChangesetFormat, T, String, Vec<u8>>) -> bool {
+        match self {
+            ParsedDiffSet::Changeset(d) => d == other,
+            ParsedDiffSet::Patchset(d) => d.is_empty() && other.is_empty(),
+        }
+    }
+}
+
+impl<T: SchemaWithPK> PartialEq<DiffSetBuilder<PatchsetFormat, T, String, Vec<u8>>>
+    for ParsedDiffSet
+{
+    fn eq(&self, other: &DiffSetBuilder<PatchsetFormat, T, String, Vec<u8>>) -> bool {
+        match self {
+            ParsedDiffSet::Changeset(d) => d.is_empty() && other.is_empty(),
+            ParsedDiffSet::Patchset(d) => d == other,
+        }
+    }
+}
+
 impl TryFrom<&[u8]> for ParsedDiffSet {
     type Error = ParseError;
 
@@ -272,22 +639,61 @@ impl ParsedDiffSet {
     ///
     /// Returns a `ParseError` if the data is malformed or contains invalid values.
     pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+        Self::parse_with(data, false).map(|(diffset, _)| diffset)
+    }
+
+    /// Parse the longest valid prefix of `data`, returning the diffset and
+    /// the number of bytes it spans.
+    ///
+    /// Parsing stops at the first table header or operation that is
+    /// malformed, truncated, or of the other format, so trailing bytes after
+    /// a valid diffset (for instance from a bad concatenation) are left
+    /// unconsumed instead of failing the whole parse. Callers detect a
+    /// trailer by comparing the consumed length with `data.len()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+    ///     ChangeSet::new().insert(Insert::from(users).set(0, 1i64).unwrap());
+    /// let mut bytes = changeset.build();
+    /// let valid_len = bytes.len();
+    /// bytes.extend_from_slice(b"\xff\xff");
+    ///
+    /// let (parsed, consumed) = ParsedDiffSet::parse_prefix(&bytes).unwrap();
+    /// assert_eq!(consumed, valid_len);
+    /// assert_eq!(parsed.operation_count(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if not even the first table header is valid.
+    pub fn parse_prefix(data: &[u8]) -> Result<(Self, usize), ParseError> {
+        Self::parse_with(data, true)
+    }
+
+    /// Shared body of [`parse`](Self::parse) and
+    /// [`parse_prefix`](Self::parse_prefix).
+    fn parse_with(data: &[u8], prefix: bool) -> Result<(Self, usize), ParseError> {
         if data.is_empty() {
             // Empty data defaults to changeset
-            return Ok(ParsedDiffSet::Changeset(DiffSet::default()));
+            return Ok((ParsedDiffSet::Changeset(DiffSet::default()), 0));
         }
 
         // Peek at the first byte to determine format
         match data[0] {
             markers::CHANGESET => {
-                let diffset = parse_as_changeset(data)?;
-                Ok(ParsedDiffSet::Changeset(diffset))
+                let (diffset, consumed) = parse_as_changeset(data, prefix)?;
+                Ok((ParsedDiffSet::Changeset(diffset), consumed))
             }
             markers::PATCHSET => {
-                let diffset = parse_as_patchset(data)?;
-                Ok(ParsedDiffSet::Patchset(diffset))
+                let (diffset, consumed) = parse_as_patchset(data, prefix)?;
+                Ok((ParsedDiffSet::Patchset(diffset), consumed))
             }
-            b => Err(ParseError::InvalidTableMarker(b, 0)),
+            first_byte => Err(ParseError::NotAChangeset { first_byte }),
         }
     }
 
@@ -321,6 +727,653 @@ impl ParsedDiffSet {
                 .collect(),
         }
     }
+
+    /// Returns the total number of operations across all tables.
+    #[must_use]
+    pub fn operation_count(&self) -> usize {
+        match self {
+            ParsedDiffSet::Changeset(d) => d.len(),
+            ParsedDiffSet::Patchset(d) => d.len(),
+        }
+    }
+
+    /// Returns the number of tables with at least one operation.
+    #[must_use]
+    pub fn table_count(&self) -> usize {
+        match self {
+            ParsedDiffSet::Changeset(d) => count_tables(d),
+            ParsedDiffSet::Patchset(d) => count_tables(d),
+        }
+    }
+
+    /// Returns the operation on the row keyed on `pk` in the table named
+    /// `table`, or `None` if no operation touches that row.
+    ///
+    /// `pk` holds the primary-key values in key order, the order given by
+    /// the table's primary-key flags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, ParsedOp, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap().set(1, "alice").unwrap());
+    /// let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+    ///
+    /// assert!(matches!(
+    ///     parsed.find("users", &[1i64.into()]),
+    ///     Some(ParsedOp::Changeset(_))
+    /// ));
+    /// assert!(parsed.find("users", &[2i64.into()]).is_none());
+    /// ```
+    #[must_use]
+    pub fn find(&self, table: &str, pk: &[Value<String, Vec<u8>>]) -> Option<ParsedOp<'_>> {
+        match self {
+            ParsedDiffSet::Changeset(d) => d.get(table, pk).map(ParsedOp::Changeset),
+            ParsedDiffSet::Patchset(d) => d.get(table, pk).map(ParsedOp::Patchset),
+        }
+    }
+
+    /// Returns the operations on the table named `table` whose value in
+    /// primary-key column `pk_col` lies in `[lo, hi)`, as a diffset of the
+    /// same kind.
+    ///
+    /// See [`DiffSet::slice_by_pk_range`] for how values are ordered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// for id in 0..10i64 {
+    ///     changeset = changeset.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    /// }
+    /// let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+    ///
+    /// let shard = parsed.slice_by_pk_range("users", 0, &Value::Integer(3), &Value::Integer(6));
+    /// assert_eq!(shard.operation_count(), 3);
+    /// assert!(shard.find("users", &[Value::Integer(6)]).is_none());
+    /// ```
+    #[must_use]
+    pub fn slice_by_pk_range(
+        &self,
+        table: &str,
+        pk_col: usize,
+        lo: &Value<String, Vec<u8>>,
+        hi: &Value<String, Vec<u8>>,
+    ) -> ParsedDiffSet {
+        match self {
+            ParsedDiffSet::Changeset(d) => {
+                ParsedDiffSet::Changeset(d.slice_by_pk_range(table, pk_col, lo, hi))
+            }
+            ParsedDiffSet::Patchset(d) => {
+                ParsedDiffSet::Patchset(d.slice_by_pk_range(table, pk_col, lo, hi))
+            }
+        }
+    }
+
+    /// Returns the operations on the table named `table`, in the order their
+    /// records appear in the parsed bytes.
+    ///
+    /// Staging them on a fresh builder records them in this order, which its
+    /// `build` then hashes anew; [`DiffSet::into_builder`] rebuilds the exact
+    /// bytes instead. See the [type documentation](Self) for rows recorded
+    /// more than once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    /// for id in 0..3i64 {
+    ///     changeset = changeset.insert(Insert::from(users.clone()).set(0, id).unwrap());
+    /// }
+    /// let bytes = changeset.build();
+    /// let parsed = ParsedDiffSet::parse(&bytes).unwrap();
+    ///
+    /// let raw = ParsedDiffSet::raw_operations(&bytes).unwrap();
+    /// let ops = parsed.operations_in_byte_order("users");
+    /// assert_eq!(ops.len(), raw.len());
+    /// assert!(parsed.operations_in_byte_order("posts").is_empty());
+    /// ```
+    #[must_use]
+    pub fn operations_in_byte_order(&self, table: &str) -> Vec<ParsedOp<'_>> {
+        match self {
+            ParsedDiffSet::Changeset(d) => d
+                .iter()
+                .filter(|op| op.table().name() == table)
+                .map(ParsedOp::Changeset)
+                .collect(),
+            ParsedDiffSet::Patchset(d) => d
+                .iter()
+                .filter(|op| op.table().name() == table)
+                .map(ParsedOp::Patchset)
+                .collect(),
+        }
+    }
+
+    /// Returns, per table in stored order, the primary keys of every row the
+    /// diffset touches, e.g. to invalidate cached rows after applying it.
+    ///
+    /// See [`DiffSet::affected_keys`] for how key-moving updates are listed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable, Value};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap().set(1, "alice").unwrap());
+    /// let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+    ///
+    /// let affected = parsed.affected_keys();
+    /// assert_eq!(affected.len(), 1);
+    /// assert_eq!(affected[0].1, vec![vec![Value::Integer(1)]]);
+    /// ```
+    #[must_use]
+    pub fn affected_keys(&self) -> ParsedAffectedKeys<'_> {
+        match self {
+            ParsedDiffSet::Changeset(d) => d.affected_keys(),
+            ParsedDiffSet::Patchset(d) => d.affected_keys(),
+        }
+    }
+
+    /// Returns the number of `(inserts, updates, deletes)` across all tables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users.clone()).set(0, 1i64).unwrap())
+    ///     .insert(Insert::from(users).set(0, 2i64).unwrap());
+    ///
+    /// let parsed = ParsedDiffSet::try_from(changeset.build().as_slice()).unwrap();
+    /// assert_eq!(parsed.counts_by_kind(), (2, 0, 0));
+    /// assert_eq!(parsed.operation_count(), 2);
+    /// assert_eq!(parsed.table_count(), 1);
+    /// ```
+    #[must_use]
+    pub fn counts_by_kind(&self) -> (usize, usize, usize) {
+        match self {
+            ParsedDiffSet::Changeset(d) => count_kinds(d),
+            ParsedDiffSet::Patchset(d) => count_kinds(d),
+        }
+    }
+
+    /// Returns the operations that would conflict if applied to `store`, in
+    /// the order the diffset stores them, without mutating it.
+    ///
+    /// An INSERT conflicts when its primary key is present, and an UPDATE or
+    /// DELETE when it is absent. For a changeset, an UPDATE or DELETE also
+    /// conflicts, as [`ConflictKind::Data`], when a defined old value
+    /// differs from the present row under the session extension's
+    /// comparison (see [`values_differ_for_patch`]). Every operation is
+    /// checked against `store` as it is: earlier operations of the diffset
+    /// are not applied first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::replicator::{ConflictKind, Replicator};
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let alice = Insert::from(users).set(0, 1i64).unwrap();
+    /// let mut store: Replicator<SimpleTable> = Replicator::new();
+    /// store.insert(alice.clone()).unwrap();
+    ///
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(alice);
+    /// let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+    /// let conflicts = parsed.check_conflicts(&store);
+    /// assert_eq!(conflicts.len(), 1);
+    /// assert_eq!(conflicts[0].kind, ConflictKind::Exists);
+    /// ```
+    ///
+    /// [`values_differ_for_patch`]: crate::values_differ_for_patch
+    #[must_use]
+    pub fn check_conflicts(&self, store: &impl RowStore) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        match self {
+            Self::Changeset(changeset) => {
+                for (table, rows) in &changeset.tables {
+                    for (pk, op) in rows {
+                        let (exists, old) = match op {
+                            Operation::Insert { .. } => (true, None),
+                            Operation::Delete { data, .. } => {
+                                (false, Some(data.iter().map(Some).collect::<Vec<_>>()))
+                            }
+                            Operation::Update { values, .. } => (
+                                false,
+                                Some(values.iter().map(|(old, _)| old.as_ref()).collect()),
+                            ),
+                        };
+                        let present = store.row(table.name(), pk);
+                        if let Some(kind) = conflict_kind(present, exists, old.as_deref()) {
+                            conflicts.push(Conflict {
+                                table: table.name().clone(),
+                                pk: pk.clone(),
+                                kind,
+                            });
+                        }
+                    }
+                }
+            }
+            Self::Patchset(patchset) => {
+                for (table, rows) in &patchset.tables {
+                    for (pk, op) in rows {
+                        let exists = matches!(op, Operation::Insert { .. });
+                        let present = store.row(table.name(), pk);
+                        if let Some(kind) = conflict_kind(present, exists, None) {
+                            conflicts.push(Conflict {
+                                table: table.name().clone(),
+                                pk: pk.clone(),
+                                kind,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+/// Counts the tables of `diffset` holding at least one operation.
+fn count_tables<F: Format<String, Vec<u8>>>(
+    diffset: &DiffSet<F, TableSchema<String>, String, Vec<u8>>,
+) -> usize {
+    diffset
+        .tables
+        .iter()
+        .filter(|(_, rows)| !rows.is_empty())
+        .count()
+}
+
+/// Counts the `(inserts, updates, deletes)` of `diffset`.
+fn count_kinds<F: Format<String, Vec<u8>>>(
+    diffset: &DiffSet<F, TableSchema<String>, String, Vec<u8>>,
+) -> (usize, usize, usize) {
+    diffset.tables.iter().flat_map(|(_, rows)| rows).fold(
+        (0, 0, 0),
+        |(inserts, updates, deletes), (_, op)| match op {
+            Operation::Insert { .. } => (inserts + 1, updates, deletes),
+            Operation::Update { .. } => (inserts, updates + 1, deletes),
+            Operation::Delete { .. } => (inserts, updates, deletes + 1),
+        },
+    )
+}
+
+/// Old values a changeset UPDATE or DELETE expects, `None` where undefined.
+type OldValues<'row> = [Option<&'row Value<String, Vec<u8>>>];
+
+/// Returns how an operation conflicts with the `present` row, if it does.
+///
+/// `exists` is true for an INSERT, which needs the key absent. `old` is
+/// only given for a changeset UPDATE or DELETE.
+fn conflict_kind(
+    present: Option<&[Value<String, Vec<u8>>]>,
+    exists: bool,
+    old: Option<&OldValues<'_>>,
+) -> Option<ConflictKind> {
+    match (present, exists) {
+        (Some(_), true) => Some(ConflictKind::Exists),
+        (None, true) => None,
+        (None, false) => Some(ConflictKind::Missing),
+        (Some(row), false) => {
+            let differs = old
+                .into_iter()
+                .flatten()
+                .zip(row)
+                .any(|(old, value)| old.is_some_and(|old| values_differ_for_patch(old, value)));
+            differs.then_some(ConflictKind::Data)
+        }
+    }
+}
+
+impl ParsedDiffSet {
+    /// Feeds every operation to `visitor`, in stored order, grouped by table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{
+    ///     ChangeSet, DiffOps, Insert, OperationVisitor, ParsedDiffSet, SimpleTable, TableSchema,
+    ///     Value,
+    /// };
+    ///
+    /// #[derive(Default)]
+    /// struct Inserts(usize);
+    ///
+    /// impl OperationVisitor<TableSchema<String>, String, Vec<u8>> for Inserts {
+    ///     fn visit_insert(&mut self, _: &TableSchema<String>, _: &[Value<String, Vec<u8>>], _: bool) {
+    ///         self.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+    ///     ChangeSet::new().insert(Insert::from(users).set(0, 1i64).unwrap());
+    /// let parsed = ParsedDiffSet::try_from(changeset.build().as_slice()).unwrap();
+    ///
+    /// let mut inserts = Inserts::default();
+    /// parsed.visit_all(&mut inserts);
+    /// assert_eq!(inserts.0, 1);
+    /// ```
+    pub fn visit_all<V>(&self, visitor: &mut V)
+    where
+        V: OperationVisitor<TableSchema<String>, String, Vec<u8>> + ?Sized,
+    {
+        match self {
+            ParsedDiffSet::Changeset(d) => d.iter().for_each(|op| op.accept(visitor)),
+            ParsedDiffSet::Patchset(d) => d.iter().for_each(|op| op.accept(visitor)),
+        }
+    }
+
+    /// Returns the names of the tables with operations, ordered so that every
+    /// parent precedes its children.
+    ///
+    /// Each `(parent, child)` pair in `fks` declares that `child` holds a
+    /// foreign key into `parent`. Applying inserts table by table in the
+    /// returned order keeps referenced rows ahead of the rows that reference
+    /// them when `PRAGMA foreign_keys = ON`; deletes should walk the order in
+    /// reverse.
+    ///
+    /// The sort is stable: tables without a constraint between them keep the
+    /// order they appear in the diffset. Pairs naming a table that has no
+    /// operations, and self-references, are ignored. Tables caught in a
+    /// dependency cycle are emitted in diffset order once nothing else can be.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id"], &[0]);
+    /// let posts = SimpleTable::new("posts", &["id", "user_id"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(posts).set(0, 1i64).unwrap().set(1, 1i64).unwrap())
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap());
+    ///
+    /// let parsed = ParsedDiffSet::try_from(changeset.build().as_slice()).unwrap();
+    /// assert_eq!(parsed.topo_order_tables(&[("users", "posts")]), ["users", "posts"]);
+    /// ```
+    #[must_use]
+    pub fn topo_order_tables(&self, fks: &[(&str, &str)]) -> Vec<&str> {
+        let mut remaining: Vec<&str> = self
+            .table_schemas()
+            .into_iter()
+            .map(|schema| schema.name().as_str())
+            .collect();
+        let mut ordered = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let ready = remaining
+                .iter()
+                .position(|&table| {
+                    !fks.iter().any(|&(parent, child)| {
+                        child == table && parent != table && remaining.contains(&parent)
+                    })
+                })
+                .unwrap_or(0);
+            ordered.push(remaining.remove(ready));
+        }
+
+        ordered
+    }
+}
+
+/// One change record of a changeset or patchset, decoded without
+/// consolidation and tied to the bytes its values were read from.
+///
+/// Returned by [`ParsedDiffSet::raw_operations`], which keeps every record of
+/// the input instead of merging records on the same row, so a proxy can
+/// forward selected values by splicing their byte ranges instead of decoding
+/// and re-encoding them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawOperation {
+    table: TableSchema<String>,
+    op_code: u8,
+    indirect: bool,
+    values: Vec<MaybeValue<String, Vec<u8>>>,
+    spans: Vec<Range<usize>>,
+}
+
+impl RawOperation {
+    /// Returns the schema from the table header the record belongs to.
+    #[must_use]
+    pub fn table(&self) -> &TableSchema<String> {
+        &self.table
+    }
+
+    /// Returns the operation code: `0x12` for INSERT, `0x09` for DELETE and
+    /// `0x17` for UPDATE.
+    #[must_use]
+    pub fn op_code(&self) -> u8 {
+        self.op_code
+    }
+
+    /// Returns the record's indirect flag.
+    #[must_use]
+    pub fn indirect(&self) -> bool {
+        self.indirect
+    }
+
+    /// Returns the decoded values in wire order, `None` for undefined ones.
+    ///
+    /// A changeset UPDATE stores every old value followed by every new one. A
+    /// patchset DELETE stores only the PK values, and a patchset UPDATE one
+    /// value per column: the PK values and the new values of the others.
+    #[must_use]
+    pub fn values(&self) -> &[MaybeValue<String, Vec<u8>>] {
+        &self.values
+    }
+
+    /// Returns, for each of [`values`](Self::values), the range of the input
+    /// buffer holding its encoding, type byte included.
+    #[must_use]
+    pub fn raw_value_spans(&self) -> Vec<Range<usize>> {
+        self.spans.clone()
+    }
+}
+
+impl ParsedDiffSet {
+    /// Decodes every change record of `data`, in stored order and without
+    /// consolidation, along with the byte range of each value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap().set(1, "Alice").unwrap());
+    /// let bytes = changeset.build();
+    ///
+    /// let ops = ParsedDiffSet::raw_operations(&bytes).unwrap();
+    /// let name = ops[0].raw_value_spans()[1].clone();
+    /// assert_eq!(&bytes[name], b"\x03\x05Alice");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the data is malformed, contains invalid
+    /// values, or mixes changeset and patchset tables.
+    pub fn raw_operations(data: &[u8]) -> Result<Vec<RawOperation>, ParseError> {
+        let mut operations = Vec::new();
+        let mut pos = 0;
+        let mut expected = None;
+
+        while pos < data.len() {
+            let (table, format, header_len) = parse_table_header(&data[pos..], pos)?;
+            let expected = *expected.get_or_insert(format);
+            if format != expected {
+                return Err(ParseError::MixedFormats {
+                    expected,
+                    found: format,
+                    position: pos,
+                });
+            }
+            pos += header_len;
+
+            let pk_count = table.pk_flags.iter().filter(|&&b| b > 0).count();
+            while pos < data.len()
+                && data[pos] != markers::CHANGESET
+                && data[pos] != markers::PATCHSET
+            {
+                let (op_code, indirect, header_len) = parse_operation_header(&data[pos..], pos)?;
+                let count = record_value_count(format, op_code, &table, pk_count, pos)?;
+                pos += header_len;
+
+                let mut values = Vec::with_capacity(count);
+                let mut spans = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let (value, len) =
+                        decode_maybe_value(&data[pos..]).ok_or(ParseError::InvalidValue(pos))?;
+                    values.push(value);
+                    spans.push(pos..pos + len);
+                    pos += len;
+                }
+                operations.push(RawOperation {
+                    table: table.clone(),
+                    op_code,
+                    indirect,
+                    values,
+                    spans,
+                });
+            }
+        }
+
+        Ok(operations)
+    }
+
+    /// Splits `data` into one byte span per table section, each holding the
+    /// table header and the records that follow it.
+    ///
+    /// Every span is itself a valid single-table changeset or patchset, so
+    /// the sections can be parsed or applied independently, for instance on
+    /// different threads. Records are validated but not decoded into values.
+    /// A table whose section occurs twice in `data` yields two spans.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let posts = SimpleTable::new("posts", &["id", "title"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users).set(0, 1i64).unwrap().set(1, "Alice").unwrap())
+    ///     .insert(Insert::from(posts).set(0, 1i64).unwrap().set(1, "Hello").unwrap());
+    /// let bytes = changeset.build();
+    ///
+    /// let sections = ParsedDiffSet::split_by_table(&bytes).unwrap();
+    /// assert_eq!(sections.len(), 2);
+    /// assert_eq!(sections[0].0.name(), "users");
+    /// assert_eq!(ParsedDiffSet::parse(sections[1].1).unwrap().table_count(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the data is malformed, contains invalid
+    /// values, or mixes changeset and patchset tables.
+    pub fn split_by_table(data: &[u8]) -> Result<Vec<TableSection<'_>>, ParseError> {
+        let mut sections = Vec::new();
+        let mut pos = 0;
+        let mut expected = None;
+
+        while pos < data.len() {
+            let start = pos;
+            let (table, format, header_len) = parse_table_header(&data[pos..], pos)?;
+            let expected = *expected.get_or_insert(format);
+            if format != expected {
+                return Err(ParseError::MixedFormats {
+                    expected,
+                    found: format,
+                    position: pos,
+                });
+            }
+            pos += header_len;
+
+            let pk_count = table.pk_flags.iter().filter(|&&b| b > 0).count();
+            while pos < data.len()
+                && data[pos] != markers::CHANGESET
+                && data[pos] != markers::PATCHSET
+            {
+                let (op_code, _, header_len) = parse_operation_header(&data[pos..], pos)?;
+                let count = record_value_count(format, op_code, &table, pk_count, pos)?;
+                pos += header_len;
+                for _ in 0..count {
+                    let (_, len) =
+                        decode_maybe_value(&data[pos..]).ok_or(ParseError::InvalidValue(pos))?;
+                    pos += len;
+                }
+            }
+            sections.push((table, &data[start..pos]));
+        }
+
+        Ok(sections)
+    }
+}
+
+/// A table schema paired with the bytes of its section, as returned by
+/// [`ParsedDiffSet::split_by_table`].
+pub type TableSection<'a> = (TableSchema<String>, &'a [u8]);
+
+/// Number of values stored by a record with `op_code`, reporting `pos` for
+/// unknown op codes.
+fn record_value_count(
+    format: FormatMarker,
+    op_code: u8,
+    table: &TableSchema<String>,
+    pk_count: usize,
+    pos: usize,
+) -> Result<usize, ParseError> {
+    match (format, op_code) {
+        (FormatMarker::Changeset, op_codes::UPDATE) => Ok(2 * table.column_count),
+        (FormatMarker::Patchset, op_codes::DELETE) => Ok(pk_count),
+        (_, op_codes::INSERT | op_codes::DELETE | op_codes::UPDATE) => Ok(table.column_count),
+        _ => Err(ParseError::InvalidOpCode(op_code, pos)),
+    }
+}
+
+impl<F: Format<String, Vec<u8>>> DiffSet<F, TableSchema<String>, String, Vec<u8>>
+where
+    Operation<F, String, Vec<u8>>: core::ops::Add<Output = Option<Operation<F, String, Vec<u8>>>>,
+{
+    /// Merges `other` into this diffset, consolidating operations on the same
+    /// row as if they had been recorded one after the other.
+    ///
+    /// Tables are matched by name. A table present on both sides must have
+    /// the same shape, otherwise the rows would be decoded against the wrong
+    /// columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SchemaMismatch`] naming the differing field when a table
+    /// of `other` disagrees with the table of the same name in `self`.
+    pub fn merge(self, other: Self) -> Result<Self, SchemaMismatch> {
+        let mut builder: DiffSetBuilder<F, TableSchema<String>, String, Vec<u8>> = self.into();
+        for (table, rows) in other.tables {
+            if let Some(existing) = builder.table_named(table.name()) {
+                existing.compatible_with(&table)?;
+            }
+            for (pk, op) in rows {
+                builder.add_operation(&table, pk, op);
+            }
+        }
+        Ok(builder.into())
+    }
 }
 
 /// Parse binary data as a changeset.
@@ -328,16 +1381,26 @@ impl ParsedDiffSet {
 /// # Errors
 ///
 /// Returns a `ParseError` if the data is malformed or not a valid changeset.
+/// With `prefix`, parsing instead stops at the first invalid record after the
+/// leading table header and returns what was parsed up to it.
 fn parse_as_changeset(
     data: &[u8],
-) -> Result<DiffSet<ChangesetFormat, TableSchema<String>, String, Vec<u8>>, ParseError> {
+    prefix: bool,
+) -> Result<ParsedPrefix<ChangesetFormat>, ParseError> {
     let mut builder: DiffSetBuilder<ChangesetFormat, TableSchema<String>, String, Vec<u8>> =
         DiffSetBuilder::new();
     let mut pos = 0;
 
-    while pos < data.len() {
-        let (schema, format, header_len) = parse_table_header(&data[pos..], pos)?;
+    'tables: while pos < data.len() {
+        let (schema, format, header_len) = match parse_table_header(&data[pos..], pos) {
+            Ok(header) => header,
+            Err(_) if prefix && pos > 0 => break,
+            Err(err) => return Err(err),
+        };
         if format != FormatMarker::Changeset {
+            if prefix {
+                break;
+            }
             return Err(ParseError::MixedFormats {
                 expected: FormatMarker::Changeset,
                 found: format,
@@ -351,12 +1414,19 @@ fn parse_as_changeset(
             if byte == markers::CHANGESET || byte == markers::PATCHSET {
                 break;
             }
-            let op_len = parse_changeset_operation(&data[pos..], pos, &schema, &mut builder)?;
-            pos += op_len;
+            match parse_changeset_operation(&data[pos..], pos, &schema, &mut builder) {
+                Ok(op_len) if prefix => pos += op_len,
+                Ok(op_len) => {
+                    check_record_end(&data[pos..], op_len, pos)?;
+                    pos += op_len;
+                }
+                Err(_) if prefix => break 'tables,
+                Err(err) => return Err(err),
+            }
         }
     }
 
-    Ok(builder.into())
+    Ok((builder.into(), pos))
 }
 
 /// Parse binary data as a patchset.
@@ -364,16 +1434,26 @@ fn parse_as_changeset(
 /// # Errors
 ///
 /// Returns a `ParseError` if the data is malformed or not a valid patchset.
+/// With `prefix`, parsing instead stops at the first invalid record after the
+/// leading table header and returns what was parsed up to it.
 fn parse_as_patchset(
     data: &[u8],
-) -> Result<DiffSet<PatchsetFormat, TableSchema<String>, String, Vec<u8>>, ParseError> {
+    prefix: bool,
+) -> Result<ParsedPrefix<PatchsetFormat>, ParseError> {
     let mut builder: DiffSetBuilder<PatchsetFormat, TableSchema<String>, String, Vec<u8>> =
         DiffSetBuilder::new();
     let mut pos = 0;
 
-    while pos < data.len() {
-        let (schema, format, header_len) = parse_table_header(&data[pos..], pos)?;
+    'tables: while pos < data.len() {
+        let (schema, format, header_len) = match parse_table_header(&data[pos..], pos) {
+            Ok(header) => header,
+            Err(_) if prefix && pos > 0 => break,
+            Err(err) => return Err(err),
+        };
         if format != FormatMarker::Patchset {
+            if prefix {
+                break;
+            }
             return Err(ParseError::MixedFormats {
                 expected: FormatMarker::Patchset,
                 found: format,
@@ -387,12 +1467,19 @@ fn parse_as_patchset(
             if byte == markers::CHANGESET || byte == markers::PATCHSET {
                 break;
             }
-            let op_len = parse_patchset_operation(&data[pos..], pos, &schema, &mut builder)?;
-            pos += op_len;
+            match parse_patchset_operation(&data[pos..], pos, &schema, &mut builder) {
+                Ok(op_len) if prefix => pos += op_len,
+                Ok(op_len) => {
+                    check_record_end(&data[pos..], op_len, pos)?;
+                    pos += op_len;
+                }
+                Err(_) if prefix => break 'tables,
+                Err(err) => return Err(err),
+            }
         }
     }
 
-    Ok(builder.into())
+    Ok((builder.into(), pos))
 }
 
 /// Parse a table header and return the schema.
@@ -408,6 +1495,7 @@ fn parse_table_header(
     let format = match data[pos] {
         markers::CHANGESET => FormatMarker::Changeset,
         markers::PATCHSET => FormatMarker::Patchset,
+        first_byte if base_pos == 0 => return Err(ParseError::NotAChangeset { first_byte }),
         b => return Err(ParseError::InvalidTableMarker(b, base_pos + pos)),
     };
     pos += 1;
@@ -460,7 +1548,8 @@ fn parse_changeset_operation(
 
     match op_code {
         op_codes::INSERT => {
-            let (values, len) = parse_values(&data[pos..], base_pos + pos, schema.column_count)?;
+            let (values, len) = parse_record(data, pos, base_pos, op_code, schema.column_count)?;
+            check_pk_defined(pk_slots(schema, &values), op_code, base_pos)?;
             pos += len;
             let values: Vec<Value<String, Vec<u8>>> = values
                 .into_iter()
@@ -470,7 +1559,8 @@ fn parse_changeset_operation(
             builder.add_operation(schema, pk, Operation::Insert { values, indirect });
         }
         op_codes::DELETE => {
-            let (values, len) = parse_values(&data[pos..], base_pos + pos, schema.column_count)?;
+            let (values, len) = parse_record(data, pos, base_pos, op_code, schema.column_count)?;
+            check_pk_defined(pk_slots(schema, &values), op_code, base_pos)?;
             pos += len;
             let values: Vec<Value<String, Vec<u8>>> = values
                 .into_iter()
@@ -488,10 +1578,11 @@ fn parse_changeset_operation(
         }
         op_codes::UPDATE => {
             let (old_values, old_len) =
-                parse_values(&data[pos..], base_pos + pos, schema.column_count)?;
+                parse_record(data, pos, base_pos, op_code, schema.column_count)?;
+            check_pk_defined(pk_slots(schema, &old_values), op_code, base_pos)?;
             pos += old_len;
             let (new_values, new_len) =
-                parse_values(&data[pos..], base_pos + pos, schema.column_count)?;
+                parse_record(data, pos, base_pos, op_code, schema.column_count)?;
             pos += new_len;
             // Extract PK using old values (convert None to Null)
             let pk_values: Vec<Value<String, Vec<u8>>> = old_values
@@ -519,7 +1610,8 @@ fn parse_patchset_operation(
 
     match op_code {
         op_codes::INSERT => {
-            let (values, len) = parse_values(&data[pos..], base_pos + pos, schema.column_count)?;
+            let (values, len) = parse_record(data, pos, base_pos, op_code, schema.column_count)?;
+            check_pk_defined(pk_slots(schema, &values), op_code, base_pos)?;
             pos += len;
             let values: Vec<Value<String, Vec<u8>>> = values
                 .into_iter()
@@ -531,7 +1623,8 @@ fn parse_patchset_operation(
         op_codes::DELETE => {
             // Patchset DELETE: only PK values in column order
             let pk_count = schema.pk_flags.iter().filter(|&&b| b > 0).count();
-            let (pk_values, len) = parse_values(&data[pos..], base_pos + pos, pk_count)?;
+            let (pk_values, len) = parse_record(data, pos, base_pos, op_code, pk_count)?;
+            check_pk_defined(pk_values.iter(), op_code, base_pos)?;
             pos += len;
             // Expand PK values to full row, then extract_pk to get ordinal-sorted PK.
             // This is needed because the binary format stores PKs in column order,
@@ -547,43 +1640,22 @@ fn parse_patchset_operation(
         }
         op_codes::UPDATE => {
             // Patchset UPDATE wire layout (matching SQLite's session extension):
-            //   old side: PK-only values, in column order, exactly `pk_count` entries
-            //            (no padding / undefined markers for non-PK columns).
-            //   new side: non-PK columns, in column order, exactly
-            //            `column_count - pk_count` entries; each entry is either the
-            //            new column value or `0x00` (undefined) if that non-PK column
-            //            did not change.
-            //
-            // Internally we rehydrate the operation into a full-width
-            // `Vec<((), MaybeValue)>` of length `column_count` so downstream code
-            // (`extract_pk`, `sql_output`, consolidation, reversal) keeps working
-            // uniformly: PK slots hold `Some(pk_value)`, non-PK slots hold either
-            // `Some(new_value)` or `None` for undefined.
-            let pk_count = schema.pk_flags.iter().filter(|&&b| b > 0).count();
-            let non_pk_count = schema.column_count.saturating_sub(pk_count);
-
-            let (old_pk_values, old_len) = parse_values(&data[pos..], base_pos + pos, pk_count)?;
-            pos += old_len;
-            let (new_non_pk_values, new_len) =
-                parse_values(&data[pos..], base_pos + pos, non_pk_count)?;
-            pos += new_len;
-
-            let mut values: Vec<((), MaybeValue<String, Vec<u8>>)> =
-                alloc::vec![((), None); schema.column_count];
-            let mut old_iter = old_pk_values.into_iter();
-            let mut new_iter = new_non_pk_values.into_iter();
-            for (col_idx, &pk_flag) in schema.pk_flags.iter().enumerate() {
-                if pk_flag > 0 {
-                    // PK columns always carry a defined value on the old side; a
-                    // stray undefined marker is normalized to Null to stay lenient
-                    // for fuzz-generated inputs (matches `expand_pk_values` in the
-                    // DELETE path).
-                    let old = old_iter.next().flatten().unwrap_or(Value::Null);
-                    values[col_idx] = ((), Some(old));
-                } else {
-                    values[col_idx] = ((), new_iter.next().flatten());
-                }
-            }
+            // a single record of `column_count` entries in column order. PK
+            // columns hold their value; every other column holds its new value,
+            // or `0x00` (undefined) if it did not change.
+            let (record, len) = parse_record(data, pos, base_pos, op_code, schema.column_count)?;
+            check_pk_defined(
+                record
+                    .iter()
+                    .zip(&schema.pk_flags)
+                    .filter(|&(_, &pk_flag)| pk_flag > 0)
+                    .map(|(value, _)| value),
+                op_code,
+                base_pos,
+            )?;
+            pos += len;
+            let values: Vec<((), MaybeValue<String, Vec<u8>>)> =
+                record.into_iter().map(|value| ((), value)).collect();
 
             let pk = schema.extract_pk(&values);
             builder.add_operation(schema, pk, Operation::Update { values, indirect });
@@ -615,6 +1687,93 @@ fn expand_pk_values(
     full
 }
 
+/// Parses the `count` values of a record of the `op_code` operation whose
+/// header starts `data`, at `base_pos`, reading from `data[pos..]`.
+///
+/// A value that cannot be decoded because the input ends or the next
+/// operation or table begins there means the record is shorter than its table
+/// declares, which is reported as a malformed operation.
+fn parse_record(
+    data: &[u8],
+    pos: usize,
+    base_pos: usize,
+    op_code: u8,
+    count: usize,
+) -> Result<ParsedValues, ParseError> {
+    parse_values(&data[pos..], base_pos + pos, count).map_err(|err| match err {
+        ParseError::InvalidValue(at) if at_record_boundary(data, at - base_pos) => {
+            ParseError::MalformedOperation {
+                op_code,
+                position: base_pos,
+            }
+        }
+        err => err,
+    })
+}
+
+/// Checks that the operation whose header starts `data`, at `base_pos`, and
+/// whose record ends at `end` is followed by the end of the input, another
+/// operation, or a table header, so its record holds no more values than its
+/// table declares.
+///
+/// Prefix parsing skips this check: there, bytes that follow a record and
+/// start no operation are a trailer to leave unconsumed.
+fn check_record_end(data: &[u8], end: usize, base_pos: usize) -> Result<(), ParseError> {
+    if at_record_boundary(data, end) {
+        Ok(())
+    } else {
+        Err(ParseError::MalformedOperation {
+            op_code: data[0],
+            position: base_pos,
+        })
+    }
+}
+
+/// Whether a record may end at `data[pos]`: the input ends there or the byte
+/// starts an operation or a table header.
+fn at_record_boundary(data: &[u8], pos: usize) -> bool {
+    matches!(
+        data.get(pos),
+        None | Some(
+            &(markers::CHANGESET
+                | markers::PATCHSET
+                | op_codes::INSERT
+                | op_codes::DELETE
+                | op_codes::UPDATE)
+        )
+    )
+}
+
+/// The values of the primary-key columns of a full-width record.
+fn pk_slots<'a>(
+    schema: &'a TableSchema<String>,
+    values: &'a [MaybeValue<String, Vec<u8>>],
+) -> impl Iterator<Item = &'a MaybeValue<String, Vec<u8>>> {
+    schema
+        .pk_flags
+        .iter()
+        .zip(values)
+        .filter(|&(&flag, _)| flag > 0)
+        .map(|(_, value)| value)
+}
+
+/// Checks that every primary-key value of the `op_code` operation at
+/// `base_pos` is defined.
+fn check_pk_defined<'a>(
+    mut pk_values: impl Iterator<Item = &'a MaybeValue<String, Vec<u8>>>,
+    op_code: u8,
+    base_pos: usize,
+) -> Result<(), ParseError> {
+    if pk_values.all(Option::is_some) {
+        Ok(())
+    } else {
+        Err(ParseError::MalformedOperation {
+            op_code,
+            position: base_pos,
+        })
+    }
+}
+
 /// Parse a sequence of values.
 fn parse_values(data: &[u8], base_pos: usize, count: usize) -> Result<ParsedValues, ParseError> {
     let mut values = Vec::with_capacity(count);
@@ -622,7 +1781,7 @@ fn parse_values(data: &[u8], base_pos: usize, count: usize) -> Result<ParsedValu
 
     for _ in 0..count {
         let (value, value_len) =
-            decode_value(&data[pos..]).ok_or(ParseError::InvalidValue(base_pos + pos))?;
+            decode_maybe_value(&data[pos..]).ok_or(ParseError::InvalidValue(base_pos + pos))?;
         values.push(value);
         pos += value_len;
     }
@@ -634,6 +1793,7 @@ fn parse_values(data: &[u8], base_pos: usize, count: usize) -> Result<ParsedValu
 mod tests {
     use super::*;
     use crate::SimpleTable;
+    use alloc::string::ToString;
     use alloc::vec;
 
     #[test]
@@ -643,6 +1803,108 @@ mod tests {
         assert!(result.unwrap().is_changeset());
     }
 
+    #[test]
+    fn test_parsed_diffset_equals_source_builder() {
+        use crate::{ChangeDelete, ChangeSet, DiffOps, Insert, PatchSet, Update};
+
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "a")
+                    .unwrap(),
+            )
+            .update(
+                Update::<SimpleTable, ChangesetFormat, String, Vec<u8>>::from(users.clone())
+                    .set(0, 2i64, 2i64)
+                    .unwrap()
+                    .set(1, "b", "c")
+                    .unwrap(),
+            )
+            .delete(ChangeDelete::from(users.clone()).set(0, 3i64).unwrap());
+        let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+        assert_eq!(parsed, changeset);
+
+        let other: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(
+            Insert::from(users.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "z")
+                .unwrap(),
+        );
+        assert_ne!(parsed, other);
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(
+            Insert::from(users)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap(),
+        );
+        assert_ne!(parsed, patchset);
+    }
+
+    fn fk_chain_changeset() -> ParsedDiffSet {
+        use crate::{ChangeSet, DiffOps, Insert};
+
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let posts = SimpleTable::new("posts", &["id", "user_id"], &[0]);
+        let comments = SimpleTable::new("comments", &["id", "post_id"], &[0]);
+        let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(
+                Insert::from(comments)
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, 1i64)
+                    .unwrap(),
+            )
+            .insert(
+                Insert::from(posts)
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, 1i64)
+                    .unwrap(),
+            )
+            .insert(Insert::from(users).set(0, 1i64).unwrap());
+        ParsedDiffSet::parse(&changeset.build()).unwrap()
+    }
+
+    #[test]
+    fn test_topo_order_tables_fk_chain() {
+        let parsed = fk_chain_changeset();
+        assert_eq!(
+            parsed.topo_order_tables(&[]),
+            ["comments", "posts", "users"]
+        );
+        assert_eq!(
+            parsed.topo_order_tables(&[("posts", "comments"), ("users", "posts")]),
+            ["users", "posts", "comments"]
+        );
+    }
+
+    #[test]
+    fn test_topo_order_tables_ignores_unknown_and_self_references() {
+        let parsed = fk_chain_changeset();
+        assert_eq!(
+            parsed.topo_order_tables(&[
+                ("tags", "posts"),
+                ("comments", "comments"),
+                ("users", "posts"),
+            ]),
+            ["comments", "users", "posts"]
+        );
+    }
+
+    #[test]
+    fn test_topo_order_tables_cycle_falls_back_to_diffset_order() {
+        let parsed = fk_chain_changeset();
+        assert_eq!(
+            parsed.topo_order_tables(&[("posts", "comments"), ("comments", "posts")]),
+            ["users", "comments", "posts"]
+        );
+    }
+
     #[test]
     fn test_parse_table_header() {
         // 'T', 2 columns, pk_flags [1, 0], table name "t\0"
@@ -772,14 +2034,53 @@ mod tests {
 
     #[test]
     fn test_parse_invalid_table_marker() {
+        // A bad first byte is reported as `NotAChangeset` instead.
         let data = [0xFFu8, 1, 1, b't', 0];
-        let err = ParsedDiffSet::parse(&data).unwrap_err();
+        let err = parse_table_header(&data, 7).unwrap_err();
         assert!(
-            matches!(err, ParseError::InvalidTableMarker(0xFF, 0)),
+            matches!(err, ParseError::InvalidTableMarker(0xFF, 7)),
             "got {err:?}"
         );
     }
 
+    #[test]
+    fn test_parse_gzip_input_is_not_a_changeset() {
+        // gzip magic, deflate method, then flags and a timestamp.
+        let data = [0x1F, 0x8B, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03];
+        let err = ParsedDiffSet::parse(&data).unwrap_err();
+        assert_eq!(err, ParseError::NotAChangeset { first_byte: 0x1F });
+        assert_eq!(ParsedDiffSet::raw_operations(&data).unwrap_err(), err);
+        assert_eq!(
+            err.to_string(),
+            "Not a changeset or patchset: first byte 0x1f is not a table marker \
+             (input may be compressed or in a different format)"
+        );
+    }
+
+    #[test]
+    fn test_parse_random_bytes_are_not_a_changeset() {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        for _ in 0..256 {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let data = state.to_le_bytes();
+            let first_byte = data[0];
+            if first_byte == markers::CHANGESET || first_byte == markers::PATCHSET {
+                continue;
+            }
+            assert_eq!(
+                ParsedDiffSet::parse(&data),
+                Err(ParseError::NotAChangeset { first_byte })
+            );
+            assert_eq!(
+                ParsedDiffSet::try_from(&data[..]),
+                Err(ParseError::NotAChangeset { first_byte })
+            );
+        }
+    }
+
     #[test]
     fn test_parse_unexpected_eof_in_table_header() {
         // 'T' marker but no column count
@@ -856,6 +2157,420 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_try_from_rejects_mixed_formats_with_operations() {
+        use crate::{ChangeSet, DiffOps, Insert, PatchSet};
+
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let changeset: Vec<u8> = ChangeSet::<SimpleTable, String, Vec<u8>>::new()
+            .insert(Insert::from(users.clone()).set(0, 1i64).unwrap())
+            .build();
+        let posts = SimpleTable::new("posts", &["id"], &[0]);
+        let patchset: Vec<u8> = PatchSet::<SimpleTable, String, Vec<u8>>::new()
+            .insert(Insert::from(posts).set(0, 2i64).unwrap())
+            .build();
+
+        // Each half parses on its own.
+        assert!(matches!(
+            ParsedDiffSet::try_from(changeset.as_slice()),
+            Ok(ParsedDiffSet::Changeset(_))
+        ));
+        assert!(matches!(
+            ParsedDiffSet::try_from(patchset.as_slice()),
+            Ok(ParsedDiffSet::Patchset(_))
+        ));
+
+        let mixed = [changeset.as_slice(), patchset.as_slice()].concat();
+        assert_eq!(
+            ParsedDiffSet::try_from(mixed.as_slice()).unwrap_err(),
+            ParseError::MixedFormats {
+                expected: FormatMarker::Changeset,
+                found: FormatMarker::Patchset,
+                position: changeset.len(),
+            }
+        );
+    }
+
+    /// Asserts that every span of `ops` re-decodes, on its own, to the value
+    /// recorded next to it.
+    fn assert_spans_decode(bytes: &[u8], ops: &[RawOperation]) {
+        for op in ops {
+            let spans = op.raw_value_spans();
+            assert_eq!(spans.len(), op.values().len());
+            for (span, value) in spans.into_iter().zip(op.values()) {
+                let (decoded, len) = decode_maybe_value(&bytes[span.clone()]).unwrap();
+                assert_eq!(len, span.len());
+                assert_eq!(&decoded, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_raw_value_spans_decode_to_values() {
+        use crate::{
+            ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, PatchDelete, PatchSet,
+            PatchsetFormat, Update,
+        };
+
+        let users = SimpleTable::new("users", &["id", "name", "avatar"], &[0]);
+        let changeset: Vec<u8> = ChangeSet::<SimpleTable, String, Vec<u8>>::new()
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "Alice")
+                    .unwrap()
+                    .set(2, vec![0xde_u8, 0xad])
+                    .unwrap(),
+            )
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(users.clone())
+                    .set(0, 2i64, 2i64)
+                    .unwrap()
+                    .set(1, "Bob", "Robert")
+                    .unwrap(),
+            )
+            .delete(
+                ChangeDelete::from(users.clone())
+                    .set(0, 3i64)
+                    .unwrap()
+                    .set(2, 1.5f64)
+                    .unwrap(),
+            )
+            .build();
+        let ops = ParsedDiffSet::raw_operations(&changeset).unwrap();
+        let op_codes: Vec<u8> = ops.iter().map(RawOperation::op_code).collect();
+        assert_eq!(
+            op_codes,
+            [op_codes::INSERT, op_codes::UPDATE, op_codes::DELETE]
+        );
+        assert_eq!(ops[1].values().len(), 6);
+        assert_eq!(ops[1].values()[5], None);
+        assert_spans_decode(&changeset, &ops);
+
+        let patchset: Vec<u8> = PatchSet::<SimpleTable, String, Vec<u8>>::new()
+            .update(
+                Update::<_, PatchsetFormat, _, _>::from(users.clone())
+                    .set(0, 2i64)
+                    .unwrap()
+                    .set(1, "Robert")
+                    .unwrap(),
+            )
+            .delete(PatchDelete::new(users, vec![Value::Integer(3)]))
+            .build();
+        let ops = ParsedDiffSet::raw_operations(&patchset).unwrap();
+        assert_eq!(ops[0].values().len(), 3);
+        assert_eq!(ops[1].values(), [Some(Value::Integer(3))]);
+        assert_spans_decode(&patchset, &ops);
+    }
+
+    #[test]
+    fn test_raw_operations_keep_records_on_the_same_row() {
+        use crate::{ChangeDelete, ChangeSet, DiffOps, Insert};
+
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let insert: Vec<u8> = ChangeSet::<SimpleTable, String, Vec<u8>>::new()
+            .insert(Insert::from(users.clone()).set(0, 1i64).unwrap())
+            .build();
+        let delete: Vec<u8> = ChangeSet::<SimpleTable, String, Vec<u8>>::new()
+            .delete(ChangeDelete::from(users).set(0, 1i64).unwrap())
+            .build();
+        let both = [insert.as_slice(), delete.as_slice()].concat();
+
+        assert_eq!(ParsedDiffSet::parse(&both).unwrap().operation_count(), 0);
+        let ops = ParsedDiffSet::raw_operations(&both).unwrap();
+        assert_eq!(ops.len(), 2);
+        // The second table header is 9 bytes long and the record header 2.
+        assert_eq!(ops[1].raw_value_spans()[0].start, insert.len() + 9 + 2);
+        assert_spans_decode(&both, &ops);
+    }
+
+    #[test]
+    fn test_split_by_table_spans_parse_independently() {
+        use crate::{ChangeDelete, ChangeSet, DiffOps, Insert, Update};
+
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let posts = SimpleTable::new("posts", &["id", "user_id", "title"], &[0]);
+        let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(
+                Insert::from(users.clone())
+                    .set(0, 1i64)
+                    .unwrap()
+                    .set(1, "alice")
+                    .unwrap(),
+            )
+            .insert(
+                Insert::from(posts.clone())
+                    .set(0, 10i64)
+                    .unwrap()
+                    .set(1, 1i64)
+                    .unwrap()
+                    .set(2, "hello")
+                    .unwrap(),
+            )
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(users.clone())
+                    .set(0, 2i64, 2i64)
+                    .unwrap()
+                    .set(1, "bob", "carol")
+                    .unwrap(),
+            )
+            .delete(
+                ChangeDelete::from(posts)
+                    .set(0, 11i64)
+                    .unwrap()
+                    .set(1, 2i64)
+                    .unwrap()
+                    .set(2, "old")
+                    .unwrap(),
+            );
+        let bytes = changeset.build();
+
+        let sections = ParsedDiffSet::split_by_table(&bytes).unwrap();
+        assert_eq!(sections.len(), 2);
+        let spans: Vec<&[u8]> = sections.iter().map(|(_, span)| *span).collect();
+        assert_eq!(spans.concat(), bytes);
+        for (schema, span) in &sections {
+            let parsed = ParsedDiffSet::parse(span).unwrap();
+            assert_eq!(parsed.table_schemas(), vec![schema]);
+            assert_eq!(parsed.operation_count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_split_by_table_rejects_truncated_record() {
+        let data = make_insert_with_indirect(0);
+        assert_eq!(
+            ParsedDiffSet::split_by_table(&data[..data.len() - 1]),
+            Err(ParseError::InvalidValue(7))
+        );
+        assert_eq!(ParsedDiffSet::split_by_table(&[]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_find_locates_operation_by_table_and_pk() {
+        use crate::{ChangeDelete, ChangeSet, DiffOps, Insert, PatchSet};
+
+        // PRIMARY KEY(b, a): the key is looked up as (b, a).
+        let items = SimpleTable::new("items", &["a", "b", "val"], &[1, 0]);
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let insert = Insert::from(items.clone())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, 2i64)
+            .unwrap()
+            .set(2, "x")
+            .unwrap();
+        let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+            .insert(insert.clone())
+            .delete(ChangeDelete::from(users.clone()).set(0, 7i64).unwrap());
+        let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+
+        let Some(ParsedOp::Changeset(ChangesetOp::Insert { table, values, .. })) =
+            parsed.find("items", &[Value::Integer(2), Value::Integer(1)])
+        else {
+            panic!("expected the items INSERT");
+        };
+        assert_eq!(table.name(), "items");
+        assert_eq!(values[2], Value::Text("x".into()));
+        assert!(matches!(
+            parsed.find("users", &[Value::Integer(7)]),
+            Some(ParsedOp::Changeset(ChangesetOp::Delete { .. }))
+        ));
+        // Column order instead of key order, an absent key, an absent table.
+        assert!(
+            parsed
+                .find("items", &[Value::Integer(1), Value::Integer(2)])
+                .is_none()
+        );
+        assert!(parsed.find("users", &[Value::Integer(8)]).is_none());
+        assert!(parsed.find("posts", &[Value::Integer(7)]).is_none());
+
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(insert);
+        let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+        assert!(matches!(
+            parsed.find("items", &[Value::Integer(2), Value::Integer(1)]),
+            Some(ParsedOp::Patchset(PatchsetOp::Insert { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_slice_by_pk_range_extracts_mid_range_shard() {
+        use crate::{ChangeDelete, ChangeSet, DiffOps, Insert, PatchSet};
+
+        // PRIMARY KEY(region, id): slicing on `id` uses its key position.
+        let orders = SimpleTable::new("orders", &["region", "id", "total"], &[0, 1]);
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let order = |id: i64| {
+            Insert::from(orders.clone())
+                .set(0, "eu")
+                .unwrap()
+                .set(1, id)
+                .unwrap()
+                .set(2, id * 10)
+                .unwrap()
+        };
+        let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        for id in 0..20i64 {
+            changeset = changeset.insert(order(id));
+        }
+        changeset = changeset
+            .delete(
+                ChangeDelete::from(orders.clone())
+                    .set(0, "eu")
+                    .unwrap()
+                    .set(1, 25i64)
+                    .unwrap(),
+            )
+            .insert(Insert::from(users.clone()).set(0, 7i64).unwrap());
+        let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+
+        // 7.5 as a bound compares numerically with the integer keys.
+        let shard = parsed.slice_by_pk_range("orders", 1, &Value::Integer(5), &Value::Real(12.5));
+        assert!(shard.is_changeset());
+        assert_eq!(shard.operation_count(), 8);
+        assert_eq!(shard.table_count(), 1);
+        for id in 0..30i64 {
+            let key = [Value::Text("eu".into()), Value::Integer(id)];
+            assert_eq!(
+                shard.find("orders", &key).is_some(),
+                (5..=12).contains(&id),
+                "id {id}"
+            );
+        }
+        assert!(shard.find("users", &[Value::Integer(7)]).is_none());
+
+        // The shards of a partition add back up to the whole table.
+        let bounds = [
+            Value::Null,
+            Value::Integer(10),
+            Value::Integer(20),
+            Value::Text(String::new()),
+        ];
+        let total: usize = bounds
+            .windows(2)
+            .map(|w| {
+                parsed
+                    .slice_by_pk_range("orders", 1, &w[0], &w[1])
+                    .operation_count()
+            })
+            .sum();
+        assert_eq!(total, 21);
+
+        // Not a key column, an absent table, an empty range.
+        let lo = Value::Integer(0);
+        let hi = Value::Integer(100);
+        assert_eq!(
+            parsed
+                .slice_by_pk_range("orders", 2, &lo, &hi)
+                .operation_count(),
+            0
+        );
+        assert_eq!(
+            parsed
+                .slice_by_pk_range("posts", 0, &lo, &hi)
+                .operation_count(),
+            0
+        );
+        assert_eq!(
+            parsed
+                .slice_by_pk_range("orders", 1, &hi, &lo)
+                .operation_count(),
+            0
+        );
+
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> =
+            PatchSet::new().insert(order(1)).insert(order(2));
+        let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+        let shard = parsed.slice_by_pk_range("orders", 1, &Value::Integer(2), &Value::Integer(3));
+        assert!(shard.is_patchset());
+        assert_eq!(shard.operation_count(), 1);
+        assert!(
+            shard
+                .find("orders", &[Value::Text("eu".into()), Value::Integer(2)])
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_operations_in_byte_order_follow_the_wire() {
+        use crate::{ChangeSet, DiffOps, Insert, PatchSet};
+
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let posts = SimpleTable::new("posts", &["id"], &[0]);
+        let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        for id in 0..200i64 {
+            changeset = changeset
+                .insert(
+                    Insert::from(users.clone())
+                        .set(0, id)
+                        .unwrap()
+                        .set(1, id.to_string())
+                        .unwrap(),
+                )
+                .insert(Insert::from(posts.clone()).set(0, id).unwrap());
+        }
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = changeset.to_patchset();
+
+        for bytes in [changeset.build(), patchset.build()] {
+            let wire: Vec<Value<String, Vec<u8>>> = ParsedDiffSet::raw_operations(&bytes)
+                .unwrap()
+                .iter()
+                .filter(|op| op.table().name() == "users")
+                .map(|op| op.values()[0].clone().unwrap())
+                .collect();
+            // The session hash scrambles the recording order.
+            assert_ne!(
+                wire,
+                (0..200i64).map(Value::Integer).collect::<Vec<_>>(),
+                "expected a hash order"
+            );
+
+            let parsed = ParsedDiffSet::parse(&bytes).unwrap();
+            let iterated: Vec<Value<String, Vec<u8>>> = parsed
+                .operations_in_byte_order("users")
+                .into_iter()
+                .map(|op| match op {
+                    ParsedOp::Changeset(ChangesetOp::Insert { values, .. })
+                    | ParsedOp::Patchset(PatchsetOp::Insert { values, .. }) => values[0].clone(),
+                    other => panic!("unexpected operation {other:?}"),
+                })
+                .collect();
+            assert_eq!(iterated, wire);
+        }
+    }
+
+    #[test]
+    fn test_slice_by_pk_range_orders_text_keys_bytewise() {
+        use crate::{ChangeSet, DiffOps, Insert};
+
+        let tags = SimpleTable::new("tags", &["name"], &[0]);
+        let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+        for name in ["apple", "Banana", "banana", "cherry", "date"] {
+            changeset = changeset.insert(Insert::from(tags.clone()).set(0, name).unwrap());
+        }
+        let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+
+        let shard = parsed.slice_by_pk_range(
+            "tags",
+            0,
+            &Value::Text("b".into()),
+            &Value::Text("d".into()),
+        );
+        let mut names: Vec<String> = shard
+            .affected_keys()
+            .into_iter()
+            .flat_map(|(_, keys)| keys)
+            .map(|key| match &key[0] {
+                Value::Text(name) => name.clone(),
+                other => panic!("unexpected key {other:?}"),
+            })
+            .collect();
+        names.sort();
+        // "Banana" sorts before "b" under the BINARY collation.
+        assert_eq!(names, ["banana", "cherry"]);
+    }
+
     /// Build the operation header bytes followed by a single integer payload.
     fn make_insert_with_indirect(indirect_byte: u8) -> Vec<u8> {
         let mut data = vec![b'T', 1, 1, b't', 0];
@@ -867,6 +2582,72 @@ mod tests {
         data
     }
 
+    /// A patchset DELETE on table `t`, with the given PK flags, carrying one
+    /// integer value per entry of `pks`.
+    fn patchset_delete(pk_flags: &[u8], pks: &[i64]) -> Vec<u8> {
+        let mut data = vec![b'P', u8::try_from(pk_flags.len()).unwrap()];
+        data.extend(pk_flags);
+        data.extend(b"t\0");
+        data.extend([op_codes::DELETE, 0]);
+        for pk in pks {
+            data.push(0x01);
+            data.extend(&pk.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_rejects_delete_with_wrong_pk_value_count() {
+        let malformed = Err(ParseError::MalformedOperation {
+            op_code: op_codes::DELETE,
+            position: 6,
+        });
+        assert!(ParsedDiffSet::parse(&patchset_delete(&[1, 0], &[7])).is_ok());
+
+        // Too many: the extra value is read where the next operation starts.
+        assert_eq!(
+            ParsedDiffSet::parse(&patchset_delete(&[1, 0], &[7, 8])),
+            malformed
+        );
+
+        // Too few, at the end of the input and before the next operation.
+        let short = patchset_delete(&[1, 2], &[7]);
+        assert_eq!(ParsedDiffSet::parse(&short), malformed);
+        let mut followed = short.clone();
+        followed.extend(&patchset_delete(&[1, 2], &[7, 8])[6..]);
+        assert_eq!(ParsedDiffSet::parse(&followed), malformed);
+
+        // Prefix parsing keeps stopping at the first bad record.
+        let (parsed, consumed) = ParsedDiffSet::parse_prefix(&followed).unwrap();
+        assert_eq!((parsed.operation_count(), consumed), (0, 6));
+    }
+
+    #[test]
+    fn test_parse_rejects_undefined_pk_value() {
+        // Changeset UPDATE on t(id PRIMARY KEY, v) whose old id is undefined.
+        let data = [
+            b'T',
+            2,
+            1,
+            0,
+            b't',
+            0,
+            op_codes::UPDATE,
+            0,
+            0x00,
+            0x05,
+            0x00,
+            0x05,
+        ];
+        assert_eq!(
+            ParsedDiffSet::parse(&data),
+            Err(ParseError::MalformedOperation {
+                op_code: op_codes::UPDATE,
+                position: 6,
+            })
+        );
+    }
+
     fn first_op_indirect_changeset(data: &[u8]) -> bool {
         let parsed = ParsedDiffSet::parse(data).unwrap();
         let ParsedDiffSet::Changeset(set) = parsed else {
@@ -997,8 +2778,8 @@ mod tests {
     /// Wire layout:
     /// - 12 bytes header ('P', 3, [1,0,0], "orders\0")
     /// - 2 bytes op header (UPDATE, indirect=0)
-    /// - 9 bytes old side: INTEGER 5 (only the PK column)
-    /// - 10 bytes new side: undefined (amount unchanged) + TEXT 'shipped'
+    /// - 21 bytes record, one entry per column: INTEGER 5 (the PK), undefined
+    ///   (amount unchanged), TEXT 'shipped'
     ///
     /// Total 33 bytes. Historically the parser expected `column_count` values on
     /// each side (padded with undefined) and returned `InvalidValue` mid-buffer.
@@ -1030,8 +2811,8 @@ mod tests {
     /// UPDATE items SET val = 'v2' WHERE a = 1 AND b = 2;
     /// ```
     ///
-    /// Wire layout: two PK values on the old side (INTEGER 1, INTEGER 2), one
-    /// non-PK value on the new side (TEXT 'v2'). 35 bytes total.
+    /// Wire layout: the two PK values (INTEGER 1, INTEGER 2) followed by the
+    /// new non-PK value (TEXT 'v2'), in column order. 35 bytes total.
     #[test]
     fn test_parse_patchset_update_sqlite_wire_layout_composite_pk() {
         let data: [u8; 35] = [
@@ -1052,7 +2833,7 @@ mod tests {
         });
     }
 
-    /// Every non-PK column is present on the new side, in column order, either
+    /// Every non-PK column is present in the record, in column order, either
     /// as its new value or as the undefined marker `0x00` when unchanged.
     ///
     /// ```text
@@ -1167,4 +2948,260 @@ mod tests {
             vec![Value::Integer(20), Value::Integer(10)]
         );
     }
+
+    #[test]
+    fn test_parsed_schema_pk_parity_non_contiguous_ordinals() {
+        // Flags [3, 0, 1] skip ordinal 2 but still describe the key (c, a).
+        let parsed: TableSchema<String> = TableSchema::new("abc".into(), 3, vec![3, 0, 1]);
+        let simple = SimpleTable::new("abc", &["a", "b", "c"], &[2, 0]);
+        let row: Vec<Value<String, Vec<u8>>> = vec![
+            Value::Integer(10),
+            Value::Text("z".into()),
+            Value::Integer(30),
+        ];
+        assert_schema_pk_parity(&parsed, &simple, &row);
+        assert_eq!(parsed.primary_key_index(0), Some(1));
+        assert_eq!(parsed.primary_key_index(2), Some(0));
+    }
+
+    fn users_changeset(columns: &[&str], pk: &[usize], id: i64) -> ParsedDiffSet {
+        use crate::{ChangeSet, DiffOps, Insert};
+
+        let users = SimpleTable::new("users", columns, pk);
+        let mut insert = Insert::from(users);
+        for col in 0..columns.len() {
+            insert = insert.set(col, id).unwrap();
+        }
+        let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(insert);
+        ParsedDiffSet::parse(&changeset.build()).unwrap()
+    }
+
+    fn expect_changeset(
+        parsed: ParsedDiffSet,
+    ) -> DiffSet<ChangesetFormat, TableSchema<String>, String, Vec<u8>> {
+        let ParsedDiffSet::Changeset(set) = parsed else {
+            panic!("expected changeset");
+        };
+        set
+    }
+
+    #[test]
+    fn test_merge_consolidates_matching_schemas() {
+        let left = expect_changeset(users_changeset(&["id", "name"], &[0], 1));
+        let right = expect_changeset(users_changeset(&["id", "name"], &[0], 2));
+        let merged = left.merge(right).unwrap();
+        assert_eq!(merged.tables().count(), 1);
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_rejects_column_count_drift() {
+        let left = expect_changeset(users_changeset(&["id", "name"], &[0], 1));
+        let right = expect_changeset(users_changeset(&["id", "name", "age"], &[0], 2));
+        let err = left.merge(right).unwrap_err();
+        assert_eq!(
+            err,
+            SchemaMismatch::ColumnCount {
+                table: "users".into(),
+                left: 2,
+                right: 3,
+            }
+        );
+        assert!(alloc::string::ToString::to_string(&err).contains("Column count"));
+    }
+
+    #[test]
+    fn test_merge_rejects_pk_flag_drift() {
+        let left = expect_changeset(users_changeset(&["id", "name"], &[0], 1));
+        let right = expect_changeset(users_changeset(&["id", "name"], &[0, 1], 2));
+        let err = left.merge(right).unwrap_err();
+        assert_eq!(
+            err,
+            SchemaMismatch::PkFlags {
+                table: "users".into(),
+                left: vec![1, 0],
+                right: vec![1, 2],
+            }
+        );
+    }
+
+    type Builder = DiffSetBuilder<ChangesetFormat, TableSchema<String>, String, Vec<u8>>;
+
+    /// Builders over two `users` schemas whose column counts differ.
+    fn drifted_builders() -> (Builder, Builder) {
+        let left = expect_changeset(users_changeset(&["id", "name"], &[0], 1));
+        let right = expect_changeset(users_changeset(&["id", "name", "age"], &[0], 2));
+        (left.into(), right.into())
+    }
+
+    #[test]
+    fn test_try_extend_rejects_column_count_drift() {
+        let (left, right) = drifted_builders();
+        let mut acc = left.clone();
+        assert_eq!(
+            acc.try_extend(&right).unwrap_err(),
+            SchemaMismatch::ColumnCount {
+                table: "users".into(),
+                left: 2,
+                right: 3,
+            }
+        );
+        assert_eq!(acc, left);
+    }
+
+    #[test]
+    #[should_panic(expected = "Column count mismatch for table \"users\": 2 vs 3")]
+    fn test_bitor_panics_on_column_count_drift() {
+        let (left, right) = drifted_builders();
+        let _ = left | right;
+    }
+
+    #[test]
+    fn test_compatible_with_reports_name_first() {
+        let users = TableSchema::new(String::from("users"), 1, vec![1]);
+        let posts = TableSchema::new(String::from("posts"), 2, vec![1, 0]);
+        assert_eq!(
+            users.compatible_with(&posts),
+            Err(SchemaMismatch::Name {
+                left: "users".into(),
+                right: "posts".into(),
+            })
+        );
+        assert_eq!(users.compatible_with(&users.clone()), Ok(()));
+    }
+
+    #[test]
+    fn test_counts_by_kind_patchset() {
+        use crate::{DiffOps, Insert, PatchDelete, PatchSet, PatchsetFormat, Update};
+
+        let users = SimpleTable::new("users", &["id", "name"], &[0]);
+        let tags = SimpleTable::new("tags", &["id"], &[0]);
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+            .insert(Insert::from(users.clone()).set(0, 1i64).unwrap())
+            .update(
+                Update::<_, PatchsetFormat, _, _>::from(users.clone())
+                    .set(0, 2i64)
+                    .unwrap()
+                    .set(1, "Bob")
+                    .unwrap(),
+            )
+            .delete(PatchDelete::new(users, vec![Value::Integer(3)]))
+            .delete(PatchDelete::new(tags, vec![Value::Integer(1)]));
+
+        let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+        assert_eq!(parsed.counts_by_kind(), (1, 1, 2));
+        assert_eq!(parsed.operation_count(), 4);
+        assert_eq!(parsed.table_count(), 2);
+    }
+
+    #[test]
+    fn test_counts_on_empty_diffset() {
+        let parsed = ParsedDiffSet::parse(&[]).unwrap();
+        assert_eq!(parsed.counts_by_kind(), (0, 0, 0));
+        assert_eq!(parsed.operation_count(), 0);
+        assert_eq!(parsed.table_count(), 0);
+    }
+
+    #[test]
+    fn test_parse_prefix_clean_input_consumes_everything() {
+        let bytes = Vec::from(users_changeset(&["id", "name"], &[0], 1));
+        let (parsed, consumed) = ParsedDiffSet::parse_prefix(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(parsed, ParsedDiffSet::parse(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_parse_prefix_stops_before_trailing_garbage() {
+        let valid = Vec::from(users_changeset(&["id", "name"], &[0], 1));
+        let mut bytes = valid.clone();
+        bytes.extend_from_slice(&[0xff, 0x00, 0x42]);
+
+        assert!(ParsedDiffSet::parse(&bytes).is_err());
+        let (parsed, consumed) = ParsedDiffSet::parse_prefix(&bytes).unwrap();
+        assert_eq!(consumed, valid.len());
+        assert_eq!(parsed, ParsedDiffSet::parse(&valid).unwrap());
+    }
+
+    #[test]
+    fn test_parse_prefix_stops_before_other_format() {
+        use crate::{DiffOps, Insert, PatchSet};
+
+        let valid = Vec::from(users_changeset(&["id"], &[0], 1));
+        let users = SimpleTable::new("users", &["id"], &[0]);
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> =
+            PatchSet::new().insert(Insert::from(users).set(0, 2i64).unwrap());
+        let mut bytes = valid.clone();
+        bytes.extend(patchset.build());
+
+        let (parsed, consumed) = ParsedDiffSet::parse_prefix(&bytes).unwrap();
+        assert_eq!(consumed, valid.len());
+        assert!(parsed.is_changeset());
+        assert_eq!(parsed.operation_count(), 1);
+    }
+
+    #[test]
+    fn test_parse_prefix_rejects_invalid_first_header() {
+        assert_eq!(
+            ParsedDiffSet::parse_prefix(&[0x42, 0x01]),
+            Err(ParseError::NotAChangeset { first_byte: 0x42 })
+        );
+    }
+
+    #[test]
+    fn test_named_parsed_schema_renders_named_sql() {
+        use crate::{ChangeSet, DiffOps, Insert};
+
+        let parsed = expect_changeset(users_changeset(&["id", "age"], &[0], 7));
+        let schema = parsed
+            .tables()
+            .next()
+            .unwrap()
+            .clone()
+            .with_column_names(vec!["id".into(), "age".into()]);
+        assert_eq!(schema.column_index("age"), Some(1));
+
+        let mut named: ChangeSet<TableSchema<String>, String, Vec<u8>> = ChangeSet::new();
+        for op in parsed.iter() {
+            let crate::ChangesetOp::Insert { values, .. } = op else {
+                panic!("expected insert");
+            };
+            let mut insert = Insert::from(schema.clone());
+            for (col, value) in values.iter().enumerate() {
+                insert = insert.set(col, value.clone()).unwrap();
+            }
+            named = named.insert(insert);
+        }
+        let sql: Vec<String> = named.sql_statements().collect();
+        assert_eq!(sql, [r#"INSERT INTO "users" ("id", "age") VALUES (7, 7)"#]);
+    }
+
+    #[test]
+    fn test_named_and_unnamed_schemas_merge_into_one_section() {
+        use crate::{ChangeSet, DiffOps, Insert};
+
+        let unnamed = expect_changeset(users_changeset(&["id", "age"], &[0], 7));
+        let schema = unnamed.tables().next().unwrap().clone();
+        let named_schema = schema
+            .clone()
+            .with_column_names(vec!["id".into(), "age".into()]);
+        assert_eq!(named_schema, schema);
+
+        let named: ChangeSet<TableSchema<String>, String, Vec<u8>> = ChangeSet::new().insert(
+            Insert::from(named_schema)
+                .set(0, 8i64)
+                .unwrap()
+                .set(1, 8i64)
+                .unwrap(),
+        );
+        let merged = unnamed.merge(named.into()).unwrap();
+        assert_eq!(merged.tables().count(), 1);
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn test_unnamed_parsed_schema_resolves_no_columns() {
+        let schema: TableSchema<String> = TableSchema::new("users".into(), 2, vec![1, 0]);
+        assert_eq!(schema.column_index("id"), None);
+        assert_eq!(schema.column_name(0), None);
+    }
 }
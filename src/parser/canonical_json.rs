@@ -0,0 +1,379 @@
+//! Canonical JSON rendering of a [`ParsedDiffSet`], for snapshot tests.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Write;
+
+use super::{ParsedDiffSet, TableSchema};
+use crate::SimpleTable;
+use crate::builders::{ChangesetFormat, DiffSet, Format, Operation, PatchsetFormat};
+use crate::encoding::hex::{HEX_LOWER, write_hex};
+use crate::encoding::{MaybeValue, Value, compare_values};
+use crate::schema::DynTable;
+
+/// Named groups of `(column, value)` pairs.
+type Groups<'a> = Vec<(&'static str, Vec<(usize, &'a Value<String, Vec<u8>>)>)>;
+
+/// One rendered operation: its key, its kind, and its named groups of
+/// `(column, value)` pairs.
+struct Row<'a> {
+    pk: &'a [Value<String, Vec<u8>>],
+    op: &'static str,
+    indirect: bool,
+    groups: Groups<'a>,
+}
+
+impl ParsedDiffSet {
+    /// Renders the diffset as pretty, deterministic JSON, for snapshot tests.
+    ///
+    /// Tables are sorted by name and their rows by primary key, under
+    /// [`compare_values`], so the output depends on the operations alone and
+    /// not on the order their bytes were stored in. Columns are named after
+    /// the [`SimpleTable`] in `schemas` with the table's name and column
+    /// count, then after the names the diffset carries, and by index
+    /// otherwise.
+    ///
+    /// Each operation lists its kind and indirect flag, then its values by
+    /// column name: `values` for an INSERT and a changeset DELETE, `key` for
+    /// a patchset DELETE, and `old` and `new` for an UPDATE. Undefined
+    /// values are left out, so they stay distinct from NULL, which renders
+    /// as `null`. Text renders as a string, integers and finite reals as
+    /// numbers with reals always carrying a fraction or exponent, and blobs
+    /// and non-finite reals as `{"blob": "<hex>"}` and `{"real": "inf"}`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParsedDiffSet, SimpleTable};
+    ///
+    /// let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    /// let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+    ///     .insert(Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, "alice").unwrap());
+    /// let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     parsed.to_canonical_json(&[users]),
+    ///     r#"{
+    ///   "format": "changeset",
+    ///   "tables": [
+    ///     {
+    ///       "name": "users",
+    ///       "columns": ["id", "name"],
+    ///       "operations": [
+    ///         {"op": "INSERT", "indirect": false, "values": {"id": 1, "name": "alice"}}
+    ///       ]
+    ///     }
+    ///   ]
+    /// }
+    /// "#
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_canonical_json(&self, schemas: &[SimpleTable]) -> String {
+        let (format, tables) = match self {
+            ParsedDiffSet::Changeset(d) => ("changeset", sorted_tables(d, changeset_row)),
+            ParsedDiffSet::Patchset(d) => ("patchset", sorted_tables(d, patchset_row)),
+        };
+
+        let mut out = format!("{{\n  \"format\": \"{format}\",\n  \"tables\": [");
+        for (i, (table, rows)) in tables.iter().enumerate() {
+            let columns = column_names(table, schemas);
+            out.push_str(if i == 0 { "\n" } else { ",\n" });
+            out.push_str("    {\n      \"name\": ");
+            write_string(&mut out, table.name());
+            out.push_str(",\n      \"columns\": [");
+            for (col, name) in columns.iter().enumerate() {
+                if col > 0 {
+                    out.push_str(", ");
+                }
+                write_string(&mut out, name);
+            }
+            out.push_str("],\n      \"operations\": [");
+            for (j, row) in rows.iter().enumerate() {
+                out.push_str(if j == 0 { "\n" } else { ",\n" });
+                let _ = write!(
+                    out,
+                    "        {{\"op\": \"{}\", \"indirect\": {}",
+                    row.op, row.indirect
+                );
+                for (group, values) in &row.groups {
+                    let _ = write!(out, ", \"{group}\": {{");
+                    for (k, (col, value)) in values.iter().enumerate() {
+                        if k > 0 {
+                            out.push_str(", ");
+                        }
+                        write_string(&mut out, &columns[*col]);
+                        out.push_str(": ");
+                        write_value(&mut out, value);
+                    }
+                    out.push('}');
+                }
+                out.push('}');
+            }
+            out.push_str("\n      ]\n    }");
+        }
+        out.push_str(if tables.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        });
+        out
+    }
+}
+
+/// Returns the tables of `diffset` holding operations, sorted by name, with
+/// their rows rendered by `render` and sorted by primary key.
+fn sorted_tables<'a, F: Format<String, Vec<u8>>>(
+    diffset: &'a DiffSet<F, TableSchema<String>, String, Vec<u8>>,
+    render: impl Fn(
+        &TableSchema<String>,
+        &'a [Value<String, Vec<u8>>],
+        &'a Operation<F, String, Vec<u8>>,
+    ) -> Row<'a>,
+) -> Vec<(&'a TableSchema<String>, Vec<Row<'a>>)> {
+    let mut tables: Vec<_> = diffset
+        .tables
+        .iter()
+        .filter(|(_, rows)| !rows.is_empty())
+        .map(|(table, rows)| {
+            let mut rows: Vec<Row<'a>> =
+                rows.iter().map(|(pk, op)| render(table, pk, op)).collect();
+            rows.sort_by(|a, b| compare_keys(a.pk, b.pk));
+            (table, rows)
+        })
+        .collect();
+    tables.sort_by(|(a, _), (b, _)| a.name().cmp(b.name()));
+    tables
+}
+
+fn compare_keys(a: &[Value<String, Vec<u8>>], b: &[Value<String, Vec<u8>>]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| compare_values(a, b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+fn changeset_row<'a>(
+    _: &TableSchema<String>,
+    pk: &'a [Value<String, Vec<u8>>],
+    op: &'a Operation<ChangesetFormat, String, Vec<u8>>,
+) -> Row<'a> {
+    let (op, indirect, groups) = match op {
+        Operation::Insert { values, indirect } => ("INSERT", *indirect, vec_of("values", values)),
+        Operation::Delete { data, indirect } => ("DELETE", *indirect, vec_of("values", data)),
+        Operation::Update { values, indirect } => (
+            "UPDATE",
+            *indirect,
+            alloc::vec![
+                ("old", defined(values.iter().map(|(old, _)| old))),
+                ("new", defined(values.iter().map(|(_, new)| new))),
+            ],
+        ),
+    };
+    Row {
+        pk,
+        op,
+        indirect,
+        groups,
+    }
+}
+
+fn patchset_row<'a>(
+    table: &TableSchema<String>,
+    pk: &'a [Value<String, Vec<u8>>],
+    op: &'a Operation<PatchsetFormat, String, Vec<u8>>,
+) -> Row<'a> {
+    let (op, indirect, groups) = match op {
+        Operation::Insert { values, indirect } => ("INSERT", *indirect, vec_of("values", values)),
+        Operation::Delete { indirect, .. } => (
+            "DELETE",
+            *indirect,
+            alloc::vec![("key", table.pk_indices().into_iter().zip(pk).collect())],
+        ),
+        Operation::Update { values, indirect } => (
+            "UPDATE",
+            *indirect,
+            alloc::vec![("new", defined(values.iter().map(|((), new)| new)))],
+        ),
+    };
+    Row {
+        pk,
+        op,
+        indirect,
+        groups,
+    }
+}
+
+fn vec_of<'a>(group: &'static str, values: &'a [Value<String, Vec<u8>>]) -> Groups<'a> {
+    alloc::vec![(group, values.iter().enumerate().collect())]
+}
+
+/// Pairs the defined values of `values` with their column.
+fn defined<'a>(
+    values: impl Iterator<Item = &'a MaybeValue<String, Vec<u8>>>,
+) -> Vec<(usize, &'a Value<String, Vec<u8>>)> {
+    values
+        .enumerate()
+        .filter_map(|(col, value)| Some((col, value.as_ref()?)))
+        .collect()
+}
+
+/// Returns one name per column of `table`.
+fn column_names(table: &TableSchema<String>, schemas: &[SimpleTable]) -> Vec<String> {
+    let count = table.number_of_columns();
+    if let Some(schema) = schemas
+        .iter()
+        .find(|schema| schema.name() == table.name() && schema.number_of_columns() == count)
+    {
+        return schema.column_names().to_vec();
+    }
+    (0..count)
+        .map(|col| {
+            table
+                .column_name(col)
+                .map_or_else(|| format!("{col}"), String::from)
+        })
+        .collect()
+}
+
+fn write_value(out: &mut String, value: &Value<String, Vec<u8>>) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Integer(i) => {
+            let _ = write!(out, "{i}");
+        }
+        Value::Real(r) if r.is_finite() => {
+            // `Debug` keeps a fraction or exponent, so `1.0` stays a real.
+            let _ = write!(out, "{r:?}");
+        }
+        Value::Real(r) => {
+            let _ = write!(out, "{{\"real\": \"{r}\"}}");
+        }
+        Value::Text(text) => write_string(out, text),
+        Value::Blob(bytes) => {
+            out.push_str("{\"blob\": \"");
+            let _ = write_hex(out, bytes, HEX_LOWER);
+            out.push_str("\"}");
+        }
+    }
+}
+
+fn write_string(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChangeDelete, ChangeSet, DiffOps, Insert, PatchDelete, PatchSet, Update};
+
+    fn users() -> SimpleTable {
+        SimpleTable::new("users", &["id", "name"], &[0])
+    }
+
+    fn files() -> SimpleTable {
+        SimpleTable::new("files", &["path", "data", "size"], &[0])
+    }
+
+    fn changeset() -> ChangeSet<SimpleTable, String, Vec<u8>> {
+        let mut changeset = ChangeSet::new();
+        for id in (0..40i64).rev() {
+            changeset = changeset.insert(
+                Insert::from(users())
+                    .set(0, id)
+                    .unwrap()
+                    .set(1, format!("user \"{id}\"\n"))
+                    .unwrap(),
+            );
+        }
+        changeset
+            .insert(
+                Insert::from(files())
+                    .set(0, "a.bin")
+                    .unwrap()
+                    .set(1, alloc::vec![0xde_u8, 0xad])
+                    .unwrap()
+                    .set(2, 2.0)
+                    .unwrap(),
+            )
+            .update(
+                Update::<_, ChangesetFormat, _, _>::from(files())
+                    .set(0, "b.bin", "b.bin")
+                    .unwrap()
+                    .set(2, 1.5, f64::INFINITY)
+                    .unwrap(),
+            )
+            .delete(
+                ChangeDelete::from(files())
+                    .set(0, "c.bin")
+                    .unwrap()
+                    .set_null(1)
+                    .unwrap()
+                    .set(2, 0i64)
+                    .unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_canonical_json_is_stable_and_sorted() {
+        let changeset = changeset();
+        let schemas = [users(), files()];
+        let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+        let json = parsed.to_canonical_json(&schemas);
+        assert_eq!(json, parsed.to_canonical_json(&schemas));
+
+        // Another table and row order on the wire renders the same.
+        let reordered = ParsedDiffSet::parse(&changeset.build_sqlite_table_order()).unwrap();
+        assert_eq!(reordered.to_canonical_json(&schemas), json);
+
+        let files_at = json.find("\"name\": \"files\"").unwrap();
+        assert!(files_at < json.find("\"name\": \"users\"").unwrap());
+        let ids: Vec<usize> = (0..40)
+            .map(|id| json.find(&format!("{{\"id\": {id},")).unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(json.contains(r#""name": "user \"7\"\n""#));
+        assert!(json.contains(
+            r#"{"op": "INSERT", "indirect": false, "values": {"path": "a.bin", "data": {"blob": "dead"}, "size": 2.0}}"#
+        ));
+        assert!(json.contains(
+            r#""old": {"path": "b.bin", "size": 1.5}, "new": {"path": "b.bin", "size": {"real": "inf"}}"#
+        ));
+        assert!(json.contains(r#""values": {"path": "c.bin", "data": null, "size": 0}"#));
+    }
+
+    #[test]
+    fn test_canonical_json_of_patchset_names_columns_without_schemas() {
+        let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().delete(
+            PatchDelete::new(files(), alloc::vec![Value::Text("a.bin".into())]),
+        );
+        let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+        let json = parsed.to_canonical_json(&[]);
+        assert!(json.starts_with("{\n  \"format\": \"patchset\""));
+        assert!(json.contains(r#""columns": ["0", "1", "2"]"#));
+        assert!(json.contains(r#"{"op": "DELETE", "indirect": false, "key": {"0": "a.bin"}}"#));
+
+        let empty = ParsedDiffSet::parse(&[]).unwrap();
+        assert_eq!(
+            empty.to_canonical_json(&[]),
+            "{\n  \"format\": \"changeset\",\n  \"tables\": []\n}\n"
+        );
+    }
+}
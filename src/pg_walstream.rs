@@ -13,12 +13,10 @@ pub use pg_walstream::Oid;
 pub use pg_walstream::{ChangeEvent, ColumnValue, EventType, Lsn, ReplicaIdentity, RowData};
 
 use crate::ChangesetFormat;
-use crate::builders::{
-    ChangeDelete, DiffOps, DiffSetBuilder, Insert, PatchDelete, PatchsetFormat, Update,
-};
+use crate::builders::{ChangeDelete, Insert, PatchDelete, PatchsetFormat, Update};
 use crate::encoding::Value;
 use crate::schema::NamedColumns;
-use crate::wire::{Sealed, WireAdapter, WireSource, WireType};
+use crate::wire::{ColumnPlacer, PlacementError, Sealed, WireAdapter, WireSource, WireType};
 use core::fmt::Debug;
 use core::hash::Hash;
 
@@ -29,6 +27,11 @@ pub enum ConversionError {
     #[error("Column '{0}' not found in table schema")]
     ColumnNotFound(String),
 
+    /// The event sets a column twice, under one name or two that resolve to
+    /// the same column.
+    #[error("Column '{0}' set more than once")]
+    DuplicateColumn(String),
+
     /// The table name in the event doesn't match the expected schema.
     #[error("Table name mismatch: expected '{expected}', got '{actual}'")]
     TableMismatch {
@@ -63,6 +66,15 @@ pub enum ConversionError {
     Decode(#[from] crate::wire::DecodeError),
 }
 
+impl From<PlacementError<'_>> for ConversionError {
+    fn from(error: PlacementError<'_>) -> Self {
+        match error {
+            PlacementError::NotFound(name) => Self::ColumnNotFound(name.into()),
+            PlacementError::Duplicate(name) => Self::DuplicateColumn(name.into()),
+        }
+    }
+}
+
 /// Marker type for the `pg_walstream` source. Passed as the `Src`
 /// generic parameter to `TypeMap`, `WireAdapter`, and `Decoder`.
 #[derive(Debug, Clone, Copy, Default)]
@@ -117,7 +129,7 @@ impl PgWalstreamColumn<'_> {
 
 // Schema-aware digest impls.
 
-use crate::wire::{Digestable, WireColumnTypes, WireSchema};
+use crate::wire::{Digestable, MaybeOperation, WireColumnTypes, WireOperation, WireSchema};
 
 impl<T, S, B> Digestable<ChangesetFormat, T, S, B> for EventType
 where
@@ -128,12 +140,11 @@ where
     type Src = PgWalstream;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<ChangesetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<ChangesetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<ChangesetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<PgWalstream, S, B>,
@@ -144,7 +155,7 @@ where
             } => {
                 let table = resolve_table(schema, name.as_ref())?;
                 let insert = build_insert_from_pg(data, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             EventType::Update {
                 table: name,
@@ -155,7 +166,7 @@ where
                 let table = resolve_table(schema, name.as_ref())?;
                 let update =
                     build_changeset_update_from_pg(old_data.as_ref(), new_data, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             EventType::Delete {
                 table: name,
@@ -164,9 +175,9 @@ where
             } => {
                 let table = resolve_table(schema, name.as_ref())?;
                 let delete = build_changeset_delete_from_pg(old_data, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            _ => Ok(builder),
+            _ => Ok(None),
         }
     }
 }
@@ -180,12 +191,11 @@ where
     type Src = PgWalstream;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<PatchsetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<PatchsetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<PatchsetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<PgWalstream, S, B>,
@@ -196,7 +206,7 @@ where
             } => {
                 let table = resolve_table(schema, name.as_ref())?;
                 let insert = build_insert_from_pg(data, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             EventType::Update {
                 table: name,
@@ -205,7 +215,7 @@ where
             } => {
                 let table = resolve_table(schema, name.as_ref())?;
                 let update = build_patchset_update_from_pg(new_data, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             EventType::Delete {
                 table: name,
@@ -214,9 +224,9 @@ where
             } => {
                 let table = resolve_table(schema, name.as_ref())?;
                 let delete = build_patch_delete_from_pg(old_data, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            _ => Ok(builder),
+            _ => Ok(None),
         }
     }
 }
@@ -242,10 +252,9 @@ where
     A: WireAdapter<PgWalstream, S, B>,
 {
     let mut insert = Insert::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in data.iter() {
-        let col_idx = table
-            .column_index(name.as_ref())
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.as_ref().into()))?;
+        let col_idx = placer.place(table, name.as_ref())?;
         let payload = PgWalstreamColumn {
             column_name: name.as_ref(),
             wire_type: table.column_type(col_idx),
@@ -272,10 +281,9 @@ where
     A: WireAdapter<PgWalstream, S, B>,
 {
     let mut update: Update<T, ChangesetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, new_value) in new_data.iter() {
-        let col_idx = table
-            .column_index(name.as_ref())
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.as_ref().into()))?;
+        let col_idx = placer.place(table, name.as_ref())?;
         let wire_type = table.column_type(col_idx);
         let new_payload = PgWalstreamColumn {
             column_name: name.as_ref(),
@@ -328,10 +336,9 @@ where
     A: WireAdapter<PgWalstream, S, B>,
 {
     let mut update: Update<T, PatchsetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in new_data.iter() {
-        let col_idx = table
-            .column_index(name.as_ref())
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.as_ref().into()))?;
+        let col_idx = placer.place(table, name.as_ref())?;
         let payload = PgWalstreamColumn {
             column_name: name.as_ref(),
             wire_type: table.column_type(col_idx),
@@ -357,10 +364,9 @@ where
     A: WireAdapter<PgWalstream, S, B>,
 {
     let mut delete = ChangeDelete::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in old_data.iter() {
-        let col_idx = table
-            .column_index(name.as_ref())
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.as_ref().into()))?;
+        let col_idx = placer.place(table, name.as_ref())?;
         let payload = PgWalstreamColumn {
             column_name: name.as_ref(),
             wire_type: table.column_type(col_idx),
@@ -388,10 +394,9 @@ where
     let num_pks = table.number_of_primary_keys();
     let mut pk_slots: Vec<Option<Value<S, B>>> = alloc::vec![None; num_pks];
 
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in old_data.iter() {
-        let col_idx = table
-            .column_index(name.as_ref())
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.as_ref().into()))?;
+        let col_idx = placer.place(table, name.as_ref())?;
         if let Some(pk_idx) = table.primary_key_index(col_idx) {
             let payload = PgWalstreamColumn {
                 column_name: name.as_ref(),
@@ -24,6 +24,7 @@ pub use pg_walstream::{ColumnData, ColumnInfo, LogicalReplicationMessage, Oid, T
 
 use crate::builders::ChangesetOp;
 use crate::encoding::Value;
+use crate::encoding::hex::{HEX_LOWER, write_hex};
 
 /// Postgres `BOOL` type OID. Routes [`Value::Integer`] through `t`/`f` text.
 pub const PG_BOOL: Oid = 16;
@@ -94,8 +95,6 @@ pub struct RelationSchema<'a> {
 /// column ordinal, must match `schema.columns.len()` when set.
 pub type UpdateFallback<'a> = Option<&'a [Value<String, Vec<u8>>]>;
 
-const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
-
 /// Map one [`Value`] to a [`ColumnData`] using the destination OID (see
 /// module docs for the full table).
 #[must_use]
@@ -125,10 +124,7 @@ where
             if target_oid == PG_BYTEA {
                 let mut out = String::with_capacity(2 + bytes.len() * 2);
                 out.push_str("\\x");
-                for byte in bytes {
-                    out.push(HEX_LOWER[(byte >> 4) as usize] as char);
-                    out.push(HEX_LOWER[(byte & 0x0f) as usize] as char);
-                }
+                write_hex(&mut out, bytes, HEX_LOWER).expect("writing to a String cannot fail");
                 ColumnData::text(out.into_bytes())
             } else {
                 ColumnData::binary(bytes.to_vec())
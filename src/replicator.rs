@@ -0,0 +1,314 @@
+//! Keeping replicas of a set of tables in sync by exchanging patchsets.
+//!
+//! A [`Replicator`] holds a local copy of the rows of some tables. The
+//! application stages its own changes on it, which updates the local rows
+//! and records the changes in a pending patchset; [`take_patchset`] hands
+//! that patchset over for sending. Patchsets received from other replicas
+//! are applied with [`apply_patchset`], which skips and reports every
+//! operation that conflicts with the local rows instead of aborting, as
+//! `SQLITE_CHANGESET_OMIT` would.
+//!
+//! Rows live in memory and no `SQLite` database is involved: inbound
+//! operations are applied by primary key, in the order the patchset stores
+//! them.
+//!
+//! [`ParsedDiffSet::check_conflicts`] reports, without applying anything,
+//! which operations of a changeset or patchset would conflict with the rows
+//! of any [`RowStore`], such as a replica.
+//!
+//! # Example
+//!
+//! ```
+//! use sqlite_diff_rs::replicator::Replicator;
+//! use sqlite_diff_rs::{Insert, SimpleTable, Value};
+//!
+//! let users = SimpleTable::new("users", &["id", "name"], &[0]);
+//! let alice = Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, "alice").unwrap();
+//!
+//! let mut local: Replicator<SimpleTable> = Replicator::new();
+//! let mut remote: Replicator<SimpleTable> = Replicator::new();
+//! local.insert(alice).unwrap();
+//!
+//! let conflicts = remote.apply_patchset(&local.take_patchset()).unwrap();
+//! assert!(conflicts.is_empty());
+//! assert_eq!(
+//!     remote.get("users", &[Value::Integer(1)]),
+//!     Some(&[Value::Integer(1), Value::Text("alice".into())][..])
+//! );
+//! ```
+//!
+//! [`take_patchset`]: Replicator::take_patchset
+//! [`apply_patchset`]: Replicator::apply_patchset
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use indexmap::IndexMap as IndexMapRaw;
+
+use crate::builders::{DiffOps, PatchDelete, PatchSet, PatchsetFormat};
+use crate::encoding::{MaybeValue, Value};
+use crate::parser::{ParseError, ParsedDiffSet};
+use crate::{Insert, PatchsetOp, SchemaWithPK, Update};
+
+/// `IndexMap` alias using hashbrown's default hasher for `no_std` compatibility.
+type IndexMap<K, V> = IndexMapRaw<K, V, hashbrown::DefaultHashBuilder>;
+
+/// Values of one row, or of its primary key.
+type Row = Vec<Value<String, Vec<u8>>>;
+
+/// Errors that can occur while applying an inbound patchset.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReplicatorError {
+    /// The inbound bytes are not a valid changeset or patchset.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    /// The inbound bytes are a changeset rather than a patchset.
+    #[error("Expected a patchset, got a changeset")]
+    NotPatchset,
+}
+
+/// How an operation conflicts with the local rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// An INSERT targets a primary key that is already present.
+    Exists,
+    /// An UPDATE or DELETE targets a primary key that is absent.
+    Missing,
+    /// A changeset UPDATE or DELETE expects old values that the present
+    /// row does not hold.
+    Data,
+    /// An UPDATE carries a different number of columns than the present
+    /// row, so it was written against another version of the table.
+    ColumnCount,
+}
+
+/// An operation that was not applied because it conflicts with the local
+/// rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Name of the table the operation targets.
+    pub table: String,
+    /// Primary-key values of the targeted row, in key order.
+    pub pk: Vec<Value<String, Vec<u8>>>,
+    /// How the operation conflicts.
+    pub kind: ConflictKind,
+}
+
+/// Read access to present rows, keyed by table name and then by primary key.
+///
+/// [`ParsedDiffSet::check_conflicts`] checks operations against a store
+/// without mutating it.
+pub trait RowStore {
+    /// Returns the row keyed on `pk` in the table named `table`, if present.
+    ///
+    /// `pk` holds the primary-key values in key order, as
+    /// [`SchemaWithPK::extract_pk`] returns them.
+    fn row(&self, table: &str, pk: &[Value<String, Vec<u8>>]) -> Option<&[Value<String, Vec<u8>>]>;
+}
+
+/// A local replica of some tables, exchanging changes as patchsets.
+///
+/// See the [module documentation](self) for the model.
+#[derive(Debug, Clone)]
+pub struct Replicator<T: SchemaWithPK> {
+    /// Present rows keyed by table name, then by primary key.
+    rows: IndexMap<String, IndexMap<Row, Row>>,
+    /// Local changes not yet taken for sending.
+    pending: PatchSet<T, String, Vec<u8>>,
+}
+
+impl<T: SchemaWithPK> Default for Replicator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: SchemaWithPK> Replicator<T> {
+    /// Create a replica holding no rows.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            rows: IndexMap::default(),
+            pending: PatchSet::new(),
+        }
+    }
+
+    /// Returns the row keyed on `pk` in the table named `table`, if present.
+    ///
+    /// `pk` holds the primary-key values in key order, as
+    /// [`SchemaWithPK::extract_pk`] returns them.
+    #[must_use]
+    pub fn get(
+        &self,
+        table: &str,
+        pk: &[Value<String, Vec<u8>>],
+    ) -> Option<&[Value<String, Vec<u8>>]> {
+        self.rows.get(table)?.get(pk).map(Vec::as_slice)
+    }
+
+    /// Returns the present rows of the table named `table`, in the order
+    /// they were first inserted.
+    pub fn rows(&self, table: &str) -> impl Iterator<Item = &[Value<String, Vec<u8>>]> {
+        self.rows
+            .get(table)
+            .into_iter()
+            .flat_map(|rows| rows.values().map(Vec::as_slice))
+    }
+
+    /// Returns true if no local change is waiting to be taken.
+    #[must_use]
+    pub fn is_synced(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Insert a row locally and stage the INSERT for sending.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConflictKind::Exists`] conflict, staging nothing, if the
+    /// row's primary key is already present.
+    pub fn insert(&mut self, insert: Insert<T, String, Vec<u8>>) -> Result<(), Conflict> {
+        let table = insert.as_ref().name().to_string();
+        let pk = insert.extract_pk();
+        self.apply_insert(&table, pk, insert.clone().into_values())?;
+        self.stage(|pending| pending.insert(insert));
+        Ok(())
+    }
+
+    /// Update a row locally and stage the UPDATE for sending.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConflictKind::Missing`] conflict, staging nothing, if no
+    /// row has the update's primary key, or a [`ConflictKind::ColumnCount`]
+    /// conflict if the update and the row have different column counts.
+    pub fn update(
+        &mut self,
+        update: Update<T, PatchsetFormat, String, Vec<u8>>,
+    ) -> Result<(), Conflict> {
+        let table = update.as_ref().name().to_string();
+        let pk = update.extract_pk();
+        self.apply_update(&table, pk, update.values())?;
+        self.stage(|pending| pending.update(update));
+        Ok(())
+    }
+
+    /// Delete a row locally and stage the DELETE for sending.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConflictKind::Missing`] conflict, staging nothing, if no
+    /// row has the delete's primary key.
+    pub fn delete(&mut self, delete: PatchDelete<T, String, Vec<u8>>) -> Result<(), Conflict> {
+        let table = delete.as_ref().name().to_string();
+        self.apply_delete(&table, delete.pk.clone())?;
+        self.stage(|pending| pending.delete(delete));
+        Ok(())
+    }
+
+    /// Returns the patchset of the local changes staged since the last call,
+    /// consolidated per row, and starts a new one.
+    ///
+    /// Returns an empty buffer if no change is pending.
+    pub fn take_patchset(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.pending).build()
+    }
+
+    /// Apply a patchset received from another replica to the local rows.
+    ///
+    /// Operations are applied in the order the patchset stores them. One
+    /// that conflicts with the local rows is skipped and reported, and the
+    /// others still apply. Inbound changes are not staged for sending.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReplicatorError`], leaving the rows untouched, if
+    /// `patchset` does not parse or is a changeset.
+    pub fn apply_patchset(&mut self, patchset: &[u8]) -> Result<Vec<Conflict>, ReplicatorError> {
+        let ParsedDiffSet::Patchset(patchset) = ParsedDiffSet::parse(patchset)? else {
+            return Err(ReplicatorError::NotPatchset);
+        };
+
+        let mut conflicts = Vec::new();
+        for op in patchset.iter() {
+            let table = op.table().name().clone();
+            let result = match op {
+                PatchsetOp::Insert {
+                    table: schema,
+                    values,
+                    ..
+                } => self.apply_insert(&table, schema.extract_pk(&values), values.to_vec()),
+                PatchsetOp::Update { pk, entries, .. } => {
+                    self.apply_update(&table, pk.to_vec(), entries)
+                }
+                PatchsetOp::Delete { pk, .. } => self.apply_delete(&table, pk.to_vec()),
+            };
+            if let Err(conflict) = result {
+                conflicts.push(conflict);
+            }
+        }
+        Ok(conflicts)
+    }
+
+    fn stage(
+        &mut self,
+        op: impl FnOnce(PatchSet<T, String, Vec<u8>>) -> PatchSet<T, String, Vec<u8>>,
+    ) {
+        let pending = core::mem::take(&mut self.pending);
+        self.pending = op(pending);
+    }
+
+    fn apply_insert(&mut self, table: &str, pk: Row, values: Row) -> Result<(), Conflict> {
+        let rows = self.rows.entry(table.to_string()).or_default();
+        if rows.contains_key(&pk) {
+            return Err(conflict(table, pk, ConflictKind::Exists));
+        }
+        rows.insert(pk, values);
+        Ok(())
+    }
+
+    fn apply_update(
+        &mut self,
+        table: &str,
+        pk: Row,
+        values: &[((), MaybeValue<String, Vec<u8>>)],
+    ) -> Result<(), Conflict> {
+        let Some(row) = self.rows.get_mut(table).and_then(|rows| rows.get_mut(&pk)) else {
+            return Err(conflict(table, pk, ConflictKind::Missing));
+        };
+        if values.len() != row.len() {
+            return Err(conflict(table, pk, ConflictKind::ColumnCount));
+        }
+        for (slot, ((), new)) in row.iter_mut().zip(values) {
+            if let Some(new) = new {
+                slot.clone_from(new);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_delete(&mut self, table: &str, pk: Row) -> Result<(), Conflict> {
+        let removed = self
+            .rows
+            .get_mut(table)
+            .and_then(|rows| rows.shift_remove(&pk));
+        match removed {
+            Some(_) => Ok(()),
+            None => Err(conflict(table, pk, ConflictKind::Missing)),
+        }
+    }
+}
+
+impl<T: SchemaWithPK> RowStore for Replicator<T> {
+    fn row(&self, table: &str, pk: &[Value<String, Vec<u8>>]) -> Option<&[Value<String, Vec<u8>>]> {
+        self.get(table, pk)
+    }
+}
+
+fn conflict(table: &str, pk: Row, kind: ConflictKind) -> Conflict {
+    Conflict {
+        table: table.to_string(),
+        pk,
+        kind,
+    }
+}
@@ -4,4 +4,4 @@ mod simple_table;
 
 pub use dyn_table::IndexableValues;
 pub use dyn_table::{DynTable, SchemaWithPK};
-pub use simple_table::{NamedColumns, SimpleTable};
+pub use simple_table::{NamedColumns, SimpleTable, SimpleTableError};
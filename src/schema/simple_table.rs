@@ -14,6 +14,40 @@ use crate::{encoding::Value, schema::dyn_table::IndexableValues};
 
 use super::{DynTable, SchemaWithPK};
 
+/// Errors reported by [`SimpleTable::try_new`] for an invalid table definition.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SimpleTableError {
+    /// Two columns share the same name, so the later one could never be
+    /// resolved by name.
+    #[error("Duplicate column name {name:?} at positions {first} and {second}")]
+    DuplicateColumn {
+        /// The repeated column name.
+        name: String,
+        /// Position of the first occurrence.
+        first: usize,
+        /// Position of the repeated occurrence.
+        second: usize,
+    },
+
+    /// A primary-key index does not name a column.
+    #[error("Primary key index {0} out of bounds for table with {1} columns")]
+    PrimaryKeyIndexOutOfBounds(usize, usize),
+
+    /// A column is listed more than once in the primary key.
+    #[error("Column {0} listed more than once in the primary key")]
+    DuplicatePrimaryKeyIndex(usize),
+
+    /// The primary key has more columns than the format can encode (255).
+    #[error("Primary key has {0} columns, at most 255 are supported")]
+    TooManyPrimaryKeyColumns(usize),
+
+    /// A deserialized table's schema disagrees with its column names on
+    /// the column count, or holds primary-key flags [`SimpleTable::try_new`]
+    /// would not produce.
+    #[error("Table schema does not match its column names")]
+    InconsistentSchema,
+}
+
 /// A simple table schema with column names for SQL generation.
 ///
 /// This type wraps [`TableSchema`] and adds column names, allowing it to be
@@ -31,6 +65,11 @@ use super::{DynTable, SchemaWithPK};
 /// patchset.digest_sql("INSERT INTO users (id, name) VALUES (1, 'Alice')").unwrap();
 /// ```
 #[derive(Debug, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "SimpleTableFields")
+)]
 pub struct SimpleTable {
     /// The underlying table schema (for binary encoding).
     schema: TableSchema<String>,
@@ -49,9 +88,56 @@ impl SimpleTable {
     ///
     /// # Panics
     ///
-    /// Panics if any `pk_indices` value is out of bounds.
+    /// Panics if the definition is rejected by [`try_new`](Self::try_new).
     #[must_use]
     pub fn new(name: impl Into<String>, columns: &[&str], pk_indices: &[usize]) -> Self {
+        Self::try_new(name, columns, pk_indices).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Create a new simple table schema, validating the definition.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - the table name.
+    /// * `columns` - column names in order.
+    /// * `pk_indices` - indices of primary key columns (in PK order).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SimpleTableError`] if two columns share a name, or if a
+    /// primary-key index is out of bounds, repeated, or there are more than
+    /// 255 of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sqlite_diff_rs::{SimpleTable, SimpleTableError};
+    ///
+    /// assert!(SimpleTable::try_new("users", &["id", "name"], &[0]).is_ok());
+    /// assert_eq!(
+    ///     SimpleTable::try_new("users", &["id", "id"], &[0]),
+    ///     Err(SimpleTableError::DuplicateColumn {
+    ///         name: "id".into(),
+    ///         first: 0,
+    ///         second: 1,
+    ///     })
+    /// );
+    /// ```
+    pub fn try_new(
+        name: impl Into<String>,
+        columns: &[&str],
+        pk_indices: &[usize],
+    ) -> Result<Self, SimpleTableError> {
+        for (second, &column) in columns.iter().enumerate() {
+            if let Some(first) = columns[..second].iter().position(|&c| c == column) {
+                return Err(SimpleTableError::DuplicateColumn {
+                    name: column.into(),
+                    first,
+                    second,
+                });
+            }
+        }
+
         let name = name.into();
         let columns: Vec<String> = columns.iter().map(|&c| String::from(c)).collect();
         let column_count = columns.len();
@@ -59,14 +145,24 @@ impl SimpleTable {
         // Convert pk_indices to pk_flags
         let mut pk_flags = vec![0u8; column_count];
         for (pk_ordinal, &col_idx) in pk_indices.iter().enumerate() {
-            assert!(col_idx < column_count, "PK index out of bounds");
-            pk_flags[col_idx] = u8::try_from(pk_ordinal + 1).expect("Too many PK columns");
+            let flag =
+                pk_flags
+                    .get_mut(col_idx)
+                    .ok_or(SimpleTableError::PrimaryKeyIndexOutOfBounds(
+                        col_idx,
+                        column_count,
+                    ))?;
+            if *flag != 0 {
+                return Err(SimpleTableError::DuplicatePrimaryKeyIndex(col_idx));
+            }
+            *flag = u8::try_from(pk_ordinal + 1)
+                .map_err(|_| SimpleTableError::TooManyPrimaryKeyColumns(pk_indices.len()))?;
         }
 
-        Self {
+        Ok(Self {
             schema: TableSchema::new(name, column_count, pk_flags),
             columns,
-        }
+        })
     }
 
     /// Get the column names.
@@ -100,6 +196,42 @@ impl SimpleTable {
     }
 }
 
+/// Fields of a deserialized [`SimpleTable`], checked by
+/// [`SimpleTable::try_new`] before they are accepted.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SimpleTableFields {
+    schema: TableSchema<String>,
+    columns: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SimpleTableFields> for SimpleTable {
+    type Error = SimpleTableError;
+
+    fn try_from(fields: SimpleTableFields) -> Result<Self, Self::Error> {
+        let columns: Vec<&str> = fields.columns.iter().map(String::as_str).collect();
+        let table = Self::try_new(
+            fields.schema.name().clone(),
+            &columns,
+            &fields.schema.pk_indices(),
+        )?;
+        if table.schema != fields.schema {
+            return Err(SimpleTableError::InconsistentSchema);
+        }
+        Ok(table)
+    }
+}
+
+impl From<&SimpleTable> for TableSchema<String> {
+    /// Returns the binary-format schema of `table`, without its column names.
+    ///
+    /// [`TableSchema::to_simple_with_names`] converts back.
+    fn from(table: &SimpleTable) -> Self {
+        table.schema.clone()
+    }
+}
+
 impl PartialEq for SimpleTable {
     fn eq(&self, other: &Self) -> bool {
         self.schema == other.schema && self.columns == other.columns
@@ -173,3 +305,67 @@ impl<T: NamedColumns> NamedColumns for &T {
         T::column_index(self, column_name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new_rejects_duplicate_column_names() {
+        assert_eq!(
+            SimpleTable::try_new("t", &["id", "name", "name"], &[0]),
+            Err(SimpleTableError::DuplicateColumn {
+                name: "name".into(),
+                first: 1,
+                second: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_out_of_range_pk_index() {
+        assert_eq!(
+            SimpleTable::try_new("t", &["id", "name"], &[2]),
+            Err(SimpleTableError::PrimaryKeyIndexOutOfBounds(2, 2))
+        );
+    }
+
+    #[test]
+    fn test_try_new_rejects_repeated_pk_index() {
+        assert_eq!(
+            SimpleTable::try_new("t", &["a", "b"], &[0, 0]),
+            Err(SimpleTableError::DuplicatePrimaryKeyIndex(0))
+        );
+    }
+
+    #[test]
+    fn test_try_new_matches_new() {
+        let table = SimpleTable::try_new("t", &["a", "b", "c"], &[1, 0]).unwrap();
+        assert_eq!(table, SimpleTable::new("t", &["a", "b", "c"], &[1, 0]));
+        assert_eq!(table.pk_indices(), [1, 0]);
+    }
+
+    #[test]
+    fn test_table_schema_round_trip_keeps_all_but_names() {
+        let table = SimpleTable::new("order_items", &["sku", "qty", "order_id"], &[2, 0]);
+        let schema = TableSchema::from(&table);
+        assert_eq!(schema.name(), "order_items");
+        assert_eq!(schema.number_of_columns(), 3);
+        assert_eq!(schema.pk_flags(), [2, 0, 1]);
+        assert_eq!(schema.column_name(0), None);
+
+        assert_eq!(
+            schema.to_simple_with_names(&["sku", "qty", "order_id"]),
+            table
+        );
+        let renamed = schema.to_simple_with_names(&["a", "b", "c"]);
+        assert_eq!(renamed.pk_indices(), [2, 0]);
+        assert_eq!(TableSchema::from(&renamed), schema);
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate column name")]
+    fn test_new_panics_on_duplicate_column_names() {
+        let _ = SimpleTable::new("t", &["id", "id"], &[0]);
+    }
+}
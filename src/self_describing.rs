@@ -0,0 +1,268 @@
+//! Self-describing wrapper that ships column names alongside a diffset.
+//!
+//! The `SQLite` changeset and patchset formats only record, per table, its name,
+//! column count and primary-key flags. A consumer that wants to turn the rows
+//! back into named columns needs the DDL from somewhere else.
+//! [`SelfDescribingChangeset`] prepends a side block holding the column names
+//! of each table, so the payload carries everything needed to interpret it.
+//! [`strip`](SelfDescribingChangeset::strip) drops the side block and returns
+//! the plain `SQLite` bytes, ready for `sqlite3changeset_apply`.
+//!
+//! ```text
+//! Wrapper:
+//! ├── Magic (4 bytes): "SQSD"
+//! ├── Table count (varint)
+//! ├── For each table:
+//! │   ├── Name length (varint) + UTF-8 name
+//! │   ├── Column count (varint)
+//! │   └── For each column: name length (varint) + UTF-8 name
+//! └── SQLite changeset or patchset (rest of the buffer)
+//! ```
+//!
+//! Despite the name, the wrapper accepts patchsets as well.
+//!
+//! # Example
+//!
+//! ```
+//! use sqlite_diff_rs::self_describing::SelfDescribingChangeset;
+//! use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+//!
+//! let users = SimpleTable::new("users", &["id", "name"], &[0]);
+//! let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+//!     .insert(Insert::from(users.clone()).set(0, 1i64).unwrap().set(1, "Alice").unwrap());
+//!
+//! let wrapped = SelfDescribingChangeset::describe(changeset.build(), &[users]).to_bytes();
+//! let received = SelfDescribingChangeset::from_bytes(&wrapped).unwrap();
+//! assert_eq!(received.column_names("users").unwrap(), ["id", "name"]);
+//! assert_eq!(received.strip(), changeset.build());
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::encoding::varint::{decode_varint, encode_varint};
+use crate::parser::{ParseError, ParsedDiffSet};
+use crate::{ColumnNames, DynTable};
+
+/// Leading bytes identifying a self-describing payload.
+pub const MAGIC: [u8; 4] = *b"SQSD";
+
+/// Errors that can occur while decoding a self-describing payload.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SelfDescribingError {
+    /// The payload does not start with [`MAGIC`].
+    #[error("Missing self-describing magic")]
+    BadMagic,
+
+    /// The side block ends before the position given.
+    #[error("Unexpected end of schema block at position {0}")]
+    UnexpectedEof(usize),
+
+    /// A table or column name at the position given is not valid UTF-8.
+    #[error("Invalid UTF-8 name at position {0}")]
+    InvalidName(usize),
+
+    /// The embedded diffset could not be parsed.
+    #[error("Invalid embedded diffset: {0}")]
+    Diffset(#[from] ParseError),
+
+    /// The side block and the embedded diffset disagree on a table's width.
+    #[error("Table {table:?} is described with {described} columns but has {actual}")]
+    ColumnCountMismatch {
+        /// The table name.
+        table: String,
+        /// Number of column names in the side block.
+        described: usize,
+        /// Column count in the diffset's table header.
+        actual: usize,
+    },
+}
+
+/// A changeset or patchset bundled with the column names of its tables.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SelfDescribingChangeset {
+    /// Column names per table, in side-block order.
+    tables: Vec<(String, Vec<String>)>,
+    /// The plain `SQLite` changeset or patchset bytes.
+    diffset: Vec<u8>,
+}
+
+impl SelfDescribingChangeset {
+    /// Wraps `diffset`, describing each of `tables` by its column names.
+    #[must_use]
+    pub fn describe<T: ColumnNames>(diffset: Vec<u8>, tables: &[T]) -> Self {
+        let tables = tables
+            .iter()
+            .map(|table| {
+                let columns = (0..table.number_of_columns())
+                    .map(|idx| table.column_name(idx).unwrap_or_default().into())
+                    .collect();
+                (table.name().into(), columns)
+            })
+            .collect();
+        Self { tables, diffset }
+    }
+
+    /// Returns the column names recorded for `table`, if it is described.
+    #[must_use]
+    pub fn column_names(&self, table: &str) -> Option<&[String]> {
+        self.tables
+            .iter()
+            .find(|(name, _)| name == table)
+            .map(|(_, columns)| columns.as_slice())
+    }
+
+    /// Iterates over the described tables and their column names.
+    pub fn tables(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.tables
+            .iter()
+            .map(|(name, columns)| (name.as_str(), columns.as_slice()))
+    }
+
+    /// Returns the wrapped `SQLite` bytes.
+    #[must_use]
+    pub fn diffset(&self) -> &[u8] {
+        &self.diffset
+    }
+
+    /// Parses the wrapped `SQLite` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParseError` if the wrapped bytes are malformed.
+    pub fn parse(&self) -> Result<ParsedDiffSet, ParseError> {
+        ParsedDiffSet::parse(&self.diffset)
+    }
+
+    /// Drops the side block, returning the plain `SQLite`-compatible bytes.
+    #[must_use]
+    pub fn strip(self) -> Vec<u8> {
+        self.diffset
+    }
+
+    /// Encodes the side block followed by the wrapped `SQLite` bytes.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::from(MAGIC);
+        out.extend(encode_varint(self.tables.len() as u64));
+        for (name, columns) in &self.tables {
+            write_name(&mut out, name);
+            out.extend(encode_varint(columns.len() as u64));
+            for column in columns {
+                write_name(&mut out, column);
+            }
+        }
+        out.extend_from_slice(&self.diffset);
+        out
+    }
+
+    /// Decodes a payload produced by [`to_bytes`](Self::to_bytes).
+    ///
+    /// The embedded diffset is parsed to check that every described table
+    /// that it contains has as many columns as it has names.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SelfDescribingError`] if the side block is malformed, the
+    /// embedded diffset does not parse, or the two disagree on a table's
+    /// column count.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SelfDescribingError> {
+        let mut pos = MAGIC.len();
+        if bytes.get(..pos) != Some(MAGIC.as_slice()) {
+            return Err(SelfDescribingError::BadMagic);
+        }
+        let table_count = read_varint(bytes, &mut pos)?;
+        let mut tables = Vec::new();
+        for _ in 0..table_count {
+            let name = read_name(bytes, &mut pos)?;
+            let column_count = read_varint(bytes, &mut pos)?;
+            let columns = (0..column_count)
+                .map(|_| read_name(bytes, &mut pos))
+                .collect::<Result<Vec<_>, _>>()?;
+            tables.push((name, columns));
+        }
+        let wrapper = Self {
+            tables,
+            diffset: bytes[pos..].to_vec(),
+        };
+
+        for schema in wrapper.parse()?.table_schemas() {
+            let actual = schema.number_of_columns();
+            let described = wrapper.column_names(schema.name()).map(<[String]>::len);
+            if let Some(described) = described.filter(|&described| described != actual) {
+                return Err(SelfDescribingError::ColumnCountMismatch {
+                    table: schema.name().clone(),
+                    described,
+                    actual,
+                });
+            }
+        }
+        Ok(wrapper)
+    }
+}
+
+/// Appends `name` as a varint length followed by its UTF-8 bytes.
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    out.extend(encode_varint(name.len() as u64));
+    out.extend_from_slice(name.as_bytes());
+}
+
+/// Reads a varint at `pos`, advancing past it.
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<usize, SelfDescribingError> {
+    let (value, len) =
+        decode_varint(&bytes[*pos..]).ok_or(SelfDescribingError::UnexpectedEof(*pos))?;
+    *pos += len;
+    usize::try_from(value).map_err(|_| SelfDescribingError::UnexpectedEof(*pos))
+}
+
+/// Reads a length-prefixed UTF-8 name at `pos`, advancing past it.
+fn read_name(bytes: &[u8], pos: &mut usize) -> Result<String, SelfDescribingError> {
+    let len = read_varint(bytes, pos)?;
+    let start = *pos;
+    let raw = start
+        .checked_add(len)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(SelfDescribingError::UnexpectedEof(start))?;
+    *pos += len;
+    core::str::from_utf8(raw)
+        .map(String::from)
+        .map_err(|_| SelfDescribingError::InvalidName(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_wrapper_round_trip() {
+        let wrapper = SelfDescribingChangeset::default();
+        let bytes = wrapper.to_bytes();
+        assert_eq!(bytes, [b'S', b'Q', b'S', b'D', 0]);
+        assert_eq!(SelfDescribingChangeset::from_bytes(&bytes), Ok(wrapper));
+    }
+
+    #[test]
+    fn test_bad_magic_is_rejected() {
+        assert_eq!(
+            SelfDescribingChangeset::from_bytes(b"T\x02\x01"),
+            Err(SelfDescribingError::BadMagic)
+        );
+    }
+
+    #[test]
+    fn test_truncated_side_block_is_rejected() {
+        // One table whose name claims 5 bytes but only 2 follow.
+        assert_eq!(
+            SelfDescribingChangeset::from_bytes(b"SQSD\x01\x05ab"),
+            Err(SelfDescribingError::UnexpectedEof(6))
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_name_is_rejected() {
+        assert_eq!(
+            SelfDescribingChangeset::from_bytes(b"SQSD\x01\x01\xff\x00"),
+            Err(SelfDescribingError::InvalidName(6))
+        );
+    }
+}
@@ -8,7 +8,17 @@
 //! enough type information to emit `CREATE TABLE` DDL. [`test_roundtrip`],
 //! [`test_apply_roundtrip`], [`test_reverse_idempotent`], [`test_sql_roundtrip`],
 //! and [`test_differential`] drive parse, serialize, apply, and reverse paths
-//! from a single fuzz or regression input.
+//! from a single fuzz or regression input, and [`assert_reversible_apply`]
+//! checks that a changeset's reverse undoes it against a real database.
+//! [`assert_row_order_matches_sqlite`] checks the simulated session hash table
+//! against the row order `SQLite` actually emits, and
+//! [`assert_changegroup_parity`] checks our consolidation against rusqlite's
+//! `Changegroup`, while [`assert_apply_parity`] checks that our changesets
+//! apply, conflicts included, as rusqlite's do. [`assert_full_pipeline`] runs a [`FuzzPipeline`] of
+//! generated row operations through build, parse, and apply at once.
+//! [`arbitrary_from_seed`] derives those structured inputs from a `u64` seed so
+//! a failure can be replayed by seed. [`Value`](crate::Value)s convert to and
+//! from `rusqlite::types::Value` with `From`.
 
 use core::fmt::{self, Write};
 use core::ops::Deref;
@@ -20,13 +30,12 @@ use alloc::string::{String, ToString};
 use alloc::vec;
 use alloc::vec::Vec;
 use rusqlite::Connection;
-use rusqlite::session::Session;
+use rusqlite::session::{Changegroup, Session};
 use std::io::Cursor;
 
 use crate::DynTable;
 use crate::PatchSet;
 use crate::Reverse;
-use crate::differential_testing::run_differential_test;
 use crate::parser::ParsedDiffSet;
 use crate::schema::SimpleTable;
 
@@ -64,6 +73,50 @@ impl<'a> arbitrary::Arbitrary<'a> for SqlType {
     }
 }
 
+/// Convert `value` to the storage class `SQLite` gives it in a column of the
+/// given `affinity`.
+///
+/// A `REAL` stored in an `INTEGER` column becomes an `INTEGER` when it is
+/// integral and strictly inside the `i64` range, so `2.0` is stored as `2`
+/// while `2.5` stays `REAL`. An `INTEGER` stored in a `REAL` column becomes a
+/// `REAL`. Conversions from `TEXT` are not modeled, and every other value is
+/// returned unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use sqlite_diff_rs::Value;
+/// use sqlite_diff_rs::testing::{SqlType, canonicalize_for_affinity};
+///
+/// let two: Value<String, Vec<u8>> = Value::Real(2.0);
+/// assert_eq!(canonicalize_for_affinity(two, SqlType::Integer), Value::Integer(2));
+/// let half: Value<String, Vec<u8>> = Value::Real(2.5);
+/// assert_eq!(canonicalize_for_affinity(half, SqlType::Integer), Value::Real(2.5));
+/// ```
+#[must_use]
+pub fn canonicalize_for_affinity<S, B>(
+    value: crate::Value<S, B>,
+    affinity: SqlType,
+) -> crate::Value<S, B> {
+    match (value, affinity) {
+        (crate::Value::Real(r), SqlType::Integer) => {
+            // Mirrors `sqlite3VdbeIntegerAffinity`, which excludes both
+            // endpoints of the `i64` range.
+            #[allow(clippy::cast_possible_truncation)]
+            let integral = r as i64;
+            #[allow(clippy::cast_precision_loss, clippy::float_cmp)]
+            if integral as f64 == r && integral > i64::MIN && integral < i64::MAX {
+                crate::Value::Integer(integral)
+            } else {
+                crate::Value::Real(r)
+            }
+        }
+        #[allow(clippy::cast_precision_loss)]
+        (crate::Value::Integer(i), SqlType::Real) => crate::Value::Real(i as f64),
+        (value, _) => value,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // TypedSimpleTable: SimpleTable + column types with Display for DDL
 // ---------------------------------------------------------------------------
@@ -287,6 +340,252 @@ impl<'a> arbitrary::Arbitrary<'a> for FuzzSchemas {
     }
 }
 
+impl FuzzSchemas {
+    /// Deterministically derive schemas from `seed`.
+    ///
+    /// The same seed always yields the same schemas, so a failure reported by
+    /// seed can be replayed locally. See [`arbitrary_from_seed`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sqlite_diff_rs::testing::FuzzSchemas;
+    ///
+    /// assert_eq!(FuzzSchemas::from_seed(42).0, FuzzSchemas::from_seed(42).0);
+    /// ```
+    #[must_use]
+    pub fn from_seed(seed: u64) -> Self {
+        arbitrary_from_seed(seed).expect("FuzzSchemas generation never fails")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FuzzPipeline: schemas with a valid sequence of row operations
+// ---------------------------------------------------------------------------
+
+/// One row operation produced by [`FuzzPipeline`] against
+/// [`FuzzSchemas`], addressing its table by index.
+///
+/// Rows hold one value per column, already canonicalized for the column's
+/// affinity as `SQLite` would store them, and never hold NULL or an
+/// integral REAL in a primary-key column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratedOp {
+    /// A row present before the session starts; not recorded.
+    Setup {
+        /// Index of the table in the schemas.
+        table: usize,
+        /// The full row.
+        row: Vec<crate::Value<String, Vec<u8>>>,
+    },
+    /// A row inserted under a key that is not live.
+    Insert {
+        /// Index of the table in the schemas.
+        table: usize,
+        /// The full row.
+        row: Vec<crate::Value<String, Vec<u8>>>,
+    },
+    /// A live row rewritten to `new`, which differs from `old` in at least
+    /// one column and may move the primary key to one that is not live.
+    Update {
+        /// Index of the table in the schemas.
+        table: usize,
+        /// The row before the update.
+        old: Vec<crate::Value<String, Vec<u8>>>,
+        /// The row after the update.
+        new: Vec<crate::Value<String, Vec<u8>>>,
+    },
+    /// A live row deleted.
+    Delete {
+        /// Index of the table in the schemas.
+        table: usize,
+        /// The row deleted.
+        old: Vec<crate::Value<String, Vec<u8>>>,
+    },
+}
+
+/// [`FuzzSchemas`] paired with a sequence of [`GeneratedOp`]s valid against
+/// them, for [`assert_full_pipeline`].
+///
+/// Generation tracks the live rows of every table, so inserts never collide
+/// with a live key and updates and deletes always hit a live row. Up to 6
+/// [`GeneratedOp::Setup`] rows come first, then up to 24 tracked operations.
+/// Values are drawn from small domains so that keys are often reused.
+#[derive(Debug, Clone)]
+pub struct FuzzPipeline {
+    /// The schemas the operations run against.
+    pub schemas: FuzzSchemas,
+    /// The operations, setup rows first.
+    pub ops: Vec<GeneratedOp>,
+}
+
+impl<'a> arbitrary::Arbitrary<'a> for FuzzPipeline {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        use crate::SchemaWithPK;
+
+        let schemas = FuzzSchemas::arbitrary(u)?;
+        let mut live: Vec<Vec<GeneratedRow>> = vec![Vec::new(); schemas.len()];
+        let mut ops = Vec::new();
+
+        let nsetup: usize = u.int_in_range(0..=6)?;
+        let ntracked: usize = u.int_in_range(0..=24)?;
+        for step in 0..nsetup + ntracked {
+            let table = u.choose_index(schemas.len())?;
+            let schema = &schemas[table];
+            let rows = &mut live[table];
+            let kind: u8 = if step < nsetup || rows.is_empty() {
+                0
+            } else {
+                u.int_in_range(0..=2)?
+            };
+            match kind {
+                0 => {
+                    let row = arbitrary_row(u, schema, None)?;
+                    let pk = schema.extract_pk(&row);
+                    if rows.iter().any(|live| schema.extract_pk(live) == pk) {
+                        continue;
+                    }
+                    rows.push(row.clone());
+                    ops.push(if step < nsetup {
+                        GeneratedOp::Setup { table, row }
+                    } else {
+                        GeneratedOp::Insert { table, row }
+                    });
+                }
+                1 => {
+                    let idx = u.choose_index(rows.len())?;
+                    let old = rows[idx].clone();
+                    let new = arbitrary_row(u, schema, Some(&old))?;
+                    let pk = schema.extract_pk(&new);
+                    let collides = rows
+                        .iter()
+                        .enumerate()
+                        .any(|(i, live)| i != idx && schema.extract_pk(live) == pk);
+                    if new == old || collides {
+                        continue;
+                    }
+                    rows[idx].clone_from(&new);
+                    ops.push(GeneratedOp::Update { table, old, new });
+                }
+                _ => {
+                    let idx = u.choose_index(rows.len())?;
+                    let old = rows.swap_remove(idx);
+                    ops.push(GeneratedOp::Delete { table, old });
+                }
+            }
+        }
+
+        Ok(Self { schemas, ops })
+    }
+}
+
+/// The values of one generated row.
+type GeneratedRow = Vec<crate::Value<String, Vec<u8>>>;
+
+/// Draw a row for `schema`, or, given `base`, a copy of it with about half of
+/// the columns redrawn.
+fn arbitrary_row(
+    u: &mut arbitrary::Unstructured<'_>,
+    schema: &TypedSimpleTable,
+    base: Option<&[crate::Value<String, Vec<u8>>]>,
+) -> arbitrary::Result<GeneratedRow> {
+    let pk_indices = schema.pk_indices();
+    let mut row = Vec::with_capacity(schema.column_types().len());
+    for (column, &ty) in schema.column_types().iter().enumerate() {
+        if let Some(base) = base
+            && u.arbitrary::<bool>()?
+        {
+            row.push(base[column].clone());
+            continue;
+        }
+        row.push(arbitrary_value(u, ty, pk_indices.contains(&column))?);
+    }
+    Ok(row)
+}
+
+/// Draw a value for a column of type `ty` from a small domain, as the column
+/// stores it. Key columns never get NULL.
+///
+/// Untyped columns never get an integral REAL, which rendered as SQL would
+/// read back as an INTEGER. Neither do key columns: the session hashes an
+/// inserted integral REAL key as the integer the column stores, so a later
+/// change to the row misses the INSERT and is recorded on its own.
+fn arbitrary_value(
+    u: &mut arbitrary::Unstructured<'_>,
+    ty: SqlType,
+    key: bool,
+) -> arbitrary::Result<crate::Value<String, Vec<u8>>> {
+    if !key && u.ratio(1u8, 6)? {
+        return Ok(crate::Value::Null);
+    }
+    let small = i64::from(u.int_in_range(-50i8..=50)?);
+    let text = |u: &mut arbitrary::Unstructured<'_>| -> arbitrary::Result<String> {
+        let len = u.int_in_range(0..=4)?;
+        (0..len)
+            .map(|_| u.int_in_range(b'a'..=b'd').map(char::from))
+            .collect()
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let value = match ty {
+        SqlType::Integer => crate::Value::Integer(small),
+        SqlType::Real if !key && u.arbitrary::<bool>()? => crate::Value::Real(small as f64),
+        SqlType::Real => crate::Value::Real(small as f64 + 0.5),
+        SqlType::Text => crate::Value::Text(text(u)?),
+        SqlType::Blob => match u.int_in_range(0..=3)? {
+            0 => crate::Value::Integer(small),
+            1 => crate::Value::Real(small as f64 + 0.5),
+            2 => crate::Value::Text(text(u)?),
+            _ => {
+                let len = u.int_in_range(0..=3)?;
+                crate::Value::Blob(
+                    (0..len)
+                        .map(|_| u.arbitrary::<u8>())
+                        .collect::<arbitrary::Result<_>>()?,
+                )
+            }
+        },
+    };
+    Ok(canonicalize_for_affinity(value, ty))
+}
+
+// ---------------------------------------------------------------------------
+// Seeded input generation
+// ---------------------------------------------------------------------------
+
+/// Number of pseudo-random bytes fed to [`arbitrary::Unstructured`] by
+/// [`arbitrary_from_seed`]. Comfortably more than the largest schema set
+/// ([`FuzzSchemas`] caps out at 5 tables of 8 columns) consumes.
+const SEED_INPUT_LEN: usize = 4096;
+
+/// Deterministically derive any [`Arbitrary`](arbitrary::Arbitrary) fuzz input
+/// from a `u64` seed.
+///
+/// The seed is expanded into a fixed-length byte stream with `SplitMix64` and
+/// handed to the type's `Arbitrary` impl, so the structured inputs the
+/// harnesses take, such as `(FuzzSchemas, String)` for `differential` or
+/// `(FuzzSchemas, Vec<u8>)` for `apply_roundtrip`, can be reproduced from a
+/// seed alone.
+///
+/// # Errors
+///
+/// Returns the `Arbitrary` impl's error if it rejects the generated bytes.
+pub fn arbitrary_from_seed<T>(seed: u64) -> arbitrary::Result<T>
+where
+    T: for<'a> arbitrary::Arbitrary<'a>,
+{
+    let mut state = seed;
+    let mut bytes = Vec::with_capacity(SEED_INPUT_LEN);
+    while bytes.len() < SEED_INPUT_LEN {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        bytes.extend_from_slice(&z.to_le_bytes());
+    }
+    T::arbitrary_take_rest(arbitrary::Unstructured::new(&bytes))
+}
+
 // ---------------------------------------------------------------------------
 // Shared fuzzer / regression-test helpers
 // ---------------------------------------------------------------------------
@@ -359,14 +658,17 @@ pub fn test_apply_roundtrip(schemas: &[TypedSimpleTable], changeset_bytes: &[u8]
     let _ = apply_changeset(&conn, &serialized);
 }
 
-/// Test reverse idempotency: `reverse(reverse(x)) == x`.
+/// Test reverse idempotency: `reverse(reverse(x)) == x` for every `x` that
+/// is itself a reverse.
 ///
 /// Parses the input as a binary changeset (patchsets are skipped because they
-/// do not support [`Reverse`]) and verifies that double-reversing yields a
-/// structurally equal changeset, that the binary representations match after
-/// double reverse, that the operation count is preserved by reversal, and
-/// that empty changesets reverse to empty. Returns early (no panic) if the
-/// input cannot be parsed or is a patchset.
+/// do not support [`Reverse`]) and verifies that double-reversing its reverse
+/// yields a structurally equal changeset, that the binary representations
+/// match after double reverse, that the operation count is preserved by
+/// reversal, and that empty changesets reverse to empty. The input itself is
+/// not compared, since reversing drops the new value an UPDATE repeats for an
+/// unchanged key column, as `sqlite3changeset_invert` does. Returns early (no
+/// panic) if the input cannot be parsed or is a patchset.
 ///
 /// # Panics
 ///
@@ -384,16 +686,17 @@ pub fn test_reverse_idempotent(input: &[u8]) {
 
     let reversed = changeset.clone().reverse();
     let double_reversed = reversed.clone().reverse();
+    let triple_reversed = double_reversed.clone().reverse();
 
     assert_eq!(
-        changeset, double_reversed,
+        reversed, triple_reversed,
         "Double reverse should equal original"
     );
 
-    let original_bytes = changeset.build();
-    let double_reversed_bytes = double_reversed.build();
+    let reversed_bytes = reversed.build();
+    let triple_reversed_bytes = triple_reversed.build();
     assert_eq!(
-        original_bytes, double_reversed_bytes,
+        reversed_bytes, triple_reversed_bytes,
         "Binary representation should be identical after double reverse"
     );
 
@@ -411,6 +714,70 @@ pub fn test_reverse_idempotent(input: &[u8]) {
     }
 }
 
+/// Assert that applying a changeset and then its reverse restores the database.
+///
+/// Creates an in-memory database from `schemas`, snapshots it into a second
+/// connection, applies `changeset_bytes`, applies its [`Reverse`], and checks
+/// both connections with [`compare_db_states`]. Unlike
+/// [`test_reverse_idempotent`], which only compares bytes, this exercises the
+/// undo path against a real `SQLite` apply.
+///
+/// Use [`assert_reversible_apply_with_setup`] to seed rows for `UPDATE` and
+/// `DELETE` operations to act on.
+///
+/// # Panics
+///
+/// Panics if the input does not parse or is a patchset, if the changeset or
+/// its reverse fails to apply, or if the final state differs from the
+/// snapshot.
+pub fn assert_reversible_apply(schemas: &[TypedSimpleTable], changeset_bytes: &[u8]) {
+    assert_reversible_apply_with_setup(schemas, &[], changeset_bytes);
+}
+
+/// Like [`assert_reversible_apply`], but runs `setup` DML on both databases
+/// after the schemas are created and before the snapshot is taken.
+///
+/// # Panics
+///
+/// Panics if the input does not parse or is a patchset, if database
+/// creation or a `setup` statement fails, if the changeset or its reverse
+/// fails to apply, or if the final state differs from the snapshot.
+pub fn assert_reversible_apply_with_setup(
+    schemas: &[TypedSimpleTable],
+    setup: &[&str],
+    changeset_bytes: &[u8],
+) {
+    let parsed = ParsedDiffSet::try_from(changeset_bytes)
+        .unwrap_or_else(|err| panic!("Input should parse as a changeset: {err}"));
+    let ParsedDiffSet::Changeset(changeset) = parsed else {
+        panic!("Input should be a changeset, found a patchset");
+    };
+
+    let create_sqls: Vec<String> = schemas.iter().map(ToString::to_string).collect();
+    let open = || {
+        let conn = Connection::open_in_memory().unwrap();
+        for sql in create_sqls
+            .iter()
+            .map(String::as_str)
+            .chain(setup.iter().copied())
+        {
+            conn.execute(sql, []).unwrap();
+        }
+        conn
+    };
+    let conn = open();
+    let snapshot = open();
+
+    apply_changeset(&conn, &changeset.build())
+        .expect("The changeset should apply cleanly to the starting state");
+
+    let reversed_bytes = changeset.reverse().build();
+    apply_changeset(&conn, &reversed_bytes)
+        .expect("The reverse of an applied changeset should apply cleanly");
+
+    compare_db_states(&conn, &snapshot, &create_sqls);
+}
+
 /// Test SQL-digest roundtrip: digest SQL into a patchset, serialize, reparse.
 ///
 /// Builds a [`PatchSet`] with the given schemas, digests the SQL, returns
@@ -446,24 +813,45 @@ pub fn test_sql_roundtrip(schemas: &[TypedSimpleTable], sql: &str) {
 
 /// Test differential (bit-parity) between our patchset output and rusqlite's.
 ///
-/// Builds a [`PatchSet`] with the given schemas, digests the SQL, returns
-/// early if digestion fails or the result is empty, then delegates to
-/// `run_differential_test` to compare our bytes against rusqlite's session
-/// extension output.
+/// Builds a [`PatchSet`] with the given schemas and digests the SQL, passing
+/// every literal through [`canonicalize_for_affinity`] with its column's
+/// [`SqlType`] as `SQLite` would when storing it. Returns early if digestion
+/// fails or the result is empty, then compares our bytes against rusqlite's
+/// session extension output for the same DDL and SQL.
+///
+/// # Panics
+///
+/// Panics if the patchset bytes differ.
 pub fn test_differential(schemas: &[TypedSimpleTable], sql: &str) {
     let mut builder: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
     for schema in schemas {
         builder.add_table(&**schema);
     }
 
-    if builder.digest_sql(sql).is_err() || builder.is_empty() {
+    let coerce =
+        |table: &SimpleTable, column: usize, value: crate::Value<String, Vec<u8>>| match schemas
+            .iter()
+            .find(|schema| schema.name() == table.name())
+        {
+            Some(schema) => canonicalize_for_affinity(value, schema.column_types()[column]),
+            None => value,
+        };
+    if builder.digest_sql_coerced(sql, &coerce).is_err() || builder.is_empty() {
         return;
     }
+    let our_patchset: Vec<u8> = builder.build();
 
     let create_sqls: Vec<String> = schemas.iter().map(ToString::to_string).collect();
-    let create_sql_refs: Vec<&str> = create_sqls.iter().map(String::as_str).collect();
-    let simples: Vec<SimpleTable> = schemas.iter().map(|s| (**s).clone()).collect();
-    run_differential_test(&simples, &create_sql_refs, &[sql]);
+    let mut statements: Vec<&str> = create_sqls.iter().map(String::as_str).collect();
+    statements.push(sql);
+    let (_, sqlite_patchset) = session_changeset_and_patchset(&statements);
+
+    let ps_report = byte_diff_report("patchset", &sqlite_patchset, &our_patchset);
+    assert!(
+        sqlite_patchset == our_patchset,
+        "Patchset bit parity failure in differential test!\n\n{ps_report}\n\nSQL:\n{}",
+        statements.join("\n")
+    );
 }
 
 /// Create an in-memory `SQLite` database, execute statements with a session,
@@ -625,9 +1013,79 @@ pub fn assert_bit_parity(sql_statements: &[&str], our_changeset: &[u8], our_patc
     );
 }
 
+/// A `(setup, tracked)` pair of SQL lists, as taken by
+/// [`session_changeset_and_patchset_with_setup`].
+pub type SessionBatch<'a> = (&'a [&'a str], &'a [&'a str]);
+
+/// Record each of `batches` in a fresh database, as
+/// [`session_changeset_and_patchset_with_setup`] does, and combine the results
+/// through rusqlite's `Changegroup`, returning the merged changeset and
+/// patchset bytes.
+///
+/// Recording every batch separately lets a test reach transitions that no
+/// single database history produces, such as two `INSERT`s of the same row.
+///
+/// # Panics
+///
+/// Panics if database creation, statement execution, or changegroup
+/// operations fail.
+#[must_use]
+pub fn changegroup_changeset_and_patchset(batches: &[SessionBatch<'_>]) -> (Vec<u8>, Vec<u8>) {
+    let mut changesets = Changegroup::new().unwrap();
+    let mut patchsets = Changegroup::new().unwrap();
+    for &(setup, tracked) in batches {
+        let (changeset, patchset) = session_changeset_and_patchset_with_setup(setup, tracked);
+        changesets.add_stream(&mut Cursor::new(changeset)).unwrap();
+        patchsets.add_stream(&mut Cursor::new(patchset)).unwrap();
+    }
+    let mut changeset = Vec::new();
+    changesets.output_strm(&mut changeset).unwrap();
+    let mut patchset = Vec::new();
+    patchsets.output_strm(&mut patchset).unwrap();
+    (changeset, patchset)
+}
+
+/// Assert that our consolidation of per-batch diffsets matches
+/// `sqlite3changegroup_add()`.
+///
+/// Each batch is recorded as in [`changegroup_changeset_and_patchset`]. The
+/// per-batch changesets and patchsets are parsed into builders and merged
+/// with `|`, and the result must be byte-identical to what rusqlite's
+/// `Changegroup` produces from the same inputs.
+///
+/// # Panics
+///
+/// Panics with a detailed diff report if the bytes don't match.
+pub fn assert_changegroup_parity(batches: &[SessionBatch<'_>]) {
+    let mut our_changeset = crate::ChangeSet::<crate::TableSchema<String>, String, Vec<u8>>::new();
+    let mut our_patchset = PatchSet::<crate::TableSchema<String>, String, Vec<u8>>::new();
+    for &(setup, tracked) in batches {
+        let (changeset, patchset) = session_changeset_and_patchset_with_setup(setup, tracked);
+        let ParsedDiffSet::Changeset(changeset) = ParsedDiffSet::parse(&changeset).unwrap() else {
+            unreachable!("the session emitted a changeset")
+        };
+        let ParsedDiffSet::Patchset(patchset) = ParsedDiffSet::parse(&patchset).unwrap() else {
+            unreachable!("the session emitted a patchset")
+        };
+        our_changeset |= changeset.into();
+        our_patchset |= patchset.into();
+    }
+    let our_changeset = our_changeset.build();
+    let our_patchset = our_patchset.build();
+
+    let (sqlite_changeset, sqlite_patchset) = changegroup_changeset_and_patchset(batches);
+    let cs_report = byte_diff_report("changeset", &sqlite_changeset, &our_changeset);
+    let ps_report = byte_diff_report("patchset", &sqlite_patchset, &our_patchset);
+    assert!(
+        sqlite_changeset == our_changeset && sqlite_patchset == our_patchset,
+        "Changegroup parity failure!\n\n{cs_report}\n{ps_report}\n\nBatches:\n{batches:#?}"
+    );
+}
+
 /// Run bit-parity test by digesting SQL into a `PatchSet` via `digest_sql`,
 /// serializing to bytes, and comparing the patchset with rusqlite's output.
-/// Only patchset parity is tested because SQL digestion is patchset-only.
+/// Only patchset parity is tested because a changeset digested from SQL
+/// carries only the old values the statements name.
 ///
 /// The `schemas` must be pre-built [`SimpleTable`]s matching the CREATE TABLE
 /// statements in `sql_statements`. The builder is seeded with these schemas
@@ -679,6 +1137,303 @@ pub fn assert_patchset_sql_parity(schemas: &[SimpleTable], sql_statements: &[&st
     );
 }
 
+/// Assert that our simulation of the session hash table predicts the order in
+/// which `SQLite` emits `rows`.
+///
+/// The rows are inserted into `table` through rusqlite, one statement each and
+/// in slice order, inside a session. Columns are declared without a type so
+/// every value is stored as given. The INSERTs of the captured changeset must
+/// appear in exactly the order predicted from the rows' primary keys.
+///
+/// The prediction hashes integral REAL keys as a REAL column stores them, so
+/// such keys mismatch here, where the column has no affinity.
+///
+/// # Panics
+///
+/// Panics if a row does not have one value per column, if two rows share a
+/// primary key, or if the orders differ.
+pub fn assert_row_order_matches_sqlite(
+    table: &SimpleTable,
+    rows: &[Vec<crate::Value<String, Vec<u8>>>],
+) {
+    use crate::SchemaWithPK;
+    use crate::builders::session_pk_order;
+
+    let columns = table.column_names();
+    let typed = TypedSimpleTable::new(
+        table.name(),
+        &columns
+            .iter()
+            .map(|name| (name.as_str(), SqlType::Blob))
+            .collect::<Vec<_>>(),
+        &table.pk_indices(),
+    );
+    // Drop the BLOB affinity so that values keep their storage class.
+    let ddl = typed.to_string().replace(" BLOB", "");
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let insert = format!("INSERT INTO \"{}\" VALUES ({placeholders})", table.name());
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&ddl, []).unwrap();
+    let mut session = Session::new(&conn).unwrap();
+    session.attach::<&str>(None).unwrap();
+    for row in rows {
+        assert_eq!(row.len(), columns.len(), "row width must match the table");
+        conn.execute(
+            &insert,
+            rusqlite::params_from_iter(row.iter().map(rusqlite::types::Value::from)),
+        )
+        .unwrap();
+    }
+    let mut changeset = Vec::new();
+    session.changeset_strm(&mut changeset).unwrap();
+
+    let pks: Vec<_> = rows.iter().map(|row| table.extract_pk(row)).collect();
+    let predicted: Vec<&[crate::Value<String, Vec<u8>>]> = session_pk_order(table, &pks)
+        .into_iter()
+        .map(|idx| pks[idx].as_slice())
+        .collect();
+    let ParsedDiffSet::Changeset(diffset) = ParsedDiffSet::parse(&changeset).unwrap() else {
+        unreachable!("the session emitted a changeset")
+    };
+    let actual: Vec<&[crate::Value<String, Vec<u8>>]> = diffset
+        .tables
+        .iter()
+        .flat_map(|(_, ops)| ops.iter().map(|(pk, _)| pk.as_slice()))
+        .collect();
+
+    assert!(
+        predicted == actual,
+        "Row order mismatch for {} rows\n  predicted: {predicted:?}\n  sqlite:    {actual:?}",
+        rows.len()
+    );
+}
+
+/// Run generated operations through every stage of the pipeline and assert
+/// that each agrees with `SQLite`.
+///
+/// The setup rows are inserted before a session is attached and the other
+/// operations are executed inside it, one parameterized statement each. The
+/// same operations staged on our changeset must then:
+///
+/// 1. build the bytes of the session's changeset and, converted to a
+///    patchset, of its patchset;
+/// 2. re-encode to the same bytes after parsing, and parse to a diffset equal
+///    to the builder;
+/// 3. bring a database holding only the setup rows to the session's final
+///    state, whether applied as a changeset, as a patchset, or as the
+///    changeset's SQL statements.
+///
+/// # Panics
+///
+/// Panics if an operation is invalid for the schemas or if any stage
+/// disagrees.
+pub fn assert_full_pipeline(schemas: &FuzzSchemas, ops: &[GeneratedOp]) {
+    let create_sqls: Vec<String> = schemas.iter().map(ToString::to_string).collect();
+    let open = || {
+        let conn = Connection::open_in_memory().unwrap();
+        for sql in &create_sqls {
+            conn.execute(sql, []).unwrap();
+        }
+        for op in ops {
+            if matches!(op, GeneratedOp::Setup { .. }) {
+                execute_generated_op(&conn, schemas, op);
+            }
+        }
+        conn
+    };
+
+    let conn = open();
+    let (sqlite_changeset, sqlite_patchset) = {
+        let mut session = Session::new(&conn).unwrap();
+        session.attach::<&str>(None).unwrap();
+        for op in ops {
+            if !matches!(op, GeneratedOp::Setup { .. }) {
+                execute_generated_op(&conn, schemas, op);
+            }
+        }
+        let mut changeset = Vec::new();
+        session.changeset_strm(&mut changeset).unwrap();
+        let mut patchset = Vec::new();
+        session.patchset_strm(&mut patchset).unwrap();
+        (changeset, patchset)
+    };
+
+    let changeset = stage_generated_ops(schemas, ops);
+    let patchset = changeset.to_patchset();
+    let context = format!("schemas: {:?}\nops: {ops:?}", schemas.0);
+
+    for (label, expected, actual) in [
+        ("changeset", &sqlite_changeset, changeset.build()),
+        ("patchset", &sqlite_patchset, patchset.build()),
+    ] {
+        assert!(
+            *expected == actual,
+            "Bit parity failure in full pipeline!\n\n{}\n\n{context}",
+            byte_diff_report(label, expected, &actual)
+        );
+        let parsed = ParsedDiffSet::try_from(actual.as_slice())
+            .unwrap_or_else(|err| panic!("{label} does not parse: {err:?}\n\n{context}"));
+        let is_source = if label == "changeset" {
+            parsed == changeset
+        } else {
+            parsed == patchset
+        };
+        assert!(
+            is_source,
+            "Parsed {label} differs from its builder\n\n{context}"
+        );
+        let reencoded: Vec<u8> = parsed.into();
+        assert_eq!(
+            reencoded, actual,
+            "Binary {label} round-trip mismatch\n\n{context}"
+        );
+    }
+
+    for bytes in [&sqlite_changeset, &sqlite_patchset] {
+        let applied = open();
+        apply_changeset(&applied, bytes)
+            .unwrap_or_else(|err| panic!("apply failed: {err}\n\n{context}"));
+        compare_db_states(&conn, &applied, &create_sqls);
+    }
+    let replayed = open();
+    for sql in changeset.sql_statements() {
+        replayed
+            .execute(&sql, [])
+            .unwrap_or_else(|err| panic!("{sql} failed: {err}\n\n{context}"));
+    }
+    compare_db_states(&conn, &replayed, &create_sqls);
+}
+
+/// Execute one generated operation as a parameterized statement.
+fn execute_generated_op(conn: &Connection, schemas: &FuzzSchemas, op: &GeneratedOp) {
+    let (table, set, filter) = match op {
+        GeneratedOp::Setup { table, row } | GeneratedOp::Insert { table, row } => {
+            (table, Some(row), None)
+        }
+        GeneratedOp::Update { table, old, new } => (table, Some(new), Some(old)),
+        GeneratedOp::Delete { table, old } => (table, None, Some(old)),
+    };
+    let schema = &schemas[*table];
+    let columns = schema.column_names();
+    let name = schema.name();
+    let assignments = columns
+        .iter()
+        .map(|column| format!("\"{column}\" = ?"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let key_filter = schema
+        .pk_indices()
+        .iter()
+        .map(|&idx| format!("\"{}\" = ?", columns[idx]))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let (sql, params): (String, Vec<&crate::Value<String, Vec<u8>>>) = match (set, filter) {
+        (Some(row), None) => (
+            format!(
+                "INSERT INTO \"{name}\" VALUES ({})",
+                vec!["?"; columns.len()].join(", ")
+            ),
+            row.iter().collect(),
+        ),
+        (Some(new), Some(old)) => (
+            format!("UPDATE \"{name}\" SET {assignments} WHERE {key_filter}"),
+            new.iter()
+                .chain(schema.pk_indices().iter().map(|&idx| &old[idx]))
+                .collect(),
+        ),
+        (None, Some(old)) => (
+            format!("DELETE FROM \"{name}\" WHERE {key_filter}"),
+            schema.pk_indices().iter().map(|&idx| &old[idx]).collect(),
+        ),
+        (None, None) => unreachable!("every operation sets or filters a row"),
+    };
+    conn.execute(
+        &sql,
+        rusqlite::params_from_iter(params.into_iter().map(rusqlite::types::Value::from)),
+    )
+    .unwrap_or_else(|err| panic!("{sql} failed: {err}"));
+}
+
+/// Stage generated operations on a changeset, as the session records them.
+fn stage_generated_ops(
+    schemas: &FuzzSchemas,
+    ops: &[GeneratedOp],
+) -> crate::ChangeSet<SimpleTable, String, Vec<u8>> {
+    use crate::SchemaWithPK;
+    use crate::builders::sql::SqlFormat;
+    use crate::builders::{ChangesetFormat, Operation};
+
+    let mut changeset = crate::ChangeSet::new();
+    for op in ops {
+        match op {
+            GeneratedOp::Setup { .. } => {}
+            GeneratedOp::Insert { table, row } => {
+                let table: &SimpleTable = &schemas[*table];
+                changeset.add_operation(
+                    table,
+                    table.extract_pk(row),
+                    Operation::Insert {
+                        values: row.clone(),
+                        indirect: false,
+                    },
+                );
+            }
+            GeneratedOp::Update { table, old, new } => {
+                let table: &SimpleTable = &schemas[*table];
+                for (pk, op) in ChangesetFormat::row_update(table, old.clone(), new.clone()) {
+                    changeset.add_operation(table, pk, op);
+                }
+            }
+            GeneratedOp::Delete { table, old } => {
+                let table: &SimpleTable = &schemas[*table];
+                changeset.add_operation(
+                    table,
+                    table.extract_pk(old),
+                    Operation::Delete {
+                        data: old.clone(),
+                        indirect: false,
+                    },
+                );
+            }
+        }
+    }
+    changeset
+}
+
+/// Converts one of our values into the rusqlite value of the same storage
+/// class, as bound to a statement parameter.
+///
+/// An undefined value, `None` in an UPDATE's `(old, new)` pairs, has no
+/// rusqlite counterpart: map the `Option` instead, keeping `None` as is.
+impl<S: AsRef<str>, B: AsRef<[u8]>> From<&crate::Value<S, B>> for rusqlite::types::Value {
+    fn from(value: &crate::Value<S, B>) -> Self {
+        match value {
+            crate::Value::Integer(i) => Self::Integer(*i),
+            crate::Value::Real(f) => Self::Real(*f),
+            crate::Value::Text(s) => Self::Text(s.as_ref().to_string()),
+            crate::Value::Blob(b) => Self::Blob(b.as_ref().to_vec()),
+            crate::Value::Null => Self::Null,
+        }
+    }
+}
+
+/// Converts a rusqlite value, such as one read from a row, into ours.
+///
+/// Every rusqlite value has a counterpart, so the conversion cannot fail.
+impl From<rusqlite::types::Value> for crate::Value<String, Vec<u8>> {
+    fn from(value: rusqlite::types::Value) -> Self {
+        match value {
+            rusqlite::types::Value::Integer(i) => Self::Integer(i),
+            rusqlite::types::Value::Real(f) => Self::Real(f),
+            rusqlite::types::Value::Text(s) => Self::Text(s),
+            rusqlite::types::Value::Blob(b) => Self::Blob(b),
+            rusqlite::types::Value::Null => Self::Null,
+        }
+    }
+}
+
 /// Apply a changeset or patchset to a database connection.
 ///
 /// Uses `SQLITE_CHANGESET_ABORT` on conflict.
@@ -696,6 +1451,115 @@ pub fn apply_changeset(conn: &Connection, changeset: &[u8]) -> Result<(), rusqli
     )
 }
 
+/// Conflicts `SQLite` reported while applying a changeset, counted by type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConflictCounts {
+    /// `SQLITE_CHANGESET_DATA`: the row exists but holds other old values.
+    pub data: usize,
+    /// `SQLITE_CHANGESET_NOTFOUND`: no row has the key to update or delete.
+    pub not_found: usize,
+    /// `SQLITE_CHANGESET_CONFLICT`: an inserted key is already taken.
+    pub conflict: usize,
+    /// `SQLITE_CHANGESET_CONSTRAINT`: the change breaks another constraint.
+    pub constraint: usize,
+    /// `SQLITE_CHANGESET_FOREIGN_KEY`: foreign keys are left violated.
+    pub foreign_key: usize,
+}
+
+/// Apply a changeset or patchset to a database connection, answering each
+/// conflict with `resolve` and counting the conflicts by type.
+///
+/// # Errors
+///
+/// Returns an error if the changeset application fails, including when
+/// `resolve` answers `SQLITE_CHANGESET_ABORT`.
+///
+/// # Panics
+///
+/// Panics if the conflict counter lock is poisoned.
+pub fn apply_changeset_with(
+    conn: &Connection,
+    changeset: &[u8],
+    resolve: fn(rusqlite::session::ConflictType) -> rusqlite::session::ConflictAction,
+) -> Result<ConflictCounts, rusqlite::Error> {
+    use rusqlite::session::{ChangesetItem, ConflictType};
+    use std::sync::{Arc, Mutex};
+
+    let counts = Arc::new(Mutex::new(ConflictCounts::default()));
+    let counter = Arc::clone(&counts);
+    let mut cursor = Cursor::new(changeset);
+    conn.apply_strm(
+        &mut cursor,
+        None::<fn(&str) -> bool>,
+        move |conflict_type: ConflictType, _item: ChangesetItem| {
+            let mut counts = counter.lock().unwrap();
+            match conflict_type {
+                ConflictType::SQLITE_CHANGESET_DATA => counts.data += 1,
+                ConflictType::SQLITE_CHANGESET_NOTFOUND => counts.not_found += 1,
+                ConflictType::SQLITE_CHANGESET_CONFLICT => counts.conflict += 1,
+                ConflictType::SQLITE_CHANGESET_CONSTRAINT => counts.constraint += 1,
+                ConflictType::SQLITE_CHANGESET_FOREIGN_KEY => counts.foreign_key += 1,
+                _ => {}
+            }
+            resolve(conflict_type)
+        },
+    )?;
+    let counts = *counts.lock().unwrap();
+    Ok(counts)
+}
+
+/// Assert that our changeset applies to a database exactly as `SQLite`'s
+/// does, conflicts included.
+///
+/// `SQLite`'s changeset is recorded as
+/// [`session_changeset_and_patchset_with_setup`] records it from `setup` and
+/// `tracked`. Both changesets are then applied, answering conflicts with
+/// `resolve`, to two fresh databases built by running `target`, which
+/// should diverge from the recorded history to provoke the conflicts under
+/// test. The two applies must succeed or fail alike, report the same
+/// conflicts, and leave the tables created by `target` with the same rows.
+///
+/// Returns the conflicts reported, so a test can check it provoked the ones
+/// it meant to, or none if both applies aborted.
+///
+/// # Panics
+///
+/// Panics if a statement fails, or if the outcomes differ.
+pub fn assert_apply_parity(
+    setup: &[&str],
+    tracked: &[&str],
+    our_changeset: &[u8],
+    target: &[&str],
+    resolve: fn(rusqlite::session::ConflictType) -> rusqlite::session::ConflictAction,
+) -> ConflictCounts {
+    let (sqlite_changeset, _) = session_changeset_and_patchset_with_setup(setup, tracked);
+    let apply = |changeset: &[u8]| {
+        let conn = Connection::open_in_memory().unwrap();
+        for &sql in target {
+            conn.execute(sql, []).unwrap();
+        }
+        let outcome = apply_changeset_with(&conn, changeset, resolve).map_err(|e| e.to_string());
+        (conn, outcome)
+    };
+    let (sqlite_conn, sqlite_outcome) = apply(&sqlite_changeset);
+    let (our_conn, our_outcome) = apply(our_changeset);
+
+    assert_eq!(
+        our_outcome,
+        sqlite_outcome,
+        "Apply outcome mismatch!\n\n{}\n\nTarget:\n{}",
+        byte_diff_report("changeset", &sqlite_changeset, our_changeset),
+        target.join("\n")
+    );
+    let create_table_sqls: Vec<String> = target
+        .iter()
+        .filter(|sql| sql.trim().to_uppercase().starts_with("CREATE TABLE"))
+        .map(|sql| (*sql).to_string())
+        .collect();
+    compare_db_states(&sqlite_conn, &our_conn, &create_table_sqls);
+    sqlite_outcome.unwrap_or_default()
+}
+
 /// Query all rows from a table as a sorted vector of string-formatted values.
 ///
 /// Rows are sorted for order-independent comparison.
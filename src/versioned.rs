@@ -0,0 +1,140 @@
+//! Application-versioned envelope for changeset and patchset payloads.
+//!
+//! The `SQLite` changeset and patchset formats carry no version header, so a
+//! receiver cannot tell frames produced under an old application schema from
+//! new ones. [`VersionedChangeset`] prefixes the payload with a magic tag and
+//! an application-defined `u16` version; the bytes after the prefix are left
+//! untouched, ready for `sqlite3changeset_apply`.
+//!
+//! ```text
+//! Envelope:
+//! ├── Magic (4 bytes): "SQVC"
+//! ├── Version (2 bytes, big-endian)
+//! └── SQLite changeset or patchset (rest of the buffer)
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use sqlite_diff_rs::versioned::VersionedChangeset;
+//!
+//! let envelope = VersionedChangeset::wrap(b"changeset bytes", 3);
+//! let (version, changeset) = VersionedChangeset::open(&envelope).unwrap();
+//! assert_eq!(version, 3);
+//! assert_eq!(changeset, b"changeset bytes");
+//! ```
+//!
+//! [`VersionedError`] converts into the binary [`ParseError`], so a receiver
+//! can open the envelope and parse its payload behind one `?` each:
+//!
+//! ```
+//! use sqlite_diff_rs::versioned::VersionedChangeset;
+//! use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, ParseError, ParsedDiffSet, SimpleTable};
+//!
+//! fn receive(envelope: &[u8]) -> Result<(u16, ParsedDiffSet), ParseError> {
+//!     let (version, diffset) = VersionedChangeset::open(envelope)?;
+//!     Ok((version, ParsedDiffSet::parse(diffset)?))
+//! }
+//!
+//! let users = SimpleTable::new("users", &["id"], &[0]);
+//! let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+//!     ChangeSet::new().insert(Insert::from(users).set(0, 1i64).unwrap());
+//! let (version, parsed) = receive(&VersionedChangeset::wrap(&changeset.build(), 2)).unwrap();
+//! assert_eq!((version, parsed.operation_count()), (2, 1));
+//! assert!(matches!(receive(b"T"), Err(ParseError::Versioned(_))));
+//! ```
+//!
+//! [`ParseError`]: crate::ParseError
+
+use alloc::vec::Vec;
+
+/// Leading bytes identifying a versioned envelope.
+pub const MAGIC: [u8; 4] = *b"SQVC";
+
+/// Length of the prefix written ahead of the payload.
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Errors that can occur while decoding a versioned envelope.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum VersionedError {
+    /// The envelope does not start with [`MAGIC`].
+    #[error("Missing versioned envelope magic")]
+    BadMagic,
+
+    /// The envelope ends inside the version field.
+    #[error("Truncated versioned envelope header")]
+    Truncated,
+}
+
+/// Wraps and unwraps changesets in an application-versioned envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VersionedChangeset;
+
+impl VersionedChangeset {
+    /// Encodes `diffset` behind the magic tag and `version`.
+    #[must_use]
+    pub fn wrap(diffset: &[u8], version: u16) -> Vec<u8> {
+        let mut envelope = Vec::with_capacity(HEADER_LEN + diffset.len());
+        envelope.extend_from_slice(&MAGIC);
+        envelope.extend_from_slice(&version.to_be_bytes());
+        envelope.extend_from_slice(diffset);
+        envelope
+    }
+
+    /// Decodes an envelope produced by [`wrap`](Self::wrap), returning the
+    /// version and the wrapped `SQLite` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`VersionedError`] if the envelope does not start with
+    /// [`MAGIC`] or ends before the version. It converts into
+    /// [`ParseError::Versioned`](crate::ParseError::Versioned) for callers
+    /// that go on to parse the payload.
+    pub fn open(envelope: &[u8]) -> Result<(u16, &[u8]), VersionedError> {
+        let magic_len = envelope.len().min(MAGIC.len());
+        if envelope[..magic_len] != MAGIC[..magic_len] {
+            return Err(VersionedError::BadMagic);
+        }
+        let (header, diffset) = envelope
+            .split_at_checked(HEADER_LEN)
+            .ok_or(VersionedError::Truncated)?;
+        let version = u16::from_be_bytes([header[MAGIC.len()], header[MAGIC.len() + 1]]);
+        Ok((version, diffset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_payload_round_trip() {
+        let envelope = VersionedChangeset::wrap(&[], 0x0102);
+        assert_eq!(envelope, [b'S', b'Q', b'V', b'C', 0x01, 0x02]);
+        assert_eq!(VersionedChangeset::open(&envelope), Ok((0x0102, &[][..])));
+    }
+
+    #[test]
+    fn test_bad_magic_is_rejected() {
+        assert_eq!(
+            VersionedChangeset::open(b"T\x02\x01\x00users\x00"),
+            Err(VersionedError::BadMagic)
+        );
+        assert_eq!(
+            VersionedChangeset::open(b"T"),
+            Err(VersionedError::BadMagic)
+        );
+    }
+
+    #[test]
+    fn test_truncated_header_is_rejected() {
+        assert_eq!(
+            VersionedChangeset::open(b"SQVC\x01"),
+            Err(VersionedError::Truncated)
+        );
+        assert_eq!(
+            VersionedChangeset::open(b"SQ"),
+            Err(VersionedError::Truncated)
+        );
+    }
+}
@@ -24,7 +24,7 @@
 //! assert_eq!(msg.table.as_deref(), Some("users"));
 //! ```
 
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
@@ -163,6 +163,11 @@ pub enum ConversionError {
     #[error("Column '{0}' not found in table schema")]
     ColumnNotFound(String),
 
+    /// The event sets a column twice, under one name or two that resolve to
+    /// the same column.
+    #[error("Column '{0}' set more than once")]
+    DuplicateColumn(String),
+
     /// The table name in the message doesn't match the expected schema.
     #[error("Table name mismatch: expected '{expected}', got '{actual}'")]
     TableMismatch {
@@ -187,9 +192,26 @@ pub enum ConversionError {
     /// User-registered decoder rejected a column payload.
     #[error("Decoder failed: {0}")]
     Decode(#[from] crate::wire::DecodeError),
+
+    /// A message in a batch could not be parsed by [`parse_v2`].
+    #[error("Malformed message: {0}")]
+    MalformedMessage(String),
+
+    /// A message carries an LSN that is not in `hi/lo` hex notation.
+    #[error("Invalid LSN '{0}'")]
+    InvalidLsn(String),
+}
+
+impl From<PlacementError<'_>> for ConversionError {
+    fn from(error: PlacementError<'_>) -> Self {
+        match error {
+            PlacementError::NotFound(name) => Self::ColumnNotFound(name.into()),
+            PlacementError::Duplicate(name) => Self::DuplicateColumn(name.into()),
+        }
+    }
 }
 
-use crate::wire::{Sealed, WireSource, WireType};
+use crate::wire::{ColumnPlacer, PlacementError, Sealed, WireSource, WireType};
 
 /// Marker type for the `wal2json` source.
 #[derive(Debug, Clone, Copy, Default)]
@@ -241,12 +263,13 @@ impl Wal2JsonColumn<'_> {
 }
 
 use crate::builders::{
-    ChangeDelete, ChangesetFormat, DiffOps, DiffSetBuilder, Insert, PatchDelete, PatchsetFormat,
-    Update,
+    ChangeDelete, ChangesetFormat, DiffSetBuilder, Insert, PatchDelete, PatchsetFormat, Update,
 };
 use crate::encoding::Value;
 use crate::schema::NamedColumns;
-use crate::wire::{Digestable, WireAdapter, WireColumnTypes, WireSchema};
+use crate::wire::{
+    Digestable, MaybeOperation, WireAdapter, WireColumnTypes, WireOperation, WireSchema,
+};
 use alloc::boxed::Box;
 use core::fmt::Debug;
 use core::hash::Hash;
@@ -269,18 +292,17 @@ where
     type Src = Wal2Json;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<ChangesetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<ChangesetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<ChangesetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Wal2Json, S, B>,
     {
         let Some(table_name) = self.table.as_deref() else {
-            return Ok(builder);
+            return Ok(None);
         };
         match self.action {
             Action::I => {
@@ -290,7 +312,7 @@ where
                     .as_ref()
                     .ok_or(ConversionError::MissingColumns)?;
                 let insert = build_insert_from_v2(columns, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             Action::U => {
                 let table = resolve_table(schema, table_name)?;
@@ -304,7 +326,7 @@ where
                     table,
                     adapter,
                 )?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             Action::D => {
                 let table = resolve_table(schema, table_name)?;
@@ -313,9 +335,9 @@ where
                     .as_ref()
                     .ok_or(ConversionError::MissingColumns)?;
                 let delete = build_changeset_delete_from_columns(identity, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            Action::B | Action::C | Action::T | Action::M => Ok(builder),
+            Action::B | Action::C | Action::T | Action::M => Ok(None),
         }
     }
 }
@@ -329,18 +351,17 @@ where
     type Src = Wal2Json;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<PatchsetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<PatchsetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<PatchsetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Wal2Json, S, B>,
     {
         let Some(table_name) = self.table.as_deref() else {
-            return Ok(builder);
+            return Ok(None);
         };
         match self.action {
             Action::I => {
@@ -350,7 +371,7 @@ where
                     .as_ref()
                     .ok_or(ConversionError::MissingColumns)?;
                 let insert = build_insert_from_v2(columns, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             Action::U => {
                 let table = resolve_table(schema, table_name)?;
@@ -359,7 +380,7 @@ where
                     .as_ref()
                     .ok_or(ConversionError::MissingColumns)?;
                 let update = build_patchset_update_from_v2(columns, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             Action::D => {
                 let table = resolve_table(schema, table_name)?;
@@ -368,9 +389,9 @@ where
                     .as_ref()
                     .ok_or(ConversionError::MissingColumns)?;
                 let delete = build_patch_delete_from_columns(identity, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            Action::B | Action::C | Action::T | Action::M => Ok(builder),
+            Action::B | Action::C | Action::T | Action::M => Ok(None),
         }
     }
 }
@@ -384,12 +405,11 @@ where
     type Src = Wal2Json;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<ChangesetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<ChangesetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<ChangesetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Wal2Json, S, B>,
@@ -398,17 +418,17 @@ where
         match self.kind.as_str() {
             "insert" => {
                 let insert = build_insert_from_v1(self, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             "update" => {
                 let update = build_changeset_update_from_v1(self, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             "delete" => {
                 let delete = build_changeset_delete_from_v1(self, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            _ => Ok(builder),
+            _ => Ok(None),
         }
     }
 }
@@ -422,12 +442,11 @@ where
     type Src = Wal2Json;
     type Error = ConversionError;
 
-    fn digest_into<Sch, A>(
+    fn to_operation<Sch, A>(
         &self,
-        builder: DiffSetBuilder<PatchsetFormat, T, S, B>,
         schema: &Sch,
         adapter: &A,
-    ) -> Result<DiffSetBuilder<PatchsetFormat, T, S, B>, ConversionError>
+    ) -> Result<MaybeOperation<PatchsetFormat, T, S, B>, ConversionError>
     where
         Sch: WireSchema<Table = T>,
         A: WireAdapter<Wal2Json, S, B>,
@@ -436,17 +455,17 @@ where
         match self.kind.as_str() {
             "insert" => {
                 let insert = build_insert_from_v1(self, table, adapter)?;
-                Ok(DiffOps::insert(builder, insert))
+                Ok(Some(WireOperation::Insert(insert)))
             }
             "update" => {
                 let update = build_patchset_update_from_v1(self, table, adapter)?;
-                Ok(DiffOps::update(builder, update))
+                Ok(Some(WireOperation::Update(update)))
             }
             "delete" => {
                 let delete = build_patch_delete_from_v1(self, table, adapter)?;
-                Ok(DiffOps::delete(builder, delete))
+                Ok(Some(WireOperation::Delete(delete)))
             }
-            _ => Ok(builder),
+            _ => Ok(None),
         }
     }
 }
@@ -465,10 +484,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut insert = Insert::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for col in columns {
-        let col_idx = table
-            .column_index(&col.name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(col.name.clone()))?;
+        let col_idx = placer.place(table, &col.name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: col.name.as_str(),
@@ -496,10 +514,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut update: Update<T, ChangesetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for col in columns {
-        let col_idx = table
-            .column_index(&col.name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(col.name.clone()))?;
+        let col_idx = placer.place(table, &col.name)?;
         let wire_type = table.column_type(col_idx);
         let new = adapter.decode(Wal2JsonColumn {
             column_name: col.name.as_str(),
@@ -509,16 +526,28 @@ where
 
         // Pair the new value with its old-row value from the identity image
         // when present, so a primary-key change keeps the old key for the
-        // WHERE clause. Non-key columns absent from the identity fall back to
-        // set_new.
-        if let Some(old_col) = identity.and_then(|id| id.iter().find(|c| c.name == col.name)) {
-            let old = adapter.decode(Wal2JsonColumn {
-                column_name: col.name.as_str(),
-                wire_type,
-                value: &old_col.value,
-            })?;
+        // WHERE clause; under REPLICA IDENTITY FULL this yields the whole old
+        // row. Without an identity image the key did not change, so primary
+        // key columns reuse their new value as the old one. Remaining columns
+        // fall back to set_new.
+        if let Some(identity) = identity {
+            if let Some(old_col) = identity.iter().find(|c| c.name == col.name) {
+                let old = adapter.decode(Wal2JsonColumn {
+                    column_name: col.name.as_str(),
+                    wire_type,
+                    value: &old_col.value,
+                })?;
+                update = update
+                    .set(col_idx, old, new)
+                    .map_err(|_| ConversionError::ColumnNotFound(col.name.clone()))?;
+            } else {
+                update = update
+                    .set_new(col_idx, new)
+                    .map_err(|_| ConversionError::ColumnNotFound(col.name.clone()))?;
+            }
+        } else if table.primary_key_index(col_idx).is_some() {
             update = update
-                .set(col_idx, old, new)
+                .set(col_idx, new.clone(), new)
                 .map_err(|_| ConversionError::ColumnNotFound(col.name.clone()))?;
         } else {
             update = update
@@ -541,10 +570,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut update: Update<T, PatchsetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for col in columns {
-        let col_idx = table
-            .column_index(&col.name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(col.name.clone()))?;
+        let col_idx = placer.place(table, &col.name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: col.name.as_str(),
@@ -571,10 +599,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut delete = ChangeDelete::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for col in identity {
-        let col_idx = table
-            .column_index(&col.name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(col.name.clone()))?;
+        let col_idx = placer.place(table, &col.name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: col.name.as_str(),
@@ -603,10 +630,9 @@ where
     let num_pks = table.number_of_primary_keys();
     let mut pk_slots: Vec<Option<Value<S, B>>> = alloc::vec![None; num_pks];
 
+    let mut placer = ColumnPlacer::new(table);
     for col in identity {
-        let col_idx = table
-            .column_index(&col.name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(col.name.clone()))?;
+        let col_idx = placer.place(table, &col.name)?;
         if let Some(pk_idx) = table.primary_key_index(col_idx) {
             let wire_type = table.column_type(col_idx);
             let payload = Wal2JsonColumn {
@@ -655,10 +681,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut insert = Insert::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in iter_v1_columns(change) {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.into()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: name,
@@ -685,10 +710,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut update: Update<T, ChangesetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in iter_v1_columns(change) {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.into()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let new = adapter.decode(Wal2JsonColumn {
             column_name: name,
@@ -733,10 +757,9 @@ where
     A: WireAdapter<Wal2Json, S, B>,
 {
     let mut update: Update<T, PatchsetFormat, S, B> = Update::from(table.clone());
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in iter_v1_columns(change) {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.into()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: name,
@@ -769,10 +792,9 @@ where
         } else {
             Box::new(iter_v1_columns(change))
         };
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in key_iter {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.into()))?;
+        let col_idx = placer.place(table, name)?;
         let wire_type = table.column_type(col_idx);
         let payload = Wal2JsonColumn {
             column_name: name,
@@ -806,10 +828,9 @@ where
     let num_pks = table.number_of_primary_keys();
     let mut pk_slots: Vec<Option<Value<S, B>>> = alloc::vec![None; num_pks];
 
+    let mut placer = ColumnPlacer::new(table);
     for (name, value) in iter_v1_oldkeys(oldkeys) {
-        let col_idx = table
-            .column_index(name)
-            .ok_or_else(|| ConversionError::ColumnNotFound(name.into()))?;
+        let col_idx = placer.place(table, name)?;
         if let Some(pk_idx) = table.primary_key_index(col_idx) {
             let wire_type = table.column_type(col_idx);
             let payload = Wal2JsonColumn {
@@ -970,3 +991,51 @@ mod arbitrary_impl {
         }
     }
 }
+
+/// Digest a batch of wal2json v2 messages, as read from a replication slot,
+/// into one serialized changeset.
+///
+/// Every message is parsed with [`parse_v2`] and digested in order, so the
+/// operations on a row consolidate as they would through
+/// [`DiffSetBuilder::digest`]. Alongside the changeset comes the highest
+/// LSN any message carried, as the 64-bit `hi << 32 | lo` position to
+/// confirm to the slot once the changeset is applied, or `None` if wal2json
+/// ran without `include-lsn`.
+///
+/// # Errors
+///
+/// Returns [`ConversionError::MalformedMessage`] if a message does not
+/// parse, [`ConversionError::InvalidLsn`] if its LSN is malformed, or the
+/// first error raised while digesting a message.
+pub fn slot_batch_to_changeset<'a, I, Sch, A>(
+    messages: I,
+    schema: &Sch,
+    adapter: &A,
+) -> Result<(Vec<u8>, Option<u64>), ConversionError>
+where
+    I: IntoIterator<Item = &'a str>,
+    Sch: WireSchema,
+    A: WireAdapter<Wal2Json, String, Vec<u8>>,
+{
+    let mut builder: DiffSetBuilder<ChangesetFormat, Sch::Table, String, Vec<u8>> =
+        DiffSetBuilder::default();
+    let mut max_lsn = None;
+    for line in messages {
+        let message =
+            parse_v2(line).map_err(|err| ConversionError::MalformedMessage(err.to_string()))?;
+        if let Some(lsn) = message.lsn.as_deref() {
+            let lsn = parse_lsn(lsn).ok_or_else(|| ConversionError::InvalidLsn(lsn.into()))?;
+            max_lsn = max_lsn.max(Some(lsn));
+        }
+        builder = message.digest_into(builder, schema, adapter)?;
+    }
+    Ok((builder.build(), max_lsn))
+}
+
+/// Parse an LSN in `hi/lo` hex notation into its 64-bit position.
+fn parse_lsn(lsn: &str) -> Option<u64> {
+    let (hi, lo) = lsn.split_once('/')?;
+    let hi = u32::from_str_radix(hi, 16).ok()?;
+    let lo = u32::from_str_radix(lo, 16).ok()?;
+    Some(u64::from(hi) << 32 | u64::from(lo))
+}
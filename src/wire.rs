@@ -1,17 +1,19 @@
 //! Schema-aware forward conversion from CDC wire formats into
 //! [`Value`](crate::encoding::Value).
 //!
-//! Every supported wire source (`pg_walstream`, `wal2json`, `maxwell`)
-//! carries per-column type metadata alongside the raw value bytes. This
-//! module funnels the three sources through one shared decoding contract
-//! so users can register a single type-to-decoder mapping and consume
-//! multiple wire formats interchangeably.
+//! Every supported wire source (`pg_walstream`, `wal2json`, `maxwell`,
+//! `debezium`) delivers rows as per-column raw values, typed by the schema
+//! through [`WireColumnTypes`] rather than by the wire: flattened `debezium`
+//! records, for one, carry no type metadata at all. This module funnels the
+//! sources through one shared decoding contract so users can register a
+//! single type-to-decoder mapping and consume multiple wire formats
+//! interchangeably.
 //!
 //! # Shape
 //!
 //! - [`WireSource`] (sealed): per-source marker with an associated
 //!   payload struct. Each payload carries a semantic [`WireType`].
-//!   Implemented by `PgWalstream`, `Wal2Json`, `Maxwell`.
+//!   Implemented by `PgWalstream`, `Wal2Json`, `Maxwell`, `Debezium`.
 //! - [`Decoder`]: one implementation per (source, semantic) pair.
 //!   Zero-sized unit types for stateless decoders (`BoolDecoder`,
 //!   `IntDecoder`, ...), state-carrying structs for user config.
@@ -22,19 +24,43 @@
 //! - [`TypeMapDefaults`]: per-source `defaults()` builder for a
 //!   [`TypeMap`] pre-populated with the crate's self-evident mappings.
 mod adapter;
-#[cfg(any(feature = "wal2json", feature = "maxwell", feature = "pg-walstream"))]
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
 mod bytes_helpers;
 mod decoder;
 mod error;
-#[cfg(any(feature = "wal2json", feature = "maxwell", feature = "pg-walstream"))]
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
 mod json_helpers;
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
+mod placement;
 mod sealed;
 mod source;
 mod type_map;
-#[cfg(any(feature = "wal2json", feature = "maxwell", feature = "pg-walstream"))]
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
 mod uuid_helpers;
 mod wire_type;
 
+#[cfg(feature = "debezium")]
+mod impls_debezium;
 #[cfg(feature = "maxwell")]
 mod impls_maxwell;
 #[cfg(feature = "pg-walstream")]
@@ -45,15 +71,34 @@ mod impls_wal2json;
 pub use adapter::WireAdapter;
 pub use decoder::Decoder;
 pub use decoder::{
-    BoolDecoder, DateVerbatimDecoder, DecimalTextDecoder, Int64OverflowToTextDecoder, IntDecoder,
-    IntervalVerbatimDecoder, JsonCanonicalDecoder, JsonVerbatimDecoder, MySqlBinaryDecoder,
-    NullDecoder, PgByteaBinaryDecoder, PgByteaTextModeDecoder, RealDecoder, TextDecoder,
-    TimeVerbatimDecoder, TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, UuidBlob16Decoder,
-    UuidText36Decoder,
+    BoolDecoder, BoundedBlobDecoder, DateVerbatimDecoder, DecimalTextDecoder,
+    Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder,
+    JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder,
+    PgByteaTextModeDecoder, RealDecoder, TextDecoder, TextOrJsonDecoder, TimeVerbatimDecoder,
+    TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, UuidBlob16Decoder, UuidText36Decoder,
 };
 pub use error::DecodeError;
-#[cfg(any(feature = "wal2json", feature = "maxwell", feature = "pg-walstream"))]
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
+pub(crate) use placement::{ColumnPlacer, PlacementError};
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
 pub(crate) use sealed::Sealed;
-pub use source::{Digestable, WireColumnTypes, WireSchema, WireSource};
+#[cfg(any(
+    feature = "wal2json",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "debezium"
+))]
+pub(crate) use source::MaybeOperation;
+pub use source::{Digestable, Enrichment, WireColumnTypes, WireOperation, WireSchema, WireSource};
 pub use type_map::{TypeMap, TypeMapDefaults};
 pub use wire_type::WireType;
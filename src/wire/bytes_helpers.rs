@@ -1,8 +1,8 @@
 //! Byte-decoding helpers for binary and hex escape payloads.
 //!
 //! Two small `no_std`-clean routines: PG `\xHEX` decode for text-mode
-//! BYTEA, and standard base64 (RFC 4648) decode for MySQL wire values
-//! delivered as JSON strings. Both are vendored to avoid pulling
+//! BYTEA, and standard base64 (RFC 4648) decode for Maxwell and Debezium
+//! wire values delivered as JSON strings. Both are vendored to avoid pulling
 //! external deps for a hundred lines of parsing.
 
 use alloc::vec::Vec;
@@ -41,6 +41,13 @@ pub(crate) fn decode_wal2json_bytea_hex(s: &str) -> Result<Vec<u8>, usize> {
     decode_hex(hex.as_bytes())
 }
 
+/// Number of bytes a hex payload decodes to, ignoring any `\x` prefix.
+/// Computed from the length alone, without validating the characters.
+#[cfg(any(feature = "wal2json", feature = "pg-walstream"))]
+pub(crate) fn hex_decoded_len(s: &str) -> usize {
+    s.strip_prefix("\\x").unwrap_or(s).len() / 2
+}
+
 /// Decode an even-length hex slice into raw bytes.
 ///
 /// # Errors
@@ -75,7 +82,7 @@ fn hex_nibble(b: u8) -> Option<u8> {
 /// Decode a standard base64 (RFC 4648) string, tolerant of trailing
 /// `=` padding. Returns `Err(())` on any invalid character or invalid
 /// length.
-#[cfg(feature = "maxwell")]
+#[cfg(any(feature = "maxwell", feature = "debezium"))]
 pub(crate) fn decode_base64(s: &str) -> Result<Vec<u8>, ()> {
     let src = s.as_bytes();
     // Strip trailing '=' padding for length calc.
@@ -102,7 +109,15 @@ pub(crate) fn decode_base64(s: &str) -> Result<Vec<u8>, ()> {
     Ok(out)
 }
 
-#[cfg(feature = "maxwell")]
+/// Number of bytes a base64 payload decodes to, ignoring trailing `=`
+/// padding. Computed from the length alone, without validating the
+/// characters.
+#[cfg(any(feature = "maxwell", feature = "debezium"))]
+pub(crate) fn base64_decoded_len(s: &str) -> usize {
+    s.trim_end_matches('=').len() * 3 / 4
+}
+
+#[cfg(any(feature = "maxwell", feature = "debezium"))]
 #[inline]
 fn base64_char(c: u8) -> Option<u8> {
     match c {
@@ -192,7 +207,7 @@ mod tests {
         assert!(decode_pg_hex_escape("\\xzz").is_err());
     }
 
-    #[cfg(feature = "maxwell")]
+    #[cfg(any(feature = "maxwell", feature = "debezium"))]
     #[test]
     fn base64_roundtrip() {
         assert_eq!(
@@ -203,13 +218,13 @@ mod tests {
         assert_eq!(decode_base64("").unwrap(), Vec::<u8>::new());
     }
 
-    #[cfg(feature = "maxwell")]
+    #[cfg(any(feature = "maxwell", feature = "debezium"))]
     #[test]
     fn base64_invalid_char() {
         assert!(decode_base64("!!!!").is_err());
     }
 
-    #[cfg(feature = "maxwell")]
+    #[cfg(any(feature = "maxwell", feature = "debezium"))]
     #[test]
     fn base64_invalid_length() {
         assert!(decode_base64("abc").is_err());
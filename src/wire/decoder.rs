@@ -26,7 +26,7 @@ pub trait Decoder<Src: WireSource, S, B> {
 // ------------------------------------------------------------------
 // Built-in decoder unit types. Every one is a zero-sized marker whose
 // `impl Decoder<Src, S, B>` for each supported `Src` lives in the format
-// module (`pg_walstream`, `wal2json`, `maxwell`).
+// module (`pg_walstream`, `wal2json`, `maxwell`, `debezium`).
 // ------------------------------------------------------------------
 
 /// Decoder producing `Value::Null` regardless of the payload's non-null
@@ -57,6 +57,13 @@ pub struct RealDecoder;
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TextDecoder;
 
+/// Decoder for text-affinity columns that may also carry JSON arrays or
+/// objects, such as `PostgreSQL` array columns. Those are stored as their
+/// JSON text; other payloads decode as with [`TextDecoder`], which rejects
+/// arrays and objects and stays the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextOrJsonDecoder;
+
 /// Decoder for pg_walstream binary-mode BYTEA.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct PgByteaBinaryDecoder;
@@ -65,10 +72,65 @@ pub struct PgByteaBinaryDecoder;
 #[derive(Debug, Clone, Copy, Default)]
 pub struct PgByteaTextModeDecoder;
 
-/// Decoder for MySQL binary-family columns (base64 on the wire).
+/// Decoder for base64-encoded binary columns: MySQL binary-family columns
+/// from Maxwell, and binary columns from Debezium.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MySqlBinaryDecoder;
 
+/// Wrapper rejecting blob payloads that would decode to more than
+/// `max_len` bytes.
+///
+/// The check runs on the encoded payload (base64 or hex length) before
+/// `inner` decodes anything, so an adversarially large field fails with
+/// [`DecodeError::ValueTooLarge`] instead of allocating. The shipped
+/// [`TypeMap::defaults`](super::TypeMap::defaults) registries stay
+/// unbounded; opt in by registering, say,
+/// `BoundedBlobDecoder::new(MySqlBinaryDecoder, 1 << 20)` for
+/// `WireType::Bytes`.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundedBlobDecoder<D> {
+    pub(super) inner: D,
+    pub(super) max_len: usize,
+}
+
+impl<D> BoundedBlobDecoder<D> {
+    /// Bound `inner` to blobs of at most `max_len` decoded bytes.
+    #[must_use]
+    pub const fn new(inner: D, max_len: usize) -> Self {
+        Self { inner, max_len }
+    }
+
+    /// The wrapped decoder.
+    #[must_use]
+    pub const fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Largest decoded blob length accepted, in bytes.
+    #[must_use]
+    pub const fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Reject a payload whose decoded length exceeds the bound.
+    #[cfg(any(
+        feature = "wal2json",
+        feature = "maxwell",
+        feature = "pg-walstream",
+        feature = "debezium"
+    ))]
+    pub(super) fn check(&self, column: &str, len: usize) -> Result<(), DecodeError> {
+        if len > self.max_len {
+            return Err(DecodeError::ValueTooLarge {
+                column: alloc::string::ToString::to_string(column),
+                len,
+                max: self.max_len,
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Decoder rendering UUID wire text into a 16-byte `Value::Blob`.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct UuidBlob16Decoder;
@@ -2,7 +2,8 @@
 //! [`wire`](super) module.
 //!
 //! Per-format `ConversionError` types (in `pg_walstream::ConversionError`,
-//! `wal2json::ConversionError`, `maxwell::ConversionError`) wrap this
+//! `wal2json::ConversionError`, `maxwell::ConversionError`,
+//! `debezium::ConversionError`) wrap this
 //! via a `Decode(DecodeError)` variant so users can pattern-match on
 //! the outer error and route to a common inner arm.
 
@@ -97,6 +98,18 @@ pub enum DecodeError {
         actual: &'static str,
     },
 
+    /// Blob payload would decode to more bytes than the registered
+    /// [`BoundedBlobDecoder`](super::BoundedBlobDecoder) allows.
+    #[error("column {column:?} blob of {len} bytes exceeds the {max}-byte limit")]
+    ValueTooLarge {
+        /// Offending column name.
+        column: String,
+        /// Decoded length implied by the encoded payload.
+        len: usize,
+        /// Configured maximum decoded length.
+        max: usize,
+    },
+
     /// Free-form failure emitted by user-supplied decoders.
     #[error("column {column:?}: {message}")]
     Custom {
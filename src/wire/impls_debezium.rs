@@ -0,0 +1,595 @@
+//! `Decoder` implementations and `TypeMapDefaults` for the `Debezium` source.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use super::decoder::{
+    BoolDecoder, BoundedBlobDecoder, DateVerbatimDecoder, DecimalTextDecoder, Decoder,
+    Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder,
+    JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder,
+    PgByteaTextModeDecoder, RealDecoder, TextDecoder, TextOrJsonDecoder, TimeVerbatimDecoder,
+    TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, UuidBlob16Decoder, UuidText36Decoder,
+};
+use super::error::DecodeError;
+use super::type_map::{TypeMap, TypeMapDefaults};
+use super::wire_type::WireType;
+use crate::debezium::{Debezium, DebeziumColumn};
+use crate::encoding::Value;
+
+impl<S, B> Decoder<Debezium, S, B> for NullDecoder {
+    fn decode(&self, _payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        Ok(Value::Null)
+    }
+}
+
+// ------------------------------------------------------------------
+// BoolDecoder
+//
+// Debezium delivers booleans as JSON `true`/`false`, and MySQL
+// `tinyint(1)` columns as integer 0/1 unless the connector maps them
+// to booleans. Both are accepted. Null pass-through. Anything else -> WrongPayloadKind.
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for BoolDecoder {
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::Bool(b) => Ok(Value::Integer(i64::from(*b))),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(0) => Ok(Value::Integer(0)),
+                Some(1) => Ok(Value::Integer(1)),
+                _ => Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "JSON bool or number 0/1",
+                    actual: "number outside {0, 1}",
+                }),
+            },
+            serde_json::Value::String(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON bool or number 0/1",
+                actual: "JSON string",
+            }),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "JSON bool or number 0/1",
+                    actual: "JSON array or object",
+                })
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// IntDecoder
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for IntDecoder {
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None => Err(DecodeError::IntegerOverflow {
+                    column: payload.column_name.to_string(),
+                    digits: n.to_string(),
+                }),
+            },
+            serde_json::Value::Bool(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON integer number",
+                actual: "JSON boolean",
+            }),
+            serde_json::Value::String(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON integer number",
+                actual: "JSON string",
+            }),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "JSON integer number",
+                    actual: "JSON array or object",
+                })
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// Int64OverflowToTextDecoder
+//
+// Load-bearing for MySQL `bigint unsigned` columns captured with
+// `bigint.unsigned.handling.mode=precise`, whose wire values can
+// exceed `i64::MAX`.
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for Int64OverflowToTextDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None => Ok(Value::Text(S::from(n.to_string()))),
+            },
+            serde_json::Value::String(s)
+                if s.trim_start_matches('-')
+                    .chars()
+                    .all(|c| c.is_ascii_digit()) =>
+            {
+                match s.parse::<i64>() {
+                    Ok(i) => Ok(Value::Integer(i)),
+                    Err(_) => Ok(Value::Text(S::from(s.clone()))),
+                }
+            }
+            _ => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON integer number or numeric string",
+                actual: "other JSON shape",
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// RealDecoder
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for RealDecoder {
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::Number(n) => match n.as_f64() {
+                Some(f) => Ok(normalize_real(f)),
+                None => Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "IEEE 754 float number",
+                    actual: "arbitrary-precision JSON number",
+                }),
+            },
+            serde_json::Value::Bool(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "IEEE 754 float number",
+                actual: "JSON boolean",
+            }),
+            serde_json::Value::String(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "IEEE 754 float number",
+                actual: "JSON string",
+            }),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "IEEE 754 float number",
+                    actual: "JSON array or object",
+                })
+            }
+        }
+    }
+}
+
+#[inline]
+fn normalize_real<S, B>(f: f64) -> Value<S, B> {
+    if f.is_nan() {
+        Value::Null
+    } else if f == 0.0 {
+        Value::Real(0.0)
+    } else {
+        Value::Real(f)
+    }
+}
+
+// ------------------------------------------------------------------
+// TextDecoder
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for TextDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => Ok(Value::Text(S::from(s.clone()))),
+            serde_json::Value::Bool(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON string",
+                actual: "JSON boolean",
+            }),
+            serde_json::Value::Number(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON string",
+                actual: "JSON number",
+            }),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "JSON string",
+                    actual: "JSON array or object",
+                })
+            }
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// TextOrJsonDecoder
+//
+// Opt-in relaxation of `TextDecoder` for PG array and similar columns,
+// which Debezium delivers as JSON arrays. Arrays and objects are stored
+// as their compact JSON text, everything else decodes as text does.
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for TextOrJsonDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                payload.decoded_by(&JsonVerbatimDecoder)
+            }
+            _ => payload.decoded_by(&TextDecoder),
+        }
+    }
+}
+
+macro_rules! not_yet_impl {
+    ($decoder:ty) => {
+        impl<S, B> Decoder<Debezium, S, B> for $decoder {
+            fn decode(&self, _payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+                Err(DecodeError::NotYetImplemented {
+                    decoder: stringify!($decoder),
+                })
+            }
+        }
+    };
+}
+
+// ------------------------------------------------------------------
+// MySqlBinaryDecoder
+//
+// Debezium delivers binary columns (PG `bytea`, MySQL binary family)
+// as base64-encoded JSON strings under the default
+// `binary.handling.mode=bytes`. Base64 decode via the vendored helper.
+// Null pass-through.
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for MySqlBinaryDecoder
+where
+    B: From<Vec<u8>>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => match super::bytes_helpers::decode_base64(s) {
+                Ok(bytes) => Ok(Value::Blob(B::from(bytes))),
+                Err(()) => Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "base64 string",
+                    actual: "malformed base64",
+                }),
+            },
+            _ => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON base64 string",
+                actual: "other JSON shape",
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// BoundedBlobDecoder
+//
+// Bounds the length implied by a base64 JSON string before delegating.
+// Other payload shapes go straight to the inner decoder.
+// ------------------------------------------------------------------
+
+impl<D, S, B> Decoder<Debezium, S, B> for BoundedBlobDecoder<D>
+where
+    D: Decoder<Debezium, S, B>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        if let serde_json::Value::String(s) = payload.value {
+            self.check(
+                payload.column_name,
+                super::bytes_helpers::base64_decoded_len(s),
+            )?;
+        }
+        self.inner.decode(payload)
+    }
+}
+
+// ------------------------------------------------------------------
+// UuidBlob16Decoder and UuidText36Decoder
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for UuidBlob16Decoder
+where
+    B: From<Vec<u8>>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => match super::uuid_helpers::parse_uuid(s) {
+                Ok(bytes) => Ok(Value::Blob(B::from(bytes.to_vec()))),
+                Err(source_len) => Err(DecodeError::InvalidUuid {
+                    column: payload.column_name.to_string(),
+                    source_len,
+                }),
+            },
+            _ => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON UUID string",
+                actual: "other JSON shape",
+            }),
+        }
+    }
+}
+
+impl<S, B> Decoder<Debezium, S, B> for UuidText36Decoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => {
+                match super::uuid_helpers::preserve_or_canonicalize_uuid_text(s) {
+                    Ok(canonical) => Ok(Value::Text(S::from(canonical))),
+                    Err(source_len) => Err(DecodeError::InvalidUuid {
+                        column: payload.column_name.to_string(),
+                        source_len,
+                    }),
+                }
+            }
+            _ => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON UUID string",
+                actual: "other JSON shape",
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// DecimalTextDecoder
+//
+// `decimal.handling.mode=double` emits a JSON number and `string` a
+// JSON string of decimal digits. The default `precise` mode emits the
+// unscaled value as base64 bytes, whose scale is only in the schema a
+// flattened record drops, so a string that is not a decimal number is
+// rejected rather than stored as text. A base64 value made of digits
+// alone cannot be told apart from one.
+// ------------------------------------------------------------------
+
+/// Whether `s` spells a decimal number, as in `-12.50` or `1e-3`, or one
+/// of the `NaN` and signed `Infinity` a `PostgreSQL` `numeric` may hold.
+fn is_decimal_text(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+    if unsigned == "Infinity" || s == "NaN" {
+        return true;
+    }
+    let (mantissa, exponent) = unsigned
+        .split_once(['e', 'E'])
+        .map_or((unsigned, None), |(mantissa, exponent)| {
+            (mantissa, Some(exponent))
+        });
+    let (integral, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    all_digits(integral)
+        && all_digits(fraction)
+        && !(integral.is_empty() && fraction.is_empty())
+        && exponent.is_none_or(|exponent| {
+            let digits = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+            !digits.is_empty() && all_digits(digits)
+        })
+}
+
+impl<S, B> Decoder<Debezium, S, B> for DecimalTextDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) if is_decimal_text(s) => {
+                Ok(Value::Text(S::from(s.clone())))
+            }
+            serde_json::Value::String(_) => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON string or number decimal",
+                actual: "JSON string that is no decimal number, such as a precise-mode base64 value",
+            }),
+            // `serde_json::Number::to_string` preserves parsed
+            // digits; callers who need arbitrary precision should
+            // enable serde_json's `arbitrary_precision` feature.
+            serde_json::Value::Number(n) => Ok(Value::Text(S::from(n.to_string()))),
+            _ => Err(DecodeError::WrongPayloadKind {
+                column: payload.column_name.to_string(),
+                expected: "JSON string or number decimal",
+                actual: "other JSON shape",
+            }),
+        }
+    }
+}
+
+// ------------------------------------------------------------------
+// Temporal verbatim decoders
+//
+// Accept the ISO-8601 strings Debezium emits for zoned types
+// (`io.debezium.time.ZonedTimestamp`, `ZonedTime`) and for intervals
+// under `interval.handling.mode=string`. A date may also arrive as
+// the day count of `io.debezium.time.Date`, which is rendered as the
+// ISO-8601 date the other sources deliver. The epoch numbers of
+// timestamps and times (`Timestamp`, `MicroTimestamp`,
+// `NanoTimestamp`, `Time`, `MicroTime`, `NanoTime`) count units that
+// only the schema names, so they are rejected as a payload kind
+// mismatch: convert them to strings upstream or register a decoder
+// that knows the unit.
+// ------------------------------------------------------------------
+
+fn decode_debezium_string_verbatim<S, B>(
+    payload: DebeziumColumn<'_>,
+) -> Result<Value<S, B>, DecodeError>
+where
+    S: From<alloc::string::String>,
+{
+    match payload.value {
+        serde_json::Value::Null => Ok(Value::Null),
+        serde_json::Value::String(s) => Ok(Value::Text(S::from(s.clone()))),
+        serde_json::Value::Number(_) => Err(DecodeError::WrongPayloadKind {
+            column: payload.column_name.to_string(),
+            expected: "JSON string",
+            actual: "JSON number, an epoch form whose unit only the schema gives",
+        }),
+        _ => Err(DecodeError::WrongPayloadKind {
+            column: payload.column_name.to_string(),
+            expected: "JSON string",
+            actual: "other JSON shape",
+        }),
+    }
+}
+
+/// Renders `days` since 1970-01-01 as an ISO-8601 `YYYY-MM-DD` date, or
+/// `None` if the year falls outside `0000` to `9999`.
+fn iso_date(days: i64) -> Option<alloc::string::String> {
+    // Days since 0000-03-01, counted in 400-year eras of 146097 days, so
+    // that the leap day ends the year.
+    let shifted = days.checked_add(719_468)?;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era
+        .checked_mul(400)?
+        .checked_add(year_of_era + i64::from(month <= 2))?;
+    (0..=9999)
+        .contains(&year)
+        .then(|| alloc::format!("{year:04}-{month:02}-{day:02}"))
+}
+
+impl<S, B> Decoder<Debezium, S, B> for DateVerbatimDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Number(n) => match n.as_i64().and_then(iso_date) {
+                Some(date) => Ok(Value::Text(S::from(date))),
+                None => Err(DecodeError::WrongPayloadKind {
+                    column: payload.column_name.to_string(),
+                    expected: "JSON string or day count",
+                    actual: "JSON number that is no day count between years 0000 and 9999",
+                }),
+            },
+            _ => decode_debezium_string_verbatim(payload),
+        }
+    }
+}
+
+macro_rules! verbatim_impl {
+    ($decoder:ty) => {
+        impl<S, B> Decoder<Debezium, S, B> for $decoder
+        where
+            S: From<alloc::string::String>,
+        {
+            fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+                decode_debezium_string_verbatim(payload)
+            }
+        }
+    };
+}
+
+verbatim_impl!(TimestampVerbatimDecoder);
+verbatim_impl!(TimestampTzVerbatimDecoder);
+verbatim_impl!(TimeVerbatimDecoder);
+verbatim_impl!(IntervalVerbatimDecoder);
+
+// ------------------------------------------------------------------
+// JsonVerbatimDecoder / JsonCanonicalDecoder
+// ------------------------------------------------------------------
+
+impl<S, B> Decoder<Debezium, S, B> for JsonVerbatimDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => Ok(Value::Text(S::from(s.clone()))),
+            other => match crate::wire::json_helpers::serialize_verbatim(other) {
+                Ok(text) => Ok(Value::Text(S::from(text))),
+                Err(error) => Err(DecodeError::JsonNotSerializable {
+                    column: payload.column_name.to_string(),
+                    error,
+                }),
+            },
+        }
+    }
+}
+
+impl<S, B> Decoder<Debezium, S, B> for JsonCanonicalDecoder
+where
+    S: From<alloc::string::String>,
+{
+    fn decode(&self, payload: DebeziumColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        match payload.value {
+            serde_json::Value::Null => Ok(Value::Null),
+            serde_json::Value::String(s) => {
+                let canon = crate::wire::json_helpers::canonicalize_string(s);
+                Ok(Value::Text(S::from(canon)))
+            }
+            other => match crate::wire::json_helpers::canonicalize_to_string(other) {
+                Ok(text) => Ok(Value::Text(S::from(text))),
+                Err(error) => Err(DecodeError::JsonNotSerializable {
+                    column: payload.column_name.to_string(),
+                    error,
+                }),
+            },
+        }
+    }
+}
+
+not_yet_impl!(PgByteaBinaryDecoder);
+not_yet_impl!(PgByteaTextModeDecoder);
+
+impl<S, B> TypeMapDefaults<S, B> for Debezium
+where
+    S: From<alloc::string::String>,
+    B: From<Vec<u8>>,
+{
+    fn defaults() -> TypeMap<Self, S, B> {
+        // `Int` routes through `Int64OverflowToTextDecoder` so unsigned
+        // 64-bit values above `i64::MAX` are preserved as
+        // base-10 text rather than erroring. In-range integers still
+        // produce `Value::Integer`.
+        TypeMap::new()
+            .with(WireType::Bool, BoolDecoder)
+            .with(WireType::Int, Int64OverflowToTextDecoder)
+            .with(WireType::Real, RealDecoder)
+            .with(WireType::Text, TextDecoder)
+            .with(WireType::Bytes, MySqlBinaryDecoder)
+            .with(WireType::Uuid, UuidText36Decoder)
+            .with(WireType::Decimal, DecimalTextDecoder)
+            .with(WireType::Timestamp, TimestampVerbatimDecoder)
+            .with(WireType::TimestampTz, TimestampTzVerbatimDecoder)
+            .with(WireType::Date, DateVerbatimDecoder)
+            .with(WireType::Time, TimeVerbatimDecoder)
+            .with(WireType::Interval, IntervalVerbatimDecoder)
+            .with(WireType::Json, JsonVerbatimDecoder)
+            .with(WireType::Jsonb, JsonVerbatimDecoder)
+    }
+}
@@ -4,11 +4,11 @@ use alloc::string::ToString;
 use alloc::vec::Vec;
 
 use super::decoder::{
-    BoolDecoder, DateVerbatimDecoder, DecimalTextDecoder, Decoder, Int64OverflowToTextDecoder,
-    IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder, JsonVerbatimDecoder,
-    MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder, PgByteaTextModeDecoder, RealDecoder,
-    TextDecoder, TimeVerbatimDecoder, TimestampTzVerbatimDecoder, TimestampVerbatimDecoder,
-    UuidBlob16Decoder, UuidText36Decoder,
+    BoolDecoder, BoundedBlobDecoder, DateVerbatimDecoder, DecimalTextDecoder, Decoder,
+    Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder,
+    JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder,
+    PgByteaTextModeDecoder, RealDecoder, TextDecoder, TimeVerbatimDecoder,
+    TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, UuidBlob16Decoder, UuidText36Decoder,
 };
 use super::error::DecodeError;
 use super::type_map::{TypeMap, TypeMapDefaults};
@@ -258,6 +258,28 @@ where
     }
 }
 
+// ------------------------------------------------------------------
+// BoundedBlobDecoder
+//
+// Bounds the length implied by a base64 JSON string before delegating.
+// Other payload shapes go straight to the inner decoder.
+// ------------------------------------------------------------------
+
+impl<D, S, B> Decoder<Maxwell, S, B> for BoundedBlobDecoder<D>
+where
+    D: Decoder<Maxwell, S, B>,
+{
+    fn decode(&self, payload: MaxwellColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        if let serde_json::Value::String(s) = payload.value {
+            self.check(
+                payload.column_name,
+                super::bytes_helpers::base64_decoded_len(s),
+            )?;
+        }
+        self.inner.decode(payload)
+    }
+}
+
 // ------------------------------------------------------------------
 // UuidBlob16Decoder and UuidText36Decoder
 // ------------------------------------------------------------------
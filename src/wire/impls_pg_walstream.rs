@@ -4,11 +4,11 @@ use alloc::string::ToString;
 use alloc::vec::Vec;
 
 use super::decoder::{
-    BoolDecoder, DateVerbatimDecoder, DecimalTextDecoder, Decoder, Int64OverflowToTextDecoder,
-    IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder, JsonVerbatimDecoder,
-    MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder, PgByteaTextModeDecoder, RealDecoder,
-    TextDecoder, TimeVerbatimDecoder, TimestampTzVerbatimDecoder, TimestampVerbatimDecoder,
-    UuidBlob16Decoder, UuidText36Decoder,
+    BoolDecoder, BoundedBlobDecoder, DateVerbatimDecoder, DecimalTextDecoder, Decoder,
+    Int64OverflowToTextDecoder, IntDecoder, IntervalVerbatimDecoder, JsonCanonicalDecoder,
+    JsonVerbatimDecoder, MySqlBinaryDecoder, NullDecoder, PgByteaBinaryDecoder,
+    PgByteaTextModeDecoder, RealDecoder, TextDecoder, TimeVerbatimDecoder,
+    TimestampTzVerbatimDecoder, TimestampVerbatimDecoder, UuidBlob16Decoder, UuidText36Decoder,
 };
 use super::error::DecodeError;
 use super::type_map::{TypeMap, TypeMapDefaults};
@@ -422,6 +422,34 @@ where
     }
 }
 
+// ------------------------------------------------------------------
+// BoundedBlobDecoder
+//
+// Binary payloads are bounded by their length, text payloads by the
+// length their `\xHEX` form decodes to. Non-UTF-8 text goes straight
+// to the inner decoder, which reports it.
+// ------------------------------------------------------------------
+
+impl<D, S, B> Decoder<PgWalstream, S, B> for BoundedBlobDecoder<D>
+where
+    D: Decoder<PgWalstream, S, B>,
+{
+    fn decode(&self, payload: PgWalstreamColumn<'_>) -> Result<Value<S, B>, DecodeError> {
+        let len = match payload.data {
+            ColumnValue::Null => None,
+            ColumnValue::Binary(b) => Some(b.len()),
+            ColumnValue::Text(_) => payload
+                .data
+                .as_str()
+                .map(super::bytes_helpers::hex_decoded_len),
+        };
+        if let Some(len) = len {
+            self.check(payload.column_name, len)?;
+        }
+        self.inner.decode(payload)
+    }
+}
+
 // PgByteaTextModeDecoder and MySqlBinaryDecoder are wire-format
 // specific to wal2json / maxwell respectively; on pg_walstream they
 // stay NotYetImplemented.
@@ -0,0 +1,63 @@
+//! Placing the columns of a wire event into a row by index.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::schema::NamedColumns;
+
+/// Why an event column could not be placed in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PlacementError<'a> {
+    /// The table has no column of that name.
+    NotFound(&'a str),
+    /// The event already set the column, under this or another name that
+    /// resolves to the same index.
+    Duplicate(&'a str),
+}
+
+/// Resolves the columns of one event to row indices, each at most once.
+///
+/// An event listing two columns that resolve to the same index, say `Name`
+/// and `name` under a case-insensitive [`NamedColumns`], would otherwise
+/// keep whichever value came last, so the row built would depend on the
+/// order the event lists its columns in. Rejecting the second keeps every
+/// conversion independent of that order.
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnPlacer {
+    placed: Vec<bool>,
+}
+
+impl ColumnPlacer {
+    /// Create a placer for one row of `table`.
+    pub(crate) fn new<T: NamedColumns>(table: &T) -> Self {
+        Self {
+            placed: vec![false; table.number_of_columns()],
+        }
+    }
+
+    /// Index in `table` of the column named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PlacementError::NotFound`] if `table` has no such column
+    /// and [`PlacementError::Duplicate`] if its index was already placed.
+    pub(crate) fn place<'a, T: NamedColumns>(
+        &mut self,
+        table: &T,
+        name: &'a str,
+    ) -> Result<usize, PlacementError<'a>> {
+        let col_idx = table
+            .column_index(name)
+            .ok_or(PlacementError::NotFound(name))?;
+        match self.placed.get_mut(col_idx) {
+            Some(placed) if *placed => Err(PlacementError::Duplicate(name)),
+            Some(placed) => {
+                *placed = true;
+                Ok(col_idx)
+            }
+            // An index past the column count is left for the row builder,
+            // which reports it as it always has.
+            None => Ok(col_idx),
+        }
+    }
+}
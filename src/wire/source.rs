@@ -6,7 +6,7 @@ use super::wire_type::WireType;
 /// Per-format marker naming a CDC wire source.
 ///
 /// Implementors are unit structs owned by each format module
-/// (`PgWalstream`, `Wal2Json`, `Maxwell`). The associated payload type
+/// (`PgWalstream`, `Wal2Json`, `Maxwell`, `Debezium`). The associated payload type
 /// describes the format's per-column wire data.
 ///
 /// Type identity is no longer source-native. Every payload carries a
@@ -43,14 +43,138 @@ pub trait WireSchema {
     fn get(&self, table_name: &str) -> Option<&Self::Table>;
 }
 
+/// The row operation one CDC wire event converts to, ready to stage on a
+/// [`DiffSetBuilder`](crate::DiffSetBuilder) of format `F`.
+///
+/// Returned by [`Digestable::to_operation`], so the same code can consume
+/// events of any source.
+#[derive(Debug, Clone)]
+pub enum WireOperation<F, T, S, B>
+where
+    F: crate::builders::Format<S, B>,
+    T: crate::schema::DynTable,
+    S: AsRef<str>,
+    B: AsRef<[u8]>,
+{
+    /// An inserted row.
+    Insert(crate::builders::Insert<T, S, B>),
+    /// An updated row.
+    Update(crate::builders::Update<T, F, S, B>),
+    /// A deleted row: a [`ChangeDelete`](crate::ChangeDelete) for changesets,
+    /// a [`PatchDelete`](crate::PatchDelete) for patchsets.
+    Delete(F::Delete<T>),
+}
+
+impl<F, T, S, B> WireOperation<F, T, S, B>
+where
+    F: crate::builders::Format<S, B>,
+    T: crate::schema::SchemaWithPK,
+    S: AsRef<str>,
+    B: AsRef<[u8]>,
+{
+    /// Add the operation to `builder`.
+    #[must_use]
+    pub fn stage(
+        self,
+        builder: crate::builders::DiffSetBuilder<F, T, S, B>,
+    ) -> crate::builders::DiffSetBuilder<F, T, S, B>
+    where
+        crate::builders::DiffSetBuilder<F, T, S, B>: crate::builders::DiffOps<
+                T,
+                S,
+                B,
+                Format = F,
+                Output = crate::builders::DiffSetBuilder<F, T, S, B>,
+                DeleteArg = F::Delete<T>,
+            >,
+    {
+        use crate::builders::DiffOps;
+        match self {
+            Self::Insert(insert) => builder.insert(insert),
+            Self::Update(update) => builder.update(update),
+            Self::Delete(delete) => builder.delete(delete),
+        }
+    }
+}
+
+impl<F, T, S, B> WireOperation<F, T, S, B>
+where
+    F: crate::builders::Format<S, B>,
+    T: crate::schema::DynTable,
+    S: AsRef<str>,
+    B: AsRef<[u8]>,
+{
+    /// Write each `(column, value)` pair into the row the operation writes,
+    /// e.g. to record event metadata looked up for an [`Enrichment`].
+    ///
+    /// An INSERT takes the value, and an UPDATE takes it as the column's new
+    /// value, keeping any old value the event carried. A DELETE writes no row
+    /// and is returned unchanged. Enriched columns should not be part of the
+    /// primary key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ColumnIndexOutOfBounds`](crate::errors::Error::ColumnIndexOutOfBounds)
+    /// if a column index is out of bounds for the table.
+    pub fn enrich(
+        self,
+        values: impl IntoIterator<Item = (usize, crate::encoding::Value<S, B>)>,
+    ) -> Result<Self, crate::errors::Error> {
+        match self {
+            Self::Insert(mut insert) => {
+                for (column, value) in values {
+                    insert = insert.set(column, value)?;
+                }
+                Ok(Self::Insert(insert))
+            }
+            Self::Update(mut update) => {
+                for (column, value) in values {
+                    update = update.set_new_value(column, value)?;
+                }
+                Ok(Self::Update(update))
+            }
+            Self::Delete(delete) => Ok(Self::Delete(delete)),
+        }
+    }
+}
+
+/// Event metadata a conversion can record in a column of the converted row,
+/// e.g. to fill a `_synced_at` column with when the change happened.
+///
+/// Sources look the metadata up with their `enrichment` method and apply it
+/// through [`WireOperation::enrich`]. Metadata the event does not carry is
+/// recorded as NULL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Enrichment {
+    /// When the source database applied the change, as an INTEGER of
+    /// milliseconds since the Unix epoch.
+    EventTimestamp,
+    /// Id of the source transaction the change belongs to.
+    TransactionId,
+    /// Position of the change in the source log: a `PostgreSQL` LSN or a
+    /// `MySQL` binlog position.
+    Lsn,
+}
+
+/// What [`Digestable::to_operation`] returns on success.
+pub(crate) type MaybeOperation<F, T, S, B> = Option<WireOperation<F, T, S, B>>;
+
 /// One CDC wire event digested via [`DiffSetBuilder::digest`](crate::DiffSetBuilder::digest).
 ///
+/// [`to_operation`](Self::to_operation) exposes the row operation an event
+/// stands for, so code generic over this trait handles every source alike.
+/// It is the only conversion an implementor writes:
+/// [`digest_into`](Self::digest_into) is provided on top of it.
+///
 /// Implemented in-crate for `pg_walstream::EventType`, `wal2json::MessageV2`,
-/// `wal2json::ChangeV1`, and `maxwell::Message` (each times both formats).
+/// `wal2json::ChangeV1`, `maxwell::Message`, and `debezium::FlatRecord`
+/// (each times both formats).
 pub trait Digestable<F, T, S, B>
 where
     F: crate::builders::Format<S, B>,
     T: crate::schema::NamedColumns + WireColumnTypes,
+    S: AsRef<str>,
+    B: AsRef<[u8]>,
 {
     /// Wire source this event came from.
     type Src: WireSource;
@@ -58,9 +182,31 @@ where
     /// Failure mode raised on schema lookup or decode failure.
     type Error;
 
+    /// Convert this event to the row operation it describes, resolving the
+    /// affected table via `schema` and decoding column payloads via
+    /// `adapter`.
+    ///
+    /// Returns `None` for events that change no row, such as transaction
+    /// markers or tombstones.
+    ///
+    /// # Errors
+    ///
+    /// Any per-source `ConversionError`.
+    fn to_operation<Sch, A>(
+        &self,
+        schema: &Sch,
+        adapter: &A,
+    ) -> Result<MaybeOperation<F, T, S, B>, Self::Error>
+    where
+        Sch: WireSchema<Table = T>,
+        A: super::WireAdapter<Self::Src, S, B>;
+
     /// Fold this event into `builder`, resolving affected tables via `schema`
     /// and decoding column payloads via `adapter`.
     ///
+    /// Provided: stages the operation [`to_operation`](Self::to_operation)
+    /// returns, if any.
+    ///
     /// # Errors
     ///
     /// Any per-source `ConversionError`.
@@ -72,5 +218,19 @@ where
     ) -> Result<crate::builders::DiffSetBuilder<F, T, S, B>, Self::Error>
     where
         Sch: WireSchema<Table = T>,
-        A: super::WireAdapter<Self::Src, S, B>;
+        A: super::WireAdapter<Self::Src, S, B>,
+        crate::builders::DiffSetBuilder<F, T, S, B>: crate::builders::DiffOps<
+                T,
+                S,
+                B,
+                Format = F,
+                Output = crate::builders::DiffSetBuilder<F, T, S, B>,
+                DeleteArg = F::Delete<T>,
+            >,
+    {
+        Ok(match self.to_operation(schema, adapter)? {
+            Some(operation) => operation.stage(builder),
+            None => builder,
+        })
+    }
 }
@@ -0,0 +1,132 @@
+//! Apply parity against rusqlite, one test per conflict type.
+//!
+//! Each test records a change to `t` from a known history, builds the same
+//! change by hand, and applies both to a database that has diverged from
+//! that history, so `SQLite` must resolve a DATA, NOTFOUND or CONFLICT
+//! conflict while applying either changeset.
+
+#![cfg(feature = "testing")]
+
+use rusqlite::session::{ConflictAction, ConflictType};
+use sqlite_diff_rs::testing::{ConflictCounts, assert_apply_parity};
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, SimpleTable, Update,
+};
+
+const CREATE: &str = "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)";
+
+fn table() -> SimpleTable {
+    SimpleTable::new("t", &["id", "v"], &[0])
+}
+
+fn replace(_: ConflictType) -> ConflictAction {
+    ConflictAction::SQLITE_CHANGESET_REPLACE
+}
+
+fn omit(_: ConflictType) -> ConflictAction {
+    ConflictAction::SQLITE_CHANGESET_OMIT
+}
+
+#[test]
+fn test_data_conflict_applies_like_sqlite() {
+    // The target holds row 1 with another value than the UPDATE expects.
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().update(
+        Update::<_, ChangesetFormat, _, _>::from(table())
+            .set(0, 1i64, 1i64)
+            .unwrap()
+            .set(1, "a", "b")
+            .unwrap(),
+    );
+    let counts = assert_apply_parity(
+        &[CREATE, "INSERT INTO t VALUES (1, 'a')"],
+        &["UPDATE t SET v = 'b' WHERE id = 1"],
+        &changeset.build(),
+        &[CREATE, "INSERT INTO t VALUES (1, 'x')"],
+        replace,
+    );
+    assert_eq!(
+        counts,
+        ConflictCounts {
+            data: 1,
+            ..ConflictCounts::default()
+        }
+    );
+}
+
+#[test]
+fn test_notfound_conflict_applies_like_sqlite() {
+    // The target lacks the row the DELETE removes; the INSERT still lands.
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .delete(
+            ChangeDelete::from(table())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap(),
+        )
+        .insert(
+            Insert::from(table())
+                .set(0, 2i64)
+                .unwrap()
+                .set(1, "b")
+                .unwrap(),
+        );
+    let counts = assert_apply_parity(
+        &[CREATE, "INSERT INTO t VALUES (1, 'a')"],
+        &[
+            "DELETE FROM t WHERE id = 1",
+            "INSERT INTO t VALUES (2, 'b')",
+        ],
+        &changeset.build(),
+        &[CREATE],
+        omit,
+    );
+    assert_eq!(
+        counts,
+        ConflictCounts {
+            not_found: 1,
+            ..ConflictCounts::default()
+        }
+    );
+}
+
+#[test]
+fn test_conflict_conflict_applies_like_sqlite() {
+    // The target already holds the inserted keys, one with the same row.
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(
+            Insert::from(table())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "a")
+                .unwrap(),
+        )
+        .insert(
+            Insert::from(table())
+                .set(0, 2i64)
+                .unwrap()
+                .set(1, "b")
+                .unwrap(),
+        );
+    let counts = assert_apply_parity(
+        &[CREATE],
+        &[
+            "INSERT INTO t VALUES (1, 'a')",
+            "INSERT INTO t VALUES (2, 'b')",
+        ],
+        &changeset.build(),
+        &[
+            CREATE,
+            "INSERT INTO t VALUES (1, 'z')",
+            "INSERT INTO t VALUES (2, 'b')",
+        ],
+        replace,
+    );
+    assert_eq!(
+        counts,
+        ConflictCounts {
+            conflict: 2,
+            ..ConflictCounts::default()
+        }
+    );
+}
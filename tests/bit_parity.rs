@@ -11,13 +11,15 @@
 //! This file catches both classes of bug by comparing raw `Vec<u8>` output.
 #![cfg(feature = "testing")]
 
+use std::fmt::Write;
+
 use sqlite_diff_rs::testing::{
     assert_bit_parity, assert_patchset_sql_parity, byte_diff_report,
     session_changeset_and_patchset_with_setup,
 };
 use sqlite_diff_rs::{
     ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, PatchDelete, PatchSet,
-    PatchsetFormat, SimpleTable, Update, Value,
+    PatchsetFormat, SimpleTable, TableSchema, Update, Value,
 };
 
 // =============================================================================
@@ -259,6 +261,161 @@ fn bit_parity_composite_pk() {
     );
 }
 
+/// `SQLite` writes each column's 1-based position in the primary key as its
+/// PK-flag byte, not a 0/1 flag, so `PRIMARY KEY (c, a, b)` over columns
+/// `a, b, c, v` is recorded as `[2, 3, 1, 0]` in both formats.
+#[test]
+fn bit_parity_pk_flags_are_key_ordinals() {
+    let items = SimpleTable::new("items", &["a", "b", "c", "v"], &[2, 0, 1]);
+    let setup =
+        ["CREATE TABLE items (a INTEGER, b INTEGER, c INTEGER, v TEXT, PRIMARY KEY (c, a, b))"];
+    let tracked = ["INSERT INTO items VALUES (1, 2, 3, 'x')"];
+    let (sqlite_changeset, sqlite_patchset) =
+        session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let insert = Insert::from(items)
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, 2i64)
+        .unwrap()
+        .set(2, 3i64)
+        .unwrap()
+        .set(3, "x")
+        .unwrap();
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(insert.clone());
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(insert);
+
+    // Header: table marker, column count, then one PK-flag byte per column.
+    for (sqlite, ours) in [
+        (&sqlite_changeset, changeset.build()),
+        (&sqlite_patchset, patchset.build()),
+    ] {
+        assert_eq!(sqlite[1], 4);
+        assert_eq!(sqlite[2..6], [2, 3, 1, 0]);
+        assert_eq!(ours[..6], sqlite[..6]);
+    }
+}
+
+/// DELETE on a key declared as `PRIMARY KEY (c, a)`: the key vector holds c
+/// then a, while the patchset record lists the key columns in column order,
+/// a then c, with the non-key b in between.
+#[test]
+fn bit_parity_delete_reordered_composite_pk() {
+    let items = SimpleTable::new("items", &["a", "b", "c"], &[2, 0]);
+    let setup = [
+        "CREATE TABLE items (a INTEGER, b TEXT, c INTEGER, PRIMARY KEY (c, a))",
+        "INSERT INTO items VALUES (1, 'x', 3)",
+        "INSERT INTO items VALUES (2, 'y', 4)",
+    ];
+    let tracked = [
+        "DELETE FROM items WHERE a = 1 AND c = 3",
+        "DELETE FROM items WHERE a = 2 AND c = 4",
+    ];
+    let (sqlite_changeset, sqlite_patchset) =
+        session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+        .delete(PatchDelete::new(
+            items.clone(),
+            vec![Value::Integer(3), Value::Integer(1)],
+        ))
+        .delete(PatchDelete::new(
+            items.clone(),
+            vec![Value::Integer(4), Value::Integer(2)],
+        ));
+    let our_patchset = patchset.build();
+    assert!(
+        sqlite_patchset == our_patchset,
+        "{}",
+        byte_diff_report("patchset", &sqlite_patchset, &our_patchset)
+    );
+
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = [(1i64, "x", 3i64), (2, "y", 4)]
+        .into_iter()
+        .fold(ChangeSet::new(), |changeset, (a, b, c)| {
+            changeset.delete(
+                ChangeDelete::from(items.clone())
+                    .set(0, a)
+                    .unwrap()
+                    .set(1, b)
+                    .unwrap()
+                    .set(2, c)
+                    .unwrap(),
+            )
+        });
+    let our_changeset = changeset.build();
+    assert!(
+        sqlite_changeset == our_changeset,
+        "{}",
+        byte_diff_report("changeset", &sqlite_changeset, &our_changeset)
+    );
+}
+
+/// Patchset UPDATE on a key that does not lead the columns: the record holds
+/// one entry per column in column order, so the non-key b comes before the
+/// key c instead of after it, and parses back to the same bytes.
+#[test]
+fn bit_parity_patchset_update_trailing_pk() {
+    let items = SimpleTable::new("items", &["a", "b", "c"], &[2]);
+    let setup = [
+        "CREATE TABLE items (a INTEGER, b TEXT, c INTEGER PRIMARY KEY)",
+        "INSERT INTO items VALUES (1, 'x', 3)",
+    ];
+    let tracked = ["UPDATE items SET b = 'y' WHERE c = 3"];
+    let (_, sqlite_patchset) = session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().update(
+        Update::<SimpleTable, PatchsetFormat, String, Vec<u8>>::from(items)
+            .set(2, 3i64)
+            .unwrap()
+            .set(1, "y")
+            .unwrap(),
+    );
+    let our_patchset = patchset.build();
+    assert!(
+        sqlite_patchset == our_patchset,
+        "{}",
+        byte_diff_report("patchset", &sqlite_patchset, &our_patchset)
+    );
+
+    let reparsed: Vec<u8> = sqlite_diff_rs::ParsedDiffSet::parse(&sqlite_patchset)
+        .unwrap()
+        .into();
+    assert_eq!(reparsed, sqlite_patchset);
+}
+
+/// `SQLite` always numbers key columns 1..n, but a hand-built schema may skip
+/// ordinals. Flags `[3, 0, 1]` describe the same key (c, a) as `[2, 0, 1]`, so
+/// every record must match `SQLite`'s; only the flag byte in the table header
+/// differs.
+#[test]
+fn bit_parity_delete_non_contiguous_pk_ordinals() {
+    let gapped: TableSchema<String> = TableSchema::new("items".into(), 3, vec![3, 0, 1])
+        .with_column_names(vec!["a".into(), "b".into(), "c".into()]);
+    let setup = [
+        "CREATE TABLE items (a INTEGER, b TEXT, c INTEGER, PRIMARY KEY (c, a))",
+        "INSERT INTO items VALUES (1, 'x', 3)",
+    ];
+    let tracked = ["DELETE FROM items WHERE a = 1 AND c = 3"];
+    let (_, sqlite_patchset) = session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let mut patchset: PatchSet<TableSchema<String>, String, Vec<u8>> = PatchSet::new();
+    patchset.add_table(&gapped);
+    patchset.digest_sql(tracked[0]).unwrap();
+    let mut our_patchset = patchset.build();
+
+    // Header: 'P', column count, flags [3, 0, 1], "items\0".
+    assert_eq!(our_patchset[2..5], [3, 0, 1]);
+    assert_eq!(sqlite_patchset[2..5], [2, 0, 1]);
+    our_patchset[2] = 2;
+    assert!(
+        sqlite_patchset == our_patchset,
+        "{}",
+        byte_diff_report("patchset", &sqlite_patchset, &our_patchset)
+    );
+}
+
 // =============================================================================
 // Builder API parity (not going through FromStr)
 // =============================================================================
@@ -521,6 +678,55 @@ fn bit_parity_builder_table_cancel_and_readd() {
     );
 }
 
+#[test]
+fn bit_parity_interleaved_three_tables() {
+    // The session appends a table to its list on the first change recorded
+    // against it, so tables come out in first-touch order: neither creation
+    // nor name order, and unaffected by later interleaving. Registering the
+    // tables up front fixes `build()`'s order, so the test goes through
+    // `build_sqlite_table_order()`.
+    let schemas = [
+        SimpleTable::new("alpha", &["id", "val"], &[0]),
+        SimpleTable::new("beta", &["id", "val"], &[0]),
+        SimpleTable::new("gamma", &["id", "val"], &[0]),
+    ];
+    let mut statements = vec![
+        "CREATE TABLE alpha (id INTEGER PRIMARY KEY, val TEXT)".to_string(),
+        "CREATE TABLE beta (id INTEGER PRIMARY KEY, val TEXT)".to_string(),
+        "CREATE TABLE gamma (id INTEGER PRIMARY KEY, val TEXT)".to_string(),
+    ];
+    for i in 0..30 {
+        for table in ["gamma", "alpha", "beta"] {
+            if (i + table.len()) % 4 != 0 {
+                statements.push(format!(
+                    "INSERT INTO {table} (id, val) VALUES ({i}, '{table}{i}')"
+                ));
+            }
+        }
+    }
+    statements.push("UPDATE alpha SET val = 'changed' WHERE id = 1".to_string());
+    statements.push("DELETE FROM gamma WHERE id = 2".to_string());
+
+    let mut changeset = ChangeSet::<SimpleTable, String, Vec<u8>>::new();
+    let mut patchset = PatchSet::<SimpleTable, String, Vec<u8>>::new();
+    for schema in &schemas {
+        changeset.add_table(schema);
+        patchset.add_table(schema);
+    }
+    for sql in &statements[3..] {
+        changeset.digest_sql(sql).unwrap();
+        patchset.digest_sql(sql).unwrap();
+    }
+    assert_ne!(changeset.build(), changeset.build_sqlite_table_order());
+
+    let statements: Vec<&str> = statements.iter().map(String::as_str).collect();
+    assert_bit_parity(
+        &statements,
+        &changeset.build_sqlite_table_order(),
+        &patchset.build_sqlite_table_order(),
+    );
+}
+
 // =============================================================================
 // Data type edge cases
 // =============================================================================
@@ -593,6 +799,126 @@ fn bit_parity_all_nulls() {
     );
 }
 
+// =============================================================================
+// BLOB primary keys
+//
+// 16-byte UUIDv7 keys written as `X'...'` literals, as in the apply benchmark.
+// Enough rows are inserted for the session hash table to rehash, so the BLOB
+// branch of the primary-key hash decides the row order.
+// =============================================================================
+
+/// Returns the hex digits of a deterministic UUIDv7 for row `i`.
+fn uuid_v7_hex(i: u64) -> String {
+    let millis = 0x0190_0000_0000 + i * 7;
+    let random = i.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xD1B5_4A32_D192_ED03;
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    bytes[6..14].copy_from_slice(&random.to_be_bytes());
+    bytes[6] = 0x70 | (bytes[6] & 0x0F);
+    bytes[8] = 0x80 | (bytes[8] & 0x3F);
+    bytes[14..].copy_from_slice(&u16::try_from(i).unwrap().to_be_bytes());
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{byte:02X}").unwrap();
+        hex
+    })
+}
+
+#[test]
+fn bit_parity_blob_pk_script() {
+    let events = SimpleTable::new("events", &["id", "kind", "seq"], &[0]);
+    let mut statements =
+        vec!["CREATE TABLE events (id BLOB PRIMARY KEY, kind TEXT, seq INTEGER)".to_string()];
+    for i in 0..300 {
+        statements.push(format!(
+            "INSERT INTO events (id, kind, seq) VALUES (X'{}', 'created', {i})",
+            uuid_v7_hex(i)
+        ));
+    }
+    for i in (0..300).step_by(3) {
+        statements.push(format!(
+            "UPDATE events SET kind = 'updated' WHERE id = X'{}'",
+            uuid_v7_hex(i)
+        ));
+    }
+    for i in (0..300).step_by(5) {
+        statements.push(format!(
+            "DELETE FROM events WHERE id = X'{}'",
+            uuid_v7_hex(i)
+        ));
+    }
+    let statements: Vec<&str> = statements.iter().map(String::as_str).collect();
+    assert_patchset_sql_parity(&[events], &statements);
+}
+
+#[test]
+fn bit_parity_blob_pk_cancelled_and_reinserted() {
+    // Cancelled rows keep their session entry: they still count towards the
+    // hash table growth, and re-inserting a key reuses its original entry.
+    let events = SimpleTable::new("events", &["id", "kind", "seq"], &[0]);
+    let mut statements =
+        vec!["CREATE TABLE events (id BLOB PRIMARY KEY, kind TEXT, seq INTEGER)".to_string()];
+    for i in 0..140 {
+        statements.push(format!(
+            "INSERT INTO events (id, kind, seq) VALUES (X'{}', 'created', {i})",
+            uuid_v7_hex(i)
+        ));
+        if i % 2 == 1 {
+            statements.push(format!(
+                "DELETE FROM events WHERE id = X'{}'",
+                uuid_v7_hex(i)
+            ));
+        }
+    }
+    for i in (1..140).step_by(4) {
+        statements.push(format!(
+            "INSERT INTO events (id, kind, seq) VALUES (X'{}', 'restored', {i})",
+            uuid_v7_hex(i)
+        ));
+    }
+    let statements: Vec<&str> = statements.iter().map(String::as_str).collect();
+    assert_patchset_sql_parity(&[events], &statements);
+}
+
+#[test]
+fn bit_parity_standalone_blob_pk_updates_and_deletes() {
+    let events = SimpleTable::new("events", &["id", "kind", "seq"], &[0]);
+    let mut setup =
+        vec!["CREATE TABLE events (id BLOB PRIMARY KEY, kind TEXT, seq INTEGER)".to_string()];
+    let mut tracked = Vec::new();
+    for i in 0..200 {
+        setup.push(format!(
+            "INSERT INTO events VALUES (X'{}', 'created', {i})",
+            uuid_v7_hex(i)
+        ));
+        tracked.push(if i % 2 == 0 {
+            format!(
+                "UPDATE events SET kind = 'updated', seq = {} WHERE id = X'{}'",
+                i + 1000,
+                uuid_v7_hex(i)
+            )
+        } else {
+            format!("DELETE FROM events WHERE id = X'{}'", uuid_v7_hex(i))
+        });
+    }
+
+    let mut patchset = PatchSet::<SimpleTable, String, Vec<u8>>::new();
+    patchset.add_table(&events);
+    for sql in &tracked {
+        patchset.digest_sql(sql).unwrap();
+    }
+    let our_patchset: Vec<u8> = patchset.build();
+
+    let setup: Vec<&str> = setup.iter().map(String::as_str).collect();
+    let tracked: Vec<&str> = tracked.iter().map(String::as_str).collect();
+    let (_sqlite_cs, sqlite_ps) = session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let ps_report = byte_diff_report("patchset", &sqlite_ps, &our_patchset);
+    assert!(
+        sqlite_ps == our_patchset,
+        "BLOB PK standalone patchset bit-parity failure\n{ps_report}",
+    );
+}
+
 // =============================================================================
 // Standalone UPDATE / DELETE against a pre-existing row.
 //
@@ -705,6 +1031,42 @@ fn bit_parity_standalone_update_all_non_pk_changed() {
     );
 }
 
+#[test]
+fn bit_parity_standalone_update_wide_table_early_column() {
+    // SQLite does not truncate trailing untouched columns of a patchset
+    // UPDATE: every non-PK column gets one value, `Undefined` (0x00) for the
+    // ones the statement left alone, so the record length depends only on the
+    // column count.
+    let columns = ["id", "c1", "c2", "c3", "c4", "c5", "c6", "c7", "c8", "c9"];
+    let schema = SimpleTable::new("wide", &columns, &[0]);
+
+    let our_patchset: Vec<u8> = PatchSet::<SimpleTable, String, Vec<u8>>::new()
+        .update(
+            Update::<SimpleTable, PatchsetFormat, String, Vec<u8>>::from(schema)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, 42i64)
+                .unwrap(),
+        )
+        .build();
+
+    let (_sqlite_cs, sqlite_ps) = session_changeset_and_patchset_with_setup(
+        &[
+            "CREATE TABLE wide (id INTEGER PRIMARY KEY, c1, c2, c3, c4, c5, c6, c7, c8, c9)",
+            "INSERT INTO wide VALUES (1, 0, 2, 3, 4, 5, 6, 7, 8, 9)",
+        ],
+        &["UPDATE wide SET c1 = 42 WHERE id = 1"],
+    );
+
+    let ps_report = byte_diff_report("patchset", &sqlite_ps, &our_patchset);
+    assert!(
+        sqlite_ps == our_patchset,
+        "wide-table early-column UPDATE bit-parity failure\n{ps_report}",
+    );
+    // The record ends with one `Undefined` byte per trailing untouched column.
+    assert!(sqlite_ps.ends_with(&[0x00; 8]));
+}
+
 #[test]
 fn bit_parity_standalone_delete_single_pk() {
     let schema = SimpleTable::new("orders", &["id", "amount", "status"], &[0]);
@@ -0,0 +1,276 @@
+//! CDC conversions place columns by index, so the row they build does not
+//! depend on the order an event lists its columns in.
+//!
+//! Each fixture is converted with its columns in sorted and in reverse order,
+//! and both outputs must serialize identically. An event naming one column
+//! twice, here through a case-insensitive schema, is rejected rather than
+//! resolved by whichever value came last.
+
+#![cfg(all(
+    feature = "debezium",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "wal2json"
+))]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use sqlite_diff_rs::pg_walstream::EventType;
+use sqlite_diff_rs::{
+    ChangeSet, DynTable, NamedColumns, PatchSet, SchemaWithPK, SimpleTable, TypeMap, Value,
+    WireColumnTypes, WireSchema, WireType, debezium, maxwell, pg_walstream, wal2json,
+};
+
+// ---------------------------------------------------------------------------
+// Test schema: users(id, name, email, age), names matched case-insensitively
+// ---------------------------------------------------------------------------
+
+const COLUMNS: [&str; 4] = ["id", "name", "email", "age"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsersTable(SimpleTable);
+
+impl DynTable for UsersTable {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+    fn number_of_columns(&self) -> usize {
+        self.0.number_of_columns()
+    }
+    fn write_pk_flags(&self, buf: &mut [u8]) {
+        self.0.write_pk_flags(buf);
+    }
+}
+
+impl SchemaWithPK for UsersTable {
+    fn extract_pk<S: Clone, B: Clone>(
+        &self,
+        values: &impl sqlite_diff_rs::IndexableValues<Text = S, Binary = B>,
+    ) -> Vec<Value<S, B>> {
+        self.0.extract_pk(values)
+    }
+    fn number_of_primary_keys(&self) -> usize {
+        self.0.number_of_primary_keys()
+    }
+    fn primary_key_index(&self, col: usize) -> Option<usize> {
+        self.0.primary_key_index(col)
+    }
+}
+
+impl NamedColumns for UsersTable {
+    fn column_index(&self, name: &str) -> Option<usize> {
+        // SQLite column names are case-insensitive.
+        COLUMNS
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+    }
+}
+
+impl WireColumnTypes for UsersTable {
+    fn column_type(&self, column_index: usize) -> WireType {
+        match column_index {
+            0 | 3 => WireType::Int,
+            _ => WireType::Text,
+        }
+    }
+}
+
+struct Schema(UsersTable);
+
+impl WireSchema for Schema {
+    type Table = UsersTable;
+    fn get(&self, table_name: &str) -> Option<&UsersTable> {
+        (table_name == self.0.name()).then_some(&self.0)
+    }
+}
+
+fn schema() -> Schema {
+    Schema(UsersTable(SimpleTable::new("users", &COLUMNS, &[0])))
+}
+
+/// The row every fixture inserts, as `(column, JSON value)` pairs sorted by
+/// column name.
+fn row() -> BTreeMap<&'static str, serde_json::Value> {
+    BTreeMap::from([
+        ("age", serde_json::json!(36)),
+        ("email", serde_json::json!("ada@example.com")),
+        ("id", serde_json::json!(1)),
+        ("name", serde_json::json!("Ada")),
+    ])
+}
+
+/// The row's pairs in sorted and in reverse order.
+fn orders() -> [Vec<(&'static str, serde_json::Value)>; 2] {
+    let sorted: Vec<_> = row().into_iter().collect();
+    let reversed = sorted.iter().rev().cloned().collect();
+    [sorted, reversed]
+}
+
+// ---------------------------------------------------------------------------
+// Fixture builders, taking the columns in the order given
+// ---------------------------------------------------------------------------
+
+fn wal2json_insert(columns: &[(&str, serde_json::Value)]) -> wal2json::MessageV2 {
+    let columns: Vec<serde_json::Value> = columns
+        .iter()
+        .map(|(name, value)| serde_json::json!({"name": name, "type": "text", "value": value}))
+        .collect();
+    let json = serde_json::json!({
+        "action": "I",
+        "schema": "public",
+        "table": "users",
+        "columns": columns,
+    });
+    wal2json::parse_v2(&json.to_string()).unwrap()
+}
+
+fn pg_walstream_insert(columns: &[(&str, serde_json::Value)]) -> EventType {
+    let mut data = pg_walstream::RowData::new();
+    for (name, value) in columns {
+        let text = match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        data.push(Arc::from(*name), pg_walstream::ColumnValue::text(&text));
+    }
+    EventType::Insert {
+        schema: Arc::from("public"),
+        table: Arc::from("users"),
+        relation_oid: 1,
+        data,
+    }
+}
+
+fn maxwell_insert(columns: &[(&str, serde_json::Value)]) -> maxwell::Message {
+    let data: serde_json::Map<String, serde_json::Value> = columns
+        .iter()
+        .map(|(name, value)| ((*name).to_string(), value.clone()))
+        .collect();
+    let json = serde_json::json!({
+        "database": "app",
+        "table": "users",
+        "type": "insert",
+        "data": data,
+    });
+    maxwell::parse(&json.to_string()).unwrap()
+}
+
+fn debezium_insert(columns: &[(&str, serde_json::Value)]) -> debezium::FlatRecord {
+    let mut record: serde_json::Map<String, serde_json::Value> = columns
+        .iter()
+        .map(|(name, value)| ((*name).to_string(), value.clone()))
+        .collect();
+    record.insert("__op".into(), "c".into());
+    record.insert("__table".into(), "users".into());
+    debezium::parse_flattened(&serde_json::Value::Object(record).to_string()).unwrap()
+}
+
+/// Serialize one event as a changeset and as a patchset.
+macro_rules! build_both {
+    ($event:expr, $src:ty) => {{
+        let adapter: TypeMap<$src, String, Vec<u8>> = TypeMap::defaults();
+        let event = $event;
+        let changeset: ChangeSet<UsersTable, String, Vec<u8>> = ChangeSet::new()
+            .digest(&event, &schema(), &adapter)
+            .unwrap();
+        let patchset: PatchSet<UsersTable, String, Vec<u8>> =
+            PatchSet::new().digest(&event, &schema(), &adapter).unwrap();
+        (changeset.build(), patchset.build())
+    }};
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn wal2json_output_ignores_column_order() {
+    let [sorted, reversed] = orders();
+    let expected = build_both!(wal2json_insert(&sorted), wal2json::Wal2Json);
+    assert!(!expected.0.is_empty());
+    assert_eq!(
+        build_both!(wal2json_insert(&reversed), wal2json::Wal2Json),
+        expected
+    );
+}
+
+#[test]
+fn pg_walstream_output_ignores_column_order() {
+    let [sorted, reversed] = orders();
+    let expected = build_both!(
+        pg_walstream_insert(&sorted),
+        sqlite_diff_rs::pg_walstream::PgWalstream
+    );
+    assert!(!expected.0.is_empty());
+    assert_eq!(
+        build_both!(
+            pg_walstream_insert(&reversed),
+            sqlite_diff_rs::pg_walstream::PgWalstream
+        ),
+        expected
+    );
+}
+
+#[test]
+fn json_object_sources_ignore_column_order() {
+    let [sorted, reversed] = orders();
+    let maxwell_output = build_both!(maxwell_insert(&sorted), maxwell::Maxwell);
+    assert_eq!(
+        build_both!(maxwell_insert(&reversed), maxwell::Maxwell),
+        maxwell_output
+    );
+
+    let debezium_output = build_both!(debezium_insert(&sorted), debezium::Debezium);
+    assert_eq!(
+        build_both!(debezium_insert(&reversed), debezium::Debezium),
+        debezium_output
+    );
+    // Both sources describe the same row.
+    assert_eq!(maxwell_output, debezium_output);
+}
+
+#[test]
+fn a_column_named_twice_is_rejected_in_either_order() {
+    let mut columns: Vec<_> = row().into_iter().collect();
+    columns.push(("NAME", serde_json::json!("Eve")));
+    let reversed: Vec<_> = columns.iter().rev().cloned().collect();
+
+    for columns in [&columns, &reversed] {
+        let adapter: TypeMap<wal2json::Wal2Json, String, Vec<u8>> = TypeMap::defaults();
+        let result: Result<ChangeSet<UsersTable, String, Vec<u8>>, _> =
+            ChangeSet::new().digest(&wal2json_insert(columns), &schema(), &adapter);
+        assert!(matches!(
+            result,
+            Err(wal2json::ConversionError::DuplicateColumn(_))
+        ));
+
+        let adapter: TypeMap<sqlite_diff_rs::pg_walstream::PgWalstream, String, Vec<u8>> =
+            TypeMap::defaults();
+        let result: Result<PatchSet<UsersTable, String, Vec<u8>>, _> =
+            PatchSet::new().digest(&pg_walstream_insert(columns), &schema(), &adapter);
+        assert!(matches!(
+            result,
+            Err(pg_walstream::ConversionError::DuplicateColumn(_))
+        ));
+
+        let adapter: TypeMap<maxwell::Maxwell, String, Vec<u8>> = TypeMap::defaults();
+        let result: Result<ChangeSet<UsersTable, String, Vec<u8>>, _> =
+            ChangeSet::new().digest(&maxwell_insert(columns), &schema(), &adapter);
+        assert!(matches!(
+            result,
+            Err(maxwell::ConversionError::DuplicateColumn(_))
+        ));
+
+        let adapter: TypeMap<debezium::Debezium, String, Vec<u8>> = TypeMap::defaults();
+        let result: Result<PatchSet<UsersTable, String, Vec<u8>>, _> =
+            PatchSet::new().digest(&debezium_insert(columns), &schema(), &adapter);
+        assert!(matches!(
+            result,
+            Err(debezium::ConversionError::DuplicateColumn(_))
+        ));
+    }
+}
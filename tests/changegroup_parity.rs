@@ -0,0 +1,111 @@
+//! Differential tests for consolidation against rusqlite's `Changegroup`.
+//!
+//! The consolidation table in `change.rs` mirrors `sqlite3changegroup_add()`.
+//! Each test below records two batches touching the same primary key, one
+//! transition row per test, and checks that merging their parsed diffsets
+//! with `|` yields the bytes `SQLite`'s changegroup emits for the same inputs.
+//! Batches are recorded in separate databases, so transitions that no single
+//! history produces (two `INSERT`s of one row, for instance) are covered too.
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::assert_changegroup_parity;
+
+const CREATE: &str = "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, qty INTEGER)";
+const SEED: &str = "INSERT INTO items VALUES (1, 'apple', 10)";
+
+#[test]
+fn test_changegroup_insert_then_insert() {
+    assert_changegroup_parity(&[
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'apple', 10)"]),
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'pear', 20)"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_insert_then_update() {
+    assert_changegroup_parity(&[
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'apple', 10)"]),
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_insert_then_delete() {
+    assert_changegroup_parity(&[
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'apple', 10)"]),
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_update_then_insert() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'pear', 20)"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_update_then_update() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+        (
+            &[CREATE, "INSERT INTO items VALUES (1, 'apple', 11)"],
+            &["UPDATE items SET name = 'pear' WHERE id = 1"],
+        ),
+    ]);
+}
+
+#[test]
+fn test_changegroup_update_then_update_back() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+        (
+            &[CREATE, "INSERT INTO items VALUES (1, 'apple', 11)"],
+            &["UPDATE items SET qty = 10 WHERE id = 1"],
+        ),
+    ]);
+}
+
+#[test]
+fn test_changegroup_update_then_delete() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+        (
+            &[CREATE, "INSERT INTO items VALUES (1, 'apple', 11)"],
+            &["DELETE FROM items WHERE id = 1"],
+        ),
+    ]);
+}
+
+#[test]
+fn test_changegroup_delete_then_insert_different() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'pear', 20)"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_delete_then_insert_same() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+        (&[CREATE], &["INSERT INTO items VALUES (1, 'apple', 10)"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_delete_then_update() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+        (&[CREATE, SEED], &["UPDATE items SET qty = 11 WHERE id = 1"]),
+    ]);
+}
+
+#[test]
+fn test_changegroup_delete_then_delete() {
+    assert_changegroup_parity(&[
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+        (&[CREATE, SEED], &["DELETE FROM items WHERE id = 1"]),
+    ]);
+}
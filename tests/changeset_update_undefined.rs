@@ -0,0 +1,76 @@
+//! Undefined old values in changeset `UPDATE`s survive parsing and
+//! re-serialization.
+//!
+//! `SQLite` records old values only for the primary key and the columns an
+//! `UPDATE` changed; every other old value is written as the `Undefined` type
+//! code (0x00), distinct from an old `NULL` (0x05). The parser must keep the
+//! two apart, or re-serializing turns "not recorded" into "was NULL" and the
+//! changeset starts reporting `SQLITE_CHANGESET_DATA` conflicts.
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::{byte_diff_report, session_changeset_and_patchset_with_setup};
+use sqlite_diff_rs::{ChangesetOp, ChangesetUpdatePair, ParsedDiffSet, Value};
+
+/// Parses `bytes` as a changeset, asserts it re-serializes byte for byte, and
+/// returns the `(old, new)` pairs of its only operation, an UPDATE.
+fn single_update(bytes: &[u8]) -> Vec<ChangesetUpdatePair<String, Vec<u8>>> {
+    let parsed = ParsedDiffSet::try_from(bytes).unwrap();
+    let serialized: Vec<u8> = parsed.clone().into();
+    assert!(
+        serialized == bytes,
+        "undefined old values were not preserved\n{}",
+        byte_diff_report("changeset", bytes, &serialized)
+    );
+    let ParsedDiffSet::Changeset(changeset) = parsed else {
+        panic!("expected a changeset");
+    };
+    let ops: Vec<_> = changeset.iter().collect();
+    let [ChangesetOp::Update { values, .. }] = ops.as_slice() else {
+        panic!("expected a single UPDATE, got {ops:?}");
+    };
+    values.to_vec()
+}
+
+#[test]
+fn live_update_keeps_untouched_old_values_undefined() {
+    let (changeset, _patchset) = session_changeset_and_patchset_with_setup(
+        &[
+            "CREATE TABLE wide (id INTEGER PRIMARY KEY, a, b, c, d)",
+            "INSERT INTO wide VALUES (1, NULL, 'b', NULL, 4)",
+        ],
+        &["UPDATE wide SET a = 'set' WHERE id = 1"],
+    );
+
+    let values = single_update(&changeset);
+    assert_eq!(
+        values,
+        [
+            (Some(Value::Integer(1)), None),
+            (Some(Value::Null), Some(Value::Text("set".into()))),
+            (None, None),
+            (None, None),
+            (None, None),
+        ]
+    );
+}
+
+#[test]
+fn crafted_update_with_undefined_old_value_of_changed_column_roundtrips() {
+    // Table "t" with three columns, the first being the primary key. The
+    // UPDATE changes column 1 but, unlike `SQLite`'s own output, leaves its
+    // old value undefined, while column 2 has an old NULL and no new value.
+    let mut bytes = vec![b'T', 3, 1, 0, 0, b't', 0, 23, 0];
+    bytes.extend([0x01, 0, 0, 0, 0, 0, 0, 0, 7]);
+    bytes.extend([0x00, 0x05]);
+    bytes.extend([0x00, 0x03, 1, b'x', 0x00]);
+
+    let values = single_update(&bytes);
+    assert_eq!(
+        values,
+        [
+            (Some(Value::Integer(7)), None),
+            (None, Some(Value::Text("x".into()))),
+            (Some(Value::Null), None),
+        ]
+    );
+}
@@ -0,0 +1,113 @@
+//! Tests for compressed patchset frames.
+//!
+//! A sender builds a patchset and wraps it with `compress_frame`; a receiver
+//! only sees the frame bytes, unwraps them with `decompress_frame`, and must
+//! recover a patchset equal to the one that was sent, or reject a frame that
+//! decompresses past its size limit.
+
+#![cfg(any(feature = "deflate", feature = "zstd"))]
+
+use sqlite_diff_rs::compression::{Codec, FrameError, compress_frame, decompress_frame};
+use sqlite_diff_rs::{DiffOps, Insert, ParsedDiffSet, PatchSet, SimpleTable};
+
+fn large_patchset() -> Vec<u8> {
+    let messages = SimpleTable::new("messages", &["id", "room", "body"], &[0]);
+    let patchset = (0..200i64).fold(
+        PatchSet::<SimpleTable, String, Vec<u8>>::new(),
+        |patchset, id| {
+            patchset.insert(
+                Insert::from(messages.clone())
+                    .set(0, id)
+                    .unwrap()
+                    .set(1, "general")
+                    .unwrap()
+                    .set(2, "the same chat message, sent over and over")
+                    .unwrap(),
+            )
+        },
+    );
+    patchset.build()
+}
+
+/// Sends `patchset` through a frame encoded with `codec` and returns what the
+/// receiving side parses.
+fn send_and_receive(patchset: &[u8], codec: Codec) -> (usize, ParsedDiffSet) {
+    let frame = compress_frame(patchset, codec);
+    assert_eq!(frame[0], codec.tag());
+
+    let received = decompress_frame(&frame, patchset.len()).unwrap();
+    (frame.len(), ParsedDiffSet::parse(&received).unwrap())
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn test_deflate_patchset_frame_round_trip() {
+    let patchset = large_patchset();
+    let (frame_len, received) = send_and_receive(&patchset, Codec::Deflate);
+
+    assert!(frame_len < patchset.len(), "repetitive rows must compress");
+    assert_eq!(received, ParsedDiffSet::parse(&patchset).unwrap());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_patchset_frame_round_trip() {
+    let patchset = large_patchset();
+    let (frame_len, received) = send_and_receive(&patchset, Codec::Zstd);
+
+    assert!(frame_len < patchset.len(), "repetitive rows must compress");
+    assert_eq!(received, ParsedDiffSet::parse(&patchset).unwrap());
+}
+
+#[test]
+fn test_identity_frame_is_one_byte_larger() {
+    let patchset = large_patchset();
+    let (frame_len, received) = send_and_receive(&patchset, Codec::Identity);
+
+    assert_eq!(frame_len, patchset.len() + 1);
+    assert_eq!(received, ParsedDiffSet::parse(&patchset).unwrap());
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn test_truncated_deflate_frame_is_corrupt() {
+    let frame = compress_frame(&large_patchset(), Codec::Deflate);
+    assert_eq!(
+        decompress_frame(&frame[..frame.len() / 2], usize::MAX),
+        Err(FrameError::Corrupt(Codec::Deflate))
+    );
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_truncated_zstd_frame_is_corrupt() {
+    let frame = compress_frame(&large_patchset(), Codec::Zstd);
+    assert_eq!(
+        decompress_frame(&frame[..frame.len() / 2], usize::MAX),
+        Err(FrameError::Corrupt(Codec::Zstd))
+    );
+}
+
+/// Asserts that a `codec` frame of a large patchset is rejected one byte short
+/// of its size, and accepted at it.
+fn assert_limit_is_enforced(codec: Codec) {
+    let patchset = large_patchset();
+    let frame = compress_frame(&patchset, codec);
+    assert_eq!(
+        decompress_frame(&frame, patchset.len() - 1),
+        Err(FrameError::TooLarge(patchset.len() - 1))
+    );
+    assert_eq!(decompress_frame(&frame, patchset.len()).unwrap(), patchset);
+}
+
+#[cfg(feature = "deflate")]
+#[test]
+fn test_oversized_deflate_frame_is_rejected() {
+    assert_limit_is_enforced(Codec::Deflate);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_oversized_zstd_frame_is_rejected() {
+    assert_limit_is_enforced(Codec::Zstd);
+}
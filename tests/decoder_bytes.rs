@@ -5,6 +5,7 @@
 //! - `PgByteaTextModeDecoder` (wal2json): decodes bare-hex (and
 //!   optionally `\xHEX`-prefixed) JSON strings into `Value::Blob`.
 //! - `MySqlBinaryDecoder` (maxwell): base64-decodes JSON strings.
+//! - `BoundedBlobDecoder`: rejects oversized encoded blobs before decoding.
 
 #![cfg(all(feature = "wal2json", feature = "pg-walstream", feature = "maxwell"))]
 
@@ -18,8 +19,8 @@ use sqlite_diff_rs::maxwell::{Maxwell, MaxwellColumn};
 use sqlite_diff_rs::pg_walstream::{ColumnValue, PgWalstream, PgWalstreamColumn};
 use sqlite_diff_rs::wal2json::{Wal2Json, Wal2JsonColumn};
 use sqlite_diff_rs::{
-    DecodeError, MySqlBinaryDecoder, PgByteaBinaryDecoder, PgByteaTextModeDecoder, TypeMap, Value,
-    WireAdapter, WireType,
+    BoundedBlobDecoder, DecodeError, MySqlBinaryDecoder, PgByteaBinaryDecoder,
+    PgByteaTextModeDecoder, TypeMap, Value, WireAdapter, WireType,
 };
 
 // -- PgByteaBinaryDecoder: pg_walstream --------------------------------------
@@ -50,6 +51,23 @@ fn pg_bytea_binary_decoder_hex_escape_text_mode() {
     assert_eq!(got, Value::Blob(alloc::vec![0xDE, 0xAD, 0xBE, 0xEF]));
 }
 
+#[test]
+fn pg_bytea_binary_decoder_empty_is_empty_blob() {
+    for cv in [
+        ColumnValue::binary_bytes(Bytes::new()),
+        ColumnValue::text("\\x"),
+    ] {
+        let got: Value<String, Vec<u8>> = PgWalstreamColumn {
+            column_name: "b",
+            wire_type: WireType::Bytes,
+            data: &cv,
+        }
+        .decoded_by(&PgByteaBinaryDecoder)
+        .unwrap();
+        assert_eq!(got, Value::Blob(Vec::new()), "{cv:?}");
+    }
+}
+
 #[test]
 fn pg_bytea_binary_decoder_null() {
     let cv = ColumnValue::Null;
@@ -93,6 +111,22 @@ fn pg_bytea_text_mode_decoder_wal2json_hex() {
     assert_eq!(got, Value::Blob(alloc::vec![0xCA, 0xFE, 0xF0, 0x0D]));
 }
 
+#[test]
+fn pg_bytea_text_mode_decoder_wal2json_empty_is_empty_blob() {
+    // An empty BYTEA stays a BLOB, distinct from an empty TEXT.
+    for encoded in ["", "\\x"] {
+        let s = serde_json::Value::String(encoded.into());
+        let got: Value<String, Vec<u8>> = Wal2JsonColumn {
+            column_name: "b",
+            wire_type: WireType::Bytes,
+            value: &s,
+        }
+        .decoded_by(&PgByteaTextModeDecoder)
+        .unwrap();
+        assert_eq!(got, Value::Blob(Vec::new()), "{encoded:?}");
+    }
+}
+
 #[test]
 fn pg_bytea_text_mode_decoder_wal2json_bare_hex() {
     // wal2json emits BYTEA as bare lowercase hex with no `\x` prefix.
@@ -206,6 +240,97 @@ fn mysql_binary_decoder_maxwell_null() {
     assert_eq!(got, Value::Null);
 }
 
+// -- BoundedBlobDecoder -----------------------------------------------------
+
+#[test]
+fn bounded_blob_decoder_rejects_huge_base64() {
+    // 16 MiB of base64 decodes to 12 MiB, well over the 1 MiB bound.
+    let s = serde_json::Value::String("A".repeat(16 << 20));
+    let result: Result<Value<String, Vec<u8>>, _> = MaxwellColumn {
+        column_name: "b",
+        wire_type: WireType::Bytes,
+        value: &s,
+    }
+    .decoded_by(&BoundedBlobDecoder::new(MySqlBinaryDecoder, 1 << 20));
+    assert_eq!(
+        result.unwrap_err(),
+        DecodeError::ValueTooLarge {
+            column: "b".into(),
+            len: 12 << 20,
+            max: 1 << 20,
+        }
+    );
+}
+
+#[test]
+fn bounded_blob_decoder_accepts_blob_at_limit() {
+    let s = serde_json::Value::String("3q2+7w==".into());
+    let got: Value<String, Vec<u8>> = MaxwellColumn {
+        column_name: "b",
+        wire_type: WireType::Bytes,
+        value: &s,
+    }
+    .decoded_by(&BoundedBlobDecoder::new(MySqlBinaryDecoder, 4))
+    .unwrap();
+    assert_eq!(got, Value::Blob(alloc::vec![0xDE, 0xAD, 0xBE, 0xEF]));
+
+    let too_long: Result<Value<String, Vec<u8>>, _> = MaxwellColumn {
+        column_name: "b",
+        wire_type: WireType::Bytes,
+        value: &s,
+    }
+    .decoded_by(&BoundedBlobDecoder::new(MySqlBinaryDecoder, 3));
+    assert!(matches!(
+        too_long.unwrap_err(),
+        DecodeError::ValueTooLarge { len: 4, max: 3, .. }
+    ));
+}
+
+#[test]
+fn bounded_blob_decoder_rejects_long_wal2json_hex() {
+    let s = serde_json::Value::String("\\xdeadbeef".into());
+    let result: Result<Value<String, Vec<u8>>, _> = Wal2JsonColumn {
+        column_name: "b",
+        wire_type: WireType::Bytes,
+        value: &s,
+    }
+    .decoded_by(&BoundedBlobDecoder::new(PgByteaTextModeDecoder, 2));
+    assert!(matches!(
+        result.unwrap_err(),
+        DecodeError::ValueTooLarge { len: 4, max: 2, .. }
+    ));
+}
+
+#[test]
+fn bounded_blob_decoder_bounds_pg_binary_and_text() {
+    let bounded = BoundedBlobDecoder::new(PgByteaBinaryDecoder, 2);
+    for cv in [
+        ColumnValue::binary_bytes(Bytes::from_static(&[0x01, 0x02, 0x03])),
+        ColumnValue::text("\\x010203"),
+    ] {
+        let result: Result<Value<String, Vec<u8>>, _> = PgWalstreamColumn {
+            column_name: "b",
+            wire_type: WireType::Bytes,
+            data: &cv,
+        }
+        .decoded_by(&bounded);
+        assert!(matches!(
+            result.unwrap_err(),
+            DecodeError::ValueTooLarge { len: 3, max: 2, .. }
+        ));
+    }
+
+    let cv = ColumnValue::Null;
+    let got: Value<String, Vec<u8>> = PgWalstreamColumn {
+        column_name: "b",
+        wire_type: WireType::Bytes,
+        data: &cv,
+    }
+    .decoded_by(&bounded)
+    .unwrap();
+    assert_eq!(got, Value::Null);
+}
+
 // -- Defaults ----------------------------------------------------------------
 
 #[test]
@@ -0,0 +1,73 @@
+//! Column affinity in the differential harness.
+//!
+//! `SQLite` stores `2.0` in an `INTEGER` column as the integer `2`, so the
+//! session records an `INTEGER` where the SQL text had a `REAL`. These tests
+//! check that values canonicalized with `canonicalize_for_affinity` match the
+//! bytes rusqlite emits.
+
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::{
+    SqlType, TypedSimpleTable, assert_bit_parity, canonicalize_for_affinity,
+    session_changeset_and_patchset, test_differential,
+};
+use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, PatchSet, SimpleTable, Value};
+
+fn items() -> TypedSimpleTable {
+    TypedSimpleTable::new(
+        "items",
+        &[
+            ("id", SqlType::Integer),
+            ("n", SqlType::Integer),
+            ("x", SqlType::Real),
+        ],
+        &[0],
+    )
+}
+
+#[test]
+fn test_integral_real_in_integer_column_byte_parity() {
+    let table = items();
+    let create = table.to_string();
+    let insert = "INSERT INTO items VALUES (1, 2.0, 3)";
+
+    let row: Vec<Value<String, Vec<u8>>> = [Value::Integer(1), Value::Real(2.0), Value::Integer(3)]
+        .into_iter()
+        .zip(table.column_types())
+        .map(|(value, &affinity)| canonicalize_for_affinity(value, affinity))
+        .collect();
+    assert_eq!(
+        row,
+        [Value::Integer(1), Value::Integer(2), Value::Real(3.0)]
+    );
+
+    let simple: SimpleTable = (*table).clone();
+    let insert_op = Insert::from_values(simple, row);
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(insert_op.clone());
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(insert_op);
+    assert_bit_parity(&[&create, insert], &changeset.build(), &patchset.build());
+}
+
+#[test]
+fn test_non_integral_real_stays_real() {
+    let (changeset, _) = session_changeset_and_patchset(&[
+        &items().to_string(),
+        "INSERT INTO items VALUES (1, 2.5, 3.5)",
+    ]);
+    let row = [Value::Integer(1), Value::Real(2.5), Value::Real(3.5)];
+    for (value, &affinity) in row.iter().zip(items().column_types()) {
+        let value: Value<String, Vec<u8>> = value.clone();
+        assert_eq!(canonicalize_for_affinity(value.clone(), affinity), value);
+    }
+
+    let insert_op = Insert::from_values((*items()).clone(), row.to_vec());
+    let ours: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(insert_op);
+    assert_eq!(changeset, ours.build());
+}
+
+#[test]
+fn test_differential_applies_affinity_to_sql_literals() {
+    // The key literal `2.0` is stored as rowid 2.
+    test_differential(&[items()], "INSERT INTO items VALUES (2.0, 4.0, 1)");
+}
@@ -0,0 +1,108 @@
+//! Byte parity for empty TEXT and empty BLOB keys.
+//!
+//! `''` and `X''` are distinct values in `SQLite`: they encode with the type
+//! codes 3 and 4 and hash to different session buckets, so two rows keyed on
+//! them never collide. These tests stage both in one table and compare our
+//! bytes, row order and parsed values against rusqlite.
+
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::{
+    SqlType, TypedSimpleTable, assert_bit_parity, byte_diff_report,
+    session_changeset_and_patchset_with_setup, test_differential,
+};
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, DiffOps, Insert, ParsedDiffSet, PatchSet, SimpleTable, Value,
+};
+
+const CREATE: &str = "CREATE TABLE t (k BLOB PRIMARY KEY, v TEXT)";
+
+fn table() -> SimpleTable {
+    SimpleTable::new("t", &["k", "v"], &[0])
+}
+
+fn rows() -> [Vec<Value<String, Vec<u8>>>; 2] {
+    [
+        vec![Value::Blob(Vec::new()), Value::Text("blob".into())],
+        vec![Value::Text(String::new()), Value::Text("text".into())],
+    ]
+}
+
+#[test]
+fn test_empty_blob_and_empty_text_keys_insert_byte_parity() {
+    let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    for row in rows() {
+        changeset = changeset.insert(Insert::from_values(table(), row.clone()));
+        patchset = patchset.insert(Insert::from_values(table(), row));
+    }
+    assert_eq!(changeset.len(), 2, "the two keys must not consolidate");
+
+    assert_bit_parity(
+        &[
+            CREATE,
+            "INSERT INTO t VALUES (X'', 'blob')",
+            "INSERT INTO t VALUES ('', 'text')",
+        ],
+        &changeset.build(),
+        &patchset.build(),
+    );
+
+    let ParsedDiffSet::Changeset(parsed) = ParsedDiffSet::parse(&changeset.build()).unwrap() else {
+        panic!("expected a changeset");
+    };
+    assert_eq!(parsed.len(), 2);
+    assert!(parsed.get("t", &[Value::Blob(Vec::new())]).is_some());
+    assert!(parsed.get("t", &[Value::Text(String::new())]).is_some());
+}
+
+#[test]
+fn test_empty_blob_and_empty_text_keys_delete_byte_parity() {
+    let setup = [
+        CREATE,
+        "INSERT INTO t VALUES (X'', 'blob')",
+        "INSERT INTO t VALUES ('', 'text')",
+    ];
+    let tracked = ["DELETE FROM t WHERE k = ''", "DELETE FROM t WHERE k = X''"];
+    let (sqlite_changeset, sqlite_patchset) =
+        session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        rows()
+            .into_iter()
+            .rev()
+            .fold(ChangeSet::new(), |changeset, row| {
+                let [k, v] = <[_; 2]>::try_from(row).unwrap();
+                changeset.delete(
+                    ChangeDelete::from(table())
+                        .set(0, k)
+                        .unwrap()
+                        .set(1, v)
+                        .unwrap(),
+                )
+            });
+    let our_changeset = changeset.build();
+    assert!(
+        sqlite_changeset == our_changeset,
+        "{}",
+        byte_diff_report("changeset", &sqlite_changeset, &our_changeset)
+    );
+
+    let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    patchset.add_table(&table());
+    for sql in tracked {
+        patchset.digest_sql(sql).unwrap();
+    }
+    let our_patchset = patchset.build();
+    assert!(
+        sqlite_patchset == our_patchset,
+        "{}",
+        byte_diff_report("patchset", &sqlite_patchset, &our_patchset)
+    );
+}
+
+#[test]
+fn test_differential_empty_blob_and_empty_text_literals() {
+    let table = TypedSimpleTable::new("t", &[("k", SqlType::Blob), ("v", SqlType::Text)], &[0]);
+    test_differential(&[table], "INSERT INTO t VALUES (X'', 'blob'), ('', 'text')");
+}
@@ -0,0 +1,122 @@
+//! Byte parity for tables keyed by a `REAL` column.
+//!
+//! A `REAL PRIMARY KEY` is not a rowid alias, so `2.0` stays a REAL in the
+//! session output. The column stores it as the integer `2`, though, and an
+//! INSERT is hashed from the stored value, which moves integral keys to
+//! other buckets than their float bits would. These tests cover negative,
+//! integral and fractional keys, comparing our row order and bytes against
+//! rusqlite in both formats.
+
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::{
+    SqlType, TypedSimpleTable, assert_bit_parity, byte_diff_report,
+    session_changeset_and_patchset_with_setup, test_differential,
+};
+use sqlite_diff_rs::{
+    ChangeSet, DiffOps, Insert, PatchDelete, PatchSet, PatchsetFormat, SimpleTable, Update, Value,
+};
+
+const CREATE: &str = "CREATE TABLE t (k REAL PRIMARY KEY, v TEXT)";
+
+const KEYS: [f64; 12] = [
+    2.0, -1.0, 0.5, -3.75, 1e10, -0.25, 100.0, 7.5, -2.0, 0.0, 1.5, -1e-3,
+];
+
+fn table() -> SimpleTable {
+    SimpleTable::new("t", &["k", "v"], &[0])
+}
+
+fn row(k: f64, v: &str) -> Vec<Value<String, Vec<u8>>> {
+    vec![Value::Real(k), Value::Text(v.into())]
+}
+
+#[test]
+fn test_real_pk_inserts_byte_parity() {
+    let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    let mut statements = vec![CREATE.to_owned()];
+    for (i, &k) in KEYS.iter().enumerate() {
+        let v = format!("v{i}");
+        changeset = changeset.insert(Insert::from_values(table(), row(k, &v)));
+        patchset = patchset.insert(Insert::from_values(table(), row(k, &v)));
+        statements.push(format!("INSERT INTO t VALUES ({k:?}, '{v}')"));
+    }
+
+    let statements: Vec<&str> = statements.iter().map(String::as_str).collect();
+    assert_bit_parity(&statements, &changeset.build(), &patchset.build());
+}
+
+#[test]
+fn test_real_pk_update_and_delete_byte_parity() {
+    let setup: Vec<String> = KEYS
+        .iter()
+        .enumerate()
+        .map(|(i, k)| format!("INSERT INTO t VALUES ({k:?}, 'v{i}')"))
+        .collect();
+    let mut setup: Vec<&str> = setup.iter().map(String::as_str).collect();
+    setup.insert(0, CREATE);
+    // Constraining `v` as well hands the changeset digest the old values.
+    let tracked = [
+        "UPDATE t SET v = 'neg' WHERE k = -1.0 AND v = 'v1'",
+        "UPDATE t SET v = 'two' WHERE k = 2.0 AND v = 'v0'",
+        "DELETE FROM t WHERE k = -3.75 AND v = 'v3'",
+        "DELETE FROM t WHERE k = 0.5 AND v = 'v2'",
+        "UPDATE t SET v = 'tiny' WHERE k = -0.001 AND v = 'v11'",
+    ];
+    let (sqlite_changeset, sqlite_patchset) =
+        session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    changeset.add_table(&table());
+    for sql in tracked {
+        changeset.digest_sql(sql).unwrap();
+    }
+    let our_changeset = changeset.build();
+    assert!(
+        sqlite_changeset == our_changeset,
+        "{}",
+        byte_diff_report("changeset", &sqlite_changeset, &our_changeset)
+    );
+
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+        .update(
+            Update::<SimpleTable, PatchsetFormat, String, Vec<u8>>::from(table())
+                .set(0, -1.0)
+                .unwrap()
+                .set(1, "neg")
+                .unwrap(),
+        )
+        .update(
+            Update::<SimpleTable, PatchsetFormat, String, Vec<u8>>::from(table())
+                .set(0, 2.0)
+                .unwrap()
+                .set(1, "two")
+                .unwrap(),
+        )
+        .delete(PatchDelete::new(table(), vec![Value::Real(-3.75)]))
+        .delete(PatchDelete::new(table(), vec![Value::Real(0.5)]))
+        .update(
+            Update::<SimpleTable, PatchsetFormat, String, Vec<u8>>::from(table())
+                .set(0, -1e-3)
+                .unwrap()
+                .set(1, "tiny")
+                .unwrap(),
+        );
+    let our_patchset = patchset.build();
+    assert!(
+        sqlite_patchset == our_patchset,
+        "{}",
+        byte_diff_report("patchset", &sqlite_patchset, &our_patchset)
+    );
+}
+
+#[test]
+fn test_differential_coerces_integer_literals_for_real_pk() {
+    let table = TypedSimpleTable::new("t", &[("k", SqlType::Real), ("v", SqlType::Text)], &[0]);
+    // `2`, `-1` and `100` are stored as the REAL keys `2.0`, `-1.0` and `100.0`.
+    test_differential(
+        &[table],
+        "INSERT INTO t VALUES (2, 'a'), (-1, 'b'), (-2.5, 'c'), (100, 'd')",
+    );
+}
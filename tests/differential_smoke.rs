@@ -1,8 +1,9 @@
 //! End-to-end byte-parity check against rusqlite's session extension.
 //!
 //! Pins the `differential_testing::run_differential_test` helper without
-//! requiring a fuzz run. The crash inputs and structured fuzzers exercise
-//! this helper through wrappers, but this file exercises it directly.
+//! requiring a fuzz run. The crash inputs and structured fuzzers go through
+//! the affinity-aware `testing::test_differential` instead, so this file is
+//! what exercises the untyped helper.
 
 #![cfg(feature = "testing")]
 
@@ -0,0 +1,75 @@
+//! Tests for the `ParsedDiffSet` count metrics on the benchmark workload.
+//!
+//! The schema and statements mirror `benches/builder_vs_rusqlite.rs`, so the
+//! counts below are those of the changeset the benchmarks generate.
+
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::ParsedDiffSet;
+use sqlite_diff_rs::testing::session_changeset_and_patchset;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE users (id INTEGER PRIMARY KEY, username TEXT NOT NULL, email TEXT NOT NULL, \
+     created_at INTEGER NOT NULL, last_login INTEGER, is_active INTEGER NOT NULL DEFAULT 1, \
+     profile_data BLOB)",
+    "CREATE TABLE posts (id INTEGER PRIMARY KEY, user_id INTEGER NOT NULL, title TEXT NOT NULL, \
+     content TEXT NOT NULL, created_at INTEGER NOT NULL, updated_at INTEGER, \
+     view_count INTEGER NOT NULL DEFAULT 0, is_published INTEGER NOT NULL DEFAULT 0)",
+    "CREATE TABLE comments (id INTEGER PRIMARY KEY, post_id INTEGER NOT NULL, \
+     user_id INTEGER NOT NULL, content TEXT NOT NULL, created_at INTEGER NOT NULL, \
+     parent_id INTEGER, is_deleted INTEGER NOT NULL DEFAULT 0)",
+    "CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE)",
+    "CREATE TABLE post_tags (post_id INTEGER NOT NULL, tag_id INTEGER NOT NULL, \
+     PRIMARY KEY (post_id, tag_id))",
+];
+
+const OPERATIONS: &[&str] = &[
+    "INSERT INTO users (id, username, email, created_at, is_active) VALUES (1, 'alice', 'alice@example.com', 1000000, 1)",
+    "INSERT INTO users (id, username, email, created_at, is_active) VALUES (2, 'bob', 'bob@example.com', 1000100, 1)",
+    "INSERT INTO users (id, username, email, created_at, is_active) VALUES (3, 'charlie', 'charlie@example.com', 1000200, 1)",
+    "INSERT INTO users (id, username, email, created_at, is_active) VALUES (4, 'diana', 'diana@example.com', 1000300, 0)",
+    "INSERT INTO users (id, username, email, created_at, is_active) VALUES (5, 'eve', 'eve@example.com', 1000400, 1)",
+    "INSERT INTO posts (id, user_id, title, content, created_at, is_published) VALUES (1, 1, 'First Post', 'Hello World!', 1000500, 1)",
+    "INSERT INTO posts (id, user_id, title, content, created_at, is_published) VALUES (2, 1, 'Second Post', 'More content', 1000600, 1)",
+    "INSERT INTO posts (id, user_id, title, content, created_at, is_published) VALUES (3, 2, 'Bob''s Post', 'My thoughts', 1000700, 1)",
+    "INSERT INTO posts (id, user_id, title, content, created_at, is_published) VALUES (4, 3, 'Draft', 'Work in progress', 1000800, 0)",
+    "INSERT INTO posts (id, user_id, title, content, created_at, is_published) VALUES (5, 5, 'Eve''s Post', 'Latest news', 1000900, 1)",
+    "INSERT INTO tags (id, name) VALUES (1, 'rust')",
+    "INSERT INTO tags (id, name) VALUES (2, 'database')",
+    "INSERT INTO tags (id, name) VALUES (3, 'tutorial')",
+    "INSERT INTO tags (id, name) VALUES (4, 'news')",
+    "INSERT INTO tags (id, name) VALUES (5, 'discussion')",
+    "INSERT INTO post_tags (post_id, tag_id) VALUES (1, 1)",
+    "INSERT INTO post_tags (post_id, tag_id) VALUES (1, 3)",
+    "INSERT INTO post_tags (post_id, tag_id) VALUES (2, 1)",
+    "INSERT INTO post_tags (post_id, tag_id) VALUES (3, 5)",
+    "INSERT INTO post_tags (post_id, tag_id) VALUES (5, 4)",
+    "INSERT INTO comments (id, post_id, user_id, content, created_at) VALUES (1, 1, 2, 'Great post!', 1001000)",
+    "INSERT INTO comments (id, post_id, user_id, content, created_at) VALUES (2, 1, 3, 'Thanks for sharing', 1001100)",
+    "INSERT INTO comments (id, post_id, user_id, content, created_at) VALUES (3, 2, 2, 'Interesting', 1001200)",
+    "INSERT INTO comments (id, post_id, user_id, content, created_at) VALUES (4, 3, 1, 'Nice work', 1001300)",
+    "INSERT INTO comments (id, post_id, user_id, content, created_at) VALUES (5, 1, 5, 'Reply to comment 1', 1001400)",
+    "UPDATE users SET last_login = 1002000 WHERE id = 1",
+    "UPDATE users SET last_login = 1002100 WHERE id = 2",
+    "UPDATE posts SET view_count = 10 WHERE id = 1",
+    "UPDATE posts SET view_count = 5 WHERE id = 2",
+    "UPDATE posts SET updated_at = 1002200, content = 'Updated content' WHERE id = 2",
+    "DELETE FROM comments WHERE id = 5",
+    "DELETE FROM post_tags WHERE post_id = 3 AND tag_id = 5",
+    "DELETE FROM users WHERE id = 4",
+];
+
+#[test]
+fn test_benchmark_changeset_counts() {
+    let statements: Vec<&str> = SCHEMA.iter().chain(OPERATIONS).copied().collect();
+    let (changeset, patchset) = session_changeset_and_patchset(&statements);
+
+    // Every row is inserted in the same session, so the updates fold into
+    // their inserts and the deletes cancel theirs: 25 inserts minus 3.
+    for bytes in [changeset, patchset] {
+        let parsed = ParsedDiffSet::parse(&bytes).unwrap();
+        assert_eq!(parsed.counts_by_kind(), (22, 0, 0));
+        assert_eq!(parsed.operation_count(), 22);
+        assert_eq!(parsed.table_count(), 5);
+    }
+}
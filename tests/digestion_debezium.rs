@@ -0,0 +1,688 @@
+//! Tests for Debezium flattened record digestion via `DiffSetBuilder::digest`.
+//!
+//! Fixtures are message values as produced by the `ExtractNewRecordState`
+//! SMT with `add.fields=op,table` and `delete.handling.mode=rewrite`. Each
+//! digested builder is compared with the one built by hand.
+
+#![cfg(feature = "debezium")]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use sqlite_diff_rs::debezium::{
+    ConversionError, Debezium, DebeziumColumn, DebeziumSchemaRegistry, FlatOp,
+    debezium_stream_to_changesets, debezium_stream_to_patchsets, parse_flattened,
+};
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Digestable, DynTable, Enrichment, Insert,
+    NamedColumns, PatchDelete, PatchSet, PatchsetFormat, SchemaWithPK, SimpleTable,
+    TextOrJsonDecoder, TypeMap, Update, Value, WireAdapter, WireColumnTypes, WireOperation,
+    WireSchema, WireType,
+};
+
+// ---------------------------------------------------------------------------
+// Test schema
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct TestSchema {
+    users: TestUsersTable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TestUsersTable(SimpleTable);
+
+impl DynTable for TestUsersTable {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+    fn number_of_columns(&self) -> usize {
+        self.0.number_of_columns()
+    }
+    fn write_pk_flags(&self, buf: &mut [u8]) {
+        self.0.write_pk_flags(buf);
+    }
+}
+
+impl SchemaWithPK for TestUsersTable {
+    fn extract_pk<S: Clone, B: Clone>(
+        &self,
+        values: &impl sqlite_diff_rs::IndexableValues<Text = S, Binary = B>,
+    ) -> Vec<Value<S, B>> {
+        self.0.extract_pk(values)
+    }
+    fn number_of_primary_keys(&self) -> usize {
+        self.0.number_of_primary_keys()
+    }
+    fn primary_key_index(&self, col: usize) -> Option<usize> {
+        self.0.primary_key_index(col)
+    }
+}
+
+impl NamedColumns for TestUsersTable {
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.0.column_index(name)
+    }
+}
+
+impl WireColumnTypes for TestUsersTable {
+    fn column_type(&self, column_index: usize) -> WireType {
+        // id -> Int, name and tags -> Text, avatar -> Bytes, profile -> Jsonb
+        match self.0.column_names()[column_index].as_str() {
+            "id" => WireType::Int,
+            "name" | "tags" => WireType::Text,
+            "avatar" => WireType::Bytes,
+            "profile" => WireType::Jsonb,
+            name => panic!("column {name} has no wire type"),
+        }
+    }
+}
+
+impl WireSchema for TestSchema {
+    type Table = TestUsersTable;
+    fn get(&self, table_name: &str) -> Option<&Self::Table> {
+        if table_name == "users" {
+            Some(&self.users)
+        } else {
+            None
+        }
+    }
+}
+
+fn users() -> TestUsersTable {
+    TestUsersTable(SimpleTable::new("users", &["id", "name", "avatar"], &[0]))
+}
+
+fn test_schema() -> TestSchema {
+    TestSchema { users: users() }
+}
+
+fn default_adapter() -> TypeMap<Debezium, String, Vec<u8>> {
+    TypeMap::defaults()
+}
+
+const CREATE: &str = r#"{"id":1,"name":"Alice","avatar":"3q2+7w==","__op":"c","__table":"users","__deleted":"false"}"#;
+const SNAPSHOT: &str = r#"{"id":1,"name":"Alice","avatar":"3q2+7w==","__op":"r","__table":"users","__deleted":"false"}"#;
+const UPDATE: &str =
+    r#"{"id":1,"name":"Alicia","avatar":null,"__op":"u","__table":"users","__deleted":"false"}"#;
+const DELETE: &str =
+    r#"{"id":1,"name":"Alicia","avatar":null,"__op":"d","__table":"users","__deleted":"true"}"#;
+
+fn alice() -> Insert<TestUsersTable, String, Vec<u8>> {
+    Insert::from(users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap()
+        .set(2, vec![0xDE, 0xAD, 0xBE, 0xEF])
+        .unwrap()
+}
+
+// -- Parsing ----------------------------------------------------------------
+
+#[test]
+fn debezium_parse_flattened_splits_metadata() {
+    let record = parse_flattened(
+        r#"{"id":1,"name":"Alice","__op":"c","__table":"users","__source_ts_ms":1700000000000}"#,
+    )
+    .unwrap();
+    assert_eq!(record.op, Some(FlatOp::Create));
+    assert_eq!(record.table.as_deref(), Some("users"));
+    assert!(!record.deleted);
+    assert!(!record.tombstone);
+    assert_eq!(
+        record.columns.keys().collect::<Vec<_>>(),
+        [&"id".to_string(), &"name".to_string()]
+    );
+    assert_eq!(
+        record.metadata.keys().collect::<Vec<_>>(),
+        [&"__source_ts_ms".to_string()]
+    );
+}
+
+#[test]
+fn debezium_parse_flattened_rejects_malformed_metadata() {
+    assert!(parse_flattened(r#"{"id":1,"__op":"x"}"#).is_err());
+    assert!(parse_flattened(r#"{"id":1,"__table":7}"#).is_err());
+    assert!(parse_flattened(r#"{"id":1,"__deleted":"maybe"}"#).is_err());
+    assert!(parse_flattened("[1, 2]").is_err());
+}
+
+// -- ChangesetFormat ----------------------------------------------------------
+
+#[test]
+fn debezium_changeset_create_and_snapshot() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+    let expected: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new().insert(alice());
+
+    for fixture in [CREATE, SNAPSHOT] {
+        let record = parse_flattened(fixture).unwrap();
+        let cs: ChangeSet<TestUsersTable, String, Vec<u8>> =
+            ChangeSet::new().digest(&record, &schema, &adapter).unwrap();
+        assert_eq!(cs, expected);
+    }
+}
+
+#[test]
+fn debezium_changeset_update_needs_before_image() {
+    let record = parse_flattened(UPDATE).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &test_schema(), &default_adapter());
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::MissingData("before", "UPDATE")
+    );
+}
+
+#[test]
+fn debezium_changeset_rewritten_delete() {
+    let record = parse_flattened(DELETE).unwrap();
+    let cs: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new()
+        .digest(&record, &test_schema(), &default_adapter())
+        .unwrap();
+    let expected: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new().delete(
+        ChangeDelete::from(users())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "Alicia")
+            .unwrap(),
+    );
+    assert_eq!(cs, expected);
+}
+
+// -- PatchsetFormat -----------------------------------------------------------
+
+#[test]
+fn debezium_patchset_update() {
+    let record = parse_flattened(UPDATE).unwrap();
+    let ps: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new()
+        .digest(&record, &test_schema(), &default_adapter())
+        .unwrap();
+    let expected: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new().update(
+        Update::<_, PatchsetFormat, _, _>::from(users())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "Alicia")
+            .unwrap()
+            .set_null(2)
+            .unwrap(),
+    );
+    assert_eq!(ps, expected);
+}
+
+#[test]
+fn debezium_patchset_create_update_delete_cancels() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+    let mut ps: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new();
+    for fixture in [CREATE, UPDATE, DELETE, "null"] {
+        ps = ps
+            .digest(&parse_flattened(fixture).unwrap(), &schema, &adapter)
+            .unwrap();
+    }
+    assert!(ps.build().is_empty());
+}
+
+#[test]
+fn debezium_patchset_delete_keeps_pk() {
+    let record = parse_flattened(DELETE).unwrap();
+    let ps: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new()
+        .digest(&record, &test_schema(), &default_adapter())
+        .unwrap();
+    let expected: PatchSet<TestUsersTable, String, Vec<u8>> =
+        PatchSet::new().delete(PatchDelete::new(users(), vec![Value::Integer(1)]));
+    assert_eq!(ps, expected);
+}
+
+// -- Tombstones and error paths -----------------------------------------------
+
+#[test]
+fn debezium_tombstone_is_a_no_op() {
+    let tombstone = parse_flattened("null").unwrap();
+    assert!(tombstone.tombstone);
+    let base: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new().insert(alice());
+    let cs = base
+        .clone()
+        .digest(&tombstone, &test_schema(), &default_adapter())
+        .unwrap();
+    assert_eq!(cs, base);
+}
+
+#[test]
+fn debezium_keyed_tombstone_deletes_the_keyed_row() {
+    let key = parse_flattened(r#"{"id":1}"#).unwrap();
+    let tombstone = parse_flattened("null").unwrap().with_key("users", &key);
+    let ps: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new()
+        .digest(&tombstone, &test_schema(), &default_adapter())
+        .unwrap();
+    let expected: PatchSet<TestUsersTable, String, Vec<u8>> =
+        PatchSet::new().delete(PatchDelete::new(users(), vec![Value::Integer(1)]));
+    assert_eq!(ps, expected);
+
+    // A changeset DELETE needs the old row, which the key does not hold.
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&tombstone, &test_schema(), &default_adapter());
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::MissingData("before", "DELETE")
+    );
+}
+
+#[test]
+fn debezium_deleted_flag_overrides_op() {
+    // Rewritten deletes may be emitted without `__op`.
+    let record =
+        parse_flattened(r#"{"id":1,"name":"Alicia","__table":"users","__deleted":true}"#).unwrap();
+    assert_eq!(record.operation().unwrap(), Some(FlatOp::Delete));
+}
+
+#[test]
+fn debezium_missing_op_is_error() {
+    let record = parse_flattened(r#"{"id":1,"__table":"users"}"#).unwrap();
+    let result: Result<PatchSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        PatchSet::new().digest(&record, &test_schema(), &default_adapter());
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::MissingData("__op", "record")
+    );
+}
+
+#[test]
+fn debezium_missing_or_unknown_table_is_error() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+
+    let record = parse_flattened(r#"{"id":1,"__op":"c"}"#).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &schema, &adapter);
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::MissingData("__table", "record")
+    );
+
+    let record = parse_flattened(r#"{"id":1,"__op":"c","__table":"orders"}"#).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &schema, &adapter);
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::TableNotFound("orders".into())
+    );
+}
+
+#[test]
+fn debezium_column_not_found_is_error() {
+    let record =
+        parse_flattened(r#"{"id":1,"email":"a@b.c","__op":"c","__table":"users"}"#).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &test_schema(), &default_adapter());
+    assert_eq!(
+        result.unwrap_err(),
+        ConversionError::ColumnNotFound("email".into())
+    );
+}
+
+// -- Transaction streams ------------------------------------------------------
+
+const TX1_CREATE: &str = r#"{"id":1,"name":"Alice","avatar":"3q2+7w==","__op":"c","__table":"users","__transaction_id":"571:53195832"}"#;
+const TX1_CREATE_BOB: &str = r#"{"id":2,"name":"Bob","avatar":null,"__op":"c","__table":"users","__transaction_id":"571:53195832"}"#;
+const TX2_DELETE: &str = r#"{"id":2,"name":"Bob","avatar":null,"__op":"d","__table":"users","__deleted":"true","__transaction_id":"572:53195901"}"#;
+const TX2_UPDATE: &str = r#"{"id":1,"name":"Alicia","avatar":null,"__op":"u","__table":"users","__transaction_id":"572:53195901"}"#;
+
+fn bob() -> Insert<TestUsersTable, String, Vec<u8>> {
+    Insert::from(users())
+        .set(0, 2i64)
+        .unwrap()
+        .set(1, "Bob")
+        .unwrap()
+}
+
+#[test]
+fn debezium_stream_yields_one_changeset_per_transaction() {
+    let stream = [TX1_CREATE, TX1_CREATE_BOB, TX2_DELETE, "null"];
+    let changesets =
+        debezium_stream_to_changesets(stream, &test_schema(), &default_adapter()).unwrap();
+
+    let first: ChangeSet<TestUsersTable, String, Vec<u8>> =
+        ChangeSet::new().insert(alice()).insert(bob());
+    let second: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new().delete(
+        ChangeDelete::from(users())
+            .set(0, 2i64)
+            .unwrap()
+            .set(1, "Bob")
+            .unwrap(),
+    );
+    assert_eq!(changesets, [first.build(), second.build()]);
+}
+
+#[test]
+fn debezium_stream_patchsets_keep_updates() {
+    let stream = [TX1_CREATE, TX2_UPDATE, TX2_DELETE];
+    let patchsets =
+        debezium_stream_to_patchsets(stream, &test_schema(), &default_adapter()).unwrap();
+
+    let first: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new().insert(alice());
+    let second: PatchSet<TestUsersTable, String, Vec<u8>> = PatchSet::new()
+        .update(
+            Update::<_, PatchsetFormat, _, _>::from(users())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "Alicia")
+                .unwrap()
+                .set_null(2)
+                .unwrap(),
+        )
+        .delete(PatchDelete::new(users(), vec![Value::Integer(2)]));
+    assert_eq!(patchsets, [first.build(), second.build()]);
+
+    // A changeset cannot record the update.
+    assert_eq!(
+        debezium_stream_to_changesets(stream, &test_schema(), &default_adapter()).unwrap_err(),
+        ConversionError::MissingData("before", "UPDATE")
+    );
+}
+
+#[test]
+fn debezium_stream_rejects_malformed_message() {
+    let result = debezium_stream_to_changesets(
+        [TX1_CREATE, r#"{"id":1,"__op":"x"}"#],
+        &test_schema(),
+        &default_adapter(),
+    );
+    assert!(matches!(result, Err(ConversionError::MalformedRecord(_))));
+}
+
+// -- Schema registry ----------------------------------------------------------
+
+#[test]
+fn debezium_registry_infers_one_schema_per_table() {
+    let mut registry = DebeziumSchemaRegistry::new();
+    let users = parse_flattened(CREATE).unwrap();
+    let orders =
+        parse_flattened(r#"{"order_id":7,"user_id":1,"total":9.5,"__op":"c","__table":"orders"}"#)
+            .unwrap();
+    registry.observe(&users).unwrap();
+    registry
+        .observe_keyed(&orders, &parse_flattened(r#"{"order_id":7}"#).unwrap())
+        .unwrap();
+
+    let users = registry.get("users").unwrap();
+    assert_eq!(users.column_names(), ["avatar", "id", "name"]);
+    assert_eq!(users.pk_indices(), [0], "no key: the first column");
+    let orders = registry.get("orders").unwrap();
+    assert_eq!(orders.column_names(), ["order_id", "total", "user_id"]);
+    assert_eq!(orders.pk_indices(), [0]);
+    assert!(registry.get("missing").is_none());
+}
+
+#[test]
+fn debezium_registry_extends_schema_with_new_columns() {
+    let mut registry = DebeziumSchemaRegistry::new();
+    let key = parse_flattened(r#"{"id":1}"#).unwrap();
+    registry
+        .observe_keyed(
+            &parse_flattened(r#"{"id":1,"name":"Alice","__op":"c","__table":"users"}"#).unwrap(),
+            &key,
+        )
+        .unwrap();
+    registry
+        .observe(
+            &parse_flattened(r#"{"id":1,"email":"a@x.io","__op":"u","__table":"users"}"#).unwrap(),
+        )
+        .unwrap();
+    registry.observe(&parse_flattened("null").unwrap()).unwrap();
+
+    let users = registry.get("users").unwrap();
+    assert_eq!(
+        users.column_names(),
+        ["id", "name", "email"],
+        "new columns are appended, keeping the registered positions"
+    );
+    assert_eq!(users.pk_indices(), [0], "the key survives the new column");
+
+    assert_eq!(
+        registry.observe(&parse_flattened(r#"{"id":1,"__op":"c"}"#).unwrap()),
+        Err(ConversionError::MissingData("__table", "record"))
+    );
+}
+
+// -- Enrichment ---------------------------------------------------------------
+
+/// The users table with a trailing `_synced_at` column the records never set.
+fn synced_users() -> TestUsersTable {
+    TestUsersTable(SimpleTable::new(
+        "users",
+        &["id", "name", "avatar", "_synced_at"],
+        &[0],
+    ))
+}
+
+#[test]
+fn debezium_insert_enriched_with_event_timestamp() {
+    let schema = TestSchema {
+        users: synced_users(),
+    };
+    let record = parse_flattened(
+        r#"{"id":1,"name":"Alice","__op":"c","__table":"users","__source_ts_ms":1700000000123}"#,
+    )
+    .unwrap();
+    let operation: Option<WireOperation<ChangesetFormat, _, _, _>> = record
+        .to_enriched_operation(
+            &schema,
+            &default_adapter(),
+            &[(3, Enrichment::EventTimestamp)],
+        )
+        .unwrap();
+
+    let expected = Insert::from(synced_users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap()
+        .set(3, 1_700_000_000_123i64)
+        .unwrap();
+    assert_eq!(
+        operation.unwrap().stage(ChangeSet::new()),
+        ChangeSet::new().insert(expected)
+    );
+}
+
+#[test]
+fn debezium_patchset_update_enriched_with_transaction_and_lsn() {
+    let schema = TestSchema {
+        users: synced_users(),
+    };
+    let record = parse_flattened(
+        r#"{"id":1,"name":"Alicia","__op":"u","__table":"users","__transaction_id":"571:53195829","__source_lsn":53195829}"#,
+    )
+    .unwrap();
+    let enrichments = [(2, Enrichment::TransactionId), (3, Enrichment::Lsn)];
+    let operation: Option<WireOperation<PatchsetFormat, _, _, _>> = record
+        .to_enriched_operation(&schema, &default_adapter(), &enrichments)
+        .unwrap();
+
+    let expected = Update::<_, PatchsetFormat, _, _>::from(synced_users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alicia")
+        .unwrap()
+        .set(2, "571:53195829")
+        .unwrap()
+        .set(3, 53_195_829i64)
+        .unwrap();
+    assert_eq!(
+        operation.unwrap().stage(PatchSet::new()),
+        PatchSet::new().update(expected)
+    );
+}
+
+#[test]
+fn debezium_enrichment_edge_cases() {
+    let schema = TestSchema {
+        users: synced_users(),
+    };
+    let adapter = default_adapter();
+
+    // Metadata the record lacks is recorded as NULL.
+    let record = parse_flattened(CREATE).unwrap();
+    assert_eq!(
+        record.enrichment::<String, Vec<u8>>(Enrichment::EventTimestamp),
+        Value::Null
+    );
+
+    // A delete writes no row, so it is left as converted.
+    let record = parse_flattened(DELETE).unwrap();
+    let enriched: Option<WireOperation<ChangesetFormat, _, _, _>> = record
+        .to_enriched_operation(&schema, &adapter, &[(3, Enrichment::Lsn)])
+        .unwrap();
+    let plain = record.to_operation(&schema, &adapter).unwrap();
+    assert_eq!(
+        enriched.unwrap().stage(ChangeSet::new()),
+        plain.unwrap().stage(ChangeSet::new())
+    );
+
+    // Tombstones still convert to nothing.
+    let tombstone = parse_flattened("null").unwrap();
+    let operation: Option<WireOperation<ChangesetFormat, _, _, _>> = tombstone
+        .to_enriched_operation(&schema, &adapter, &[(3, Enrichment::Lsn)])
+        .unwrap();
+    assert!(operation.is_none());
+
+    let record = parse_flattened(CREATE).unwrap();
+    let result: Result<Option<WireOperation<ChangesetFormat, _, _, _>>, _> =
+        record.to_enriched_operation(&schema, &adapter, &[(4, Enrichment::Lsn)]);
+    assert!(matches!(result, Err(ConversionError::Enrichment(_))));
+}
+
+// -- JSON arrays and objects --------------------------------------------------
+
+/// Users with a `PostgreSQL` `text[]` column and a `jsonb` column.
+fn tagged_users() -> TestUsersTable {
+    TestUsersTable(SimpleTable::new("users", &["id", "tags", "profile"], &[0]))
+}
+
+const TAGGED: &str = r#"{"id":1,"tags":["admin","ops"],"profile":{"age":30,"langs":["it","en"]},"__op":"c","__table":"users"}"#;
+
+#[test]
+fn debezium_array_in_text_column_is_rejected_by_default() {
+    let schema = TestSchema {
+        users: tagged_users(),
+    };
+    let record = parse_flattened(TAGGED).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &schema, &default_adapter());
+    assert!(matches!(result, Err(ConversionError::Decode(_))));
+}
+
+#[test]
+fn debezium_array_and_jsonb_columns_stored_as_json_text_when_lenient() {
+    let schema = TestSchema {
+        users: tagged_users(),
+    };
+    let adapter = default_adapter().with(WireType::Text, TextOrJsonDecoder);
+    let record = parse_flattened(TAGGED).unwrap();
+    let changeset: ChangeSet<TestUsersTable, String, Vec<u8>> =
+        ChangeSet::new().digest(&record, &schema, &adapter).unwrap();
+
+    let expected = Insert::from(tagged_users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, r#"["admin","ops"]"#)
+        .unwrap()
+        .set(2, r#"{"age":30,"langs":["it","en"]}"#)
+        .unwrap();
+    assert_eq!(changeset, ChangeSet::new().insert(expected));
+
+    // Plain strings still decode as text, and other scalars still error.
+    let record = parse_flattened(r#"{"id":2,"tags":"solo","__op":"c","__table":"users"}"#).unwrap();
+    let changeset: ChangeSet<TestUsersTable, String, Vec<u8>> =
+        ChangeSet::new().digest(&record, &schema, &adapter).unwrap();
+    let expected = Insert::from(tagged_users())
+        .set(0, 2i64)
+        .unwrap()
+        .set(1, "solo")
+        .unwrap();
+    assert_eq!(changeset, ChangeSet::new().insert(expected));
+    let record = parse_flattened(r#"{"id":3,"tags":7,"__op":"c","__table":"users"}"#).unwrap();
+    let result: Result<ChangeSet<TestUsersTable, String, Vec<u8>>, ConversionError> =
+        ChangeSet::new().digest(&record, &schema, &adapter);
+    assert!(matches!(result, Err(ConversionError::Decode(_))));
+}
+
+// -- Column decoding ---------------------------------------------------------
+
+fn decode(
+    wire_type: WireType,
+    json: &str,
+) -> Result<Value<String, Vec<u8>>, sqlite_diff_rs::DecodeError> {
+    let value: serde_json::Value = serde_json::from_str(json).unwrap();
+    default_adapter().decode(DebeziumColumn {
+        column_name: "c",
+        wire_type,
+        value: &value,
+    })
+}
+
+#[test]
+fn debezium_decimal_strings_must_be_numeric() {
+    for wire in ["12.50", "-0.001", "1e-3", ".5", "7.", "NaN", "-Infinity"] {
+        assert_eq!(
+            decode(WireType::Decimal, &format!("{wire:?}")).unwrap(),
+            Value::Text(wire.into()),
+        );
+    }
+    assert_eq!(
+        decode(WireType::Decimal, "12.5").unwrap(),
+        Value::Text("12.5".into())
+    );
+    // `decimal.handling.mode=precise` sends the unscaled value in base64.
+    for wire in ["\"BNI=\"", "\"\"", "\"1e\"", "\"-\"", "\".\""] {
+        assert!(
+            matches!(
+                decode(WireType::Decimal, wire),
+                Err(sqlite_diff_rs::DecodeError::WrongPayloadKind { .. })
+            ),
+            "{wire}"
+        );
+    }
+}
+
+#[test]
+fn debezium_dates_accept_epoch_day_counts() {
+    for (days, date) in [
+        (0, "1970-01-01"),
+        (19_723, "2024-01-01"),
+        (19_782, "2024-02-29"),
+        (-1, "1969-12-31"),
+        (-719_528, "0000-01-01"),
+        (2_932_896, "9999-12-31"),
+    ] {
+        assert_eq!(
+            decode(WireType::Date, &days.to_string()).unwrap(),
+            Value::Text(date.into()),
+        );
+    }
+    assert_eq!(
+        decode(WireType::Date, "\"2024-01-01\"").unwrap(),
+        Value::Text("2024-01-01".into())
+    );
+    for days in ["-719529", "2932897", "1.5"] {
+        assert!(decode(WireType::Date, days).is_err(), "{days}");
+    }
+}
+
+#[test]
+fn debezium_epoch_timestamps_are_rejected() {
+    // `io.debezium.time.MicroTimestamp` and `io.debezium.time.MicroTime`.
+    for wire_type in [WireType::Timestamp, WireType::Time] {
+        assert!(matches!(
+            decode(wire_type, "1700000000000000"),
+            Err(sqlite_diff_rs::DecodeError::WrongPayloadKind { .. })
+        ));
+    }
+    assert_eq!(
+        decode(WireType::TimestampTz, "\"2024-01-01T00:00:00Z\"").unwrap(),
+        Value::Text("2024-01-01T00:00:00Z".into())
+    );
+}
@@ -13,8 +13,9 @@ use alloc::vec::Vec;
 
 use sqlite_diff_rs::maxwell::{ConversionError, Maxwell, Message, OpType};
 use sqlite_diff_rs::{
-    ChangeSet, ChangesetOp, DecodeError, DynTable, NamedColumns, PatchSet, SchemaWithPK,
-    SimpleTable, TypeMap, Value, WireColumnTypes, WireSchema, WireType,
+    ChangeSet, ChangesetFormat, ChangesetOp, DecodeError, DiffOps, DynTable, Enrichment, Insert,
+    NamedColumns, PatchSet, SchemaWithPK, SimpleTable, TypeMap, Update, Value, WireColumnTypes,
+    WireOperation, WireSchema, WireType,
 };
 
 // ---------------------------------------------------------------------------
@@ -388,3 +389,122 @@ fn maxwell_changeset_update_captures_changed_pk() {
         other => panic!("expected update, got {other:?}"),
     }
 }
+
+#[test]
+fn maxwell_unsigned_bigint_pk_above_i64_max_is_kept_as_text() {
+    // MySQL `bigint unsigned` keys above `i64::MAX` must not be rounded to a
+    // float: 2^64 - 1 and 2^64 - 2 are the same `f64`, so two distinct rows
+    // would collapse onto one key.
+    let schema = test_schema();
+    let adapter = default_adapter();
+    let row = |id: &str| {
+        let mut data = data_map(0, "Alice", true);
+        data.insert("id".to_string(), serde_json::from_str(id).unwrap());
+        message(OpType::Insert, data, None)
+    };
+
+    let cs: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new()
+        .digest(&row("18446744073709551615"), &schema, &adapter)
+        .unwrap()
+        .digest(&row("18446744073709551614"), &schema, &adapter)
+        .unwrap();
+
+    let keys: Vec<_> = cs
+        .iter()
+        .map(|op| match op {
+            ChangesetOp::Insert { values, .. } => values[0].clone(),
+            other => panic!("expected insert, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            Value::Text("18446744073709551615".to_string()),
+            Value::Text("18446744073709551614".to_string()),
+        ]
+    );
+}
+
+// -- Enrichment ---------------------------------------------------------------
+
+#[test]
+fn maxwell_insert_enriched_with_event_timestamp() {
+    // id, name, active, then a `_synced_at` column maxwell never sets.
+    let synced = TestUsersTable(SimpleTable::new(
+        "users",
+        &["id", "name", "active", "_synced_at"],
+        &[0],
+    ));
+    let schema = TestSchema {
+        users: synced.clone(),
+    };
+    let mut msg = message(OpType::Insert, data_map(1, "Alice", true), None);
+    msg.ts = Some(1_700_000_000);
+    msg.xid = Some(42);
+
+    let operation: Option<WireOperation<ChangesetFormat, _, _, _>> = msg
+        .to_enriched_operation(
+            &schema,
+            &default_adapter(),
+            &[(3, Enrichment::EventTimestamp)],
+        )
+        .unwrap();
+    let cs = operation.unwrap().stage(ChangeSet::new());
+
+    let expected = Insert::from(synced)
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap()
+        .set(2, 1i64)
+        .unwrap()
+        .set(3, 1_700_000_000_000i64)
+        .unwrap();
+    assert_eq!(cs, ChangeSet::new().insert(expected));
+
+    assert_eq!(
+        msg.enrichment::<String, Vec<u8>>(Enrichment::TransactionId),
+        Value::Integer(42)
+    );
+    assert_eq!(
+        msg.enrichment::<String, Vec<u8>>(Enrichment::Lsn),
+        Value::Null
+    );
+}
+
+#[test]
+fn maxwell_changeset_update_enrichment_sets_new_value_only() {
+    let synced = TestUsersTable(SimpleTable::new(
+        "users",
+        &["id", "name", "active", "_synced_at"],
+        &[0],
+    ));
+    let schema = TestSchema {
+        users: synced.clone(),
+    };
+    let mut msg = message(
+        OpType::Update,
+        data_map(1, "Alicia", true),
+        Some(data_map(1, "Alice", true)),
+    );
+    msg.position = Some("master.000006:800911".to_string());
+
+    let operation: Option<WireOperation<ChangesetFormat, _, _, _>> = msg
+        .to_enriched_operation(&schema, &default_adapter(), &[(3, Enrichment::Lsn)])
+        .unwrap();
+    // The changed name pairs old and new values, the unchanged columns
+    // repeat theirs, and the enriched column has a new value only.
+    let expected = Update::<_, ChangesetFormat, _, _>::from(synced)
+        .set(0, 1i64, 1i64)
+        .unwrap()
+        .set(1, "Alice", "Alicia")
+        .unwrap()
+        .set(2, 1i64, 1i64)
+        .unwrap()
+        .set_new(3, "master.000006:800911")
+        .unwrap();
+    assert_eq!(
+        operation.unwrap().stage(ChangeSet::new()),
+        ChangeSet::new().update(expected)
+    );
+}
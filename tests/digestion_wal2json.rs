@@ -12,6 +12,7 @@ use alloc::vec::Vec;
 
 use sqlite_diff_rs::wal2json::{
     Action, ChangeV1, Column, ConversionError, MessageV2, OldKeys, Wal2Json, parse_v2,
+    slot_batch_to_changeset,
 };
 use sqlite_diff_rs::{
     ChangeSet, ChangesetOp, DecodeError, DynTable, NamedColumns, PatchSet, SchemaWithPK,
@@ -716,6 +717,60 @@ fn w2j_v2_lsn_does_not_affect_digest() {
     );
 }
 
+// -- Replication-slot batches ----------------------------------------------
+
+const SLOT_BATCH: [&str; 6] = [
+    r#"{"action":"B","lsn":"0/16B2270"}"#,
+    r#"{"action":"I","schema":"public","table":"users","lsn":"0/16B2298","columns":[{"name":"id","type":"integer","value":1},{"name":"name","type":"text","value":"Alice"},{"name":"active","type":"boolean","value":true}]}"#,
+    r#"{"action":"I","schema":"public","table":"users","lsn":"0/16B2300","columns":[{"name":"id","type":"integer","value":2},{"name":"name","type":"text","value":"Bob"},{"name":"active","type":"boolean","value":false}]}"#,
+    r#"{"action":"U","schema":"public","table":"users","lsn":"1/A0","columns":[{"name":"id","type":"integer","value":3},{"name":"name","type":"text","value":"Carol"},{"name":"active","type":"boolean","value":true}],"identity":[{"name":"id","type":"integer","value":3},{"name":"name","type":"text","value":"Caro"},{"name":"active","type":"boolean","value":true}]}"#,
+    r#"{"action":"D","schema":"public","table":"users","lsn":"0/16B2400","identity":[{"name":"id","type":"integer","value":2},{"name":"name","type":"text","value":"Bob"},{"name":"active","type":"boolean","value":false}]}"#,
+    r#"{"action":"C","lsn":"0/16B2500"}"#,
+];
+
+#[test]
+fn w2j_slot_batch_consolidates_into_one_changeset() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+    let (bytes, lsn) = slot_batch_to_changeset(SLOT_BATCH, &schema, &adapter).unwrap();
+
+    let mut expected: ChangeSet<TestUsersTable, String, Vec<u8>> = ChangeSet::new();
+    for line in SLOT_BATCH {
+        expected = expected
+            .digest(&parse_v2(line).unwrap(), &schema, &adapter)
+            .unwrap();
+    }
+    // Bob's insert and delete cancel out, leaving Alice's insert and Carol's update.
+    assert_eq!(expected.len(), 2);
+    assert_eq!(bytes, expected.build());
+    // `1/A0` is the highest position even though it is not the last message.
+    assert_eq!(lsn, Some(0x1_0000_00A0));
+}
+
+#[test]
+fn w2j_slot_batch_without_lsn_reports_none() {
+    let batch = [
+        r#"{"action":"I","schema":"public","table":"users","columns":[{"name":"id","type":"integer","value":1}]}"#,
+    ];
+    let (bytes, lsn) = slot_batch_to_changeset(batch, &test_schema(), &default_adapter()).unwrap();
+    assert!(!bytes.is_empty());
+    assert_eq!(lsn, None);
+}
+
+#[test]
+fn w2j_slot_batch_rejects_malformed_messages() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+    assert!(matches!(
+        slot_batch_to_changeset(["{not json"], &schema, &adapter),
+        Err(ConversionError::MalformedMessage(_))
+    ));
+    assert_eq!(
+        slot_batch_to_changeset([r#"{"action":"B","lsn":"16B2270"}"#], &schema, &adapter),
+        Err(ConversionError::InvalidLsn("16B2270".into()))
+    );
+}
+
 // -- Changeset UPDATE captures the old-row image ---------------------------
 //
 // The changeset format stores old and new per column, so the digest must read
@@ -828,6 +883,69 @@ fn w2j_v2_changeset_update_default_identity_captures_pk_only() {
     }
 }
 
+#[test]
+fn w2j_v2_changeset_update_full_identity_captures_every_old_column() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+
+    // REPLICA IDENTITY FULL: identity is the whole old row, every column
+    // changes except the key.
+    let msg = MessageV2 {
+        action: Action::U,
+        schema: Some("public".to_string()),
+        table: Some("users".to_string()),
+        columns: Some(all_columns(1, "Alicia", false)),
+        identity: Some(all_columns(1, "Alice", true)),
+        lsn: None,
+    };
+
+    let cs: ChangeSet<TestUsersTable, String, Vec<u8>> =
+        ChangeSet::new().digest(&msg, &schema, &adapter).unwrap();
+    let ops: Vec<_> = cs.iter().collect();
+    match &ops[0] {
+        ChangesetOp::Update { values, .. } => {
+            assert_eq!(values[0].0, Some(Value::Integer(1)), "old id captured");
+            assert_eq!(values[1].0, Some(Value::Text("Alice".to_string())));
+            assert_eq!(values[2].0, Some(Value::Integer(1)), "old active captured");
+            assert_eq!(values[2].1, Some(Value::Integer(0)), "new active present");
+        }
+        other => panic!("expected update, got {other:?}"),
+    }
+}
+
+#[test]
+fn w2j_v2_changeset_update_without_identity_falls_back_to_pk() {
+    let schema = test_schema();
+    let adapter = default_adapter();
+
+    // No identity image: the key is unchanged, so the old key is the new one.
+    let msg = MessageV2 {
+        action: Action::U,
+        schema: Some("public".to_string()),
+        table: Some("users".to_string()),
+        columns: Some(all_columns(1, "Alicia", true)),
+        identity: None,
+        lsn: None,
+    };
+
+    let cs: ChangeSet<TestUsersTable, String, Vec<u8>> =
+        ChangeSet::new().digest(&msg, &schema, &adapter).unwrap();
+    let ops: Vec<_> = cs.iter().collect();
+    match &ops[0] {
+        ChangesetOp::Update { values, .. } => {
+            assert_eq!(
+                values[0].0,
+                Some(Value::Integer(1)),
+                "old pk taken from columns"
+            );
+            assert_eq!(values[1].0, None, "non-key old stays undefined");
+            assert_eq!(values[1].1, Some(Value::Text("Alicia".to_string())));
+            assert_eq!(values[2].0, None, "non-key old stays undefined");
+        }
+        other => panic!("expected update, got {other:?}"),
+    }
+}
+
 #[test]
 fn w2j_v1_changeset_update_captures_old_pk_from_oldkeys() {
     let schema = test_schema();
@@ -0,0 +1,120 @@
+//! Primary keys holding the same number as an INTEGER and as a REAL.
+//!
+//! `SQLite` compares `1` and `1.0` as equal, so `WHERE k = 1.0` finds the row
+//! stored with the key `1`. The session keys its changes on the stored
+//! values, though, whose storage class is part of the key: an UPDATE or
+//! DELETE reaching a row through the other class consolidates with the
+//! row's earlier changes and keeps its stored key, while an INSERT always
+//! starts a row of its own. These tests check that the builder does the
+//! same, against rusqlite in both formats.
+//!
+//! Only an untyped key column is compared with rusqlite. A table cannot hold
+//! both `1` and `1.0` as keys, so a second INSERT of an equal number is only
+//! checked on the builder. A REAL column stores an integral key as an
+//! integer, which the session then fails to match with the row's later
+//! changes; the builder knows no column types and does not reproduce that.
+
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::assert_bit_parity;
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, DiffSetBuilder, Insert, ParsedDiffSet,
+    PatchDelete, PatchSet, PatchsetFormat, SimpleTable, TableSchema, Update, Value,
+};
+
+/// An untyped key column, which stores every value as given.
+const CREATE: &str = "CREATE TABLE n (k PRIMARY KEY, v TEXT)";
+
+fn untyped() -> SimpleTable {
+    SimpleTable::new("n", &["k", "v"], &[0])
+}
+
+fn digest(statements: &[&str]) -> (Vec<u8>, Vec<u8>) {
+    let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    let mut patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new();
+    changeset.add_table(&untyped());
+    patchset.add_table(&untyped());
+    for sql in statements {
+        changeset.digest_sql(sql).unwrap();
+        patchset.digest_sql(sql).unwrap();
+    }
+    (changeset.build(), patchset.build())
+}
+
+#[test]
+fn test_updates_and_deletes_address_rows_through_equal_numbers() {
+    let dml = [
+        "INSERT INTO n VALUES (1, 'a')",
+        "INSERT INTO n VALUES (2, 'b')",
+        "INSERT INTO n VALUES (3, 'c')",
+        "INSERT INTO n VALUES (2.5, 'd')",
+        "UPDATE n SET v = 'a2' WHERE k = 1.0",
+        "UPDATE n SET v = 'b2' WHERE k = 2",
+        "DELETE FROM n WHERE k = 3.0",
+        "UPDATE n SET v = 'd2' WHERE k = 2.5",
+    ];
+    let (changeset, patchset) = digest(&dml);
+    let mut statements = vec![CREATE];
+    statements.extend(dml);
+    assert_bit_parity(&statements, &changeset, &patchset);
+}
+
+#[test]
+fn test_builder_keeps_the_stored_key() {
+    let insert = Insert::from(untyped())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "a")
+        .unwrap();
+    let update = Update::<_, ChangesetFormat, _, _>::from(untyped())
+        .set(0, 1.0, 1.0)
+        .unwrap()
+        .set(1, "a", "b")
+        .unwrap();
+    let consolidated: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(insert.clone()).update(update);
+    let expected: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().insert(
+        Insert::from(untyped())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "b")
+            .unwrap(),
+    );
+    assert_eq!(consolidated.build(), expected.build());
+
+    let cancelled: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+        .insert(insert.clone())
+        .delete(PatchDelete::new(untyped(), vec![Value::Real(1.0)]));
+    assert!(cancelled.is_empty());
+
+    // 1.5 equals no integer, and an INSERT never reaches another row.
+    let distinct: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(insert)
+        .insert(
+            Insert::from(untyped())
+                .set(0, 1.0)
+                .unwrap()
+                .set(1, "c")
+                .unwrap(),
+        )
+        .delete(ChangeDelete::from(untyped()).set(0, 1.5).unwrap());
+    assert_eq!(distinct.len(), 3);
+}
+
+#[test]
+fn test_parsed_rows_are_addressed_through_equal_numbers() {
+    let staged: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().insert(
+        Insert::from(untyped())
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "a")
+            .unwrap(),
+    );
+    let ParsedDiffSet::Patchset(parsed) = ParsedDiffSet::parse(&staged.build()).unwrap() else {
+        unreachable!();
+    };
+    let schema: TableSchema<String> = TableSchema::new("n".into(), 2, vec![1, 0]);
+    let cancelled: DiffSetBuilder<PatchsetFormat, TableSchema<String>, String, Vec<u8>> =
+        DiffSetBuilder::from(parsed).delete(PatchDelete::new(schema, vec![Value::Real(1.0)]));
+    assert!(cancelled.is_empty());
+}
@@ -0,0 +1,278 @@
+//! Two [`Replicator`]s exchanging patchsets until they hold the same rows.
+
+use sqlite_diff_rs::replicator::{Conflict, ConflictKind, Replicator, ReplicatorError};
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, ParsedDiffSet, PatchDelete,
+    PatchSet, PatchsetFormat, SimpleTable, Update, Value,
+};
+
+type Row = Vec<Value<String, Vec<u8>>>;
+
+fn table() -> SimpleTable {
+    SimpleTable::new("notes", &["id", "body"], &[0])
+}
+
+fn insert(id: i64, body: &str) -> Insert<SimpleTable, String, Vec<u8>> {
+    Insert::from(table())
+        .set(0, id)
+        .unwrap()
+        .set(1, body)
+        .unwrap()
+}
+
+fn update(id: i64, body: &str) -> Update<SimpleTable, PatchsetFormat, String, Vec<u8>> {
+    Update::<_, PatchsetFormat, _, _>::from(table())
+        .set(0, id)
+        .unwrap()
+        .set(1, body)
+        .unwrap()
+}
+
+fn delete(id: i64) -> PatchDelete<SimpleTable, String, Vec<u8>> {
+    PatchDelete::new(table(), vec![Value::Integer(id)])
+}
+
+fn rows(replicator: &Replicator<SimpleTable>) -> Vec<Row> {
+    let mut rows: Vec<Row> = replicator.rows("notes").map(<[_]>::to_vec).collect();
+    rows.sort_by_key(|row| match row[0] {
+        Value::Integer(id) => id,
+        _ => unreachable!(),
+    });
+    rows
+}
+
+fn row(id: i64, body: &str) -> Row {
+    vec![Value::Integer(id), Value::Text(body.into())]
+}
+
+#[test]
+fn replicators_converge_after_exchanging_patchsets() {
+    let mut alice: Replicator<SimpleTable> = Replicator::new();
+    let mut bob: Replicator<SimpleTable> = Replicator::new();
+
+    alice.insert(insert(1, "groceries")).unwrap();
+    alice.insert(insert(2, "call mum")).unwrap();
+    alice.update(update(1, "groceries: milk")).unwrap();
+    assert!(!alice.is_synced());
+    assert!(
+        bob.apply_patchset(&alice.take_patchset())
+            .unwrap()
+            .is_empty()
+    );
+    assert!(alice.is_synced());
+    assert_eq!(rows(&alice), rows(&bob));
+
+    bob.insert(insert(3, "dentist")).unwrap();
+    bob.update(update(2, "call mum back")).unwrap();
+    bob.delete(delete(1)).unwrap();
+    alice.insert(insert(4, "water plants")).unwrap();
+
+    let from_bob = bob.take_patchset();
+    let from_alice = alice.take_patchset();
+    assert!(alice.apply_patchset(&from_bob).unwrap().is_empty());
+    assert!(bob.apply_patchset(&from_alice).unwrap().is_empty());
+
+    let expected = vec![
+        row(2, "call mum back"),
+        row(3, "dentist"),
+        row(4, "water plants"),
+    ];
+    assert_eq!(rows(&alice), expected);
+    assert_eq!(rows(&bob), expected);
+    assert!(alice.is_synced() && bob.is_synced());
+}
+
+#[test]
+fn staged_changes_cancel_out_before_sending() {
+    let mut local: Replicator<SimpleTable> = Replicator::new();
+    local.insert(insert(1, "draft")).unwrap();
+    local.update(update(1, "still a draft")).unwrap();
+    local.delete(delete(1)).unwrap();
+
+    assert!(local.take_patchset().is_empty());
+    assert_eq!(local.rows("notes").count(), 0);
+}
+
+#[test]
+fn conflicting_local_changes_are_refused() {
+    let mut local: Replicator<SimpleTable> = Replicator::new();
+    local.insert(insert(1, "first")).unwrap();
+    local.take_patchset();
+
+    assert_eq!(
+        local.insert(insert(1, "again")),
+        Err(Conflict {
+            table: "notes".into(),
+            pk: vec![Value::Integer(1)],
+            kind: ConflictKind::Exists,
+        })
+    );
+    assert_eq!(
+        local.update(update(2, "nowhere")).unwrap_err().kind,
+        ConflictKind::Missing
+    );
+    assert_eq!(
+        local.delete(delete(2)).unwrap_err().kind,
+        ConflictKind::Missing
+    );
+    assert!(local.is_synced());
+    assert_eq!(rows(&local), vec![row(1, "first")]);
+}
+
+#[test]
+fn conflicting_inbound_operations_are_skipped_and_reported() {
+    let mut alice: Replicator<SimpleTable> = Replicator::new();
+    let mut bob: Replicator<SimpleTable> = Replicator::new();
+    alice.insert(insert(1, "shared")).unwrap();
+    bob.apply_patchset(&alice.take_patchset()).unwrap();
+
+    // Both sides touch row 1 concurrently and alice also adds row 2, which
+    // bob has independently created.
+    alice.delete(delete(1)).unwrap();
+    alice.insert(insert(2, "alice's")).unwrap();
+    alice.insert(insert(3, "only alice")).unwrap();
+    bob.delete(delete(1)).unwrap();
+    bob.insert(insert(2, "bob's")).unwrap();
+
+    let conflicts = bob.apply_patchset(&alice.take_patchset()).unwrap();
+    assert_eq!(
+        conflicts,
+        vec![
+            Conflict {
+                table: "notes".into(),
+                pk: vec![Value::Integer(1)],
+                kind: ConflictKind::Missing,
+            },
+            Conflict {
+                table: "notes".into(),
+                pk: vec![Value::Integer(2)],
+                kind: ConflictKind::Exists,
+            },
+        ]
+    );
+    assert_eq!(rows(&bob), vec![row(2, "bob's"), row(3, "only alice")]);
+}
+
+#[test]
+fn updates_of_another_table_width_are_reported() {
+    let mut alice: Replicator<SimpleTable> = Replicator::new();
+    let mut bob: Replicator<SimpleTable> = Replicator::new();
+    let wider = SimpleTable::new("notes", &["id", "body", "tag"], &[0]);
+    bob.insert(
+        Insert::from(wider)
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, "old")
+            .unwrap()
+            .set(2, "t")
+            .unwrap(),
+    )
+    .unwrap();
+    bob.take_patchset();
+
+    alice.insert(insert(1, "old")).unwrap();
+    alice.take_patchset();
+    alice.update(update(1, "new")).unwrap();
+
+    let conflicts = bob.apply_patchset(&alice.take_patchset()).unwrap();
+    assert_eq!(
+        conflicts,
+        vec![Conflict {
+            table: "notes".into(),
+            pk: vec![Value::Integer(1)],
+            kind: ConflictKind::ColumnCount,
+        }]
+    );
+    assert_eq!(
+        bob.get("notes", &[Value::Integer(1)]),
+        Some(
+            &[
+                Value::Integer(1),
+                Value::Text("old".into()),
+                Value::Text("t".into())
+            ][..]
+        )
+    );
+}
+
+#[test]
+fn changesets_and_garbage_are_rejected() {
+    let mut local: Replicator<SimpleTable> = Replicator::new();
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(insert(1, "x"));
+
+    assert_eq!(
+        local.apply_patchset(&changeset.build()),
+        Err(ReplicatorError::NotPatchset)
+    );
+    assert!(matches!(
+        local.apply_patchset(&[b'P', 0xFF]),
+        Err(ReplicatorError::Parse(_))
+    ));
+    assert_eq!(local.rows("notes").count(), 0);
+}
+
+#[test]
+fn conflicts_are_checked_without_applying() {
+    let mut store: Replicator<SimpleTable> = Replicator::new();
+    for (id, body) in [(1, "a"), (2, "b"), (3, "c")] {
+        store.insert(insert(id, body)).unwrap();
+    }
+    let change = |id: i64, old: &str, new: &str| {
+        Update::<_, ChangesetFormat, _, _>::from(table())
+            .set(0, id, id)
+            .unwrap()
+            .set(1, old, new)
+            .unwrap()
+    };
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(insert(4, "new"))
+        .insert(insert(1, "taken"))
+        .update(change(2, "b", "x"))
+        .update(change(3, "stale", "y"))
+        .delete(ChangeDelete::from_values(table(), row(6, "gone")))
+        .delete(ChangeDelete::from_values(table(), row(7, "stale")));
+    let mut changeset_store = store.clone();
+    changeset_store.insert(insert(7, "fresh")).unwrap();
+    let parsed = ParsedDiffSet::parse(&changeset.build()).unwrap();
+
+    let mut conflicts = parsed.check_conflicts(&changeset_store);
+    conflicts.sort_by_key(|conflict| match conflict.pk[0] {
+        Value::Integer(id) => id,
+        _ => unreachable!(),
+    });
+    let expected = [
+        (1, ConflictKind::Exists),
+        (3, ConflictKind::Data),
+        (6, ConflictKind::Missing),
+        (7, ConflictKind::Data),
+    ];
+    assert_eq!(
+        conflicts,
+        expected.map(|(id, kind)| Conflict {
+            table: "notes".into(),
+            pk: vec![Value::Integer(id)],
+            kind,
+        })
+    );
+
+    // A patchset carries no old values, so only keys are checked.
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+        .insert(insert(1, "taken"))
+        .update(update(3, "y"))
+        .delete(delete(9));
+    let parsed = ParsedDiffSet::parse(&patchset.build()).unwrap();
+    let mut conflicts = parsed.check_conflicts(&store);
+    conflicts.sort_by_key(|conflict| match conflict.pk[0] {
+        Value::Integer(id) => id,
+        _ => unreachable!(),
+    });
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(|conflict| conflict.kind)
+            .collect::<Vec<_>>(),
+        [ConflictKind::Exists, ConflictKind::Missing]
+    );
+    assert_eq!(rows(&store), vec![row(1, "a"), row(2, "b"), row(3, "c")]);
+}
@@ -5,13 +5,20 @@
 //! - Reversing complete changesets
 //! - Applying a changeset and its reverse yields the original state
 //! - Double reversal is idempotent (reverse(reverse(x)) == x)
+//! - Reversal matches `SQLite`'s own `sqlite3changeset_invert`
+//! - Undo changesets delete child rows before the parents they reference
 
 #![cfg(feature = "testing")]
 
 use rusqlite::Connection;
-use sqlite_diff_rs::testing::{apply_changeset, get_all_rows};
+use rusqlite::session::ConflictAction;
+use sqlite_diff_rs::testing::{
+    SqlType, TypedSimpleTable, apply_changeset, apply_changeset_with, assert_reversible_apply,
+    assert_reversible_apply_with_setup, get_all_rows, session_changeset_and_patchset_with_setup,
+};
 use sqlite_diff_rs::{
-    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, Reverse, SimpleTable, Update,
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, ParsedDiffSet, PatchDelete,
+    PatchSet, PatchsetFormat, Reverse, ShadowPatchSet, SimpleTable, Update,
 };
 
 // =============================================================================
@@ -159,8 +166,11 @@ fn test_double_reverse_complex() {
         .update(update)
         .delete(delete);
 
-    let double_reversed = original.clone().reverse().reverse();
-    assert_eq!(original, double_reversed);
+    // Reversing drops the new value the UPDATE repeats for its unchanged key,
+    // as `sqlite3changeset_invert` does, so the reverse is the fixed point.
+    let reversed = original.reverse();
+    let double_reversed = reversed.clone().reverse().reverse();
+    assert_eq!(reversed, double_reversed);
 }
 
 // =============================================================================
@@ -378,6 +388,111 @@ fn test_apply_and_reverse_multiple_operations() {
     assert_eq!(final_rows, conn2_rows);
 }
 
+#[test]
+fn test_assert_reversible_apply_mixed_operations() {
+    let typed = TypedSimpleTable::new(
+        "users",
+        &[("id", SqlType::Integer), ("name", SqlType::Text)],
+        &[0],
+    );
+    let schema: SimpleTable = (*typed).clone();
+
+    let insert = Insert::<_, String, Vec<u8>>::from(schema.clone())
+        .set(0, 3i64)
+        .unwrap()
+        .set(1, "Charlie")
+        .unwrap();
+    let update = Update::<SimpleTable, ChangesetFormat, String, Vec<u8>>::from(schema.clone())
+        .set(0, 1i64, 1i64)
+        .unwrap()
+        .set(1, "Alice", "Alicia")
+        .unwrap();
+    let delete = ChangeDelete::<_, String, Vec<u8>>::from(schema)
+        .set(0, 2i64)
+        .unwrap()
+        .set(1, "Bob")
+        .unwrap();
+
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(insert)
+        .update(update)
+        .delete(delete);
+
+    assert_reversible_apply_with_setup(
+        &[typed],
+        &[
+            "INSERT INTO users (id, name) VALUES (1, 'Alice')",
+            "INSERT INTO users (id, name) VALUES (2, 'Bob')",
+        ],
+        &changeset.build(),
+    );
+}
+
+#[test]
+fn test_assert_reversible_apply_inserts_on_empty_db() {
+    let typed = TypedSimpleTable::new(
+        "items",
+        &[("id", SqlType::Integer), ("data", SqlType::Blob)],
+        &[0],
+    );
+    let schema: SimpleTable = (*typed).clone();
+
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(
+            Insert::<_, String, Vec<u8>>::from(schema.clone())
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, vec![0xDEu8, 0xAD])
+                .unwrap(),
+        )
+        .insert(
+            Insert::<_, String, Vec<u8>>::from(schema)
+                .set(0, 2i64)
+                .unwrap()
+                .set_null(1)
+                .unwrap(),
+        );
+
+    assert_reversible_apply(&[typed], &changeset.build());
+}
+
+#[test]
+#[should_panic(expected = "Input should be a changeset")]
+fn test_assert_reversible_apply_rejects_patchset() {
+    let typed = TypedSimpleTable::new("items", &[("id", SqlType::Integer)], &[0]);
+    let schema: SimpleTable = (*typed).clone();
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> =
+        PatchSet::new().insert(Insert::from(schema).set(0, 1i64).unwrap());
+
+    assert_reversible_apply(&[typed], &patchset.build());
+}
+
+#[test]
+#[should_panic(expected = "Input should parse")]
+fn test_assert_reversible_apply_rejects_unparseable_input() {
+    let typed = TypedSimpleTable::new("items", &[("id", SqlType::Integer)], &[0]);
+    assert_reversible_apply(&[typed], &[0x42]);
+}
+
+#[test]
+#[should_panic(expected = "should apply cleanly to the starting state")]
+fn test_assert_reversible_apply_rejects_failed_forward_apply() {
+    let typed = TypedSimpleTable::new(
+        "users",
+        &[("id", SqlType::Integer), ("name", SqlType::Text)],
+        &[0],
+    );
+    let schema: SimpleTable = (*typed).clone();
+    let delete = ChangeDelete::<_, String, Vec<u8>>::from(schema)
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap();
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().delete(delete);
+
+    assert_reversible_apply(&[typed], &changeset.build());
+}
+
 #[test]
 fn test_reverse_with_composite_primary_key() {
     let schema = SimpleTable::new("orders", &["user_id", "order_id", "status"], &[0, 1]);
@@ -490,3 +605,251 @@ fn test_reverse_cancelled_operations() {
     let reversed = changeset.clone().reverse();
     assert_eq!(reversed.len(), 0);
 }
+
+// =============================================================================
+// Differential tests against SQLite's own changeset inversion
+// =============================================================================
+
+/// Records `tracked` after `setup` with a session, then returns the changeset
+/// and the copy inverted by `sqlite3changeset_invert`.
+fn sqlite_changeset_and_inverse(setup: &[&str], tracked: &[&str]) -> (Vec<u8>, Vec<u8>) {
+    let (changeset, _) = session_changeset_and_patchset_with_setup(setup, tracked);
+    let mut inverted = Vec::new();
+    rusqlite::session::invert_strm(&mut changeset.as_slice(), &mut inverted).unwrap();
+    (changeset, inverted)
+}
+
+/// Asserts that parsing SQLite's inverse equals reversing our parse.
+fn assert_inverse_matches_sqlite(setup: &[&str], tracked: &[&str]) {
+    let (changeset, inverted) = sqlite_changeset_and_inverse(setup, tracked);
+    let ParsedDiffSet::Changeset(ours) = ParsedDiffSet::parse(&changeset).unwrap() else {
+        panic!("session output must be a changeset");
+    };
+    let ParsedDiffSet::Changeset(theirs) = ParsedDiffSet::parse(&inverted).unwrap() else {
+        panic!("inverted session output must be a changeset");
+    };
+    let reversed = ours.reverse();
+    assert_eq!(theirs, reversed);
+    assert_eq!(Vec::from(reversed), inverted);
+}
+
+#[test]
+fn test_sqlite_invert_matches_reverse_mixed_operations() {
+    assert_inverse_matches_sqlite(
+        &[
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)",
+            "INSERT INTO users VALUES (1, 'Alice', 30)",
+            "INSERT INTO users VALUES (2, 'Bob', 25)",
+            "INSERT INTO users VALUES (3, 'Carol', NULL)",
+        ],
+        &[
+            "INSERT INTO users VALUES (4, 'Dave', 41)",
+            "UPDATE users SET age = 31 WHERE id = 1",
+            "UPDATE users SET name = 'Caroline', age = 28 WHERE id = 3",
+            "DELETE FROM users WHERE id = 2",
+        ],
+    );
+}
+
+#[test]
+fn test_sqlite_invert_matches_reverse_composite_key_and_blobs() {
+    assert_inverse_matches_sqlite(
+        &[
+            "CREATE TABLE files (owner TEXT, path TEXT, data BLOB, size REAL, \
+             PRIMARY KEY (path, owner))",
+            "CREATE TABLE tags (id INTEGER PRIMARY KEY, label TEXT)",
+            "INSERT INTO files VALUES ('alice', '/a', X'00FF', 2.0)",
+            "INSERT INTO files VALUES ('bob', '/b', X'DEAD', 2.5)",
+            "INSERT INTO tags VALUES (1, 'rust')",
+        ],
+        &[
+            "UPDATE files SET data = X'BEEF', size = 2.25 WHERE owner = 'alice'",
+            "INSERT INTO tags VALUES (2, 'sqlite')",
+            "DELETE FROM files WHERE owner = 'bob'",
+            "UPDATE tags SET label = NULL WHERE id = 1",
+        ],
+    );
+}
+
+#[test]
+fn test_sqlite_invert_matches_reverse_of_update_with_defined_new_key() {
+    // The builders let an UPDATE repeat its unchanged key in the new values,
+    // which the session extension never does.
+    let files = SimpleTable::new("files", &["owner", "path", "size"], &[1, 0]);
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new().update(
+        Update::<_, ChangesetFormat, _, _>::from(files)
+            .set(0, "alice", "alice")
+            .unwrap()
+            .set(1, "/a", "/a")
+            .unwrap()
+            .set(2, 2i64, 3i64)
+            .unwrap(),
+    );
+    let bytes = changeset.build();
+    let mut inverted = Vec::new();
+    rusqlite::session::invert_strm(&mut bytes.as_slice(), &mut inverted).unwrap();
+    assert_eq!(changeset.reverse().build(), inverted);
+}
+
+// =============================================================================
+// Shadow log of a patchset
+// =============================================================================
+
+#[test]
+fn test_shadow_patchset_reverse_log_restores_rows() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER);
+         INSERT INTO users VALUES (1, 'Alice', 30);
+         INSERT INTO users VALUES (2, 'Bob', 25);
+         INSERT INTO users VALUES (3, 'Carol', NULL);",
+    )
+    .unwrap();
+    let original = get_all_rows(&conn, "users");
+
+    let schema = SimpleTable::new("users", &["id", "name", "age"], &[0]);
+    let delete = |id: i64, name: &str, age: Option<i64>| {
+        let delete = ChangeDelete::<_, String, Vec<u8>>::from(schema.clone())
+            .set(0, id)
+            .unwrap()
+            .set(1, name)
+            .unwrap();
+        match age {
+            Some(age) => delete.set(2, age).unwrap(),
+            None => delete,
+        }
+    };
+    let shadowed = ShadowPatchSet::new()
+        .delete(delete(1, "Alice", Some(30)))
+        .delete(delete(3, "Carol", None))
+        .update(
+            Update::<_, ChangesetFormat, _, _>::from(schema.clone())
+                .set(0, 2i64, 2i64)
+                .unwrap()
+                .set(1, "Bob", "Robert")
+                .unwrap(),
+        )
+        .unwrap()
+        .insert(
+            Insert::from(schema.clone())
+                .set(0, 4i64)
+                .unwrap()
+                .set(1, "Dave")
+                .unwrap()
+                .set(2, 41i64)
+                .unwrap(),
+        );
+
+    // The patchset is the one staged without a shadow log.
+    let plain: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new()
+        .delete(PatchDelete::new(schema.clone(), vec![1i64.into()]))
+        .delete(PatchDelete::new(schema.clone(), vec![3i64.into()]))
+        .update(
+            Update::<_, PatchsetFormat, _, _>::from(schema.clone())
+                .set(0, 2i64)
+                .unwrap()
+                .set(1, "Robert")
+                .unwrap(),
+        )
+        .insert(
+            Insert::from(schema)
+                .set(0, 4i64)
+                .unwrap()
+                .set(1, "Dave")
+                .unwrap()
+                .set(2, 41i64)
+                .unwrap(),
+        );
+    assert_eq!(shadowed.build(), plain.build());
+
+    apply_changeset(&conn, &shadowed.build()).unwrap();
+    assert_eq!(
+        get_all_rows(&conn, "users"),
+        vec![
+            vec!["Integer(2)", "Text(\"Robert\")", "Integer(25)"],
+            vec!["Integer(4)", "Text(\"Dave\")", "Integer(41)"],
+        ]
+    );
+
+    apply_changeset(&conn, &shadowed.reverse_log().build()).unwrap();
+    assert_eq!(get_all_rows(&conn, "users"), original);
+}
+
+#[test]
+fn test_shadow_patchset_rejects_primary_key_change() {
+    let schema = SimpleTable::new("users", &["id", "name"], &[0]);
+    let update = Update::<_, ChangesetFormat, String, Vec<u8>>::from(schema)
+        .set(0, 1i64, 2i64)
+        .unwrap()
+        .set(1, "Alice", "Alicia")
+        .unwrap();
+    assert_eq!(
+        ShadowPatchSet::new().update(update).unwrap_err(),
+        sqlite_diff_rs::Error::PatchsetPkChange
+    );
+}
+
+// =============================================================================
+// Undo table order
+// =============================================================================
+
+/// Applies `parts` one changeset at a time, with foreign keys enforced, and
+/// returns how many foreign-key conflicts `SQLite` reported.
+fn foreign_key_conflicts(parts: &[ChangeSet<SimpleTable, String, Vec<u8>>]) -> usize {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "PRAGMA foreign_keys = ON;
+         CREATE TABLE parents (id INTEGER PRIMARY KEY);
+         CREATE TABLE children (id INTEGER PRIMARY KEY, parent INTEGER REFERENCES parents(id));
+         INSERT INTO parents VALUES (1), (2);
+         INSERT INTO children VALUES (1, 1), (2, 1), (3, 2);",
+    )
+    .unwrap();
+    let mut conflicts = 0;
+    for part in parts {
+        conflicts += apply_changeset_with(&conn, &part.build(), |_| {
+            ConflictAction::SQLITE_CHANGESET_OMIT
+        })
+        .unwrap()
+        .foreign_key;
+    }
+    assert!(get_all_rows(&conn, "children").is_empty());
+    assert!(get_all_rows(&conn, "parents").is_empty());
+    conflicts
+}
+
+#[test]
+fn test_reverse_for_undo_deletes_children_before_parents() {
+    let parents = SimpleTable::new("parents", &["id"], &[0]);
+    let children = SimpleTable::new("children", &["id", "parent"], &[0]);
+
+    let mut changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    changeset
+        .table(&parents)
+        .insert(vec![1i64.into()])
+        .insert(vec![2i64.into()]);
+    changeset
+        .table(&children)
+        .insert(vec![1i64.into(), 1i64.into()])
+        .insert(vec![2i64.into(), 1i64.into()])
+        .insert(vec![3i64.into(), 2i64.into()]);
+
+    let plain = changeset.clone().reverse();
+    let undo = changeset.reverse_for_undo();
+    assert_eq!(
+        undo.non_empty_tables().collect::<Vec<_>>(),
+        [&children, &parents]
+    );
+
+    // Same table sections as the plain reverse, in the opposite order.
+    let plain_parts = plain.split_by_table();
+    let undo_parts = undo.split_by_table();
+    let reordered: Vec<Vec<u8>> = plain_parts.iter().rev().map(ChangeSet::build).collect();
+    assert_eq!(
+        undo_parts.iter().map(ChangeSet::build).collect::<Vec<_>>(),
+        reordered
+    );
+
+    assert_eq!(foreign_key_conflicts(&undo_parts), 0);
+    assert_eq!(foreign_key_conflicts(&plain_parts), 1);
+}
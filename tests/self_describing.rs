@@ -0,0 +1,92 @@
+//! Tests for the self-describing changeset wrapper.
+//!
+//! A sender wraps a changeset with its tables' column names; the receiver
+//! reads the names back without any DDL, then strips the wrapper and applies
+//! the plain bytes with SQLite.
+
+#![cfg(feature = "testing")]
+
+use rusqlite::Connection;
+use sqlite_diff_rs::self_describing::{SelfDescribingChangeset, SelfDescribingError};
+use sqlite_diff_rs::testing::{apply_changeset, get_all_rows};
+use sqlite_diff_rs::{ChangeSet, DiffOps, Insert, SimpleTable};
+
+fn tables() -> [SimpleTable; 2] {
+    [
+        SimpleTable::new("users", &["id", "name", "email"], &[0]),
+        SimpleTable::new("tags", &["post_id", "tag"], &[0, 1]),
+    ]
+}
+
+fn changeset() -> Vec<u8> {
+    let [users, tags] = tables();
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new()
+        .insert(
+            Insert::from(users)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "Alice")
+                .unwrap()
+                .set(2, "alice@example.com")
+                .unwrap(),
+        )
+        .insert(
+            Insert::from(tags)
+                .set(0, 1i64)
+                .unwrap()
+                .set(1, "rust")
+                .unwrap(),
+        );
+    changeset.build()
+}
+
+#[test]
+fn test_column_names_round_trip_and_strip_applies() {
+    let changeset = changeset();
+    let wrapped = SelfDescribingChangeset::describe(changeset.clone(), &tables()).to_bytes();
+
+    let received = SelfDescribingChangeset::from_bytes(&wrapped).unwrap();
+    assert_eq!(
+        received.column_names("users").unwrap(),
+        ["id", "name", "email"]
+    );
+    assert_eq!(received.column_names("tags").unwrap(), ["post_id", "tag"]);
+    assert_eq!(received.column_names("posts"), None);
+    assert_eq!(received.tables().count(), 2);
+
+    let stripped = received.strip();
+    assert_eq!(stripped, changeset);
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT);
+         CREATE TABLE tags (post_id INTEGER, tag TEXT, PRIMARY KEY (post_id, tag));",
+    )
+    .unwrap();
+    apply_changeset(&conn, &stripped).unwrap();
+    assert_eq!(get_all_rows(&conn, "users").len(), 1);
+    assert_eq!(get_all_rows(&conn, "tags").len(), 1);
+}
+
+#[test]
+fn test_column_count_mismatch_is_rejected() {
+    let narrow = [SimpleTable::new("users", &["id", "name"], &[0])];
+    let wrapped = SelfDescribingChangeset::describe(changeset(), &narrow).to_bytes();
+
+    assert_eq!(
+        SelfDescribingChangeset::from_bytes(&wrapped),
+        Err(SelfDescribingError::ColumnCountMismatch {
+            table: "users".into(),
+            described: 2,
+            actual: 3,
+        })
+    );
+}
+
+#[test]
+fn test_plain_changeset_is_not_self_describing() {
+    assert_eq!(
+        SelfDescribingChangeset::from_bytes(&changeset()),
+        Err(SelfDescribingError::BadMagic)
+    );
+}
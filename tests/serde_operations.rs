@@ -0,0 +1,162 @@
+//! Tests for the `serde` feature on operation builders.
+//!
+//! Pending operations are persisted as JSON, read back, and flushed into a
+//! builder; the result must encode to the same bytes as a builder fed the
+//! original operations directly.
+
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+use sqlite_diff_rs::{
+    ChangeDelete, ChangeSet, ChangesetFormat, DiffOps, Insert, PatchDelete, SimpleTable,
+    TableSchema, Update, Value,
+};
+
+/// A queued changeset operation, as a work queue would persist it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum PendingOp {
+    Insert(Insert<SimpleTable, String, Vec<u8>>),
+    Update(Update<SimpleTable, ChangesetFormat, String, Vec<u8>>),
+    Delete(ChangeDelete<SimpleTable, String, Vec<u8>>),
+}
+
+fn users() -> SimpleTable {
+    SimpleTable::new("users", &["id", "name", "avatar"], &[0])
+}
+
+fn pending_ops() -> Vec<PendingOp> {
+    let insert = Insert::<_, String, Vec<u8>>::from(users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap()
+        .set(2, vec![0xde_u8, 0xad])
+        .unwrap();
+    let update = Update::<_, ChangesetFormat, String, Vec<u8>>::from(users())
+        .set(0, 2i64, 2i64)
+        .unwrap()
+        .set(1, "Bob", "Robert")
+        .unwrap();
+    let delete = ChangeDelete::<_, String, Vec<u8>>::from(users())
+        .set(0, 3i64)
+        .unwrap()
+        .set(1, "Carol")
+        .unwrap()
+        .set_null(2)
+        .unwrap();
+    vec![
+        PendingOp::Insert(insert),
+        PendingOp::Update(update),
+        PendingOp::Delete(delete),
+    ]
+}
+
+fn flush(ops: Vec<PendingOp>) -> ChangeSet<SimpleTable, String, Vec<u8>> {
+    ops.into_iter()
+        .fold(ChangeSet::new(), |builder, op| match op {
+            PendingOp::Insert(insert) => builder.insert(insert),
+            PendingOp::Update(update) => builder.update(update),
+            PendingOp::Delete(delete) => builder.delete(delete),
+        })
+}
+
+#[test]
+fn test_mixed_operations_round_trip_through_json() {
+    let ops = pending_ops();
+    let json = serde_json::to_string(&ops).unwrap();
+    let restored: Vec<PendingOp> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored, ops);
+    assert_eq!(flush(restored).build(), flush(ops).build());
+}
+
+#[test]
+fn test_patch_delete_round_trips_through_json() {
+    let delete = PatchDelete::<_, String, Vec<u8>>::new(users(), vec![Value::Integer(7)]);
+    let json = serde_json::to_string(&delete).unwrap();
+    let restored: PatchDelete<SimpleTable, String, Vec<u8>> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored, delete);
+}
+
+#[test]
+fn test_invalid_simple_tables_are_rejected() {
+    let table: SimpleTable =
+        serde_json::from_str(&serde_json::to_string(&users()).unwrap()).unwrap();
+    assert_eq!(table, users());
+
+    let parse =
+        |json: &str| serde_json::from_str::<SimpleTable>(json).map_err(|err| err.to_string());
+    let duplicate =
+        parse(r#"{"schema":{"name":"t","column_count":2,"pk_flags":[1,0]},"columns":["id","id"]}"#);
+    assert!(duplicate.unwrap_err().contains("Duplicate column name"));
+    let miscounted = parse(
+        r#"{"schema":{"name":"t","column_count":3,"pk_flags":[1,0,0]},"columns":["id","name"]}"#,
+    );
+    assert!(miscounted.unwrap_err().contains("does not match"));
+    let repeated_ordinal =
+        parse(r#"{"schema":{"name":"t","column_count":2,"pk_flags":[1,1]},"columns":["a","b"]}"#);
+    assert!(repeated_ordinal.unwrap_err().contains("does not match"));
+}
+
+#[test]
+fn test_operations_of_the_wrong_width_are_rejected() {
+    let table = serde_json::to_string(&users()).unwrap();
+    let parse_error =
+        |result: Result<PendingOp, serde_json::Error>| result.unwrap_err().to_string();
+
+    let insert =
+        format!(r#"{{"Insert":{{"table":{table},"values":[{{"Integer":1}}],"indirect":false}}}}"#);
+    assert!(
+        parse_error(serde_json::from_str(&insert))
+            .contains("1 values given for table with 3 columns")
+    );
+
+    let update = format!(
+        r#"{{"Update":{{"table":{table},"values":[[{{"Integer":1}},null],[null,{{"Text":"x"}}]],"indirect":false}}}}"#
+    );
+    assert!(
+        parse_error(serde_json::from_str(&update))
+            .contains("2 values given for table with 3 columns")
+    );
+
+    let delete = format!(r#"{{"Delete":{{"table":{table},"values":[],"indirect":false}}}}"#);
+    assert!(
+        parse_error(serde_json::from_str(&delete))
+            .contains("0 values given for table with 3 columns")
+    );
+
+    let patch_delete =
+        format!(r#"{{"table":{table},"pk":[{{"Integer":1}},{{"Integer":2}}],"indirect":false}}"#);
+    let err = serde_json::from_str::<PatchDelete<SimpleTable, String, Vec<u8>>>(&patch_delete)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("2 key values given for table with 1 primary-key columns"));
+}
+
+#[test]
+fn test_table_schemas_of_the_wrong_width_are_rejected() {
+    let schema = TableSchema::<String>::new("users".into(), 2, vec![1, 0])
+        .with_column_names(vec!["id".into(), "name".into()]);
+    let restored: TableSchema<String> =
+        serde_json::from_str(&serde_json::to_string(&schema).unwrap()).unwrap();
+    assert_eq!(restored, schema);
+    assert_eq!(restored.column_name(1), Some("name"));
+
+    let parse = |json: &str| {
+        serde_json::from_str::<TableSchema<String>>(json).map_err(|err| err.to_string())
+    };
+    let flags = parse(r#"{"name":"users","column_count":3,"pk_flags":[1,0]}"#);
+    assert!(
+        flags
+            .unwrap_err()
+            .contains("2 primary-key flags given for table with 3 columns")
+    );
+    let names =
+        parse(r#"{"name":"users","column_count":2,"pk_flags":[1,0],"column_names":["id"]}"#);
+    assert!(
+        names
+            .unwrap_err()
+            .contains("1 column names given for table with 2 columns")
+    );
+}
@@ -798,3 +798,69 @@ fn session_output_parser_apply_roundtrip_delete_composite_pk() {
         &["DELETE FROM items WHERE a = 1 AND b = 2"],
     );
 }
+
+// --- Virtual tables ------------------------------------------------------------
+//
+// The session extension never records a virtual table itself: `fts5` and
+// `rtree` keep their state in ordinary shadow tables (`docs_data`,
+// `docs_content`, `boxes_node`, ...), and those are what a session attached
+// to every table captures. The entries are regular rows, often with large
+// opaque blobs, and must survive the parser byte for byte.
+
+fn shadow_table_names(bytes: &[u8]) -> Vec<String> {
+    ParsedDiffSet::try_from(bytes)
+        .unwrap()
+        .table_schemas()
+        .into_iter()
+        .map(|schema| schema.name().clone())
+        .collect()
+}
+
+#[test]
+fn session_output_parser_roundtrip_fts5_shadow_tables() {
+    let setup = ["CREATE VIRTUAL TABLE docs USING fts5(title, body)"];
+    let tracked = [
+        "INSERT INTO docs VALUES ('hello', 'world wide web')",
+        "INSERT INTO docs VALUES ('second', 'another document body')",
+    ];
+    let (cs, _ps) = session_changeset_and_patchset_with_setup(&setup, &tracked);
+    let tables = shadow_table_names(&cs);
+    assert!(tables.iter().any(|name| name == "docs_data"), "{tables:?}");
+    assert!(
+        tables.iter().any(|name| name == "docs_content"),
+        "{tables:?}"
+    );
+
+    roundtrip_both("fts5 insert", &setup, &tracked);
+}
+
+#[test]
+fn session_output_parser_roundtrip_fts5_update_and_delete() {
+    roundtrip_both(
+        "fts5 update and delete",
+        &[
+            "CREATE VIRTUAL TABLE docs USING fts5(title, body)",
+            "INSERT INTO docs VALUES ('hello', 'world wide web')",
+            "INSERT INTO docs VALUES ('second', 'another document body')",
+        ],
+        &[
+            "UPDATE docs SET body = 'rewritten body' WHERE rowid = 1",
+            "DELETE FROM docs WHERE rowid = 2",
+        ],
+    );
+}
+
+#[test]
+fn session_output_parser_roundtrip_rtree_shadow_tables() {
+    roundtrip_both(
+        "rtree",
+        &[
+            "CREATE VIRTUAL TABLE boxes USING rtree(id, minx, maxx)",
+            "INSERT INTO boxes VALUES (1, 0.0, 2.0)",
+        ],
+        &[
+            "INSERT INTO boxes VALUES (2, 1.0, 3.0)",
+            "UPDATE boxes SET maxx = 4.0 WHERE id = 1",
+        ],
+    );
+}
@@ -0,0 +1,121 @@
+//! Differential tests for the simulated session hash table.
+//!
+//! Our builders emit rows in the order `SQLite`'s session extension would,
+//! which depends on how the primary keys hash into its bucket table and on
+//! when that table is grown and rehashed. Any drift from the real extension is
+//! a silent parity bug, so these tests insert rows through rusqlite and check
+//! that the captured order is exactly the predicted one, across primary-key
+//! distributions that collide in buckets and push the table through several
+//! rehashes.
+#![cfg(feature = "testing")]
+
+use sqlite_diff_rs::testing::assert_row_order_matches_sqlite;
+use sqlite_diff_rs::{SimpleTable, Value};
+
+type Row = Vec<Value<String, Vec<u8>>>;
+
+fn keyed(pk: Value<String, Vec<u8>>, payload: i64) -> Row {
+    vec![pk, Value::Integer(payload)]
+}
+
+fn single_key_table() -> SimpleTable {
+    SimpleTable::new("t", &["id", "payload"], &[0])
+}
+
+#[test]
+fn test_row_order_sequential_integers() {
+    let rows: Vec<Row> = (0..100).map(|i| keyed(Value::Integer(i), i)).collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_integers_across_rehashes() {
+    // The table grows at 128, 256 and 512 entries.
+    let rows: Vec<Row> = (0..1200).map(|i| keyed(Value::Integer(i), i)).collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_sparse_and_negative_integers() {
+    let rows: Vec<Row> = (0..300i64)
+        .map(|i| {
+            let pk = (i - 150).wrapping_mul(0x9E37_79B9_7F4A_7C15_u64.cast_signed());
+            keyed(Value::Integer(pk), i)
+        })
+        .chain([
+            keyed(Value::Integer(i64::MIN), -1),
+            keyed(Value::Integer(i64::MAX), -2),
+        ])
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_multiples_of_bucket_count() {
+    // Keys spaced by the bucket count are prone to landing in shared buckets.
+    let rows: Vec<Row> = (0..400)
+        .map(|i| keyed(Value::Integer(i * 256), i))
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_text_keys() {
+    let rows: Vec<Row> = (0..500)
+        .map(|i| keyed(Value::Text(format!("user-{i:04}")), i))
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_real_keys() {
+    let rows: Vec<Row> = (0..300)
+        .map(|i| keyed(Value::Real(f64::from(i) * 0.5 + 0.25), i.into()))
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_blob_keys() {
+    let rows: Vec<Row> = (0..300u32)
+        .map(|i| {
+            let mut pk = [0u8; 16];
+            pk[..4].copy_from_slice(&i.to_be_bytes());
+            pk[12..].copy_from_slice(&i.wrapping_mul(2_654_435_761).to_le_bytes());
+            keyed(Value::Blob(pk.to_vec()), i.into())
+        })
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_mixed_storage_classes() {
+    let rows: Vec<Row> = (0..200i64)
+        .map(|i| {
+            let pk = match i % 4 {
+                0 => Value::Integer(i),
+                1 => Value::Real(f64::from(i32::try_from(i).unwrap()) + 0.5),
+                2 => Value::Text(i.to_string()),
+                _ => Value::Blob(i.to_le_bytes().to_vec()),
+            };
+            keyed(pk, i)
+        })
+        .collect();
+    assert_row_order_matches_sqlite(&single_key_table(), &rows);
+}
+
+#[test]
+fn test_row_order_composite_keys() {
+    // Primary key declared in a different order than the columns.
+    let table = SimpleTable::new("t", &["a", "b", "payload"], &[1, 0]);
+    let rows: Vec<Row> = (0..600i64)
+        .map(|i| {
+            vec![
+                Value::Integer(i % 7),
+                Value::Text(format!("k{}", i / 7)),
+                Value::Integer(i),
+            ]
+        })
+        .collect();
+    assert_row_order_matches_sqlite(&table, &rows);
+}
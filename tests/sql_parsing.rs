@@ -1,13 +1,15 @@
 //! Integration tests for SQL digestion into `DiffSetBuilder` via `digest_sql`.
 //!
 //! These tests verify that `DiffSetBuilder::digest_sql` correctly parses SQL
-//! DML statements (INSERT, UPDATE, DELETE) and populates the patchset builder.
-//! Schemas are created manually via `SimpleTable::new`.
+//! DML statements (INSERT, UPDATE, DELETE) and populates the patchset and
+//! changeset builders. Schemas are created manually via `SimpleTable::new`.
 
 #![cfg(feature = "testing")]
 
-use sqlite_diff_rs::testing::assert_patchset_sql_parity;
-use sqlite_diff_rs::{PatchSet, SimpleTable};
+use sqlite_diff_rs::testing::{
+    assert_bit_parity, assert_patchset_sql_parity, session_changeset_and_patchset_with_setup,
+};
+use sqlite_diff_rs::{ChangeSet, ChangesetOp, ParsedDiffSet, PatchSet, SimpleTable, Value};
 
 /// Helper: create a `PatchSet` with tables pre-registered.
 fn patchset_with(tables: &[SimpleTable]) -> PatchSet<SimpleTable, String, Vec<u8>> {
@@ -18,6 +20,15 @@ fn patchset_with(tables: &[SimpleTable]) -> PatchSet<SimpleTable, String, Vec<u8
     ps
 }
 
+/// Helper: create a `ChangeSet` with tables pre-registered.
+fn changeset_with(tables: &[SimpleTable]) -> ChangeSet<SimpleTable, String, Vec<u8>> {
+    let mut cs = ChangeSet::new();
+    for t in tables {
+        cs.add_table(t);
+    }
+    cs
+}
+
 // =============================================================================
 // Basic parsing tests
 // =============================================================================
@@ -351,3 +362,366 @@ fn parity_composite_pk() {
         ],
     );
 }
+
+// =============================================================================
+// Changeset digestion tests
+// =============================================================================
+
+#[test]
+fn test_digest_changeset_mixed_script() {
+    let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    let mut cs = changeset_with(&[users]);
+    cs.digest_sql(
+        "INSERT INTO users (id, name) VALUES (3, 'Carol'); \
+         UPDATE users SET name = 'Alicia' WHERE id = 1 AND name = 'Alice'; \
+         DELETE FROM users WHERE id = 2 AND name = 'Bob';",
+    )
+    .unwrap();
+
+    let ops: Vec<_> = cs.iter().collect();
+    assert_eq!(ops.len(), 3);
+    assert!(
+        matches!(ops[0], ChangesetOp::Insert { values, .. } if values == [Value::Integer(3), Value::Text("Carol".into())]),
+        "got {:?}",
+        ops[0]
+    );
+    assert!(
+        matches!(ops[1], ChangesetOp::Update { values, .. } if values == [
+            (Some(Value::Integer(1)), None),
+            (Some(Value::Text("Alice".into())), Some(Value::Text("Alicia".into()))),
+        ]),
+        "got {:?}",
+        ops[1]
+    );
+    assert!(
+        matches!(ops[2], ChangesetOp::Delete { old_values, .. } if old_values == [Value::Integer(2), Value::Text("Bob".into())]),
+        "got {:?}",
+        ops[2]
+    );
+}
+
+#[test]
+fn test_digest_changeset_matches_sqlite_session() {
+    let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    let mut cs = changeset_with(&[users]);
+    cs.digest_sql(
+        "INSERT INTO users (id, name) VALUES (3, 'Carol'); \
+         UPDATE users SET name = 'Alicia' WHERE id = 1 AND name = 'Alice'; \
+         DELETE FROM users WHERE id = 2 AND name = 'Bob';",
+    )
+    .unwrap();
+
+    let (sqlite_changeset, _) = session_changeset_and_patchset_with_setup(
+        &[
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)",
+            "INSERT INTO users (id, name) VALUES (1, 'Alice')",
+            "INSERT INTO users (id, name) VALUES (2, 'Bob')",
+        ],
+        &[
+            "INSERT INTO users (id, name) VALUES (3, 'Carol')",
+            "UPDATE users SET name = 'Alicia' WHERE id = 1",
+            "DELETE FROM users WHERE id = 2",
+        ],
+    );
+    assert_eq!(cs.build(), sqlite_changeset);
+}
+
+#[test]
+fn test_digest_changeset_update_without_old_value() {
+    let users = SimpleTable::new("users", &["id", "name"], &[0]);
+    let mut cs = changeset_with(&[users]);
+    cs.digest_sql("UPDATE users SET name = 'Bob' WHERE id = 1;")
+        .unwrap();
+
+    // The old name is unknown, so only the key's old value is defined, and
+    // the unchanged key has no new value.
+    let ParsedDiffSet::Changeset(decoded) = ParsedDiffSet::parse(&cs.build()).unwrap() else {
+        panic!("expected a changeset");
+    };
+    let ops: Vec<_> = decoded.iter().collect();
+    assert_eq!(ops.len(), 1);
+    assert!(
+        matches!(ops[0], ChangesetOp::Update { values, .. } if values == [
+            (Some(Value::Integer(1)), None),
+            (None, Some(Value::Text("Bob".into()))),
+        ]),
+        "got {:?}",
+        ops[0]
+    );
+}
+
+#[test]
+fn test_digest_changeset_rejects_unassigned_non_pk_filter() {
+    let users = SimpleTable::new("users", &["id", "name", "age"], &[0]);
+    let mut cs = changeset_with(&[users]);
+    let err = cs
+        .digest_sql("UPDATE users SET name = 'Bob' WHERE id = 1 AND age = 30;")
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            sqlite_diff_rs::builders::sql::ParseError::WhereNonPKColumn { column: "age" }
+        ),
+        "got {err:?}"
+    );
+}
+
+// =============================================================================
+// `.dump` ingestion
+// =============================================================================
+
+/// Output of `sqlite3 app.db .dump` for a small blog database.
+const BLOG_DUMP: &str = "PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE users (
+  id INTEGER PRIMARY KEY,
+  name TEXT NOT NULL,
+  email TEXT UNIQUE
+);
+INSERT INTO users VALUES(1,'Alice','alice@example.com');
+INSERT INTO users VALUES(2,'Bob',NULL);
+CREATE TABLE posts (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  user_id INTEGER NOT NULL REFERENCES users(id),
+  title TEXT CHECK(length(title) > 0),
+  score REAL DEFAULT 0.0,
+  cover BLOB
+);
+INSERT INTO posts VALUES(1,1,'Hello; world',4.5,X'89504E47');
+INSERT INTO posts VALUES(2,2,'It''s me',-1.5,NULL);
+CREATE TABLE audit_log(message TEXT, at INTEGER);
+INSERT INTO audit_log VALUES('boot',1700000000);
+DELETE FROM sqlite_sequence;
+INSERT INTO sqlite_sequence VALUES('posts',2);
+CREATE INDEX posts_by_user ON posts(user_id);
+CREATE TRIGGER posts_touch AFTER UPDATE ON posts BEGIN
+  UPDATE posts SET score = score + 1 WHERE id = NEW.id;
+END;
+COMMIT;
+";
+
+#[test]
+fn test_digest_sql_with_rows_updates_rows_matching_non_pk_predicate() {
+    let setup = [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, status TEXT, score INTEGER)",
+        "INSERT INTO users VALUES (1, 'active', 10), (2, 'banned', 20)",
+        "INSERT INTO users VALUES (3, 'active', 30), (4, 'active', 0)",
+    ];
+    let tracked = ["UPDATE users SET score = 0 WHERE status = 'active' AND score IN (10, 0, 30)"];
+    let (sqlite_changeset, sqlite_patchset) =
+        session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let users = SimpleTable::new("users", &["id", "status", "score"], &[0]);
+    let rows = |_: &SimpleTable| {
+        [
+            (1, "active", 10),
+            (2, "banned", 20),
+            (3, "active", 30),
+            (4, "active", 0),
+        ]
+        .into_iter()
+        .map(|(id, status, score)| {
+            vec![
+                Value::Integer(id),
+                Value::Text(status.into()),
+                Value::Integer(score),
+            ]
+        })
+        .collect()
+    };
+
+    let mut changeset = changeset_with(core::slice::from_ref(&users));
+    changeset.digest_sql_with_rows(tracked[0], &rows).unwrap();
+    // Row 4 already holds the assigned score, so only rows 1 and 3 change.
+    assert_eq!(changeset.len(), 2);
+    assert_eq!(changeset.build(), sqlite_changeset);
+
+    let mut patchset = patchset_with(&[users]);
+    patchset.digest_sql_with_rows(tracked[0], &rows).unwrap();
+    assert_eq!(patchset.build(), sqlite_patchset);
+}
+
+#[test]
+fn test_digest_sql_with_rows_compares_integers_with_reals_exactly() {
+    let setup = [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, score INTEGER)",
+        "INSERT INTO users VALUES (1, 9007199254740992), (2, 9007199254740993)",
+    ];
+    // 2^53 + 1 rounds to 2^53 as a double, but `SQLite` still tells them apart.
+    let tracked = ["UPDATE users SET score = 0 WHERE score = 9007199254740992.0"];
+    let (sqlite_changeset, _) = session_changeset_and_patchset_with_setup(&setup, &tracked);
+
+    let users = SimpleTable::new("users", &["id", "score"], &[0]);
+    let rows = |_: &SimpleTable| {
+        vec![
+            vec![Value::Integer(1), Value::Integer(9_007_199_254_740_992)],
+            vec![Value::Integer(2), Value::Integer(9_007_199_254_740_993)],
+        ]
+    };
+
+    let mut changeset = changeset_with(core::slice::from_ref(&users));
+    changeset.digest_sql_with_rows(tracked[0], &rows).unwrap();
+    assert_eq!(changeset.len(), 1);
+    assert_eq!(changeset.build(), sqlite_changeset);
+}
+
+#[test]
+fn test_digest_sql_with_rows_rejects_unsupported_predicate() {
+    let users = SimpleTable::new("users", &["id", "score"], &[0]);
+    let mut patchset = patchset_with(&[users]);
+    let rows = |_: &SimpleTable| Vec::new();
+    assert!(
+        patchset
+            .digest_sql_with_rows("UPDATE users SET score = 1 WHERE score > 3", &rows)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_from_sqlite_dump_matches_sqlite_session() {
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::from_sqlite_dump(BLOG_DUMP).unwrap();
+    // `audit_log` has no primary key, so only the users and posts rows remain.
+    assert_eq!(changeset.len(), 4);
+
+    let (sqlite_changeset, _) = session_changeset_and_patchset_with_setup(
+        &[
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, email TEXT UNIQUE)",
+            "CREATE TABLE posts (id INTEGER PRIMARY KEY AUTOINCREMENT, user_id INTEGER NOT NULL \
+             REFERENCES users(id), title TEXT CHECK(length(title) > 0), score REAL DEFAULT 0.0, \
+             cover BLOB)",
+        ],
+        &[
+            "INSERT INTO users VALUES(1,'Alice','alice@example.com')",
+            "INSERT INTO users VALUES(2,'Bob',NULL)",
+            "INSERT INTO posts VALUES(1,1,'Hello; world',4.5,X'89504E47')",
+            "INSERT INTO posts VALUES(2,2,'It''s me',-1.5,NULL)",
+        ],
+    );
+    assert_eq!(changeset.build(), sqlite_changeset);
+}
+
+/// A dump written by the `sqlite3` shell of a table whose name has to be
+/// quoted, holding text with newlines. Since 3.50 the shell spells control
+/// characters with `unistr()`; older shells use `replace()` and `char()`,
+/// with a marker other than `\n` where the text already holds one. The last
+/// rows name the table bracketed and qualified with its schema.
+const QUOTED_DUMP: &str = r#"PRAGMA foreign_keys=OFF;
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "order items" (id INTEGER PRIMARY KEY, note TEXT);
+INSERT INTO "order items" VALUES(1,unistr('a\u000ab'));
+INSERT INTO "order items" VALUES(2,unistr('c\u000d\u000ad'));
+INSERT INTO "order items" VALUES(3,unistr('has \\n literal\u000a'));
+INSERT INTO "order items" VALUES(4,replace('e\nf','\n',char(10)));
+INSERT INTO "order items" VALUES(5,replace(replace('g\r\nh','\r',char(13)),'\n',char(10)));
+INSERT INTO "order items" VALUES(6,replace('has \n literal\012','\012',char(10)));
+INSERT INTO [order items] VALUES(7,'i');
+INSERT INTO main."order items" VALUES(8,'j');
+COMMIT;
+"#;
+
+#[test]
+fn test_from_sqlite_dump_reads_quoted_names_and_escaped_text() {
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::from_sqlite_dump(QUOTED_DUMP).unwrap();
+    let rendered: Vec<String> = changeset.sql_statements().collect();
+    assert_eq!(
+        rendered[0],
+        "INSERT INTO \"order items\" (\"id\", \"note\") VALUES (1, 'a\nb')"
+    );
+
+    let inserts: Vec<&str> = QUOTED_DUMP
+        .lines()
+        .filter(|line| line.starts_with("INSERT"))
+        .collect();
+    let (sqlite_changeset, _) = session_changeset_and_patchset_with_setup(
+        &["CREATE TABLE \"order items\" (id INTEGER PRIMARY KEY, note TEXT)"],
+        &inserts,
+    );
+    assert_eq!(changeset.build(), sqlite_changeset);
+}
+
+/// Digest `statements` as a dump and check both formats against the session.
+fn assert_dump_parity(statements: &[&str]) {
+    let dump = statements.join(";\n");
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::from_sqlite_dump(&dump).unwrap();
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> =
+        PatchSet::from_sqlite_dump(&dump).unwrap();
+    assert_bit_parity(statements, &changeset.build(), &patchset.build());
+}
+
+#[test]
+fn test_from_sqlite_dump_assigns_rowids_to_integer_primary_keys() {
+    // `id` aliases the rowid, so `SQLite` keys the NULL rows on 3 and 4.
+    assert_dump_parity(&[
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)",
+        "INSERT INTO t VALUES (2, 'a')",
+        "INSERT INTO t VALUES (NULL, 'b')",
+        "INSERT INTO t (v) VALUES ('c')",
+        "DELETE FROM t WHERE id = 3",
+    ]);
+}
+
+#[test]
+fn test_from_sqlite_dump_never_reuses_autoincrement_rowids() {
+    // Once row 5 is deleted, a plain rowid table would hand out 2 again,
+    // while an `AUTOINCREMENT` table moves on to 6, and then to 7 although
+    // row 6 has moved to 3.
+    assert_dump_parity(&[
+        "CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT, v TEXT)",
+        "INSERT INTO t VALUES (NULL, 'a')",
+        "INSERT INTO t VALUES (5, 'b')",
+        "DELETE FROM t WHERE id = 5",
+        "INSERT INTO t (v) VALUES ('c')",
+        "UPDATE t SET id = 3 WHERE id = 6",
+        "INSERT INTO t (v) VALUES ('d')",
+    ]);
+}
+
+#[test]
+fn test_from_sqlite_dump_skips_null_int_primary_keys() {
+    // `INT PRIMARY KEY` does not alias the rowid: the NULL rows are stored
+    // with a NULL key, which the session does not record.
+    assert_dump_parity(&[
+        "CREATE TABLE t (id INT PRIMARY KEY, v TEXT)",
+        "INSERT INTO t VALUES (2, 'a')",
+        "INSERT INTO t VALUES (NULL, 'b')",
+        "INSERT INTO t (v) VALUES ('c')",
+    ]);
+    assert_dump_parity(&[
+        "CREATE TABLE t (id INTEGER PRIMARY KEY DESC, v TEXT)",
+        "INSERT INTO t VALUES (NULL, 'a')",
+        "INSERT INTO t VALUES (1, 'b')",
+    ]);
+}
+
+#[test]
+fn test_from_sqlite_dump_renders_like_hand_built_schema() {
+    // The columns named in `CREATE TABLE` reach the shared SQL renderer.
+    let dumped: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::from_sqlite_dump(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);\n\
+         INSERT INTO users VALUES(1,'Alice');\n",
+    )
+    .unwrap();
+    let mut hand_built: ChangeSet<SimpleTable, String, Vec<u8>> = ChangeSet::new();
+    hand_built.add_table(&SimpleTable::new("users", &["id", "name"], &[0]));
+    hand_built
+        .digest_sql("INSERT INTO users VALUES (1, 'Alice')")
+        .unwrap();
+
+    let rendered: Vec<String> = dumped.sql_statements().collect();
+    assert_eq!(
+        rendered,
+        ["INSERT INTO \"users\" (\"id\", \"name\") VALUES (1, 'Alice')"]
+    );
+    assert_eq!(rendered, hand_built.sql_statements().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_from_sqlite_dump_rejects_unknown_statement() {
+    let result = ChangeSet::<SimpleTable, String, Vec<u8>>::from_sqlite_dump(
+        "BEGIN TRANSACTION;\nVACUUM;\nCOMMIT;",
+    );
+    assert!(result.is_err());
+}
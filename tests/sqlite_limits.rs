@@ -0,0 +1,72 @@
+//! `validate_sqlite_limits` catches diffsets `SQLite` could not apply.
+#![cfg(feature = "testing")]
+
+use rusqlite::Connection;
+use sqlite_diff_rs::{
+    ChangeSet, DiffOps, Error, Insert, PatchSet, PatchsetFormat, SimpleTable, Update, Value,
+};
+
+const MAX_COLUMN: usize = 2000;
+const MAX_LENGTH: usize = 1_000_000_000;
+
+fn column_names(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("c{i}")).collect()
+}
+
+fn table(name: &str, columns: &[String]) -> SimpleTable {
+    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+    SimpleTable::new(name, &columns, &[0])
+}
+
+#[test]
+fn column_count_overflow_is_caught() {
+    let at_limit = column_names(MAX_COLUMN);
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(Insert::from(table("wide", &at_limit)).set(0, 1i64).unwrap());
+    assert_eq!(changeset.validate_sqlite_limits(), Ok(()));
+
+    let over = column_names(MAX_COLUMN + 1);
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(Insert::from(table("wider", &over)).set(0, 1i64).unwrap());
+    assert_eq!(
+        changeset.validate_sqlite_limits(),
+        Err(Error::TooManyColumns("wider".into(), MAX_COLUMN + 1))
+    );
+
+    // SQLite cannot even create the table the changeset would apply to.
+    let conn = Connection::open_in_memory().unwrap();
+    let err = conn
+        .execute_batch(&format!("CREATE TABLE wider ({})", over.join(", ")))
+        .unwrap_err();
+    assert!(err.to_string().contains("too many columns"), "{err}");
+}
+
+#[test]
+fn oversized_values_are_caught() {
+    let columns = column_names(3);
+    let files = table("files", &columns);
+    // Zeroed allocations are not touched until written, so this stays cheap.
+    let huge = || vec![0u8; MAX_LENGTH + 1];
+
+    let changeset: ChangeSet<SimpleTable, String, Vec<u8>> =
+        ChangeSet::new().insert(Insert::from_values(
+            files.clone(),
+            vec![1i64.into(), Value::Blob(vec![1, 2]), Value::Blob(huge())],
+        ));
+    assert_eq!(
+        changeset.validate_sqlite_limits(),
+        Err(Error::ValueTooLarge("files".into(), 2, MAX_LENGTH + 1))
+    );
+
+    let patchset: PatchSet<SimpleTable, String, Vec<u8>> = PatchSet::new().update(
+        Update::<_, PatchsetFormat, _, _>::from(files)
+            .set(0, 1i64)
+            .unwrap()
+            .set(1, Value::Blob(huge()))
+            .unwrap(),
+    );
+    assert_eq!(
+        patchset.validate_sqlite_limits(),
+        Err(Error::ValueTooLarge("files".into(), 1, MAX_LENGTH + 1))
+    );
+}
@@ -0,0 +1,128 @@
+//! Round-trips through the public value codec, [`encode_value`] and
+//! [`decode_value`].
+
+use sqlite_diff_rs::{ParseError, Value, decode_value, encode_value};
+
+type OwnedValue = Value<String, Vec<u8>>;
+
+fn roundtrip(value: Option<&OwnedValue>) -> (Option<OwnedValue>, Vec<u8>) {
+    let mut out = Vec::new();
+    encode_value(&mut out, value);
+    let (decoded, consumed) = decode_value(&out).unwrap();
+    assert_eq!(consumed, out.len(), "{value:?} consumed partially");
+    (decoded, out)
+}
+
+#[test]
+fn every_variant_roundtrips() {
+    let values = [
+        Value::Null,
+        Value::Integer(0),
+        Value::Integer(i64::MIN),
+        Value::Integer(i64::MAX),
+        Value::Real(1.5),
+        Value::Real(f64::INFINITY),
+        Value::Real(f64::NEG_INFINITY),
+        Value::Text(String::new()),
+        Value::Text("héllo".into()),
+        Value::Text("x".repeat(300)),
+        Value::Blob(Vec::new()),
+        Value::Blob(vec![0, 255, 7]),
+    ];
+    for value in values {
+        let (decoded, _) = roundtrip(Some(&value));
+        assert_eq!(decoded, Some(value));
+    }
+}
+
+#[test]
+fn undefined_roundtrips() {
+    let (decoded, bytes) = roundtrip(None);
+    assert_eq!(decoded, None);
+    assert_eq!(bytes, [0x00]);
+}
+
+#[test]
+fn reals_are_normalized_as_sqlite_does() {
+    assert_eq!(roundtrip(Some(&Value::Real(f64::NAN))).0, Some(Value::Null));
+    let (decoded, bytes) = roundtrip(Some(&Value::Real(-0.0)));
+    assert_eq!(decoded, Some(Value::Real(0.0)));
+    assert_eq!(bytes, [0x02, 0, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn borrowed_values_encode_like_owned_ones() {
+    let mut borrowed = Vec::new();
+    encode_value(&mut borrowed, Some(&Value::<&str, &[u8]>::Blob(b"ab")));
+    let mut owned = Vec::new();
+    encode_value(&mut owned, Some(&OwnedValue::Blob(b"ab".to_vec())));
+    assert_eq!(borrowed, owned);
+}
+
+#[test]
+fn decoding_stops_after_one_value() {
+    let mut out = Vec::new();
+    encode_value(&mut out, Some(&OwnedValue::Integer(7)));
+    encode_value(&mut out, Some(&OwnedValue::Text("tail".into())));
+    assert_eq!(decode_value(&out), Ok((Some(Value::Integer(7)), 9)));
+    assert_eq!(
+        decode_value(&out[9..]),
+        Ok((Some(Value::Text("tail".into())), 6))
+    );
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    assert_eq!(decode_value(&[]), Err(ParseError::UnexpectedEof(0)));
+    // Unknown type code.
+    assert_eq!(decode_value(&[0x06]), Err(ParseError::InvalidValue(0)));
+    // Integer with 3 of its 8 bytes.
+    assert_eq!(
+        decode_value(&[0x01, 0, 0, 0]),
+        Err(ParseError::InvalidValue(0))
+    );
+    // Text whose length overruns the input, and invalid UTF-8.
+    assert_eq!(
+        decode_value(&[0x03, 5, b'a']),
+        Err(ParseError::InvalidValue(0))
+    );
+    assert_eq!(
+        decode_value(&[0x03, 1, 0xFF]),
+        Err(ParseError::InvalidValue(0))
+    );
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn every_variant_roundtrips_through_rusqlite() {
+    let values = [
+        Value::Null,
+        Value::Integer(i64::MIN),
+        Value::Real(-1.5),
+        Value::Real(f64::INFINITY),
+        Value::Text("héllo".into()),
+        Value::Blob(vec![0, 255, 7]),
+    ];
+    for value in values {
+        let converted = rusqlite::types::Value::from(&value);
+        assert_eq!(OwnedValue::from(converted), value);
+    }
+
+    // Undefined is `None` on our side and has no rusqlite value: it stays
+    // `None` when the option is mapped.
+    let undefined: Option<&OwnedValue> = None;
+    assert_eq!(undefined.map(rusqlite::types::Value::from), None);
+
+    // A value read back from SQLite converts too.
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    let read: rusqlite::types::Value = conn
+        .query_row(
+            "SELECT ?1",
+            [rusqlite::types::Value::from(&Value::<&str, &[u8]>::Text(
+                "x",
+            ))],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(OwnedValue::from(read), Value::Text("x".into()));
+}
@@ -0,0 +1,49 @@
+//! Tests for application-versioned changeset envelopes.
+//!
+//! A sender wraps each changeset with the application schema version it was
+//! produced under; the receiver reads the version back before deciding how to
+//! interpret the plain `SQLite` bytes.
+
+use sqlite_diff_rs::versioned::{VersionedChangeset, VersionedError};
+use sqlite_diff_rs::{ChangeSet, DiffOps, DynTable, Insert, ParsedDiffSet, SimpleTable};
+
+fn changeset(columns: &[&str]) -> Vec<u8> {
+    let messages = SimpleTable::new("messages", columns, &[0]);
+    let insert = columns.iter().enumerate().skip(1).fold(
+        Insert::from(messages).set(0, 1i64).unwrap(),
+        |insert, (idx, column)| insert.set(idx, format!("{column} of message 1")).unwrap(),
+    );
+    ChangeSet::<SimpleTable, String, Vec<u8>>::new()
+        .insert(insert)
+        .build()
+}
+
+#[test]
+fn test_receiver_tells_schema_versions_apart() {
+    let old = changeset(&["id", "body"]);
+    let new = changeset(&["id", "body", "room"]);
+    let frames = [
+        VersionedChangeset::wrap(&old, 1),
+        VersionedChangeset::wrap(&new, 2),
+    ];
+
+    for frame in &frames {
+        let (version, diffset) = VersionedChangeset::open(frame).unwrap();
+        let parsed = ParsedDiffSet::parse(diffset).unwrap();
+        let columns = parsed.table_schemas()[0].number_of_columns();
+        match version {
+            1 => assert_eq!((diffset, columns), (old.as_slice(), 2)),
+            2 => assert_eq!((diffset, columns), (new.as_slice(), 3)),
+            other => panic!("unexpected version {other}"),
+        }
+    }
+}
+
+#[test]
+fn test_unversioned_changeset_is_rejected() {
+    let plain = changeset(&["id", "body"]);
+    assert_eq!(
+        VersionedChangeset::open(&plain),
+        Err(VersionedError::BadMagic)
+    );
+}
@@ -0,0 +1,228 @@
+//! Tests for [`Digestable::to_operation`] across every wire source.
+//!
+//! One INSERT fixture per source is converted through the same generic
+//! function, which knows nothing about the source, and the resulting
+//! operations are compared with each other and with the one built by hand.
+
+#![cfg(all(
+    feature = "debezium",
+    feature = "maxwell",
+    feature = "pg-walstream",
+    feature = "wal2json"
+))]
+
+extern crate alloc;
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use sqlite_diff_rs::pg_walstream::EventType;
+use sqlite_diff_rs::{
+    ChangeSet, ChangesetFormat, DiffOps, Digestable, DynTable, Insert, NamedColumns, PatchSet,
+    PatchsetFormat, SchemaWithPK, SimpleTable, TypeMap, TypeMapDefaults, Value, WireAdapter,
+    WireColumnTypes, WireOperation, WireSchema, WireType, debezium, maxwell, wal2json,
+};
+
+// ---------------------------------------------------------------------------
+// Test schema
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsersTable(SimpleTable);
+
+impl DynTable for UsersTable {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+    fn number_of_columns(&self) -> usize {
+        self.0.number_of_columns()
+    }
+    fn write_pk_flags(&self, buf: &mut [u8]) {
+        self.0.write_pk_flags(buf);
+    }
+}
+
+impl SchemaWithPK for UsersTable {
+    fn extract_pk<S: Clone, B: Clone>(
+        &self,
+        values: &impl sqlite_diff_rs::IndexableValues<Text = S, Binary = B>,
+    ) -> Vec<Value<S, B>> {
+        self.0.extract_pk(values)
+    }
+    fn number_of_primary_keys(&self) -> usize {
+        self.0.number_of_primary_keys()
+    }
+    fn primary_key_index(&self, col: usize) -> Option<usize> {
+        self.0.primary_key_index(col)
+    }
+}
+
+impl NamedColumns for UsersTable {
+    fn column_index(&self, name: &str) -> Option<usize> {
+        NamedColumns::column_index(&self.0, name)
+    }
+}
+
+impl WireColumnTypes for UsersTable {
+    fn column_type(&self, column_index: usize) -> WireType {
+        match column_index {
+            0 => WireType::Int,
+            _ => WireType::Text,
+        }
+    }
+}
+
+struct Schema(UsersTable);
+
+impl WireSchema for Schema {
+    type Table = UsersTable;
+    fn get(&self, table_name: &str) -> Option<&UsersTable> {
+        (table_name == self.0.name()).then_some(&self.0)
+    }
+}
+
+fn users() -> UsersTable {
+    UsersTable(SimpleTable::new("users", &["id", "name"], &[0]))
+}
+
+fn schema() -> Schema {
+    Schema(users())
+}
+
+fn alice() -> Insert<UsersTable, String, Vec<u8>> {
+    Insert::from(users())
+        .set(0, 1i64)
+        .unwrap()
+        .set(1, "Alice")
+        .unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Fixtures, one per source, all inserting the same row
+// ---------------------------------------------------------------------------
+
+fn debezium_insert() -> debezium::FlatRecord {
+    debezium::parse_flattened(r#"{"id":1,"name":"Alice","__op":"c","__table":"users"}"#).unwrap()
+}
+
+fn maxwell_insert() -> maxwell::Message {
+    maxwell::parse(
+        r#"{"database":"app","table":"users","type":"insert","data":{"id":1,"name":"Alice"}}"#,
+    )
+    .unwrap()
+}
+
+fn pg_walstream_insert() -> EventType {
+    let mut data = pg_walstream::RowData::new();
+    data.push(Arc::from("id"), pg_walstream::ColumnValue::text("1"));
+    data.push(Arc::from("name"), pg_walstream::ColumnValue::text("Alice"));
+    EventType::Insert {
+        schema: Arc::from("public"),
+        table: Arc::from("users"),
+        relation_oid: 1,
+        data,
+    }
+}
+
+fn wal2json_insert() -> wal2json::MessageV2 {
+    wal2json::parse_v2(
+        r#"{"action":"I","schema":"public","table":"users","columns":[{"name":"id","type":"integer","value":1},{"name":"name","type":"text","value":"Alice"}]}"#,
+    )
+    .unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Source-agnostic consumers
+// ---------------------------------------------------------------------------
+
+/// Stage one event of any source on a fresh changeset.
+fn to_changeset<E, Src>(event: &E) -> ChangeSet<UsersTable, String, Vec<u8>>
+where
+    Src: TypeMapDefaults<String, Vec<u8>>,
+    E: Digestable<ChangesetFormat, UsersTable, String, Vec<u8>, Src = Src>,
+    E::Error: Debug,
+    TypeMap<Src, String, Vec<u8>>: WireAdapter<Src, String, Vec<u8>>,
+{
+    let operation = event
+        .to_operation(&schema(), &Src::defaults())
+        .unwrap()
+        .expect("the fixture changes a row");
+    assert!(matches!(operation, WireOperation::Insert(_)));
+    operation.stage(ChangeSet::new())
+}
+
+/// Stage one event of any source on a fresh patchset.
+fn to_patchset<E, Src>(event: &E) -> PatchSet<UsersTable, String, Vec<u8>>
+where
+    Src: TypeMapDefaults<String, Vec<u8>>,
+    E: Digestable<PatchsetFormat, UsersTable, String, Vec<u8>, Src = Src>,
+    E::Error: Debug,
+    TypeMap<Src, String, Vec<u8>>: WireAdapter<Src, String, Vec<u8>>,
+{
+    event
+        .to_operation(&schema(), &Src::defaults())
+        .unwrap()
+        .expect("the fixture changes a row")
+        .stage(PatchSet::new())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn every_source_converts_to_the_same_changeset_operation() {
+    let expected = ChangeSet::new().insert(alice());
+    assert_eq!(to_changeset(&debezium_insert()), expected);
+    assert_eq!(to_changeset(&maxwell_insert()), expected);
+    assert_eq!(to_changeset(&pg_walstream_insert()), expected);
+    assert_eq!(to_changeset(&wal2json_insert()), expected);
+}
+
+#[test]
+fn every_source_converts_to_the_same_patchset_operation() {
+    let expected = PatchSet::new().insert(alice());
+    assert_eq!(to_patchset(&debezium_insert()), expected);
+    assert_eq!(to_patchset(&maxwell_insert()), expected);
+    assert_eq!(to_patchset(&pg_walstream_insert()), expected);
+    assert_eq!(to_patchset(&wal2json_insert()), expected);
+}
+
+#[test]
+fn to_operation_matches_digest() {
+    let adapter: TypeMap<wal2json::Wal2Json, String, Vec<u8>> = TypeMap::defaults();
+    let event = wal2json_insert();
+    let digested = ChangeSet::new()
+        .digest(&event, &schema(), &adapter)
+        .unwrap();
+    assert_eq!(to_changeset(&event), digested);
+}
+
+#[test]
+fn events_changing_no_row_convert_to_none() {
+    let begin = wal2json::parse_v2(r#"{"action":"B"}"#).unwrap();
+    let adapter: TypeMap<wal2json::Wal2Json, String, Vec<u8>> = TypeMap::defaults();
+    let operation: Option<WireOperation<ChangesetFormat, _, _, _>> =
+        begin.to_operation(&schema(), &adapter).unwrap();
+    assert!(operation.is_none());
+
+    let tombstone = debezium::parse_flattened("null").unwrap();
+    let adapter: TypeMap<debezium::Debezium, String, Vec<u8>> = TypeMap::defaults();
+    let operation: Option<WireOperation<PatchsetFormat, _, _, _>> =
+        tombstone.to_operation(&schema(), &adapter).unwrap();
+    assert!(operation.is_none());
+}
+
+#[test]
+fn conversion_errors_are_reported() {
+    let mut event = maxwell_insert();
+    event.table = "orders".into();
+    let adapter: TypeMap<maxwell::Maxwell, String, Vec<u8>> = TypeMap::defaults();
+    let result: Result<Option<WireOperation<ChangesetFormat, _, _, _>>, _> =
+        event.to_operation(&schema(), &adapter);
+    assert!(matches!(
+        result,
+        Err(maxwell::ConversionError::TableNotFound(table)) if table == "orders"
+    ));
+}